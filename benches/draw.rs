@@ -0,0 +1,97 @@
+//! tessellation, text shaping and full frame construction
+//!
+//! run with `cargo bench --bench draw`. shaping and frame construction need a gpu adapter,
+//! the glyph atlas and vertex buffers live on the gpu
+
+use std::sync::Arc;
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use glam::{Vec2, vec2};
+use wgpui::{Context, Dir, RGBA, Renderer, Window, tessellate_convex_fill, tessellate_line};
+
+fn circle(n: usize, r: f32) -> Vec<Vec2> {
+    (0..n)
+        .map(|i| Vec2::from_angle(i as f32 / n as f32 * std::f32::consts::TAU) * r)
+        .collect()
+}
+
+fn headless_context() -> Context {
+    let wgpu = Arc::new(Renderer::new_headless());
+    let window = Window::headless(1280, 720, &wgpu);
+    let mut ui = Context::new(wgpu, window);
+    ui.draw.screen_size = vec2(1280.0, 720.0);
+    ui.init();
+    ui.begin_dockspace();
+    ui.end();
+    let panel = ui.panel_id("bench");
+    ui.dock_to_dockspace(panel, 1.0, Dir::E);
+    ui.update_draworder();
+    ui
+}
+
+fn tessellation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tessellate");
+
+    for n in [64, 1024, 16_384] {
+        let line: Vec<Vec2> = (0..n).map(|i| vec2(i as f32, (i as f32 * 0.1).sin() * 50.0)).collect();
+        group.bench_with_input(BenchmarkId::new("line", n), &line, |b, pts| {
+            b.iter(|| tessellate_line(black_box(pts), RGBA::WHITE, 1.5, false))
+        });
+
+        let poly = circle(n, 200.0);
+        group.bench_with_input(BenchmarkId::new("convex_fill", n), &poly, |b, pts| {
+            b.iter(|| tessellate_convex_fill(black_box(pts), RGBA::WHITE, true))
+        });
+    }
+
+    group.finish();
+}
+
+fn shaping(c: &mut Criterion) {
+    let ui = headless_context();
+    let mut group = c.benchmark_group("shape");
+
+    let paragraph = "the quick brown fox jumps over the lazy dog. ".repeat(20);
+    for (name, text) in [("label", "button label"), ("paragraph", paragraph.as_str())] {
+        // clear the cache so every iteration shapes the text again
+        group.bench_function(BenchmarkId::new("uncached", name), |b| {
+            b.iter(|| {
+                ui.text_item_cache.borrow_mut().clear();
+                ui.layout_text(black_box(text), 16.0)
+            })
+        });
+        group.bench_function(BenchmarkId::new("cached", name), |b| {
+            b.iter(|| ui.layout_text(black_box(text), 16.0))
+        });
+    }
+
+    group.finish();
+}
+
+fn frame(c: &mut Criterion) {
+    let mut ui = headless_context();
+    let mut group = c.benchmark_group("frame");
+
+    for n in [100, 1000, 10_000] {
+        let labels: Vec<String> = (0..n).map(|i| format!("button {i}")).collect();
+        group.bench_with_input(BenchmarkId::new("buttons", n), &labels, |b, labels| {
+            b.iter(|| {
+                ui.begin_frame();
+                ui.begin("bench");
+                for (i, label) in labels.iter().enumerate() {
+                    if i % 10 != 0 {
+                        ui.same_line();
+                    }
+                    ui.button(label);
+                }
+                ui.end();
+                ui.end_frame();
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, tessellation, shaping, frame);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+//! per-frame cost of turning widget labels into ids
+//!
+//! run with `cargo bench --bench id_gen`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use wgpui::Id;
+
+const N_WIDGETS: usize = 10_000;
+const N_FRAMES: usize = 200;
+
+fn frame_time(mut frame: impl FnMut() -> u64) -> Duration {
+    // warm up caches
+    black_box(frame());
+
+    let start = Instant::now();
+    for _ in 0..N_FRAMES {
+        black_box(frame());
+    }
+    start.elapsed() / N_FRAMES as u32
+}
+
+fn main() {
+    let labels: Vec<&'static str> = (0..N_WIDGETS)
+        .map(|i| &*Box::leak(format!("some fairly long widget label #{i}").into_boxed_str()))
+        .collect();
+    let ids: Vec<Id> = labels.iter().map(|l| Id::from_str(l)).collect();
+
+    let hashed = frame_time(|| labels.iter().map(|l| Id::from_str(l).0).fold(0, u64::wrapping_add));
+    let cached = frame_time(|| {
+        labels
+            .iter()
+            .map(|l| Id::from_static_str(l).0)
+            .fold(0, u64::wrapping_add)
+    });
+    let precomputed = frame_time(|| {
+        ids.iter()
+            .map(|id| Id::from_u64(id.0).0)
+            .fold(0, u64::wrapping_add)
+    });
+
+    println!("{N_WIDGETS} widgets per frame, {N_FRAMES} frames");
+    println!("Id::from_str:        {hashed:?} / frame");
+    println!("Id::from_static_str: {cached:?} / frame");
+    println!("Id::from_u64:        {precomputed:?} / frame");
+}
@@ -0,0 +1,57 @@
+//! headless harness shared by the stress examples
+//!
+//! builds the scene for a number of frames, renders each frame into a texture and prints
+//! where the time went. no window is opened, so the numbers only include the work done by
+//! the ui and the gpu submission
+
+use std::{sync::Arc, time::Instant};
+
+use wgpui::{Context, Dir, RenderTarget, Renderer, Texture, Window};
+
+const WIDTH: u32 = 1280;
+const HEIGHT: u32 = 720;
+const N_FRAMES: u32 = 120;
+
+/// runs `scene` inside a panel that fills the window and prints the per frame statistics
+pub fn run_stress(name: &str, mut scene: impl FnMut(&mut Context)) {
+    let wgpu = Arc::new(Renderer::new_headless());
+    let window = Window::headless(WIDTH, HEIGHT, &wgpu);
+    let target = Texture::create_render_texture(&wgpu, WIDTH, HEIGHT);
+
+    let mut ui = Context::new(wgpu.clone(), window);
+    ui.draw.screen_size = target.size();
+    ui.init();
+    ui.begin_dockspace();
+    ui.end();
+    let panel = ui.panel_id(name);
+    ui.dock_to_dockspace(panel, 1.0, Dir::E);
+    ui.update_draworder();
+
+    let (mut build_time, mut render_time) = (0.0, 0.0);
+    for _ in 0..N_FRAMES {
+        let start = Instant::now();
+        ui.begin_frame();
+        ui.begin(name);
+        scene(&mut ui);
+        ui.end();
+        ui.end_frame();
+        build_time += start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        {
+            let mut target = RenderTarget::from_texture(&target, &wgpu);
+            target.render(&ui.draw);
+        }
+        wgpu.device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        render_time += start.elapsed().as_secs_f64();
+    }
+
+    let ms = |t: f64| t * 1000.0 / N_FRAMES as f64;
+    println!("{name}: {WIDTH}x{HEIGHT}, {N_FRAMES} frames");
+    println!("  build:      {:.3} ms / frame", ms(build_time));
+    println!("  render:     {:.3} ms / frame", ms(render_time));
+    println!("  vertices:   {}", ui.draw.call_list.vtx_ptr);
+    println!("  indices:    {}", ui.draw.call_list.idx_ptr);
+    println!("  draw calls: {}", ui.n_draw_calls);
+    println!("  culled:     {}", ui.prev_n_culled_items);
+}
@@ -0,0 +1,21 @@
+//! 10k buttons in a single scrolling panel, most of them are culled
+//!
+//! run with `cargo run --release --example stress_buttons`
+
+mod common;
+
+const N_BUTTONS: usize = 10_000;
+const PER_ROW: usize = 10;
+
+fn main() {
+    let labels: Vec<String> = (0..N_BUTTONS).map(|i| format!("button {i}")).collect();
+
+    common::run_stress("10k buttons", |ui| {
+        for (i, label) in labels.iter().enumerate() {
+            if i % PER_ROW != 0 {
+                ui.same_line();
+            }
+            ui.button(label);
+        }
+    });
+}
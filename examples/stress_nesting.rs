@@ -0,0 +1,31 @@
+//! deeply nested frames and collapsing headers
+//!
+//! run with `cargo run --release --example stress_nesting`
+
+use wgpui::{Context, Frame};
+
+mod common;
+
+const DEPTH: u32 = 64;
+
+fn nest(ui: &mut Context, depth: u32) {
+    if depth == DEPTH {
+        ui.button("leaf");
+        return;
+    }
+
+    let frame = Frame::group(ui.style());
+    ui.frame(frame, |ui| {
+        ui.text(&format!("depth {depth}"));
+        ui.push_id(wgpui::Id::from_u64(depth as u64 + 1));
+        let mut open = true;
+        if ui.collapsing_header("children", &mut open) {
+            nest(ui, depth + 1);
+        }
+        ui.pop_id();
+    });
+}
+
+fn main() {
+    common::run_stress("deep nesting", |ui| nest(ui, 0));
+}
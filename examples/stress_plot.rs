@@ -0,0 +1,40 @@
+//! a line plot with 1M points
+//!
+//! run with `cargo run --release --example stress_plot`
+
+use wgpui::{Stroke, prelude::*};
+
+mod common;
+
+const N_POINTS: usize = 1_000_000;
+/// points per polyline, a single polyline has to fit into one draw call
+const CHUNK: usize = 4096;
+
+fn main() {
+    let samples: Vec<f32> = (0..N_POINTS)
+        .map(|i| {
+            let t = i as f32 / N_POINTS as f32;
+            (t * 300.0).sin() * 0.5 + (t * 7919.0).sin() * 0.2
+        })
+        .collect();
+    let mut points = Vec::with_capacity(N_POINTS);
+
+    common::run_stress("1M point plot", |ui| {
+        let size = ui.available_content();
+        let (rect, _) = ui.allocate_rect(size, Sense::Hover);
+
+        points.clear();
+        points.extend(samples.iter().enumerate().map(|(i, &y)| {
+            let x = i as f32 / (N_POINTS - 1) as f32;
+            rect.min + vec2(x, 0.5 - y * 0.5) * rect.size()
+        }));
+
+        let painter = ui.painter(rect);
+        painter.add_draw_rect(rect.draw_rect().fill(RGBA::BLACK));
+        // chunks overlap by one point so the line stays connected
+        for start in (0..N_POINTS - 1).step_by(CHUNK - 1) {
+            let end = (start + CHUNK).min(N_POINTS);
+            painter.add_polyline(&points[start..end], Stroke::hairline(RGBA::WHITE), false);
+        }
+    });
+}
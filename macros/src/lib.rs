@@ -161,6 +161,343 @@ pub fn wgsl(attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 
+/// implements `AsVertexFormat` for a single field tuple struct with the format of its field,
+/// or for any struct with the format given by `#[vertex_format(Unorm8x4)]`
+#[proc_macro_derive(AsVertexFormat, attributes(vertex_format))]
+pub fn derive_as_vertex_format(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let explicit = input
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident("vertex_format"))
+        .map(|a| a.parse_args::<Ident>());
+
+    let (body, check) = match explicit {
+        Some(Ok(format)) => {
+            let msg = format!("`{name}` does not have the size of a `{format}` attribute");
+            let body = quote! {
+                const VERTEX_FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::#format;
+                const WGSL: Option<&'static str> =
+                    wgpui::vertex_format_wgsl(wgpu::VertexFormat::#format);
+            };
+            let check = quote! {
+                const _: () = assert!(
+                    ::std::mem::size_of::<#name #ty_generics>() as u64
+                        == wgpu::VertexFormat::#format.size(),
+                    #msg
+                );
+            };
+            (body, check)
+        }
+        Some(Err(e)) => return e.to_compile_error().into(),
+        None => {
+            let inner = match &input.data {
+                syn::Data::Struct(syn::DataStruct {
+                    fields: syn::Fields::Unnamed(fields),
+                    ..
+                }) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+                _ => {
+                    let msg = "derive(AsVertexFormat) needs a single field tuple struct or a \
+                        #[vertex_format(..)] attribute";
+                    return syn::Error::new_spanned(name, msg).to_compile_error().into();
+                }
+            };
+            let body = quote! {
+                const VERTEX_FORMAT: wgpu::VertexFormat =
+                    <#inner as wgpui::AsVertexFormat>::VERTEX_FORMAT;
+                const WGSL: Option<&'static str> = <#inner as wgpui::AsVertexFormat>::WGSL;
+            };
+            (body, quote!())
+        }
+    };
+
+    let expanded = quote! {
+        impl #impl_generics wgpui::AsVertexFormat for #name #ty_generics #where_clause {
+            #body
+        }
+
+        #check
+    };
+
+    TokenStream::from(expanded)
+}
+
+
+/// options of a field from `#[inspect(..)]`
+#[derive(Default)]
+struct InspectAttrs {
+    skip: bool,
+    label: Option<syn::LitStr>,
+    range: Option<(syn::Expr, syn::Expr)>,
+}
+
+fn inspect_attrs(attrs: &[syn::Attribute]) -> Result<InspectAttrs> {
+    let mut out = InspectAttrs::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("inspect")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                out.skip = true;
+            } else if meta.path.is_ident("label") {
+                out.label = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("range") {
+                let range: syn::ExprRange = meta.value()?.parse()?;
+                match (range.start, range.end) {
+                    (Some(start), Some(end)) => out.range = Some((*start, *end)),
+                    _ => {
+                        let msg = "expected a range with both ends, e.g. `0.0..=1.0`";
+                        return Err(meta.error(msg));
+                    }
+                }
+            } else {
+                return Err(meta.error("expected `skip`, `label = \"..\"` or `range = min..=max`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(out)
+}
+
+/// implements `Inspect` for a struct or an enum with unit variants, see
+/// `ui::Context::inspect`
+///
+/// struct fields are shown with the widget of their type, nested structs get a collapsible
+/// section. fields take `#[inspect(skip)]`, `#[inspect(label = "..")]` and for numbers
+/// `#[inspect(range = 0.0..=1.0)]`, which shows a slider. enums are shown as a combo box
+#[proc_macro_derive(Inspect, attributes(inspect))]
+pub fn derive_inspect(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    match inspect_impl(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn inspect_impl(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        syn::Data::Struct(data) => {
+            let mut fields = Vec::new();
+            for (i, field) in data.fields.iter().enumerate() {
+                let attrs = inspect_attrs(&field.attrs)?;
+                if attrs.skip {
+                    continue;
+                }
+                let (member, default_label) = match &field.ident {
+                    Some(ident) => (quote!(#ident), ident.to_string()),
+                    None => {
+                        let index = syn::Index::from(i);
+                        (quote!(#index), i.to_string())
+                    }
+                };
+                let label = match attrs.label {
+                    Some(label) => label.value(),
+                    None => default_label,
+                };
+                fields.push(match attrs.range {
+                    Some((min, max)) => quote! {
+                        changed |= wgpui::InspectRange::inspect_range(
+                            &mut self.#member, ui, #label, (#min) as f64, (#max) as f64
+                        );
+                    },
+                    None => quote! {
+                        changed |= wgpui::Inspect::inspect(&mut self.#member, ui, #label);
+                    },
+                });
+            }
+            quote! {
+                fn inspect(&mut self, ui: &mut wgpui::Context, label: &str) -> bool {
+                    ui.inspect_section(label, |ui| self.inspect_fields(ui))
+                }
+
+                fn inspect_fields(&mut self, ui: &mut wgpui::Context) -> bool {
+                    let mut changed = false;
+                    #(#fields)*
+                    changed
+                }
+            }
+        }
+        syn::Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for v in &data.variants {
+                if !matches!(v.fields, syn::Fields::Unit) {
+                    let msg = "derive(Inspect) only supports enums with unit variants";
+                    return Err(syn::Error::new_spanned(v, msg));
+                }
+                variants.push(&v.ident);
+            }
+            let names: Vec<String> = variants.iter().map(|v| v.to_string()).collect();
+            let indices = 0..variants.len();
+            let indices2 = indices.clone();
+            quote! {
+                fn inspect(&mut self, ui: &mut wgpui::Context, label: &str) -> bool {
+                    const NAMES: &[&str] = &[#(#names),*];
+                    let current = match self {
+                        #(Self::#variants => #indices,)*
+                    };
+                    match ui.inspect_enum(label, NAMES, current) {
+                        #(Some(#indices2) => *self = Self::#variants,)*
+                        _ => return false,
+                    }
+                    true
+                }
+            }
+        }
+        syn::Data::Union(data) => {
+            let msg = "derive(Inspect) does not support unions";
+            return Err(syn::Error::new_spanned(data.union_token, msg));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics wgpui::Inspect for #name #ty_generics #where_clause {
+            #body
+        }
+    })
+}
+
+
+/// lays out a struct like wgsl does in storage buffers, or in uniform buffers with
+/// `#[shader_struct(uniform)]`, and implements `WgslType` for it
+///
+/// padding fields are inserted where wgsl aligns the next field, construct the struct with
+/// `..bytemuck::Zeroable::zeroed()`. fields without a wgsl equivalent or with a different
+/// size, e.g. arrays of `vec3`, are compile errors
+#[proc_macro_attribute]
+pub fn shader_struct(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let uniform = match syn::parse::<Option<Ident>>(attr) {
+        Ok(None) => false,
+        Ok(Some(ident)) if ident == "uniform" => true,
+        Ok(Some(ident)) => {
+            return syn::Error::new(ident.span(), "expected `uniform` or nothing")
+                .to_compile_error()
+                .into();
+        }
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let input = parse_macro_input!(item as ItemStruct);
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let vis = &input.vis;
+    let attrs = &input.attrs;
+
+    let fields = match &input.fields {
+        syn::Fields::Named(named) => named.named.iter().collect::<Vec<_>>(),
+        _ => panic!("#[shader_struct] can only be used on structs with named fields"),
+    };
+    if !input.generics.params.is_empty() {
+        return syn::Error::new_spanned(&input.generics, "shader structs can not be generic")
+            .to_compile_error()
+            .into();
+    }
+
+    let (align, size) = if uniform {
+        (quote!(UNIFORM_ALIGN), quote!(UNIFORM_SIZE))
+    } else {
+        (quote!(ALIGN), quote!(SIZE))
+    };
+    let tys = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let layout = quote! {
+        [#((<#tys as wgpui::WgslType>::#align, <#tys as wgpui::WgslType>::#size)),*]
+    };
+    let storage_layout = quote! {
+        [#((<#tys as wgpui::WgslType>::ALIGN, <#tys as wgpui::WgslType>::SIZE)),*]
+    };
+    let uniform_layout = quote! {
+        [#((<#tys as wgpui::WgslType>::UNIFORM_ALIGN, <#tys as wgpui::WgslType>::UNIFORM_SIZE)),*]
+    };
+    let struct_align = if uniform {
+        quote!(wgpui::wgsl_round_up(16, wgpui::wgsl_struct_align(#layout)))
+    } else {
+        quote!(wgpui::wgsl_struct_align(#layout))
+    };
+
+    let pads_const = quote::format_ident!("__{}_WGSL_PADS", name_str.to_uppercase());
+    let end_pad_const = quote::format_ident!("__{}_WGSL_END_PAD", name_str.to_uppercase());
+
+    let members = fields.iter().enumerate().map(|(i, f)| {
+        let pad = quote::format_ident!("_pad{}", i);
+        let (f_attrs, f_vis, f_ident, f_ty) = (&f.attrs, &f.vis, &f.ident, &f.ty);
+        quote! {
+            #[doc(hidden)]
+            pub #pad: [u8; #pads_const[#i]],
+            #(#f_attrs)*
+            #f_vis #f_ident: #f_ty,
+        }
+    });
+
+    let size_checks = fields.iter().map(|f| {
+        let (ident, ty) = (f.ident.as_ref().unwrap(), &f.ty);
+        let msg = format!(
+            "`{name_str}::{ident}` has a different size in WGSL, e.g. an array of vec3 or of \
+            scalars in a uniform buffer"
+        );
+        quote! {
+            assert!(::std::mem::size_of::<#ty>() == <#ty as wgpui::WgslType>::#size, #msg);
+        }
+    });
+
+    let field_names = fields.iter().map(|f| f.ident.as_ref().unwrap().to_string());
+    let n_fields = fields.len();
+
+    let expanded = quote! {
+        #[doc(hidden)]
+        const #pads_const: [usize; #n_fields] = wgpui::wgsl_paddings(#layout);
+        #[doc(hidden)]
+        const #end_pad_const: usize = wgpui::wgsl_struct_size(#layout, #struct_align)
+            - wgpui::wgsl_struct_size(#layout, 1);
+
+        #(#attrs)*
+        #[repr(C)]
+        #[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+        #vis struct #name {
+            #(#members)*
+            #[doc(hidden)]
+            pub _pad_end: [u8; #end_pad_const],
+        }
+
+        const _: () = {
+            #(#size_checks)*
+        };
+
+        impl wgpui::WgslType for #name {
+            const ALIGN: usize = wgpui::wgsl_struct_align(#storage_layout);
+            const SIZE: usize = ::std::mem::size_of::<Self>();
+            const UNIFORM_ALIGN: usize =
+                wgpui::wgsl_round_up(16, wgpui::wgsl_struct_align(#uniform_layout));
+            const UNIFORM_SIZE: usize = wgpui::wgsl_round_up(16, ::std::mem::size_of::<Self>());
+
+            fn wgsl_type() -> String {
+                #name_str.into()
+            }
+
+            fn wgsl_structs(defs: &mut Vec<String>) {
+                #(<#tys as wgpui::WgslType>::wgsl_structs(defs);)*
+                let mut def = format!("struct {} {{\n", #name_str);
+                #(
+                    def.push_str(&format!(
+                        "    {}: {},\n",
+                        #field_names,
+                        <#tys as wgpui::WgslType>::wgsl_type()
+                    ));
+                )*
+                def.push_str("}\n");
+                if !defs.contains(&def) {
+                    defs.push(def);
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+
 
 
 
@@ -455,10 +792,13 @@ pub fn flags(input: TokenStream) -> TokenStream {
         }
     }
 
+    let repr = if flags.len() > 32 { quote!(u64) } else { quote!(u32) };
+
     let expanded = quote! {
         bitflags::bitflags! {
             #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-            pub struct #ty: u32 {
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+            pub struct #ty: #repr {
                 const NONE = 0;
                 #(#consts)*
             }
@@ -494,3 +834,35 @@ pub fn flags(input: TokenStream) -> TokenStream {
 
 
 
+
+/// parses `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA`, the `#` is optional
+fn parse_hex_color(hex: &str) -> std::result::Result<[u8; 4], String> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    let vals = digits
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8).ok_or(format!("invalid hex digit '{c}'")))
+        .collect::<std::result::Result<Vec<u8>, String>>()?;
+
+    let byte = |i: usize| vals[i] << 4 | vals[i + 1];
+    match vals.len() {
+        3 => Ok([vals[0] * 17, vals[1] * 17, vals[2] * 17, 255]),
+        4 => Ok([vals[0] * 17, vals[1] * 17, vals[2] * 17, vals[3] * 17]),
+        6 => Ok([byte(0), byte(2), byte(4), 255]),
+        8 => Ok([byte(0), byte(2), byte(4), byte(6)]),
+        n => Err(format!("expected 3, 4, 6 or 8 hex digits, got {n}")),
+    }
+}
+
+/// `rgba!("#242933")` validates the color at compile time and expands to a const `RGBA`
+#[proc_macro]
+pub fn rgba(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as syn::LitStr);
+
+    match parse_hex_color(&lit.value()) {
+        Ok(col) => {
+            let [r, g, b, a] = col.map(|v| v as f32 / 255.0);
+            quote! { wgpui::RGBA::rgba_f(#r, #g, #b, #a) }.into()
+        }
+        Err(msg) => syn::Error::new(lit.span(), msg).to_compile_error().into(),
+    }
+}
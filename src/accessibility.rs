@@ -0,0 +1,471 @@
+use crate::{
+    core::HashMap,
+    rect::Rect,
+    ui::{Id, Signal},
+};
+
+/// semantic role of an item exposed to assistive technology
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessRole {
+    Window,
+    Panel,
+    Button,
+    CheckBox,
+    Switch,
+    Slider,
+    TextInput,
+    Label,
+    Image,
+    Tab,
+    Header,
+    ComboBox,
+    ListItem,
+}
+
+/// position inside a text input, `index` is a byte offset into `line`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextPos {
+    pub line: usize,
+    pub index: usize,
+}
+
+impl TextPos {
+    pub fn new(line: usize, index: usize) -> Self {
+        Self { line, index }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessNode {
+    pub id: Id,
+    /// panel the item was registered in, `Id::NULL` for top level panels
+    pub parent: Id,
+    pub role: AccessRole,
+    pub label: String,
+    pub bounds: Rect,
+
+    pub value: Option<String>,
+    pub toggled: Option<bool>,
+    pub expanded: Option<bool>,
+    pub selected: Option<bool>,
+    /// `(value, min, max)`
+    pub numeric: Option<(f32, f32, f32)>,
+
+    /// lines of a text input
+    pub lines: Vec<String>,
+    /// `(anchor, focus)` of the text selection, equal if there is only a cursor
+    pub text_selection: Option<(TextPos, TextPos)>,
+    pub multiline: bool,
+}
+
+impl AccessNode {
+    pub fn new(id: Id, parent: Id, role: AccessRole, label: &str, bounds: Rect) -> Self {
+        // only the part before "##" is visible
+        let label = label.split("##").next().unwrap_or_default();
+
+        Self {
+            id,
+            parent,
+            role,
+            label: label.to_string(),
+            bounds,
+            value: None,
+            toggled: None,
+            expanded: None,
+            selected: None,
+            numeric: None,
+            lines: Vec::new(),
+            text_selection: None,
+            multiline: false,
+        }
+    }
+}
+
+/// actions requested by assistive technology, e.g. a screen reader "pressing" a button
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessAction {
+    Focus,
+    Click,
+    SetValue(String),
+    SetNumericValue(f32),
+    Increment,
+    Decrement,
+    ReplaceSelectedText(String),
+    SetTextSelection { anchor: TextPos, focus: TextPos },
+}
+
+impl AccessAction {
+    /// applies a numeric action to `val`, increments are 1% of the range
+    pub fn apply_numeric(&self, val: &mut f32, min: f32, max: f32) -> bool {
+        let step = (max - min) / 100.0;
+        let new_val = match self {
+            AccessAction::SetNumericValue(v) => *v,
+            AccessAction::Increment => *val + step,
+            AccessAction::Decrement => *val - step,
+            _ => return false,
+        };
+        *val = new_val.clamp(min.min(max), max.max(min));
+        true
+    }
+}
+
+/// accessibility tree rebuilt from the registered items every frame
+///
+/// nodes are only recorded while `enabled` is set, i.e. when assistive technology is listening
+#[derive(Debug, Default)]
+pub struct AccessTree {
+    pub enabled: bool,
+    pub nodes: Vec<AccessNode>,
+    pub focus: Id,
+
+    /// requested actions, applied at the start of the next frame
+    pub requests: Vec<(Id, AccessAction)>,
+    /// actions of the current frame that are handled by the items themselves
+    pub frame_actions: HashMap<Id, AccessAction>,
+}
+
+impl AccessTree {
+    /// id of the synthetic window node all top level panels are attached to
+    pub const ROOT: Id = Id(u64::MAX);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn request(&mut self, id: Id, action: AccessAction) {
+        self.requests.push((id, action));
+    }
+
+    pub fn get(&self, id: Id) -> Option<&AccessNode> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    pub fn children(&self, id: Id) -> impl Iterator<Item = &AccessNode> {
+        let parent = if id == Self::ROOT { Id::NULL } else { id };
+        self.nodes.iter().filter(move |n| n.parent == parent && n.id != id)
+    }
+
+    /// removes the action for `id` of the current frame
+    pub fn take_action(&mut self, id: Id) -> Option<AccessAction> {
+        self.frame_actions.remove(&id)
+    }
+
+    /// signal of a click action, the whole press is reported in a single frame
+    pub fn take_signal(&mut self, id: Id) -> Signal {
+        if self.frame_actions.get(&id) == Some(&AccessAction::Click) {
+            self.frame_actions.remove(&id);
+            Signal::JUST_PRESSED_KEYBOARD | Signal::PRESSED_KEYBOARD | Signal::RELEASED_KEYBOARD
+        } else {
+            Signal::NONE
+        }
+    }
+}
+
+#[cfg(feature = "accesskit")]
+pub use accesskit_adapter::AccessKit;
+
+/// connects the [`AccessTree`] with the platform accessibility api
+#[cfg(feature = "accesskit")]
+mod accesskit_adapter {
+    use std::sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    };
+
+    use accesskit as ak;
+    use unicode_segmentation::UnicodeSegmentation;
+    use winit::{event::WindowEvent, event_loop::ActiveEventLoop, window::Window};
+
+    use super::*;
+
+    // text runs are not registered items so they need ids that do not collide with any item
+    fn text_run_id(id: Id, line: usize) -> ak::NodeId {
+        ak::NodeId(Id::from_hash(&(id.0, line)).0)
+    }
+
+    fn grapheme_to_byte(text: &str, index: usize) -> usize {
+        text.grapheme_indices(true)
+            .nth(index)
+            .map_or(text.len(), |(i, _)| i)
+    }
+
+    fn byte_to_grapheme(text: &str, index: usize) -> usize {
+        text.grapheme_indices(true)
+            .take_while(|(i, _)| *i < index)
+            .count()
+    }
+
+    fn ak_rect(r: Rect) -> ak::Rect {
+        ak::Rect::new(r.min.x as f64, r.min.y as f64, r.max.x as f64, r.max.y as f64)
+    }
+
+    impl AccessRole {
+        fn ak_role(self, multiline: bool) -> ak::Role {
+            match self {
+                AccessRole::Window => ak::Role::Window,
+                AccessRole::Panel => ak::Role::Pane,
+                AccessRole::Button => ak::Role::Button,
+                AccessRole::CheckBox => ak::Role::CheckBox,
+                AccessRole::Switch => ak::Role::Switch,
+                AccessRole::Slider => ak::Role::Slider,
+                AccessRole::TextInput if multiline => ak::Role::MultilineTextInput,
+                AccessRole::TextInput => ak::Role::TextInput,
+                AccessRole::Label => ak::Role::Label,
+                AccessRole::Image => ak::Role::Image,
+                AccessRole::Tab => ak::Role::Tab,
+                AccessRole::Header => ak::Role::Button,
+                AccessRole::ComboBox => ak::Role::ComboBox,
+                AccessRole::ListItem => ak::Role::ListBoxOption,
+            }
+        }
+    }
+
+    impl AccessTree {
+        fn build_node(&self, n: &AccessNode, nodes: &mut Vec<(ak::NodeId, ak::Node)>) {
+            let mut node = ak::Node::new(n.role.ak_role(n.multiline));
+            node.set_bounds(ak_rect(n.bounds));
+            if !n.label.is_empty() {
+                node.set_label(n.label.as_str());
+            }
+            if let Some(value) = &n.value {
+                node.set_value(value.as_str());
+            }
+            if let Some(toggled) = n.toggled {
+                node.set_toggled(if toggled { ak::Toggled::True } else { ak::Toggled::False });
+            }
+            if let Some(expanded) = n.expanded {
+                node.set_expanded(expanded);
+                node.add_action(if expanded { ak::Action::Collapse } else { ak::Action::Expand });
+            }
+            if let Some(selected) = n.selected {
+                node.set_selected(selected);
+            }
+            if let Some((val, min, max)) = n.numeric {
+                node.set_numeric_value(val as f64);
+                node.set_min_numeric_value(min as f64);
+                node.set_max_numeric_value(max as f64);
+                node.add_action(ak::Action::SetValue);
+                node.add_action(ak::Action::Increment);
+                node.add_action(ak::Action::Decrement);
+            }
+
+            match n.role {
+                AccessRole::Window | AccessRole::Panel | AccessRole::Label | AccessRole::Image => (),
+                _ => {
+                    node.add_action(ak::Action::Focus);
+                    node.add_action(ak::Action::Click);
+                }
+            }
+
+            let mut children: Vec<ak::NodeId> = self.children(n.id).map(|c| ak::NodeId(c.id.0)).collect();
+
+            if n.role == AccessRole::TextInput {
+                node.set_value(n.lines.join("\n"));
+                node.add_action(ak::Action::SetValue);
+                node.add_action(ak::Action::ReplaceSelectedText);
+                node.add_action(ak::Action::SetTextSelection);
+
+                for (i, line) in n.lines.iter().enumerate() {
+                    let run_id = text_run_id(n.id, i);
+                    let mut run = ak::Node::new(ak::Role::TextRun);
+                    run.set_value(line.as_str());
+                    run.set_character_lengths(
+                        line.graphemes(true).map(|g| g.len() as u8).collect::<Vec<_>>(),
+                    );
+                    children.push(run_id);
+                    nodes.push((run_id, run));
+                }
+
+                if let Some((anchor, focus)) = n.text_selection {
+                    let pos = |p: TextPos| ak::TextPosition {
+                        node: text_run_id(n.id, p.line),
+                        character_index: n
+                            .lines
+                            .get(p.line)
+                            .map_or(0, |l| byte_to_grapheme(l, p.index)),
+                    };
+                    node.set_text_selection(ak::TextSelection {
+                        anchor: pos(anchor),
+                        focus: pos(focus),
+                    });
+                }
+            }
+
+            node.set_children(children);
+            nodes.push((ak::NodeId(n.id.0), node));
+        }
+
+        /// full tree, accesskit diffs it against the previous update
+        pub fn tree_update(&self, title: &str) -> ak::TreeUpdate {
+            let mut nodes = Vec::with_capacity(self.nodes.len() + 1);
+
+            let mut root = ak::Node::new(ak::Role::Window);
+            root.set_label(title);
+            root.set_children(
+                self.children(Self::ROOT)
+                    .map(|c| ak::NodeId(c.id.0))
+                    .collect::<Vec<_>>(),
+            );
+            nodes.push((ak::NodeId(Self::ROOT.0), root));
+
+            for n in &self.nodes {
+                self.build_node(n, &mut nodes);
+            }
+
+            let focus = match self.get(self.focus) {
+                Some(n) => ak::NodeId(n.id.0),
+                None => ak::NodeId(Self::ROOT.0),
+            };
+
+            let mut tree = ak::Tree::new(ak::NodeId(Self::ROOT.0));
+            tree.toolkit_name = Some("wgpui".into());
+
+            ak::TreeUpdate {
+                nodes,
+                tree: Some(tree),
+                focus,
+            }
+        }
+
+        fn text_pos(&self, pos: ak::TextPosition) -> Option<(Id, TextPos)> {
+            self.nodes
+                .iter()
+                .filter(|n| n.role == AccessRole::TextInput)
+                .find_map(|n| {
+                    let line = (0..n.lines.len()).find(|&l| text_run_id(n.id, l) == pos.node)?;
+                    let index = grapheme_to_byte(&n.lines[line], pos.character_index);
+                    Some((n.id, TextPos::new(line, index)))
+                })
+        }
+
+        /// converts a platform request into an [`AccessAction`] targeting one of the nodes
+        pub fn action_from_request(&self, req: ak::ActionRequest) -> Option<(Id, AccessAction)> {
+            let id = Id(req.target.0);
+            let node = self.get(id);
+
+            let action = match (req.action, req.data) {
+                (ak::Action::Focus, _) => AccessAction::Focus,
+                (ak::Action::Click, _) => AccessAction::Click,
+                (ak::Action::Expand, _) if node?.expanded == Some(false) => AccessAction::Click,
+                (ak::Action::Collapse, _) if node?.expanded == Some(true) => AccessAction::Click,
+                (ak::Action::Increment, _) => AccessAction::Increment,
+                (ak::Action::Decrement, _) => AccessAction::Decrement,
+                (ak::Action::SetValue, Some(ak::ActionData::NumericValue(v))) => {
+                    AccessAction::SetNumericValue(v as f32)
+                }
+                (ak::Action::SetValue, Some(ak::ActionData::Value(s))) => {
+                    AccessAction::SetValue(s.into())
+                }
+                (ak::Action::ReplaceSelectedText, Some(ak::ActionData::Value(s))) => {
+                    AccessAction::ReplaceSelectedText(s.into())
+                }
+                (ak::Action::SetTextSelection, Some(ak::ActionData::SetTextSelection(sel))) => {
+                    let (id, anchor) = self.text_pos(sel.anchor)?;
+                    let (_, focus) = self.text_pos(sel.focus)?;
+                    return Some((id, AccessAction::SetTextSelection { anchor, focus }));
+                }
+                _ => return None,
+            };
+
+            node.map(|n| (n.id, action))
+        }
+    }
+
+    #[derive(Default)]
+    struct Shared {
+        active: AtomicBool,
+        requests: Mutex<Vec<ak::ActionRequest>>,
+    }
+
+    struct Handler(Arc<Shared>);
+
+    impl ak::ActivationHandler for Handler {
+        fn request_initial_tree(&mut self) -> Option<ak::TreeUpdate> {
+            // the tree is only known after the next frame
+            self.0.active.store(true, Ordering::Relaxed);
+            None
+        }
+    }
+
+    impl ak::ActionHandler for Handler {
+        fn do_action(&mut self, request: ak::ActionRequest) {
+            self.0.requests.lock().unwrap().push(request);
+        }
+    }
+
+    impl ak::DeactivationHandler for Handler {
+        fn deactivate_accessibility(&mut self) {
+            self.0.active.store(false, Ordering::Relaxed);
+        }
+    }
+
+    pub struct AccessKit {
+        adapter: accesskit_winit::Adapter,
+        shared: Arc<Shared>,
+    }
+
+    impl AccessKit {
+        /// must be created before the window is visible for the first time
+        pub fn new(event_loop: &ActiveEventLoop, window: &Window) -> Self {
+            let shared = Arc::new(Shared::default());
+            let adapter = accesskit_winit::Adapter::with_direct_handlers(
+                event_loop,
+                window,
+                Handler(shared.clone()),
+                Handler(shared.clone()),
+                Handler(shared.clone()),
+            );
+            Self { adapter, shared }
+        }
+
+        pub fn process_event(&mut self, window: &Window, event: &WindowEvent) {
+            self.adapter.process_event(window, event);
+        }
+
+        /// forwards pending requests to `tree` and pushes the tree built during the last frame
+        pub fn update(&mut self, tree: &mut AccessTree, title: &str) {
+            tree.enabled = self.shared.active.load(Ordering::Relaxed);
+
+            let requests = std::mem::take(&mut *self.shared.requests.lock().unwrap());
+            for req in requests {
+                if let Some((id, action)) = tree.action_from_request(req) {
+                    tree.request(id, action);
+                }
+            }
+
+            if tree.enabled {
+                self.adapter.update_if_active(|| tree.tree_update(title));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_tree() {
+        let mut tree = AccessTree::new();
+        let (panel, btn) = (Id(1), Id(2));
+        let bb = Rect::from_min_size(glam::Vec2::ZERO, glam::Vec2::splat(10.0));
+
+        tree.nodes.push(AccessNode::new(panel, Id::NULL, AccessRole::Panel, "Debug##DBG", bb));
+        tree.nodes.push(AccessNode::new(btn, panel, AccessRole::Button, "ok", bb));
+
+        assert_eq!(tree.get(panel).unwrap().label, "Debug");
+        assert_eq!(tree.children(AccessTree::ROOT).map(|n| n.id).collect::<Vec<_>>(), [panel]);
+        assert_eq!(tree.children(panel).map(|n| n.id).collect::<Vec<_>>(), [btn]);
+
+        tree.frame_actions.insert(btn, AccessAction::Click);
+        assert!(tree.take_signal(btn).released());
+        assert_eq!(tree.take_signal(btn), Signal::NONE);
+
+        let mut val = 5.0;
+        assert!(AccessAction::Increment.apply_numeric(&mut val, 0.0, 10.0));
+        assert_eq!(val, 5.1);
+        assert!(AccessAction::SetNumericValue(20.0).apply_numeric(&mut val, 0.0, 10.0));
+        assert_eq!(val, 10.0);
+        assert!(!AccessAction::Click.apply_numeric(&mut val, 0.0, 10.0));
+    }
+}
@@ -4,19 +4,22 @@ use glam::{UVec2, Vec2};
 use winit::{
     application::ApplicationHandler,
     dpi::PhysicalSize,
-    event::{KeyEvent, WindowEvent},
+    event::WindowEvent,
     event_loop::ActiveEventLoop,
     window::Window as WinitWindow,
 };
 
 use crate::{
-    core::{self, Duration, Instant, RGBA},
+    core::{self, Duration, RGBA},
     gpu::{self, WGPU, WGPUHandle, Window, WindowId},
-    mouse::{self, MouseBtn},
+    mouse::{self, InputEvent, MouseBtn},
+    platform::WindowIcon,
     rect::Rect,
     ui,
 };
 
+/// a pass that only clears the target, see [`gpu::Background`] for gradients and custom
+/// backgrounds
 #[derive(Debug, Clone)]
 pub struct ClearScreen(pub RGBA);
 
@@ -34,36 +37,81 @@ impl gpu::RenderPassHandle for ClearScreen {
     fn draw<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>, wgpu: &WGPU) {}
 }
 
+/// user code called from the phases of a frame, every method has an empty default
+///
+/// the phases run in order: `fixed_update` as often as the timestep requires, `update` once
+/// per frame and `render` once per window that is redrawn
+pub trait AppHandler: 'static {
+    /// deterministic simulation step, always called with the same `fixed_dt`
+    fn fixed_update(&mut self, _fixed_dt: Duration) {}
+
+    /// called between `begin_frame` and `end_frame` of the ui with the variable frame time
+    fn update(&mut self, _ui: &mut ui::Context, _dt: Duration) {}
+
+    /// draws into the window after the sprites and before the ui
+    fn render(&mut self, _target: &mut gpu::RenderTarget<'_>) {}
+}
+
+impl AppHandler for () {}
+
+/// creates the event loop and runs the app until all windows are closed
+pub fn run() {
+    run_app(());
+}
+
+/// same as [`run`], but calls `handler` from each phase of the frame
+pub fn run_app(handler: impl AppHandler) {
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    let mut app = AppSetup::new(handler);
+    event_loop.run_app(&mut app).unwrap();
+}
+
 pub enum AppSetup {
     UnInit {
         // window: Option<WinitWindow>,
         created_window: bool,
         #[cfg(target_arch = "wasm32")]
         renderer_rec: Option<futures::channel::oneshot::Receiver<(WGPU, Window)>>,
+        /// moved into the [`App`] once it is created
+        handler: Box<dyn AppHandler>,
     },
     Init(App),
 }
 
 impl Default for AppSetup {
     fn default() -> Self {
+        Self::new(())
+    }
+}
+
+fn load_window_icon() -> Option<winit::window::Icon> {
+    WindowIcon::from_encoded(include_bytes!("../res/icon.png"))
+        .ok()?
+        .to_winit()
+}
+
+impl AppSetup {
+    pub fn new(handler: impl AppHandler) -> Self {
         Self::UnInit {
             // window: None,
             created_window: false,
             #[cfg(target_arch = "wasm32")]
             renderer_rec: None,
+            handler: Box::new(handler),
         }
     }
-}
 
-fn load_window_icon() -> winit::window::Icon {
-    let icon_bytes = include_bytes!("../res/icon.png");
-    let img = image::load_from_memory(icon_bytes).unwrap().into_rgba8();
-    let (width, height) = img.dimensions();
-    let rgba = img.into_raw();
-    winit::window::Icon::from_rgba(rgba, width, height).unwrap()
-}
+    /// creates the app and hands it the user handler
+    fn init_app(&mut self, wgpu: WGPU, window: Window) {
+        let handler = match self {
+            Self::UnInit { handler, .. } => std::mem::replace(handler, Box::new(())),
+            Self::Init(app) => std::mem::replace(&mut app.handler, Box::new(())),
+        };
+        let mut app = App::new(wgpu, window);
+        app.handler = handler;
+        *self = Self::Init(app);
+    }
 
-impl AppSetup {
     pub fn is_init(&self) -> bool {
         matches!(self, Self::Init(_))
     }
@@ -78,7 +126,7 @@ impl AppSetup {
             .with_title("Atlas")
             .with_decorations(false)
             // .with_resizable(true)
-            .with_window_icon(Some(load_window_icon()));
+            .with_window_icon(load_window_icon());
 
         #[cfg(target_os = "windows")]
         {
@@ -87,8 +135,19 @@ impl AppSetup {
                 attribs.with_corner_preference(winit::platform::windows::CornerPreference::Round);
         }
 
+        // the accesskit adapter has to exist before the window is shown
+        #[cfg(feature = "accesskit")]
+        let attribs = attribs.with_visible(false);
+
         let window = event_loop.create_window(attribs).unwrap();
 
+        #[cfg(feature = "accesskit")]
+        let accesskit = {
+            let accesskit = crate::accessibility::AccessKit::new(event_loop, &window);
+            window.set_visible(true);
+            accesskit
+        };
+
         // self.window = Some(window_handle.clone());
 
         let size = window.inner_size();
@@ -96,10 +155,15 @@ impl AppSetup {
         // let window_handle_2 = window_handle.clone();
 
         let (window, wgpu) = core::futures::wait_for(async move {
-            WGPU::new_async(window, size.width, size.height).await
+            WGPU::new_async(Arc::new(window), size.width, size.height).await
         });
 
-        *self = Self::Init(App::new(window, wgpu));
+        self.init_app(window, wgpu);
+
+        #[cfg(feature = "accesskit")]
+        {
+            self.init_unwrap().accesskit = Some(accesskit);
+        }
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -125,6 +189,7 @@ impl AppSetup {
                 // window,
                 created_window,
                 renderer_rec,
+                ..
             } = self
             {
                 // let first_window_handle = window.is_none();
@@ -139,7 +204,7 @@ impl AppSetup {
 
                     wasm_bindgen_futures::spawn_local(async move {
                         let (wgpu, window) =
-                            WGPU::new_async(new_window, canvas_width, canvas_height).await;
+                            WGPU::new_async(Arc::new(new_window), canvas_width, canvas_height).await;
                         if sender.send((wgpu, window)).is_err() {
                             log::error!("Failed to create and send renderer!");
                         }
@@ -170,6 +235,7 @@ impl AppSetup {
             let Self::UnInit {
                 created_window,
                 renderer_rec,
+                ..
             } = self
             else {
                 unreachable!();
@@ -182,7 +248,7 @@ impl AppSetup {
                     // window.raw.set_prevent_default(false);
                     window.request_redraw();
                     let size = window.window_size();
-                    *self = Self::Init(App::new(wgpu, window));
+                    self.init_app(wgpu, window);
                     let app = self.init_unwrap();
                     app.ui
                         .resize_window(window_id, size.x as u32, size.y as u32);
@@ -210,7 +276,7 @@ impl ApplicationHandler for AppSetup {
         event: WindowEvent,
     ) {
         if let Some(app) = self.try_init() {
-            app.on_window_event(event_loop, window_id, event);
+            app.on_window_event(event_loop, window_id.into(), event);
         }
     }
 
@@ -226,15 +292,34 @@ pub struct App {
     pub panels: Vec<u32>,
 
     pub mouse_pos: Vec2,
-
-    pub prev_frame_time: Instant,
+    /// input not used by the ui, handled by `on_scene_input`
+    pub scene_input: Vec<InputEvent>,
+    /// last pointer position seen by the scene
+    pub scene_pointer: Vec2,
+    /// the camera of the sprites is dragged with the right mouse button
+    pub scene_drag: bool,
+
+    pub prev_frame_time: Duration,
     pub delta_time: Duration,
+    pub timestep: core::FixedTimestep,
+    /// number of fixed updates since the start
+    pub fixed_frame: u64,
+
+    /// user code called from the phases of the frame
+    pub handler: Box<dyn AppHandler>,
 
     pub wgpu: WGPUHandle,
     pub main_window: WindowId,
     // pub windows: HashMap<WindowId, Window>,
 
     pub dbg_tex: [gpu::Texture; 4],
+    /// cpu generated texture, re-uploaded every frame
+    pub stream_tex: gpu::StreamingTexture,
+    /// rendered below the ui
+    pub sprites: gpu::SpriteBatch,
+
+    #[cfg(feature = "accesskit")]
+    pub accesskit: Option<crate::accessibility::AccessKit>,
 }
 
 impl App {
@@ -253,18 +338,31 @@ impl App {
             )
         });
 
+        let stream_tex = gpu::StreamingTexture::new(&wgpu, 64, 64);
+
         let mut app = Self {
             ui: ui::Context::new(wgpu.clone(), window),
+            sprites: gpu::SpriteBatch::new(wgpu.clone()),
             panels: vec![],
-            prev_frame_time: Instant::now(),
+            prev_frame_time: Duration::ZERO,
             delta_time: Duration::ZERO,
+            timestep: core::FixedTimestep::default(),
+            fixed_frame: 0,
             mouse_pos: Vec2::NAN,
+            scene_input: Vec::new(),
+            scene_pointer: Vec2::NAN,
+            scene_drag: false,
+            handler: Box::new(()),
             wgpu,
             main_window,
             dbg_tex,
+            stream_tex,
+            #[cfg(feature = "accesskit")]
+            accesskit: None,
         };
 
         app.ui.init();
+        app.ui.use_system_text_scale();
         app.reset_layout();
         app
     }
@@ -277,78 +375,44 @@ impl App {
         let w_size = self.ui.window.window_size();
         let w_rect = Rect::from_min_size(Vec2::ZERO, w_size);
 
+        #[cfg(feature = "accesskit")]
+        if let Some(accesskit) = &mut self.accesskit {
+            if let Some(window) = self.ui.window.raw.as_any().downcast_ref::<WinitWindow>() {
+                accesskit.process_event(window, &event);
+            }
+        }
+
+        for input in InputEvent::from_winit(&event) {
+            // releases always reach the scene, so it never misses the end of a drag
+            let captured = match input {
+                InputEvent::PointerButton { pressed: false, .. }
+                | InputEvent::Key { pressed: false, .. } => false,
+                InputEvent::PointerMoved(_) if self.scene_drag => false,
+                InputEvent::PointerMoved(_)
+                | InputEvent::PointerButton { .. }
+                | InputEvent::Wheel(_) => self.ui.wants_pointer_input(),
+                InputEvent::Key { .. } | InputEvent::Text(_) | InputEvent::Ime(_) => {
+                    self.ui.wants_keyboard_input()
+                }
+                InputEvent::Modifiers(_) | InputEvent::Focused(_) => false,
+            };
+            self.ui.on_event(&input);
+            if !captured {
+                self.scene_input.push(input);
+            }
+        }
+
         match event {
             WE::CursorMoved { position: pos, .. } => {
                 self.mouse_pos = (pos.x as f32, pos.y as f32).into();
-                self.ui.set_mouse_pos(self.mouse_pos.x, self.mouse_pos.y);
                 if id == self.ui.window.id && !self.ui.window.raw.has_focus() {
-                    self.on_update(event_loop);
+                    self.on_frame(event_loop);
                     self.on_redraw(event_loop, id);
-
-                    // println!("request redraw");
-                    // self.ui2.window.request_redraw();
-                }
-                // self.windows.get_mut(&id).unwrap().on_mouse_moved(self.mouse_pos);
-            }
-            WE::Touch(winit::event::Touch {
-                phase, location, ..
-            }) => {
-                let pos: winit::dpi::LogicalPosition<f32> =
-                    location.to_logical(self.ui.window.raw.scale_factor());
-                self.ui.set_mouse_pos(pos.x, pos.y);
-                match phase {
-                    winit::event::TouchPhase::Started => {
-                        self.ui.set_mouse_press(MouseBtn::Left, true)
-                    }
-                    winit::event::TouchPhase::Moved => (),
-                    winit::event::TouchPhase::Ended => {
-                        self.ui.set_mouse_press(MouseBtn::Left, false)
-                    }
-                    winit::event::TouchPhase::Cancelled => {
-                        self.ui.set_mouse_press(MouseBtn::Left, false)
-                    }
-                }
-            }
-            WE::CursorEntered { .. } => {
-                // self.ui.cursor_in_window = true;
-            }
-            WE::CursorLeft { .. } => {
-                // self.ui.mouse_in_window = false;
-                // self.ui.cursor_in_window = true;
-            }
-
-            WE::MouseWheel { delta, .. } => {
-                use winit::event::MouseScrollDelta;
-                let delta = match delta {
-                    MouseScrollDelta::LineDelta(x, y) => Vec2::new(x, y) * 20.0,
-                    MouseScrollDelta::PixelDelta(d) => Vec2::new(d.x as f32, d.y as f32),
-                };
-                self.ui.set_mouse_scroll(delta);
-            }
-
-            WE::MouseInput { state, button, .. } => {
-                use winit::event::{ElementState, MouseButton};
-                let pressed = match state {
-                    ElementState::Pressed => true,
-                    ElementState::Released => false,
-                };
-
-                match button {
-                    MouseButton::Left => {
-                        self.ui.set_mouse_press(MouseBtn::Left, pressed);
-                    }
-                    MouseButton::Middle => {
-                        self.ui.set_mouse_press(MouseBtn::Middle, pressed);
-                    }
-                    MouseButton::Right => {
-                        self.ui.set_mouse_press(MouseBtn::Right, pressed);
-                    }
-                    _ => (),
                 }
             }
             WE::RedrawRequested => {
                 if id == self.main_window {
-                    self.on_update(event_loop);
+                    self.on_frame(event_loop);
                     let pid = self.ui.get_root_panel();
                     if self.ui.close_pressed {
                         event_loop.exit();
@@ -357,13 +421,6 @@ impl App {
                 self.on_redraw(event_loop, id);
             }
 
-            WE::ModifiersChanged(modifiers) => {
-                self.ui.modifiers = modifiers.state();
-            }
-
-            WE::KeyboardInput { event, .. } => {
-                self.on_keyboard(&event, event_loop);
-            }
             WE::Resized(PhysicalSize { width, height }) => {
                 let (width, height) = (width.max(1), height.max(1));
                 self.ui.resize_window(id, width, height);
@@ -393,9 +450,66 @@ impl App {
         ui.update_draworder();
     }
 
+    /// runs the update phases of a frame: `fixed_update` as often as the timestep requires,
+    /// then `on_update` once with the variable frame time. rendering happens in `on_redraw`.
+    /// each phase calls the matching method of [`AppHandler`]
+    fn on_frame(&mut self, event_loop: &ActiveEventLoop) {
+        let curr_time = self.ui.clock.now();
+        let dt = curr_time
+            .saturating_sub(self.prev_frame_time)
+            .min(self.timestep.max_frame_time);
+        self.prev_frame_time = curr_time;
+        self.delta_time = dt;
+
+        for _ in 0..self.timestep.advance(dt) {
+            self.fixed_update(self.timestep.fixed_dt);
+        }
+
+        self.on_scene_input();
+        self.on_update(event_loop);
+    }
+
+    /// pans and zooms the sprite camera with the input the ui did not use
+    fn on_scene_input(&mut self) {
+        let camera = &mut self.sprites.camera;
+
+        for input in std::mem::take(&mut self.scene_input) {
+            match input {
+                InputEvent::PointerButton {
+                    button: MouseBtn::Right,
+                    pressed,
+                } => self.scene_drag = pressed,
+                InputEvent::PointerMoved(pos) => {
+                    if self.scene_drag && self.scene_pointer.is_finite() {
+                        camera.pos -= (pos - self.scene_pointer) / camera.zoom;
+                    }
+                    self.scene_pointer = pos;
+                }
+                InputEvent::Wheel(delta) => {
+                    camera.zoom = (camera.zoom * (1.0 + delta.y * 0.002)).clamp(0.1, 10.0);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// deterministic simulation step, always called with the same `fixed_dt`
+    fn fixed_update(&mut self, fixed_dt: Duration) {
+        self.fixed_frame += 1;
+        self.handler.fixed_update(fixed_dt);
+    }
+
     fn on_update(&mut self, event_loop: &ActiveEventLoop) {
         let ui = &mut self.ui;
         ui.begin_frame();
+        self.handler.update(ui, self.delta_time);
+
+        ui.edge_panel(ui::TopBottomPanel::bottom("Status").with_default_size(60.0), |ui| {
+            ui.text(&format!("frame {}", ui.frame_index()));
+        });
+        ui.edge_panel(ui::SidePanel::left("Explorer"), |ui| {
+            ui.text("files");
+        });
 
         ui.begin_ex("Debug", ui::PanelFlag::NO_DOCK_TARGET | ui::PanelFlag::NO_DOCKING);
         ui.set_current_panel_min_size(|prev, full, content| full);
@@ -404,6 +518,32 @@ impl App {
             ui.image(Vec2::splat(64.0), Vec2::ZERO, Vec2::ONE, tex);
         }
 
+        {
+            let (w, h) = (self.stream_tex.width(), self.stream_tex.height());
+            let t = self.stream_tex.frame as u32;
+            let mut pixels = vec![0u8; (4 * w * h) as usize];
+            for (i, px) in pixels.chunks_exact_mut(4).enumerate() {
+                let (x, y) = (i as u32 % w, i as u32 / w);
+                px.copy_from_slice(&[(x * 4 + t) as u8, (y * 4) as u8, (t / 2) as u8, 255]);
+            }
+            self.stream_tex.update(&self.wgpu, &pixels);
+            ui.image_rounded(
+                Vec2::splat(64.0),
+                Vec2::ZERO,
+                Vec2::ONE,
+                self.stream_tex.texture(),
+                ui::CornerRadii::new(16.0, 0.0, 0.0, 16.0),
+            );
+        }
+
+        let frame = ui::Frame::group(&ui.style)
+            .with_shadow(ui::Shadow::new(Vec2::new(0.0, 2.0), 6.0, RGBA::rgba_f(0.0, 0.0, 0.0, 0.4)));
+        ui.frame(frame, |ui| {
+            ui.text("frame");
+            ui.same_line();
+            ui.button("inside");
+        });
+
         if ui.button("create panel") {
             static mut PANELS_COUNT: u32 = 0;
             unsafe {
@@ -447,6 +587,22 @@ impl App {
             ui.button("the quick brown fox jumps over the lazy dog");
 
             ui.slider_f32_intern("test slider", 0.0, 10.0);
+
+            // custom widget, a swatch that cycles through colors when clicked
+            ui.add(|ui: &mut ui::Context| {
+                let id = ui.gen_id("swatch");
+                let size = Vec2::splat(ui.style().line_height());
+                let (rect, sig) = ui.allocate_rect_id(id, size, ui::Sense::Click);
+                let hue = ui.data_get_or_insert_with(id, || 0.0f32);
+                if sig.clicked() {
+                    *hue = (*hue + 40.0) % 360.0;
+                }
+                let col = RGBA::from(core::HSVA::new(*hue, 0.6, 0.9, 1.0));
+                let corners = if sig.hovering() { rect.height() * 0.5 } else { 4.0 };
+                ui.painter(rect)
+                    .draw(rect.draw_rect().fill(col).corners(corners));
+                sig
+            });
             ui.end();
 
             !closed
@@ -470,43 +626,64 @@ impl App {
         //     ui.end();
         // }
 
-        ui.push_style(ui::StyleVar::PanelBg(ui.style.panel_dark_bg()));
-        ui.begin("Viewport##VIEWPORT");
-        ui.pop_style();
-        ui.end();
+        ui.central_viewport("Viewport##VIEWPORT");
 
         ui.debug_panel();
+        #[cfg(feature = "demo")]
+        ui.demo_window();
 
         ui.end_frame();
+
+        #[cfg(feature = "accesskit")]
+        if let Some(accesskit) = &mut self.accesskit {
+            accesskit.update(&mut self.ui.access, "Atlas");
+        }
     }
 
-    fn on_keyboard(&mut self, event: &KeyEvent, event_loop: &ActiveEventLoop) {
-        use winit::keyboard::{KeyCode, PhysicalKey};
+    fn save_capture(&mut self, img: Option<gpu::CapturedImage>) {
+        let Some(img) = img else {
+            return;
+        };
+
+        #[cfg(all(feature = "clipboard-image", not(target_arch = "wasm32")))]
+        match self.ui.clipboard.set_image(&img) {
+            Ok(()) => return,
+            Err(e) => log::warn!("failed to copy screenshot to the clipboard: {e}"),
+        }
 
-        self.ui.on_key_event(event);
+        match img.save("screenshot.png") {
+            Ok(()) => log::info!("saved screenshot.png"),
+            Err(e) => log::warn!("failed to save screenshot: {e}"),
+        }
     }
 
     fn on_redraw(&mut self, event_loop: &ActiveEventLoop, id: WindowId) {
-        let prev_time = self.prev_frame_time;
-        let curr_time = Instant::now();
-        let dt = curr_time - prev_time;
-        self.prev_frame_time = curr_time;
-        self.delta_time = dt;
-
-        {
+        let captured = {
             let window = self.ui.get_mut_window(id);
             let Some(mut target) = window.prepare_frame(&self.wgpu) else {
                 return;
             };
 
             self.ui.draw.screen_size = target.target_size();
+            self.sprites.screen_size = target.target_size();
+            self.sprites.prepare();
 
-            target.render(&ClearScreen(RGBA::rgba_f(0.0, 0.0, 0.0, 0.0)));
+            target.clear();
+            target.render(&self.sprites);
+            self.handler.render(&mut target);
             target.render(&self.ui.draw);
-        }
+            self.ui.record_frame(&target);
+
+            self.ui
+                .take_capture_request()
+                .and_then(|rect| target.capture_region(rect))
+        };
+        self.save_capture(captured);
 
         let window = self.ui.get_mut_window(id);
         window.present_frame();
         window.request_redraw();
+        #[cfg(feature = "profiling")]
+        profiling::finish_frame!();
     }
 }
@@ -0,0 +1,160 @@
+//! access to the system clipboard, see [`Clipboard`]
+
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, rc::Rc};
+
+#[cfg(all(feature = "clipboard-image", not(target_arch = "wasm32")))]
+use crate::gpu::CapturedImage;
+
+/// the system clipboard, available as [`Context::clipboard`](crate::Context::clipboard)
+///
+/// natively the clipboard is accessed directly. on the web the async clipboard api is used,
+/// so reading text is a request whose result arrives a few frames later, see
+/// [`Clipboard::request_text`]. without a system clipboard the text stays inside the app
+pub struct Clipboard {
+    #[cfg(not(target_arch = "wasm32"))]
+    native: Option<arboard::Clipboard>,
+    /// the text last copied by the app, read when the system clipboard is unavailable
+    local: Option<String>,
+    /// result of the last [`Clipboard::request_text`]
+    #[cfg(not(target_arch = "wasm32"))]
+    received: Option<String>,
+    #[cfg(target_arch = "wasm32")]
+    received: Rc<RefCell<Option<String>>>,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let native = match arboard::Clipboard::new() {
+            Ok(ctx) => Some(ctx),
+            Err(e) => {
+                log::warn!("system clipboard unavailable: {e}");
+                None
+            }
+        };
+
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            native,
+            local: None,
+            received: Default::default(),
+        }
+    }
+
+    /// reads the clipboard immediately, on the web this only sees text copied by the app
+    pub fn get_text(&mut self) -> Option<String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(native) = &mut self.native {
+            return native.get_text().ok();
+        }
+        self.local.clone()
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.local = Some(text.to_string());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(native) = &mut self.native
+            && let Err(e) = native.set_text(text)
+        {
+            log::warn!("{e}")
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let text = text.to_string();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(e) = web::clipboard_write_text(&text).await {
+                    log::warn!("failed to write to the clipboard: {e:?}");
+                }
+            });
+        }
+    }
+
+    /// starts reading the text of the clipboard, take it with [`Clipboard::take_text`]
+    ///
+    /// natively the text is available right away, on the web once the browser resolved the
+    /// read, which may ask the user for permission
+    pub fn request_text(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.received = self.get_text();
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let received = self.received.clone();
+            let fallback = self.local.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let text = match web::clipboard_read_text().await {
+                    Ok(text) => text.as_string(),
+                    Err(e) => {
+                        log::warn!("failed to read the clipboard: {e:?}");
+                        fallback
+                    }
+                };
+                *received.borrow_mut() = text;
+            });
+        }
+    }
+
+    /// the text read by the last [`Clipboard::request_text`], once it is available
+    pub fn take_text(&mut self) -> Option<String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let received = &mut self.received;
+        #[cfg(target_arch = "wasm32")]
+        let mut received = self.received.borrow_mut();
+        received.take()
+    }
+
+    /// copies an image to the clipboard, native only for now
+    #[cfg(all(feature = "clipboard-image", not(target_arch = "wasm32")))]
+    pub fn set_image(&mut self, img: &CapturedImage) -> Result<(), arboard::Error> {
+        let native = self.native.as_mut().ok_or(arboard::Error::ClipboardNotSupported)?;
+        let img = arboard::ImageData {
+            width: img.width as usize,
+            height: img.height as usize,
+            bytes: std::borrow::Cow::Borrowed(&img.rgba),
+        };
+        native.set_image(img)
+    }
+
+    /// the image on the clipboard as rgba8 pixels, native only for now
+    #[cfg(all(feature = "clipboard-image", not(target_arch = "wasm32")))]
+    pub fn get_image(&mut self) -> Option<CapturedImage> {
+        let img = self.native.as_mut()?.get_image().ok()?;
+        Some(CapturedImage {
+            width: img.width as u32,
+            height: img.height as u32,
+            rgba: img.bytes.into_owned(),
+        })
+    }
+}
+
+/// bridge to the browser's async clipboard api
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(inline_js = "
+        export async function clipboard_write_text(text) {
+            await navigator.clipboard.writeText(text);
+        }
+        export async function clipboard_read_text() {
+            return await navigator.clipboard.readText();
+        }
+    ")]
+    extern "C" {
+        #[wasm_bindgen(catch)]
+        pub async fn clipboard_write_text(text: &str) -> Result<JsValue, JsValue>;
+        #[wasm_bindgen(catch)]
+        pub async fn clipboard_read_text() -> Result<JsValue, JsValue>;
+    }
+}
@@ -16,6 +16,7 @@ pub type Instant = std::time::Instant;
 pub type Duration = std::time::Duration;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Axis {
     X = 0,
     Y = 1,
@@ -52,7 +53,10 @@ pub const fn rand_u32() -> u32 {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(macros::AsVertexFormat)]
+#[vertex_format(Float32x4)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RGBA {
     pub r: f32,
     pub g: f32,
@@ -74,35 +78,28 @@ impl fmt::Display for RGBA {
     }
 }
 
-pub const fn hex_to_rgba(s: &str) -> RGBA {
-    const fn hex_val(b: u8) -> u8 {
-        match b {
-            b'0'..=b'9' => b - b'0',
-            b'a'..=b'f' => b - b'a' + 10,
-            b'A'..=b'F' => b - b'A' + 10,
-            _ => 0,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    /// number of hex digits, excluding the `#`
+    InvalidLength(usize),
+    /// byte index of the first invalid digit
+    InvalidDigit(usize),
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength(n) => write!(f, "expected 3, 4, 6 or 8 hex digits, got {n}"),
+            Self::InvalidDigit(i) => write!(f, "invalid hex digit at index {i}"),
         }
     }
+}
 
-    const fn byte(h: u8, l: u8) -> u8 {
-        (hex_val(h) << 4) | hex_val(l)
-    }
+impl std::error::Error for HexError {}
 
-    let bytes = s.as_bytes();
-    match bytes.len() {
-        7 => RGBA::rgb(
-            byte(bytes[1], bytes[2]),
-            byte(bytes[3], bytes[4]),
-            byte(bytes[5], bytes[6]),
-        ),
-        9 => RGBA::rgba(
-            byte(bytes[1], bytes[2]),
-            byte(bytes[3], bytes[4]),
-            byte(bytes[5], bytes[6]),
-            byte(bytes[7], bytes[8]),
-        ),
-        _ => RGBA::rgba(0, 0, 0, 255),
-    }
+#[deprecated = "use `RGBA::try_hex`, `RGBA::hex` or `rgba!`"]
+pub const fn hex_to_rgba(s: &str) -> RGBA {
+    RGBA::hex(s)
 }
 
 impl RGBA {
@@ -170,6 +167,14 @@ impl RGBA {
         }
     }
 
+    fn srgb_to_linear(srgb: f32) -> f32 {
+        if srgb <= 0.04045 {
+            srgb / 12.92
+        } else {
+            ((srgb + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
     fn linear_to_srgb(l: f32) -> f32 {
         if l <= 0.0031308 {
             l * 12.92
@@ -195,34 +200,54 @@ impl RGBA {
         Self::rgba_f(r, g, b, a)
     }
 
-    pub const fn hex(hex: &str) -> Self {
-        const fn hex_val(b: u8) -> u8 {
+    /// parses `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA`, the `#` is optional
+    pub const fn try_hex(hex: &str) -> Result<Self, HexError> {
+        const fn hex_val(b: u8) -> Option<u8> {
             match b {
-                b'0'..=b'9' => b - b'0',
-                b'a'..=b'f' => b - b'a' + 10,
-                b'A'..=b'F' => b - b'A' + 10,
-                _ => 0,
+                b'0'..=b'9' => Some(b - b'0'),
+                b'a'..=b'f' => Some(b - b'a' + 10),
+                b'A'..=b'F' => Some(b - b'A' + 10),
+                _ => None,
             }
         }
 
-        const fn byte(h: u8, l: u8) -> u8 {
-            (hex_val(h) << 4) | hex_val(l)
+        let bytes = hex.as_bytes();
+        let start = if !bytes.is_empty() && bytes[0] == b'#' { 1 } else { 0 };
+        let n = bytes.len() - start;
+        if !matches!(n, 3 | 4 | 6 | 8) {
+            return Err(HexError::InvalidLength(n));
         }
 
-        let bytes = hex.as_bytes();
-        match bytes.len() {
-            7 => RGBA::rgb(
-                byte(bytes[1], bytes[2]),
-                byte(bytes[3], bytes[4]),
-                byte(bytes[5], bytes[6]),
-            ),
-            9 => RGBA::rgba(
-                byte(bytes[1], bytes[2]),
-                byte(bytes[3], bytes[4]),
-                byte(bytes[5], bytes[6]),
-                byte(bytes[7], bytes[8]),
+        let mut vals = [0u8; 8];
+        let mut i = 0;
+        while i < n {
+            match hex_val(bytes[start + i]) {
+                Some(v) => vals[i] = v,
+                None => return Err(HexError::InvalidDigit(start + i)),
+            }
+            i += 1;
+        }
+
+        let v = vals;
+        Ok(match n {
+            3 => RGBA::rgb(v[0] * 17, v[1] * 17, v[2] * 17),
+            4 => RGBA::rgba(v[0] * 17, v[1] * 17, v[2] * 17, v[3] * 17),
+            6 => RGBA::rgb(v[0] << 4 | v[1], v[2] << 4 | v[3], v[4] << 4 | v[5]),
+            _ => RGBA::rgba(
+                v[0] << 4 | v[1],
+                v[2] << 4 | v[3],
+                v[4] << 4 | v[5],
+                v[6] << 4 | v[7],
             ),
-            _ => RGBA::rgba(0, 0, 0, 255),
+        })
+    }
+
+    /// like [`Self::try_hex`] but returns opaque black on invalid input, for literals prefer
+    /// `rgba!`
+    pub const fn hex(hex: &str) -> Self {
+        match Self::try_hex(hex) {
+            Ok(col) => col,
+            Err(_) => RGBA::rgba(0, 0, 0, 255),
         }
     }
 
@@ -231,26 +256,26 @@ impl RGBA {
     pub const BLUE: RGBA = RGBA::rgb(0, 0, 255);
     pub const YELLOW: RGBA = RGBA::rgb(255, 255, 0);
 
-    pub const PURPLE: RGBA = RGBA::hex("#740580");
-    pub const MAGENTA: RGBA = RGBA::hex("#B10065");
-    pub const FOLLY: RGBA = RGBA::hex("#FF1D68");
-    pub const ORANGE: RGBA = RGBA::hex("#F76218");
-    pub const SAFFRON: RGBA = RGBA::hex("#F2C447");
-    pub const INDIGO: RGBA = RGBA::hex("#214675");
-    pub const DARK_BLUE: RGBA = RGBA::hex("#122741");
-    pub const CYAN: RGBA = RGBA::hex("#00f7f7");
-    pub const TEAL: RGBA = RGBA::hex("#007c7c");
+    pub const PURPLE: RGBA = macros::rgba!("#740580");
+    pub const MAGENTA: RGBA = macros::rgba!("#B10065");
+    pub const FOLLY: RGBA = macros::rgba!("#FF1D68");
+    pub const ORANGE: RGBA = macros::rgba!("#F76218");
+    pub const SAFFRON: RGBA = macros::rgba!("#F2C447");
+    pub const INDIGO: RGBA = macros::rgba!("#214675");
+    pub const DARK_BLUE: RGBA = macros::rgba!("#122741");
+    pub const CYAN: RGBA = macros::rgba!("#00f7f7");
+    pub const TEAL: RGBA = macros::rgba!("#007c7c");
 
     pub const WHITE: RGBA = RGBA::rgb(255, 255, 255);
     pub const BLACK: RGBA = RGBA::rgb(0, 0, 0);
 
-    pub const PASTEL_PINK: RGBA = RGBA::hex("#FFB5E8");
-    pub const PASTEL_BLUE: RGBA = RGBA::hex("#B5DEFF");
-    pub const PASTEL_GREEN: RGBA = RGBA::hex("#C1FFD7");
-    pub const PASTEL_YELLOW: RGBA = RGBA::hex("#FFFACD");
-    pub const PASTEL_PURPLE: RGBA = RGBA::hex("#D7B5FF");
-    pub const PASTEL_ORANGE: RGBA = RGBA::hex("#FFD1B5");
-    pub const PASTEL_MINT: RGBA = RGBA::hex("#B5FFF9");
+    pub const PASTEL_PINK: RGBA = macros::rgba!("#FFB5E8");
+    pub const PASTEL_BLUE: RGBA = macros::rgba!("#B5DEFF");
+    pub const PASTEL_GREEN: RGBA = macros::rgba!("#C1FFD7");
+    pub const PASTEL_YELLOW: RGBA = macros::rgba!("#FFFACD");
+    pub const PASTEL_PURPLE: RGBA = macros::rgba!("#D7B5FF");
+    pub const PASTEL_ORANGE: RGBA = macros::rgba!("#FFD1B5");
+    pub const PASTEL_MINT: RGBA = macros::rgba!("#B5FFF9");
 
     pub const CARMINE: RGBA = RGBA::rgb(200, 0, 100);
 
@@ -268,36 +293,6 @@ impl From<RGBA> for wgpu::Color {
     }
 }
 
-pub fn hex_to_col(hex: &str) -> wgpu::Color {
-    fn to_linear(u: u8) -> f64 {
-        let srgb = u as f64 / 255.0;
-        if srgb <= 0.04045 {
-            srgb / 12.92
-        } else {
-            ((srgb + 0.055) / 1.055).powf(2.4)
-        }
-    }
-
-    let hex = hex.trim_start_matches('#');
-    let vals: Vec<u8> = (0..hex.len())
-        .step_by(2)
-        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
-        .collect();
-
-    let (r8, g8, b8, a8) = match vals.as_slice() {
-        [r, g, b] => (*r, *g, *b, 255),
-        [r, g, b, a] => (*r, *g, *b, *a),
-        _ => panic!("Hex code must be 6 or 8 characters long"),
-    };
-
-    wgpu::Color {
-        r: to_linear(r8),
-        g: to_linear(g8),
-        b: to_linear(b8),
-        a: a8 as f64 / 255.0, // alpha is linear already
-    }
-}
-
 impl From<(u8, u8, u8)> for RGBA {
     fn from(v: (u8, u8, u8)) -> Self {
         RGBA::rgb(v.0, v.1, v.2)
@@ -371,7 +366,10 @@ impl From<u32> for RGBA {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(macros::AsVertexFormat)]
+#[vertex_format(Float32x3)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RGB {
     pub r: f32,
     pub g: f32,
@@ -405,19 +403,7 @@ impl RGB {
     }
 
     pub fn hex(hex: &str) -> Self {
-        let hex = hex.trim_start_matches('#');
-        let vals: Vec<u8> = (0..hex.len())
-            .step_by(2)
-            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
-            .collect();
-
-        let (r8, g8, b8) = match vals.as_slice() {
-            [r, g, b] => (*r, *g, *b),
-            [r, g, b, _a] => (*r, *g, *b),
-            _ => panic!("Hex code must be 6 or 8 characters long"),
-        };
-
-        Self::rgb(r8, g8, b8)
+        RGBA::hex(hex).into()
     }
 }
 
@@ -504,6 +490,400 @@ impl From<RGB> for [f32; 3] {
     }
 }
 
+// BEGIN COLOR SPACES
+//---------------------------------------------------------------------------------------
+
+/// hue in degrees `[0, 360)`, saturation, value and alpha in `[0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HSVA {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub a: f32,
+}
+
+/// hue in degrees `[0, 360)`, saturation, lightness and alpha in `[0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HSLA {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub a: f32,
+}
+
+/// perceptual lightness `[0, 1]`, a and b roughly in `[-0.4, 0.4]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OKLAB {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+/// polar form of [`OKLAB`], chroma roughly in `[0, 0.4]`, hue in degrees `[0, 360)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OKLCH {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+fn hue_from_rgb(r: f32, g: f32, b: f32, max: f32, delta: f32) -> f32 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+    let h = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    h * 60.0
+}
+
+/// rgb from hue, chroma and the value added to every channel
+fn rgb_from_hue(h: f32, c: f32, m: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    (r + m, g + m, b + m)
+}
+
+impl HSVA {
+    pub fn new(h: f32, s: f32, v: f32, a: f32) -> Self {
+        Self { h, s, v, a }
+    }
+}
+
+impl HSLA {
+    pub fn new(h: f32, s: f32, l: f32, a: f32) -> Self {
+        Self { h, s, l, a }
+    }
+}
+
+impl OKLCH {
+    pub fn new(l: f32, c: f32, h: f32, alpha: f32) -> Self {
+        Self { l, c, h, alpha }
+    }
+}
+
+impl From<RGBA> for HSVA {
+    fn from(c: RGBA) -> Self {
+        let max = c.r.max(c.g).max(c.b);
+        let min = c.r.min(c.g).min(c.b);
+        let delta = max - min;
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+        Self::new(hue_from_rgb(c.r, c.g, c.b, max, delta), s, max, c.a)
+    }
+}
+
+impl From<HSVA> for RGBA {
+    fn from(c: HSVA) -> Self {
+        let chroma = c.v * c.s;
+        let (r, g, b) = rgb_from_hue(c.h, chroma, c.v - chroma);
+        RGBA::rgba_f(r, g, b, c.a)
+    }
+}
+
+impl From<RGBA> for HSLA {
+    fn from(c: RGBA) -> Self {
+        let max = c.r.max(c.g).max(c.b);
+        let min = c.r.min(c.g).min(c.b);
+        let delta = max - min;
+        let l = (max + min) * 0.5;
+        let s = if delta == 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        Self::new(hue_from_rgb(c.r, c.g, c.b, max, delta), s, l, c.a)
+    }
+}
+
+impl From<HSLA> for RGBA {
+    fn from(c: HSLA) -> Self {
+        let chroma = (1.0 - (2.0 * c.l - 1.0).abs()) * c.s;
+        let (r, g, b) = rgb_from_hue(c.h, chroma, c.l - chroma * 0.5);
+        RGBA::rgba_f(r, g, b, c.a)
+    }
+}
+
+// based on: https://bottosson.github.io/posts/oklab/
+impl From<RGBA> for OKLAB {
+    fn from(c: RGBA) -> Self {
+        let (r, g, b) = (
+            RGBA::srgb_to_linear(c.r),
+            RGBA::srgb_to_linear(c.g),
+            RGBA::srgb_to_linear(c.b),
+        );
+
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+        Self {
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+            alpha: c.a,
+        }
+    }
+}
+
+/// out of gamut colors are clamped per channel
+impl From<OKLAB> for RGBA {
+    fn from(c: OKLAB) -> Self {
+        let l = (c.l + 0.3963377774 * c.a + 0.2158037573 * c.b).powi(3);
+        let m = (c.l - 0.1055613458 * c.a - 0.0638541728 * c.b).powi(3);
+        let s = (c.l - 0.0894841775 * c.a - 1.2914855480 * c.b).powi(3);
+
+        let r = 4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s;
+        let g = -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s;
+        let b = -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s;
+
+        let to_srgb = |v: f32| RGBA::linear_to_srgb(v.clamp(0.0, 1.0));
+        RGBA::rgba_f(to_srgb(r), to_srgb(g), to_srgb(b), c.alpha)
+    }
+}
+
+impl From<OKLAB> for OKLCH {
+    fn from(c: OKLAB) -> Self {
+        let chroma = (c.a * c.a + c.b * c.b).sqrt();
+        let h = c.b.atan2(c.a).to_degrees().rem_euclid(360.0);
+        Self::new(c.l, chroma, h, c.alpha)
+    }
+}
+
+impl From<OKLCH> for OKLAB {
+    fn from(c: OKLCH) -> Self {
+        let h = c.h.to_radians();
+        Self {
+            l: c.l,
+            a: c.c * h.cos(),
+            b: c.c * h.sin(),
+            alpha: c.alpha,
+        }
+    }
+}
+
+impl From<RGBA> for OKLCH {
+    fn from(c: RGBA) -> Self {
+        OKLAB::from(c).into()
+    }
+}
+
+impl From<OKLCH> for RGBA {
+    fn from(c: OKLCH) -> Self {
+        OKLAB::from(c).into()
+    }
+}
+
+impl RGBA {
+    pub fn with_alpha(mut self, a: f32) -> Self {
+        self.a = a;
+        self
+    }
+
+    pub fn to_hsva(self) -> HSVA {
+        self.into()
+    }
+
+    pub fn to_hsla(self) -> HSLA {
+        self.into()
+    }
+
+    pub fn to_oklab(self) -> OKLAB {
+        self.into()
+    }
+
+    pub fn to_oklch(self) -> OKLCH {
+        self.into()
+    }
+
+    /// interpolates in oklab, which keeps the perceived brightness even between the colors
+    pub fn lerp_oklab(self, other: Self, t: f32) -> Self {
+        let (c1, c2) = (self.to_oklab(), other.to_oklab());
+        OKLAB {
+            l: c1.l + (c2.l - c1.l) * t,
+            a: c1.a + (c2.a - c1.a) * t,
+            b: c1.b + (c2.b - c1.b) * t,
+            alpha: c1.alpha + (c2.alpha - c1.alpha) * t,
+        }
+        .into()
+    }
+
+    /// raises the perceptual lightness by `amount` in `[0, 1]`
+    pub fn lighten(self, amount: f32) -> Self {
+        let mut c = self.to_oklch();
+        c.l = (c.l + amount).clamp(0.0, 1.0);
+        c.into()
+    }
+
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// scales the chroma by `1 + amount`, negative values desaturate
+    pub fn saturate(self, amount: f32) -> Self {
+        let mut c = self.to_oklch();
+        c.c = (c.c * (1.0 + amount)).max(0.0);
+        c.into()
+    }
+
+    pub fn desaturate(self, amount: f32) -> Self {
+        self.saturate(-amount)
+    }
+
+    /// wcag relative luminance, alpha is ignored
+    pub fn relative_luminance(&self) -> f32 {
+        0.2126 * Self::srgb_to_linear(self.r)
+            + 0.7152 * Self::srgb_to_linear(self.g)
+            + 0.0722 * Self::srgb_to_linear(self.b)
+    }
+
+    /// wcag contrast ratio in `[1, 21]`, text should have at least 4.5 against its background
+    pub fn contrast_ratio(&self, other: Self) -> f32 {
+        let l1 = self.relative_luminance();
+        let l2 = other.relative_luminance();
+        (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+    }
+
+    /// `n` colors with the lightness and chroma of `self` and evenly spaced hues
+    pub fn hue_palette(self, n: usize) -> Vec<Self> {
+        let base = self.to_oklch();
+        (0..n)
+            .map(|i| OKLCH { h: base.h + 360.0 * i as f32 / n as f32, ..base }.into())
+            .collect()
+    }
+
+    /// `n` shades from dark to light with the hue and chroma of `self`
+    pub fn shades(self, n: usize) -> Vec<Self> {
+        let base = self.to_oklch();
+        (0..n)
+            .map(|i| {
+                let t = (i as f32 + 1.0) / (n as f32 + 1.0);
+                OKLCH { l: t, ..base }.into()
+            })
+            .collect()
+    }
+}
+
+// END COLOR SPACES
+//---------------------------------------------------------------------------------------
+
+/// source of time for the ui, frame timing, mouse clicks and animations all read from it
+///
+/// the system clock uses [`Instant`], which is backed by `performance.now()` on wasm through
+/// `web_time`. the manual clock only moves when advanced, which makes time dependent code
+/// deterministic in tests
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Clock {
+    System { start: Instant },
+    Manual { now: Duration },
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+impl Clock {
+    pub fn system() -> Self {
+        Self::System {
+            start: Instant::now(),
+        }
+    }
+
+    pub fn manual() -> Self {
+        Self::Manual {
+            now: Duration::ZERO,
+        }
+    }
+
+    /// time since the clock was created
+    pub fn now(&self) -> Duration {
+        match self {
+            Self::System { start } => start.elapsed(),
+            Self::Manual { now } => *now,
+        }
+    }
+
+    /// moves a manual clock forward, does nothing for the system clock
+    pub fn advance(&mut self, dt: Duration) {
+        if let Self::Manual { now } = self {
+            *now += dt;
+        }
+    }
+}
+
+/// accumulator for running a simulation at a fixed rate independent of the frame rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedTimestep {
+    pub fixed_dt: Duration,
+    /// frame times are clamped to this, so a long stall doesn't cause a burst of fixed updates
+    pub max_frame_time: Duration,
+    accumulator: Duration,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / 60.0))
+    }
+}
+
+impl FixedTimestep {
+    pub fn new(fixed_dt: Duration) -> Self {
+        Self {
+            fixed_dt,
+            max_frame_time: Duration::from_millis(250),
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    pub fn max_frame_time(mut self, max: Duration) -> Self {
+        self.max_frame_time = max;
+        self
+    }
+
+    /// adds the frame time and returns the number of fixed updates to run this frame
+    pub fn advance(&mut self, frame_time: Duration) -> u32 {
+        self.accumulator += frame_time.min(self.max_frame_time);
+
+        let mut steps = 0;
+        while self.accumulator >= self.fixed_dt && !self.fixed_dt.is_zero() {
+            self.accumulator -= self.fixed_dt;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// fraction of a fixed step left in the accumulator, used to interpolate between two states
+    pub fn alpha(&self) -> f32 {
+        if self.fixed_dt.is_zero() {
+            return 0.0;
+        }
+        self.accumulator.as_secs_f32() / self.fixed_dt.as_secs_f32()
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = Duration::ZERO;
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod futures {
     use std::sync::{Arc, Mutex};
@@ -871,6 +1251,7 @@ impl<T, const N: usize> std::ops::IndexMut<usize> for ArrVec<T, N> {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dir {
     N,
     NE,
@@ -917,6 +1298,7 @@ impl Dir {
         }
     }
 
+    #[cfg(feature = "winit")]
     pub fn as_winit_resize(&self) -> winit::window::ResizeDirection {
         use winit::window::ResizeDirection as RD;
         match self {
@@ -935,11 +1317,17 @@ impl Dir {
 macro_rules! id_type {
     ($id_ty:ident) => {
         #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $id_ty(pub u64);
 
         impl $id_ty {
             pub const NULL: $id_ty = $id_ty(0);
 
+            /// wrap a precomputed value, e.g. for ids built once and kept in a `const`
+            pub const fn from_u64(v: u64) -> Self {
+                Self(v)
+            }
+
             pub fn from_hash(h: &impl std::hash::Hash) -> Self {
                 use std::hash::{Hash, Hasher};
                 let mut hasher = ahash::AHasher::new_with_keys(0, 0);
@@ -1015,6 +1403,19 @@ pub fn global_hash64(h: &impl std::hash::Hash) -> u64 {
     hasher.finish()
 }
 
+/// [`global_hash64`] of a static string, cached by its address and length
+///
+/// static strings never change, so the label is only hashed the first time it is seen.
+pub fn static_str_hash64(s: &'static str) -> u64 {
+    thread_local! {
+        static CACHE: std::cell::RefCell<HashMap<(usize, usize), u64>> =
+            std::cell::RefCell::new(HashMap::new());
+    }
+
+    let key = (s.as_ptr() as usize, s.len());
+    CACHE.with_borrow_mut(|cache| *cache.entry(key).or_insert_with(|| global_hash64(&s)))
+}
+
 // a bit ugly... :(
 macro_rules! stacked_fields_struct {
     (@count: ) => {
@@ -1110,6 +1511,7 @@ macro_rules! stacked_fields_struct {
             }
 
             #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             pub enum [< $name Field >] {
                 $([< $field:camel >]),+
             }
@@ -1134,6 +1536,7 @@ macro_rules! stacked_fields_struct {
             }
 
             #[derive(Debug, Clone, Copy, PartialEq)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             pub enum [< $name Var>] {
                 $([< $field:camel >]($ty)),+
             }
@@ -1152,6 +1555,38 @@ macro_rules! stacked_fields_struct {
                     &mut self.values[field.index()]
                 }
             }
+
+            /// only the current values are stored, pushed vars are not
+            #[cfg(feature = "serde")]
+            impl serde::Serialize for Table {
+                fn serialize<S: serde::Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+                    serde::Serialize::serialize(self.values.as_slice(), s)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<'de> serde::Deserialize<'de> for Table {
+                fn deserialize<D: serde::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+                    let vars = <Vec<Var> as serde::Deserialize>::deserialize(d)?;
+                    let mut found: [Option<Var>; Table::N_VARIABLES] = [None; Table::N_VARIABLES];
+                    for var in vars {
+                        found[var.index()] = Some(var);
+                    }
+
+                    let mut values = Vec::with_capacity(Table::N_VARIABLES);
+                    for (field, var) in Field::list().into_iter().zip(found) {
+                        let Some(var) = var else {
+                            return Err(serde::de::Error::custom(format!("missing value for {field:?}")));
+                        };
+                        values.push(var);
+                    }
+
+                    Ok(Self {
+                        values: values.try_into().unwrap(),
+                        var_stack: Vec::new(),
+                    })
+                }
+            }
         }
 
 
@@ -1159,81 +1594,128 @@ macro_rules! stacked_fields_struct {
 }
 pub(crate) use stacked_fields_struct;
 
+struct DataEntry {
+    type_name: &'static str,
+    last_used: std::cell::Cell<u64>,
+    value: Box<dyn std::any::Any>,
+}
+
+impl DataEntry {
+    fn new<T: 'static>(value: T, frame: u64) -> Self {
+        Self {
+            type_name: std::any::type_name::<T>(),
+            last_used: std::cell::Cell::new(frame),
+            value: Box::new(value),
+        }
+    }
+}
+
+/// per key storage of values of any type, keyed by the key and the type of the value
+///
+/// every access marks the entry as used in the current frame, see [`DataMap::gc`]
 pub struct DataMap<K> {
-    data: HashMap<u64, Box<dyn std::any::Any>>,
-    _key_ty: std::marker::PhantomData<K>,
+    data: HashMap<(K, std::any::TypeId), DataEntry>,
+    frame: u64,
 }
 
-impl<K: Eq + hash::Hash> DataMap<K> {
+impl<K: Eq + hash::Hash + Clone> DataMap<K> {
     pub fn new() -> Self {
         Self {
             data: HashMap::new(),
-            _key_ty: std::marker::PhantomData,
+            frame: 0,
         }
     }
 
     pub fn get<T: 'static>(&self, key: &K) -> Option<&T> {
-        let key = Self::key_hash::<T>(key);
-        self.data.get(&key)?.downcast_ref::<T>()
+        let entry = self.data.get(&Self::data_key::<T>(key))?;
+        entry.last_used.set(self.frame);
+        entry.value.downcast_ref::<T>()
     }
 
     pub fn get_mut<T: 'static>(&mut self, key: &K) -> Option<&mut T> {
-        let key = Self::key_hash::<T>(key);
-        self.data.get_mut(&key)?.downcast_mut::<T>()
+        let entry = self.data.get_mut(&Self::data_key::<T>(key))?;
+        entry.last_used.set(self.frame);
+        entry.value.downcast_mut::<T>()
     }
 
     pub fn insert<T: 'static>(&mut self, key: K, value: T) {
-        let key = Self::key_hash::<T>(&key);
-        self.data.insert(key, Box::new(value));
+        self.data
+            .insert(Self::data_key::<T>(&key), DataEntry::new(value, self.frame));
     }
 
-    pub fn get_or_insert<T: 'static>(&mut self, key: K, value: T) -> &mut T
-    where
-        K: Clone,
-    {
-        let key = Self::key_hash::<T>(&key);
-        self.data
-            .entry(key)
-            .or_insert_with(|| Box::new(value))
-            .downcast_mut::<T>()
-            .expect("Type mismatch in TypeMap")
+    pub fn get_or_insert<T: 'static>(&mut self, key: K, value: T) -> &mut T {
+        self.get_or_insert_with(key, || value)
     }
 
     pub fn get_or_insert_with<T: 'static, F: FnOnce() -> T>(&mut self, key: K, f: F) -> &mut T {
-        let key = Self::key_hash::<T>(&key);
-        self.data
-            .entry(key)
-            .or_insert_with(|| Box::new(f()))
+        let frame = self.frame;
+        let entry = self
+            .data
+            .entry(Self::data_key::<T>(&key))
+            .or_insert_with(|| DataEntry::new(f(), frame));
+        entry.last_used.set(frame);
+        entry
+            .value
             .downcast_mut::<T>()
             .expect("Type mismatch in TypeMap")
     }
 
-    pub fn remove<T: 'static>(&mut self, key: &K) -> bool {
-        let key = Self::key_hash::<T>(key);
-        self.data.remove(&key).is_some()
+    /// removes and returns the value of type `T` stored for `key`
+    pub fn remove<T: 'static>(&mut self, key: &K) -> Option<T> {
+        let entry = self.data.remove(&Self::data_key::<T>(key))?;
+        entry.value.downcast::<T>().ok().map(|v| *v)
+    }
+
+    /// removes the values of all types stored for `key`, returns how many were removed
+    pub fn remove_all(&mut self, key: &K) -> usize {
+        let len = self.data.len();
+        self.data.retain(|(k, _), _| k != key);
+        len - self.data.len()
     }
 
     pub fn contains_key<T: 'static>(&self, key: &K) -> bool {
-        let key = Self::key_hash::<T>(key);
-        self.data.contains_key(&key)
+        self.data.contains_key(&Self::data_key::<T>(key))
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
     }
 
     pub fn clear(&mut self) {
         self.data.clear();
     }
 
-    fn key_hash<T: 'static>(key: &K) -> u64 {
-        use std::hash::{Hash, Hasher};
-        let type_id = std::any::TypeId::of::<T>();
+    /// sets the frame that accesses are recorded with
+    pub fn set_frame(&mut self, frame: u64) {
+        self.frame = frame;
+    }
+
+    /// removes entries that were not accessed in the last `max_age` frames, returns how many
+    /// were removed
+    pub fn gc(&mut self, max_age: u64) -> usize {
+        let len = self.data.len();
+        let frame = self.frame;
+        self.data.retain(|_, e| frame.saturating_sub(e.last_used.get()) <= max_age);
+        len - self.data.len()
+    }
+
+    /// key, type name and the frame of the last access of every entry
+    pub fn entries(&self) -> impl Iterator<Item = (&K, &'static str, u64)> {
+        self.data
+            .iter()
+            .map(|((k, _), e)| (k, e.type_name, e.last_used.get()))
+    }
 
-        let mut hasher = ahash::AHasher::new_with_keys(0, 0);
-        type_id.hash(&mut hasher);
-        key.hash(&mut hasher);
-        hasher.finish()
+    fn data_key<T: 'static>(key: &K) -> (K, std::any::TypeId) {
+        (key.clone(), std::any::TypeId::of::<T>())
     }
 }
 
-impl<K: Eq + hash::Hash> Default for DataMap<K> {
+impl<K: Eq + hash::Hash + Clone> Default for DataMap<K> {
     fn default() -> Self {
         Self::new()
     }
@@ -1244,6 +1726,104 @@ impl<K: Eq + hash::Hash> Default for DataMap<K> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_manual_clock() {
+        let mut clock = Clock::manual();
+        assert_eq!(clock.now(), Duration::ZERO);
+        clock.advance(Duration::from_millis(16));
+        clock.advance(Duration::from_millis(16));
+        assert_eq!(clock.now(), Duration::from_millis(32));
+
+        let mut clock = Clock::system();
+        let t = clock.now();
+        clock.advance(Duration::from_secs(100));
+        assert!(clock.now() < t + Duration::from_secs(100));
+    }
+
+    #[test]
+    fn test_data_map_gc() {
+        let mut data = DataMap::<u64>::new();
+        data.insert(1, 1.0f32);
+        data.insert(1, "one");
+        data.insert(2, 2.0f32);
+
+        data.set_frame(10);
+        assert_eq!(data.get::<f32>(&2), Some(&2.0));
+        assert_eq!(data.gc(5), 2);
+        assert_eq!(data.len(), 1);
+        assert!(data.contains_key::<f32>(&2));
+
+        data.insert(2, 'x');
+        let types: Vec<_> = data.entries().map(|(_, ty, _)| ty).collect();
+        assert!(types.contains(&"char"));
+        assert_eq!(data.remove_all(&2), 2);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_data_map_typed() {
+        let mut data = DataMap::<u64>::new();
+        *data.get_or_insert_with(1, || 0u32) += 3;
+        data.insert(1, String::from("a"));
+
+        assert_eq!(data.get::<u32>(&1), Some(&3));
+        assert_eq!(data.get::<String>(&1).map(|s| s.as_str()), Some("a"));
+        assert_eq!(data.get::<u32>(&2), None);
+        assert_eq!(data.remove::<u32>(&1), Some(3));
+        assert_eq!(data.remove::<u32>(&1), None);
+        assert_eq!(data.len(), 1);
+    }
+
+    #[test]
+    fn test_fixed_timestep() {
+        let mut ts = FixedTimestep::new(Duration::from_millis(10));
+
+        assert_eq!(ts.advance(Duration::from_millis(25)), 2);
+        assert!((ts.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(ts.advance(Duration::from_millis(5)), 1);
+        assert_eq!(ts.alpha(), 0.0);
+
+        // a long stall is clamped to max_frame_time
+        assert_eq!(ts.advance(Duration::from_secs(10)), 25);
+    }
+
+    fn assert_col_eq(a: RGBA, b: RGBA) {
+        let d = (a.r - b.r).abs() + (a.g - b.g).abs() + (a.b - b.b).abs() + (a.a - b.a).abs();
+        assert!(d < 1e-3, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_color_space_roundtrip() {
+        for col in [RGBA::ORANGE, RGBA::INDIGO, RGBA::PASTEL_MINT, RGBA::WHITE, RGBA::BLACK] {
+            assert_col_eq(col.to_hsva().into(), col);
+            assert_col_eq(col.to_hsla().into(), col);
+            assert_col_eq(col.to_oklch().into(), col);
+        }
+
+        let hsv = RGBA::rgb(255, 0, 0).to_hsva();
+        assert_eq!((hsv.h, hsv.s, hsv.v), (0.0, 1.0, 1.0));
+        assert_col_eq(HSLA::new(120.0, 1.0, 0.5, 1.0).into(), RGBA::GREEN);
+    }
+
+    #[test]
+    fn test_try_hex() {
+        assert_eq!(RGBA::try_hex("#ff0000"), Ok(RGBA::RED));
+        assert_eq!(RGBA::try_hex("0f0"), Ok(RGBA::GREEN));
+        assert_eq!(RGBA::try_hex("#00000000"), Ok(RGBA::ZERO));
+        assert_eq!(RGBA::try_hex("#12345"), Err(HexError::InvalidLength(5)));
+        assert_eq!(RGBA::try_hex("#12g456"), Err(HexError::InvalidDigit(3)));
+        assert_eq!(macros::rgba!("#214675"), RGBA::hex("#214675"));
+        assert_eq!(RGBA::hex("#12g456"), RGBA::BLACK);
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!((RGBA::WHITE.contrast_ratio(RGBA::BLACK) - 21.0).abs() < 1e-3);
+        assert_eq!(RGBA::ORANGE.contrast_ratio(RGBA::ORANGE), 1.0);
+        assert!(RGBA::INDIGO.lighten(0.2).relative_luminance() > RGBA::INDIGO.relative_luminance());
+        assert_eq!(RGBA::ORANGE.hue_palette(4).len(), 4);
+    }
+
     #[test]
     fn test_basic_operations() {
         let mut vec: ArrVec<i32, 5> = ArrVec::new();
@@ -1,14 +1,22 @@
 use std::{
     cell::RefCell,
     fmt, hash,
-    sync::{Arc, Mutex},
+    marker::PhantomData,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
-use glam::Vec2;
+use glam::{Mat4, UVec2, Vec2, Vec3};
+use wgpu::{rwh, util::DeviceExt};
 
 use crate::{
-    core::{self, HashMap},
+    core::{self, HashMap, RGBA},
     mouse,
+    platform::{
+        HeadlessWindow, MonitorInfo, PlatformWindow, UserAttention, WindowIcon, WindowPlacement,
+    },
     rect::Rect,
 };
 
@@ -83,10 +91,18 @@ impl Texture {
         assert_eq!((width * height * 4) as usize, data.len());
 
         let texture = Self::create_empty_with_usage(wgpu, width, height, usage | wgpu::TextureUsages::COPY_DST);
+        texture.write(wgpu, data);
+        texture
+    }
+
+    /// replaces the whole content of the texture, it has to be created with `COPY_DST` usage
+    pub fn write(&self, wgpu: &WGPU, data: &[u8]) {
+        let (width, height) = (self.width(), self.height());
+        assert_eq!((width * height * 4) as usize, data.len());
 
         wgpu.queue.write_texture(
             wgpu::TexelCopyTextureInfoBase {
-                texture: texture.raw(),
+                texture: self.raw(),
                 mip_level: 0,
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
@@ -103,8 +119,6 @@ impl Texture {
                 depth_or_array_layers: 1,
             },
         );
-
-        texture
     }
 
     pub fn create(wgpu: &WGPU, width: u32, height: u32, data: &[u8]) -> Self {
@@ -198,7 +212,7 @@ pub fn pre_process_shader_code<const N: usize>(
                 ));
             };
 
-            let Some(desc_wgsl_typ) = vertex_format_to_wgsl(attr.format) else {
+            let Some(desc_wgsl_typ) = vertex_format_wgsl(attr.format) else {
                 return Err(format!("unsupported format: {:?}", attr.format));
             };
 
@@ -238,9 +252,14 @@ pub fn pre_process_shader_code<const N: usize>(
         let mut struct_str = format!("\nstruct {name} {{\n");
 
         for (attrib, member) in desc.attributes.iter().zip(&desc.members) {
-            let ty = vertex_format_to_wgsl(attrib.format).unwrap();
-            struct_str.push_str(&format!("@location({location}) {}: {},\n", member, ty));
-            location += 1;
+            let ty = vertex_format_wgsl(attrib.format).unwrap();
+            // uniforms use the plain struct form, vertex inputs need a location
+            if desc.uniform {
+                struct_str.push_str(&format!("{}: {},\n", member, ty));
+            } else {
+                struct_str.push_str(&format!("@location({location}) {}: {},\n", member, ty));
+                location += 1;
+            }
         }
 
         wgsl_structs.push_str(&struct_str);
@@ -306,11 +325,81 @@ pub trait Vertex: Sized + Copy + bytemuck::Pod + bytemuck::Zeroable {
     }
 }
 
+/// a type that can be a field of a `#[macros::vertex]` struct
+///
+/// `#[derive(macros::AsVertexFormat)]` implements it for newtypes, e.g. `struct Uv(Vec2)`,
+/// or with `#[vertex_format(Unorm8x4)]` for any type of the same size as the format
+#[diagnostic::on_unimplemented(message = "`{Self}` can not be used as a vertex attribute")]
 pub trait AsVertexFormat {
     const VERTEX_FORMAT: wgpu::VertexFormat;
     const WGSL: Option<&'static str>;
 }
 
+/// the type a shader reads a vertex attribute of `format` as, `None` for 64 bit floats
+pub const fn vertex_format_wgsl(format: wgpu::VertexFormat) -> Option<&'static str> {
+    use wgpu::VertexFormat as F;
+
+    Some(match format {
+        F::Uint8 | F::Uint16 | F::Uint32 => "u32",
+        F::Uint8x2 | F::Uint16x2 | F::Uint32x2 => "vec2<u32>",
+        F::Uint32x3 => "vec3<u32>",
+        F::Uint8x4 | F::Uint16x4 | F::Uint32x4 => "vec4<u32>",
+        F::Sint8 | F::Sint16 | F::Sint32 => "i32",
+        F::Sint8x2 | F::Sint16x2 | F::Sint32x2 => "vec2<i32>",
+        F::Sint32x3 => "vec3<i32>",
+        F::Sint8x4 | F::Sint16x4 | F::Sint32x4 => "vec4<i32>",
+        F::Unorm8 | F::Snorm8 | F::Unorm16 | F::Snorm16 | F::Float16 | F::Float32 => "f32",
+        F::Unorm8x2 | F::Snorm8x2 | F::Unorm16x2 | F::Snorm16x2 => "vec2<f32>",
+        F::Float16x2 | F::Float32x2 => "vec2<f32>",
+        F::Float32x3 => "vec3<f32>",
+        F::Unorm8x4 | F::Snorm8x4 | F::Unorm16x4 | F::Snorm16x4 => "vec4<f32>",
+        F::Float16x4 | F::Float32x4 | F::Unorm10_10_10_2 | F::Unorm8x4Bgra => "vec4<f32>",
+        F::Float64 | F::Float64x2 | F::Float64x3 | F::Float64x4 => return None,
+    })
+}
+
+macro_rules! normalized_formats {
+    ($($name:ident: [$int:ty; $n:literal], $min:literal);* $(;)?) => {
+        $(
+            /// normalized integer attribute, the shader reads it as floats in
+            #[doc = concat!("`", stringify!($min), "..=1`")]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+            #[derive(bytemuck::Pod, bytemuck::Zeroable, macros::AsVertexFormat)]
+            #[vertex_format($name)]
+            #[repr(transparent)]
+            pub struct $name(pub [$int; $n]);
+
+            impl $name {
+                /// rounds the values, clamped to the normalized range
+                pub fn from_f32(v: [f32; $n]) -> Self {
+                    Self(v.map(|x| (x.clamp($min, 1.0) * <$int>::MAX as f32).round() as $int))
+                }
+
+                pub fn to_f32(self) -> [f32; $n] {
+                    self.0.map(|x| (x as f32 / <$int>::MAX as f32).max($min))
+                }
+            }
+        )*
+    };
+}
+
+normalized_formats! {
+    Unorm8x2: [u8; 2], 0.0;
+    Unorm8x4: [u8; 4], 0.0;
+    Snorm8x2: [i8; 2], -1.0;
+    Snorm8x4: [i8; 4], -1.0;
+    Unorm16x2: [u16; 2], 0.0;
+    Unorm16x4: [u16; 4], 0.0;
+    Snorm16x2: [i16; 2], -1.0;
+    Snorm16x4: [i16; 4], -1.0;
+}
+
+impl From<RGBA> for Unorm8x4 {
+    fn from(c: RGBA) -> Self {
+        Self::from_f32([c.r, c.g, c.b, c.a])
+    }
+}
+
 macro_rules! impl_as_vertex_fmt {
     // single entry, optionally with WGSL
     ($($ty:ty : $fmt:ident $( : $wgsl:expr )?),* $(,)?) => {
@@ -320,17 +409,6 @@ macro_rules! impl_as_vertex_fmt {
                 const WGSL: Option<&'static str> = impl_as_vertex_fmt!(@wgsl $($wgsl)?);
             }
         )*
-
-        pub fn vertex_format_to_wgsl(fmt: wgpu::VertexFormat) -> Option<&'static str> {
-            match fmt {
-                $(
-                    wgpu::VertexFormat::$fmt => {
-                        None$(.or(Some($wgsl)))?
-                    }
-                ),*
-                _ => None
-            }
-        }
     };
 
     // helper to expand WGSL presence
@@ -394,11 +472,141 @@ impl_as_vertex_fmt! {
     glam::Vec2: Float32x2: "vec2<f32>",
     glam::Vec3: Float32x3: "vec3<f32>",
     glam::Vec4: Float32x4: "vec4<f32>",
+}
+
+// BEGIN WGSL TYPES
+//---------------------------------------------------------------------------------------
+
+/// a rust type with the same memory layout as a wgsl type in uniform and storage buffers
+///
+/// implemented for scalars, vectors, matrices, arrays and structs tagged with
+/// `#[macros::shader_struct]`
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no WGSL equivalent",
+    note = "use a 32 bit scalar, a glam vector, `Mat2`, `Mat4`, an array or a shader struct"
+)]
+pub trait WgslType: bytemuck::Pod {
+    /// alignment and size in storage buffers
+    const ALIGN: usize;
+    const SIZE: usize;
+    /// alignment and size in uniform buffers, where arrays and structs are 16 byte aligned
+    const UNIFORM_ALIGN: usize = Self::ALIGN;
+    const UNIFORM_SIZE: usize = Self::SIZE;
+
+    /// the wgsl name of the type, e.g. `vec4<f32>` or `array<Light, 4>`
+    fn wgsl_type() -> String;
+
+    /// appends the declarations of the structs the type is made of, each one once and
+    /// before its users
+    fn wgsl_structs(_defs: &mut Vec<String>) {}
+
+    /// the struct declarations to put in front of a shader using the type
+    fn wgsl_definitions() -> String {
+        let mut defs = Vec::new();
+        Self::wgsl_structs(&mut defs);
+        defs.concat()
+    }
+}
+
+pub const fn wgsl_round_up(align: usize, n: usize) -> usize {
+    n.div_ceil(align) * align
+}
+
+/// the padding in front of each `(align, size)` field when laid out like a wgsl struct
+pub const fn wgsl_paddings<const N: usize>(fields: [(usize, usize); N]) -> [usize; N] {
+    let mut pads = [0; N];
+    let mut end = 0;
+    let mut i = 0;
+    while i < N {
+        let (align, size) = fields[i];
+        let offset = wgsl_round_up(align, end);
+        pads[i] = offset - end;
+        end = offset + size;
+        i += 1;
+    }
+    pads
+}
+
+/// the largest alignment of the `(align, size)` fields
+pub const fn wgsl_struct_align<const N: usize>(fields: [(usize, usize); N]) -> usize {
+    let mut align = 1;
+    let mut i = 0;
+    while i < N {
+        if fields[i].0 > align {
+            align = fields[i].0;
+        }
+        i += 1;
+    }
+    align
+}
+
+/// the size of a struct with the `(align, size)` fields, padded to `align`
+pub const fn wgsl_struct_size<const N: usize>(fields: [(usize, usize); N], align: usize) -> usize {
+    let pads = wgsl_paddings(fields);
+    let mut end = 0;
+    let mut i = 0;
+    while i < N {
+        end += pads[i] + fields[i].1;
+        i += 1;
+    }
+    wgsl_round_up(align, end)
+}
+
+macro_rules! impl_wgsl_type {
+    ($($ty:ty : $wgsl:literal, $align:literal, $size:literal);* $(;)?) => {
+        $(
+            impl WgslType for $ty {
+                const ALIGN: usize = $align;
+                const SIZE: usize = $size;
+
+                fn wgsl_type() -> String {
+                    $wgsl.into()
+                }
+            }
+        )*
+    };
+}
+
+impl_wgsl_type! {
+    f32: "f32", 4, 4;
+    u32: "u32", 4, 4;
+    i32: "i32", 4, 4;
+    glam::Vec2: "vec2<f32>", 8, 8;
+    glam::UVec2: "vec2<u32>", 8, 8;
+    glam::IVec2: "vec2<i32>", 8, 8;
+    glam::Vec3: "vec3<f32>", 16, 12;
+    glam::UVec3: "vec3<u32>", 16, 12;
+    glam::IVec3: "vec3<i32>", 16, 12;
+    glam::Vec4: "vec4<f32>", 16, 16;
+    glam::UVec4: "vec4<u32>", 16, 16;
+    glam::IVec4: "vec4<i32>", 16, 16;
+    glam::Mat2: "mat2x2<f32>", 8, 16;
+    glam::Mat4: "mat4x4<f32>", 16, 64;
+    core::RGB: "vec3<f32>", 16, 12;
+    core::RGBA: "vec4<f32>", 16, 16;
+}
+
+impl<T: WgslType, const N: usize> WgslType for [T; N]
+where
+    [T; N]: bytemuck::Pod,
+{
+    const ALIGN: usize = T::ALIGN;
+    const SIZE: usize = N * wgsl_round_up(T::ALIGN, T::SIZE);
+    const UNIFORM_ALIGN: usize = wgsl_round_up(16, T::UNIFORM_ALIGN);
+    const UNIFORM_SIZE: usize = N * wgsl_round_up(Self::UNIFORM_ALIGN, T::UNIFORM_SIZE);
+
+    fn wgsl_type() -> String {
+        format!("array<{}, {N}>", T::wgsl_type())
+    }
 
-    core::RGB: Float32x3: "vec3<f32>",
-    core::RGBA: Float32x4: "vec4<f32>",
+    fn wgsl_structs(defs: &mut Vec<String>) {
+        T::wgsl_structs(defs);
+    }
 }
 
+// END WGSL TYPES
+//---------------------------------------------------------------------------------------
+
 #[derive(Debug)]
 pub struct ResourceCache<ID, RSRC> {
     pub cache: HashMap<ID, Arc<RSRC>>,
@@ -419,6 +627,14 @@ impl<ID: Copy + Eq + hash::Hash + fmt::Debug, RSRC> ResourceCache<ID, RSRC> {
         self.cache.get(&id).cloned()
     }
 
+    fn remove(&mut self, id: ID) -> Option<Arc<RSRC>> {
+        self.cache.remove(&id)
+    }
+
+    fn clear(&mut self) {
+        self.cache.clear();
+    }
+
     /// lazy create helper (if you want one-shot creation)
     fn get_or_insert_with<F>(&mut self, id: ID, load_fn: F) -> Arc<RSRC>
     where
@@ -431,10 +647,383 @@ impl<ID: Copy + Eq + hash::Hash + fmt::Debug, RSRC> ResourceCache<ID, RSRC> {
     }
 }
 
+/// a resource bound by a cached bind group, see [`BindGroupCache::bind_group`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BindResource {
+    /// `size` bytes of the buffer, the offset is passed when the bind group is set
+    Buffer(wgpu::Buffer, Option<wgpu::BufferSize>),
+    Sampler(wgpu::Sampler),
+    TextureView(wgpu::TextureView),
+}
+
+impl BindResource {
+    fn binding(&self) -> wgpu::BindingResource<'_> {
+        match self {
+            Self::Buffer(buffer, size) => wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset: 0,
+                size: *size,
+            }),
+            Self::Sampler(sampler) => wgpu::BindingResource::Sampler(sampler),
+            Self::TextureView(view) => wgpu::BindingResource::TextureView(view),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CachedBindGroup {
+    group: wgpu::BindGroup,
+    last_used: u64,
+}
+
+/// deduplicates bind group layouts and reuses bind groups whose resources did not change
+///
+/// bind groups keep their resources alive, they are dropped once they were not used for
+/// [`BindGroupCache::MAX_UNUSED_FRAMES`] calls to [`BindGroupCache::end_frame`]
+#[derive(Debug, Default)]
+pub struct BindGroupCache {
+    layouts: HashMap<Vec<wgpu::BindGroupLayoutEntry>, wgpu::BindGroupLayout>,
+    groups: HashMap<(wgpu::BindGroupLayout, Vec<BindResource>), CachedBindGroup>,
+    samplers: HashMap<PipelineKey, wgpu::Sampler>,
+    frame: u64,
+}
+
+impl BindGroupCache {
+    pub const MAX_UNUSED_FRAMES: u64 = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the layout with `entries`, created on first use
+    pub fn layout(
+        &mut self,
+        device: &wgpu::Device,
+        entries: &[wgpu::BindGroupLayoutEntry],
+    ) -> wgpu::BindGroupLayout {
+        if let Some(layout) = self.layouts.get(entries) {
+            return layout.clone();
+        }
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cached_bind_group_layout"),
+            entries,
+        });
+        self.layouts.insert(entries.to_vec(), layout.clone());
+        layout
+    }
+
+    /// a bind group binding `resources[i]` to binding `i` of `layout`
+    pub fn bind_group(
+        &mut self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        resources: &[BindResource],
+    ) -> wgpu::BindGroup {
+        let frame = self.frame;
+        let key = (layout.clone(), resources.to_vec());
+        if let Some(cached) = self.groups.get_mut(&key) {
+            cached.last_used = frame;
+            return cached.group.clone();
+        }
+
+        let entries: Vec<_> = resources
+            .iter()
+            .enumerate()
+            .map(|(i, r)| wgpu::BindGroupEntry {
+                binding: i as u32,
+                resource: r.binding(),
+            })
+            .collect();
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("cached_bind_group"),
+            layout,
+            entries: &entries,
+        });
+        let cached = CachedBindGroup {
+            group: group.clone(),
+            last_used: frame,
+        };
+        self.groups.insert(key, cached);
+        group
+    }
+
+    /// the sampler stored under `key`, created from `desc` on first use
+    pub fn sampler(
+        &mut self,
+        device: &wgpu::Device,
+        key: impl Into<PipelineKey>,
+        desc: &wgpu::SamplerDescriptor<'_>,
+    ) -> wgpu::Sampler {
+        self.samplers
+            .entry(key.into())
+            .or_insert_with(|| device.create_sampler(desc))
+            .clone()
+    }
+
+    /// drops the bind groups that were not used for a while, call it once per frame
+    pub fn end_frame(&mut self) {
+        self.frame += 1;
+        let frame = self.frame;
+        self.groups.retain(|_, g| frame - g.last_used <= Self::MAX_UNUSED_FRAMES);
+    }
+
+    pub fn n_bind_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// drops everything, e.g. after the device was lost
+    pub fn clear(&mut self) {
+        self.layouts.clear();
+        self.groups.clear();
+        self.samplers.clear();
+    }
+}
+
+/// uniform buffer split into slots that are handed out in turn and bound with a dynamic
+/// offset, so every draw can use its own uniform data without allocating a buffer
+///
+/// the values of a frame are packed into consecutive slots aligned to
+/// `min_uniform_buffer_offset_alignment`. [`UniformRing::reset`] starts the next frame at the
+/// first slot again. when a frame needs more slots than the buffer has, the ring continues in
+/// a new buffer with twice as many slots, so bind groups have to be taken from
+/// [`UniformRing::resource`] after the push
+#[derive(Debug)]
+pub struct UniformRing {
+    device: wgpu::Device,
+    size: u64,
+    slot_size: u64,
+    slots: Mutex<(wgpu::Buffer, SlotCursor)>,
+}
+
+/// hands out the slots of a [`UniformRing`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SlotCursor {
+    n_slots: u64,
+    next: u64,
+}
+
+impl SlotCursor {
+    /// the next free slot and if the ring had to grow to get it, the slots handed out since
+    /// the last reset are never reused
+    fn advance(&mut self) -> (u64, bool) {
+        let grow = self.next == self.n_slots;
+        if grow {
+            self.n_slots *= 2;
+            self.next = 0;
+        }
+        self.next += 1;
+        (self.next - 1, grow)
+    }
+}
+
+impl UniformRing {
+    /// a ring of `n_slots` slots for uniforms of `size` bytes
+    pub fn new(device: &wgpu::Device, size: u64, n_slots: u64) -> Self {
+        let align = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let slot_size = size.div_ceil(align) * align;
+        let n_slots = n_slots.max(1);
+        let buffer = Self::create_buffer(device, slot_size * n_slots);
+        Self {
+            device: device.clone(),
+            size,
+            slot_size,
+            slots: Mutex::new((buffer, SlotCursor { n_slots, next: 0 })),
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, size: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("uniform_ring_buffer"),
+            size,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// the buffer bound with the size of one uniform
+    pub fn resource(&self) -> BindResource {
+        let buffer = self.slots.lock().unwrap().0.clone();
+        BindResource::Buffer(buffer, wgpu::BufferSize::new(self.size))
+    }
+
+    /// number of slots of the current buffer
+    pub fn n_slots(&self) -> u64 {
+        self.slots.lock().unwrap().1.n_slots
+    }
+
+    /// writes `data` into the next slot, returns the dynamic offset of that slot
+    pub fn push<T: bytemuck::Pod>(&self, queue: &wgpu::Queue, data: &T) -> u32 {
+        debug_assert_eq!(std::mem::size_of::<T>() as u64, self.size);
+        let mut slots = self.slots.lock().unwrap();
+        let (buffer, cursor) = &mut *slots;
+        let (slot, grow) = cursor.advance();
+        if grow {
+            log::debug!("uniform ring grew to {} slots", cursor.n_slots);
+            *buffer = Self::create_buffer(&self.device, self.slot_size * cursor.n_slots);
+        }
+        let offset = slot * self.slot_size;
+        queue.write_buffer(buffer, offset, bytemuck::bytes_of(data));
+        offset as u32
+    }
+
+    /// starts a new frame at the first slot, the previous frame has to be submitted already
+    pub fn reset(&self) {
+        self.slots.lock().unwrap().1.next = 0;
+    }
+}
+
+/// small per draw constants of type `T`, e.g. a tint color or a transform, set without
+/// touching any bind group
+///
+/// push constants are used where the device supports them, otherwise the values go through a
+/// [`UniformRing`] bound with a dynamic offset at bind group `group`. the ring has to be reset
+/// with [`DrawConstants::begin_frame`] once per frame. pipelines have to include
+/// [`DrawConstants::push_constant_ranges`] and [`DrawConstants::bind_group_layout`] and the
+/// shader declares the constants with [`DrawConstants::wgsl`]
+#[derive(Debug)]
+pub struct DrawConstants<T> {
+    stages: wgpu::ShaderStages,
+    group: u32,
+    ranges: Vec<wgpu::PushConstantRange>,
+    fallback: Option<UniformFallback>,
+    _marker: PhantomData<T>,
+}
+
+#[derive(Debug)]
+struct UniformFallback {
+    ring: UniformRing,
+    layout: wgpu::BindGroupLayout,
+    /// rebuilt when the ring grew, keyed by its number of slots
+    bind_group: Mutex<(u64, wgpu::BindGroup)>,
+}
+
+impl UniformFallback {
+    fn bind_group(&self) -> wgpu::BindGroup {
+        let n_slots = self.ring.n_slots();
+        let mut bind_group = self.bind_group.lock().unwrap();
+        if bind_group.0 != n_slots {
+            let resource = self.ring.resource();
+            let group = self.ring.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("draw_constants_bind_group"),
+                layout: &self.layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: resource.binding(),
+                }],
+            });
+            *bind_group = (n_slots, group);
+        }
+        bind_group.1.clone()
+    }
+}
+
+impl<T: bytemuck::Pod> DrawConstants<T> {
+    /// initial number of slots of the uniform fallback, it grows when a frame needs more
+    pub const FALLBACK_SLOTS: u64 = 256;
+
+    pub fn new(wgpu: &WGPU, stages: wgpu::ShaderStages, group: u32) -> Self {
+        let size = std::mem::size_of::<T>() as u32;
+        assert!(size.is_multiple_of(4), "draw constants have to be a multiple of 4 bytes");
+
+        if wgpu.supports_push_constants(size) {
+            return Self {
+                stages,
+                group,
+                ranges: vec![wgpu::PushConstantRange {
+                    stages,
+                    range: 0..size,
+                }],
+                fallback: None,
+                _marker: PhantomData,
+            };
+        }
+
+        let ring = UniformRing::new(&wgpu.device, size as u64, Self::FALLBACK_SLOTS);
+        let mut cache = wgpu.bind_groups.lock().unwrap();
+        let layout = cache.layout(
+            &wgpu.device,
+            &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: stages,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(size as u64),
+                },
+                count: None,
+            }],
+        );
+        let bind_group = cache.bind_group(&wgpu.device, &layout, &[ring.resource()]);
+        Self {
+            stages,
+            group,
+            ranges: Vec::new(),
+            fallback: Some(UniformFallback {
+                bind_group: Mutex::new((ring.n_slots(), bind_group)),
+                ring,
+                layout,
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn uses_push_constants(&self) -> bool {
+        self.fallback.is_none()
+    }
+
+    /// for [`PipelineBuilder::push_constants`], empty when the uniform fallback is used
+    pub fn push_constant_ranges(&self) -> &[wgpu::PushConstantRange] {
+        &self.ranges
+    }
+
+    /// layout of bind group `group` for the uniform fallback
+    pub fn bind_group_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.fallback.as_ref().map(|f| &f.layout)
+    }
+
+    /// wgsl declaration of the constants as `var name: ty`
+    pub fn wgsl(&self, name: &str, ty: &str) -> String {
+        match self.fallback {
+            None => format!("var<push_constant> {name}: {ty};"),
+            Some(_) => format!(
+                "@group({}) @binding(0) var<uniform> {name}: {ty};",
+                self.group
+            ),
+        }
+    }
+
+    /// starts a new frame of the uniform fallback, call it after the previous frame was
+    /// submitted and before the first [`Self::set`]
+    pub fn begin_frame(&self) {
+        if let Some(f) = &self.fallback {
+            f.ring.reset();
+        }
+    }
+
+    /// sets the constants for the following draws of `rpass`
+    pub fn set(&self, rpass: &mut wgpu::RenderPass<'_>, queue: &wgpu::Queue, value: &T) {
+        match &self.fallback {
+            None => rpass.set_push_constants(self.stages, 0, bytemuck::bytes_of(value)),
+            Some(f) => {
+                let offset = f.ring.push(queue, value);
+                rpass.set_bind_group(self.group, &f.bind_group(), &[offset]);
+            }
+        }
+    }
+}
+
 pub type WGPUHandle = Arc<WGPU>;
 
+/// format of the stencil attachment used for clip masks
+pub const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Stencil8;
+
 pub struct WGPU {
-    pub pipeline_cache: Mutex<ResourceCache<UUID, wgpu::RenderPipeline>>,
+    pub pipeline_cache: Mutex<ResourceCache<PipelineKey, wgpu::RenderPipeline>>,
+    pub bind_groups: Mutex<BindGroupCache>,
+    /// stencil attachment shared by the render targets, see [`WGPU::stencil_view`]
+    pub stencil: Mutex<Option<wgpu::TextureView>>,
+    /// temporary textures and buffers, e.g. of [`FrameGraph`]s
+    pub resource_pool: Mutex<ResourcePool>,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub instance: wgpu::Instance,
@@ -446,53 +1035,98 @@ pub struct WGPU {
 
 impl WGPU {
     /// Register a new render pipeline with the given ID
-    pub fn register_pipeline(&self, id: UUID, pipeline: wgpu::RenderPipeline) {
-        self.pipeline_cache.lock().unwrap().register(id, pipeline);
+    pub fn register_pipeline(&self, id: impl Into<PipelineKey>, pipeline: wgpu::RenderPipeline) {
+        self.pipeline_cache.lock().unwrap().register(id.into(), pipeline);
     }
 
     /// Get a registered pipeline by ID
-    pub fn get_pipeline(&self, id: UUID) -> Option<Arc<wgpu::RenderPipeline>> {
-        self.pipeline_cache.lock().unwrap().get(id)
+    pub fn get_pipeline(&self, id: impl Into<PipelineKey>) -> Option<Arc<wgpu::RenderPipeline>> {
+        self.pipeline_cache.lock().unwrap().get(id.into())
     }
 
     /// Get or create a pipeline
-    pub fn get_or_init_pipeline<F>(&self, id: UUID, load: F) -> Arc<wgpu::RenderPipeline>
+    pub fn get_or_init_pipeline<F>(
+        &self,
+        id: impl Into<PipelineKey>,
+        load: F,
+    ) -> Arc<wgpu::RenderPipeline>
     where
         F: FnOnce() -> wgpu::RenderPipeline,
     {
         self.pipeline_cache
             .lock()
             .unwrap()
-            .get_or_insert_with(id, load)
+            .get_or_insert_with(id.into(), load)
             .clone()
     }
 
-    pub async fn new_async(
-        window: winit::window::Window,
-        width: u32,
-        height: u32,
-    ) -> (Self, Window) {
-        let window = Box::new(window);
+    /// Remove a pipeline so it is rebuilt on the next use, e.g. after its shader changed
+    pub fn remove_pipeline(
+        &self,
+        id: impl Into<PipelineKey>,
+    ) -> Option<Arc<wgpu::RenderPipeline>> {
+        self.pipeline_cache.lock().unwrap().remove(id.into())
+    }
 
-        let backends = if cfg!(target_os = "linux") {
-            wgpu::Backends::PRIMARY
-        } else if cfg!(target_os = "macos") {
-            wgpu::Backends::METAL
-        } else if cfg!(target_os = "windows") {
-            wgpu::Backends::PRIMARY
-        } else if cfg!(target_arch = "wasm32") {
-            wgpu::Backends::GL | wgpu::Backends::BROWSER_WEBGPU
-        } else {
-            wgpu::Backends::all()
-        };
+    /// true if pipelines can use `size` bytes of push constants
+    pub fn supports_push_constants(&self, size: u32) -> bool {
+        self.device
+            .features()
+            .contains(wgpu::Features::PUSH_CONSTANTS)
+            && self.device.limits().max_push_constant_size >= size
+    }
+
+    /// a [`STENCIL_FORMAT`] view matching the size and sample count of `target`
+    ///
+    /// the texture is reused until the target size changes
+    pub fn stencil_view(&self, target: &wgpu::Texture) -> wgpu::TextureView {
+        let mut stencil = self.stencil.lock().unwrap();
+        if let Some(view) = &*stencil {
+            let tex = view.texture();
+            if tex.size() == target.size() && tex.sample_count() == target.sample_count() {
+                return view.clone();
+            }
+        }
+
+        let tex = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("stencil_texture"),
+            size: target.size(),
+            mip_level_count: 1,
+            sample_count: target.sample_count(),
+            dimension: wgpu::TextureDimension::D2,
+            format: STENCIL_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = tex.create_view(&wgpu::TextureViewDescriptor::default());
+        *stencil = Some(view.clone());
+        view
+    }
+
+    /// true if pipelines can draw wireframes with `PolygonMode::Line`, never on the web
+    pub fn supports_wireframe(&self) -> bool {
+        self.device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE)
+    }
+
+    /// Remove all pipelines, e.g. on hot reload or after the device was lost
+    pub fn clear_pipelines(&self) {
+        self.pipeline_cache.lock().unwrap().clear();
+    }
+
+    pub async fn new_async<W>(window: Arc<W>, width: u32, height: u32) -> (Self, Window)
+    where
+        W: PlatformWindow + wgpu::WindowHandle,
+    {
+        let backends = Self::backends();
 
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends,
             ..Default::default()
         });
 
-        let (window, surface) = unsafe { create_static_surface_with_window(window, &instance) };
-        // let surface = instance.create_surface(window).unwrap();
+        let surface = instance.create_surface(window.clone()).unwrap();
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
@@ -503,32 +1137,7 @@ impl WGPU {
             .await
             .expect("Failed to request adapter!");
 
-        let (device, queue) = {
-            log::info!("WGPU Adapter Info: {:#?}", adapter.get_info());
-            log::info!("WGPU Adapter Features: {:#?}", adapter.features());
-            adapter
-                .request_device(&wgpu::DeviceDescriptor {
-                    label: Some("WGPU Device"),
-                    memory_hints: wgpu::MemoryHints::default(),
-                    trace: wgpu::Trace::Off,
-                    experimental_features: wgpu::ExperimentalFeatures::disabled(),
-
-                    #[cfg(not(target_arch = "wasm32"))]
-                    required_features: wgpu::Features::POLYGON_MODE_LINE,
-                    #[cfg(target_arch = "wasm32")]
-                    required_features: wgpu::Features::default(),
-
-                    #[cfg(not(target_arch = "wasm32"))]
-                    required_limits: wgpu::Limits::default().using_resolution(adapter.limits()),
-                    #[cfg(all(target_arch = "wasm32", feature = "webgpu"))]
-                    required_limits: wgpu::Limits::default().using_resolution(adapter.limits()),
-                    #[cfg(all(target_arch = "wasm32", feature = "webgl"))]
-                    required_limits: wgpu::Limits::downlevel_webgl2_defaults()
-                        .using_resolution(adapter.limits()),
-                })
-                .await
-                .expect("Failed to request a device!")
-        };
+        let (device, queue) = Self::request_device(&adapter).await;
 
         let surface_capabilities = surface.get_capabilities(&adapter);
 
@@ -559,11 +1168,14 @@ impl WGPU {
 
         surface.configure(&device, &surface_config);
 
-        let window = Window::from_surface(window.into(), surface, surface_config);
+        let window = Window::from_surface(window, Some(surface), surface_config);
 
         (
             Self {
                 pipeline_cache: Mutex::new(ResourceCache::new()),
+                bind_groups: Mutex::new(BindGroupCache::new()),
+                stencil: Mutex::new(None),
+                resource_pool: Mutex::new(ResourcePool::new()),
                 device,
                 queue,
                 instance,
@@ -575,48 +1187,169 @@ impl WGPU {
             window,
         )
     }
-}
-
-pub struct PipelineBuilder<'a> {
-    pub label: Option<&'a str>,
-    pub shader_source: &'a str,
-    pub vertex_entry: &'a str,
-    pub fragment_entry: &'a str,
-    // pub vertex_buffers: &'a [wgpu::VertexBufferLayout<'a>],
-    pub vertex_buffers: &'a [&'a VertexDesc],
-    pub bind_group_layouts: &'a [&'a wgpu::BindGroupLayout],
-    pub surface_format: wgpu::TextureFormat,
-    pub blend_state: Option<wgpu::BlendState>,
-    pub primitive_topology: wgpu::PrimitiveTopology,
-    pub cull_mode: Option<wgpu::Face>,
-    pub depth_format: Option<wgpu::TextureFormat>,
-    pub sample_count: u32,
-}
 
-impl<'a> PipelineBuilder<'a> {
-    pub fn new(shader_source: &'a str, surface_format: wgpu::TextureFormat) -> Self {
-        Self {
-            label: None,
-            shader_source,
-            vertex_entry: "vs_main",
-            fragment_entry: "fs_main",
-            vertex_buffers: &[],
-            bind_group_layouts: &[],
-            surface_format,
-            blend_state: Some(wgpu::BlendState::REPLACE),
-            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
-            cull_mode: None,
-            depth_format: None,
-            sample_count: 1,
+    fn backends() -> wgpu::Backends {
+        if cfg!(target_os = "linux") {
+            wgpu::Backends::PRIMARY
+        } else if cfg!(target_os = "macos") {
+            wgpu::Backends::METAL
+        } else if cfg!(target_os = "windows") {
+            wgpu::Backends::PRIMARY
+        } else if cfg!(target_arch = "wasm32") {
+            wgpu::Backends::GL | wgpu::Backends::BROWSER_WEBGPU
+        } else {
+            wgpu::Backends::all()
         }
     }
 
-    pub fn label(mut self, label: &'a str) -> Self {
-        self.label = Some(label);
-        self
+    async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+        log::info!("WGPU Adapter Info: {:#?}", adapter.get_info());
+        log::info!("WGPU Adapter Features: {:#?}", adapter.features());
+        adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("WGPU Device"),
+                memory_hints: wgpu::MemoryHints::default(),
+                trace: wgpu::Trace::Off,
+                experimental_features: wgpu::ExperimentalFeatures::disabled(),
+
+                #[cfg(not(target_arch = "wasm32"))]
+                required_features: adapter.features()
+                    & (wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::PUSH_CONSTANTS),
+                #[cfg(target_arch = "wasm32")]
+                required_features: wgpu::Features::default(),
+
+                #[cfg(not(target_arch = "wasm32"))]
+                required_limits: wgpu::Limits {
+                    max_push_constant_size: adapter.limits().max_push_constant_size.min(128),
+                    ..wgpu::Limits::default().using_resolution(adapter.limits())
+                },
+                #[cfg(all(target_arch = "wasm32", feature = "webgpu"))]
+                required_limits: wgpu::Limits::default().using_resolution(adapter.limits()),
+                #[cfg(all(target_arch = "wasm32", feature = "webgl"))]
+                required_limits: wgpu::Limits::downlevel_webgl2_defaults()
+                    .using_resolution(adapter.limits()),
+            })
+            .await
+            .expect("Failed to request a device!")
     }
 
-    pub fn vertex_entry(mut self, entry: &'a str) -> Self {
+    /// creates the gpu state without a window, for rendering the ui offscreen
+    pub async fn new_headless_async() -> Self {
+        let backends = Self::backends();
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
+        });
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await
+            .expect("Failed to request adapter!");
+
+        let (device, queue) = Self::request_device(&adapter).await;
+
+        Self {
+            pipeline_cache: Mutex::new(ResourceCache::new()),
+            bind_groups: Mutex::new(BindGroupCache::new()),
+            stencil: Mutex::new(None),
+            resource_pool: Mutex::new(ResourcePool::new()),
+            device,
+            queue,
+            instance,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            backends,
+            present_mode: wgpu::PresentMode::Fifo,
+            surface_format: wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+
+    /// wraps a device created by another renderer, e.g. a game engine, so the ui draws with
+    /// its graphics context instead of creating its own
+    ///
+    /// `surface_format` is the format of the targets the ui renders into
+    pub fn from_device(
+        instance: wgpu::Instance,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        surface_format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            pipeline_cache: Mutex::new(ResourceCache::new()),
+            bind_groups: Mutex::new(BindGroupCache::new()),
+            stencil: Mutex::new(None),
+            resource_pool: Mutex::new(ResourcePool::new()),
+            device,
+            queue,
+            instance,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            backends: wgpu::Backends::all(),
+            present_mode: wgpu::PresentMode::Fifo,
+            surface_format,
+        }
+    }
+
+    /// blocking version of [`WGPU::new_headless_async`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_headless() -> Self {
+        core::futures::wait_for(Self::new_headless_async())
+    }
+}
+
+pub struct PipelineBuilder<'a> {
+    pub label: Option<&'a str>,
+    pub shader_source: &'a str,
+    pub vertex_entry: &'a str,
+    pub fragment_entry: &'a str,
+    // pub vertex_buffers: &'a [wgpu::VertexBufferLayout<'a>],
+    pub vertex_buffers: &'a [&'a VertexDesc],
+    pub bind_group_layouts: &'a [&'a wgpu::BindGroupLayout],
+    /// entries of the `bind_group_layouts`, only used to validate the shader
+    pub bind_group_entries: &'a [&'a [wgpu::BindGroupLayoutEntry]],
+    pub push_constant_ranges: &'a [wgpu::PushConstantRange],
+    pub surface_format: wgpu::TextureFormat,
+    pub blend_state: Option<wgpu::BlendState>,
+    pub primitive_topology: wgpu::PrimitiveTopology,
+    pub cull_mode: Option<wgpu::Face>,
+    pub polygon_mode: wgpu::PolygonMode,
+    pub depth_format: Option<wgpu::TextureFormat>,
+    pub stencil: Option<(wgpu::TextureFormat, wgpu::StencilState)>,
+    pub color_writes: wgpu::ColorWrites,
+    pub sample_count: u32,
+}
+
+impl<'a> PipelineBuilder<'a> {
+    pub fn new(shader_source: &'a str, surface_format: wgpu::TextureFormat) -> Self {
+        Self {
+            label: None,
+            shader_source,
+            vertex_entry: "vs_main",
+            fragment_entry: "fs_main",
+            vertex_buffers: &[],
+            bind_group_layouts: &[],
+            bind_group_entries: &[],
+            push_constant_ranges: &[],
+            surface_format,
+            blend_state: Some(wgpu::BlendState::REPLACE),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            depth_format: None,
+            stencil: None,
+            color_writes: wgpu::ColorWrites::ALL,
+            sample_count: 1,
+        }
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn vertex_entry(mut self, entry: &'a str) -> Self {
         self.vertex_entry = entry;
         self
     }
@@ -636,6 +1369,19 @@ impl<'a> PipelineBuilder<'a> {
         self
     }
 
+    /// the entries the `bind_groups` were created with, lets [`Self::validate`] check the
+    /// bindings of the shader and not only the group count
+    pub fn bind_group_entries(mut self, entries: &'a [&'a [wgpu::BindGroupLayoutEntry]]) -> Self {
+        self.bind_group_entries = entries;
+        self
+    }
+
+    /// needs [`wgpu::Features::PUSH_CONSTANTS`], see [`DrawConstants`] for a fallback
+    pub fn push_constants(mut self, ranges: &'a [wgpu::PushConstantRange]) -> Self {
+        self.push_constant_ranges = ranges;
+        self
+    }
+
     pub fn blend_state(mut self, blend: Option<wgpu::BlendState>) -> Self {
         self.blend_state = blend;
         self
@@ -651,6 +1397,12 @@ impl<'a> PipelineBuilder<'a> {
         self
     }
 
+    /// `Line` and `Point` need the matching [`wgpu::Features`]
+    pub fn polygon_mode(mut self, mode: wgpu::PolygonMode) -> Self {
+        self.polygon_mode = mode;
+        self
+    }
+
     pub fn depth(mut self, format: wgpu::TextureFormat) -> Self {
         self.depth_format = Some(format);
         self
@@ -661,27 +1413,20 @@ impl<'a> PipelineBuilder<'a> {
         self
     }
 
-    pub fn build(self, device: &wgpu::Device) -> wgpu::RenderPipeline {
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: self.label,
-            source: wgpu::ShaderSource::Wgsl(self.shader_source.into()),
-        });
-
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: self.label,
-            bind_group_layouts: self.bind_group_layouts,
-            push_constant_ranges: &[],
-        });
+    /// tests and writes a stencil attachment of `format`, ignored if a depth format is set
+    pub fn stencil(mut self, format: wgpu::TextureFormat, state: wgpu::StencilState) -> Self {
+        self.stencil = Some((format, state));
+        self
+    }
 
-        let depth_stencil = self.depth_format.map(|format| wgpu::DepthStencilState {
-            format,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-        });
+    /// the color channels written, e.g. none for passes that only write the stencil
+    pub fn color_writes(mut self, writes: wgpu::ColorWrites) -> Self {
+        self.color_writes = writes;
+        self
+    }
 
-        let mut buffer_layouts = Vec::new();
+    /// the vertex attributes of every buffer, numbered by their location in the shader
+    fn vertex_attributes(&self) -> Vec<Vec<wgpu::VertexAttribute>> {
         let mut location_offset = 0;
 
         let mut vertices_attribs: Vec<_> = self
@@ -704,6 +1449,74 @@ impl<'a> PipelineBuilder<'a> {
             location_offset += vertex_attribs.len() as u32;
         }
 
+        vertices_attribs
+    }
+
+    /// checks the vertex inputs and resource bindings of the shader against the vertex
+    /// buffers and bind groups of the pipeline
+    pub fn validate(&self) -> Result<(), Vec<ShaderMismatch>> {
+        let attributes: Vec<_> = self.vertex_attributes().into_iter().flatten().collect();
+        let members: Vec<_> = self
+            .vertex_buffers
+            .iter()
+            .filter(|desc| !desc.uniform)
+            .flat_map(|desc| desc.members.iter().copied())
+            .collect();
+
+        validate_shader(
+            self.shader_source,
+            [self.vertex_entry, self.fragment_entry],
+            &attributes,
+            &members,
+            self.bind_group_layouts.len(),
+            self.bind_group_entries,
+        )
+    }
+
+    /// panics with a description of every mismatch if the shader does not fit the pipeline,
+    /// see [`Self::validate`]
+    pub fn build(self, device: &wgpu::Device) -> wgpu::RenderPipeline {
+        if let Err(errors) = self.validate() {
+            let errors: Vec<_> = errors.iter().map(|e| format!("  {e}")).collect();
+            panic!(
+                "pipeline '{}' does not match its shader:\n{}",
+                self.label.unwrap_or("unlabeled"),
+                errors.join("\n")
+            );
+        }
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: self.label,
+            source: wgpu::ShaderSource::Wgsl(self.shader_source.into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: self.label,
+            bind_group_layouts: self.bind_group_layouts,
+            push_constant_ranges: self.push_constant_ranges,
+        });
+
+        let vertices_attribs = self.vertex_attributes();
+
+        let depth_stencil = self.depth_format.map(|format| wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        });
+        let depth_stencil = depth_stencil.or(self.stencil.map(|(format, stencil)| {
+            wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil,
+                bias: wgpu::DepthBiasState::default(),
+            }
+        }));
+
+        let mut buffer_layouts = Vec::new();
+
         for (desc, fixed_attribs) in self.vertex_buffers.iter().zip(vertices_attribs.iter()) {
             let layout = wgpu::VertexBufferLayout {
                 array_stride: desc.byte_size as wgpu::BufferAddress,
@@ -732,7 +1545,7 @@ impl<'a> PipelineBuilder<'a> {
                 targets: &[Some(wgpu::ColorTargetState {
                     format: self.surface_format,
                     blend: self.blend_state,
-                    write_mask: wgpu::ColorWrites::ALL,
+                    write_mask: self.color_writes,
                 })],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             }),
@@ -741,7 +1554,7 @@ impl<'a> PipelineBuilder<'a> {
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: self.cull_mode,
-                polygon_mode: wgpu::PolygonMode::Fill,
+                polygon_mode: self.polygon_mode,
                 unclipped_depth: false,
                 conservative: false,
             },
@@ -757,6 +1570,281 @@ impl<'a> PipelineBuilder<'a> {
     }
 }
 
+/// a difference between a shader and the pipeline it is built into, see
+/// [`PipelineBuilder::validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShaderMismatch {
+    /// the shader does not compile, contains the formatted naga error
+    Invalid(String),
+    MissingEntryPoint(String),
+    /// the shader reads a location that no vertex buffer provides
+    MissingAttribute { location: u32, name: String },
+    /// the attribute at `location` has a different scalar type or fewer components than the
+    /// shader input
+    AttributeFormat {
+        location: u32,
+        name: String,
+        shader: String,
+        format: wgpu::VertexFormat,
+    },
+    /// the shader uses a group past the bind group layouts of the pipeline
+    MissingBindGroup { group: u32, name: String },
+    MissingBinding { group: u32, binding: u32, name: String },
+    BindingType {
+        group: u32,
+        binding: u32,
+        name: String,
+        layout: wgpu::BindingType,
+    },
+}
+
+impl fmt::Display for ShaderMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "invalid shader: {e}"),
+            Self::MissingEntryPoint(name) => write!(f, "no entry point named '{name}'"),
+            Self::MissingAttribute { location, name } => {
+                write!(f, "'{name}' at @location({location}) is not provided by a vertex buffer")
+            }
+            Self::AttributeFormat {
+                location,
+                name,
+                shader,
+                format,
+            } => write!(
+                f,
+                "'{name}' at @location({location}) is {shader} in the shader, \
+                but the vertex attribute is {format:?}"
+            ),
+            Self::MissingBindGroup { group, name } => {
+                write!(f, "'{name}' uses @group({group}), but the pipeline has no such group")
+            }
+            Self::MissingBinding {
+                group,
+                binding,
+                name,
+            } => write!(
+                f,
+                "'{name}' at @group({group}) @binding({binding}) is not in the bind group layout"
+            ),
+            Self::BindingType {
+                group,
+                binding,
+                name,
+                layout,
+            } => write!(
+                f,
+                "'{name}' at @group({group}) @binding({binding}) does not match the layout \
+                entry {layout:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShaderMismatch {}
+
+/// reflects the wgsl `src` with naga and compares the inputs of the vertex entry point with
+/// `attributes` and the resources used by the `entries` with the bind group layouts
+///
+/// `members` names the attributes in the errors. the bindings of a group are only checked
+/// if its entries are given
+pub fn validate_shader(
+    src: &str,
+    entries: [&str; 2],
+    attributes: &[wgpu::VertexAttribute],
+    members: &[&str],
+    n_bind_groups: usize,
+    bind_group_entries: &[&[wgpu::BindGroupLayoutEntry]],
+) -> Result<(), Vec<ShaderMismatch>> {
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    let module = naga::front::wgsl::parse_str(src)
+        .map_err(|e| vec![ShaderMismatch::Invalid(e.emit_to_string(src))])?;
+    let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|e| vec![ShaderMismatch::Invalid(e.emit_to_string(src))])?;
+
+    let mut errors = Vec::new();
+    let [vertex_entry, fragment_entry] = entries;
+    let find_entry = |name: &str, stage| {
+        module
+            .entry_points
+            .iter()
+            .position(|ep| ep.name == name && ep.stage == stage)
+    };
+
+    let mut used = Vec::new();
+    match find_entry(vertex_entry, naga::ShaderStage::Vertex) {
+        Some(i) => {
+            used.push(i);
+            let inputs = vertex_inputs(&module, &module.entry_points[i].function);
+            for (location, name, ty) in inputs {
+                let attrib = attributes.iter().position(|a| a.shader_location == location);
+                let Some(attrib) = attrib else {
+                    errors.push(ShaderMismatch::MissingAttribute { location, name });
+                    continue;
+                };
+                let format = attributes[attrib].format;
+                let name = members.get(attrib).map_or(name, |m| m.to_string());
+                let (kind, n) = vertex_format_shape(format);
+                let matches = match &module.types[ty].inner {
+                    naga::TypeInner::Scalar(s) => s.kind == kind,
+                    naga::TypeInner::Vector { size, scalar } => {
+                        scalar.kind == kind && *size as u32 <= n
+                    }
+                    _ => false,
+                };
+                if !matches {
+                    errors.push(ShaderMismatch::AttributeFormat {
+                        location,
+                        name,
+                        shader: wgsl_type_name(&module, ty),
+                        format,
+                    });
+                }
+            }
+        }
+        None => errors.push(ShaderMismatch::MissingEntryPoint(vertex_entry.into())),
+    }
+    match find_entry(fragment_entry, naga::ShaderStage::Fragment) {
+        Some(i) => used.push(i),
+        None => errors.push(ShaderMismatch::MissingEntryPoint(fragment_entry.into())),
+    }
+
+    for (handle, var) in module.global_variables.iter() {
+        let Some(naga::ResourceBinding { group, binding }) = var.binding else {
+            continue;
+        };
+        if used.iter().all(|&i| info.get_entry_point(i)[handle].is_empty()) {
+            continue;
+        }
+        let name = var.name.clone().unwrap_or_default();
+
+        if group as usize >= n_bind_groups {
+            errors.push(ShaderMismatch::MissingBindGroup { group, name });
+            continue;
+        }
+        let Some(layout) = bind_group_entries.get(group as usize) else {
+            continue;
+        };
+        let Some(entry) = layout.iter().find(|e| e.binding == binding) else {
+            errors.push(ShaderMismatch::MissingBinding {
+                group,
+                binding,
+                name,
+            });
+            continue;
+        };
+        if !binding_matches(&module, var, &entry.ty) {
+            errors.push(ShaderMismatch::BindingType {
+                group,
+                binding,
+                name,
+                layout: entry.ty,
+            });
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// location, name and type of every input of a vertex entry point, including struct members
+fn vertex_inputs(
+    module: &naga::Module,
+    function: &naga::Function,
+) -> Vec<(u32, String, naga::Handle<naga::Type>)> {
+    let mut inputs = Vec::new();
+    for arg in &function.arguments {
+        let name = arg.name.clone().unwrap_or_default();
+        match (&arg.binding, &module.types[arg.ty].inner) {
+            (Some(naga::Binding::Location { location, .. }), _) => {
+                inputs.push((*location, name, arg.ty));
+            }
+            (None, naga::TypeInner::Struct { members, .. }) => {
+                for m in members {
+                    if let Some(naga::Binding::Location { location, .. }) = m.binding {
+                        let name = format!("{name}.{}", m.name.as_deref().unwrap_or_default());
+                        inputs.push((location, name, m.ty));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    inputs
+}
+
+/// scalar kind and component count the shader sees for a vertex format
+fn vertex_format_shape(format: wgpu::VertexFormat) -> (naga::ScalarKind, u32) {
+    use naga::ScalarKind as K;
+    use wgpu::VertexFormat as F;
+
+    let kind = match format {
+        F::Uint8 | F::Uint8x2 | F::Uint8x4 | F::Uint16 | F::Uint16x2 | F::Uint16x4 => K::Uint,
+        F::Uint32 | F::Uint32x2 | F::Uint32x3 | F::Uint32x4 => K::Uint,
+        F::Sint8 | F::Sint8x2 | F::Sint8x4 | F::Sint16 | F::Sint16x2 | F::Sint16x4 => K::Sint,
+        F::Sint32 | F::Sint32x2 | F::Sint32x3 | F::Sint32x4 => K::Sint,
+        _ => K::Float,
+    };
+    let components = match format {
+        F::Uint8x2 | F::Sint8x2 | F::Unorm8x2 | F::Snorm8x2 | F::Uint16x2 | F::Sint16x2 => 2,
+        F::Unorm16x2 | F::Snorm16x2 | F::Float16x2 | F::Float32x2 | F::Uint32x2 => 2,
+        F::Sint32x2 | F::Float64x2 => 2,
+        F::Float32x3 | F::Uint32x3 | F::Sint32x3 | F::Float64x3 => 3,
+        F::Uint8x4 | F::Sint8x4 | F::Unorm8x4 | F::Snorm8x4 | F::Uint16x4 | F::Sint16x4 => 4,
+        F::Unorm16x4 | F::Snorm16x4 | F::Float16x4 | F::Float32x4 | F::Uint32x4 => 4,
+        F::Sint32x4 | F::Float64x4 | F::Unorm10_10_10_2 | F::Unorm8x4Bgra => 4,
+        _ => 1,
+    };
+    (kind, components)
+}
+
+fn wgsl_type_name(module: &naga::Module, ty: naga::Handle<naga::Type>) -> String {
+    let scalar_name = |s: naga::Scalar| match s.kind {
+        naga::ScalarKind::Uint => format!("u{}", s.width * 8),
+        naga::ScalarKind::Sint => format!("i{}", s.width * 8),
+        naga::ScalarKind::Float => format!("f{}", s.width * 8),
+        kind => format!("{kind:?}").to_lowercase(),
+    };
+    match &module.types[ty].inner {
+        naga::TypeInner::Scalar(s) => scalar_name(*s),
+        naga::TypeInner::Vector { size, scalar } => {
+            format!("vec{}<{}>", *size as u32, scalar_name(*scalar))
+        }
+        inner => format!("{inner:?}"),
+    }
+}
+
+/// whether the resource of a global variable can be bound with a layout entry of type `ty`
+fn binding_matches(
+    module: &naga::Module,
+    var: &naga::GlobalVariable,
+    ty: &wgpu::BindingType,
+) -> bool {
+    use naga::{AddressSpace as S, TypeInner as T};
+    use wgpu::{BindingType as B, BufferBindingType as BB};
+
+    let mut inner = &module.types[var.ty].inner;
+    if let T::BindingArray { base, .. } = inner {
+        inner = &module.types[*base].inner;
+    }
+
+    match (var.space, inner, ty) {
+        (S::Uniform, _, B::Buffer { ty, .. }) => *ty == BB::Uniform,
+        (S::Storage { .. }, _, B::Buffer { ty, .. }) => matches!(ty, BB::Storage { .. }),
+        (S::Handle, T::Sampler { .. }, B::Sampler(_)) => true,
+        (S::Handle, T::Image { class, .. }, B::StorageTexture { .. }) => {
+            matches!(class, naga::ImageClass::Storage { .. })
+        }
+        (S::Handle, T::Image { class, .. }, B::Texture { .. }) => {
+            !matches!(class, naga::ImageClass::Storage { .. })
+        }
+        (S::Handle, T::Image { .. }, B::ExternalTexture) => true,
+        (S::Handle, T::AccelerationStructure { .. }, B::AccelerationStructure { .. }) => true,
+        _ => false,
+    }
+}
+
 #[derive(Debug)]
 pub struct PipelineRequirement {
     pub name: String,
@@ -825,8 +1913,25 @@ impl PipelineRequirement {
     }
 }
 
+/// key of a pipeline in the [`WGPU`] pipeline registry
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct UUID(pub u64);
+pub struct PipelineKey(pub u64);
+
+impl PipelineKey {
+    /// key from any hashable value, e.g. a user defined enum of pipelines
+    pub fn of<T: hash::Hash + ?Sized>(key: &T) -> Self {
+        use std::hash::Hasher;
+        let mut hasher = ahash::AHasher::default();
+        key.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+impl From<&str> for PipelineKey {
+    fn from(name: &str) -> Self {
+        Self::of(name)
+    }
+}
 
 pub type ShaderID = &'static str;
 
@@ -863,14 +1968,11 @@ pub trait ShaderHandle {
     const RENDER_PIPELINE_ID: ShaderID;
     fn build_pipeline<const N: usize>(&self, config: ShaderBuildConfig<'_, N>, wgpu: &WGPU) -> wgpu::RenderPipeline;
 
-    fn pipeline_generic_id() -> UUID {
-        use std::hash::{Hash, Hasher};
-        let mut hasher = ahash::AHasher::default();
-        Self::RENDER_PIPELINE_ID.hash(&mut hasher);
-        UUID(hasher.finish())
+    fn pipeline_generic_id() -> PipelineKey {
+        PipelineKey::of(Self::RENDER_PIPELINE_ID)
     }
 
-    fn pipeline_vertex_id<const N: usize>(config: ShaderBuildConfig<'_, N>) -> UUID {
+    fn pipeline_vertex_id<const N: usize>(config: ShaderBuildConfig<'_, N>) -> PipelineKey {
         use std::hash::{Hash, Hasher};
         let mut hasher = ahash::AHasher::default();
         Self::RENDER_PIPELINE_ID.hash(&mut hasher);
@@ -878,7 +1980,7 @@ pub trait ShaderHandle {
             d.attributes.hash(&mut hasher);
             d.members.hash(&mut hasher);
         }
-        UUID(hasher.finish())
+        PipelineKey(hasher.finish())
     }
 
     fn should_rebuild(&self) -> bool {
@@ -897,6 +1999,11 @@ pub trait ShaderHandle {
         );
     }
 
+    /// drops the pipeline built for `config`, it is rebuilt on the next use
+    fn invalidate<const N: usize>(&self, config: ShaderBuildConfig<'_, N>, wgpu: &WGPU) {
+        wgpu.remove_pipeline(Self::pipeline_vertex_id(config));
+    }
+
     fn get_pipeline<const N: usize>(&self, config: ShaderBuildConfig<'_, N>, wgpu: &WGPU) -> Arc<wgpu::RenderPipeline> {
         if self.should_rebuild() {
             self.try_rebuild(config, wgpu);
@@ -925,6 +2032,11 @@ pub trait RenderPassHandle {
 
     fn draw<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>, wgpu: &WGPU);
 
+    /// attach a [`STENCIL_FORMAT`] buffer, cleared to 0 at the start of every pass
+    fn uses_stencil(&self) -> bool {
+        false
+    }
+
     fn n_render_passes(&self) -> u32 {
         1
     }
@@ -1037,6 +2149,8 @@ pub struct RenderTarget<'a> {
     pub resolve_view: Option<wgpu::TextureView>,
     pub encoder: EncoderHandle<'a>,
     pub wgpu: &'a WGPU,
+    /// drawn by [`Self::clear`]
+    pub background: Background,
 }
 
 impl<'a> Drop for RenderTarget<'a> {
@@ -1048,6 +2162,18 @@ impl<'a> Drop for RenderTarget<'a> {
 }
 
 impl<'a> RenderTarget<'a> {
+    /// render into a texture instead of a window surface, e.g. one created with
+    /// [`Texture::create_render_texture`]
+    pub fn from_texture(texture: &Texture, wgpu: &'a WGPU) -> Self {
+        Self {
+            target_view: texture.view().clone(),
+            resolve_view: None,
+            encoder: EncoderHandle::new(&wgpu.device, &wgpu.queue, "texture_encoder"),
+            wgpu,
+            background: Background::default(),
+        }
+    }
+
     pub fn target_size(&self) -> Vec2 {
         let size = self.target_view.texture().size();
         Vec2::new(size.width as f32, size.height as f32)
@@ -1060,7 +2186,11 @@ impl<'a> RenderTarget<'a> {
 
     pub fn render<RH: RenderPassHandle>(&mut self, rh: &RH) {
         let n_passes = rh.n_render_passes();
+        let stencil = rh
+            .uses_stencil()
+            .then(|| self.wgpu.stencil_view(self.target_view.texture()));
 
+        crate::profile_scope!("render_pass", RH::LABEL);
         if n_passes == 1 {
             log::trace!("[RENDERPASS] {}", RH::LABEL);
 
@@ -1075,7 +2205,7 @@ impl<'a> RenderTarget<'a> {
                             store: rh.store_op(),
                         },
                     })],
-                    depth_stencil_attachment: None,
+                    depth_stencil_attachment: stencil_attachment(stencil.as_ref()),
                     label: Some("main render pass"),
                     timestamp_writes: None,
                     occlusion_query_set: None,
@@ -1100,7 +2230,7 @@ impl<'a> RenderTarget<'a> {
                                 store: rh.store_op(),
                             },
                         })],
-                        depth_stencil_attachment: None,
+                        depth_stencil_attachment: stencil_attachment(stencil.as_ref()),
                         label: Some("main render pass"),
                         timestamp_writes: None,
                         occlusion_query_set: None,
@@ -1116,97 +2246,279 @@ impl<'a> RenderTarget<'a> {
     }
 }
 
-pub type WindowId = winit::window::WindowId;
-
-#[derive(Debug)]
-pub struct WindowCore {
-    pub surface: wgpu::Surface<'static>,
-    pub width: u32,
-    pub height: u32,
-    pub current_surface_texture: Option<wgpu::SurfaceTexture>,
-
-    // keep as last field, so its dropped after all the others
-    pub raw: Box<winit::window::Window>,
+fn stencil_attachment(
+    view: Option<&wgpu::TextureView>,
+) -> Option<wgpu::RenderPassDepthStencilAttachment<'_>> {
+    view.map(|view| wgpu::RenderPassDepthStencilAttachment {
+        view,
+        depth_ops: None,
+        stencil_ops: Some(wgpu::Operations {
+            load: wgpu::LoadOp::Clear(0),
+            store: wgpu::StoreOp::Discard,
+        }),
+    })
 }
 
-#[derive(Debug)]
-pub struct Window {
-    pub id: WindowId,
-    pub surface_present_mode: wgpu::PresentMode,
-    pub surface_alpha_mode: wgpu::CompositeAlphaMode,
-    pub surface_usage: wgpu::TextureUsages,
-    pub surface_format: wgpu::TextureFormat,
+impl RenderTarget<'_> {
+    /// submits the recorded passes and reads back `rect` of the target, see
+    /// [`WGPU::capture_region`]
+    pub fn capture_region(&self, rect: Rect) -> Option<CapturedImage> {
+        self.encoder.submit_and_continue();
+        self.wgpu.capture_region(self.target_view.texture(), rect)
+    }
+}
 
-    pub surface: wgpu::Surface<'static>,
+/// rgba8 pixels read back from a texture
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedImage {
     pub width: u32,
     pub height: u32,
-    pub current_surface_texture: Option<wgpu::SurfaceTexture>,
-
-    // keep as last field, so its dropped after all the others
-    pub raw: Box<winit::window::Window>,
-    // pub titlebar_height: Option<f32>,
-
-    // we do this so that we can have cheap copies of a window that can be mutably borrowed
-    // core must remain equal for all window copies
-    // pub core: Arc<RefCell<WindowCore>>,
-}
-
-/// create a surface with a static lifetime of the given window
-///
-/// the caller must ensure that the window outlives the surface
-unsafe fn create_static_surface_with_window(
-    window: Box<winit::window::Window>,
-    instance: &wgpu::Instance,
-) -> (Box<winit::window::Window>, wgpu::Surface<'static>) {
-    let raw_ptr = Box::into_raw(window);
-
-    let surface: wgpu::Surface<'static> = unsafe {
-        let static_window_ref: &'static winit::window::Window = &*raw_ptr;
-        instance.create_surface(&*static_window_ref).unwrap()
-    };
-
-    let window = unsafe { Box::from_raw(raw_ptr) };
-    (window, surface)
+    /// rows are tightly packed
+    pub rgba: Vec<u8>,
 }
 
-impl Window {
-    const DESIRED_MAXIMUM_FRAME_LATENCY: u32 = 0;
-
-    pub fn window_rect(&self) -> Rect {
-        Rect::from_min_size(Vec2::ZERO, self.window_size())
+impl CapturedImage {
+    /// number of pixels with a channel that differs by more than `tolerance`, e.g. to compare a
+    /// frame with a reference image. `None` if the sizes differ
+    pub fn count_differences(&self, other: &Self, tolerance: u8) -> Option<usize> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+        let pixels = self.rgba.chunks_exact(4).zip(other.rgba.chunks_exact(4));
+        Some(
+            pixels
+                .filter(|(a, b)| a.iter().zip(*b).any(|(a, b)| a.abs_diff(*b) > tolerance))
+                .count(),
+        )
     }
 
-    pub fn set_cursor_icon(&self, icon: mouse::CursorIcon) {
-        self.raw.set_cursor(icon);
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> image::ImageResult<()> {
+        image::save_buffer(
+            path,
+            &self.rgba,
+            self.width,
+            self.height,
+            image::ExtendedColorType::Rgba8,
+        )
     }
+}
 
-    pub fn start_drag_resize_window(&self, dir: core::Dir) {
-        if self.is_maximized() {
-            return;
+impl WGPU {
+    /// copies `rect` of `texture` to the cpu, blocks until the gpu is done
+    ///
+    /// the texture needs `COPY_SRC` usage and an 8 bit rgba or bgra format, the rect is
+    /// clamped to the texture
+    pub fn capture_region(&self, texture: &wgpu::Texture, rect: Rect) -> Option<CapturedImage> {
+        let is_bgra = readback_is_bgra(texture)?;
+
+        let size = texture.size();
+        let min = rect.min.max(Vec2::ZERO).floor().as_uvec2();
+        let max = rect
+            .max
+            .ceil()
+            .as_uvec2()
+            .min(UVec2::new(size.width, size.height));
+        if min.x >= max.x || min.y >= max.y {
+            return None;
         }
+        let (width, height) = (max.x - min.x, max.y - min.y);
+
+        let row_bytes = width * 4;
+        let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture_buffer"),
+            size: (padded_row_bytes * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
 
-        let res = self.raw.drag_resize_window(dir.as_winit_resize());
+        self.copy_texture_to_buffer(texture, min, UVec2::new(width, height), &buffer);
 
-        if let Err(e) = res {
-            log::warn!("{e}");
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |res| {
+            let _ = tx.send(res);
+        });
+        if let Err(e) = self.device.poll(wgpu::PollType::wait_indefinitely()) {
+            log::warn!("capture_region: {e}");
+            return None;
+        }
+        match rx.recv() {
+            Ok(Ok(())) => (),
+            Ok(Err(e)) => {
+                log::warn!("capture_region: {e}");
+                return None;
+            }
+            Err(_) => {
+                log::warn!("capture_region: buffer was never mapped");
+                return None;
+            }
         }
+
+        let rgba = unpack_captured_rows(
+            &slice.get_mapped_range(),
+            row_bytes as usize,
+            padded_row_bytes as usize,
+            is_bgra,
+        );
+        buffer.unmap();
+
+        Some(CapturedImage {
+            width,
+            height,
+            rgba,
+        })
     }
+}
 
-    pub fn start_drag_window(&self) {
-        let res = self.raw.drag_window();
-        if let Err(e) = res {
-            log::warn!("{e}");
+impl WGPU {
+    /// copies a region of `texture` into `buffer` with rows padded to
+    /// [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`] and submits the copy
+    fn copy_texture_to_buffer(
+        &self,
+        texture: &wgpu::Texture,
+        min: UVec2,
+        size: UVec2,
+        buffer: &wgpu::Buffer,
+    ) {
+        let padded_row_bytes = (size.x * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("capture_encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: min.x,
+                    y: min.y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(size.y),
+                },
+            },
+            wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+}
+
+/// whether the pixels of `texture` are stored as bgra, `None` if it can not be read back
+fn readback_is_bgra(texture: &wgpu::Texture) -> Option<bool> {
+    use wgpu::TextureFormat as TF;
+
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        log::warn!("capture: texture is missing COPY_SRC usage");
+        return None;
+    }
+    match texture.format() {
+        TF::Rgba8Unorm | TF::Rgba8UnormSrgb => Some(false),
+        TF::Bgra8Unorm | TF::Bgra8UnormSrgb => Some(true),
+        format => {
+            log::warn!("capture: unsupported texture format {format:?}");
+            None
+        }
+    }
+}
+
+/// removes the row padding of a texture copy and converts bgra to rgba
+fn unpack_captured_rows(
+    data: &[u8],
+    row_bytes: usize,
+    padded_row_bytes: usize,
+    is_bgra: bool,
+) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(data.len() / padded_row_bytes.max(1) * row_bytes);
+    for row in data.chunks(padded_row_bytes) {
+        rgba.extend_from_slice(&row[..row_bytes]);
+    }
+    if is_bgra {
+        rgba.chunks_exact_mut(4).for_each(|px| px.swap(0, 2));
+    }
+    rgba
+}
+
+core::id_type!(WindowId);
+
+#[derive(Debug)]
+pub struct Window {
+    pub id: WindowId,
+    pub surface_present_mode: wgpu::PresentMode,
+    pub surface_alpha_mode: wgpu::CompositeAlphaMode,
+    pub surface_usage: wgpu::TextureUsages,
+    pub surface_format: wgpu::TextureFormat,
+
+    /// `None` for headless windows
+    pub surface: Option<wgpu::Surface<'static>>,
+    pub width: u32,
+    pub height: u32,
+    pub current_surface_texture: Option<wgpu::SurfaceTexture>,
+    /// the background of the frames returned by [`Self::prepare_frame`]
+    pub background: Background,
+
+    pub raw: Arc<dyn PlatformWindow>,
+}
+
+impl rwh::HasWindowHandle for Window {
+    fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        self.raw.window_handle()
+    }
+}
+
+impl rwh::HasDisplayHandle for Window {
+    fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
+        self.raw.display_handle()
+    }
+}
+
+impl fmt::Debug for dyn PlatformWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PlatformWindow").field("id", &self.id()).finish()
+    }
+}
+
+impl Window {
+    const DESIRED_MAXIMUM_FRAME_LATENCY: u32 = 0;
+
+    pub fn window_rect(&self) -> Rect {
+        Rect::from_min_size(Vec2::ZERO, self.window_size())
+    }
+
+    pub fn set_cursor_icon(&self, icon: mouse::CursorIcon) {
+        self.raw.set_cursor_icon(icon);
+    }
+
+    pub fn start_drag_resize_window(&self, dir: core::Dir) {
+        if self.is_maximized() {
+            return;
         }
+
+        self.raw.drag_resize_window(dir);
+    }
+
+    pub fn start_drag_window(&self) {
+        self.raw.drag_window();
     }
 
     pub fn is_maximized(&self) -> bool {
-        let w = &self.raw;
-        w.is_maximized()
+        self.raw.is_maximized()
     }
 
     pub fn toggle_maximize(&self) {
-        let w = &self.raw;
-        w.set_maximized(!w.is_maximized());
+        self.raw.set_maximized(!self.raw.is_maximized());
     }
 
     pub fn minimize(&self) {
@@ -1217,6 +2529,10 @@ impl Window {
         self.raw.set_maximized(true)
     }
 
+    pub fn scale_factor(&self) -> f32 {
+        self.raw.scale_factor()
+    }
+
     pub fn surface_config(&self, width: u32, height: u32) -> wgpu::SurfaceConfiguration {
         wgpu::SurfaceConfiguration {
             usage: self.surface_usage,
@@ -1243,30 +2559,26 @@ impl Window {
         self.width = width.max(1);
         self.height = height.max(1);
         let config = self.surface_config(self.width, self.height);
-        self.surface.configure(device, &config);
+        match &self.surface {
+            Some(surface) => surface.configure(device, &config),
+            None => self.raw.request_inner_size(UVec2::new(self.width, self.height)),
+        }
     }
 
     pub fn window_size(&self) -> Vec2 {
-        let size = self.raw.inner_size();
-        Vec2::new(size.width as f32, size.height as f32)
+        self.raw.inner_size().as_vec2()
     }
 
     pub fn window_pos(&self) -> Vec2 {
-        let pos = self.raw.inner_position().unwrap_or_default();
-        Vec2::new(pos.y as f32, pos.y as f32)
+        self.raw.inner_position()
     }
 
     pub fn set_window_size(&mut self, width: u32, height: u32) {
-        use winit::dpi::PhysicalSize;
-        self.raw
-            .request_inner_size(PhysicalSize::new(width, height));
+        self.raw.request_inner_size(UVec2::new(width, height));
     }
 
     pub fn set_window_pos(&mut self, pos: Vec2) {
-        use winit::dpi::PhysicalPosition;
-        let s = self
-            .raw
-            .set_outer_position(PhysicalPosition::new(pos.x as u32, pos.y as u32));
+        self.raw.set_outer_position(pos);
     }
 
     pub fn set_window_decorations(&self, b: bool) {
@@ -1277,9 +2589,78 @@ impl Window {
         self.raw.is_decorated()
     }
 
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        self.raw.available_monitors()
+    }
+
+    /// the monitor the window is mostly on, falls back to the primary monitor
+    pub fn current_monitor(&self) -> Option<MonitorInfo> {
+        self.raw.current_monitor().or_else(|| self.raw.primary_monitor())
+    }
+
+    pub fn center_on_monitor(&mut self) {
+        let Some(monitor) = self.current_monitor() else {
+            return;
+        };
+        let size = self.raw.inner_size().min(monitor.size);
+        self.set_window_pos(monitor.position + (monitor.size - size).as_vec2() * 0.5);
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.raw.is_fullscreen()
+    }
+
+    /// borderless fullscreen on the current monitor
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        self.raw.set_fullscreen(fullscreen)
+    }
+
+    pub fn toggle_fullscreen(&self) {
+        self.raw.set_fullscreen(!self.raw.is_fullscreen())
+    }
+
+    /// `None` restores the default icon
+    pub fn set_icon(&self, icon: Option<&WindowIcon>) {
+        self.raw.set_window_icon(icon)
+    }
+
+    /// flashes the taskbar entry until the window is focused
+    pub fn request_attention(&self, attention: UserAttention) {
+        self.raw.request_user_attention(Some(attention))
+    }
+
+    pub fn cancel_attention_request(&self) {
+        self.raw.request_user_attention(None)
+    }
+
+    /// progress in `0..=1` on the taskbar entry where supported, `None` hides it
+    pub fn set_taskbar_progress(&self, progress: Option<f32>) {
+        self.raw.set_taskbar_progress(progress.map(|p| p.clamp(0.0, 1.0)))
+    }
+
+    /// the current position and size, store it to restore the window with
+    /// [`Window::restore_placement`]
+    pub fn placement(&self) -> WindowPlacement {
+        WindowPlacement {
+            pos: self.raw.outer_position(),
+            size: self.raw.inner_size(),
+            maximized: self.raw.is_maximized(),
+            fullscreen: self.raw.is_fullscreen(),
+        }
+    }
+
+    /// moves and resizes the window to a stored placement, keeping it on a connected monitor
+    pub fn restore_placement(&mut self, placement: &WindowPlacement) {
+        let p = placement.clamp_to_monitors(&self.monitors());
+        self.set_window_size(p.size.x, p.size.y);
+        self.set_window_pos(p.pos);
+        self.raw.set_maximized(p.maximized);
+        self.raw.set_fullscreen(p.fullscreen);
+    }
+
     pub fn from_surface(
-        raw: Box<winit::window::Window>,
-        surface: wgpu::Surface<'static>,
+        raw: Arc<dyn PlatformWindow>,
+        surface: Option<wgpu::Surface<'static>>,
         cfg: wgpu::SurfaceConfiguration,
     ) -> Self {
         let id = raw.id();
@@ -1294,17 +2675,27 @@ impl Window {
             surface_alpha_mode: cfg.alpha_mode,
             surface_usage: cfg.usage,
             surface_format: cfg.format,
+            background: Background::default(),
         }
     }
 
-    pub fn new(raw_window: winit::window::Window, width: u32, height: u32, wgpu: &WGPU) -> Self {
-        // SAFETY: create_static_surface_with_window handles the unsafe lifetime extension
-        // The returned Window struct must ensure Surface is dropped before the window
-        let (raw, surface) =
-            unsafe { create_static_surface_with_window(raw_window.into(), &wgpu.instance) };
+    pub fn set_clear_color(&mut self, col: RGBA) {
+        self.background.clear = Some(col);
+    }
 
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+    }
+
+    fn default_surface_config(width: u32, height: u32, wgpu: &WGPU) -> wgpu::SurfaceConfiguration {
+        // COPY_SRC so frames can be read back with `RenderTarget::capture_region`
+        #[cfg(not(target_arch = "wasm32"))]
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC;
+        #[cfg(target_arch = "wasm32")]
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT;
+
+        wgpu::SurfaceConfiguration {
+            usage,
             format: wgpu.surface_format,
             width,
             height,
@@ -1312,23 +2703,52 @@ impl Window {
             alpha_mode: wgpu.alpha_mode,
             view_formats: vec![],
             desired_maximum_frame_latency: Self::DESIRED_MAXIMUM_FRAME_LATENCY,
-        };
+        }
+    }
+
+    pub fn new<W>(raw_window: Arc<W>, width: u32, height: u32, wgpu: &WGPU) -> Self
+    where
+        W: PlatformWindow + wgpu::WindowHandle,
+    {
+        let surface = wgpu.instance.create_surface(raw_window.clone()).unwrap();
+        Self::with_surface(raw_window, surface, width, height, wgpu)
+    }
 
+    /// uses a surface created outside of this crate, it is reconfigured for the ui
+    pub fn with_surface(
+        raw_window: Arc<dyn PlatformWindow>,
+        surface: wgpu::Surface<'static>,
+        width: u32,
+        height: u32,
+        wgpu: &WGPU,
+    ) -> Self {
+        let surface_config = Self::default_surface_config(width, height, wgpu);
         surface.configure(&wgpu.device, &surface_config);
+        Self::from_surface(raw_window, Some(surface), surface_config)
+    }
+
+    /// a window without a surface, the ui has to be rendered into a texture with
+    /// [`RenderTarget::from_texture`]
+    pub fn headless(width: u32, height: u32, wgpu: &WGPU) -> Self {
+        let raw = Arc::new(HeadlessWindow::new(width, height));
+        let surface_config = Self::default_surface_config(width, height, wgpu);
+        Self::from_surface(raw, None, surface_config)
+    }
 
-        Self::from_surface(raw, surface, surface_config)
+    pub fn is_headless(&self) -> bool {
+        self.surface.is_none()
     }
 
     pub fn window_width(&self) -> u32 {
-        self.raw.inner_size().width
+        self.raw.inner_size().x
     }
     pub fn window_height(&self) -> u32 {
-        self.raw.inner_size().height
+        self.raw.inner_size().y
     }
 
     pub fn reconfigure(&mut self, device: &wgpu::Device) {
         let size = self.raw.inner_size();
-        self.resize(size.width, size.height, device)
+        self.resize(size.x, size.y, device)
     }
 
     /// returns false when unable to accquire the current surface texture
@@ -1339,14 +2759,14 @@ impl Window {
             panic!();
         }
 
-        let mut reconfigure = false;
+        let Some(surface) = &self.surface else {
+            log::warn!("prepare_frame called on a headless window");
+            return None;
+        };
 
-        let surface_texture = match self.surface.get_current_texture() {
+        let surface_texture = match surface.get_current_texture() {
             Ok(st) => Some(st),
-            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                reconfigure = true;
-                None
-            }
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => None,
             Err(e) => {
                 log::error!("surface_texture: {e}");
                 panic!();
@@ -1357,15 +2777,13 @@ impl Window {
             self.reconfigure(&wgpu.device);
             return None;
         };
-        // if reconfigure {
-        //     self.reconfigure(&wgpu.device);
-        //     return None;
-        // }
 
         let surface_texture_view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
         self.current_surface_texture = Some(surface_texture);
+        wgpu.bind_groups.lock().unwrap().end_frame();
+        wgpu.resource_pool.lock().unwrap().end_frame();
 
         Some(RenderTarget {
             target_view: surface_texture_view,
@@ -1373,10 +2791,12 @@ impl Window {
             // encoder: EncoderHandle::new(device, queue),
             encoder: EncoderHandle::new(&wgpu.device, &wgpu.queue, "surface_texture_encoder"),
             wgpu,
+            background: self.background.clone(),
         })
     }
 
     pub fn present_frame(&mut self) {
+        crate::profile_scope!("present_frame");
         self.raw.pre_present_notify();
         let surface_texture = self
             .current_surface_texture
@@ -1389,3 +2809,2123 @@ impl Window {
         self.raw.request_redraw();
     }
 }
+
+// BEGIN SPRITE BATCH
+//---------------------------------------------------------------------------------------
+
+/// 2d camera, `pos` is the world position at the center of the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    pub pos: Vec2,
+    pub zoom: f32,
+    /// rotation in radians
+    pub rotation: f32,
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self::new(Vec2::ZERO)
+    }
+}
+
+impl Camera2D {
+    pub fn new(pos: Vec2) -> Self {
+        Self {
+            pos,
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    pub fn zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// world to clip space, y points down like in the ui
+    pub fn view_proj(&self, screen_size: Vec2) -> Mat4 {
+        let half = screen_size / 2.0;
+        let proj = Mat4::orthographic_lh(-half.x, half.x, half.y, -half.y, -1.0, 1.0);
+        let view = Mat4::from_rotation_z(-self.rotation)
+            * Mat4::from_scale(Vec3::new(self.zoom, self.zoom, 1.0))
+            * Mat4::from_translation(-self.pos.extend(0.0));
+        proj * view
+    }
+
+    pub fn screen_to_world(&self, screen_pos: Vec2, screen_size: Vec2) -> Vec2 {
+        let ndc = screen_pos / screen_size * Vec2::new(2.0, -2.0) - Vec2::new(1.0, -1.0);
+        self.view_proj(screen_size)
+            .inverse()
+            .project_point3(ndc.extend(0.0))
+            .truncate()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    pub pos: Vec2,
+    pub size: Vec2,
+    /// rotation in radians around `pos`
+    pub rotation: f32,
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    pub tint: RGBA,
+    /// sprites on higher layers are drawn on top
+    pub layer: i32,
+}
+
+impl Sprite {
+    pub fn new(pos: Vec2, size: Vec2) -> Self {
+        Self {
+            pos,
+            size,
+            rotation: 0.0,
+            uv_min: Vec2::ZERO,
+            uv_max: Vec2::ONE,
+            tint: RGBA::WHITE,
+            layer: 0,
+        }
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.size *= scale;
+        self
+    }
+
+    pub fn uv(mut self, uv_min: Vec2, uv_max: Vec2) -> Self {
+        self.uv_min = uv_min;
+        self.uv_max = uv_max;
+        self
+    }
+
+    pub fn tint(mut self, tint: RGBA) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    fn instance(&self) -> SpriteInstance {
+        SpriteInstance {
+            pos: self.pos,
+            size: self.size,
+            uv_min: self.uv_min,
+            uv_max: self.uv_max,
+            rotation: self.rotation,
+            _pad: 0.0,
+            tint: self.tint,
+        }
+    }
+}
+
+#[macros::vertex]
+pub struct SpriteInstance {
+    pub pos: Vec2,
+    pub size: Vec2,
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    pub rotation: f32,
+    pub _pad: f32,
+    pub tint: RGBA,
+}
+
+/// contiguous range of instances that share a texture
+#[derive(Debug, Clone, PartialEq)]
+struct SpriteRun {
+    tex: usize,
+    instances: std::ops::Range<u32>,
+}
+
+/// sorts the (layer, texture) keys and returns the order of the sprites and the instance runs
+fn sort_sprite_runs(keys: &[(i32, usize)]) -> (Vec<usize>, Vec<SpriteRun>) {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    // stable, so sprites with the same key keep their push order
+    order.sort_by_key(|&i| keys[i]);
+
+    let mut runs: Vec<SpriteRun> = Vec::new();
+    for (n, &i) in order.iter().enumerate() {
+        let tex = keys[i].1;
+        match runs.last_mut() {
+            Some(run) if run.tex == tex => run.instances.end += 1,
+            _ => runs.push(SpriteRun {
+                tex,
+                instances: n as u32..n as u32 + 1,
+            }),
+        }
+    }
+
+    (order, runs)
+}
+
+/// instanced renderer for textured quads in world space
+///
+/// sprites are sorted by layer and then by texture, every run of sprites with the same texture
+/// is one instanced draw call. call [`SpriteBatch::prepare`] before rendering
+pub struct SpriteBatch {
+    pub camera: Camera2D,
+    pub screen_size: Vec2,
+    pub filter: wgpu::FilterMode,
+
+    sprites: Vec<Sprite>,
+    keys: Vec<(i32, usize)>,
+    textures: Vec<Texture>,
+
+    runs: Vec<SpriteRun>,
+    /// a bind group per texture, taken from [`WGPU::bind_groups`]
+    bind_groups: Vec<wgpu::BindGroup>,
+    /// grows when the sprites don't fit, kept otherwise
+    gpu_instances: Option<wgpu::Buffer>,
+    gpu_camera: Option<wgpu::Buffer>,
+
+    pub wgpu: WGPUHandle,
+}
+
+impl SpriteBatch {
+    pub fn new(wgpu: WGPUHandle) -> Self {
+        Self {
+            camera: Camera2D::default(),
+            screen_size: Vec2::ONE,
+            filter: wgpu::FilterMode::Linear,
+            sprites: Vec::new(),
+            keys: Vec::new(),
+            textures: Vec::new(),
+            runs: Vec::new(),
+            bind_groups: Vec::new(),
+            gpu_instances: None,
+            gpu_camera: None,
+            wgpu,
+        }
+    }
+
+    pub fn push(&mut self, texture: &Texture, sprite: Sprite) {
+        let tex = match self.textures.iter().position(|t| t == texture) {
+            Some(i) => i,
+            None => {
+                self.textures.push(texture.clone());
+                self.textures.len() - 1
+            }
+        };
+
+        self.keys.push((sprite.layer, tex));
+        self.sprites.push(sprite);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// number of draw calls of the last [`SpriteBatch::prepare`]
+    pub fn n_draw_calls(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+        self.keys.clear();
+        self.textures.clear();
+        self.runs.clear();
+        self.bind_groups.clear();
+    }
+
+    /// sorts the sprites and uploads them to the gpu, the buffers of the last call are reused
+    pub fn prepare(&mut self) {
+        let (order, runs) = sort_sprite_runs(&self.keys);
+        self.runs = runs;
+
+        if order.is_empty() {
+            self.bind_groups.clear();
+            return;
+        }
+
+        let instances: Vec<SpriteInstance> =
+            order.iter().map(|&i| self.sprites[i].instance()).collect();
+        let size = std::mem::size_of_val(instances.as_slice()) as u64;
+
+        let wgpu = &self.wgpu;
+        if self.gpu_instances.as_ref().is_none_or(|b| b.size() < size) {
+            self.gpu_instances = Some(wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("sprite_instance_buffer"),
+                size: size.next_power_of_two(),
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            }));
+        }
+        let buffer = self.gpu_instances.as_ref().unwrap();
+        wgpu.queue
+            .write_buffer(buffer, 0, bytemuck::cast_slice(&instances));
+
+        let view_proj = self.camera.view_proj(self.screen_size);
+        let camera = self.gpu_camera.get_or_insert_with(|| {
+            wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("sprite_camera_buffer"),
+                size: size_of_val(&view_proj) as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+        wgpu.queue
+            .write_buffer(camera, 0, bytemuck::cast_slice(&[view_proj]));
+
+        let mut cache = wgpu.bind_groups.lock().unwrap();
+        let sampler = cache.sampler(
+            &wgpu.device,
+            PipelineKey::of(&("sprite_sampler", self.filter)),
+            &wgpu::SamplerDescriptor {
+                label: Some("sprite_sampler"),
+                mag_filter: self.filter,
+                min_filter: self.filter,
+                ..Default::default()
+            },
+        );
+
+        let layout = cache.layout(&wgpu.device, &sprite_bind_group_entries());
+        self.bind_groups = self
+            .textures
+            .iter()
+            .map(|tex| {
+                let resources = [
+                    BindResource::Buffer(camera.clone(), None),
+                    BindResource::Sampler(sampler.clone()),
+                    BindResource::TextureView(tex.view().clone()),
+                ];
+                cache.bind_group(&wgpu.device, &layout, &resources)
+            })
+            .collect();
+    }
+}
+
+impl RenderPassHandle for SpriteBatch {
+    const LABEL: &'static str = "sprite_batch_render_pass";
+
+    fn draw<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>, wgpu: &WGPU) {
+        let Some(buffer) = &self.gpu_instances else {
+            return;
+        };
+        if self.runs.is_empty() {
+            return;
+        }
+
+        let desc = SpriteInstance::instance_desc();
+        let config = ShaderBuildConfig::new([(&desc, "SpriteInstance")]);
+        rpass.set_pipeline(&SpriteShader.get_pipeline(config, wgpu));
+        rpass.set_vertex_buffer(0, buffer.slice(..));
+
+        for run in &self.runs {
+            rpass.set_bind_group(0, &self.bind_groups[run.tex], &[]);
+            rpass.draw(0..6, run.instances.clone());
+        }
+    }
+}
+
+fn sprite_bind_group_entries() -> [wgpu::BindGroupLayoutEntry; 3] {
+    [
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        },
+    ]
+}
+
+fn sprite_bind_group_layout(wgpu: &WGPU) -> wgpu::BindGroupLayout {
+    let entries = sprite_bind_group_entries();
+    wgpu.bind_groups.lock().unwrap().layout(&wgpu.device, &entries)
+}
+
+pub struct SpriteShader;
+
+impl ShaderHandle for SpriteShader {
+    const RENDER_PIPELINE_ID: ShaderID = "sprite_shader";
+
+    fn build_pipeline<const N: usize>(&self, config: ShaderBuildConfig<'_, N>, wgpu: &WGPU) -> wgpu::RenderPipeline {
+        const SHADER_SRC: &str = r#"
+            @rust struct SpriteInstance {
+                pos: vec2<f32>,
+                size: vec2<f32>,
+                uv_min: vec2<f32>,
+                uv_max: vec2<f32>,
+                rotation: f32,
+                tint: vec4<f32>,
+                ...
+            }
+
+            @group(0) @binding(0)
+            var<uniform> view_proj: mat4x4<f32>;
+
+            @group(0) @binding(1)
+            var samp: sampler;
+
+            @group(0) @binding(2)
+            var tex: texture_2d<f32>;
+
+            struct VSOut {
+                @builtin(position) pos: vec4<f32>,
+                @location(0) uv: vec2<f32>,
+                @location(1) tint: vec4<f32>,
+            };
+
+            @vertex
+            fn vs_main(@builtin(vertex_index) vi: u32, s: SpriteInstance) -> VSOut {
+                var corners = array<vec2<f32>, 6>(
+                    vec2(-0.5, -0.5), vec2(0.5, -0.5), vec2(0.5, 0.5),
+                    vec2(-0.5, -0.5), vec2(0.5, 0.5), vec2(-0.5, 0.5),
+                );
+                let c = corners[vi];
+                let p = c * s.size;
+                let cs = cos(s.rotation);
+                let sn = sin(s.rotation);
+                let world = s.pos + vec2(p.x * cs - p.y * sn, p.x * sn + p.y * cs);
+
+                var out: VSOut;
+                out.pos = view_proj * vec4(world, 0.0, 1.0);
+                out.uv = mix(s.uv_min, s.uv_max, c + vec2(0.5));
+                out.tint = s.tint;
+                return out;
+            }
+
+            @fragment
+            fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+                return textureSample(tex, samp, in.uv) * in.tint;
+            }
+            "#;
+
+        let shader_src = pre_process_shader_code(SHADER_SRC, &config.shader_templates).unwrap();
+        let layout = sprite_bind_group_layout(wgpu);
+
+        let vertices = config.shader_templates.iter().map(|d| d.0).collect::<Vec<_>>();
+        PipelineBuilder::new(&shader_src, wgpu.surface_format)
+            .label("sprite_pipeline")
+            .vertex_buffers(&vertices)
+            .bind_groups(&[&layout])
+            .bind_group_entries(&[&sprite_bind_group_entries()])
+            .blend_state(Some(wgpu::BlendState::ALPHA_BLENDING))
+            .build(&wgpu.device)
+    }
+}
+
+// END SPRITE BATCH
+//---------------------------------------------------------------------------------------
+
+// BEGIN PICKING
+//---------------------------------------------------------------------------------------
+
+/// vertex of the picking pass, `pos` is in the screen space of the ui vertices
+#[macros::vertex]
+pub struct PickVertex {
+    pub pos: Vec2,
+    pub id: u32,
+}
+
+/// result of the readback, set by the map callback
+type PickReadback = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+/// renders item ids into an offscreen `R32Uint` target and reads back the id under a position
+///
+/// the readback does not block, [`PickingBuffer::poll`] returns the id once the gpu is done,
+/// usually a frame or two later. id 0 is the background
+pub struct PickingBuffer {
+    texture: wgpu::Texture,
+    readback: wgpu::Buffer,
+    /// the readback in flight
+    pending: Option<PickReadback>,
+}
+
+impl PickingBuffer {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+    pub fn new(wgpu: &WGPU, size: UVec2) -> Self {
+        let readback = wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("picking_readback_buffer"),
+            size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            texture: Self::create_texture(wgpu, size),
+            readback,
+            pending: None,
+        }
+    }
+
+    fn create_texture(wgpu: &WGPU, size: UVec2) -> wgpu::Texture {
+        wgpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("picking_texture"),
+            size: wgpu::Extent3d {
+                width: size.x.max(1),
+                height: size.y.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    pub fn size(&self) -> UVec2 {
+        UVec2::new(self.texture.width(), self.texture.height())
+    }
+
+    /// a readback is in flight, [`Self::render`] does nothing until it is polled
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// renders the triangles into a target of `size` and starts reading back the id at `pos`
+    ///
+    /// returns false if nothing was started, because a readback is pending or `pos` is outside
+    /// of the target
+    pub fn render(
+        &mut self,
+        wgpu: &WGPU,
+        size: UVec2,
+        vtx: &[PickVertex],
+        idx: &[u32],
+        pos: Vec2,
+    ) -> bool {
+        let size = size.max(UVec2::ONE);
+        let inside = pos.cmpge(Vec2::ZERO).all() && pos.cmplt(size.as_vec2()).all();
+        if self.is_pending() || !inside {
+            return false;
+        }
+        if self.size() != size {
+            self.texture = Self::create_texture(wgpu, size);
+        }
+        let view = self.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = wgpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("picking_encoder"),
+            });
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                label: Some("picking_render_pass"),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            if !idx.is_empty() {
+                // positions are converted to clip space here, so the pass needs no uniforms
+                let scale = 2.0 / size.as_vec2();
+                let vtx: Vec<PickVertex> = vtx
+                    .iter()
+                    .map(|v| PickVertex {
+                        pos: Vec2::new(v.pos.x * scale.x - 1.0, 1.0 - v.pos.y * scale.y),
+                        id: v.id,
+                    })
+                    .collect();
+
+                let vtx_buffer = wgpu
+                    .device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("picking_vertex_buffer"),
+                        contents: bytemuck::cast_slice(&vtx),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    });
+                let idx_buffer = wgpu
+                    .device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("picking_index_buffer"),
+                        contents: bytemuck::cast_slice(idx),
+                        usage: wgpu::BufferUsages::INDEX,
+                    });
+
+                let desc = PickVertex::desc();
+                let config = ShaderBuildConfig::new([(&desc, "PickVertex")]);
+                rpass.set_pipeline(&PickShader.get_pipeline(config, wgpu));
+                rpass.set_vertex_buffer(0, vtx_buffer.slice(..));
+                rpass.set_index_buffer(idx_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                rpass.draw_indexed(0..idx.len() as u32, 0, 0..1);
+            }
+        }
+
+        let pos = pos.as_uvec2();
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: pos.x,
+                    y: pos.y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: Some(1),
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        wgpu.queue.submit(std::iter::once(encoder.finish()));
+
+        let pending = PickReadback::default();
+        let result = pending.clone();
+        self.readback
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |res| {
+                *result.lock().unwrap() = Some(res);
+            });
+        self.pending = Some(pending);
+        true
+    }
+
+    /// the id read back by the last [`Self::render`], once the gpu copied it
+    pub fn poll(&mut self, wgpu: &WGPU) -> Option<u32> {
+        let pending = self.pending.as_ref()?;
+        let _ = wgpu.device.poll(wgpu::PollType::Poll);
+        let res = pending.lock().unwrap().take()?;
+        self.pending = None;
+
+        if let Err(e) = res {
+            log::warn!("picking readback failed: {e}");
+            return None;
+        }
+        let id = {
+            let data = self.readback.slice(..).get_mapped_range();
+            u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+        };
+        self.readback.unmap();
+        Some(id)
+    }
+}
+
+pub struct PickShader;
+
+impl ShaderHandle for PickShader {
+    const RENDER_PIPELINE_ID: ShaderID = "pick_shader";
+
+    fn build_pipeline<const N: usize>(
+        &self,
+        config: ShaderBuildConfig<'_, N>,
+        wgpu: &WGPU,
+    ) -> wgpu::RenderPipeline {
+        const SHADER_SRC: &str = r#"
+            @rust struct PickVertex {
+                pos: vec2<f32>,
+                id: u32,
+                ...
+            }
+
+            struct VSOut {
+                @builtin(position) pos: vec4<f32>,
+                @location(0) @interpolate(flat) id: u32,
+            };
+
+            @vertex
+            fn vs_main(v: PickVertex) -> VSOut {
+                var out: VSOut;
+                out.pos = vec4(v.pos, 0.0, 1.0);
+                out.id = v.id;
+                return out;
+            }
+
+            @fragment
+            fn fs_main(in: VSOut) -> @location(0) u32 {
+                return in.id;
+            }
+            "#;
+
+        let shader_src = pre_process_shader_code(SHADER_SRC, &config.shader_templates).unwrap();
+
+        let vertices = config.shader_templates.iter().map(|d| d.0).collect::<Vec<_>>();
+        PipelineBuilder::new(&shader_src, PickingBuffer::FORMAT)
+            .label("pick_pipeline")
+            .vertex_buffers(&vertices)
+            .blend_state(None)
+            .build(&wgpu.device)
+    }
+}
+
+// END PICKING
+//---------------------------------------------------------------------------------------
+
+// BEGIN BACKGROUND
+//---------------------------------------------------------------------------------------
+
+/// draws a background into the render pass, gets the size of the target in pixels
+pub type BackgroundFn = Arc<dyn Fn(&mut wgpu::RenderPass<'_>, &WGPU, Vec2) + Send + Sync>;
+
+/// what a render target is filled with before anything else is drawn, see
+/// [`RenderTarget::clear`]
+///
+/// the layers are drawn in order: the clear color, the gradient and the custom background
+#[derive(Clone)]
+pub struct Background {
+    /// `None` keeps the previous content of the target
+    pub clear: Option<RGBA>,
+    /// vertical gradient from the top to the bottom color
+    pub gradient: Option<(RGBA, RGBA)>,
+    /// e.g. a skybox, drawn with the pipelines of the caller
+    pub custom: Option<BackgroundFn>,
+}
+
+impl fmt::Debug for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Background")
+            .field("clear", &self.clear)
+            .field("gradient", &self.gradient)
+            .field("custom", &self.custom.is_some())
+            .finish()
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self::color(RGBA::ZERO)
+    }
+}
+
+impl Background {
+    pub fn color(col: RGBA) -> Self {
+        Self {
+            clear: Some(col),
+            gradient: None,
+            custom: None,
+        }
+    }
+
+    /// draws over the previous content of the target
+    pub fn load() -> Self {
+        Self {
+            clear: None,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_gradient(mut self, top: RGBA, bottom: RGBA) -> Self {
+        self.gradient = Some((top, bottom));
+        self
+    }
+
+    pub fn with_custom(
+        mut self,
+        f: impl Fn(&mut wgpu::RenderPass<'_>, &WGPU, Vec2) + Send + Sync + 'static,
+    ) -> Self {
+        self.custom = Some(Arc::new(f));
+        self
+    }
+
+    fn load_op(&self) -> wgpu::LoadOp<wgpu::Color> {
+        match self.clear {
+            Some(col) => wgpu::LoadOp::Clear(col.into()),
+            None => wgpu::LoadOp::Load,
+        }
+    }
+
+    /// the corners of the gradient quad in clip space
+    fn gradient_vertices(top: RGBA, bottom: RGBA) -> [BackgroundVertex; 4] {
+        let v = |x: f32, y: f32, col: RGBA| BackgroundVertex {
+            pos: Vec2::new(x, y),
+            col,
+        };
+        [v(-1.0, 1.0, top), v(1.0, 1.0, top), v(-1.0, -1.0, bottom), v(1.0, -1.0, bottom)]
+    }
+
+    fn draw_gradient(rpass: &mut wgpu::RenderPass<'_>, wgpu: &WGPU, top: RGBA, bottom: RGBA) {
+        let vtx = Self::gradient_vertices(top, bottom);
+        let vtx_buffer = wgpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("background_vertex_buffer"),
+                contents: bytemuck::cast_slice(&vtx),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        rpass.set_vertex_buffer(0, vtx_buffer.slice(..));
+        rpass.draw(0..4, 0..1);
+    }
+
+    /// the gradient pipeline for targets of `format`
+    fn gradient_pipeline(
+        wgpu: &WGPU,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Arc<wgpu::RenderPipeline> {
+
+        let key = PipelineKey::of(&("background_gradient", format, sample_count));
+        wgpu.get_or_init_pipeline(key, || {
+            let desc = BackgroundVertex::desc();
+            let templates = [(&desc, "BackgroundVertex")];
+            let shader_src = pre_process_shader_code(BACKGROUND_SHADER, &templates).unwrap();
+            PipelineBuilder::new(&shader_src, format)
+                .label("background_gradient_pipeline")
+                .vertex_buffers(&[&desc])
+                .primitive_topology(wgpu::PrimitiveTopology::TriangleStrip)
+                .blend_state(Some(wgpu::BlendState::ALPHA_BLENDING))
+                .sample_count(sample_count)
+                .build(&wgpu.device)
+        })
+    }
+}
+
+/// draws the gradient of a [`Background`] with [`BackgroundVertex`]s
+pub(crate) const BACKGROUND_SHADER: &str = r#"
+    @rust struct BackgroundVertex {
+        pos: vec2<f32>,
+        col: vec4<f32>,
+        ...
+    }
+
+    struct VSOut {
+        @builtin(position) pos: vec4<f32>,
+        @location(0) col: vec4<f32>,
+    };
+
+    @vertex
+    fn vs_main(v: BackgroundVertex) -> VSOut {
+        var out: VSOut;
+        out.pos = vec4(v.pos, 0.0, 1.0);
+        out.col = v.col;
+        return out;
+    }
+
+    @fragment
+    fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+        return in.col;
+    }
+    "#;
+
+/// vertex of the gradient of a [`Background`], `pos` is in clip space
+#[macros::vertex]
+pub struct BackgroundVertex {
+    pub pos: Vec2,
+    pub col: RGBA,
+}
+
+impl RenderTarget<'_> {
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn set_clear_color(&mut self, col: RGBA) {
+        self.background.clear = Some(col);
+    }
+
+    /// fills the target with its [`Background`], call it before rendering anything else
+    pub fn clear(&mut self) {
+        crate::profile_scope!("render_pass", "background");
+        log::trace!("[RENDERPASS] background");
+        let texture = self.target_view.texture();
+        let (format, sample_count) = (texture.format(), texture.sample_count());
+        let size = self.target_size();
+        let bg = &self.background;
+
+        self.encoder.with_encoder(|encoder| {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.target_view,
+                    resolve_target: self.resolve_view.as_ref(),
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load: bg.load_op(),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                label: Some("background_pass"),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            if let Some((top, bottom)) = bg.gradient {
+                let pipeline = Background::gradient_pipeline(self.wgpu, format, sample_count);
+                rpass.set_pipeline(&pipeline);
+                Background::draw_gradient(&mut rpass, self.wgpu, top, bottom);
+            }
+            if let Some(custom) = &bg.custom {
+                custom(&mut rpass, self.wgpu, size);
+            }
+        });
+    }
+}
+
+// END BACKGROUND
+//---------------------------------------------------------------------------------------
+
+// BEGIN STREAMING TEXTURE
+//---------------------------------------------------------------------------------------
+
+/// rgba8 texture that is updated from cpu pixel data every frame, e.g. video frames, camera
+/// feeds or cpu rendered content
+///
+/// uploads go to the back texture which becomes the front texture afterwards, so the texture
+/// that is currently displayed is never written to. display it with `ui.image(.., stream.texture())`
+#[derive(Debug, Clone)]
+pub struct StreamingTexture {
+    textures: [Texture; 2],
+    front: usize,
+    /// number of uploaded frames
+    pub frame: u64,
+}
+
+impl StreamingTexture {
+    pub fn new(wgpu: &WGPU, width: u32, height: u32) -> Self {
+        let create = || {
+            Texture::create_empty_with_usage(
+                wgpu,
+                width.max(1),
+                height.max(1),
+                wgpu::TextureUsages::COPY_DST,
+            )
+        };
+
+        Self {
+            textures: [create(), create()],
+            front: 0,
+            frame: 0,
+        }
+    }
+
+    /// minimum length of the pixel data for the given row pitch, the last row does not need padding
+    pub fn required_len(width: u32, height: u32, bytes_per_row: u32) -> usize {
+        if height == 0 {
+            return 0;
+        }
+        (bytes_per_row as usize) * (height as usize - 1) + 4 * width as usize
+    }
+
+    /// uploads tightly packed rgba8 pixels
+    pub fn update(&mut self, wgpu: &WGPU, data: &[u8]) {
+        self.update_with_stride(wgpu, data, 4 * self.width());
+    }
+
+    /// uploads rgba8 pixels whose rows are `bytes_per_row` apart, e.g. padded decoder output
+    pub fn update_with_stride(&mut self, wgpu: &WGPU, data: &[u8], bytes_per_row: u32) {
+        let (width, height) = (self.width(), self.height());
+        assert!(bytes_per_row >= 4 * width, "row pitch is smaller than a row");
+        assert!(
+            data.len() >= Self::required_len(width, height, bytes_per_row),
+            "expected at least {} bytes for {width}x{height} pixels, got {}",
+            Self::required_len(width, height, bytes_per_row),
+            data.len(),
+        );
+
+        let back = 1 - self.front;
+
+        // unlike buffer to texture copies, write_texture has no row alignment requirement
+        wgpu.queue.write_texture(
+            wgpu::TexelCopyTextureInfoBase {
+                texture: self.textures[back].raw(),
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.front = back;
+        self.frame += 1;
+    }
+
+    /// recreates the textures, the content is lost
+    pub fn resize(&mut self, wgpu: &WGPU, width: u32, height: u32) {
+        if width == self.width() && height == self.height() {
+            return;
+        }
+        *self = Self::new(wgpu, width, height);
+    }
+
+    /// the most recently uploaded frame
+    pub fn texture(&self) -> &Texture {
+        &self.textures[self.front]
+    }
+
+    pub fn width(&self) -> u32 {
+        self.texture().width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture().height()
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.texture().size()
+    }
+}
+
+// END STREAMING TEXTURE
+//---------------------------------------------------------------------------------------
+
+// BEGIN FRAME RECORDER
+//---------------------------------------------------------------------------------------
+
+/// where a [`FrameRecorder`] writes the recorded frames
+#[derive(Debug)]
+pub enum FrameSink {
+    /// numbered png files `frame_000000.png` in a directory, which is created if needed
+    Images(std::path::PathBuf),
+    /// kept in memory, e.g. to compare them in visual regression tests
+    Memory(Vec<CapturedImage>),
+    /// raw rgba frames piped to an `ffmpeg` process that encodes them into `path`
+    #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+    Video {
+        path: std::path::PathBuf,
+        fps: u32,
+        /// started with the size of the first frame
+        encoder: Option<(std::process::Child, UVec2)>,
+    },
+}
+
+impl FrameSink {
+    pub fn images(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self::Images(dir.into())
+    }
+
+    pub fn memory() -> Self {
+        Self::Memory(Vec::new())
+    }
+
+    /// requires `ffmpeg` in the `PATH`, the container is chosen by the extension of `path`
+    #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+    pub fn video(path: impl Into<std::path::PathBuf>, fps: u32) -> Self {
+        Self::Video {
+            path: path.into(),
+            fps,
+            encoder: None,
+        }
+    }
+
+    /// writes the `index`th frame of the recording
+    pub fn write(&mut self, index: u64, img: CapturedImage) -> std::io::Result<()> {
+        match self {
+            Self::Images(dir) => {
+                if index == 0 {
+                    std::fs::create_dir_all(&*dir)?;
+                }
+                let path = dir.join(format!("frame_{index:06}.png"));
+                img.save(path).map_err(std::io::Error::other)
+            }
+            Self::Memory(frames) => {
+                frames.push(img);
+                Ok(())
+            }
+            #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+            Self::Video { path, fps, encoder } => {
+                use std::io::Write;
+
+                let size = UVec2::new(img.width, img.height);
+                let (child, encoder_size) = match encoder {
+                    Some(encoder) => encoder,
+                    None => encoder.insert((Self::spawn_encoder(path, *fps, size)?, size)),
+                };
+                // the video has the size of the first frame
+                if *encoder_size != size {
+                    log::warn!("frame recorder: skipped frame {index} of size {size}");
+                    return Ok(());
+                }
+                child.stdin.as_mut().unwrap().write_all(&img.rgba)
+            }
+        }
+    }
+
+    #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+    fn spawn_encoder(
+        path: &std::path::Path,
+        fps: u32,
+        size: UVec2,
+    ) -> std::io::Result<std::process::Child> {
+        let (size, fps) = (format!("{}x{}", size.x, size.y), fps.to_string());
+        std::process::Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &size, "-r", &fps, "-i", "-", "-pix_fmt", "yuv420p"])
+            .arg(path)
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+    }
+
+    /// closes the encoder of a video and waits until it is done
+    pub fn close(&mut self) -> std::io::Result<()> {
+        #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+        if let Self::Video { encoder, .. } = self
+            && let Some((mut child, _)) = encoder.take()
+        {
+            drop(child.stdin.take());
+            let status = child.wait()?;
+            if !status.success() {
+                return Err(std::io::Error::other(format!("ffmpeg failed: {status}")));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// result of a frame readback, set by the map callback
+type FrameReadback = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+/// a staging buffer of the [`FrameRecorder`] ring
+struct FrameStaging {
+    buffer: wgpu::Buffer,
+    size: UVec2,
+    is_bgra: bool,
+    /// index of the frame in flight
+    pending: Option<(u64, FrameReadback)>,
+}
+
+/// copies every rendered frame to the cpu and writes it to a [`FrameSink`], e.g. for demo
+/// recordings or visual regression videos
+///
+/// the copies go through a ring of staging buffers that are read back once the gpu is done,
+/// so recording only stalls when all buffers are still in flight. frames are written in order
+pub struct FrameRecorder {
+    wgpu: WGPUHandle,
+    sink: FrameSink,
+    ring: Vec<Option<FrameStaging>>,
+    /// the slot of the next copy, which is also the oldest copy in flight
+    next: usize,
+    n_frames: u64,
+}
+
+impl FrameRecorder {
+    pub const DEFAULT_RING_SIZE: usize = 3;
+
+    pub fn new(wgpu: &WGPUHandle, sink: FrameSink) -> Self {
+        Self {
+            wgpu: wgpu.clone(),
+            sink,
+            ring: (0..Self::DEFAULT_RING_SIZE).map(|_| None).collect(),
+            next: 0,
+            n_frames: 0,
+        }
+    }
+
+    /// more buffers avoid stalls when the gpu is several frames behind
+    pub fn with_ring_size(mut self, n: usize) -> Self {
+        self.ring = (0..n.max(1)).map(|_| None).collect();
+        self
+    }
+
+    /// number of frames recorded so far
+    pub fn n_frames(&self) -> u64 {
+        self.n_frames
+    }
+
+    /// copies the frame rendered into `target`, call it after rendering and before presenting
+    pub fn record(&mut self, target: &RenderTarget) {
+        target.encoder.submit_and_continue();
+        let texture = target.resolve_view.as_ref().unwrap_or(&target.target_view).texture();
+        let Some(is_bgra) = readback_is_bgra(texture) else {
+            return;
+        };
+        let size = UVec2::new(texture.width(), texture.height());
+
+        self.poll();
+        // the ring is full, wait for the oldest copy
+        if self.ring[self.next].as_ref().is_some_and(|s| s.pending.is_some()) {
+            let _ = self.wgpu.device.poll(wgpu::PollType::wait_indefinitely());
+            self.poll();
+        }
+
+        let wgpu = self.wgpu.clone();
+        let staging = match &mut self.ring[self.next] {
+            Some(s) if s.size == size => s,
+            slot => slot.insert(FrameStaging {
+                buffer: wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("frame_recorder_buffer"),
+                    size: ((size.x * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+                        * size.y) as u64,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                size,
+                is_bgra,
+                pending: None,
+            }),
+        };
+        staging.is_bgra = is_bgra;
+        wgpu.copy_texture_to_buffer(texture, UVec2::ZERO, size, &staging.buffer);
+
+        let pending = FrameReadback::default();
+        let result = pending.clone();
+        staging
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |res| {
+                *result.lock().unwrap() = Some(res);
+            });
+        staging.pending = Some((self.n_frames, pending));
+        self.n_frames += 1;
+        self.next = (self.next + 1) % self.ring.len();
+    }
+
+    /// writes the frames whose copies are done, without blocking
+    pub fn poll(&mut self) {
+        let _ = self.wgpu.device.poll(wgpu::PollType::Poll);
+        const COPY_ALIGN: usize = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+        let n = self.ring.len();
+        for i in 0..n {
+            let slot = &mut self.ring[(self.next + i) % n];
+            let Some(staging) = slot.as_mut() else {
+                continue;
+            };
+            let Some((index, pending)) = &staging.pending else {
+                continue;
+            };
+            // later copies wait for this one so the frames stay in order
+            let Some(res) = pending.lock().unwrap().take() else {
+                break;
+            };
+            let index = *index;
+            staging.pending = None;
+
+            if let Err(e) = res {
+                log::warn!("frame recorder: readback of frame {index} failed: {e}");
+                continue;
+            }
+            let size = staging.size;
+            let row_bytes = size.x as usize * 4;
+            let padded_row_bytes = row_bytes.next_multiple_of(COPY_ALIGN);
+            let rgba = unpack_captured_rows(
+                &staging.buffer.slice(..).get_mapped_range(),
+                row_bytes,
+                padded_row_bytes,
+                staging.is_bgra,
+            );
+            staging.buffer.unmap();
+
+            let img = CapturedImage {
+                width: size.x,
+                height: size.y,
+                rgba,
+            };
+            if let Err(e) = self.sink.write(index, img) {
+                log::warn!("frame recorder: failed to write frame {index}: {e}");
+            }
+        }
+    }
+
+    /// waits for the copies in flight, writes them and closes the sink. returns the sink, e.g.
+    /// to take the frames of [`FrameSink::Memory`]
+    pub fn finish(mut self) -> std::io::Result<FrameSink> {
+        self.flush();
+        let mut sink = std::mem::replace(&mut self.sink, FrameSink::memory());
+        sink.close()?;
+        Ok(sink)
+    }
+
+    fn flush(&mut self) {
+        if self.ring.iter().flatten().any(|s| s.pending.is_some()) {
+            let _ = self.wgpu.device.poll(wgpu::PollType::wait_indefinitely());
+            self.poll();
+        }
+    }
+}
+
+impl Drop for FrameRecorder {
+    fn drop(&mut self) {
+        self.flush();
+        if let Err(e) = self.sink.close() {
+            log::warn!("frame recorder: {e}");
+        }
+    }
+}
+
+// END FRAME RECORDER
+//---------------------------------------------------------------------------------------
+
+/// a texture of a [`FrameGraph`] that lives for one frame, textures with equal descriptors
+/// can share memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureDesc {
+    pub size: UVec2,
+    pub format: wgpu::TextureFormat,
+    pub sample_count: u32,
+    pub usage: wgpu::TextureUsages,
+}
+
+impl TextureDesc {
+    /// a single sampled texture that can be rendered into, sampled and copied
+    pub fn new(size: UVec2, format: wgpu::TextureFormat) -> Self {
+        Self {
+            size,
+            format,
+            sample_count: 1,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+        }
+    }
+
+    /// multisampled textures can't be copied, so the copy usages are dropped
+    pub fn sample_count(mut self, count: u32) -> Self {
+        self.sample_count = count;
+        if count > 1 {
+            self.usage -= wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST;
+        }
+        self
+    }
+
+    pub fn usage(mut self, usage: wgpu::TextureUsages) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// bytes the texture takes on the gpu, ignoring alignment
+    pub fn byte_size(&self) -> u64 {
+        let texel = self.format.block_copy_size(None).unwrap_or(4) as u64;
+        texel * self.size.x as u64 * self.size.y as u64 * self.sample_count as u64
+    }
+
+    fn create(&self, device: &wgpu::Device) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("transient_texture"),
+            size: wgpu::Extent3d {
+                width: self.size.x.max(1),
+                height: self.size.y.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: self.usage,
+            view_formats: &[],
+        })
+    }
+}
+
+/// a buffer handed out by a [`ResourcePool`], buffers with equal descriptors are shared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BufferDesc {
+    pub size: u64,
+    pub usage: wgpu::BufferUsages,
+}
+
+impl BufferDesc {
+    /// the size is rounded up to a power of two, so requests of a similar size can reuse the
+    /// same buffer
+    pub fn new(size: u64, usage: wgpu::BufferUsages) -> Self {
+        Self {
+            size: size.max(wgpu::COPY_BUFFER_ALIGNMENT).next_power_of_two(),
+            usage,
+        }
+    }
+
+    fn create(&self, device: &wgpu::Device) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("transient_buffer"),
+            size: self.size,
+            usage: self.usage,
+            mapped_at_creation: false,
+        })
+    }
+}
+
+/// the resources of one kind in a [`ResourcePool`]
+#[derive(Debug)]
+struct PoolSet<D, R> {
+    /// free resources with the frame they were last used in
+    free: HashMap<D, Vec<(R, u64)>>,
+    in_use: Vec<(D, R)>,
+}
+
+impl<D, R> Default for PoolSet<D, R> {
+    fn default() -> Self {
+        Self {
+            free: HashMap::default(),
+            in_use: Vec::new(),
+        }
+    }
+}
+
+impl<D: Copy + Eq + hash::Hash, R: Clone + PartialEq> PoolSet<D, R> {
+    /// a free resource matching `desc` and whether it was reused
+    fn acquire(&mut self, desc: D, create: impl FnOnce() -> R) -> (R, bool) {
+        let (res, reused) = match self.free.get_mut(&desc).and_then(Vec::pop) {
+            Some((res, _)) => (res, true),
+            None => (create(), false),
+        };
+        self.in_use.push((desc, res.clone()));
+        (res, reused)
+    }
+
+    fn release(&mut self, res: &R, frame: u64) -> bool {
+        let Some(i) = self.in_use.iter().position(|(_, r)| r == res) else {
+            return false;
+        };
+        let (desc, res) = self.in_use.swap_remove(i);
+        self.free.entry(desc).or_default().push((res, frame));
+        true
+    }
+
+    fn end_frame(&mut self, frame: u64, max_unused: u64) {
+        for (desc, res) in self.in_use.drain(..) {
+            self.free.entry(desc).or_default().push((res, frame));
+        }
+        for list in self.free.values_mut() {
+            list.retain(|(_, last_used)| frame - last_used < max_unused);
+        }
+        self.free.retain(|_, list| !list.is_empty());
+    }
+
+    fn n_free(&self) -> usize {
+        self.free.values().map(Vec::len).sum()
+    }
+
+    fn bytes(&self, size: impl Fn(&D) -> u64) -> u64 {
+        let free: u64 = self
+            .free
+            .iter()
+            .map(|(desc, list)| size(desc) * list.len() as u64)
+            .sum();
+        free + self.in_use.iter().map(|(desc, _)| size(desc)).sum::<u64>()
+    }
+
+    fn clear(&mut self) {
+        self.free.clear();
+        self.in_use.clear();
+    }
+}
+
+/// memory held by a [`ResourcePool`], the counters are of the last finished frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    pub textures: usize,
+    pub buffers: usize,
+    /// resources handed out and not released yet
+    pub in_use: usize,
+    pub texture_bytes: u64,
+    pub buffer_bytes: u64,
+    /// resources that had to be created, a warmed up pool creates none
+    pub created: usize,
+    pub reused: usize,
+}
+
+impl fmt::Display for PoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+        write!(
+            f,
+            "{} textures ({:.1} MiB), {} buffers ({:.1} MiB), {} in use, {} created, {} reused",
+            self.textures,
+            mib(self.texture_bytes),
+            self.buffers,
+            mib(self.buffer_bytes),
+            self.in_use,
+            self.created,
+            self.reused,
+        )
+    }
+}
+
+/// temporary textures and buffers, e.g. for blur chains or thumbnails, that are reused by
+/// later requests with an equal descriptor instead of creating new ones every frame
+///
+/// resources are handed out until they are released or the frame ends, free resources are
+/// dropped once they were not used for [`ResourcePool::MAX_UNUSED_FRAMES`] calls to
+/// [`ResourcePool::end_frame`]
+///
+/// ```ignore
+/// let mut pool = wgpu.resource_pool.lock().unwrap();
+/// let blur = pool.acquire_texture(&wgpu.device, &TextureDesc::new(size, format));
+/// ```
+#[derive(Debug, Default)]
+pub struct ResourcePool {
+    textures: PoolSet<TextureDesc, wgpu::Texture>,
+    buffers: PoolSet<BufferDesc, wgpu::Buffer>,
+    frame: u64,
+    created: usize,
+    reused: usize,
+    prev_counts: (usize, usize),
+}
+
+impl ResourcePool {
+    pub const MAX_UNUSED_FRAMES: u64 = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a texture matching `desc` until the end of the frame, created if none is free
+    pub fn acquire_texture(&mut self, device: &wgpu::Device, desc: &TextureDesc) -> wgpu::Texture {
+        let (texture, reused) = self.textures.acquire(*desc, || desc.create(device));
+        self.count(reused);
+        texture
+    }
+
+    /// a buffer matching `desc` until the end of the frame, created if none is free
+    pub fn acquire_buffer(&mut self, device: &wgpu::Device, desc: &BufferDesc) -> wgpu::Buffer {
+        let (buffer, reused) = self.buffers.acquire(*desc, || desc.create(device));
+        self.count(reused);
+        buffer
+    }
+
+    fn count(&mut self, reused: bool) {
+        if reused {
+            self.reused += 1;
+        } else {
+            self.created += 1;
+        }
+    }
+
+    /// hands `texture` back before the frame ends, so later requests of the same frame can
+    /// use it. the commands using it must be recorded already. returns false if the texture
+    /// is not from this pool
+    pub fn release_texture(&mut self, texture: &wgpu::Texture) -> bool {
+        self.textures.release(texture, self.frame)
+    }
+
+    /// like [`Self::release_texture`], buffers that are still mapped must not be released
+    pub fn release_buffer(&mut self, buffer: &wgpu::Buffer) -> bool {
+        self.buffers.release(buffer, self.frame)
+    }
+
+    /// takes back the resources of the frame and drops the ones that were not used for a
+    /// while, call it once per frame
+    pub fn end_frame(&mut self) {
+        self.textures.end_frame(self.frame, Self::MAX_UNUSED_FRAMES);
+        self.buffers.end_frame(self.frame, Self::MAX_UNUSED_FRAMES);
+        self.frame += 1;
+        self.prev_counts = (
+            std::mem::take(&mut self.created),
+            std::mem::take(&mut self.reused),
+        );
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let in_use = self.textures.in_use.len() + self.buffers.in_use.len();
+        PoolStats {
+            textures: self.textures.n_free() + self.textures.in_use.len(),
+            buffers: self.buffers.n_free() + self.buffers.in_use.len(),
+            in_use,
+            texture_bytes: self.textures.bytes(TextureDesc::byte_size),
+            buffer_bytes: self.buffers.bytes(|desc| desc.size),
+            created: self.prev_counts.0,
+            reused: self.prev_counts.1,
+        }
+    }
+
+    /// drops everything, e.g. after the device was lost
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.buffers.clear();
+    }
+}
+
+/// a texture used by the passes of a [`FrameGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GraphTexture(usize);
+
+#[derive(Debug)]
+enum GraphResource {
+    /// owned outside of the graph, e.g. the surface. writing it keeps a pass alive
+    Imported(wgpu::Texture),
+    /// taken from the [`ResourcePool`] for the passes that use it
+    Transient(TextureDesc),
+}
+
+struct GraphPass<'a> {
+    label: String,
+    reads: Vec<GraphTexture>,
+    writes: Vec<GraphTexture>,
+    exec: Box<dyn FnOnce(&mut PassContext<'_>) + 'a>,
+}
+
+/// passed to a pass of a [`FrameGraph`] when it executes
+pub struct PassContext<'a> {
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub wgpu: &'a WGPU,
+    textures: &'a [Option<wgpu::Texture>],
+    reads: &'a [GraphTexture],
+    writes: &'a [GraphTexture],
+}
+
+impl PassContext<'_> {
+    /// the texture behind `tex`, the pass must have declared it
+    pub fn texture(&self, tex: GraphTexture) -> &wgpu::Texture {
+        debug_assert!(
+            self.reads.contains(&tex) || self.writes.contains(&tex),
+            "{tex:?} was not declared by the pass"
+        );
+        self.textures[tex.0]
+            .as_ref()
+            .expect("textures of executed passes are allocated")
+    }
+
+    pub fn view(&self, tex: GraphTexture) -> wgpu::TextureView {
+        self.texture(tex)
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    /// a render pass drawing into `tex`
+    pub fn render_pass(
+        &mut self,
+        tex: GraphTexture,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPass<'_> {
+        let view = self.view(tex);
+        self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("frame_graph_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameGraphError {
+    /// the passes depend on each other in a loop
+    Cycle(Vec<String>),
+    /// a pass reads a transient texture that no pass writes
+    ReadBeforeWrite { pass: String, texture: String },
+}
+
+impl fmt::Display for FrameGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle(passes) => {
+                write!(f, "the passes {} depend on each other", passes.join(", "))
+            }
+            Self::ReadBeforeWrite { pass, texture } => {
+                write!(f, "pass {pass} reads {texture}, which is never written")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameGraphError {}
+
+/// the order and texture assignment a [`FrameGraph`] executes with, its [`fmt::Display`]
+/// impl dumps the graph for debugging
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledGraph {
+    /// the passes in execution order, as indices in the order they were added
+    pub order: Vec<usize>,
+    /// passes whose output is never used
+    pub culled: Vec<usize>,
+    /// the physical texture of every transient texture, `None` for imported or unused ones
+    pub slots: Vec<Option<usize>>,
+    /// descriptors of the physical textures, transients share one if their lifetimes don't
+    /// overlap
+    pub slot_descs: Vec<TextureDesc>,
+    pass_labels: Vec<String>,
+    texture_labels: Vec<String>,
+    /// first and last position in `order` that uses each texture
+    lifetimes: Vec<Option<(usize, usize)>>,
+}
+
+impl CompiledGraph {
+    /// bytes of the physical transient textures
+    pub fn transient_bytes(&self) -> u64 {
+        self.slot_descs.iter().map(TextureDesc::byte_size).sum()
+    }
+}
+
+impl fmt::Display for CompiledGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "frame graph: {} passes, {} culled, {} transient textures in {} slots ({:.1} MiB)",
+            self.order.len(),
+            self.culled.len(),
+            self.slots.iter().flatten().count(),
+            self.slot_descs.len(),
+            self.transient_bytes() as f64 / (1024.0 * 1024.0),
+        )?;
+        for (i, &pass) in self.order.iter().enumerate() {
+            writeln!(f, "  {i}: {}", self.pass_labels[pass])?;
+        }
+        for &pass in &self.culled {
+            writeln!(f, "  culled: {}", self.pass_labels[pass])?;
+        }
+        for (tex, label) in self.texture_labels.iter().enumerate() {
+            let lifetime = match self.lifetimes[tex] {
+                Some((first, last)) => format!("passes {first}..={last}"),
+                None => "unused".to_string(),
+            };
+            match self.slots[tex] {
+                Some(slot) => {
+                    let desc = &self.slot_descs[slot];
+                    writeln!(
+                        f,
+                        "  {label}: slot {slot}, {}x{} {:?}, {lifetime}",
+                        desc.size.x, desc.size.y, desc.format
+                    )?;
+                }
+                None => writeln!(f, "  {label}: imported, {lifetime}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// passes of a frame declared with the textures they read and write. the graph orders the
+/// passes by their dependencies, culls passes whose output is never used and backs the
+/// transient textures with textures from [`WGPU::resource_pool`], sharing them between
+/// textures whose lifetimes don't overlap
+///
+/// passes writing the same texture run in the order they were added. a pass that reads a
+/// texture sees the contents of the last pass writing it that was added before the reader, and
+/// runs before the next writer. a reader added before all writers of a texture sees the
+/// contents of the last writer
+///
+/// ```ignore
+/// let mut graph = FrameGraph::new();
+/// let surface = graph.import_texture("surface", target.target_view.texture());
+/// let scene = graph.create_texture("scene", TextureDesc::new(size, format));
+/// graph.add_pass("composite", &[scene], &[surface], |ctx| { .. });
+/// graph.add_pass("scene", &[], &[scene], |ctx| { .. });
+/// graph.execute(&target)?;
+/// ```
+#[derive(Default)]
+pub struct FrameGraph<'a> {
+    textures: Vec<(String, GraphResource)>,
+    passes: Vec<GraphPass<'a>>,
+}
+
+impl<'a> FrameGraph<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a texture owned outside of the graph, passes writing it are never culled
+    pub fn import_texture(&mut self, label: &str, texture: &wgpu::Texture) -> GraphTexture {
+        let resource = GraphResource::Imported(texture.clone());
+        self.textures.push((label.to_string(), resource));
+        GraphTexture(self.textures.len() - 1)
+    }
+
+    /// a texture that only lives while the passes using it execute
+    pub fn create_texture(&mut self, label: &str, desc: TextureDesc) -> GraphTexture {
+        self.textures
+            .push((label.to_string(), GraphResource::Transient(desc)));
+        GraphTexture(self.textures.len() - 1)
+    }
+
+    /// a pass that samples or copies from `reads` and renders into `writes`, `exec` records
+    /// its commands. passes without writes are never culled, e.g. readbacks
+    pub fn add_pass(
+        &mut self,
+        label: &str,
+        reads: &[GraphTexture],
+        writes: &[GraphTexture],
+        exec: impl FnOnce(&mut PassContext<'_>) + 'a,
+    ) {
+        self.passes.push(GraphPass {
+            label: label.to_string(),
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+            exec: Box::new(exec),
+        });
+    }
+
+    pub fn n_passes(&self) -> usize {
+        self.passes.len()
+    }
+
+    pub fn compile(&self) -> Result<CompiledGraph, FrameGraphError> {
+        let n = self.passes.len();
+        let mut writers = vec![Vec::new(); self.textures.len()];
+        for (i, pass) in self.passes.iter().enumerate() {
+            for tex in &pass.writes {
+                writers[tex.0].push(i);
+            }
+        }
+
+        // edges from every pass to the passes that depend on it
+        let mut next = vec![Vec::new(); n];
+        for w in writers.iter() {
+            for pair in w.windows(2) {
+                next[pair[0]].push(pair[1]);
+            }
+        }
+        for (i, pass) in self.passes.iter().enumerate() {
+            for tex in pass.reads.iter().filter(|t| !pass.writes.contains(t)) {
+                let w = &writers[tex.0];
+                let is_transient = matches!(self.textures[tex.0].1, GraphResource::Transient(_));
+                if w.is_empty() && is_transient {
+                    return Err(FrameGraphError::ReadBeforeWrite {
+                        pass: pass.label.clone(),
+                        texture: self.textures[tex.0].0.clone(),
+                    });
+                }
+                // the last writer added before the reader, or the last one if there is none
+                let n_before = w.partition_point(|&w| w < i);
+                let writer = n_before.checked_sub(1).or(w.len().checked_sub(1));
+                if let Some(k) = writer {
+                    next[w[k]].push(i);
+                    // the next writer must not overwrite the texture before it was read
+                    if let Some(&overwrite) = w.get(k + 1) {
+                        next[i].push(overwrite);
+                    }
+                }
+            }
+        }
+
+        // kahn's algorithm, ready passes run in the order they were added
+        let mut n_deps = vec![0; n];
+        next.iter().flatten().for_each(|&j| n_deps[j] += 1);
+        let mut ready: std::collections::BTreeSet<usize> =
+            (0..n).filter(|&i| n_deps[i] == 0).collect();
+        let mut sorted = Vec::with_capacity(n);
+        while let Some(i) = ready.pop_first() {
+            sorted.push(i);
+            for &j in &next[i] {
+                n_deps[j] -= 1;
+                if n_deps[j] == 0 {
+                    ready.insert(j);
+                }
+            }
+        }
+        if sorted.len() != n {
+            let cycle = (0..n)
+                .filter(|i| n_deps[*i] != 0)
+                .map(|i| self.passes[i].label.clone())
+                .collect();
+            return Err(FrameGraphError::Cycle(cycle));
+        }
+
+        // a pass is alive if it has side effects or a live pass depends on it
+        let mut alive = vec![false; n];
+        for &i in sorted.iter().rev() {
+            let pass = &self.passes[i];
+            let has_effect = pass.writes.is_empty()
+                || pass
+                    .writes
+                    .iter()
+                    .any(|t| matches!(self.textures[t.0].1, GraphResource::Imported(_)));
+            alive[i] = has_effect || next[i].iter().any(|&j| alive[j]);
+        }
+        let (order, culled): (Vec<usize>, Vec<usize>) =
+            sorted.into_iter().partition(|&i| alive[i]);
+
+        let mut lifetimes: Vec<Option<(usize, usize)>> = vec![None; self.textures.len()];
+        for (pos, &i) in order.iter().enumerate() {
+            let pass = &self.passes[i];
+            for tex in pass.reads.iter().chain(&pass.writes) {
+                let lt = lifetimes[tex.0].get_or_insert((pos, pos));
+                lt.1 = pos;
+            }
+        }
+
+        // transients share a slot with an equal descriptor that is free again
+        let mut transients: Vec<(usize, TextureDesc, (usize, usize))> = self
+            .textures
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, r))| match (r, lifetimes[i]) {
+                (GraphResource::Transient(desc), Some(lt)) => Some((i, *desc, lt)),
+                _ => None,
+            })
+            .collect();
+        transients.sort_by_key(|(_, _, (first, _))| *first);
+        let mut slots = vec![None; self.textures.len()];
+        let mut slot_descs = Vec::new();
+        let mut slot_free_after: Vec<usize> = Vec::new();
+        for (tex, desc, (first, last)) in transients {
+            let slot = (0..slot_descs.len())
+                .find(|&s| slot_descs[s] == desc && slot_free_after[s] < first)
+                .unwrap_or_else(|| {
+                    slot_descs.push(desc);
+                    slot_free_after.push(0);
+                    slot_descs.len() - 1
+                });
+            slot_free_after[slot] = last;
+            slots[tex] = Some(slot);
+        }
+
+        Ok(CompiledGraph {
+            order,
+            culled,
+            slots,
+            slot_descs,
+            pass_labels: self.passes.iter().map(|p| p.label.clone()).collect(),
+            texture_labels: self.textures.iter().map(|(l, _)| l.clone()).collect(),
+            lifetimes,
+        })
+    }
+
+    /// compiles the graph and records the live passes into the encoder of `target`
+    pub fn execute(self, target: &RenderTarget) -> Result<CompiledGraph, FrameGraphError> {
+        crate::profile_scope!("frame_graph");
+        let compiled = self.compile()?;
+        let wgpu = target.wgpu;
+
+        let physical: Vec<wgpu::Texture> = {
+            let mut pool = wgpu.resource_pool.lock().unwrap();
+            compiled
+                .slot_descs
+                .iter()
+                .map(|desc| pool.acquire_texture(&wgpu.device, desc))
+                .collect()
+        };
+        let textures: Vec<Option<wgpu::Texture>> = self
+            .textures
+            .iter()
+            .enumerate()
+            .map(|(i, (_, r))| match r {
+                GraphResource::Imported(texture) => Some(texture.clone()),
+                GraphResource::Transient(_) => compiled.slots[i].map(|s| physical[s].clone()),
+            })
+            .collect();
+
+        let mut passes: Vec<Option<GraphPass<'a>>> = self.passes.into_iter().map(Some).collect();
+        target.encoder.with_encoder(|encoder| {
+            for &i in &compiled.order {
+                let pass = passes[i].take().expect("passes execute once");
+                crate::profile_scope!("graph_pass", pass.label.as_str());
+                log::trace!("[RENDERPASS] graph: {}", pass.label);
+                let mut ctx = PassContext {
+                    encoder,
+                    wgpu,
+                    textures: &textures,
+                    reads: &pass.reads,
+                    writes: &pass.writes,
+                };
+                (pass.exec)(&mut ctx);
+            }
+        });
+
+        // the commands are recorded, so the textures can be handed to the next graph
+        let mut pool = wgpu.resource_pool.lock().unwrap();
+        for texture in &physical {
+            pool.release_texture(texture);
+        }
+        Ok(compiled)
+    }
+}
+
+// END FRAME GRAPH
+//---------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_sprite_runs() {
+        // (layer, texture)
+        let keys = [(1, 0), (0, 1), (0, 0), (1, 0), (0, 1)];
+        let (order, runs) = sort_sprite_runs(&keys);
+
+        assert_eq!(order, [2, 1, 4, 0, 3]);
+        assert_eq!(
+            runs,
+            [
+                SpriteRun { tex: 0, instances: 0..1 },
+                SpriteRun { tex: 1, instances: 1..3 },
+                SpriteRun { tex: 0, instances: 3..5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_streaming_required_len() {
+        assert_eq!(StreamingTexture::required_len(4, 2, 16), 32);
+        // padded rows, the last row is not padded
+        assert_eq!(StreamingTexture::required_len(4, 3, 256), 2 * 256 + 16);
+        assert_eq!(StreamingTexture::required_len(4, 0, 256), 0);
+    }
+
+    #[test]
+    fn test_unpack_captured_rows() {
+        // two rows of one pixel, padded to 8 bytes
+        let data = [1, 2, 3, 4, 0, 0, 0, 0, 5, 6, 7, 8, 0, 0, 0, 0];
+        assert_eq!(unpack_captured_rows(&data, 4, 8, false), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(unpack_captured_rows(&data, 4, 8, true), [3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+
+    #[test]
+    fn test_camera_screen_to_world() {
+        let screen = Vec2::new(800.0, 600.0);
+        let cam = Camera2D::new(Vec2::new(100.0, 50.0)).zoom(2.0);
+
+        let center = cam.screen_to_world(screen / 2.0, screen);
+        assert!(center.distance(cam.pos) < 1e-3);
+
+        let top_left = cam.screen_to_world(Vec2::ZERO, screen);
+        assert!(top_left.distance(Vec2::new(-100.0, -100.0)) < 1e-3);
+    }
+
+    #[test]
+    fn test_frame_sink() {
+        let img = CapturedImage {
+            width: 1,
+            height: 1,
+            rgba: vec![255, 0, 0, 255],
+        };
+        let mut sink = FrameSink::memory();
+        sink.write(0, img.clone()).unwrap();
+        assert!(matches!(&sink, FrameSink::Memory(frames) if frames.len() == 1));
+
+        let dir = std::env::temp_dir().join(format!("wgpui_frames_{}", std::process::id()));
+        let mut sink = FrameSink::images(&dir);
+        sink.write(0, img.clone()).unwrap();
+        sink.write(1, img).unwrap();
+        sink.close().unwrap();
+        assert!(dir.join("frame_000001.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_frame_graph_compile() {
+        let desc = TextureDesc::new(UVec2::new(64, 64), wgpu::TextureFormat::Rgba8Unorm);
+        let mut graph = FrameGraph::new();
+        let scene = graph.create_texture("scene", desc);
+        let blur_a = graph.create_texture("blur_a", desc);
+        let blur_b = graph.create_texture("blur_b", desc);
+        let unused = graph.create_texture("unused", desc);
+        graph.add_pass("blur_h", &[scene], &[blur_a], |_| {});
+        graph.add_pass("blur_v", &[blur_a], &[blur_b], |_| {});
+        graph.add_pass("readback", &[blur_b], &[], |_| {});
+        graph.add_pass("scene", &[], &[scene], |_| {});
+        graph.add_pass("unused", &[], &[unused], |_| {});
+
+        let compiled = graph.compile().unwrap();
+        assert_eq!(compiled.order, [3, 0, 1, 2]);
+        assert_eq!(compiled.culled, [4]);
+        // blur_b reuses the texture of scene, which is dead once blur_h ran
+        assert_eq!(compiled.slots, [Some(0), Some(1), Some(0), None]);
+        assert_eq!(compiled.slot_descs.len(), 2);
+        assert!(compiled.to_string().contains("culled: unused"));
+
+        let mut graph = FrameGraph::new();
+        let x = graph.create_texture("x", desc);
+        let y = graph.create_texture("y", desc);
+        graph.add_pass("a", &[x], &[y], |_| {});
+        graph.add_pass("b", &[y], &[x], |_| {});
+        assert!(matches!(graph.compile(), Err(FrameGraphError::Cycle(p)) if p.len() == 2));
+
+        // write -> read -> write of x, the reader sees the first write
+        let mut graph = FrameGraph::new();
+        let x = graph.create_texture("x", desc);
+        let y = graph.create_texture("y", desc);
+        graph.add_pass("write_x", &[], &[x], |_| {});
+        graph.add_pass("read_x", &[x, y], &[], |_| {});
+        graph.add_pass("overwrite_x", &[], &[x], |_| {});
+        graph.add_pass("write_y", &[], &[y], |_| {});
+        graph.add_pass("read_x_again", &[x], &[], |_| {});
+        let compiled = graph.compile().unwrap();
+        assert_eq!(compiled.order, [0, 3, 1, 2, 4]);
+        assert!(compiled.culled.is_empty());
+
+        let mut graph = FrameGraph::new();
+        let x = graph.create_texture("x", desc);
+        graph.add_pass("a", &[x], &[], |_| {});
+        assert!(matches!(
+            graph.compile(),
+            Err(FrameGraphError::ReadBeforeWrite { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resource_pool_set() {
+        let mut set = PoolSet::<u32, u32>::default();
+        let (a, reused) = set.acquire(1, || 10);
+        assert!(!reused);
+        // handed out until released or the frame ends
+        assert_eq!(set.acquire(1, || 11), (11, false));
+        assert!(set.release(&a, 0));
+        assert_eq!(set.acquire(1, || 12), (10, true));
+
+        set.end_frame(0, 2);
+        assert_eq!((set.n_free(), set.in_use.len()), (2, 0));
+        set.end_frame(1, 2);
+        assert_eq!(set.n_free(), 2);
+        set.end_frame(2, 2);
+        assert_eq!(set.n_free(), 0);
+
+        assert_eq!(BufferDesc::new(100, wgpu::BufferUsages::UNIFORM).size, 128);
+    }
+
+    #[test]
+    fn test_uniform_ring_grows() {
+        let mut cursor = SlotCursor {
+            n_slots: 2,
+            next: 0,
+        };
+        assert_eq!(cursor.advance(), (0, false));
+        assert_eq!(cursor.advance(), (1, false));
+        // a third draw in the same frame does not overwrite the first one
+        assert_eq!(cursor.advance(), (0, true));
+        assert_eq!(cursor.n_slots, 4);
+        assert_eq!(cursor.advance(), (1, false));
+
+        // the next frame fits into the grown buffer
+        cursor.next = 0;
+        let slots: Vec<_> = (0..4).map(|_| cursor.advance()).collect();
+        assert_eq!(slots, [(0, false), (1, false), (2, false), (3, false)]);
+        assert_eq!(cursor.advance(), (0, true));
+    }
+}
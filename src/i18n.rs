@@ -0,0 +1,402 @@
+//! translated ui strings, see [`Localization`] and `Context::tr`
+
+use std::fmt;
+
+use crate::{core::HashMap, ui};
+
+/// a value interpolated into a translation, numbers also pick plural variants
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrArg {
+    Num(f64),
+    Str(String),
+}
+
+impl fmt::Display for TrArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Num(n) => write!(f, "{n}"),
+            Self::Str(s) => f.write_str(s),
+        }
+    }
+}
+
+macro_rules! impl_tr_arg_num {
+    ($($ty:ty),*) => {$(
+        impl From<$ty> for TrArg {
+            fn from(n: $ty) -> Self {
+                Self::Num(n as f64)
+            }
+        }
+    )*};
+}
+
+impl_tr_arg_num!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl From<&str> for TrArg {
+    fn from(s: &str) -> Self {
+        Self::Str(s.to_string())
+    }
+}
+
+impl From<String> for TrArg {
+    fn from(s: String) -> Self {
+        Self::Str(s)
+    }
+}
+
+impl From<&String> for TrArg {
+    fn from(s: &String) -> Self {
+        Self::Str(s.clone())
+    }
+}
+
+/// the translations of one language, parsed from a subset of the fluent syntax
+///
+/// ```text
+/// # comment
+/// open = Open
+/// greeting = Hello, { $name }!
+/// unread = { $count ->
+///     [0] No new mail
+///     [one] One new mail
+///    *[other] { $count } new mails
+/// }
+/// ```
+///
+/// multi line messages continue on indented lines. `{ "{" }` escapes a brace
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    pub lang: String,
+    messages: HashMap<String, Vec<Part>>,
+}
+
+/// a piece of a parsed message
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Arg(String),
+    /// `{ $arg -> [key] .. *[default] .. }`
+    Select {
+        arg: String,
+        variants: Vec<(String, Vec<Part>)>,
+        default: usize,
+    },
+}
+
+impl Catalog {
+    pub fn new(lang: &str) -> Self {
+        Self {
+            lang: lang.to_string(),
+            messages: HashMap::new(),
+        }
+    }
+
+    /// parses the messages of an `.ftl` file, invalid messages are logged and skipped
+    pub fn parse(lang: &str, src: &str) -> Self {
+        let mut catalog = Self::new(lang);
+        let mut lines = src.lines().peekable();
+        while let Some(line) = lines.next() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, first)) = line.split_once('=') else {
+                log::warn!("{lang}: expected `key = message`, got `{line}`");
+                continue;
+            };
+            let mut msg = first.trim().to_string();
+            while let Some(next) = lines.next_if(|l| l.starts_with([' ', '\t', '}'])) {
+                if !msg.is_empty() {
+                    msg.push('\n');
+                }
+                msg.push_str(next.trim());
+            }
+            catalog.insert(key.trim(), &msg);
+        }
+        catalog
+    }
+
+    /// adds or replaces a message, e.g. `catalog.insert("greeting", "Hello, { $name }!")`
+    pub fn insert(&mut self, key: &str, msg: &str) {
+        let mut chars = msg.chars().peekable();
+        match parse_pattern(&mut chars, false) {
+            Ok(parts) => {
+                self.messages.insert(key.to_string(), parts);
+            }
+            Err(e) => log::warn!("{}: invalid message `{key}`: {e}", self.lang),
+        }
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.messages.contains_key(key)
+    }
+
+    /// the message `key` with `args` filled in
+    pub fn format(&self, key: &str, args: &[(&str, TrArg)]) -> Option<String> {
+        let parts = self.messages.get(key)?;
+        let mut out = String::new();
+        self.format_parts(parts, args, &mut out);
+        Some(out)
+    }
+
+    fn format_parts(&self, parts: &[Part], args: &[(&str, TrArg)], out: &mut String) {
+        use fmt::Write;
+
+        let arg = |name: &str| args.iter().find(|(n, _)| *n == name).map(|(_, a)| a);
+        for part in parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Arg(name) => match arg(name) {
+                    Some(a) => _ = write!(out, "{a}"),
+                    None => _ = write!(out, "{{${name}}}"),
+                },
+                Part::Select {
+                    arg: name,
+                    variants,
+                    default,
+                } => {
+                    let matches = |key: &str| match arg(name) {
+                        Some(TrArg::Num(n)) => {
+                            key.parse::<f64>() == Ok(*n)
+                                || key == plural_category(&self.lang, *n)
+                        }
+                        Some(TrArg::Str(s)) => key == s,
+                        None => false,
+                    };
+                    // exact numbers take precedence over plural categories
+                    let exact = variants
+                        .iter()
+                        .position(|(k, _)| k.parse::<f64>().is_ok() && matches(k));
+                    let i = exact
+                        .or_else(|| variants.iter().position(|(k, _)| matches(k)))
+                        .unwrap_or(*default);
+                    self.format_parts(&variants[i].1, args, out);
+                }
+            }
+        }
+    }
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+/// text and placeables until the end, or until the end of the line for select variants
+fn parse_pattern(chars: &mut Chars, variant: bool) -> Result<Vec<Part>, String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    while let Some(&c) = chars.peek() {
+        if variant && c == '\n' {
+            break;
+        }
+        chars.next();
+        if c != '{' {
+            text.push(c);
+            continue;
+        }
+        skip_ws(chars);
+        match chars.peek() {
+            Some('"') => {
+                chars.next();
+                text.extend(chars.by_ref().take_while(|&c| c != '"'));
+                skip_ws(chars);
+                expect(chars, '}')?;
+            }
+            Some('$') => {
+                chars.next();
+                let name = ident(chars);
+                skip_ws(chars);
+                if !text.is_empty() {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                }
+                if chars.next_if_eq(&'-').is_some() {
+                    expect(chars, '>')?;
+                    parts.push(parse_select(chars, name)?);
+                } else {
+                    expect(chars, '}')?;
+                    parts.push(Part::Arg(name));
+                }
+            }
+            c => return Err(format!("unexpected {c:?} in placeable")),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    Ok(parts)
+}
+
+/// the variants after `->` up to the closing brace
+fn parse_select(chars: &mut Chars, arg: String) -> Result<Part, String> {
+    let mut variants = Vec::new();
+    let mut default = None;
+    loop {
+        skip_ws(chars);
+        match chars.next() {
+            Some('}') => break,
+            Some('*') => {
+                default = Some(variants.len());
+                expect(chars, '[')?;
+            }
+            Some('[') => (),
+            c => return Err(format!("expected a variant, got {c:?}")),
+        }
+        let key: String = chars.by_ref().take_while(|&c| c != ']').collect();
+        while chars.next_if(|&c| c == ' ' || c == '\t').is_some() {}
+        variants.push((key.trim().to_string(), parse_pattern(chars, true)?));
+    }
+    let default = default.ok_or_else(|| format!("`${arg}` select has no default variant"))?;
+    Ok(Part::Select {
+        arg,
+        variants,
+        default,
+    })
+}
+
+fn skip_ws(chars: &mut Chars) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn ident(chars: &mut Chars) -> String {
+    let mut name = String::new();
+    while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_') {
+        name.push(c);
+    }
+    name
+}
+
+fn expect(chars: &mut Chars, c: char) -> Result<(), String> {
+    match chars.next() {
+        Some(n) if n == c => Ok(()),
+        n => Err(format!("expected {c:?}, got {n:?}")),
+    }
+}
+
+/// the cldr plural category of `n` in `lang`, for the common languages. other languages
+/// use the english rules
+pub fn plural_category(lang: &str, n: f64) -> &'static str {
+    let base = lang
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(lang)
+        .to_ascii_lowercase();
+    let int = n.fract() == 0.0;
+    let (i, i10, i100) = (n.abs() as u64, n.abs() as u64 % 10, n.abs() as u64 % 100);
+    match base.as_str() {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "tr" => "other",
+        "fr" | "pt" if (0.0..2.0).contains(&n.abs()) => "one",
+        "ru" | "uk" | "be" | "pl" if !int => "other",
+        "ru" | "uk" | "be" if i10 == 1 && i100 != 11 => "one",
+        "pl" if i == 1 => "one",
+        "ru" | "uk" | "be" | "pl" if (2..=4).contains(&i10) && !(12..=14).contains(&i100) => "few",
+        "ru" | "uk" | "be" | "pl" => "many",
+        "cs" | "sk" if int && i == 1 => "one",
+        "cs" | "sk" if int && (2..=4).contains(&i) => "few",
+        "cs" | "sk" if !int => "many",
+        "fr" | "pt" | "cs" | "sk" => "other",
+        _ if int && i == 1 => "one",
+        _ => "other",
+    }
+}
+
+/// the catalogs and the current language of a context, see [`ui::Context::tr`]
+#[derive(Debug, Clone)]
+pub struct Localization {
+    catalogs: HashMap<String, Catalog>,
+    language: String,
+    /// used for keys missing in the current language
+    pub fallback: String,
+    /// frames left to redraw after a language switch, so auto sized panels fit the new text
+    relayout: u8,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self {
+            catalogs: HashMap::new(),
+            language: "en".into(),
+            fallback: "en".into(),
+            relayout: 0,
+        }
+    }
+}
+
+impl Localization {
+    /// merges `catalog` into the catalog of its language
+    pub fn add_catalog(&mut self, catalog: Catalog) {
+        match self.catalogs.get_mut(&catalog.lang) {
+            Some(c) => c.messages.extend(catalog.messages),
+            None => {
+                self.catalogs.insert(catalog.lang.clone(), catalog);
+            }
+        }
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn set_language(&mut self, lang: &str) {
+        if self.language != lang {
+            self.language = lang.to_string();
+            self.relayout = 2;
+        }
+    }
+
+    /// the languages with a catalog
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.catalogs.keys().map(String::as_str)
+    }
+
+    /// the message in the current language, then in the fallback language, then the key
+    pub fn tr(&self, key: &str, args: &[(&str, TrArg)]) -> String {
+        [&self.language, &self.fallback]
+            .into_iter()
+            .filter_map(|lang| self.catalogs.get(lang.as_str()))
+            .find_map(|c| c.format(key, args))
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+impl ui::Context {
+    /// the translation of `key` in the current language, see [`Catalog`] and [`crate::tr!`]
+    ///
+    /// widget ids are derived from labels, so the state of a widget with a translated label
+    /// is reset by a language switch unless it is inside a [`Self::keyed`] scope
+    pub fn tr(&self, key: &str, args: &[(&str, TrArg)]) -> String {
+        self.i18n.tr(key, args)
+    }
+
+    pub fn add_catalog(&mut self, catalog: Catalog) {
+        self.i18n.add_catalog(catalog);
+    }
+
+    pub fn language(&self) -> &str {
+        self.i18n.language()
+    }
+
+    /// switches the language of [`Self::tr`], the ui is laid out again with the new text
+    pub fn set_language(&mut self, lang: &str) {
+        self.i18n.set_language(lang);
+        self.update_relayout();
+    }
+
+    /// keeps redrawing until panels sized to their content were laid out with the new
+    /// language, called at the end of every frame
+    pub(crate) fn update_relayout(&mut self) {
+        if self.i18n.relayout > 0 {
+            self.i18n.relayout -= 1;
+            self.window.request_redraw();
+        }
+    }
+}
+
+/// looks up a translated string, see [`ui::Context::tr`]
+///
+/// ```ignore
+/// ui.button(&tr!(ui, "open"));
+/// ui.text(&tr!(ui, "unread", count = mails.len()));
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($ui:expr, $key:expr $(, $arg:ident = $val:expr)* $(,)?) => {
+        $ui.tr($key, &[$((stringify!($arg), $crate::TrArg::from($val))),*])
+    };
+}
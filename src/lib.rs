@@ -1,24 +1,126 @@
-pub mod app;
+mod accessibility;
+#[cfg(feature = "winit")]
+mod app;
+mod clipboard;
 mod core;
 mod gpu;
+mod i18n;
 mod mouse;
-pub mod rect;
+mod particles;
+mod platform;
+mod rect;
+mod settings;
 mod ui;
+mod ui_attention;
+mod ui_code;
+mod ui_compose;
 mod ui_context;
+mod ui_curve;
+mod ui_deferred;
+mod ui_effect;
+#[cfg(feature = "chrono")]
+mod ui_date;
+#[cfg(feature = "demo")]
+mod ui_demo;
+mod ui_form;
+mod ui_gizmo;
+mod ui_gradient;
+mod ui_inspect;
 mod ui_items;
+mod ui_occlusion;
+mod ui_palette;
 mod ui_panel;
+mod ui_picking;
+mod ui_plot;
+mod ui_raster;
+mod ui_select;
+mod ui_selection;
+mod ui_shader_canvas;
+mod ui_skeleton;
+mod ui_snapshot;
+mod ui_svg;
 
 use std::sync::Arc;
 
-use core::RGBA;
 use glam::Vec4;
-use gpu::{VertexDesc, WGPU};
+use gpu::WGPU;
 use wgpu::util::DeviceExt;
 
-pub extern crate self as wgpui;
+extern crate self as wgpui;
 
-pub use gpu::AsVertexFormat;
-pub use gpu::Vertex;
+// BEGIN PUBLIC API
+//---------------------------------------------------------------------------------------
+
+#[cfg(feature = "winit")]
+pub use app::{App, AppHandler, AppSetup, ClearScreen, run, run_app};
+pub use crate::clipboard::Clipboard;
+#[allow(deprecated)]
+pub use core::hex_to_rgba;
+pub use core::{Axis, Dir, HSLA, HSVA, HexError, OKLAB, OKLCH, RGB, RGBA};
+pub use gpu::{
+    AsVertexFormat, Background, BackgroundFn, BackgroundVertex, BindGroupCache, BindResource,
+    BufferDesc, Camera2D, CapturedImage, CompiledGraph, DrawConstants, FrameGraph, FrameGraphError,
+    FrameRecorder, FrameSink, GraphTexture, PassContext, PickVertex, PickingBuffer, PipelineBuilder,
+    PipelineKey, PoolStats, RenderPassHandle, RenderTarget, ResourcePool, ShaderBuildConfig,
+    ShaderHandle, ShaderMismatch, Snorm16x2, Snorm16x4, Snorm8x2, Snorm8x4, Sprite, SpriteBatch,
+    StreamingTexture, Texture, TextureDesc, UniformRing, Unorm16x2, Unorm16x4, Unorm8x2, Unorm8x4,
+    Vertex, VertexDesc, WGPUHandle, WgslType, Window, WindowId, validate_shader, vertex_format_wgsl,
+    wgsl_paddings, wgsl_round_up, wgsl_struct_align, wgsl_struct_size,
+};
+pub use i18n::{Catalog, Localization, TrArg, plural_category};
+pub use mouse::{
+    CursorIcon, ImeEvent, InputEvent, InputSettings, KeyCode, Modifiers, MouseBtn, Shortcut,
+};
+pub use platform::{
+    HeadlessWindow, MonitorInfo, PlatformWindow, UserAttention, WindowIcon, WindowPlacement,
+};
+pub use rect::{Rect, almost_equal};
+pub use settings::{MonitorUiScale, Settings};
+pub use ui::{
+    Anchor, AnchorTarget, Attention, ClipShape, CanvasUniform, CodeLanguage, CodeToken,
+    Colormap, Command, CommandPalette, CompactVertex, Context, CornerRadii, Curve, CurveEditor,
+    CurveInterp, CurveKey, DeferredItem, DeferredQueue, DeferredSender, DeferredShape,
+    DeferredUi, DrawComposer, DrawList, DrawOutput, DrawRect, DrawableRects, EdgePanel,
+    EdgePanelState, EffectId, EffectPipelineFn, FloatingPanel, Form, Frame, GizmoPart,
+    GizmoResponse, Gradient, GradientInterp, GradientStop, HeatmapPlot, HitLayer, Id, Inspect,
+    InspectRange, ItemPainter, LayerTransform, LayoutDir, LayoutState, LineMode,
+    MAX_CANVAS_PARAMS, MAX_TEXT_LEN, MONO_FONT, Occlusion, Margins, Outline, OutlinePlacement,
+    Painter, PanelAnchor, PanelBudget, PanelFill, PanelFlag, PanelLayout, PanelStats,
+    PanelStyle, Picking, RectGizmo, Response, Selection, Sense, Shadow, ShapedText, SidePanel,
+    Signal, SoftRasterizer, StencilMode, StreamBuffer, StreamPlot, Stroke, StyleField,
+    StyleScope, StyleTable, StyleVar, SvgWriter, TextAlign, TextInputFlags, TextWrap, TextureId,
+    TopBottomPanel, Transform2D, TransformGizmo, UiEffect, Validator, Viewport, Widget,
+    fuzzy_match, highlight_line, line_primitives, sanitize_text, set_draw_validation,
+    tessellate_convex_fill, tessellate_line,
+};
+#[cfg(feature = "chrono")]
+pub use ui::DateRange;
+
+pub use accessibility::{AccessAction, AccessNode, AccessRole, AccessTree, TextPos};
+pub use particles::{Emitter, Particle, ParticleSystem};
+
+pub use macros::{Inspect, rgba, vertex, wgsl};
+
+/// the gpu state (instance, device, queue) shared by all windows
+pub type Renderer = WGPU;
+
+/// everything needed to build and run a ui
+///
+/// ```ignore
+/// use wgpui::prelude::*;
+/// ```
+pub mod prelude {
+    pub use crate::{
+        Context, CornerRadii, Frame, Id, Margins, RGBA, Rect, Renderer, Sense, Signal, StyleVar,
+        TextAlign, Texture, Vertex, Widget, rgba, vertex, wgsl,
+    };
+    #[cfg(feature = "winit")]
+    pub use crate::run;
+    pub use glam::{Vec2, vec2};
+}
+
+// END PUBLIC API
+//---------------------------------------------------------------------------------------
 
 #[macros::vertex]
 pub struct VertexPosCol {
@@ -38,3 +140,17 @@ macro_rules! build {
     }};
 }
 pub(crate) use build;
+
+/// a profiler scope until the end of the enclosing block, only recorded with the `profiling`
+/// feature. `data` is attached to the scope, e.g. the label of a render pass
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profiling")]
+        profiling::scope!($name);
+    };
+    ($name:expr, $data:expr) => {
+        #[cfg(feature = "profiling")]
+        profiling::scope!($name, $data);
+    };
+}
+pub(crate) use profile_scope;
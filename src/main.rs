@@ -20,7 +20,5 @@ fn main() {
             .init();
     }
 
-    let event_loop = winit::event_loop::EventLoop::new().unwrap();
-    let mut app = wgpui::app::AppSetup::default();
-    event_loop.run_app(&mut app).unwrap();
+    wgpui::run();
 }
@@ -2,7 +2,7 @@ use std::{fmt, ops};
 
 use glam::Vec2;
 
-use crate::core::{Duration, Instant};
+use crate::core::{Duration, HashMap};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MouseBtn {
@@ -28,11 +28,47 @@ impl<T> ops::IndexMut<MouseBtn> for PerButton<T> {
     }
 }
 
+/// timing and distance thresholds of the input handling, can be changed at runtime
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputSettings {
+    /// max time between the clicks of a double or triple click
+    pub double_click_time: Duration,
+    /// max press duration that still counts as a click
+    pub click_time: Duration,
+    /// distance in points the mouse has to move while pressed to start a drag
+    pub drag_threshold: f32,
+    /// time a key has to be held before it starts repeating
+    pub key_repeat_delay: Duration,
+    /// time between two repeats of a held key
+    pub key_repeat_interval: Duration,
+    pub scroll_speed: f32,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            double_click_time: Duration::from_millis(400),
+            click_time: Duration::from_millis(200),
+            drag_threshold: 5.0,
+            key_repeat_delay: Duration::from_millis(500),
+            key_repeat_interval: Duration::from_millis(33),
+            scroll_speed: 1.0,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct MouseState {
     pub pos: Vec2,
     pub prev_pos: Vec2,
+    /// movement since the last frame
+    pub delta: Vec2,
+    /// wheel movement since the last frame
+    pub scroll: Vec2,
     pub buttons: PerButton<ButtonState>,
+    /// clock time used for click and drag timing, see [`crate::core::Clock`]
+    pub time: Duration,
 }
 
 impl MouseState {
@@ -40,13 +76,29 @@ impl MouseState {
         Self {
             pos: Vec2::NAN,
             prev_pos: Vec2::NAN,
+            delta: Vec2::ZERO,
+            scroll: Vec2::ZERO,
             buttons: PerButton([ButtonState::new(); 3]),
+            time: Duration::ZERO,
+        }
+    }
+
+    /// `scale_factor` converts the drag threshold from points to pixels
+    pub fn apply_settings(&mut self, settings: &InputSettings, scale_factor: f32) {
+        for b in [MouseBtn::Left, MouseBtn::Right, MouseBtn::Middle] {
+            let b = &mut self.buttons[b];
+            b.click_threshold = settings.click_time;
+            b.drag_threshold = settings.drag_threshold * scale_factor;
+            b.multi_click_timeout = settings.double_click_time;
         }
     }
 
     pub fn set_mouse_pos(&mut self, x: f32, y: f32) {
         self.prev_pos = self.pos;
         self.pos = Vec2::new(x, y);
+        if self.prev_pos.is_finite() {
+            self.delta += self.pos - self.prev_pos;
+        }
 
         for b in [MouseBtn::Left, MouseBtn::Right, MouseBtn::Middle] {
             self.buttons[b].update_pos(self.pos);
@@ -63,7 +115,7 @@ impl MouseState {
     }
 
     pub fn set_button_press(&mut self, button: MouseBtn, pressed: bool) {
-        self.buttons[button].set_press(self.pos, pressed);
+        self.buttons[button].set_press(self.pos, pressed, self.time);
     }
 
     pub fn released(&self, btn: MouseBtn) -> bool {
@@ -90,6 +142,10 @@ impl MouseState {
         self.buttons[btn].double_clicked()
     }
 
+    pub fn triple_pressed(&self, btn: MouseBtn) -> bool {
+        self.buttons[btn].triple_pressed()
+    }
+
     pub fn triple_clicked(&self, btn: MouseBtn) -> bool {
         self.buttons[btn].triple_clicked()
     }
@@ -112,8 +168,10 @@ impl MouseState {
 
     pub fn end_frame(&mut self) {
         for b in [MouseBtn::Left, MouseBtn::Right, MouseBtn::Middle] {
-            self.buttons[b].end_frame();
+            self.buttons[b].end_frame(self.time);
         }
+        self.delta = Vec2::ZERO;
+        self.scroll = Vec2::ZERO;
     }
 
     pub fn reset(&mut self) {
@@ -142,9 +200,9 @@ impl fmt::Display for MouseState {
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ButtonState {
-    pub last_press_time: Instant,
-    pub last_release_time: Option<Instant>,
-    pub click_count: Option<(u16, Instant)>,
+    pub last_press_time: Duration,
+    pub last_release_time: Option<Duration>,
+    pub click_count: Option<(u16, Duration)>,
     pub pressed: bool,
     pub just_pressed: bool,
     pub released: bool,
@@ -193,7 +251,7 @@ impl fmt::Display for ButtonState {
 impl ButtonState {
     pub fn new() -> Self {
         Self {
-            last_press_time: Instant::now(),
+            last_press_time: Duration::ZERO,
             last_release_time: None,
             released: false,
             click_count: None,
@@ -207,13 +265,12 @@ impl ButtonState {
         }
     }
 
-    pub fn end_frame(&mut self) {
+    pub fn end_frame(&mut self, now: Duration) {
         self.released = false;
         self.just_pressed = false;
 
-        let now = Instant::now();
         if let Some((_, click_time)) = self.click_count {
-            if now.duration_since(click_time) > self.multi_click_timeout {
+            if now.saturating_sub(click_time) > self.multi_click_timeout {
                 self.click_count = None;
             }
         }
@@ -232,9 +289,7 @@ impl ButtonState {
         }
     }
 
-    pub fn set_press(&mut self, pos: Vec2, press: bool) {
-        let now = Instant::now();
-
+    pub fn set_press(&mut self, pos: Vec2, press: bool, now: Duration) {
         if press && !self.pressed {
             // Button just pressed
             self.pressed = true;
@@ -249,7 +304,7 @@ impl ButtonState {
             self.just_pressed = false;
             self.last_release_time = Some(now);
 
-            let press_duration = now.duration_since(self.last_press_time);
+            let press_duration = now.saturating_sub(self.last_press_time);
             let is_quick_press = press_duration < self.click_threshold;
             let is_within_drag_threshold = self
                 .press_start_pos
@@ -266,14 +321,14 @@ impl ButtonState {
         }
     }
 
-    fn add_click(&mut self, click_time: Instant) {
+    fn add_click(&mut self, click_time: Duration) {
         match self.click_count {
             None => {
                 self.click_count = Some((1, click_time));
             }
             Some((count, first_click_time)) => {
                 // Check if this click is within the multi-click timeout
-                if click_time.duration_since(first_click_time) < self.multi_click_timeout {
+                if click_time.saturating_sub(first_click_time) < self.multi_click_timeout {
                     self.click_count = Some((count + 1, first_click_time));
                 } else {
                     // Start a new click sequence
@@ -299,8 +354,12 @@ impl ButtonState {
         self.get_click_count() == 2 && self.released == true
     }
 
+    pub fn triple_pressed(&self) -> bool {
+        self.get_click_count() == 2 && self.pressed
+    }
+
     pub fn triple_clicked(&self) -> bool {
-        self.get_click_count() == 2 && self.released == true
+        self.get_click_count() == 3 && self.released == true
     }
 
     pub fn update_pos(&mut self, pos: Vec2) {
@@ -321,11 +380,11 @@ impl ButtonState {
         }
     }
 
-    pub fn get_press_duration(&self) -> Option<Duration> {
+    pub fn get_press_duration(&self, now: Duration) -> Option<Duration> {
         if self.pressed {
-            Some(Instant::now().duration_since(self.last_press_time))
+            Some(now.saturating_sub(self.last_press_time))
         } else if let Some(release_time) = self.last_release_time {
-            Some(release_time.duration_since(self.last_press_time))
+            Some(release_time.saturating_sub(self.last_press_time))
         } else {
             None
         }
@@ -339,6 +398,237 @@ impl ButtonState {
     }
 }
 
+macro_rules! key_codes {
+    ($($key:ident),* $(,)?) => {
+        /// physical key position, named after the key on a us layout
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum KeyCode {
+            $($key,)*
+        }
+
+        #[cfg(feature = "winit")]
+        impl KeyCode {
+            pub fn from_winit(code: winit::keyboard::KeyCode) -> Option<Self> {
+                use winit::keyboard::KeyCode as WKC;
+                match code {
+                    $(WKC::$key => Some(Self::$key),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+key_codes!(
+    Backquote, Backslash, BracketLeft, BracketRight, Comma, Equal, Minus, Period, Quote,
+    Semicolon, Slash,
+    Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+    KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM,
+    KeyN, KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+    AltLeft, AltRight, ControlLeft, ControlRight, ShiftLeft, ShiftRight, SuperLeft, SuperRight,
+    Backspace, CapsLock, ContextMenu, Enter, Space, Tab, Escape,
+    Delete, End, Home, Insert, PageDown, PageUp,
+    ArrowDown, ArrowLeft, ArrowRight, ArrowUp,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    NumpadAdd, NumpadSubtract, NumpadMultiply, NumpadDivide, NumpadDecimal, NumpadEnter,
+);
+
+macros::flags!(Modifiers:
+    SHIFT,
+    CONTROL,
+    ALT,
+    SUPER,
+);
+
+impl Modifiers {
+    pub fn shift_key(self) -> bool {
+        self.has(Self::SHIFT)
+    }
+
+    pub fn control_key(self) -> bool {
+        self.has(Self::CONTROL)
+    }
+
+    pub fn alt_key(self) -> bool {
+        self.has(Self::ALT)
+    }
+
+    pub fn super_key(self) -> bool {
+        self.has(Self::SUPER)
+    }
+}
+
+#[cfg(feature = "winit")]
+impl From<winit::keyboard::ModifiersState> for Modifiers {
+    fn from(m: winit::keyboard::ModifiersState) -> Self {
+        let mut res = Self::NONE;
+        res.set(Self::SHIFT, m.shift_key());
+        res.set(Self::CONTROL, m.control_key());
+        res.set(Self::ALT, m.alt_key());
+        res.set(Self::SUPER, m.super_key());
+        res
+    }
+}
+
+/// key combination, e.g. `Shortcut::new(Modifiers::CONTROL, KeyCode::KeyP)` for ctrl + p
+///
+/// the modifiers have to match exactly, `Ctrl+P` is not triggered by `Ctrl+Shift+P`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    pub key: KeyCode,
+}
+
+impl Shortcut {
+    pub const fn new(modifiers: Modifiers, key: KeyCode) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// the key without modifiers
+    pub const fn key(key: KeyCode) -> Self {
+        Self::new(Modifiers::NONE, key)
+    }
+}
+
+impl fmt::Display for Shortcut {
+    /// e.g. `Ctrl+Shift+P`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (flag, name) in [
+            (Modifiers::CONTROL, "Ctrl"),
+            (Modifiers::ALT, "Alt"),
+            (Modifiers::SHIFT, "Shift"),
+            (Modifiers::SUPER, "Super"),
+        ] {
+            if self.modifiers.has(flag) {
+                write!(f, "{name}+")?;
+            }
+        }
+        let key = format!("{:?}", self.key);
+        let key = key.strip_prefix("Key").or(key.strip_prefix("Digit")).unwrap_or(&key);
+        write!(f, "{key}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HeldKey {
+    next_repeat: Duration,
+    just_pressed: bool,
+    repeated: bool,
+}
+
+/// keys that are currently held down
+///
+/// repeats are generated from the [`InputSettings`] instead of the os key repeat, which is
+/// delivered inconsistently across platforms
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardState {
+    held: HashMap<KeyCode, HeldKey>,
+    /// keys that were pressed and released before the next frame
+    released: Vec<KeyCode>,
+}
+
+impl KeyboardState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// os repeats of an already held key are ignored
+    pub fn set_key_press(&mut self, key: KeyCode, pressed: bool, now: Duration, settings: &InputSettings) {
+        if !pressed {
+            if self.held.get(&key).is_some_and(|k| k.just_pressed) {
+                self.released.push(key);
+            } else {
+                self.held.remove(&key);
+            }
+            return;
+        }
+
+        self.held.entry(key).or_insert(HeldKey {
+            next_repeat: now + settings.key_repeat_delay,
+            just_pressed: true,
+            repeated: false,
+        });
+    }
+
+    /// generates the repeats of held keys, called once at the start of a frame
+    pub fn update(&mut self, now: Duration, settings: &InputSettings) {
+        let interval = settings.key_repeat_interval.max(Duration::from_millis(1));
+
+        for k in self.held.values_mut() {
+            if !k.just_pressed && now >= k.next_repeat {
+                k.repeated = true;
+                // only one repeat per frame, skip the ones we missed
+                while k.next_repeat <= now {
+                    k.next_repeat += interval;
+                }
+            }
+        }
+    }
+
+    pub fn end_frame(&mut self) {
+        for key in self.released.drain(..) {
+            self.held.remove(&key);
+        }
+
+        for k in self.held.values_mut() {
+            k.just_pressed = false;
+            k.repeated = false;
+        }
+    }
+
+    pub fn pressed(&self, key: KeyCode) -> bool {
+        self.held.contains_key(&key)
+    }
+
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.held.get(&key).is_some_and(|k| k.just_pressed)
+    }
+
+    /// true on the first frame the key is pressed and on every repeat while it is held
+    pub fn pressed_with_repeat(&self, key: KeyCode) -> bool {
+        self.held
+            .get(&key)
+            .is_some_and(|k| k.just_pressed || k.repeated)
+    }
+
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.released.clear();
+    }
+}
+
+/// input method editor events, used for composing text in languages like chinese or japanese
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImeEvent {
+    Enabled,
+    /// text that is still being composed, with the byte range of the cursor in it
+    Preedit(String, Option<(usize, usize)>),
+    /// the finished text
+    Commit(String),
+    Disabled,
+}
+
+/// platform independent input event, see [`Context::on_event`](crate::Context::on_event)
+///
+/// positions are in physical pixels relative to the top left of the window
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    PointerMoved(Vec2),
+    PointerButton { button: MouseBtn, pressed: bool },
+    /// scroll delta in pixels
+    Wheel(Vec2),
+    /// `code` is `None` for keys without a [`KeyCode`]
+    Key { code: Option<KeyCode>, pressed: bool },
+    /// text produced by the keyboard, control characters are ignored
+    Text(String),
+    Ime(ImeEvent),
+    Modifiers(Modifiers),
+    Focused(bool),
+}
+
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum CursorIcon {
     #[default]
@@ -358,6 +648,8 @@ pub enum CursorIcon {
 
     MoveH,
     MoveV,
+
+    Crosshair,
 }
 
 impl CursorIcon {
@@ -376,6 +668,7 @@ impl CursorIcon {
     }
 }
 
+#[cfg(feature = "winit")]
 impl From<CursorIcon> for winit::window::Cursor {
     fn from(value: CursorIcon) -> Self {
         use CursorIcon as CI;
@@ -394,32 +687,91 @@ impl From<CursorIcon> for winit::window::Cursor {
             CI::ResizeNW => WCI::NwResize,
             CI::MoveH => WCI::EwResize,
             CI::MoveV => WCI::NsResize,
+            CI::Crosshair => WCI::Crosshair,
         }
         .into()
     }
 }
 
-pub struct Clipboard {
-    // pub repr: arboard::Clipboard,
-    pub repr: clipboard::ClipboardContext,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl Clipboard {
-    pub fn new() -> Self {
-        let repr = clipboard::ClipboardProvider::new().unwrap();
-        Self { repr }
-    }
+    #[test]
+    fn test_key_repeat() {
+        let settings = InputSettings {
+            key_repeat_delay: Duration::from_millis(500),
+            key_repeat_interval: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let ms = Duration::from_millis;
+        let key = KeyCode::ArrowLeft;
+        let mut kb = KeyboardState::new();
+
+        let frame = |kb: &mut KeyboardState, t: u64| {
+            kb.update(ms(t), &settings);
+            let pressed = kb.pressed_with_repeat(key);
+            kb.end_frame();
+            pressed
+        };
 
-    pub fn get_text(&mut self) -> Option<String> {
-        use clipboard::ClipboardProvider;
-        self.repr.get_contents().ok()
-        // self.repr.get_text().ok()
+        kb.set_key_press(key, true, ms(0), &settings);
+        assert!(frame(&mut kb, 0));
+        assert!(!frame(&mut kb, 400));
+        // os repeats are ignored
+        kb.set_key_press(key, true, ms(450), &settings);
+        assert!(!frame(&mut kb, 450));
+        assert!(frame(&mut kb, 500));
+        assert!(!frame(&mut kb, 550));
+        // a long frame only produces a single repeat
+        assert!(frame(&mut kb, 900));
+        assert!(!frame(&mut kb, 950));
+
+        kb.set_key_press(key, false, ms(960), &settings);
+        assert!(!frame(&mut kb, 1000));
+        assert!(!kb.pressed(key));
+
+        // pressed and released between two frames still registers
+        kb.set_key_press(key, true, ms(1010), &settings);
+        kb.set_key_press(key, false, ms(1020), &settings);
+        assert!(frame(&mut kb, 1030));
+        assert!(!kb.pressed(key));
+    }
+
+    #[test]
+    fn test_multi_click() {
+        let mut b = ButtonState::new();
+        let ms = Duration::from_millis;
+
+        let click = |b: &mut ButtonState, t: u64| {
+            b.set_press(Vec2::ZERO, true, ms(t));
+            let pressed = (b.double_pressed(), b.triple_pressed());
+            b.set_press(Vec2::ZERO, false, ms(t + 20));
+            pressed
+        };
+
+        assert_eq!(click(&mut b, 0), (false, false));
+        assert_eq!(click(&mut b, 50), (true, false));
+        assert!(b.double_clicked());
+        assert_eq!(click(&mut b, 100), (false, true));
+        assert!(b.triple_clicked());
+
+        // too slow for a multi click
+        b.end_frame(ms(1000));
+        assert_eq!(click(&mut b, 1000), (false, false));
     }
 
-    pub fn set_text(&mut self, text: &str) {
-        use clipboard::ClipboardProvider;
-        if let Err(e) = self.repr.set_contents(text.to_string()) {
-            log::warn!("{e}")
-        }
+    #[test]
+    fn test_mouse_frame_delta() {
+        let mut m = MouseState::new();
+        m.set_mouse_pos(10.0, 10.0);
+        assert_eq!(m.delta, Vec2::ZERO);
+
+        m.set_mouse_pos(15.0, 12.0);
+        m.set_mouse_pos(20.0, 8.0);
+        assert_eq!(m.delta, Vec2::new(10.0, -2.0));
+
+        m.end_frame();
+        assert_eq!(m.delta, Vec2::ZERO);
     }
 }
@@ -0,0 +1,298 @@
+use std::f32::consts::TAU;
+
+use glam::Vec2;
+
+use crate::{
+    core::{self, RGBA},
+    gpu::{self, Sprite, SpriteBatch},
+    ui::{DrawList, DrawableRects},
+};
+
+fn rand_range(min: f32, max: f32) -> f32 {
+    min + (max - min) * core::rand_f32()
+}
+
+/// parameters used to spawn and animate particles
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Emitter {
+    pub pos: Vec2,
+    /// particles spawned per second
+    pub rate: f32,
+    pub max_particles: usize,
+
+    /// lifetime range in seconds
+    pub lifetime: (f32, f32),
+    /// initial speed range
+    pub speed: (f32, f32),
+    /// emit direction in radians
+    pub direction: f32,
+    /// angle in radians around `direction` in which particles are emitted
+    pub spread: f32,
+    /// particles are spawned at a random position inside this radius
+    pub radius: f32,
+
+    pub gravity: Vec2,
+    /// fraction of the velocity lost per second
+    pub drag: f32,
+    /// angular velocity range in radians per second
+    pub spin: (f32, f32),
+
+    /// size at the start and the end of the lifetime
+    pub size: (f32, f32),
+    /// color at the start and the end of the lifetime
+    pub col: (RGBA, RGBA),
+}
+
+impl Emitter {
+    pub fn new(pos: Vec2) -> Self {
+        Self {
+            pos,
+            rate: 50.0,
+            max_particles: 1024,
+            lifetime: (1.0, 2.0),
+            speed: (50.0, 100.0),
+            direction: -TAU / 4.0,
+            spread: TAU,
+            radius: 0.0,
+            gravity: Vec2::ZERO,
+            drag: 0.0,
+            spin: (0.0, 0.0),
+            size: (6.0, 0.0),
+            col: (RGBA::WHITE, RGBA::rgba(255, 255, 255, 0)),
+        }
+    }
+
+    /// short burst of colorful particles falling down, meant for [`ParticleSystem::burst`]
+    pub fn confetti(pos: Vec2) -> Self {
+        Self::new(pos)
+            .rate(0.0)
+            .lifetime(1.5, 2.5)
+            .speed(150.0, 350.0)
+            .direction(-TAU / 4.0, TAU / 4.0)
+            .gravity(Vec2::new(0.0, 400.0))
+            .drag(1.0)
+            .spin(-TAU, TAU)
+            .size(8.0, 6.0)
+            .col(RGBA::SAFFRON, RGBA::FOLLY)
+    }
+
+    pub fn rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn max_particles(mut self, max: usize) -> Self {
+        self.max_particles = max;
+        self
+    }
+
+    pub fn lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+
+    pub fn speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max);
+        self
+    }
+
+    pub fn direction(mut self, direction: f32, spread: f32) -> Self {
+        self.direction = direction;
+        self.spread = spread;
+        self
+    }
+
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn gravity(mut self, gravity: Vec2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    pub fn spin(mut self, min: f32, max: f32) -> Self {
+        self.spin = (min, max);
+        self
+    }
+
+    pub fn size(mut self, start: f32, end: f32) -> Self {
+        self.size = (start, end);
+        self
+    }
+
+    pub fn col(mut self, start: RGBA, end: RGBA) -> Self {
+        self.col = (start, end);
+        self
+    }
+
+    fn spawn(&self) -> Particle {
+        let angle = self.direction + rand_range(-0.5, 0.5) * self.spread;
+        let offset = Vec2::from_angle(rand_range(0.0, TAU)) * self.radius * core::rand_f32().sqrt();
+
+        Particle {
+            pos: self.pos + offset,
+            vel: Vec2::from_angle(angle) * rand_range(self.speed.0, self.speed.1),
+            rotation: rand_range(0.0, TAU),
+            spin: rand_range(self.spin.0, self.spin.1),
+            age: 0.0,
+            lifetime: rand_range(self.lifetime.0, self.lifetime.1).max(f32::EPSILON),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub rotation: f32,
+    pub spin: f32,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Particle {
+    /// normalized age in `0..=1`
+    pub fn t(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+/// cpu simulated particles
+///
+/// can be drawn into a ui drawlist with `ui.draw(&particles)` or pushed into a [`SpriteBatch`]
+/// to be rendered instanced below the ui
+#[derive(Debug, Clone)]
+pub struct ParticleSystem {
+    pub emitter: Emitter,
+    /// spawn new particles every update
+    pub emitting: bool,
+    pub particles: Vec<Particle>,
+    spawn_acc: f32,
+}
+
+impl ParticleSystem {
+    pub fn new(emitter: Emitter) -> Self {
+        Self {
+            emitter,
+            emitting: true,
+            particles: Vec::new(),
+            spawn_acc: 0.0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.spawn_acc = 0.0;
+    }
+
+    /// spawns `n` particles at once
+    pub fn burst(&mut self, n: usize) {
+        let n = n.min(self.emitter.max_particles.saturating_sub(self.particles.len()));
+        self.particles.extend((0..n).map(|_| self.emitter.spawn()));
+    }
+
+    /// advances the simulation by `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        let e = &self.emitter;
+        let damping = (1.0 - e.drag * dt).max(0.0);
+
+        self.particles.retain_mut(|p| {
+            p.age += dt;
+            if p.age >= p.lifetime {
+                return false;
+            }
+
+            p.vel = (p.vel + e.gravity * dt) * damping;
+            p.pos += p.vel * dt;
+            p.rotation += p.spin * dt;
+            true
+        });
+
+        if self.emitting && e.rate > 0.0 {
+            self.spawn_acc += e.rate * dt;
+            let n = self.spawn_acc as usize;
+            self.spawn_acc -= n as f32;
+            self.burst(n);
+        }
+    }
+
+    fn particle_size_col(&self, p: &Particle) -> (f32, RGBA) {
+        let t = p.t();
+        let (s0, s1) = self.emitter.size;
+        let (c0, c1) = self.emitter.col;
+        (s0 + (s1 - s0) * t, c0.lerp(c1, t))
+    }
+
+    /// pushes the particles as sprites, `offset` is added to every particle position
+    pub fn push_sprites(&self, batch: &mut SpriteBatch, tex: &gpu::Texture, offset: Vec2, layer: i32) {
+        for p in &self.particles {
+            let (size, col) = self.particle_size_col(p);
+            batch.push(
+                tex,
+                Sprite::new(p.pos + offset, Vec2::splat(size))
+                    .rotation(p.rotation)
+                    .tint(col)
+                    .layer(layer),
+            );
+        }
+    }
+}
+
+impl DrawableRects for &ParticleSystem {
+    fn add_to_drawlist(self, drawlist: &DrawList) {
+        for p in &self.particles {
+            let (size, col) = self.particle_size_col(p);
+            if size <= 0.0 || col.a <= 0.0 {
+                continue;
+            }
+
+            let h = size / 2.0;
+            let (x, y) = (Vec2::from_angle(p.rotation) * h, Vec2::from_angle(p.rotation).perp() * h);
+            let quad = [p.pos - x - y, p.pos + x - y, p.pos + x + y, p.pos - x + y];
+            drawlist.add_convex_poly_filled(&quad, col);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_particle_spawn_and_expire() {
+        let emitter = Emitter::new(Vec2::ZERO).rate(10.0).lifetime(1.0, 1.0).max_particles(8);
+        let mut sys = ParticleSystem::new(emitter);
+
+        sys.update(0.55);
+        assert_eq!(sys.len(), 5);
+
+        // the first 5 particles expire, 10 more are spawned but capped at 8
+        sys.update(1.0);
+        assert_eq!(sys.len(), 8);
+
+        sys.emitting = false;
+        sys.clear();
+        sys.burst(3);
+        assert_eq!(sys.len(), 3);
+        sys.burst(100);
+        assert_eq!(sys.len(), 8);
+
+        sys.update(1.0);
+        assert!(sys.is_empty());
+    }
+}
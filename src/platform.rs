@@ -0,0 +1,672 @@
+use std::{
+    any::Any,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use glam::{UVec2, Vec2};
+use wgpu::rwh;
+
+use crate::{core::Dir, gpu::WindowId, mouse::CursorIcon, rect::Rect};
+
+/// the window operations the [`Context`](crate::Context) needs from the platform
+///
+/// everything except the size has a no-op default, so a host application can embed the ui
+/// in its own window without handing over control of it
+pub trait PlatformWindow: Send + Sync + 'static {
+    fn id(&self) -> WindowId;
+
+    /// size of the client area in physical pixels
+    fn inner_size(&self) -> UVec2;
+
+    fn inner_position(&self) -> Vec2 {
+        Vec2::ZERO
+    }
+
+    fn scale_factor(&self) -> f32 {
+        1.0
+    }
+
+    fn has_focus(&self) -> bool {
+        true
+    }
+
+    fn set_cursor_icon(&self, _icon: CursorIcon) {}
+
+    fn drag_window(&self) {}
+
+    fn drag_resize_window(&self, _dir: Dir) {}
+
+    fn is_maximized(&self) -> bool {
+        false
+    }
+
+    fn set_maximized(&self, _maximized: bool) {}
+
+    fn set_minimized(&self, _minimized: bool) {}
+
+    /// when false the ui draws its own titlebar and handles moving and resizing the window
+    fn is_decorated(&self) -> bool {
+        true
+    }
+
+    fn set_decorations(&self, _decorated: bool) {}
+
+    fn request_inner_size(&self, _size: UVec2) {}
+
+    /// top left corner of the window including its decorations, in physical pixels
+    fn outer_position(&self) -> Vec2 {
+        self.inner_position()
+    }
+
+    fn set_outer_position(&self, _pos: Vec2) {}
+
+    /// the monitor the window is mostly on
+    fn current_monitor(&self) -> Option<MonitorInfo> {
+        None
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorInfo> {
+        None
+    }
+
+    fn available_monitors(&self) -> Vec<MonitorInfo> {
+        Vec::new()
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        false
+    }
+
+    /// borderless fullscreen on the current monitor
+    fn set_fullscreen(&self, _fullscreen: bool) {}
+
+    /// `None` restores the default icon
+    fn set_window_icon(&self, _icon: Option<&WindowIcon>) {}
+
+    /// flashes the taskbar entry or bounces the dock icon until the window is focused,
+    /// `None` cancels the request
+    fn request_user_attention(&self, _attention: Option<UserAttention>) {}
+
+    /// progress in `0..=1` shown on the taskbar entry, `None` hides it. ignored by platforms
+    /// without taskbar progress
+    fn set_taskbar_progress(&self, _progress: Option<f32>) {}
+
+    fn pre_present_notify(&self) {}
+
+    fn request_redraw(&self) {}
+
+    /// native handle for renderers and integrations outside of this crate
+    fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        Err(rwh::HandleError::Unavailable)
+    }
+
+    fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
+        Err(rwh::HandleError::Unavailable)
+    }
+
+    /// used by integrations to get back the concrete window type
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// rgba8 image used as the window and taskbar icon
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowIcon {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl WindowIcon {
+    /// `rgba` has to contain `width * height` tightly packed pixels
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Option<Self> {
+        (rgba.len() == width as usize * height as usize * 4).then_some(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    /// decodes an image file, e.g. the bytes of an `include_bytes!("icon.png")`
+    pub fn from_encoded(bytes: &[u8]) -> image::ImageResult<Self> {
+        let img = image::load_from_memory(bytes)?.into_rgba8();
+        let (width, height) = img.dimensions();
+        Ok(Self {
+            width,
+            height,
+            rgba: img.into_raw(),
+        })
+    }
+}
+
+/// how urgently [`PlatformWindow::request_user_attention`] asks for focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UserAttention {
+    /// flashes until the window is focused
+    Critical,
+    /// flashes once
+    #[default]
+    Informational,
+}
+
+/// a display connected to the system, positions and sizes are in physical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    /// top left corner on the virtual desktop spanning all monitors
+    pub position: Vec2,
+    pub size: UVec2,
+    pub scale_factor: f32,
+    pub refresh_rate_hz: Option<f32>,
+}
+
+impl MonitorInfo {
+    pub fn rect(&self) -> Rect {
+        Rect::from_min_size(self.position, self.size.as_vec2())
+    }
+}
+
+/// position and size of a window, store it to restore the window on the next start, see
+/// [`Window::placement`](crate::Window::placement)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowPlacement {
+    /// outer position in physical pixels
+    pub pos: Vec2,
+    /// inner size in physical pixels
+    pub size: UVec2,
+    pub maximized: bool,
+    pub fullscreen: bool,
+}
+
+impl WindowPlacement {
+    /// moves the window onto the first of `monitors` if its top edge is not visible on any of
+    /// them, e.g. because the monitor it was on got disconnected. the size is shrunk to fit
+    pub fn clamp_to_monitors(mut self, monitors: &[MonitorInfo]) -> Self {
+        let grab = self.pos + Vec2::new(self.size.x.min(200) as f32 * 0.5, 8.0);
+        if monitors.iter().any(|m| m.rect().contains(grab)) {
+            return self;
+        }
+        let Some(monitor) = monitors.first() else {
+            return self;
+        };
+        self.size = self.size.min(monitor.size);
+        self.pos = monitor.position + (monitor.size - self.size).as_vec2() * 0.5;
+        self
+    }
+}
+
+/// the text size preference of the os, e.g. `1.5` for text at 150%. this is separate from the
+/// scale factor of the monitor and `None` where it can't be read
+///
+/// read from the `TextScaleFactor` accessibility setting in the registry on windows. on linux
+/// `GDK_DPI_SCALE` is used, or with the `xdg-portal` feature the gnome `text-scaling-factor`
+/// from the settings of the desktop portal, which is a d-bus call that can block
+pub fn system_text_scale() -> Option<f32> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::System::Registry::{
+            HKEY_CURRENT_USER, RRF_RT_REG_DWORD, RegGetValueW,
+        };
+
+        let wide = |s: &str| s.encode_utf16().chain([0]).collect::<Vec<u16>>();
+        let key = wide(r"Software\Microsoft\Accessibility");
+        let value = wide("TextScaleFactor");
+        let mut percent = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        // SAFETY: the strings are nul terminated and `size` is the size of `percent`
+        let err = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                value.as_ptr(),
+                RRF_RT_REG_DWORD,
+                std::ptr::null_mut(),
+                (&mut percent as *mut u32).cast(),
+                &mut size,
+            )
+        };
+        (err == 0 && percent > 0).then(|| percent as f32 / 100.0)
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let env = std::env::var("GDK_DPI_SCALE").ok().and_then(|v| v.parse().ok());
+        #[cfg(feature = "xdg-portal")]
+        let env = env.or_else(portal_text_scale);
+        env.filter(|&scale: &f32| scale > 0.0)
+    }
+
+    #[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+    None
+}
+
+/// reads `org.gnome.desktop.interface text-scaling-factor` through the settings interface of
+/// the xdg desktop portal
+#[cfg(all(unix, not(target_os = "macos"), feature = "xdg-portal"))]
+fn portal_text_scale() -> Option<f32> {
+    let conn = zbus::blocking::Connection::session().ok()?;
+    let reply = conn
+        .call_method(
+            Some("org.freedesktop.portal.Desktop"),
+            "/org/freedesktop/portal/desktop",
+            Some("org.freedesktop.portal.Settings"),
+            "ReadOne",
+            &("org.gnome.desktop.interface", "text-scaling-factor"),
+        )
+        .ok()?;
+    let value: zbus::zvariant::OwnedValue = reply.body().deserialize().ok()?;
+    f64::try_from(value).ok().map(|scale| scale as f32)
+}
+
+/// a window without a surface, used to render the ui offscreen
+#[derive(Debug)]
+pub struct HeadlessWindow {
+    id: WindowId,
+    size: Mutex<UVec2>,
+}
+
+impl HeadlessWindow {
+    pub fn new(width: u32, height: u32) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self {
+            id: WindowId(NEXT_ID.fetch_add(1, Ordering::Relaxed)),
+            size: Mutex::new(UVec2::new(width, height)),
+        }
+    }
+}
+
+impl PlatformWindow for HeadlessWindow {
+    fn id(&self) -> WindowId {
+        self.id
+    }
+
+    fn inner_size(&self) -> UVec2 {
+        *self.size.lock().unwrap()
+    }
+
+    fn request_inner_size(&self, size: UVec2) {
+        *self.size.lock().unwrap() = size;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// translation of winit windows and events
+#[cfg(feature = "winit")]
+mod winit_impl {
+    use super::*;
+    use crate::mouse::{ImeEvent, InputEvent, KeyCode, MouseBtn};
+    use winit::{
+        dpi::{PhysicalPosition, PhysicalSize},
+        event::{Ime, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent},
+        keyboard::PhysicalKey,
+        monitor::MonitorHandle,
+        window::{Fullscreen, Icon, UserAttentionType, Window},
+    };
+
+    impl From<winit::window::WindowId> for WindowId {
+        fn from(id: winit::window::WindowId) -> Self {
+            Self(id.into())
+        }
+    }
+
+    impl PlatformWindow for Window {
+        fn id(&self) -> WindowId {
+            Window::id(self).into()
+        }
+
+        fn inner_size(&self) -> UVec2 {
+            let size = Window::inner_size(self);
+            UVec2::new(size.width, size.height)
+        }
+
+        fn inner_position(&self) -> Vec2 {
+            let pos = Window::inner_position(self).unwrap_or_default();
+            Vec2::new(pos.x as f32, pos.y as f32)
+        }
+
+        fn scale_factor(&self) -> f32 {
+            Window::scale_factor(self) as f32
+        }
+
+        fn has_focus(&self) -> bool {
+            Window::has_focus(self)
+        }
+
+        fn set_cursor_icon(&self, icon: CursorIcon) {
+            self.set_cursor(icon);
+        }
+
+        fn drag_window(&self) {
+            if let Err(e) = Window::drag_window(self) {
+                log::warn!("{e}");
+            }
+        }
+
+        fn drag_resize_window(&self, dir: Dir) {
+            if let Err(e) = Window::drag_resize_window(self, dir.as_winit_resize()) {
+                log::warn!("{e}");
+            }
+        }
+
+        fn is_maximized(&self) -> bool {
+            Window::is_maximized(self)
+        }
+
+        fn set_maximized(&self, maximized: bool) {
+            Window::set_maximized(self, maximized)
+        }
+
+        fn set_minimized(&self, minimized: bool) {
+            Window::set_minimized(self, minimized)
+        }
+
+        fn is_decorated(&self) -> bool {
+            Window::is_decorated(self)
+        }
+
+        fn set_decorations(&self, decorated: bool) {
+            Window::set_decorations(self, decorated)
+        }
+
+        fn request_inner_size(&self, size: UVec2) {
+            let _ = Window::request_inner_size(self, PhysicalSize::new(size.x, size.y));
+        }
+
+        fn outer_position(&self) -> Vec2 {
+            let pos = Window::outer_position(self).unwrap_or_default();
+            Vec2::new(pos.x as f32, pos.y as f32)
+        }
+
+        fn set_outer_position(&self, pos: Vec2) {
+            Window::set_outer_position(self, PhysicalPosition::new(pos.x as i32, pos.y as i32))
+        }
+
+        fn current_monitor(&self) -> Option<MonitorInfo> {
+            Window::current_monitor(self).map(monitor_info)
+        }
+
+        fn primary_monitor(&self) -> Option<MonitorInfo> {
+            Window::primary_monitor(self).map(monitor_info)
+        }
+
+        fn available_monitors(&self) -> Vec<MonitorInfo> {
+            Window::available_monitors(self).map(monitor_info).collect()
+        }
+
+        fn is_fullscreen(&self) -> bool {
+            Window::fullscreen(self).is_some()
+        }
+
+        fn set_fullscreen(&self, fullscreen: bool) {
+            let mode = fullscreen.then_some(Fullscreen::Borderless(None));
+            Window::set_fullscreen(self, mode)
+        }
+
+        fn set_window_icon(&self, icon: Option<&WindowIcon>) {
+            Window::set_window_icon(self, icon.and_then(WindowIcon::to_winit))
+        }
+
+        fn request_user_attention(&self, attention: Option<UserAttention>) {
+            let attention = attention.map(|a| match a {
+                UserAttention::Critical => UserAttentionType::Critical,
+                UserAttention::Informational => UserAttentionType::Informational,
+            });
+            Window::request_user_attention(self, attention)
+        }
+
+        fn pre_present_notify(&self) {
+            Window::pre_present_notify(self)
+        }
+
+        fn request_redraw(&self) {
+            Window::request_redraw(self)
+        }
+
+        fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+            rwh::HasWindowHandle::window_handle(self)
+        }
+
+        fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
+            rwh::HasDisplayHandle::display_handle(self)
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    impl WindowIcon {
+        pub(crate) fn to_winit(&self) -> Option<Icon> {
+            Icon::from_rgba(self.rgba.clone(), self.width, self.height)
+                .inspect_err(|e| log::warn!("invalid window icon: {e}"))
+                .ok()
+        }
+    }
+
+    fn monitor_info(m: MonitorHandle) -> MonitorInfo {
+        let (pos, size) = (m.position(), m.size());
+        MonitorInfo {
+            name: m.name(),
+            position: Vec2::new(pos.x as f32, pos.y as f32),
+            size: UVec2::new(size.width, size.height),
+            scale_factor: m.scale_factor() as f32,
+            refresh_rate_hz: m.refresh_rate_millihertz().map(|mhz| mhz as f32 / 1000.0),
+        }
+    }
+
+    impl InputEvent {
+        /// translates the input part of a winit window event, other events return nothing
+        pub fn from_winit(event: &WindowEvent) -> Vec<InputEvent> {
+            use WindowEvent as WE;
+
+            match event {
+                WE::CursorMoved { position, .. } => {
+                    vec![InputEvent::PointerMoved(Vec2::new(position.x as f32, position.y as f32))]
+                }
+                WE::MouseInput { state, button, .. } => {
+                    let button = match button {
+                        MouseButton::Left => MouseBtn::Left,
+                        MouseButton::Right => MouseBtn::Right,
+                        MouseButton::Middle => MouseBtn::Middle,
+                        _ => return vec![],
+                    };
+                    let pressed = state.is_pressed();
+                    vec![InputEvent::PointerButton { button, pressed }]
+                }
+                WE::Touch(Touch {
+                    phase, location, ..
+                }) => {
+                    let pos = Vec2::new(location.x as f32, location.y as f32);
+                    let button = |pressed| InputEvent::PointerButton {
+                        button: MouseBtn::Left,
+                        pressed,
+                    };
+                    match phase {
+                        TouchPhase::Started => vec![InputEvent::PointerMoved(pos), button(true)],
+                        TouchPhase::Moved => vec![InputEvent::PointerMoved(pos)],
+                        TouchPhase::Ended | TouchPhase::Cancelled => {
+                            vec![InputEvent::PointerMoved(pos), button(false)]
+                        }
+                    }
+                }
+                WE::MouseWheel { delta, .. } => {
+                    let delta = match delta {
+                        MouseScrollDelta::LineDelta(x, y) => Vec2::new(*x, *y) * 20.0,
+                        MouseScrollDelta::PixelDelta(d) => Vec2::new(d.x as f32, d.y as f32),
+                    };
+                    vec![InputEvent::Wheel(delta)]
+                }
+                WE::KeyboardInput { event, .. } => {
+                    let code = match event.physical_key {
+                        PhysicalKey::Code(code) => KeyCode::from_winit(code),
+                        PhysicalKey::Unidentified(_) => None,
+                    };
+                    let pressed = event.state.is_pressed();
+                    let mut events = vec![InputEvent::Key { code, pressed }];
+                    if let (true, Some(text)) = (pressed, &event.text) {
+                        events.push(InputEvent::Text(text.to_string()));
+                    }
+                    events
+                }
+                WE::Ime(ime) => vec![InputEvent::Ime(match ime {
+                    Ime::Enabled => ImeEvent::Enabled,
+                    Ime::Preedit(text, cursor) => ImeEvent::Preedit(text.clone(), *cursor),
+                    Ime::Commit(text) => ImeEvent::Commit(text.clone()),
+                    Ime::Disabled => ImeEvent::Disabled,
+                })],
+                WE::ModifiersChanged(modifiers) => {
+                    vec![InputEvent::Modifiers(modifiers.state().into())]
+                }
+                WE::Focused(focused) => vec![InputEvent::Focused(*focused)],
+                _ => vec![],
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_window() {
+        let a = HeadlessWindow::new(100, 50);
+        let b = HeadlessWindow::new(100, 50);
+        assert_ne!(a.id(), b.id());
+        assert!(!a.id().is_null());
+
+        a.request_inner_size(UVec2::new(20, 10));
+        assert_eq!(a.inner_size(), UVec2::new(20, 10));
+        assert_eq!(b.inner_size(), UVec2::new(100, 50));
+    }
+
+    #[test]
+    fn test_window_icon_from_rgba() {
+        assert!(WindowIcon::from_rgba(vec![0; 2 * 3 * 4], 2, 3).is_some());
+        assert!(WindowIcon::from_rgba(vec![0; 2 * 3 * 3], 2, 3).is_none());
+    }
+
+    #[test]
+    fn test_placement_clamp_to_monitors() {
+        let monitor = |x: f32, w: u32| MonitorInfo {
+            name: None,
+            position: Vec2::new(x, 0.0),
+            size: UVec2::new(w, 1080),
+            scale_factor: 1.0,
+            refresh_rate_hz: Some(60.0),
+        };
+        let monitors = [monitor(0.0, 1920), monitor(1920.0, 2560)];
+        let placement = WindowPlacement {
+            pos: Vec2::new(2000.0, 100.0),
+            size: UVec2::new(800, 600),
+            maximized: false,
+            fullscreen: false,
+        };
+        assert_eq!(placement.clamp_to_monitors(&monitors), placement);
+
+        // the second monitor got disconnected
+        let clamped = placement.clamp_to_monitors(&monitors[..1]);
+        assert_eq!(clamped.pos, Vec2::new(560.0, 240.0));
+        assert_eq!(clamped.size, placement.size);
+
+        let big = WindowPlacement {
+            size: UVec2::new(4000, 600),
+            ..placement
+        };
+        let clamped = big.clamp_to_monitors(&monitors[..1]);
+        assert_eq!(clamped.size, UVec2::new(1920, 600));
+        assert_eq!(clamped.pos, Vec2::new(0.0, 240.0));
+    }
+
+    #[cfg(feature = "winit")]
+    #[test]
+    fn test_input_event_from_winit() {
+        use crate::mouse::{ImeEvent, InputEvent as IE, KeyCode, Modifiers, MouseBtn};
+        use winit::{
+            dpi::PhysicalPosition,
+            event::{
+                DeviceId, ElementState, Ime, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+                WindowEvent as WE,
+            },
+            keyboard::{KeyCode as WKC, ModifiersState},
+        };
+
+        let device_id = DeviceId::dummy();
+        let pos = PhysicalPosition::new(3.0, 4.0);
+        let wheel = |delta| WE::MouseWheel {
+            device_id,
+            delta,
+            phase: TouchPhase::Moved,
+        };
+        let button = |button, pressed| IE::PointerButton { button, pressed };
+        let cases = [
+            (
+                WE::CursorMoved {
+                    device_id,
+                    position: pos,
+                },
+                vec![IE::PointerMoved(Vec2::new(3.0, 4.0))],
+            ),
+            (
+                WE::MouseInput {
+                    device_id,
+                    state: ElementState::Pressed,
+                    button: MouseButton::Right,
+                },
+                vec![button(MouseBtn::Right, true)],
+            ),
+            (
+                WE::MouseInput {
+                    device_id,
+                    state: ElementState::Released,
+                    button: MouseButton::Back,
+                },
+                vec![],
+            ),
+            // a touch moves the pointer before pressing the left button
+            (
+                WE::Touch(Touch {
+                    device_id,
+                    phase: TouchPhase::Started,
+                    location: pos,
+                    force: None,
+                    id: 0,
+                }),
+                vec![IE::PointerMoved(Vec2::new(3.0, 4.0)), button(MouseBtn::Left, true)],
+            ),
+            // lines are scrolled by 20 pixels
+            (
+                wheel(MouseScrollDelta::LineDelta(1.0, -2.0)),
+                vec![IE::Wheel(Vec2::new(20.0, -40.0))],
+            ),
+            (wheel(MouseScrollDelta::PixelDelta(pos)), vec![IE::Wheel(Vec2::new(3.0, 4.0))]),
+            (WE::Ime(Ime::Enabled), vec![IE::Ime(ImeEvent::Enabled)]),
+            (
+                WE::Ime(Ime::Preedit("ni".into(), Some((0, 2)))),
+                vec![IE::Ime(ImeEvent::Preedit("ni".into(), Some((0, 2))))],
+            ),
+            (WE::Ime(Ime::Commit("你".into())), vec![IE::Ime(ImeEvent::Commit("你".into()))]),
+            (
+                WE::ModifiersChanged((ModifiersState::SHIFT | ModifiersState::CONTROL).into()),
+                vec![IE::Modifiers(Modifiers::SHIFT | Modifiers::CONTROL)],
+            ),
+            (WE::Focused(false), vec![IE::Focused(false)]),
+            (WE::RedrawRequested, vec![]),
+        ];
+        for (event, expected) in cases {
+            assert_eq!(IE::from_winit(&event), expected, "{event:?}");
+        }
+
+        // keys are mapped by their physical position
+        assert_eq!(KeyCode::from_winit(WKC::KeyZ), Some(KeyCode::KeyZ));
+        assert_eq!(KeyCode::from_winit(WKC::NumpadEnter), Some(KeyCode::NumpadEnter));
+        assert_eq!(KeyCode::from_winit(WKC::ShiftRight), Some(KeyCode::ShiftRight));
+        assert_eq!(KeyCode::from_winit(WKC::F24), None);
+    }
+}
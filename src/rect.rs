@@ -31,6 +31,7 @@ use std::{fmt, ops};
 use glam::{Vec2, vec2};
 
 #[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     pub min: Vec2,
     pub max: Vec2,
@@ -224,6 +225,21 @@ impl Rect {
         Self::from_min_max(self.min + amnt, self.max - amnt)
     }
 
+    /// Expand each side by its own margin
+    #[must_use]
+    pub fn expand_sides(self, left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self::from_min_max(
+            self.min - vec2(left, top),
+            self.max + vec2(right, bottom),
+        )
+    }
+
+    /// Shrink each side by its own margin
+    #[must_use]
+    pub fn shrink_sides(self, left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        self.expand_sides(-left, -top, -right, -bottom)
+    }
+
     #[must_use]
     #[inline]
     pub fn translate(self, amnt: Vec2) -> Self {
@@ -297,6 +313,46 @@ impl Rect {
         p.clamp(self.min, self.max)
     }
 
+    /// Move `other` (keeping its size) so that it lies inside `self`.
+    /// If `other` is larger than `self` it is aligned to the left-top.
+    #[must_use]
+    pub fn clamp_rect(&self, other: Self) -> Self {
+        let size = other.size();
+        let min = other.min.min(self.max - size).max(self.min);
+        Self::from_min_size(min, size)
+    }
+
+    /// Like [`Self::contains`], but the corners are rounded by `rounding`
+    #[must_use]
+    pub fn contains_rounded(&self, p: Vec2, rounding: f32) -> bool {
+        let r = rounding.min(self.width() * 0.5).min(self.height() * 0.5).max(0.0);
+        if r == 0.0 {
+            return self.contains(p);
+        }
+        let inner = self.shrink(r);
+        let q = (p - inner.center()).abs() - inner.size() * 0.5;
+        q.max(Vec2::ZERO).length_squared() <= r * r && q.max_element() <= r
+    }
+
+    /// The point at normalized coordinates `t`, `[0, 0]` is `min`, `[1, 1]` is `max`
+    #[inline]
+    pub fn lerp_inside(&self, t: Vec2) -> Vec2 {
+        self.min + (self.max - self.min) * t
+    }
+
+    /// Inverse of [`Self::lerp_inside`]
+    #[inline]
+    pub fn normalize_pos(&self, p: Vec2) -> Vec2 {
+        (p - self.min) / self.size()
+    }
+
+    /// Linearly interpolate both corners towards `other`
+    #[must_use]
+    #[inline]
+    pub fn lerp_towards(&self, other: &Self, t: f32) -> Self {
+        Self::from_min_max(self.min.lerp(other.min, t), self.max.lerp(other.max, t))
+    }
+
     #[must_use]
     pub fn clip(&self, p: Self) -> Option<Self> {
         if self.overlaps(p) {
@@ -399,7 +455,7 @@ impl Rect {
     /// This is never negative, and instead returns zero for negative rectangles.
     #[inline(always)]
     pub fn area(&self) -> f32 {
-        self.width().clamp(f32::NEG_INFINITY, 0.0) * self.height().clamp(f32::NEG_INFINITY, 0.0)
+        self.width().max(0.0) * self.height().max(0.0)
     }
 
     /// The distance from the rect to the position.
@@ -450,7 +506,7 @@ impl Rect {
     ///
     /// ```
     /// # use glam::vec2;
-    /// # use wgpui::rect::Rect;
+    /// # use wgpui::Rect;
     /// let rect = Rect::from_min_max(vec2(0.0, 0.0), vec2(1.0, 1.0));
     /// assert_eq!(rect.signed_distance_to_pos(vec2(0.50, 0.50)), -0.50);
     /// assert_eq!(rect.signed_distance_to_pos(vec2(0.75, 0.50)), -0.25);
@@ -610,6 +666,61 @@ impl Rect {
         vec2(self.right(), self.bottom())
     }
 
+    /// All four corners, clockwise starting at the left-top
+    #[inline]
+    pub fn corners(&self) -> [Vec2; 4] {
+        [
+            self.left_top(),
+            self.right_top(),
+            self.right_bottom(),
+            self.left_bottom(),
+        ]
+    }
+
+    /// Cut `w` off the left side, returns `(left, rest)`
+    pub fn split_left(&self, w: f32) -> (Self, Self) {
+        self.split_left_right_at_x((self.min.x + w).min(self.max.x))
+    }
+
+    /// Cut `w` off the right side, returns `(right, rest)`
+    pub fn split_right(&self, w: f32) -> (Self, Self) {
+        let (rest, right) = self.split_left_right_at_x((self.max.x - w).max(self.min.x));
+        (right, rest)
+    }
+
+    /// Cut `h` off the top, returns `(top, rest)`
+    pub fn split_top(&self, h: f32) -> (Self, Self) {
+        self.split_top_bottom_at_y((self.min.y + h).min(self.max.y))
+    }
+
+    /// Cut `h` off the bottom, returns `(bottom, rest)`
+    pub fn split_bottom(&self, h: f32) -> (Self, Self) {
+        let (rest, bottom) = self.split_top_bottom_at_y((self.max.y - h).max(self.min.y));
+        (bottom, rest)
+    }
+
+    /// Largest centered rect with the given aspect ratio (width / height) that fits inside
+    pub fn aspect_fit(&self, aspect_ratio: f32) -> Self {
+        let size = self.size();
+        let size = if size.x / size.y > aspect_ratio {
+            vec2(size.y * aspect_ratio, size.y)
+        } else {
+            vec2(size.x, size.x / aspect_ratio)
+        };
+        Self::from_center_size(self.center(), size)
+    }
+
+    /// Smallest centered rect with the given aspect ratio (width / height) that covers `self`
+    pub fn aspect_fill(&self, aspect_ratio: f32) -> Self {
+        let size = self.size();
+        let size = if size.x / size.y > aspect_ratio {
+            vec2(size.x, size.x / aspect_ratio)
+        } else {
+            vec2(size.y * aspect_ratio, size.y)
+        };
+        Self::from_center_size(self.center(), size)
+    }
+
     /// Split rectangle in left and right halves at the given `x` coordinate.
     pub fn split_left_right_at_x(&self, split_x: f32) -> (Self, Self) {
         let left = Self::from_min_max(self.min, Vec2::new(split_x, self.max.y));
@@ -769,3 +880,43 @@ impl ops::BitOrAssign for Rect {
         *self = self.union(other);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect {
+        Rect::from_min_max(vec2(x0, y0), vec2(x1, y1))
+    }
+
+    #[test]
+    fn test_rect_area() {
+        assert_eq!(r(0.0, 0.0, 2.0, 3.0).area(), 6.0);
+        assert_eq!(r(0.0, 0.0, -2.0, 3.0).area(), 0.0);
+    }
+
+    #[test]
+    fn test_rect_split() {
+        let rect = r(0.0, 0.0, 10.0, 4.0);
+        assert_eq!(rect.split_left(3.0), (r(0.0, 0.0, 3.0, 4.0), r(3.0, 0.0, 10.0, 4.0)));
+        assert_eq!(rect.split_right(3.0), (r(7.0, 0.0, 10.0, 4.0), r(0.0, 0.0, 7.0, 4.0)));
+        assert_eq!(rect.split_bottom(10.0).1, r(0.0, 0.0, 10.0, 0.0));
+    }
+
+    #[test]
+    fn test_rect_clamp_and_aspect() {
+        let outer = r(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(outer.clamp_rect(r(8.0, -2.0, 12.0, 2.0)), r(6.0, 0.0, 10.0, 4.0));
+        assert_eq!(outer.aspect_fit(2.0), r(0.0, 2.5, 10.0, 7.5));
+        assert_eq!(outer.aspect_fill(2.0), r(-5.0, 0.0, 15.0, 10.0));
+        assert_eq!(outer.lerp_inside(vec2(0.5, 1.0)), vec2(5.0, 10.0));
+    }
+
+    #[test]
+    fn test_rect_contains_rounded() {
+        let rect = r(0.0, 0.0, 10.0, 10.0);
+        assert!(rect.contains_rounded(vec2(5.0, 0.0), 2.0));
+        assert!(!rect.contains_rounded(vec2(0.1, 0.1), 2.0));
+        assert!(rect.contains_rounded(vec2(1.0, 1.0), 2.0));
+    }
+}
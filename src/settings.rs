@@ -0,0 +1,391 @@
+//! small key value store for app preferences, see [`Settings`] and `Context::settings`
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use crate::{
+    core::{Duration, Instant},
+    platform::MonitorInfo,
+    ui,
+};
+
+/// changes are written at most this often while the app is running
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// typed preferences stored as text, e.g. the theme or the last opened file of a tool
+///
+/// natively the values are kept in `<config dir>/<app>/settings.cfg`, which is
+/// `$XDG_CONFIG_HOME`, `~/Library/Application Support` or `%APPDATA%`. on the web they are
+/// kept in the local storage of the page. changes are flushed shortly after they are made
+/// and when the settings are dropped
+///
+/// ```ignore
+/// let scale = ui.settings().get_or("ui_scale", 1.0f32);
+/// ui.settings().set("ui_scale", 1.25f32);
+/// ```
+pub struct Settings {
+    values: BTreeMap<String, String>,
+    /// the config file, or the local storage key on the web. `None` keeps the values in memory
+    location: Option<String>,
+    dirty: bool,
+    last_flush: Instant,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl Drop for Settings {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl fmt::Debug for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Settings")
+            .field("location", &self.location)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+/// one `key = value` line per entry, newlines and backslashes in values are escaped
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, val) in &self.values {
+            let val = val
+                .replace('\\', "\\\\")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r");
+            writeln!(f, "{key} = {val}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Settings {
+    /// settings that are never written anywhere, e.g. for tests
+    pub fn in_memory() -> Self {
+        Self {
+            values: BTreeMap::new(),
+            location: None,
+            dirty: false,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// loads the settings of `app`, missing or unreadable settings start out empty
+    pub fn open(app: &str) -> Self {
+        let location = platform::location(app);
+        let mut settings = location
+            .as_deref()
+            .and_then(platform::read)
+            .map_or_else(Self::in_memory, |text| Self::parse(&text));
+        settings.location = location;
+        settings
+    }
+
+    /// in memory settings from the text written by the [`fmt::Display`] impl
+    pub fn parse(text: &str) -> Self {
+        let mut settings = Self::in_memory();
+        for line in text.lines() {
+            let line = line.trim_start();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, val)) = line.split_once(" = ") else {
+                log::warn!("invalid settings line: {line}");
+                continue;
+            };
+            settings
+                .values
+                .insert(key.trim_end().to_string(), unescape(val));
+        }
+        settings
+    }
+
+    /// where the settings are flushed to, see [`Self::open`]
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    /// `None` if the key is missing or its value does not parse as `T`
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.values.get(key)?.parse().ok()
+    }
+
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> T {
+        self.get(key).unwrap_or(default)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// `key` may not contain newlines or ` = `
+    pub fn set<T: fmt::Display>(&mut self, key: &str, val: T) {
+        debug_assert!(
+            !key.contains('\n') && !key.contains(" = "),
+            "invalid settings key: {key:?}"
+        );
+        let val = val.to_string();
+        if self.values.get(key) != Some(&val) {
+            self.values.insert(key.to_string(), val);
+            self.dirty = true;
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.dirty |= self.values.remove(key).is_some();
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// there are changes that were not flushed yet
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// writes the changes to the storage, failures are logged and retried on the next change
+    pub fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        self.last_flush = Instant::now();
+        if let Some(location) = &self.location
+            && let Err(e) = platform::write(location, &self.to_string())
+        {
+            log::warn!("failed to write the settings to {location}: {e}");
+        }
+    }
+
+    /// flushes unless the last flush was less than [`FLUSH_INTERVAL`] ago, called at the end
+    /// of every frame
+    pub(crate) fn flush_if_due(&mut self) {
+        if self.dirty && self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush();
+        }
+    }
+}
+
+fn unescape(val: &str) -> String {
+    let mut out = String::with_capacity(val.len());
+    let mut chars = val.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{env, fs, io, path::PathBuf};
+
+    fn config_dir() -> Option<PathBuf> {
+        let var = |key| {
+            env::var_os(key)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        };
+        if cfg!(windows) {
+            var("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            var("HOME").map(|home| home.join("Library/Application Support"))
+        } else {
+            var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))
+        }
+    }
+
+    pub fn location(app: &str) -> Option<String> {
+        let path = config_dir()?.join(app).join("settings.cfg");
+        Some(path.to_string_lossy().into_owned())
+    }
+
+    pub fn read(location: &str) -> Option<String> {
+        fs::read_to_string(location).ok()
+    }
+
+    /// writes a temporary file first, so a crash can't leave half written settings behind
+    pub fn write(location: &str, text: &str) -> io::Result<()> {
+        let path = PathBuf::from(location);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("cfg.tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// bridge to the local storage of the browser
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(inline_js = "
+        export function storage_get(key) {
+            return window.localStorage.getItem(key);
+        }
+        export function storage_set(key, val) {
+            window.localStorage.setItem(key, val);
+        }
+    ")]
+    extern "C" {
+        #[wasm_bindgen(catch)]
+        fn storage_get(key: &str) -> Result<Option<String>, JsValue>;
+        #[wasm_bindgen(catch)]
+        fn storage_set(key: &str, val: &str) -> Result<(), JsValue>;
+    }
+
+    pub fn location(app: &str) -> Option<String> {
+        Some(format!("{app}.settings"))
+    }
+
+    pub fn read(location: &str) -> Option<String> {
+        storage_get(location).ok().flatten()
+    }
+
+    pub fn write(location: &str, text: &str) -> Result<(), String> {
+        storage_set(location, text).map_err(|e| format!("{e:?}"))
+    }
+}
+
+/// the ui scale stored per monitor, see `Context::persist_ui_scale`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonitorUiScale {
+    /// name of the monitor the scale was last restored for
+    pub monitor: Option<String>,
+}
+
+impl MonitorUiScale {
+    fn key(monitor: &str) -> String {
+        format!("ui_scale.{monitor}")
+    }
+
+    /// the scale to switch to when the window is on `monitor`, `None` while it stays on the
+    /// same monitor. monitors without a stored scale use `default`
+    pub fn moved_to(
+        &mut self,
+        monitor: &MonitorInfo,
+        settings: &Settings,
+        default: f32,
+    ) -> Option<f32> {
+        let name = monitor
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{}", monitor.position));
+        if self.monitor.as_ref() == Some(&name) {
+            return None;
+        }
+        let scale = settings.get(&Self::key(&name)).unwrap_or(default);
+        self.monitor = Some(name);
+        Some(scale.max(0.1))
+    }
+
+    /// stores `scale` for the current monitor
+    pub fn store(&self, settings: &mut Settings, scale: f32) {
+        if let Some(monitor) = &self.monitor {
+            settings.set(&Self::key(monitor), scale);
+        }
+    }
+}
+
+impl ui::Context {
+    /// the settings of the app, opened with the name of the executable on first use unless
+    /// they were replaced with [`Self::set_settings`]
+    pub fn settings(&mut self) -> &mut Settings {
+        self.settings.get_or_insert_with(|| {
+            let exe = std::env::current_exe().ok();
+            let name = exe
+                .as_ref()
+                .and_then(|p| p.file_stem())
+                .and_then(|s| s.to_str());
+            Settings::open(name.unwrap_or("wgpui"))
+        })
+    }
+
+    /// e.g. `ui.set_settings(Settings::open("my_tool"))`, the previous settings are flushed
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = Some(settings);
+    }
+
+    /// a checkbox bound to the settings `key`, returns its value
+    pub fn checkbox_setting(&mut self, label: &str, key: &str, default: bool) -> bool {
+        let mut val = self.settings().get_or(key, default);
+        if self.checkbox(label, &mut val).changed() {
+            self.settings().set(key, val);
+        }
+        val
+    }
+
+    /// a slider bound to the settings `key`, returns its value
+    pub fn slider_f32_setting(
+        &mut self,
+        label: &str,
+        key: &str,
+        min: f32,
+        max: f32,
+        default: f32,
+    ) -> f32 {
+        let mut val = self.settings().get_or(key, default);
+        if self.slider_f32(label, min, max, &mut val).changed() {
+            self.settings().set(key, val);
+        }
+        val
+    }
+
+    /// a text field bound to the settings `key`, the value is only stored when the text parses
+    /// as `T`. returns the value
+    pub fn input_setting<T: FromStr + fmt::Display + PartialEq>(
+        &mut self,
+        label: &str,
+        key: &str,
+        default: T,
+    ) -> T {
+        let mut val = self.settings().get_or(key, default);
+        if self.inspect_parsed(label, &mut val) {
+            self.settings().set(key, &val);
+        }
+        val
+    }
+
+    /// a combo box over `options` that stores the picked option in the settings `key`, returns
+    /// its index
+    pub fn select_setting<S: AsRef<str>>(
+        &mut self,
+        label: &str,
+        key: &str,
+        options: &[S],
+        default: usize,
+    ) -> usize {
+        let stored = self.settings().get_str(key).map(str::to_string);
+        let current = stored
+            .and_then(|s| options.iter().position(|o| o.as_ref() == s))
+            .unwrap_or(default);
+        let mut selected = Some(current);
+        if self.searchable_select(label, options, &mut selected).changed()
+            && let Some(i) = selected
+        {
+            self.settings().set(key, options[i].as_ref());
+        }
+        selected.unwrap_or(current)
+    }
+}
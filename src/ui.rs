@@ -7,16 +7,38 @@ use wgpu::util::DeviceExt;
 
 use crate::{
     Vertex as VertexTyp,
+    accessibility::TextPos,
     core::{
-        ArrVec, Axis, DataMap, Dir, HashMap, HashSet, Instant, RGBA, id_type, stacked_fields_struct,
+        ArrVec, Axis, DataMap, Dir, HashMap, HashSet, RGBA, id_type, stacked_fields_struct,
     },
     gpu::{self, RenderPassHandle, ShaderHandle, WGPU, WGPUHandle, Window, WindowId},
-    mouse::{Clipboard, CursorIcon, MouseBtn, MouseState},
+    mouse::{CursorIcon, Modifiers, MouseBtn, MouseState},
     rect::Rect,
 };
 
+pub use crate::ui_attention::*;
+pub use crate::ui_code::*;
+pub use crate::ui_compose::*;
 pub use crate::ui_context::*;
+pub use crate::ui_curve::*;
+pub use crate::ui_deferred::*;
+pub use crate::ui_effect::*;
+#[cfg(feature = "chrono")]
+pub use crate::ui_date::*;
+pub use crate::ui_form::*;
+pub use crate::ui_gizmo::*;
+pub use crate::ui_gradient::*;
+pub use crate::ui_inspect::*;
+pub use crate::ui_occlusion::*;
+pub use crate::ui_palette::*;
 pub use crate::ui_panel::*;
+pub use crate::ui_picking::*;
+pub use crate::ui_plot::*;
+pub use crate::ui_raster::*;
+pub use crate::ui_select::*;
+pub use crate::ui_selection::*;
+pub use crate::ui_shader_canvas::*;
+pub use crate::ui_svg::*;
 
 // TODO[NOTE]: when docked there sometimes is a border a bit wider then it should be
 // TODO[NOTE]: framepadding style?
@@ -36,6 +58,7 @@ impl TextureId {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RootId {
     Panel(Id),
     Dock(Id),
@@ -51,16 +74,37 @@ impl Id {
 
         Self::from_hash(&str)
     }
+
+    /// same as [`Id::from_str`], but the hash is cached by the address of the label
+    pub fn from_static_str(str: &'static str) -> Id {
+        let str = match str.find("##") {
+            Some(idx) => &str[idx..],
+            None => str,
+        };
+
+        Id(crate::core::static_str_hash64(str))
+    }
+
+    /// the id pushed by [`Context::keyed`]
+    pub fn from_key(key: impl hash::Hash) -> Id {
+        Self::from_hash(&("##keyed", key))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Outline {
+    /// in physical pixels, like the sizes and paddings of the layout
     pub width: f32,
     pub place: OutlinePlacement,
     pub col: RGBA,
+    /// ignores `width` and is always exactly one physical pixel wide
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hairline: bool,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OutlinePlacement {
     Outer,
     #[default]
@@ -74,6 +118,32 @@ impl Outline {
             width,
             col,
             place: OutlinePlacement::default(),
+            hairline: false,
+        }
+    }
+
+    /// one physical pixel wide and inside of the rect, see [`Stroke::hairline`]
+    pub fn hairline(col: RGBA) -> Self {
+        Self {
+            hairline: true,
+            ..Self::inner(col, 1.0)
+        }
+    }
+
+    pub fn stroke(&self) -> Stroke {
+        Stroke {
+            width: self.width,
+            col: self.col,
+            hairline: self.hairline,
+        }
+    }
+
+    /// the outline with its drawn width, see [`Stroke::pixel_width`]
+    pub fn to_pixels(self) -> Self {
+        Self {
+            width: self.stroke().pixel_width(),
+            hairline: false,
+            ..self
         }
     }
 
@@ -107,12 +177,426 @@ impl Outline {
     }
 }
 
+/// width and color of a line
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    /// in physical pixels, like the sizes and paddings of the layout
+    pub width: f32,
+    pub col: RGBA,
+    /// ignores `width` and is always exactly one physical pixel wide
+    pub hairline: bool,
+}
+
+/// how a polyline is turned into geometry, see [`DrawList::add_polyline_ex`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineMode {
+    /// joined and antialiased, see [`tessellate_line`]
+    #[default]
+    Tessellated,
+    /// native line primitives for lines up to 1 pixel wide and an unjoined quad per segment
+    /// otherwise. much cheaper for plots with many segments, see [`line_primitives`]
+    Fast,
+}
+
+impl Stroke {
+    pub fn new(col: RGBA, width: f32) -> Self {
+        Self {
+            width,
+            col,
+            hairline: false,
+        }
+    }
+
+    pub fn hairline(col: RGBA) -> Self {
+        Self {
+            width: 1.0,
+            col,
+            hairline: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self::new(RGBA::ZERO, 0.0)
+    }
+
+    /// the drawn width in physical pixels, 1 for hairlines
+    pub fn pixel_width(&self) -> f32 {
+        if self.hairline { 1.0 } else { self.width }
+    }
+}
+
+impl From<Outline> for Stroke {
+    fn from(value: Outline) -> Self {
+        value.stroke()
+    }
+}
+
+/// distances from the edges of a rect
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Margins {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl From<f32> for Margins {
+    fn from(value: f32) -> Self {
+        Self::all(value)
+    }
+}
+
+impl Margins {
+    pub fn new(left: f32, right: f32, top: f32, bottom: f32) -> Self {
+        Self {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    pub fn all(m: f32) -> Self {
+        Self::new(m, m, m, m)
+    }
+
+    pub fn zero() -> Self {
+        Self::all(0.0)
+    }
+
+    pub fn symmetric(x: f32, y: f32) -> Self {
+        Self::new(x, x, y, y)
+    }
+
+    /// `left + right` and `top + bottom`
+    pub fn sum(&self) -> Vec2 {
+        Vec2::new(self.left + self.right, self.top + self.bottom)
+    }
+
+    pub fn left_top(&self) -> Vec2 {
+        Vec2::new(self.left, self.top)
+    }
+
+    pub fn right_bottom(&self) -> Vec2 {
+        Vec2::new(self.right, self.bottom)
+    }
+
+    pub fn expand_rect(&self, rect: Rect) -> Rect {
+        rect.expand_sides(self.left, self.top, self.right, self.bottom)
+    }
+
+    pub fn shrink_rect(&self, rect: Rect) -> Rect {
+        rect.shrink_sides(self.left, self.top, self.right, self.bottom)
+    }
+
+    /// scales the margins down so that opposite margins don't overlap inside `size`
+    pub fn fit(self, size: Vec2) -> Self {
+        let sx = fit_factor(self.left + self.right, size.x);
+        let sy = fit_factor(self.top + self.bottom, size.y);
+        Self::new(
+            self.left * sx,
+            self.right * sx,
+            self.top * sy,
+            self.bottom * sy,
+        )
+    }
+}
+
+fn fit_factor(len: f32, max: f32) -> f32 {
+    if len > max && len > 0.0 {
+        max.max(0.0) / len
+    } else {
+        1.0
+    }
+}
+
+/// soft drop shadow, approximated by layered rounded rects
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shadow {
+    pub offset: Vec2,
+    pub blur: f32,
+    pub spread: f32,
+    pub col: RGBA,
+}
+
+impl Shadow {
+    const LAYERS: u32 = 6;
+
+    pub fn new(offset: Vec2, blur: f32, col: RGBA) -> Self {
+        Self {
+            offset,
+            blur,
+            spread: 0.0,
+            col,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self::new(Vec2::ZERO, 0.0, RGBA::ZERO)
+    }
+
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.col.a > 0.0
+    }
+
+    /// rects of the shadow cast by `rect`, from the outermost to the innermost layer
+    pub fn draw_rects(&self, rect: Rect, corners: CornerRadii) -> Vec<DrawRect> {
+        if !self.is_visible() {
+            return Vec::new();
+        }
+
+        let base = rect.translate(self.offset).expand(self.spread);
+        let layers = if self.blur > 0.0 { Self::LAYERS } else { 1 };
+        let col = self.col.with_alpha(self.col.a / layers as f32);
+
+        (0..layers)
+            .map(|i| {
+                let grow = self.blur * (1.0 - i as f32 / layers as f32);
+                let r = grow + self.spread;
+                base.expand(grow)
+                    .draw_rect()
+                    .corners(corners.expand(r))
+                    .fill(col)
+            })
+            .collect()
+    }
+}
+
+/// container decoration drawn around arbitrary content, see [`Context::frame`]
+///
+/// `inner_margin` is the padding between the border and the content,
+/// `outer_margin` is the space around the border
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    pub inner_margin: Margins,
+    pub outer_margin: Margins,
+    pub fill: RGBA,
+    pub border: Outline,
+    pub corners: CornerRadii,
+    pub shadow: Shadow,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl Frame {
+    /// no decoration and no margins
+    pub fn none() -> Self {
+        Self {
+            inner_margin: Margins::zero(),
+            outer_margin: Margins::zero(),
+            fill: RGBA::ZERO,
+            border: Outline::none(),
+            corners: CornerRadii::zero(),
+            shadow: Shadow::none(),
+        }
+    }
+
+    /// darker background with the button rounding and the default spacing as padding
+    pub fn group(style: &StyleTable) -> Self {
+        Self::none()
+            .with_inner_margin(Margins::symmetric(style.spacing_h(), style.spacing_v()))
+            .with_fill(style.panel_dark_bg())
+            .with_corners(style.btn_corners())
+    }
+
+    pub fn with_inner_margin(mut self, margin: impl Into<Margins>) -> Self {
+        self.inner_margin = margin.into();
+        self
+    }
+
+    pub fn with_outer_margin(mut self, margin: impl Into<Margins>) -> Self {
+        self.outer_margin = margin.into();
+        self
+    }
+
+    pub fn with_fill(mut self, fill: RGBA) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn with_border(mut self, border: Outline) -> Self {
+        self.border = border;
+        self
+    }
+
+    pub fn with_corners(mut self, corners: impl Into<CornerRadii>) -> Self {
+        self.corners = corners.into();
+        self
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = shadow;
+        self
+    }
+
+    /// total size of the frame around content of size `content`
+    pub fn outer_size(&self, content: Vec2) -> Vec2 {
+        content + self.inner_margin.sum() + self.outer_margin.sum()
+    }
+
+    /// shadow, fill and border of the frame, `rect` excludes the outer margin
+    pub fn draw_rects(&self, rect: Rect) -> Vec<DrawRect> {
+        let mut rects = self.shadow.draw_rects(rect, self.corners);
+        rects.push(
+            rect.draw_rect()
+                .fill(self.fill)
+                .outline(self.border)
+                .corners(self.corners),
+        );
+        rects
+    }
+}
+
+/// background of a panel
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PanelFill {
+    Solid(RGBA),
+    /// vertical gradient from the top to the bottom color
+    Gradient(RGBA, RGBA),
+}
+
+impl PanelFill {
+    /// color at the top edge of the panel
+    pub fn top(self) -> RGBA {
+        match self {
+            Self::Solid(col) => col,
+            Self::Gradient(top, _) => top,
+        }
+    }
+}
+
+impl From<RGBA> for PanelFill {
+    fn from(col: RGBA) -> Self {
+        Self::Solid(col)
+    }
+}
+
+/// per panel overrides of the style, unset fields use the [`StyleTable`] values
+///
+/// see [`Context::push_panel_style`]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PanelStyle {
+    pub fill: Option<PanelFill>,
+    pub outline: Option<Outline>,
+    pub corners: Option<CornerRadii>,
+    pub padding: Option<f32>,
+    pub titlebar_color: Option<RGBA>,
+    pub titlebar_height: Option<f32>,
+    pub title_col: Option<RGBA>,
+}
+
+impl PanelStyle {
+    pub fn with_fill(mut self, fill: impl Into<PanelFill>) -> Self {
+        self.fill = Some(fill.into());
+        self
+    }
+
+    pub fn with_gradient(mut self, top: RGBA, bottom: RGBA) -> Self {
+        self.fill = Some(PanelFill::Gradient(top, bottom));
+        self
+    }
+
+    pub fn with_outline(mut self, outline: Outline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    pub fn with_corners(mut self, corners: impl Into<CornerRadii>) -> Self {
+        self.corners = Some(corners.into());
+        self
+    }
+
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    pub fn with_titlebar_color(mut self, col: RGBA) -> Self {
+        self.titlebar_color = Some(col);
+        self
+    }
+
+    pub fn with_titlebar_height(mut self, height: f32) -> Self {
+        self.titlebar_height = Some(height);
+        self
+    }
+
+    pub fn with_title_col(mut self, col: RGBA) -> Self {
+        self.title_col = Some(col);
+        self
+    }
+
+    /// fields set in `other` take precedence
+    pub fn merge(self, other: PanelStyle) -> Self {
+        Self {
+            fill: other.fill.or(self.fill),
+            outline: other.outline.or(self.outline),
+            corners: other.corners.or(self.corners),
+            padding: other.padding.or(self.padding),
+            titlebar_color: other.titlebar_color.or(self.titlebar_color),
+            titlebar_height: other.titlebar_height.or(self.titlebar_height),
+            title_col: other.title_col.or(self.title_col),
+        }
+    }
+}
+
+/// uv region of a nine-slice image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NineSliceUV {
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    /// margins of the border slices in uv space
+    pub margins: Margins,
+}
+
+impl NineSliceUV {
+    pub fn new(uv_min: Vec2, uv_max: Vec2, margins: Margins) -> Self {
+        Self {
+            uv_min,
+            uv_max,
+            margins,
+        }
+    }
+
+    /// the region `px_min..px_max` of a texture with size `tex_size` where `margins` are given in pixels
+    pub fn from_pixels(tex_size: Vec2, px_min: Vec2, px_max: Vec2, margins: Margins) -> Self {
+        Self::new(
+            px_min / tex_size,
+            px_max / tex_size,
+            Margins::new(
+                margins.left / tex_size.x,
+                margins.right / tex_size.x,
+                margins.top / tex_size.y,
+                margins.bottom / tex_size.y,
+            ),
+        )
+    }
+}
+
+/// radii of the four corners of a rect, `x` is the horizontal and `y` the vertical radius
+/// so corners can be elliptical
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CornerRadii {
-    pub tl: f32,
-    pub tr: f32,
-    pub bl: f32,
-    pub br: f32,
+    pub tl: Vec2,
+    pub tr: Vec2,
+    pub bl: Vec2,
+    pub br: Vec2,
 }
 
 impl From<f32> for CornerRadii {
@@ -121,8 +605,18 @@ impl From<f32> for CornerRadii {
     }
 }
 
+impl From<Vec2> for CornerRadii {
+    fn from(value: Vec2) -> Self {
+        Self::elliptical(value)
+    }
+}
+
 impl CornerRadii {
     pub fn new(tl: f32, tr: f32, bl: f32, br: f32) -> Self {
+        Self::new_elliptical(Vec2::splat(tl), Vec2::splat(tr), Vec2::splat(bl), Vec2::splat(br))
+    }
+
+    pub fn new_elliptical(tl: Vec2, tr: Vec2, bl: Vec2, br: Vec2) -> Self {
         Self { tl, tr, bl, br }
     }
 
@@ -130,6 +624,11 @@ impl CornerRadii {
         Self::new(r, r, r, r)
     }
 
+    /// the same elliptical radii for all corners
+    pub fn elliptical(r: Vec2) -> Self {
+        Self::new_elliptical(r, r, r, r)
+    }
+
     pub fn zero() -> Self {
         Self::all(0.0)
     }
@@ -142,8 +641,64 @@ impl CornerRadii {
         Self::new(0.0, 0.0, r, r)
     }
 
+    pub fn left(r: f32) -> Self {
+        Self::new(r, 0.0, r, 0.0)
+    }
+
+    pub fn right(r: f32) -> Self {
+        Self::new(0.0, r, 0.0, r)
+    }
+
+    pub fn with_top(mut self, r: f32) -> Self {
+        self.tl = Vec2::splat(r);
+        self.tr = Vec2::splat(r);
+        self
+    }
+
+    pub fn with_bottom(mut self, r: f32) -> Self {
+        self.bl = Vec2::splat(r);
+        self.br = Vec2::splat(r);
+        self
+    }
+
     pub fn any_round_corners(&self) -> bool {
-        !(self.tl == 0.0 && self.tr == 0.0 && self.bl == 0.0 && self.br == 0.0)
+        [self.tl, self.tr, self.bl, self.br]
+            .iter()
+            .any(|r| r.x > 0.0 && r.y > 0.0)
+    }
+
+    /// grows every rounded corner by `d`, square corners stay square
+    pub fn expand(self, d: f32) -> Self {
+        let grow = |r: Vec2| {
+            if r.x > 0.0 && r.y > 0.0 {
+                (r + d).max(Vec2::ZERO)
+            } else {
+                r
+            }
+        };
+        Self::new_elliptical(grow(self.tl), grow(self.tr), grow(self.bl), grow(self.br))
+    }
+
+    /// scales all radii down uniformly so that adjacent corners don't overlap inside `size`
+    pub fn clamp(self, size: Vec2) -> Self {
+        let size = size.max(Vec2::ZERO);
+        // NaN radii become 0 and infinite ones the size of the rect
+        let fit = |r: Vec2| r.max(Vec2::ZERO).min(size);
+        let (tl, tr, br, bl) = (fit(self.tl), fit(self.tr), fit(self.br), fit(self.bl));
+        let ratio = |len: f32, r0: f32, r1: f32| {
+            if r0 + r1 > len {
+                len / (r0 + r1)
+            } else {
+                1.0
+            }
+        };
+
+        let f = ratio(size.x, tl.x, tr.x)
+            .min(ratio(size.x, bl.x, br.x))
+            .min(ratio(size.y, tl.y, bl.y))
+            .min(ratio(size.y, tr.y, br.y));
+
+        Self::new_elliptical(tl * f, tr * f, bl * f, br * f)
     }
 }
 
@@ -155,8 +710,13 @@ stacked_fields_struct!(Style {
     line_height: f32,
     text_size: f32,
     text_col: RGBA,
+    // how `Context::text` and the labels of widgets fit into the available width
+    text_wrap: TextWrap,
+    text_max_lines: Option<u32>,
 
     btn_roundness: f32,
+    // per corner radii of buttons and other widgets, overrides `btn_roundness` when set
+    btn_corner_radii: Option<CornerRadii>,
 
     btn_default: RGBA,
     btn_hover: RGBA,
@@ -169,6 +729,8 @@ stacked_fields_struct!(Style {
     panel_dark_bg: RGBA,
 
     panel_corner_radius: f32,
+    // per corner radii of panels, overrides `panel_corner_radius` when set
+    panel_corner_radii: Option<CornerRadii>,
     panel_outline: Outline,
     panel_hover_outline: Outline,
     panel_padding: f32,
@@ -180,12 +742,85 @@ stacked_fields_struct!(Style {
     spacing_v: f32,
 
     red: RGBA,
+
+    // multiplier the sizes of the style are scaled by, see `StyleTable::rescale`
+    ui_scale: f32,
 });
 
 impl StyleTable {
     pub fn btn_corner_radius(&self) -> f32 {
         self.btn_roundness() * self.line_height()
     }
+
+    pub fn btn_corners(&self) -> CornerRadii {
+        self.btn_corner_radii()
+            .unwrap_or_else(|| CornerRadii::all(self.btn_corner_radius()))
+    }
+
+    pub fn panel_corners(&self) -> CornerRadii {
+        self.panel_corner_radii()
+            .unwrap_or_else(|| CornerRadii::all(self.panel_corner_radius()))
+    }
+
+    /// multiplies all sizes and the [`StyleVar::UiScale`] by `factor`, pushed vars included
+    /// so they are restored at the new scale
+    pub fn rescale(&mut self, factor: f32) {
+        for var in self.values.iter_mut().chain(self.var_stack.iter_mut()) {
+            scale_style_var(var, factor);
+        }
+    }
+
+    /// pushes every size of the current style multiplied by `factor`, returns the number of
+    /// pushed vars to pop
+    pub fn push_scaled(&mut self, factor: f32) -> u32 {
+        let mut n = 0;
+        for mut var in self.values {
+            if scale_style_var(&mut var, factor) {
+                self.push_var(var);
+                n += 1;
+            }
+        }
+        n
+    }
+}
+
+/// multiplies `var` by `f` if it is a size, returns false for other vars
+fn scale_style_var(var: &mut StyleVar, f: f32) -> bool {
+    use StyleVar as SV;
+    match var {
+        SV::TitlebarHeight(v)
+        | SV::WindowTitlebarHeight(v)
+        | SV::LineHeight(v)
+        | SV::TextSize(v)
+        | SV::PanelCornerRadius(v)
+        | SV::PanelPadding(v)
+        | SV::ScrollbarWidth(v)
+        | SV::ScrollbarPadding(v)
+        | SV::SpacingH(v)
+        | SV::SpacingV(v)
+        | SV::UiScale(v) => *v *= f,
+        SV::BtnCornerRadii(Some(c)) | SV::PanelCornerRadii(Some(c)) => {
+            *c = CornerRadii::new_elliptical(c.tl * f, c.tr * f, c.bl * f, c.br * f)
+        }
+        SV::PanelOutline(o) | SV::PanelHoverOutline(o) => o.width *= f,
+        // colors, and the button roundness which is relative to the line height
+        SV::TitlebarColor(_)
+        | SV::TextCol(_)
+        | SV::TextWrap(_)
+        | SV::TextMaxLines(_)
+        | SV::BtnRoundness(_)
+        | SV::BtnCornerRadii(None)
+        | SV::BtnDefault(_)
+        | SV::BtnHover(_)
+        | SV::BtnPress(_)
+        | SV::BtnPressText(_)
+        | SV::WindowBg(_)
+        | SV::PanelBg(_)
+        | SV::PanelDarkBg(_)
+        | SV::PanelCornerRadii(None)
+        | SV::Red(_) => return false,
+    }
+    true
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -200,6 +835,8 @@ pub struct NextPanelData {
     pub min_size: Vec2,
     pub max_size: Vec2,
     pub content_size: Option<Vec2>,
+    pub auto_size: bool,
+    pub anchor: Option<PanelAnchor>,
 }
 
 impl Default for NextPanelData {
@@ -222,6 +859,8 @@ impl NextPanelData {
             min_size: Vec2::ZERO,
             max_size: Vec2::INFINITY,
             content_size: None,
+            auto_size: false,
+            anchor: None,
         }
     }
 
@@ -252,23 +891,114 @@ impl PrevItemData {
         }
     }
 
-    pub fn reset(&mut self) {
-        *self = PrevItemData::new()
+    pub fn reset(&mut self) {
+        *self = PrevItemData::new()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layout {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PanelPlacement {
+    #[default]
+    TopLeft,
+    Center,
+}
+
+/// a point on the border or in the center of a rect
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// position of the anchor relative to the size of a rect, from (0, 0) to (1, 1)
+    pub fn align(self) -> Vec2 {
+        match self {
+            Anchor::TopLeft => Vec2::new(0.0, 0.0),
+            Anchor::Top => Vec2::new(0.5, 0.0),
+            Anchor::TopRight => Vec2::new(1.0, 0.0),
+            Anchor::Left => Vec2::new(0.0, 0.5),
+            Anchor::Center => Vec2::new(0.5, 0.5),
+            Anchor::Right => Vec2::new(1.0, 0.5),
+            Anchor::BottomLeft => Vec2::new(0.0, 1.0),
+            Anchor::Bottom => Vec2::new(0.5, 1.0),
+            Anchor::BottomRight => Vec2::new(1.0, 1.0),
+        }
+    }
+
+    pub fn point(self, rect: Rect) -> Vec2 {
+        rect.min + rect.size() * self.align()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnchorTarget {
+    /// the window below its titlebar
+    Screen,
+    Panel(Id),
+}
+
+/// keeps a panel at a position relative to the screen or to another panel
+///
+/// the position is recomputed every frame, so anchored panels follow window resizes and the
+/// panel they are attached to. anchored panels can not be moved by the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PanelAnchor {
+    pub target: AnchorTarget,
+    /// point on the target
+    pub target_anchor: Anchor,
+    /// point on the panel placed on `target_anchor`
+    pub anchor: Anchor,
+    /// added to the position after anchoring
+    pub offset: Vec2,
+}
+
+impl PanelAnchor {
+    /// places the `anchor` point of the panel on the same point of the screen
+    ///
+    /// e.g. `PanelAnchor::screen(Anchor::TopRight, Vec2::new(-10.0, 10.0))`
+    pub fn screen(anchor: Anchor, offset: Vec2) -> Self {
+        Self {
+            target: AnchorTarget::Screen,
+            target_anchor: anchor,
+            anchor,
+            offset,
+        }
+    }
+
+    /// places the `anchor` point of the panel on the `target_anchor` point of the top level
+    /// panel `name`
+    ///
+    /// e.g. `PanelAnchor::panel("Tools", Anchor::TopLeft, Anchor::TopRight, Vec2::ZERO)` places
+    /// the panel right next to `Tools`
+    pub fn panel(name: &str, anchor: Anchor, target_anchor: Anchor, offset: Vec2) -> Self {
+        Self {
+            target: AnchorTarget::Panel(Id::from_str(name)),
+            target_anchor,
+            anchor,
+            offset,
+        }
     }
-}
-
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum Layout {
-    #[default]
-    Vertical,
-    Horizontal,
-}
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum PanelPlacement {
-    #[default]
-    TopLeft,
-    Center,
+    /// position of a panel with `size` anchored to `target`
+    pub fn panel_pos(&self, target: Rect, size: Vec2) -> Vec2 {
+        self.target_anchor.point(target) - self.anchor.align() * size + self.offset
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -358,6 +1088,7 @@ impl PanelAction {
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IdMap<T> {
     pub map: HashMap<Id, T>,
 }
@@ -483,6 +1214,7 @@ impl<T> std::ops::IndexMut<Id> for IdMap<T> {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TabBar {
     pub panel_id: Id,
     pub id: Id,
@@ -601,6 +1333,7 @@ impl TabBar {
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TabItem {
     pub id: Id,
     pub width: f32,
@@ -614,6 +1347,8 @@ pub struct TextInputState {
     pub edit: ctext::Editor<'static>,
     pub fonts: FontTable,
     pub multiline: bool,
+    /// the text was edited since the owning widget last cleared the flag
+    pub changed: bool,
 }
 
 impl std::hash::Hash for TextInputState {
@@ -637,7 +1372,7 @@ impl TextInputState {
         let font_attrib = fonts.get_font_attrib(text.font);
         buffer.set_text(
             &mut fonts.sys(),
-            &text.string,
+            &sanitize_text(&text.string),
             &font_attrib,
             ctext::Shaping::Advanced,
         );
@@ -649,10 +1384,12 @@ impl TextInputState {
             edit,
             fonts,
             multiline,
+            changed: false,
         }
     }
 
     pub fn layout_text(&self, cache: &mut GlyphCache, wgpu: &WGPU) -> ShapedText {
+        crate::profile_scope!("shape_text_input");
         use ctext::Edit;
 
         let buffer = match self.edit.buffer_ref() {
@@ -663,6 +1400,8 @@ impl TextInputState {
         let mut glyphs = Vec::new();
         let mut width = 0.0;
         let mut height = 0.0;
+        let rtl = buffer.layout_runs().next().is_some_and(|run| run.rtl);
+        let baseline = buffer.layout_runs().next().map_or(0.0, |run| run.line_y);
 
         for run in buffer.layout_runs() {
             width = run.line_w.max(width);
@@ -688,6 +1427,8 @@ impl TextInputState {
             glyphs,
             width,
             height,
+            rtl,
+            baseline,
         };
         text
     }
@@ -721,23 +1462,67 @@ impl TextInputState {
 
     pub fn paste(&mut self, text: &str) {
         use ctext::Edit;
-        self.edit.insert_string(text, None)
+        self.changed |= !text.is_empty() || self.has_selection();
+        self.edit.insert_string(&sanitize_text(text), None)
+    }
+
+    /// replaces the whole text
+    pub fn set_text(&mut self, text: &str) {
+        use ctext::Edit;
+        self.changed |= self.copy_all() != text;
+        self.select_all();
+        self.edit.delete_selection();
+        self.edit.insert_string(&sanitize_text(text), None)
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        use ctext::Edit;
+        self.edit
+            .with_buffer(|buf| buf.lines.iter().map(|l| l.text().to_string()).collect())
+    }
+
+    /// `(anchor, focus)` of the selection, both are the cursor if nothing is selected
+    pub fn text_selection(&self) -> (TextPos, TextPos) {
+        use ctext::{Edit, Selection};
+        let cursor = self.edit.cursor();
+        let anchor = match self.edit.selection() {
+            Selection::None => cursor,
+            Selection::Normal(c) | Selection::Line(c) | Selection::Word(c) => c,
+        };
+        (
+            TextPos::new(anchor.line, anchor.index),
+            TextPos::new(cursor.line, cursor.index),
+        )
+    }
+
+    pub fn set_text_selection(&mut self, anchor: TextPos, focus: TextPos) {
+        use ctext::{Edit, Selection};
+        self.edit.set_cursor(ctext::Cursor::new(focus.line, focus.index));
+        if anchor == focus {
+            self.edit.set_selection(Selection::None);
+        } else {
+            self.edit
+                .set_selection(Selection::Normal(ctext::Cursor::new(anchor.line, anchor.index)));
+        }
     }
 
     pub fn delete(&mut self) {
         use ctext::{Action, Edit};
+        let before = self.copy_all();
         self.edit.action(&mut self.fonts.sys(), Action::Delete);
+        self.changed |= self.copy_all() != before;
     }
 
     pub fn delete_selection(&mut self) {
         use ctext::Edit;
-        self.edit.delete_selection();
+        self.changed |= self.edit.delete_selection();
     }
 
     pub fn enter(&mut self) {
         use ctext::{Action, Edit};
         if self.multiline {
             self.edit.action(&mut self.fonts.sys(), Action::Enter);
+            self.changed = true;
         }
     }
 
@@ -746,20 +1531,24 @@ impl TextInputState {
         self.edit.action(&mut self.fonts.sys(), Action::Escape);
     }
 
-    pub fn backspace(&mut self, mods: &winit::keyboard::ModifiersState) {
+    pub fn backspace(&mut self, mods: Modifiers) {
         use ctext::{Action, Edit, Motion};
         let ctrl = mods.control_key();
 
-        let sys = &mut self.fonts.sys();
+        let before = self.copy_all();
+        {
+            let sys = &mut self.fonts.sys();
 
-        if ctrl && self.edit.selection_bounds().is_none() {
-            let end = self.edit.cursor();
-            self.edit.action(sys, Action::Motion(Motion::LeftWord));
-            let start = self.edit.cursor();
-            self.edit.delete_range(start, end);
-        } else {
-            self.edit.action(sys, Action::Backspace)
+            if ctrl && self.edit.selection_bounds().is_none() {
+                let end = self.edit.cursor();
+                self.edit.action(sys, Action::Motion(Motion::LeftWord));
+                let start = self.edit.cursor();
+                self.edit.delete_range(start, end);
+            } else {
+                self.edit.action(sys, Action::Backspace)
+            }
         }
+        self.changed |= self.copy_all() != before;
     }
 
     pub fn deselect_all(&mut self) {
@@ -787,7 +1576,7 @@ impl TextInputState {
         self.edit.set_selection(Selection::Normal(end));
     }
 
-    pub fn move_cursor_up(&mut self, mods: &winit::keyboard::ModifiersState) {
+    pub fn move_cursor_up(&mut self, mods: Modifiers) {
         use ctext::{Action, Edit, Motion, Selection};
 
         let ctrl = mods.control_key();
@@ -823,7 +1612,7 @@ impl TextInputState {
         }
     }
 
-    pub fn move_cursor_down(&mut self, mods: &winit::keyboard::ModifiersState) {
+    pub fn move_cursor_down(&mut self, mods: Modifiers) {
         use ctext::{Action, Edit, Motion, Selection};
 
         let ctrl = mods.control_key();
@@ -859,7 +1648,7 @@ impl TextInputState {
         }
     }
 
-    pub fn move_cursor_right(&mut self, mods: &winit::keyboard::ModifiersState) {
+    pub fn move_cursor_right(&mut self, mods: Modifiers) {
         use ctext::{Action, Edit, Motion, Selection};
 
         let ctrl = mods.control_key();
@@ -895,7 +1684,7 @@ impl TextInputState {
         }
     }
 
-    pub fn move_cursor_left(&mut self, mods: &winit::keyboard::ModifiersState) {
+    pub fn move_cursor_left(&mut self, mods: Modifiers) {
         use ctext::{Action, Edit, Motion, Selection};
 
         let ctrl = mods.control_key();
@@ -931,6 +1720,24 @@ impl TextInputState {
         }
     }
 
+    /// forwards the mouse input of the widget to the editor
+    ///
+    /// a double press selects a word and a triple press the whole line, dragging afterwards
+    /// extends the selection by words or lines respectively
+    pub fn handle_mouse(&mut self, pos: Vec2, sig: Signal) {
+        if sig.contains(Signal::JUST_PRESSED_LEFT) {
+            if sig.triple_pressed() {
+                self.mouse_triple_clicked(pos);
+            } else if sig.double_pressed() {
+                self.mouse_double_clicked(pos);
+            } else {
+                self.mouse_pressed(pos);
+            }
+        } else if sig.dragging() {
+            self.mouse_dragging(pos);
+        }
+    }
+
     pub fn mouse_pressed(&mut self, pos: Vec2) {
         use ctext::{Action, Edit};
         let mut pos = pos.as_ivec2();
@@ -978,6 +1785,55 @@ impl TextInputState {
     }
 }
 
+/// which interactions an allocated rect responds to, see [`Context::allocate_rect`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Sense {
+    /// only reports hovering, the item never becomes active
+    #[default]
+    Hover,
+    /// becomes active when pressed, use for buttons and drag handles
+    Press,
+    /// becomes active on a full click
+    Click,
+    /// becomes active when the mouse is released over it
+    Release,
+}
+
+impl Sense {
+    pub fn item_flags(self) -> ItemFlags {
+        match self {
+            Sense::Hover => ItemFlags::NONE,
+            Sense::Press => ItemFlags::SET_ACTIVE_ON_PRESS,
+            Sense::Click => ItemFlags::SET_ACTIVE_ON_CLICK,
+            Sense::Release => ItemFlags::SET_ACTIVE_ON_RELEASE,
+        }
+    }
+}
+
+/// a reusable ui element, added with [`Context::add`]
+///
+/// ```ignore
+/// struct Swatch(RGBA);
+///
+/// impl Widget for Swatch {
+///     fn ui(self, ui: &mut Context) -> Signal {
+///         let size = Vec2::splat(ui.style().line_height());
+///         let (rect, sig) = ui.allocate_rect(size, Sense::Click);
+///         ui.painter(rect).draw(rect.draw_rect().fill(self.0).corners(4.0));
+///         sig
+///     }
+/// }
+/// ```
+pub trait Widget {
+    fn ui(self, ui: &mut Context) -> Signal;
+}
+
+impl<F: FnOnce(&mut Context) -> Signal> Widget for F {
+    fn ui(self, ui: &mut Context) -> Signal {
+        self(ui)
+    }
+}
+
 //---------------------------------------------------------------------------------------
 // END TYPES
 
@@ -1015,6 +1871,7 @@ macros::flags!(
     RELEASED_LEFT,
     RELEASED_MIDDLE,
     RELEASED_RIGHT,
+    RELEASED_KEYBOARD,
 
     CLICKED_LEFT,
     CLICKED_MIDDLE,
@@ -1028,6 +1885,10 @@ macros::flags!(
     DOUBLE_PRESSED_MIDDLE,
     DOUBLE_PRESSED_RIGHT,
 
+    TRIPLE_PRESSED_LEFT,
+    TRIPLE_PRESSED_MIDDLE,
+    TRIPLE_PRESSED_RIGHT,
+
     TRIPLE_CLICKED_LEFT,
     TRIPLE_CLICKED_MIDDLE,
     TRIPLE_CLICKED_RIGHT,
@@ -1056,10 +1917,56 @@ sig_fn!(pressed => PRESSED_LEFT, PRESSED_KEYBOARD);
 sig_fn!(clicked => CLICKED_LEFT, PRESSED_KEYBOARD);
 sig_fn!(double_clicked => DOUBLE_CLICKED_LEFT);
 sig_fn!(double_pressed => DOUBLE_PRESSED_LEFT);
+sig_fn!(triple_clicked => TRIPLE_CLICKED_LEFT);
+sig_fn!(triple_pressed => TRIPLE_PRESSED_LEFT);
 sig_fn!(dragging => DRAGGING_LEFT);
-sig_fn!(released => RELEASED_LEFT);
+sig_fn!(released => RELEASED_LEFT, RELEASED_KEYBOARD);
 sig_fn!(keyboard_focused => GAINED_KEYBOARD_FOCUS);
 
+/// interaction with a value widget, e.g. [`Context::slider_f32`] or [`Context::input_text`]
+///
+/// an interaction lasts from pressing the widget until releasing it, or for text while it is
+/// focused. to record undo steps snapshot the value when [`Response::drag_stopped`] is set
+/// instead of on every change. changes from outside an interaction, e.g. from a screen
+/// reader, start and stop in the same frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Response {
+    signal: Signal,
+    changed: bool,
+    drag_started: bool,
+    drag_stopped: bool,
+}
+
+impl Response {
+    pub(crate) fn new(signal: Signal, changed: bool, started: bool, stopped: bool) -> Self {
+        Self {
+            signal,
+            changed,
+            drag_started: started,
+            drag_stopped: stopped,
+        }
+    }
+
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// the value was changed this frame
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// an interaction started this frame
+    pub fn drag_started(&self) -> bool {
+        self.drag_started
+    }
+
+    /// an interaction ended this frame
+    pub fn drag_stopped(&self) -> bool {
+        self.drag_stopped
+    }
+}
+
 // impl fmt::Display for Signal {
 //     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 //         if *self == Self::NONE {
@@ -1091,6 +1998,20 @@ pub struct DrawCmd {
 
     pub clip_rect: Rect,
     pub clip_rect_used: bool,
+    /// index + 1 into [`DrawListData::clip_masks`], 0 if the command is not masked
+    pub clip_mask: u32,
+    /// `TriangleList` or `LineList`
+    pub topology: wgpu::PrimitiveTopology,
+    pub effect: EffectId,
+}
+
+/// a clip region that is not a rect, applied with the stencil buffer in addition to the clip
+/// rect, see [`DrawList::push_clip_mask`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipShape {
+    RoundedRect(Rect, CornerRadii),
+    /// a closed polygon, overlapping parts are filled with the even-odd rule
+    Polygon(Vec<Vec2>),
 }
 
 impl Default for DrawCmd {
@@ -1103,6 +2024,9 @@ impl Default for DrawCmd {
             idx_count: 0,
             clip_rect: Rect::NAN,
             clip_rect_used: false,
+            clip_mask: 0,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            effect: EffectId::NONE,
         }
     }
 }
@@ -1122,16 +2046,32 @@ impl DrawList {
         }
     }
 
+    /// see [`DrawListData::resolve_pending_shapes`]
+    pub fn resolve_pending_shapes(&self) {
+        self.data.borrow_mut().resolve_pending_shapes();
+    }
+
+    /// the data with the pending shapes resolved. if the list is already borrowed, e.g. by
+    /// [`Self::clip_masks`], they have to be resolved before
+    fn resolved_data(&self) -> Ref<'_, DrawListData> {
+        if let Ok(mut data) = self.data.try_borrow_mut() {
+            data.resolve_pending_shapes();
+        }
+        let data = self.data.borrow();
+        debug_assert!(data.pending_shapes.is_empty(), "draw list read with pending shapes");
+        data
+    }
+
     pub fn commands(&self) -> Ref<'_, [DrawCmd]> {
-        Ref::map(self.data.borrow(), |data| data.cmd_buffer.as_slice())
+        Ref::map(self.resolved_data(), |data| data.cmd_buffer.as_slice())
     }
 
     pub fn vtx_slice(&self, range: std::ops::Range<usize>) -> Ref<'_, [Vertex]> {
-        Ref::map(self.data.borrow(), |data| &data.vtx_buffer[range])
+        Ref::map(self.resolved_data(), |data| &data.vtx_buffer[range])
     }
 
     pub fn idx_slice(&self, range: std::ops::Range<usize>) -> Ref<'_, [u32]> {
-        Ref::map(self.data.borrow(), |data| &data.idx_buffer[range])
+        Ref::map(self.resolved_data(), |data| &data.idx_buffer[range])
     }
 
     pub fn current_clip_rect(&self) -> Rect {
@@ -1160,6 +2100,111 @@ impl DrawList {
         data.clear();
     }
 
+    pub fn add_polyline(&self, points: &[Vec2], stroke: impl Into<Stroke>, closed: bool) {
+        self.data
+            .borrow_mut()
+            .add_polyline(points, stroke.into(), closed);
+    }
+
+    pub fn add_polyline_ex(
+        &self,
+        points: &[Vec2],
+        stroke: impl Into<Stroke>,
+        closed: bool,
+        mode: LineMode,
+    ) {
+        self.data
+            .borrow_mut()
+            .add_polyline_ex(points, stroke.into(), closed, mode);
+    }
+
+    pub fn add_line(&self, a: Vec2, b: Vec2, stroke: impl Into<Stroke>) {
+        self.add_polyline(&[a, b], stroke, false);
+    }
+
+    /// angles in radians, counter clockwise starting at the positive x axis
+    pub fn add_arc(
+        &self,
+        center: Vec2,
+        radius: f32,
+        start_angle: f32,
+        sweep_angle: f32,
+        stroke: impl Into<Stroke>,
+    ) {
+        let mut data = self.data.borrow_mut();
+        data.path_clear();
+        data.path_arc(center, radius, start_angle, sweep_angle);
+        data.path_stroke(stroke.into(), false);
+    }
+
+    pub fn add_polyline_multicolor(&self, points: &[Vec2], cols: &[RGBA], thickness: f32, closed: bool) {
+        self.data
+            .borrow_mut()
+            .add_polyline_multicolor(points, cols, thickness, closed);
+    }
+
+    pub fn add_polyline_var_width(&self, points: &[Vec2], col: RGBA, thickness: &[f32], closed: bool) {
+        self.data
+            .borrow_mut()
+            .add_polyline_var_width(points, col, thickness, closed);
+    }
+
+    pub fn add_convex_poly_filled(&self, points: &[Vec2], col: RGBA) {
+        self.data.borrow_mut().add_convex_poly_filled(points, col);
+    }
+
+    pub fn add_rect_colormap(&self, rect: Rect, tex_id: TextureId, cmap: Colormap) {
+        self.data
+            .borrow_mut()
+            .add_rect_colormap(rect.min, rect.max, Vec2::ZERO, Vec2::ONE, tex_id, cmap);
+    }
+
+    pub fn add_convex_poly_filled_multicolor(&self, points: &[Vec2], cols: &[RGBA]) {
+        self.data
+            .borrow_mut()
+            .add_convex_poly_filled_multicolor(points, cols);
+    }
+
+    /// rounded rect filled with a vertical gradient
+    pub fn add_rect_gradient(&self, rect: Rect, corners: CornerRadii, top: RGBA, bottom: RGBA) {
+        let mut data = self.data.borrow_mut();
+        data.path_clear();
+        data.path_rect(rect.min, rect.max, corners);
+        let points = std::mem::take(&mut data.path);
+        let cols = points
+            .iter()
+            .map(|p| top.lerp(bottom, ((p.y - rect.min.y) / rect.height()).clamp(0.0, 1.0)))
+            .collect::<Vec<_>>();
+        data.add_convex_poly_filled_multicolor(&points, &cols);
+    }
+
+    /// rect filled with the colors of `gradient` along `axis`, without anti aliased edges
+    pub fn add_rect_gradient_stops(&self, rect: Rect, gradient: &Gradient, axis: Axis) {
+        let mut data = self.data.borrow_mut();
+        if gradient.stops.is_empty() || !data.clip_points(&[rect.min, rect.max], 0.0) {
+            return;
+        }
+        let (vtx, idx) = gradient.tessellate(rect, axis);
+        data.push_vtx_idx(&vtx, &idx);
+    }
+
+    pub fn image_nine_slice(&self, rect: Rect, tex_id: TextureId, margins: Margins, uv: NineSliceUV) {
+        self.image_nine_slice_tinted(rect, tex_id, margins, uv, RGBA::WHITE);
+    }
+
+    pub fn image_nine_slice_tinted(
+        &self,
+        rect: Rect,
+        tex_id: TextureId,
+        margins: Margins,
+        uv: NineSliceUV,
+        tint: RGBA,
+    ) {
+        self.data
+            .borrow_mut()
+            .add_image_nine_slice(rect, tex_id, margins, uv, tint);
+    }
+
     pub fn draw(&self, itm: impl DrawableRects) {
         itm.add_to_drawlist(self);
     }
@@ -1182,12 +2227,30 @@ impl DrawList {
         }
     }
 
+    /// see [`DrawListData::set_effect`]
+    pub fn set_effect(&self, effect: EffectId) {
+        self.data.borrow_mut().set_effect(effect);
+    }
+
     pub fn push_clip_rect(&self, rect: Rect) {
         self.data.borrow_mut().push_clip_rect(rect);
         if self.draw_clip_rect {
             self.add_draw_rect(rect.draw_rect().outline(Outline::inner(RGBA::RED, 2.0)));
         }
     }
+
+    /// clips the following draws to `shape`, the innermost mask replaces the outer ones
+    pub fn push_clip_mask(&self, shape: ClipShape) {
+        self.data.borrow_mut().push_clip_mask(shape);
+    }
+
+    pub fn pop_clip_mask(&self) {
+        self.data.borrow_mut().pop_clip_mask();
+    }
+
+    pub fn clip_masks(&self) -> Ref<'_, [Vec<Vec2>]> {
+        Ref::map(self.data.borrow(), |data| data.clip_masks.as_slice())
+    }
     // pub fn vertices(&self) -> Ref<'_, [Vertex]> {
     //     Ref::map(self.data.borrow(), |data| &data.vtx_buffer)
     // }
@@ -1310,6 +2373,14 @@ impl DrawList {
     // }
 }
 
+/// geometry moved out of a [`DrawListData`], see [`DrawListData::begin_channel`]
+#[derive(Debug, Default, Clone)]
+pub struct DrawChannel {
+    pub vtx_buffer: Vec<Vertex>,
+    pub idx_buffer: Vec<u32>,
+    pub cmd_buffer: Vec<DrawCmd>,
+}
+
 /// The draw list itself: holds geometry and draw commands
 #[derive(Clone)]
 pub struct DrawListData {
@@ -1321,44 +2392,409 @@ pub struct DrawListData {
     pub path: Vec<Vec2>,
     pub clip_rect: Rect,
     pub clip_stack: Vec<Rect>,
+    /// outlines of the pushed clip masks, see [`DrawCmd::clip_mask`]
+    pub clip_masks: Vec<Vec<Vec2>>,
+    pub clip_mask: u32,
+    pub clip_mask_stack: Vec<u32>,
+
+    pub circle_max_err: f32,
+    pub clip_content: bool,
+    /// quads drawn with the glyph atlas since the last clear, see [`PanelStats::glyphs`]
+    pub n_glyphs: usize,
+
+    /// tessellated shapes, usually shared between all drawlists of a context
+    pub shape_cache: Rc<RefCell<ShapeCache>>,
+    /// rounded rects that are not in the shape cache are tessellated together once the
+    /// geometry is read, on the rayon thread pool with the `parallel` feature. see
+    /// [`Self::resolve_pending_shapes`]
+    pub defer_tessellation: bool,
+    pending_shapes: Vec<PendingShape>,
+}
+
+/// a rounded rect recorded while [`DrawListData::defer_tessellation`] is set, its geometry is
+/// inserted at `vtx` and `idx` of the command `cmd` once it is tessellated
+#[derive(Debug, Clone, Copy)]
+struct PendingShape {
+    key: ShapeKey,
+    min: Vec2,
+    size: Vec2,
+    corners: CornerRadii,
+    tint: RGBA,
+    outline: Outline,
+    max_err: f32,
+    cmd: usize,
+    vtx: usize,
+    idx: usize,
+}
+
+impl fmt::Debug for DrawListData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrawList")
+            .field("vtx_buffer_size", &self.vtx_buffer.len())
+            .field("idx_buffer_size", &self.idx_buffer.len())
+            .field("cmd_buffer", &self.cmd_buffer)
+            .field("resolution", &self.resolution)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl Default for DrawListData {
+    fn default() -> Self {
+        Self {
+            vtx_buffer: vec![],
+            idx_buffer: vec![],
+            cmd_buffer: vec![],
+            resolution: 20.0,
+            path: vec![],
+            clip_stack: vec![],
+            clip_rect: Rect::INFINITY,
+            clip_masks: vec![],
+            clip_mask: 0,
+            clip_mask_stack: vec![],
+
+            circle_max_err: 0.3,
+            clip_content: true,
+            n_glyphs: 0,
+            shape_cache: Rc::new(RefCell::new(ShapeCache::new())),
+            defer_tessellation: cfg!(feature = "parallel"),
+            pending_shapes: vec![],
+        }
+    }
+}
+
+/// identifies a tessellated shape independent of its position
+///
+/// floats are compared by their bit patterns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShapeKey {
+    size: [u32; 2],
+    corners: [[u32; 2]; 4],
+    fill: [u32; 4],
+    outline_col: [u32; 4],
+    outline_width: u32,
+    circle_max_err: u32,
+}
+
+impl ShapeKey {
+    pub fn rounded_rect(
+        size: Vec2,
+        corners: CornerRadii,
+        fill: RGBA,
+        outline: Outline,
+        circle_max_err: f32,
+    ) -> Self {
+        let col_bits = |c: RGBA| [c.r.to_bits(), c.g.to_bits(), c.b.to_bits(), c.a.to_bits()];
+
+        Self {
+            size: [size.x.to_bits(), size.y.to_bits()],
+            corners: [corners.tl, corners.tr, corners.bl, corners.br]
+                .map(|r| [r.x.to_bits(), r.y.to_bits()]),
+            fill: col_bits(fill),
+            outline_col: col_bits(outline.col),
+            outline_width: outline.width.to_bits(),
+            circle_max_err: circle_max_err.to_bits(),
+        }
+    }
+}
+
+/// tessellated geometry of a shape with its min corner at the origin
+#[derive(Debug, Clone, Default)]
+pub struct ShapeTemplate {
+    pub fill_vtx: Vec<Vertex>,
+    pub fill_idx: Vec<u32>,
+    pub outline_vtx: Vec<Vertex>,
+    pub outline_idx: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShapeCacheStats {
+    pub entries: usize,
+    pub hits: usize,
+    pub misses: usize,
+    pub evicted: usize,
+}
+
+impl fmt::Display for ShapeCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.hits + self.misses;
+        let rate = if total == 0 {
+            0.0
+        } else {
+            self.hits as f32 / total as f32 * 100.0
+        };
+        write!(
+            f,
+            "{} entries, {} hits, {} misses ({rate:.1}%), {} evicted",
+            self.entries, self.hits, self.misses, self.evicted
+        )
+    }
+}
+
+/// LRU cache of tessellated shapes
+///
+/// shapes that are drawn with the same parameters (e.g. buttons of the same size) only have to be
+/// tessellated once, afterwards the cached vertices are offset to the new position.
+#[derive(Debug, Clone)]
+pub struct ShapeCache {
+    pub shapes: HashMap<ShapeKey, (ShapeTemplate, u64)>,
+    pub capacity: usize,
+    pub enabled: bool,
+    /// incremented on every lookup, used to find the least recently used shapes
+    tick: u64,
+    /// holds the last shape while caching is disabled
+    scratch: ShapeTemplate,
+    /// stats of the current frame
+    pub stats: ShapeCacheStats,
+    /// stats of the previous frame
+    pub prev_stats: ShapeCacheStats,
+}
+
+impl ShapeCache {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            shapes: HashMap::default(),
+            capacity,
+            enabled: true,
+            tick: 0,
+            scratch: ShapeTemplate::default(),
+            stats: ShapeCacheStats::default(),
+            prev_stats: ShapeCacheStats::default(),
+        }
+    }
+
+    /// the shape is cached and caching is enabled
+    pub fn is_cached(&self, key: &ShapeKey) -> bool {
+        self.enabled && self.shapes.contains_key(key)
+    }
+
+    pub fn get_or_insert_with(
+        &mut self,
+        key: ShapeKey,
+        f: impl FnOnce() -> ShapeTemplate,
+    ) -> &ShapeTemplate {
+        self.tick += 1;
+
+        if !self.enabled {
+            self.stats.misses += 1;
+            self.scratch = f();
+            return &self.scratch;
+        }
+
+        if self.shapes.contains_key(&key) {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+            if self.shapes.len() >= self.capacity {
+                self.evict();
+            }
+        }
+
+        let tick = self.tick;
+        let (shape, last_used) = self.shapes.entry(key).or_insert_with(|| (f(), tick));
+        *last_used = tick;
+        shape
+    }
+
+    /// removes the least recently used quarter of the shapes
+    fn evict(&mut self) {
+        let mut ticks: Vec<u64> = self.shapes.values().map(|(_, t)| *t).collect();
+        let n = (self.capacity / 4).max(1).min(ticks.len());
+        let (_, &mut oldest, _) = ticks.select_nth_unstable(n - 1);
+
+        let len = self.shapes.len();
+        self.shapes.retain(|_, (_, t)| *t > oldest);
+        self.stats.evicted += len - self.shapes.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    pub fn end_frame(&mut self) {
+        self.stats.entries = self.shapes.len();
+        self.prev_stats = self.stats;
+        self.stats = ShapeCacheStats::default();
+    }
+}
+
+fn calc_circle_segment_count(rad: f32, max_err: f32) -> u8 {
+    use std::f32::consts::PI;
+    let tmp = (PI / (1.0 - rad.min(max_err) / rad).cos()).ceil() as u32;
+    tmp.clamp(4, u8::MAX as u32) as u8
+}
+
+/// appends the outline of a rounded rect to `path`, see [`DrawListData::path_rect`]
+fn rounded_rect_path(
+    path: &mut Vec<Vec2>,
+    min: Vec2,
+    max: Vec2,
+    corners: CornerRadii,
+    max_err: f32,
+) {
+    const PI: f32 = std::f32::consts::PI;
+    let arc = |path: &mut Vec<Vec2>, center, radii: Vec2, start_angle| {
+        if radii.x > 0.0 && radii.y > 0.0 {
+            elliptical_arc_path(path, center, radii, start_angle, -PI / 2.0, max_err);
+        }
+    };
+
+    // radii larger than the rect would make the path self-intersect
+    let corners = corners.clamp(max - min);
+    let r0 = corners.tl;
+    let r1 = corners.tr;
+    let r2 = corners.br;
+    let r3 = corners.bl;
+    path.push(Vec2::new(min.x + r0.x, min.y));
+
+    path.push(Vec2::new(max.x - r1.x, min.y));
+    arc(path, Vec2::new(max.x - r1.x, min.y + r1.y), r1, PI / 2.0);
+
+    path.push(Vec2::new(max.x, min.y + r1.y));
+    path.push(Vec2::new(max.x, max.y - r2.y));
+    arc(path, Vec2::new(max.x - r2.x, max.y - r2.y), r2, 0.0);
+
+    path.push(Vec2::new(max.x - r2.x, max.y));
+    path.push(Vec2::new(min.x + r3.x, max.y));
+    arc(path, Vec2::new(min.x + r3.x, max.y - r3.y), r3, -PI / 2.0);
+
+    path.push(Vec2::new(min.x, max.y - r3.y));
+    path.push(Vec2::new(min.x, min.y + r0.y));
+    arc(path, Vec2::new(min.x + r0.x, min.y + r0.y), r0, PI);
+}
+
+fn elliptical_arc_path(
+    path: &mut Vec<Vec2>,
+    center: Vec2,
+    radii: Vec2,
+    start_angle: f32,
+    sweep_angle: f32,
+    max_err: f32,
+) {
+    if radii.x == 0.0 || radii.y == 0.0 || sweep_angle == 0.0 {
+        return;
+    }
+
+    let segments = calc_circle_segment_count(radii.max_element(), max_err);
+    let step = sweep_angle / segments as f32;
+
+    for i in 0..=segments {
+        let theta = start_angle + step * (i as f32);
+        let p = Vec2::new(
+            center.x + theta.cos() * radii.x,
+            center.y - theta.sin() * radii.y,
+        );
+        path.push(p);
+    }
+}
+
+/// tessellates a rounded rect with its min corner at the origin. does not touch a
+/// [`DrawListData`], so shapes can be tessellated on other threads
+fn tessellate_rounded_rect(
+    size: Vec2,
+    corners: CornerRadii,
+    tint: RGBA,
+    outline: Outline,
+    max_err: f32,
+) -> ShapeTemplate {
+    crate::profile_scope!("tessellate_rounded_rect");
+    let mut path = Vec::new();
+    rounded_rect_path(&mut path, Vec2::ZERO, size, corners, max_err);
+
+    let (fill_vtx, fill_idx) = tessellate_convex_fill(&path, tint, true);
+    let (outline_vtx, outline_idx) = if outline.width != 0.0 {
+        tessellate_line(&path, outline.col, outline.width, true)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    ShapeTemplate {
+        fill_vtx,
+        fill_idx,
+        outline_vtx,
+        outline_idx,
+    }
+}
+
+static DRAW_VALIDATION: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// logs every primitive and text that is skipped or repaired because of invalid input, e.g. to
+/// find where NaN coordinates come from. off by default
+///
+/// invalid input never panics or hangs: primitives with non finite coordinates or widths are
+/// skipped, radii are clamped to the rect and text is sanitized, see [`sanitize_text`]
+pub fn set_draw_validation(enabled: bool) {
+    DRAW_VALIDATION.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
 
-    pub circle_max_err: f32,
-    pub clip_content: bool,
+/// reports invalid input of a primitive or text, see [`set_draw_validation`]
+fn report_invalid(what: &str, input: &dyn fmt::Debug) {
+    if DRAW_VALIDATION.load(std::sync::atomic::Ordering::Relaxed) {
+        log::warn!("invalid {what}: {input:?}");
+    }
 }
 
-impl fmt::Debug for DrawListData {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("DrawList")
-            .field("vtx_buffer_size", &self.vtx_buffer.len())
-            .field("idx_buffer_size", &self.idx_buffer.len())
-            .field("cmd_buffer", &self.cmd_buffer)
-            .field("resolution", &self.resolution)
-            .field("path", &self.path)
-            .finish()
+/// whether all points of a primitive are finite, reports it otherwise
+fn check_points(what: &str, points: &[Vec2]) -> bool {
+    if points.iter().all(|p| p.is_finite()) {
+        return true;
     }
+    report_invalid(what, &points);
+    false
 }
 
-impl Default for DrawListData {
-    fn default() -> Self {
-        Self {
-            vtx_buffer: vec![],
-            idx_buffer: vec![],
-            cmd_buffer: vec![],
-            resolution: 20.0,
-            path: vec![],
-            clip_stack: vec![],
-            clip_rect: Rect::INFINITY,
+fn is_valid_width(width: f32) -> bool {
+    width.is_finite() && width > 0.0
+}
 
-            circle_max_err: 0.3,
-            clip_content: true,
+/// splits geometry of primitives with `n` indices into chunks of at most `max_vtx` vertices and
+/// `max_idx` indices, vertices shared by primitives of different chunks are duplicated
+fn split_primitives(
+    vtx: &[Vertex],
+    idx: &[u32],
+    n: usize,
+    max_vtx: usize,
+    max_idx: usize,
+) -> Vec<(Vec<Vertex>, Vec<u32>)> {
+    let mut chunks = Vec::new();
+    let mut remap: HashMap<u32, u32> = HashMap::default();
+    let (mut chunk_vtx, mut chunk_idx) = (Vec::new(), Vec::new());
+    for prim in idx.chunks_exact(n) {
+        if prim.iter().any(|&i| i as usize >= vtx.len()) {
+            report_invalid("index", &prim);
+            continue;
+        }
+        if chunk_vtx.len() + n > max_vtx || chunk_idx.len() + n > max_idx {
+            chunks.push((std::mem::take(&mut chunk_vtx), std::mem::take(&mut chunk_idx)));
+            remap.clear();
+        }
+        for &i in prim {
+            let j = *remap.entry(i).or_insert_with(|| {
+                chunk_vtx.push(vtx[i as usize]);
+                chunk_vtx.len() as u32 - 1
+            });
+            chunk_idx.push(j);
         }
     }
+    if !chunk_idx.is_empty() {
+        chunks.push((chunk_vtx, chunk_idx));
+    }
+    chunks
 }
 
-fn calc_circle_segment_count(rad: f32, max_err: f32) -> u8 {
-    use std::f32::consts::PI;
-    let tmp = (PI / (1.0 - rad.min(max_err) / rad).cos()).ceil() as u32;
-    tmp.clamp(4, 512) as u8
+/// whether the corners and the outline of a rect are finite, reports it otherwise
+fn check_rect(min: Vec2, max: Vec2, outline: Outline) -> bool {
+    if min.is_finite() && max.is_finite() && outline.width.is_finite() {
+        return true;
+    }
+    report_invalid("rect", &(min, max, outline.width));
+    false
 }
 
 impl DrawListData {
@@ -1370,8 +2806,13 @@ impl DrawListData {
         self.vtx_buffer.clear();
         self.idx_buffer.clear();
         self.cmd_buffer.clear();
+        self.pending_shapes.clear();
         self.path.clear();
         self.clip_stack.clear();
+        self.clip_masks.clear();
+        self.clip_mask = 0;
+        self.clip_mask_stack.clear();
+        self.n_glyphs = 0;
     }
 
     fn calc_circle_segment_count(&self, radius: f32) -> u8 {
@@ -1423,6 +2864,44 @@ impl DrawListData {
         self.clip_rect
     }
 
+    pub fn set_clip_mask(&mut self, mask: u32) {
+        let empty = self.cmd_is_empty();
+        let cmd = self.current_draw_cmd();
+
+        if empty {
+            cmd.clip_mask = mask;
+        } else if cmd.clip_mask != mask {
+            self.begin_new_draw_cmd().clip_mask = mask;
+        }
+    }
+
+    pub fn push_clip_mask(&mut self, shape: ClipShape) {
+        if !self.clip_content {
+            return;
+        }
+        let points = match shape {
+            ClipShape::RoundedRect(rect, corners) => {
+                let path = std::mem::take(&mut self.path);
+                self.path_rect(rect.min, rect.max, corners);
+                std::mem::replace(&mut self.path, path)
+            }
+            ClipShape::Polygon(points) => points,
+        };
+
+        self.clip_masks.push(points);
+        self.clip_mask_stack.push(self.clip_mask);
+        self.clip_mask = self.clip_masks.len() as u32;
+        self.set_clip_mask(self.clip_mask);
+    }
+
+    pub fn pop_clip_mask(&mut self) {
+        if !self.clip_content {
+            return;
+        }
+        self.clip_mask = self.clip_mask_stack.pop().unwrap();
+        self.set_clip_mask(self.clip_mask);
+    }
+
     pub fn current_draw_cmd(&mut self) -> &mut DrawCmd {
         if self.cmd_buffer.is_empty() {
             self.cmd_buffer.push(DrawCmd::default())
@@ -1443,6 +2922,18 @@ impl DrawListData {
         let _ = self.begin_new_draw_cmd();
     }
 
+    /// the current command has no geometry, including shapes that are not tessellated yet
+    fn cmd_is_empty(&self) -> bool {
+        let Some(cmd) = self.cmd_buffer.last() else {
+            return true;
+        };
+        let pending = self
+            .pending_shapes
+            .last()
+            .is_some_and(|p| p.cmd + 1 == self.cmd_buffer.len());
+        cmd.vtx_count == 0 && !pending
+    }
+
     pub fn begin_new_draw_cmd(&mut self) -> &mut DrawCmd {
         let last = self.cmd_buffer.last().copied();
         // if let Some(last) = last {
@@ -1460,10 +2951,24 @@ impl DrawListData {
             cmd.texture_id = last.texture_id;
             cmd.clip_rect = last.clip_rect;
             cmd.clip_rect_used = last.clip_rect_used;
+            cmd.clip_mask = last.clip_mask;
+            cmd.topology = last.topology;
+            cmd.effect = last.effect;
         }
         cmd
     }
 
+    /// draws the following geometry with `effect`, [`EffectId::NONE`] for the default shader
+    pub fn set_effect(&mut self, effect: EffectId) {
+        let empty = self.cmd_is_empty();
+        let cmd = self.current_draw_cmd();
+        if empty {
+            cmd.effect = effect;
+        } else if cmd.effect != effect {
+            self.begin_new_draw_cmd().effect = effect;
+        }
+    }
+
     pub fn push_texture(&mut self, tex_id: TextureId) {
         if tex_id == TextureId::WHITE {
             return;
@@ -1487,6 +2992,43 @@ impl DrawListData {
 
     #[inline]
     pub fn push_vtx_idx(&mut self, vtx: &[Vertex], idx: &[u32]) {
+        self.push_vtx_idx_topology(vtx, idx, wgpu::PrimitiveTopology::TriangleList);
+    }
+
+    /// like [`Self::push_vtx_idx`], with `LineList` every pair of indices is a line
+    pub fn push_vtx_idx_topology(
+        &mut self,
+        vtx: &[Vertex],
+        idx: &[u32],
+        topology: wgpu::PrimitiveTopology,
+    ) {
+        let (max_vtx, max_idx) = (RenderData::MAX_VERTEX_COUNT, RenderData::MAX_INDEX_COUNT);
+        if vtx.len() > max_vtx as usize || idx.len() > max_idx as usize {
+            let n = if topology == wgpu::PrimitiveTopology::LineList { 2 } else { 3 };
+            for (vtx, idx) in split_primitives(vtx, idx, n, max_vtx as usize, max_idx as usize) {
+                self.push_vtx_idx_topology(&vtx, &idx, topology);
+            }
+            return;
+        }
+
+        let empty = self.cmd_is_empty();
+        let cmd = self.current_draw_cmd();
+        if empty {
+            cmd.topology = topology;
+        } else if cmd.topology != topology {
+            self.begin_new_draw_cmd().topology = topology;
+        }
+
+        // a command has to fit into a single draw call, see [`RenderData::MAX_VERTEX_COUNT`]
+        let empty = self.cmd_is_empty();
+        let cmd = self.current_draw_cmd();
+        if !empty
+            && (cmd.vtx_count + vtx.len() > RenderData::MAX_VERTEX_COUNT as usize
+                || cmd.idx_count + idx.len() > RenderData::MAX_INDEX_COUNT as usize)
+        {
+            self.begin_new_draw_cmd();
+        }
+
         let cmd = self.current_draw_cmd();
         let base = cmd.vtx_count as u32;
 
@@ -1498,6 +3040,74 @@ impl DrawListData {
         cmd.idx_count += idx.len();
     }
 
+    /// continues recording into empty buffers and returns the geometry recorded so far.
+    /// used with [`Self::end_channel`] and [`Self::append_channel`] to draw behind geometry
+    /// that is recorded before it, e.g. the background of a frame sized by its content
+    pub fn begin_channel(&mut self) -> DrawChannel {
+        self.resolve_pending_shapes();
+        let last = self.cmd_buffer.last().copied();
+        let prev = DrawChannel {
+            vtx_buffer: std::mem::take(&mut self.vtx_buffer),
+            idx_buffer: std::mem::take(&mut self.idx_buffer),
+            cmd_buffer: std::mem::take(&mut self.cmd_buffer),
+        };
+
+        // keep the texture and clip state of the current command
+        if let Some(last) = last {
+            self.cmd_buffer.push(DrawCmd {
+                vtx_offset: 0,
+                vtx_count: 0,
+                idx_offset: 0,
+                idx_count: 0,
+                ..last
+            });
+        }
+        prev
+    }
+
+    /// restores the geometry returned by [`Self::begin_channel`] and returns everything recorded since
+    pub fn end_channel(&mut self, prev: DrawChannel) -> DrawChannel {
+        self.resolve_pending_shapes();
+        DrawChannel {
+            vtx_buffer: std::mem::replace(&mut self.vtx_buffer, prev.vtx_buffer),
+            idx_buffer: std::mem::replace(&mut self.idx_buffer, prev.idx_buffer),
+            cmd_buffer: std::mem::replace(&mut self.cmd_buffer, prev.cmd_buffer),
+        }
+    }
+
+    /// appends the geometry of a channel on top of the current geometry
+    pub fn append_channel(&mut self, channel: DrawChannel) {
+        for cmd in &channel.cmd_buffer {
+            if cmd.idx_count == 0 {
+                continue;
+            }
+
+            let curr = self.current_draw_cmd();
+            let same_state = curr.texture_id == cmd.texture_id
+                && curr.clip_mask == cmd.clip_mask
+                && curr.topology == cmd.topology
+                && curr.effect == cmd.effect
+                && (curr.clip_rect == cmd.clip_rect
+                    || (curr.clip_rect.is_nan() && cmd.clip_rect.is_nan()));
+            let curr = if curr.idx_count == 0 || same_state {
+                curr
+            } else {
+                self.begin_new_draw_cmd()
+            };
+            curr.clip_rect_used |= cmd.clip_rect_used;
+            curr.texture_id = cmd.texture_id;
+            curr.clip_rect = cmd.clip_rect;
+            curr.clip_mask = cmd.clip_mask;
+            curr.effect = cmd.effect;
+
+            self.push_vtx_idx_topology(
+                &channel.vtx_buffer[cmd.vtx_offset..cmd.vtx_offset + cmd.vtx_count],
+                &channel.idx_buffer[cmd.idx_offset..cmd.idx_offset + cmd.idx_count],
+                cmd.topology,
+            );
+        }
+    }
+
     pub fn circle(&mut self, center: Vec2, radius: f32) -> DrawRect {
         let r = Vec2::splat(radius);
         let min = center - r;
@@ -1513,6 +3123,7 @@ impl DrawListData {
             fill: RGBA::ZERO,
             outline: Outline::none(),
             corners: CornerRadii::all(radius),
+            effect: EffectId::NONE,
         }
     }
 
@@ -1742,8 +3353,12 @@ impl DrawListData {
         outline: Outline,
         corners: CornerRadii,
     ) {
+        if !check_rect(min, max, outline) {
+            return;
+        }
+        let outline = outline.to_pixels();
         if !corners.any_round_corners() {
-            return self.add_rect(min, max, uv_min, uv_max, tex_id, tint, outline);
+            return self.add_rect_px(min, max, uv_min, uv_max, tex_id, tint, outline);
         }
 
         let offset = Vec2::splat(outline.offset());
@@ -1774,23 +3389,158 @@ impl DrawListData {
             max += Vec2::splat(offset);
         }
 
-        self.path_clear();
-        self.path_rect(min, max, corners);
+        let size = max - min;
+        let corners = corners.clamp(size);
+        let shape_cache = self.shape_cache.clone();
+        let mut shape_cache = shape_cache.borrow_mut();
+
+        let max_err = self.circle_max_err;
+        let key = ShapeKey::rounded_rect(size, corners, tint, outline, max_err);
+        if self.defer_tessellation && tex_id == TextureId::WHITE && !shape_cache.is_cached(&key) {
+            self.defer_shape(PendingShape {
+                key,
+                min,
+                size,
+                corners,
+                tint,
+                outline,
+                max_err,
+                cmd: 0,
+                vtx: 0,
+                idx: 0,
+            });
+            return;
+        }
+        let shape = shape_cache.get_or_insert_with(key, || {
+            tessellate_rounded_rect(size, corners, tint, outline, max_err)
+        });
+
+        let offset = |v: &Vertex| Vertex {
+            pos: v.pos + min,
+            ..*v
+        };
 
         let start = self.vtx_buffer.len();
-        let (vtx, idx) = tessellate_convex_fill(&self.path, tint, true);
-        self.push_vtx_idx(&vtx, &idx);
+        let vtx: Vec<Vertex> = shape.fill_vtx.iter().map(offset).collect();
+        self.push_vtx_idx(&vtx, &shape.fill_idx);
         let end = start + vtx.len();
         if tex_id != TextureId::WHITE {
             self.distribute_uvs(start, end, min, max, uv_min, uv_max, true, tex_id);
         }
 
-        if outline.width != 0.0 {
-            let (vtx_o, idx_o) = tessellate_line(&self.path, outline.col, outline.width, true);
-            self.push_vtx_idx(&vtx_o, &idx_o);
+        if !shape.outline_vtx.is_empty() {
+            let vtx: Vec<Vertex> = shape.outline_vtx.iter().map(offset).collect();
+            self.push_vtx_idx(&vtx, &shape.outline_idx);
+        }
+    }
+
+    /// records `shape` at the end of the current command, the command is set up like
+    /// [`Self::push_vtx_idx`] would
+    fn defer_shape(&mut self, mut shape: PendingShape) {
+        let topology = wgpu::PrimitiveTopology::TriangleList;
+        let empty = self.cmd_is_empty();
+        let cmd = self.current_draw_cmd();
+        if empty {
+            cmd.topology = topology;
+        } else if cmd.topology != topology {
+            self.begin_new_draw_cmd().topology = topology;
+        }
+
+        shape.cmd = self.cmd_buffer.len() - 1;
+        shape.vtx = self.vtx_buffer.len();
+        shape.idx = self.idx_buffer.len();
+        self.pending_shapes.push(shape);
+    }
+
+    /// tessellates the shapes recorded while [`Self::defer_tessellation`] is set and inserts
+    /// them where they were drawn, the result is the same as drawing them immediately
+    ///
+    /// with the `parallel` feature the shapes are tessellated on the rayon thread pool.
+    /// called before the geometry is read, e.g. by [`DrawList::commands`]
+    pub fn resolve_pending_shapes(&mut self) {
+        if self.pending_shapes.is_empty() {
+            return;
+        }
+        crate::profile_scope!("resolve_pending_shapes");
+        // recorded indices move by the vertices of the shapes inserted before them. geometry
+        // only refers to its own vertices, so these are all shapes inserted so far
+        fn copy_recorded(
+            vtx: &mut Vec<Vertex>,
+            idx: &mut Vec<u32>,
+            (recorded_vtx, recorded_idx): (&[Vertex], &[u32]),
+            shift: u32,
+        ) {
+            vtx.extend_from_slice(recorded_vtx);
+            idx.extend(recorded_idx.iter().map(|&k| k + shift));
+        }
+
+        let pending = std::mem::take(&mut self.pending_shapes);
+        let shape_cache = self.shape_cache.clone();
+        let mut shape_cache = shape_cache.borrow_mut();
+
+        let mut missing: Vec<PendingShape> = Vec::new();
+        for p in &pending {
+            if !shape_cache.is_cached(&p.key) && !missing.iter().any(|m| m.key == p.key) {
+                missing.push(*p);
+            }
         }
+        let tessellated = par_map(&missing, |p| {
+            tessellate_rounded_rect(p.size, p.corners, p.tint, p.outline, p.max_err)
+        });
+        let mut tessellated: HashMap<ShapeKey, ShapeTemplate> =
+            missing.iter().map(|p| p.key).zip(tessellated).collect();
+
+        let vtx_buffer = std::mem::take(&mut self.vtx_buffer);
+        let idx_buffer = std::mem::take(&mut self.idx_buffer);
+        let cmd_buffer = std::mem::take(&mut self.cmd_buffer);
+        let mut pending = pending.into_iter().peekable();
+
+        for (i, cmd) in cmd_buffer.iter().enumerate() {
+            self.cmd_buffer.push(DrawCmd {
+                vtx_offset: self.vtx_buffer.len(),
+                idx_offset: self.idx_buffer.len(),
+                vtx_count: 0,
+                idx_count: 0,
+                ..*cmd
+            });
+
+            // the geometry of the command with the shapes inserted, indices are relative to
+            // the command
+            let mut vtx = Vec::with_capacity(cmd.vtx_count);
+            let mut idx = Vec::with_capacity(cmd.idx_count);
+            // vertices of the shapes inserted so far
+            let mut shift = 0;
+            let (mut v, mut j) = (cmd.vtx_offset, cmd.idx_offset);
+            while let Some(p) = pending.next_if(|p| p.cmd == i) {
+                let recorded = (&vtx_buffer[v..p.vtx], &idx_buffer[j..p.idx]);
+                copy_recorded(&mut vtx, &mut idx, recorded, shift);
+                (v, j) = (p.vtx, p.idx);
+
+                let shape = shape_cache.get_or_insert_with(p.key, || {
+                    tessellated.remove(&p.key).unwrap_or_else(|| {
+                        tessellate_rounded_rect(p.size, p.corners, p.tint, p.outline, p.max_err)
+                    })
+                });
+                let offset = |v: &Vertex| Vertex {
+                    pos: v.pos + p.min,
+                    ..*v
+                };
+                let fill_base = vtx.len() as u32;
+                vtx.extend(shape.fill_vtx.iter().map(offset));
+                let outline_base = vtx.len() as u32;
+                vtx.extend(shape.outline_vtx.iter().map(offset));
+                idx.extend(shape.fill_idx.iter().map(|k| k + fill_base));
+                idx.extend(shape.outline_idx.iter().map(|k| k + outline_base));
+                shift += vtx.len() as u32 - fill_base;
+            }
+            let end = (cmd.vtx_offset + cmd.vtx_count, cmd.idx_offset + cmd.idx_count);
+            let recorded = (&vtx_buffer[v..end.0], &idx_buffer[j..end.1]);
+            copy_recorded(&mut vtx, &mut idx, recorded, shift);
 
-        self.path_clear();
+            if !vtx.is_empty() || !idx.is_empty() {
+                self.push_vtx_idx_topology(&vtx, &idx, cmd.topology);
+            }
+        }
     }
 
     fn push_rect_vertices(
@@ -1802,6 +3552,7 @@ impl DrawListData {
         color: RGBA,
         tex_id: TextureId,
     ) {
+        self.n_glyphs += (tex_id == TextureId::GLYPH) as usize;
         const QUAD_IDX: [u32; 6] = [0, 1, 2, 0, 2, 3];
 
         let raw_tex_id = tex_id.0 as u32;
@@ -1835,36 +3586,13 @@ impl DrawListData {
     }
 
     pub fn path_rect(&mut self, min: Vec2, max: Vec2, corners: CornerRadii) {
-        const PI: f32 = std::f32::consts::PI;
-
-        let r0 = corners.tl;
-        let r1 = corners.tr;
-        let r2 = corners.br;
-        let r3 = corners.bl;
-        self.path_to(Vec2::new(min.x + r0, min.y));
-
-        self.path_to(Vec2::new(max.x - r1, min.y));
-        if r1 > 0.0 {
-            self.path_arc(Vec2::new(max.x - r1, min.y + r1), r1, PI / 2.0, -PI / 2.0);
-        }
-
-        self.path_to(Vec2::new(max.x, min.y + r1));
-        self.path_to(Vec2::new(max.x, max.y - r2));
-        if r2 > 0.0 {
-            self.path_arc(Vec2::new(max.x - r2, max.y - r2), r2, 0.0, -PI / 2.0);
-        }
-
-        self.path_to(Vec2::new(max.x - r2, max.y));
-        self.path_to(Vec2::new(min.x + r3, max.y));
-        if r3 > 0.0 {
-            self.path_arc(Vec2::new(min.x + r3, max.y - r3), r3, -PI / 2.0, -PI / 2.0);
-        }
+        rounded_rect_path(&mut self.path, min, max, corners, self.circle_max_err);
+    }
 
-        self.path_to(Vec2::new(min.x, max.y - r3));
-        self.path_to(Vec2::new(min.x, min.y + r0));
-        if r0 > 0.0 {
-            self.path_arc(Vec2::new(min.x + r0, min.y + r0), r0, PI, -PI / 2.0);
-        }
+    /// like [`Self::path_arc`] with separate horizontal and vertical radii
+    pub fn path_arc_elliptical(&mut self, center: Vec2, radii: Vec2, start_angle: f32, sweep_angle: f32) {
+        let max_err = self.circle_max_err;
+        elliptical_arc_path(&mut self.path, center, radii, start_angle, sweep_angle, max_err);
     }
 
     pub fn path_arc(&mut self, center: Vec2, radius: f32, start_angle: f32, sweep_angle: f32) {
@@ -1887,6 +3615,14 @@ impl DrawListData {
         }
     }
 
+    /// strokes the current path and clears it
+    pub fn path_stroke(&mut self, stroke: Stroke, closed: bool) {
+        let path = std::mem::take(&mut self.path);
+        self.add_polyline(&path, stroke, closed);
+        self.path = path;
+        self.path.clear();
+    }
+
     pub fn distribute_uvs(
         &mut self,
         vert_start: usize,
@@ -1945,6 +3681,24 @@ impl DrawListData {
         tex_id: TextureId,
         tint: RGBA,
         outline: Outline,
+    ) {
+        if !check_rect(min, max, outline) {
+            return;
+        }
+        let outline = outline.to_pixels();
+        self.add_rect_px(min, max, uv_min, uv_max, tex_id, tint, outline);
+    }
+
+    /// [`Self::add_rect`] with the width of `outline` already in physical pixels
+    fn add_rect_px(
+        &mut self,
+        min: Vec2,
+        max: Vec2,
+        uv_min: Vec2,
+        uv_max: Vec2,
+        tex_id: TextureId,
+        tint: RGBA,
+        outline: Outline,
     ) {
         // Fast path: opaque solid fill with outline (no texture)
         if tex_id == TextureId::WHITE && tint.a == 1.0 && outline.width > 0.0 {
@@ -2052,19 +3806,229 @@ impl DrawListData {
                 tex_id,
             );
         }
-    }
+    }
+
+    /// textured rect whose red channel is mapped to a color by `cmap` in the fragment shader
+    pub fn add_rect_colormap(
+        &mut self,
+        min: Vec2,
+        max: Vec2,
+        uv_min: Vec2,
+        uv_max: Vec2,
+        tex_id: TextureId,
+        cmap: Colormap,
+    ) {
+        if !check_rect(min, max, Outline::none()) {
+            return;
+        }
+        let start = self.vtx_buffer.len();
+        self.add_simple_rect(min, max, uv_min, uv_max, tex_id, RGBA::WHITE);
+        for v in &mut self.vtx_buffer[start..] {
+            v.colormap = cmap as u32;
+        }
+    }
+
+    // TODO[NOTE]: add clip?
+    // TODO[NOTE]: consider outline placement for clipping
+    fn add_rect_outline(&mut self, min: Vec2, max: Vec2, outline: Outline) {
+        let pts = [
+            Vec2::new(min.x, max.y), // bottom-left
+            max,                     // top-right
+            Vec2::new(max.x, min.y), // top-left
+            min,                     // bottom-right
+        ];
+        let (vtx, idx) = tessellate_line(&pts, outline.col, outline.width, true);
+        self.push_vtx_idx(&vtx, &idx);
+    }
+
+    /// marks the current draw command as clipped if the bounding box of `points` is not inside
+    /// the clip rect, returns false if nothing is visible
+    fn clip_points(&mut self, points: &[Vec2], pad: f32) -> bool {
+        if points.is_empty() || !check_points("path", points) {
+            return false;
+        }
+
+        let clip = self.clip_rect;
+        let bb = Rect::from_points(points).expand(pad);
+        if !clip.overlaps(bb) {
+            return false;
+        }
+
+        if !clip.contains_rect(bb) {
+            self.current_draw_cmd().clip_rect_used = true;
+        }
+        true
+    }
+
+    pub fn add_polyline(&mut self, points: &[Vec2], stroke: Stroke, closed: bool) {
+        let width = stroke.pixel_width();
+        if !is_valid_width(width) || !self.clip_points(points, width) {
+            return;
+        }
+
+        let (vtx, idx) = tessellate_line(points, stroke.col, width, closed);
+        self.push_vtx_idx(&vtx, &idx);
+    }
+
+    pub fn add_polyline_ex(
+        &mut self,
+        points: &[Vec2],
+        stroke: Stroke,
+        closed: bool,
+        mode: LineMode,
+    ) {
+        match mode {
+            LineMode::Tessellated => self.add_polyline(points, stroke, closed),
+            LineMode::Fast => self.add_polyline_fast(points, stroke, closed),
+        }
+    }
+
+    /// polyline drawn with [`LineMode::Fast`]
+    pub fn add_polyline_fast(&mut self, points: &[Vec2], stroke: Stroke, closed: bool) {
+        // every segment of a chunk has to fit into a single draw command
+        const CHUNK: usize = RenderData::MAX_VERTEX_COUNT as usize / 4;
+
+        let width = stroke.pixel_width();
+        if !is_valid_width(width) || points.len() < 2 || !self.clip_points(points, width) {
+            return;
+        }
+
+        let closed_points;
+        let points = if closed {
+            closed_points = [points, &points[..1]].concat();
+            &closed_points
+        } else {
+            points
+        };
+
+        let mut start = 0;
+        while start + 1 < points.len() {
+            let end = (start + CHUNK).min(points.len());
+            let (vtx, idx, topology) =
+                line_primitives(&points[start..end], stroke.col, width, false);
+            self.push_vtx_idx_topology(&vtx, &idx, topology);
+            start = end - 1;
+        }
+    }
+
+    /// polyline with a color per point
+    pub fn add_polyline_multicolor(
+        &mut self,
+        points: &[Vec2],
+        cols: &[RGBA],
+        thickness: f32,
+        closed: bool,
+    ) {
+        if points.len() != cols.len() {
+            report_invalid("polyline colors", &(points.len(), cols.len()));
+            return;
+        }
+        if !is_valid_width(thickness) || !self.clip_points(points, thickness) {
+            return;
+        }
+
+        let (vtx, idx) = tessellate_line_ex(points, |i| cols[i], |_| thickness, closed);
+        self.push_vtx_idx(&vtx, &idx);
+    }
+
+    /// polyline with a thickness per segment
+    ///
+    /// `thickness[i]` is the width of the segment starting at `points[i]`, so an open line
+    /// needs `points.len() - 1` values and a closed one `points.len()`.
+    pub fn add_polyline_var_width(
+        &mut self,
+        points: &[Vec2],
+        col: RGBA,
+        thickness: &[f32],
+        closed: bool,
+    ) {
+        let n_segments = if closed {
+            points.len()
+        } else {
+            points.len().saturating_sub(1)
+        };
+        if n_segments != thickness.len() || !thickness.iter().all(|t| t.is_finite()) {
+            report_invalid("polyline widths", &thickness);
+            return;
+        }
+
+        let max_thickness = thickness.iter().copied().fold(0.0, f32::max);
+        if max_thickness <= 0.0 || !self.clip_points(points, max_thickness) {
+            return;
+        }
+
+        let (vtx, idx) = tessellate_line_ex(points, |_| col, |i| thickness[i], closed);
+        self.push_vtx_idx(&vtx, &idx);
+    }
+
+    pub fn add_convex_poly_filled(&mut self, points: &[Vec2], col: RGBA) {
+        if !self.clip_points(points, 1.0) {
+            return;
+        }
+
+        let (vtx, idx) = tessellate_convex_fill(points, col, true);
+        self.push_vtx_idx(&vtx, &idx);
+    }
+
+    /// convex polygon with a color per point, colors are interpolated across the polygon
+    pub fn add_convex_poly_filled_multicolor(&mut self, points: &[Vec2], cols: &[RGBA]) {
+        if points.len() != cols.len() {
+            report_invalid("polygon colors", &(points.len(), cols.len()));
+            return;
+        }
+        if !self.clip_points(points, 1.0) {
+            return;
+        }
+
+        let (vtx, idx) = tessellate_convex_fill_ex(points, |i| cols[i], true);
+        self.push_vtx_idx(&vtx, &idx);
+    }
+
+    /// draws the texture stretched over `rect` while the border slices given by `margins` keep
+    /// their size, the corners are not scaled at all
+    pub fn add_image_nine_slice(
+        &mut self,
+        rect: Rect,
+        tex_id: TextureId,
+        margins: Margins,
+        uv: NineSliceUV,
+        tint: RGBA,
+    ) {
+        if !check_rect(rect.min, rect.max, Outline::none()) || rect.clip(self.clip_rect).is_none() {
+            return;
+        }
 
-    // TODO[NOTE]: add clip?
-    // TODO[NOTE]: consider outline placement for clipping
-    fn add_rect_outline(&mut self, min: Vec2, max: Vec2, outline: Outline) {
-        let pts = [
-            Vec2::new(min.x, max.y), // bottom-left
-            max,                     // top-right
-            Vec2::new(max.x, min.y), // top-left
-            min,                     // bottom-right
+        let m = margins.fit(rect.size());
+        let xs = [rect.min.x, rect.min.x + m.left, rect.max.x - m.right, rect.max.x];
+        let ys = [rect.min.y, rect.min.y + m.top, rect.max.y - m.bottom, rect.max.y];
+
+        let um = uv.margins;
+        let us = [
+            uv.uv_min.x,
+            uv.uv_min.x + um.left,
+            uv.uv_max.x - um.right,
+            uv.uv_max.x,
         ];
-        let (vtx, idx) = tessellate_line(&pts, outline.col, outline.width, true);
-        self.push_vtx_idx(&vtx, &idx);
+        let vs = [
+            uv.uv_min.y,
+            uv.uv_min.y + um.top,
+            uv.uv_max.y - um.bottom,
+            uv.uv_max.y,
+        ];
+
+        for y in 0..3 {
+            for x in 0..3 {
+                let min = Vec2::new(xs[x], ys[y]);
+                let max = Vec2::new(xs[x + 1], ys[y + 1]);
+                if max.x - min.x <= 0.0 || max.y - min.y <= 0.0 {
+                    continue;
+                }
+
+                let uv_min = Vec2::new(us[x], vs[y]);
+                let uv_max = Vec2::new(us[x + 1], vs[y + 1]);
+                self.add_simple_rect(min, max, uv_min, uv_max, tex_id, tint);
+            }
+        }
     }
 }
 
@@ -2138,6 +4102,58 @@ pub fn tessellate_line(
     col: RGBA,
     thickness: f32,
     closed: bool,
+) -> (Vec<Vertex>, Vec<u32>) {
+    tessellate_line_ex(points, |_| col, |_| thickness, closed)
+}
+
+/// geometry of a polyline drawn with [`LineMode::Fast`]
+///
+/// lines up to 1 pixel wide use a `LineList`, thinner lines fade out instead of getting
+/// thinner. wider lines get a screen space quad per segment without joins
+pub fn line_primitives(
+    points: &[Vec2],
+    col: RGBA,
+    width: f32,
+    closed: bool,
+) -> (Vec<Vertex>, Vec<u32>, wgpu::PrimitiveTopology) {
+    let n = points.len();
+    let n_segments = match n {
+        0 | 1 => 0,
+        2 => 1,
+        _ if closed => n,
+        _ => n - 1,
+    };
+    let segments = (0..n_segments).map(|i| (i, (i + 1) % n));
+
+    if width <= 1.0 {
+        let col = RGBA { a: col.a * width, ..col };
+        let vtx = points.iter().map(|&p| Vertex::color(p, col)).collect();
+        let idx = segments.flat_map(|(a, b)| [a as u32, b as u32]).collect();
+        return (vtx, idx, wgpu::PrimitiveTopology::LineList);
+    }
+
+    let mut vtx = Vec::with_capacity(n_segments * 4);
+    let mut idx = Vec::with_capacity(n_segments * 6);
+    for (a, b) in segments {
+        let (a, b) = (points[a], points[b]);
+        let normal = (b - a).normalize_or_zero().perp() * (width * 0.5);
+
+        let base = vtx.len() as u32;
+        vtx.extend([a + normal, b + normal, b - normal, a - normal].map(|p| Vertex::color(p, col)));
+        idx.extend([0, 1, 2, 0, 2, 3].map(|i| base + i));
+    }
+    (vtx, idx, wgpu::PrimitiveTopology::TriangleList)
+}
+
+/// like [`tessellate_line`] but with a color per point and a thickness per segment
+///
+/// `col(i)` is called with the index of the point and `thickness(i)` with the index of the
+/// segment starting at point `i`.
+pub fn tessellate_line_ex(
+    points: &[Vec2],
+    col: impl Fn(usize) -> RGBA,
+    thickness: impl Fn(usize) -> f32,
+    closed: bool,
 ) -> (Vec<Vertex>, Vec<u32>) {
     if points.len() < 2 {
         return (Vec::new(), Vec::new());
@@ -2148,7 +4164,6 @@ pub fn tessellate_line(
     } else {
         points.len() - 1
     };
-    let half = thickness * 0.5;
 
     let mut verts: Vec<Vertex> = Vec::with_capacity(count * 4);
     let mut idxs: Vec<u32> = Vec::with_capacity(count * 12);
@@ -2174,14 +4189,17 @@ pub fn tessellate_line(
         }
 
         // perpendicular (normalized) scaled by half thickness
+        let half = thickness(i) * 0.5;
         let px = dy_next * half;
         let py = -dx_next * half;
+        let col_curr = col(i);
+        let col_next = col(i_next);
 
         // 4 verts for the rect, vert 0 and 1 are "above" and "below" the first point and vert 2 and 3 are "above" and "below" the second point
-        verts.push(Vertex::color(Vec2::new(p_curr.x + px, p_curr.y + py), col));
-        verts.push(Vertex::color(Vec2::new(p_curr.x - px, p_curr.y - py), col));
-        verts.push(Vertex::color(Vec2::new(p_next.x + px, p_next.y + py), col));
-        verts.push(Vertex::color(Vec2::new(p_next.x - px, p_next.y - py), col));
+        verts.push(Vertex::color(Vec2::new(p_curr.x + px, p_curr.y + py), col_curr));
+        verts.push(Vertex::color(Vec2::new(p_curr.x - px, p_curr.y - py), col_curr));
+        verts.push(Vertex::color(Vec2::new(p_next.x + px, p_next.y + py), col_next));
+        verts.push(Vertex::color(Vec2::new(p_next.x - px, p_next.y - py), col_next));
     }
 
     let mut base_idx_prev: u32 = 0;
@@ -2220,6 +4238,15 @@ pub fn tessellate_convex_fill(
     points: &[Vec2],
     col: RGBA,
     antialias: bool,
+) -> (Vec<Vertex>, Vec<u32>) {
+    tessellate_convex_fill_ex(points, |_| col, antialias)
+}
+
+/// like [`tessellate_convex_fill`] but with a color per point
+pub fn tessellate_convex_fill_ex(
+    points: &[Vec2],
+    col: impl Fn(usize) -> RGBA,
+    antialias: bool,
 ) -> (Vec<Vertex>, Vec<u32>) {
     let n = points.len();
     if n < 3 {
@@ -2230,8 +4257,8 @@ pub fn tessellate_convex_fill(
         let mut verts = Vec::new();
         let mut idxs = Vec::new();
         // no-AA: just triangulate polygon fan
-        for p in points {
-            verts.push(Vertex::color(*p, col));
+        for (i, p) in points.iter().enumerate() {
+            verts.push(Vertex::color(*p, col(i)));
         }
 
         for i in 2..n {
@@ -2242,7 +4269,6 @@ pub fn tessellate_convex_fill(
 
     const AA_SIZE: f32 = 1.0;
     const EPS: f32 = 1e-12;
-    let col_trans = RGBA::rgba_f(col.r, col.g, col.b, 0.0);
     let mut verts = Vec::with_capacity(n * 2);
     let mut idxs = Vec::with_capacity((n - 2) * 3 + n * 6);
 
@@ -2295,6 +4321,8 @@ pub fn tessellate_convex_fill(
             y: p.y + dm_y,
         };
 
+        let col = col(i1);
+        let col_trans = RGBA::rgba_f(col.r, col.g, col.b, 0.0);
         verts.push(Vertex::color(inner, col));
         verts.push(Vertex::color(outer, col_trans));
     }
@@ -2329,6 +4357,96 @@ pub fn tessellate_convex_fill(
     (verts, idxs)
 }
 
+/// tessellates every item with `f` and concatenates the results in the order of `items`,
+/// offsetting the indices accordingly.
+///
+/// with the `parallel` feature the items are split into one chunk per thread and tessellated on
+/// the rayon thread pool, the output is identical to the sequential version.
+pub fn tessellate_batch<T, F>(items: &[T], f: F) -> (Vec<Vertex>, Vec<u32>)
+where
+    T: Sync,
+    F: Fn(&T) -> (Vec<Vertex>, Vec<u32>) + Sync,
+{
+    fn tessellate_chunk<T>(
+        items: &[T],
+        f: &impl Fn(&T) -> (Vec<Vertex>, Vec<u32>),
+    ) -> (Vec<Vertex>, Vec<u32>) {
+        let mut verts = Vec::new();
+        let mut idxs = Vec::new();
+        for itm in items {
+            let (vtx, idx) = f(itm);
+            let base = verts.len() as u32;
+            verts.extend_from_slice(&vtx);
+            idxs.extend(idx.into_iter().map(|i| base + i));
+        }
+        (verts, idxs)
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        // not worth the overhead for small batches
+        const MIN_CHUNK_SIZE: usize = 64;
+
+        if items.len() > MIN_CHUNK_SIZE {
+            let chunk_size = items
+                .len()
+                .div_ceil(rayon::current_num_threads())
+                .max(MIN_CHUNK_SIZE);
+
+            let chunks: Vec<_> = items
+                .par_chunks(chunk_size)
+                .map(|chunk| tessellate_chunk(chunk, &f))
+                .collect();
+
+            let n_vtx = chunks.iter().map(|(vtx, _)| vtx.len()).sum();
+            let n_idx = chunks.iter().map(|(_, idx)| idx.len()).sum();
+            let mut verts = Vec::with_capacity(n_vtx);
+            let mut idxs = Vec::with_capacity(n_idx);
+
+            for (vtx, idx) in chunks {
+                let base = verts.len() as u32;
+                verts.extend_from_slice(&vtx);
+                idxs.extend(idx.into_iter().map(|i| base + i));
+            }
+            return (verts, idxs);
+        }
+    }
+
+    tessellate_chunk(items, &f)
+}
+
+/// maps the items with `f` and keeps their order. with the `parallel` feature the items are
+/// split into one chunk per thread, each mapped into its own buffer on the rayon thread pool
+fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        const MIN_CHUNK_SIZE: usize = 8;
+
+        if items.len() > MIN_CHUNK_SIZE {
+            let chunk_size = items
+                .len()
+                .div_ceil(rayon::current_num_threads())
+                .max(MIN_CHUNK_SIZE);
+            let chunks: Vec<Vec<R>> = items
+                .par_chunks(chunk_size)
+                .map(|chunk| chunk.iter().map(&f).collect())
+                .collect();
+            return chunks.into_iter().flatten().collect();
+        }
+    }
+
+    items.iter().map(f).collect()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct DrawRect {
     // pub draw_list: &'a mut DrawList,
@@ -2340,6 +4458,7 @@ pub struct DrawRect {
     pub fill: RGBA,
     pub outline: Outline,
     pub corners: CornerRadii,
+    pub effect: EffectId,
 }
 
 impl ShapedText {
@@ -2387,7 +4506,11 @@ where
 
 impl DrawableRects for DrawRect {
     fn add_to_drawlist(self, drawlist: &DrawList) {
-        drawlist.data.borrow_mut().add_rect_rounded(
+        let mut data = drawlist.data.borrow_mut();
+        if self.effect != EffectId::NONE {
+            data.set_effect(self.effect);
+        }
+        data.add_rect_rounded(
             self.min,
             self.max,
             self.uv_min,
@@ -2397,6 +4520,56 @@ impl DrawableRects for DrawRect {
             self.outline,
             self.corners,
         );
+        if self.effect != EffectId::NONE {
+            data.set_effect(EffectId::NONE);
+        }
+    }
+}
+
+/// draws into the current panel clipped to a rect, see [`Context::painter`]
+///
+/// the clip rect is popped when the painter is dropped
+pub struct ItemPainter<'a> {
+    list: &'a DrawList,
+    rect: Rect,
+}
+
+impl<'a> ItemPainter<'a> {
+    pub fn new(list: &'a DrawList, rect: Rect) -> Self {
+        list.push_merged_clip_rect(rect);
+        Self { list, rect }
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// converts a position relative to the top left of the rect to screen space
+    pub fn to_screen(&self, pos: Vec2) -> Vec2 {
+        self.rect.min + pos
+    }
+
+    pub fn draw(&self, itm: impl DrawableRects) -> &Self {
+        itm.add_to_drawlist(self.list);
+        self
+    }
+
+    pub fn text(&self, pos: Vec2, text: &ShapedText, col: RGBA) -> &Self {
+        self.draw(text.draw_rects(pos, col))
+    }
+}
+
+impl std::ops::Deref for ItemPainter<'_> {
+    type Target = DrawList;
+
+    fn deref(&self) -> &DrawList {
+        self.list
+    }
+}
+
+impl Drop for ItemPainter<'_> {
+    fn drop(&mut self) {
+        self.list.pop_clip_rect();
     }
 }
 
@@ -2417,6 +4590,7 @@ impl DrawRect {
             fill: RGBA::ZERO,
             outline: Outline::none(),
             corners: CornerRadii::zero(),
+            effect: EffectId::NONE,
         }
     }
 
@@ -2472,6 +4646,12 @@ impl DrawRect {
         self
     }
 
+    /// shades the rect with a registered effect, see [`UiEffect`]
+    pub fn effect(mut self, effect: EffectId) -> Self {
+        self.effect = effect;
+        self
+    }
+
     // pub fn add(self) {
     //     self.draw_list.add_rect_rounded(
     //         self.min,
@@ -2514,6 +4694,10 @@ pub struct ShapedText {
     pub glyphs: Vec<Glyph>,
     pub width: f32,
     pub height: f32,
+    /// direction of the first paragraph as detected by the bidi algorithm
+    pub rtl: bool,
+    /// distance from the top to the baseline of the first line
+    pub baseline: f32,
 }
 
 impl ShapedText {
@@ -2522,6 +4706,75 @@ impl ShapedText {
     }
 }
 
+/// how text behaves when it is wider than the available width
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextWrap {
+    /// the text may overflow
+    #[default]
+    None,
+    /// the text is cut off at the available width
+    Clip,
+    /// lines are broken at word boundaries to fit the available width
+    Wrap,
+    /// lines are shortened and end with "…" to fit the available width
+    Truncate,
+}
+
+/// horizontal alignment of the lines of a text
+///
+/// lines are aligned inside the width of the text item, or the widest line if no width is set
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextAlign {
+    /// left for left-to-right paragraphs, right for right-to-left ones
+    #[default]
+    Start,
+    /// opposite of `Start`
+    End,
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    fn as_ctext(self) -> Option<ctext::Align> {
+        match self {
+            TextAlign::Start => None,
+            TextAlign::End => Some(ctext::Align::End),
+            TextAlign::Left => Some(ctext::Align::Left),
+            TextAlign::Center => Some(ctext::Align::Center),
+            TextAlign::Right => Some(ctext::Align::Right),
+        }
+    }
+}
+
+/// direction in which items are placed inside a panel
+///
+/// `Rtl` mirrors where items are placed in the panel content and points the caret of
+/// collapsing headers to the left. scrollbars, the titlebar and the icons of other items
+/// are not mirrored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayoutDir {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl LayoutDir {
+    /// position an item placed left-to-right at `rect` inside `content`
+    pub fn place(self, rect: Rect, content: Rect) -> Rect {
+        match self {
+            LayoutDir::Ltr => rect,
+            LayoutDir::Rtl => {
+                let min_x = content.min.x + content.max.x - rect.max.x;
+                Rect::from_min_size(Vec2::new(min_x, rect.min.y), rect.size())
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TextItem {
     // pub font: FontId,
@@ -2531,8 +4784,15 @@ pub struct TextItem {
     pub line_height_i: u64,
     pub width_i: Option<u64>,
     pub height_i: Option<u64>,
+    /// only used if width is set
+    pub wrap: TextWrap,
+    pub max_lines: Option<u32>,
+    pub align: TextAlign,
 }
 
+/// font of the text of the widgets, see `Context::text_font`
+pub const DEFAULT_FONT: &str = "Inter";
+
 #[derive(Debug, Clone)]
 pub struct FontTable {
     // pub id_to_name: Vec<(FontId, String)>,
@@ -2573,30 +4833,19 @@ impl FontTable {
 
 impl TextItem {
     pub fn layout(&self, fonts: &mut FontTable, cache: &mut GlyphCache, wgpu: &WGPU) -> ShapedText {
-        let mut buffer = ctext::Buffer::new(
-            &mut fonts.sys(),
-            ctext::Metrics {
-                font_size: self.font_size(),
-                line_height: self.scaled_line_height(),
-            },
-        );
-
-        let font_attrib = fonts.get_font_attrib(self.font);
-        buffer.set_size(&mut fonts.sys(), self.width(), self.height());
-        buffer.set_text(
-            &mut fonts.sys(),
-            &self.string,
-            &font_attrib,
-            ctext::Shaping::Advanced,
-        );
-        buffer.shape_until_scroll(&mut fonts.sys(), false);
+        let buffer = self.shape(fonts);
 
         let mut glyphs = Vec::new();
         let mut width = 0.0;
         let mut height = 0.0;
-
-        for run in buffer.layout_runs() {
-            width = run.line_w.max(width);
+        let max_lines = self.max_lines.map_or(usize::MAX, |n| n as usize);
+        let rtl = buffer.layout_runs().next().is_some_and(|run| run.rtl);
+        let baseline = buffer.layout_runs().next().map_or(0.0, |run| run.line_y);
+
+        for run in buffer.layout_runs().take(max_lines) {
+            // aligned lines can end after line_w
+            let line_end = run.glyphs.iter().map(|g| g.x + g.w).fold(run.line_w, f32::max);
+            width = line_end.max(width);
             // TODO[CHECK]: is it the sum?
             // height = run.line_height.max(height);
             height += run.line_height;
@@ -2619,9 +4868,204 @@ impl TextItem {
             glyphs,
             width,
             height,
+            rtl,
+            baseline,
         };
         text
     }
+
+    /// shapes the text with the wrapping, truncation, line limit and alignment of the item
+    pub fn shape(&self, fonts: &mut FontTable) -> ctext::Buffer {
+        let mut buffer = ctext::Buffer::new(
+            &mut fonts.sys(),
+            ctext::Metrics {
+                font_size: self.font_size(),
+                line_height: self.scaled_line_height(),
+            },
+        );
+
+        let font_attrib = fonts.get_font_attrib(self.font);
+        let wrap_width = match self.wrap {
+            TextWrap::Wrap => self.width(),
+            _ => None,
+        };
+        buffer.set_size(&mut fonts.sys(), wrap_width, self.height());
+        buffer.set_text(
+            &mut fonts.sys(),
+            &sanitize_text(&self.string),
+            &font_attrib,
+            ctext::Shaping::Advanced,
+        );
+        buffer.shape_until_scroll(&mut fonts.sys(), false);
+
+        if let (TextWrap::Truncate, Some(max_w)) = (self.wrap, self.width()) {
+            let ellipsis_w = self.ellipsis_width(fonts);
+            if let Some(truncated) = truncate_lines(&buffer, max_w, ellipsis_w) {
+                buffer.set_text(
+                    &mut fonts.sys(),
+                    &truncated,
+                    &font_attrib,
+                    ctext::Shaping::Advanced,
+                );
+                buffer.shape_until_scroll(&mut fonts.sys(), false);
+            }
+        }
+
+        // the last kept line ends with an ellipsis when lines are cut
+        if let Some(max_lines) = self.max_lines {
+            let max_w = match self.wrap {
+                TextWrap::Wrap | TextWrap::Truncate => self.width(),
+                TextWrap::None | TextWrap::Clip => None,
+            };
+            let ellipsis_w = self.ellipsis_width(fonts);
+            if let Some(limited) = limit_lines(&buffer, max_lines as usize, max_w, ellipsis_w) {
+                buffer.set_text(
+                    &mut fonts.sys(),
+                    &limited,
+                    &font_attrib,
+                    ctext::Shaping::Advanced,
+                );
+                buffer.shape_until_scroll(&mut fonts.sys(), false);
+            }
+        }
+
+        // cosmic-text already reorders bidi runs, but it aligns every paragraph on its own
+        // so we lay out again with a common width
+        let any_rtl = buffer.layout_runs().any(|run| run.rtl);
+        if self.align != TextAlign::Start || any_rtl {
+            let widest = buffer.layout_runs().map(|run| run.line_w).fold(0.0, f32::max);
+            if wrap_width.is_none() {
+                buffer.set_wrap(&mut fonts.sys(), ctext::Wrap::None);
+                buffer.set_size(&mut fonts.sys(), Some(self.width().unwrap_or(widest)), self.height());
+            }
+            for line in &mut buffer.lines {
+                line.set_align(self.align.as_ctext());
+            }
+            buffer.shape_until_scroll(&mut fonts.sys(), false);
+        }
+
+        buffer
+    }
+
+    fn ellipsis_width(&self, fonts: &mut FontTable) -> f32 {
+        let mut buffer = ctext::Buffer::new(
+            &mut fonts.sys(),
+            ctext::Metrics {
+                font_size: self.font_size(),
+                line_height: self.scaled_line_height(),
+            },
+        );
+        let font_attrib = fonts.get_font_attrib(self.font);
+        buffer.set_text(
+            &mut fonts.sys(),
+            ELLIPSIS,
+            &font_attrib,
+            ctext::Shaping::Advanced,
+        );
+        buffer.shape_until_scroll(&mut fonts.sys(), false);
+        buffer.layout_runs().map(|run| run.line_w).fold(0.0, f32::max)
+    }
+}
+
+const ELLIPSIS: &str = "\u{2026}";
+
+/// longest text in bytes that is shaped, see [`sanitize_text`]
+pub const MAX_TEXT_LEN: usize = 1 << 20;
+
+/// the text that is shaped for `text`: control characters except line breaks and tabs are
+/// removed, `\r\n` and lone `\r` become `\n` and text is cut after [`MAX_TEXT_LEN`] bytes.
+/// valid text is borrowed
+pub fn sanitize_text(text: &str) -> std::borrow::Cow<'_, str> {
+    let is_invalid = |c: char| c.is_control() && c != '\n' && c != '\t';
+    if text.len() <= MAX_TEXT_LEN && !text.chars().any(is_invalid) {
+        return std::borrow::Cow::Borrowed(text);
+    }
+    report_invalid("text", &text.chars().take(32).collect::<String>());
+
+    let mut end = text.len().min(MAX_TEXT_LEN);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut sanitized = String::with_capacity(end);
+    let mut chars = text[..end].chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\r' => sanitized.push('\n'),
+            c if is_invalid(c) => (),
+            c => sanitized.push(c),
+        }
+    }
+    std::borrow::Cow::Owned(sanitized)
+}
+
+/// shortens every line of the shaped `buffer` that is wider than `max_w` and appends an ellipsis
+///
+/// returns `None` if all lines already fit
+fn truncate_lines(buffer: &ctext::Buffer, max_w: f32, ellipsis_w: f32) -> Option<String> {
+    let mut truncated = false;
+
+    let lines: Vec<String> = buffer
+        .layout_runs()
+        .map(|run| {
+            if run.line_w <= max_w {
+                return run.text.to_string();
+            }
+            truncated = true;
+
+            let end = run
+                .glyphs
+                .iter()
+                .take_while(|g| g.x + g.w + ellipsis_w <= max_w)
+                .last()
+                .map_or(0, |g| g.end);
+            format!("{}{ELLIPSIS}", run.text[..end].trim_end())
+        })
+        .collect();
+
+    truncated.then(|| lines.join("\n"))
+}
+
+/// the text of a laid out line, wrapped lines only hold a part of `run.text`
+fn run_text<'a>(run: &ctext::LayoutRun<'a>) -> &'a str {
+    let start = run.glyphs.iter().map(|g| g.start).min().unwrap_or(0);
+    let end = run.glyphs.iter().map(|g| g.end).max().unwrap_or(start);
+    &run.text[start..end]
+}
+
+/// keeps the first `max_lines` lines of the shaped `buffer` and ends the last one with an
+/// ellipsis, the line is shortened so the ellipsis fits into `max_w`
+///
+/// returns `None` if there are no more than `max_lines` lines
+fn limit_lines(
+    buffer: &ctext::Buffer,
+    max_lines: usize,
+    max_w: Option<f32>,
+    ellipsis_w: f32,
+) -> Option<String> {
+    buffer.layout_runs().nth(max_lines)?;
+
+    let mut lines: Vec<String> = buffer
+        .layout_runs()
+        .take(max_lines)
+        .map(|run| run_text(&run).to_string())
+        .collect();
+    let last = buffer.layout_runs().nth(max_lines.checked_sub(1)?)?;
+    let text = match max_w {
+        Some(max_w) => {
+            let start = last.glyphs.iter().map(|g| g.start).min().unwrap_or(0);
+            let end = last
+                .glyphs
+                .iter()
+                .take_while(|g| g.x + g.w + ellipsis_w <= max_w)
+                .last()
+                .map_or(start, |g| g.end);
+            &last.text[start..end]
+        }
+        None => run_text(&last),
+    };
+    *lines.last_mut()? = format!("{}{ELLIPSIS}", text.trim_end());
+    Some(lines.join("\n"))
 }
 
 // fn shape_text_item(
@@ -2691,6 +5135,9 @@ impl TextItem {
             line_height_i: (line_height * Self::RESOLUTION) as u64,
             width_i: None,
             height_i: None,
+            wrap: TextWrap::Wrap,
+            max_lines: None,
+            align: TextAlign::Start,
         }
     }
 
@@ -2699,6 +5146,22 @@ impl TextItem {
         self
     }
 
+    /// sets how the text is fit into `width`
+    pub fn with_wrap(mut self, wrap: TextWrap, width: f32) -> Self {
+        self.wrap = wrap;
+        self.with_width(width)
+    }
+
+    pub fn with_max_lines(mut self, max_lines: Option<u32>) -> Self {
+        self.max_lines = max_lines;
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
     pub fn with_height(mut self, height: f32) -> Self {
         self.height_i = Some((height * Self::RESOLUTION) as u64);
         self
@@ -2734,6 +5197,8 @@ pub struct GlyphCache {
     pub cached_glyphs: HashMap<ctext::CacheKey, GlyphMeta>,
     pub swash_cache: ctext::SwashCache,
     pub fonts: FontTable,
+    /// the pixels of [`Self::texture`], read by the [`SoftRasterizer`]
+    pub cpu_atlas: gpu::CapturedImage,
 }
 
 // TODO[NOTE]: dealloc with garbage collector
@@ -2772,6 +5237,11 @@ impl GlyphCache {
             cached_glyphs: Default::default(),
             swash_cache: ctext::SwashCache::new(),
             fonts,
+            cpu_atlas: gpu::CapturedImage {
+                width: size,
+                height: size,
+                rgba: vec![0; (size * size * 4) as usize],
+            },
         }
     }
 
@@ -2834,6 +5304,12 @@ impl GlyphCache {
             },
         );
 
+        let (x, row_bytes) = (rect.min.x as usize * 4, w as usize * 4);
+        for (row, src) in data.chunks_exact(row_bytes.max(1)).enumerate() {
+            let start = (rect.min.y as usize + row) * self.cpu_atlas.width as usize * 4 + x;
+            self.cpu_atlas.rgba[start..start + row_bytes].copy_from_slice(src);
+        }
+
         let tex_size = self.texture.width();
         assert!(self.texture.height() == tex_size);
         // let pos = Vec2::new(x as f32, -y as f32);
@@ -2890,6 +5366,71 @@ impl GlyphCache {
     }
 }
 
+/// icon font whose glyphs are looked up by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IconFont {
+    /// family name of the font
+    pub family: &'static str,
+    pub glyphs: HashMap<String, char>,
+}
+
+impl IconFont {
+    pub fn new(family: &'static str) -> Self {
+        Self {
+            family,
+            glyphs: HashMap::default(),
+        }
+    }
+
+    pub fn glyph(mut self, name: impl Into<String>, ch: char) -> Self {
+        self.glyphs.insert(name.into(), ch);
+        self
+    }
+
+    pub fn glyphs<S: Into<String>>(mut self, glyphs: impl IntoIterator<Item = (S, char)>) -> Self {
+        self.glyphs.extend(glyphs.into_iter().map(|(n, c)| (n.into(), c)));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<char> {
+        self.glyphs.get(name).copied()
+    }
+}
+
+/// registered icon fonts, searched in registration order
+#[derive(Debug, Clone, Default)]
+pub struct IconTable {
+    pub fonts: Vec<IconFont>,
+}
+
+impl IconTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// replaces a font with the same family
+    pub fn register(&mut self, font: IconFont) {
+        match self.fonts.iter_mut().find(|f| f.family == font.family) {
+            Some(f) => *f = font,
+            None => self.fonts.push(font),
+        }
+    }
+
+    /// returns the family and glyph of the icon
+    ///
+    /// `name` can be prefixed with the family to pick a specific font, e.g. `"Phosphor:x"`
+    pub fn find(&self, name: &str) -> Option<(&'static str, char)> {
+        if let Some((family, name)) = name.split_once(':') {
+            let font = self.fonts.iter().find(|f| f.family == family)?;
+            return font.get(name).map(|ch| (font.family, ch));
+        }
+
+        self.fonts
+            .iter()
+            .find_map(|f| f.get(name).map(|ch| (f.family, ch)))
+    }
+}
+
 pub mod phosphor_font {
     // from https://phosphoricons.com/
     pub const X: &'static str = "\u{E4F6}";
@@ -2897,7 +5438,29 @@ pub mod phosphor_font {
     pub const MAXIMIZE: &'static str = "\u{E3F0}";
     pub const MINIMIZE: &'static str = "\u{E32A}";
     pub const CARET_RIGHT: &'static str = "\u{E13A}";
+    pub const CARET_LEFT: &'static str = "\u{E138}";
     pub const CARET_DOWN: &'static str = "\u{E136}";
+    pub const CARET_UP: &'static str = "\u{E13C}";
+    pub const PUSH_PIN: &'static str = "\u{E3E2}";
+    pub const PUSH_PIN_SLASH: &'static str = "\u{E3E6}";
+
+    pub const FAMILY: &'static str = "Phosphor";
+
+    pub fn icon_font() -> super::IconFont {
+        let glyph = |s: &str| s.chars().next().unwrap();
+        super::IconFont::new(FAMILY).glyphs([
+            ("x", glyph(X)),
+            ("maximize-off", glyph(MAXIMIZE_OFF)),
+            ("maximize", glyph(MAXIMIZE)),
+            ("minimize", glyph(MINIMIZE)),
+            ("caret-right", glyph(CARET_RIGHT)),
+            ("caret-left", glyph(CARET_LEFT)),
+            ("caret-down", glyph(CARET_DOWN)),
+            ("caret-up", glyph(CARET_UP)),
+            ("push-pin", glyph(PUSH_PIN)),
+            ("push-pin-slash", glyph(PUSH_PIN_SLASH)),
+        ])
+    }
 }
 
 //---------------------------------------------------------------------------------------
@@ -2911,18 +5474,38 @@ pub const MAX_N_TEXTURES_PER_DRAW_CALL: usize = 8;
 pub struct RenderData {
     pub gpu_vertices: wgpu::Buffer,
     pub gpu_indices: wgpu::Buffer,
+    /// triangles of the clip mask of the current draw call
+    pub gpu_mask_vertices: wgpu::Buffer,
 
     pub call_list: DrawCallList,
     pub screen_size: Vec2,
 
     pub antialias: bool,
 
+    /// merge compatible draw calls before submitting
+    pub batching: bool,
+    /// upload [`CompactVertex`]s, halves the vertex bandwidth at the cost of 8 bit colors and
+    /// uvs clamped to `0..=1`
+    pub compact_vertices: bool,
+    /// draw the triangle edges with a `PolygonMode::Line` pipeline, see
+    /// [`WGPU::supports_wireframe`]
+    pub wireframe: bool,
+    pub batch_stats: BatchStats,
+
     pub white_texture: gpu::Texture,
     // pub glyph_texture: gpu::Texture,
     /// registered textures
     /// 
     /// texture id is defined as the index + 1 in this array, 0 is reserved for white texture
     pub texture_reg: Vec<gpu::Texture>,
+    /// registered effects, the effect id is the index + 1
+    pub effects: Vec<UiEffect>,
+    /// seconds since the start of the context, passed to the effects
+    pub time: f32,
+    /// counts the calls to [`Self::clear`], see [`DrawOutput::frame`]
+    pub frame: u64,
+    /// a slot per draw call
+    pub global_uniforms: gpu::UniformRing,
 
     pub wgpu: WGPUHandle,
 }
@@ -2932,6 +5515,7 @@ impl RenderData {
     pub const MAX_VERTEX_COUNT: u64 = 65_536;
     // 2^17
     pub const MAX_INDEX_COUNT: u64 = 131_072;
+    pub const MAX_MASK_VERTEX_COUNT: u64 = 4096;
 
     pub fn new(glyph_texture: gpu::Texture, wgpu: WGPUHandle) -> Self {
         // let mut font_db = ctext::fontdb::Database::new();
@@ -2957,56 +5541,87 @@ impl RenderData {
             mapped_at_creation: false,
         });
 
+        let gpu_mask_vertices = wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("draw_list_mask_vertex_buffer"),
+            size: std::mem::size_of::<Vertex>() as u64 * Self::MAX_MASK_VERTEX_COUNT,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        });
+
         let texture_reg = vec![glyph_texture];
+        let global_uniforms = GlobalUniform::ring(&wgpu.device);
 
         Self {
             gpu_vertices,
             gpu_indices,
+            gpu_mask_vertices,
             screen_size: Vec2::ONE,
             antialias: true,
+            batching: true,
+            compact_vertices: false,
+            wireframe: false,
+            batch_stats: BatchStats::default(),
             call_list: DrawCallList::new(
                 Self::MAX_VERTEX_COUNT as usize,
                 Self::MAX_INDEX_COUNT as usize,
             ),
             white_texture,
             texture_reg,
+            effects: Vec::new(),
+            time: 0.0,
+            frame: 0,
+            global_uniforms,
             wgpu,
         }
     }
 
     pub fn push_drawlist(&mut self, list: &DrawList) {
-        for cmd in list.commands().iter(){
-            let vtx = &list.vtx_slice(cmd.vtx_offset..cmd.vtx_offset + cmd.vtx_count);
-            let idx = &list.idx_slice(cmd.idx_offset..cmd.idx_offset + cmd.idx_count);
-
-            let mut curr_clip = self.call_list.current_clip_rect();
-            curr_clip.min = curr_clip.min.max(Vec2::ZERO);
-            curr_clip.max = curr_clip.max.min(self.screen_size);
-
-            let mut clip = cmd.clip_rect;
-            clip.min = clip.min.max(Vec2::ZERO);
-            clip.max = clip.max.min(self.screen_size);
+        self.batch_stats.n_cmds += self.call_list.push_drawlist(list, self.screen_size);
+    }
 
-            // draw_buff.set_clip_rect(cmd.clip_rect);
-            if cmd.clip_rect_used {
-                self.call_list.set_clip_rect(cmd.clip_rect);
-            } else if !self.call_list.current_clip_rect().contains_rect(clip) {
-                self.call_list.set_clip_rect(Rect::from_min_size(Vec2::ZERO, self.screen_size));
+    /// merges the pushed draw calls if batching is enabled, see [`DrawCallList::merge_calls`]
+    pub fn batch_calls(&mut self) {
+        let n_cmds = self.batch_stats.n_cmds;
+
+        self.batch_stats = if self.batching {
+            self.call_list.merge_calls()
+        } else {
+            BatchStats {
+                n_calls_unbatched: self.call_list.len(),
+                n_calls: self.call_list.len(),
+                ..Default::default()
             }
-            
-            self.call_list.push_texture(cmd.texture_id);
-            self.call_list.push(vtx, idx); 
-        }
+        };
+
+        self.batch_stats.n_cmds = n_cmds;
     }
 
     pub fn clear(&mut self) {
         self.call_list.clear();
+        self.batch_stats = BatchStats::default();
+        self.global_uniforms.reset();
+        self.frame += 1;
+    }
+
+    /// the draw calls of the last frame, for painting them with a [`Painter`]
+    pub fn output(&self) -> DrawOutput<'_> {
+        DrawOutput {
+            calls: &self.call_list,
+            textures: &self.texture_reg,
+            effects: &self.effects,
+            time: self.time,
+            frame: self.frame,
+        }
     }
 }
 
 impl RenderPassHandle for RenderData {
     const LABEL: &'static str = "draw_list_render_pass";
 
+    fn uses_stencil(&self) -> bool {
+        true
+    }
+
     fn n_render_passes(&self) -> u32 {
         self.call_list.calls.len() as u32
         // 1
@@ -3079,7 +5694,7 @@ impl RenderPassHandle for RenderData {
         let proj =
             Mat4::orthographic_lh(0.0, self.screen_size.x, self.screen_size.y, 0.0, -1.0, 1.0);
 
-        let global_uniform = GlobalUniform::new(self.screen_size, proj);
+        let global_uniform = GlobalUniform::new(self.screen_size, proj).with_time(self.time);
 
         // let bind_group = build_bind_group(global_uniform, self.glyph_texture.view(), wgpu);
         let mut tex_views = self.call_list.calls[i as usize]
@@ -3092,23 +5707,36 @@ impl RenderPassHandle for RenderData {
             tex_views.push(self.white_texture.view().clone());
         }
 
-
-        let bind_group = build_bind_group(global_uniform, &tex_views, wgpu);
+        let offset = self.global_uniforms.push(&wgpu.queue, &global_uniform);
+        let bind_group = ui_bind_group(
+            &mut wgpu.bind_groups.lock().unwrap(),
+            &wgpu.device,
+            &self.global_uniforms,
+            &tex_views,
+        );
 
         let (verts, indxs, clip) = self.call_list.get_draw_call_data(i).unwrap();
+        let call = &self.call_list.calls[i as usize];
+        let effect = call.effect.index().and_then(|i| self.effects.get(i));
+        // the pipelines of shader effects read full vertices
+        let compact = self.compact_vertices && !matches!(effect, Some(UiEffect::Pipeline(_)));
 
-        wgpu.queue
-            .write_buffer(&self.gpu_vertices, 0, bytemuck::cast_slice(verts));
-        wgpu.queue
-            .write_buffer(&self.gpu_indices, 0, bytemuck::cast_slice(indxs));
+        {
+            crate::profile_scope!("upload_buffers");
+            if compact {
+                let verts: Vec<CompactVertex> = verts.iter().map(CompactVertex::from).collect();
+                wgpu.queue
+                    .write_buffer(&self.gpu_vertices, 0, bytemuck::cast_slice(&verts));
+            } else {
+                wgpu.queue
+                    .write_buffer(&self.gpu_vertices, 0, bytemuck::cast_slice(verts));
+            }
+            wgpu.queue
+                .write_buffer(&self.gpu_indices, 0, bytemuck::cast_slice(indxs));
+        }
 
-        rpass.set_bind_group(0, &bind_group, &[]);
-        rpass.set_vertex_buffer(0, self.gpu_vertices.slice(..));
+        rpass.set_bind_group(0, &bind_group, &[offset]);
         rpass.set_index_buffer(self.gpu_indices.slice(..), wgpu::IndexFormat::Uint32);
-        
-        let desc = Vertex::desc();
-        let config = gpu::ShaderBuildConfig::new([(&desc, "Vertex")]);
-        rpass.set_pipeline(&UiShader.get_pipeline(config, wgpu));
 
         let target_size = self.screen_size.as_uvec2();
         let clip_min = clip.min.as_uvec2().max(UVec2::ZERO).min(target_size);
@@ -3119,14 +5747,78 @@ impl RenderPassHandle for RenderData {
         // let clip_size = clip.size().as_uvec2().clamp(Vec2::ZERO, target_size);
         rpass.set_scissor_rect(clip_min.x, clip_min.y, clip_size.x, clip_size.y);
 
+        let mut mask_vtx = self
+            .call_list
+            .clip_mask(call)
+            .map(clip_mask_triangles)
+            .unwrap_or_default();
+
+        if mask_vtx.len() > Self::MAX_MASK_VERTEX_COUNT as usize {
+            log::warn!("clip mask with {} vertices is truncated", mask_vtx.len());
+            mask_vtx.truncate(Self::MAX_MASK_VERTEX_COUNT as usize / 3 * 3);
+        }
+
+        let stencil = if mask_vtx.is_empty() {
+            StencilMode::Ignore
+        } else {
+            // mark the inside of the mask in the stencil buffer
+            wgpu.queue
+                .write_buffer(&self.gpu_mask_vertices, 0, bytemuck::cast_slice(&mask_vtx));
+            let variant = UiPipelineVariant {
+                stencil: StencilMode::WriteMask,
+                ..Default::default()
+            };
+            rpass.set_pipeline(&UiShader.pipeline(variant, wgpu));
+            rpass.set_vertex_buffer(0, self.gpu_mask_vertices.slice(..));
+            rpass.draw(0..mask_vtx.len() as u32, 0..1);
+            StencilMode::TestMask
+        };
+
+        let polygon_mode = if self.wireframe {
+            wgpu::PolygonMode::Line
+        } else {
+            wgpu::PolygonMode::Fill
+        };
+        let variant = UiPipelineVariant {
+            stencil,
+            topology: call.topology,
+            polygon_mode,
+            compact,
+        };
+        let pipeline = match effect {
+            Some(UiEffect::Fragment(wgsl)) => UiShader.effect_pipeline(wgsl, variant, wgpu),
+            Some(UiEffect::Pipeline(pipeline)) => pipeline(wgpu),
+            None => UiShader.pipeline(variant, wgpu),
+        };
+        rpass.set_pipeline(&pipeline);
+        rpass.set_vertex_buffer(0, self.gpu_vertices.slice(..));
         rpass.draw_indexed(0..indxs.len() as u32, 0, 0..1);
     }
 }
 
+/// triangulates a clip mask outline as a fan, drawn with [`StencilMode::WriteMask`] the
+/// overlapping triangles cancel out so concave outlines are filled correctly
+pub fn clip_mask_triangles(outline: &[Vec2]) -> Vec<Vertex> {
+    if outline.len() < 3 {
+        return vec![];
+    }
+
+    let p0 = Vertex::color(outline[0], RGBA::ZERO);
+    outline
+        .windows(2)
+        .skip(1)
+        .flat_map(|w| [p0, Vertex::color(w[0], RGBA::ZERO), Vertex::color(w[1], RGBA::ZERO)])
+        .collect()
+}
+
 /// Represents a contiguous segment of vertex and index data
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct DrawCall {
     pub clip_rect: Rect,
+    /// index + 1 into [`DrawCallList::masks`], 0 if the call is not masked
+    pub clip_mask: u32,
+    pub topology: wgpu::PrimitiveTopology,
+    pub effect: EffectId,
     pub vtx_ptr: usize,
     pub idx_ptr: usize,
     pub n_vtx: usize,
@@ -3138,6 +5830,9 @@ impl DrawCall {
     pub fn new() -> Self {
         Self {
             clip_rect: Rect::ZERO,
+            clip_mask: 0,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            effect: EffectId::NONE,
             vtx_ptr: 0,
             idx_ptr: 0,
             n_vtx: 0,
@@ -3162,6 +5857,8 @@ pub struct DrawCallList {
     /// Current write offset in `idx_alloc`.
     pub idx_ptr: usize,
     pub calls: Vec<DrawCall>,
+    /// outlines of the clip masks used by the calls
+    pub masks: Vec<Vec<Vec2>>,
 }
 
 impl fmt::Debug for DrawCallList {
@@ -3174,6 +5871,7 @@ impl fmt::Debug for DrawCallList {
             .field("vtx_ptr", &self.vtx_ptr)
             .field("idx_ptr", &self.idx_ptr)
             .field("calls", &self.calls)
+            .field("masks", &self.masks.len())
             .finish()
     }
 }
@@ -3181,6 +5879,7 @@ impl fmt::Debug for DrawCallList {
 impl DrawCallList {
     pub fn clear(&mut self) {
         self.calls.clear();
+        self.masks.clear();
         self.vtx_ptr = 0;
         self.idx_ptr = 0;
     }
@@ -3200,7 +5899,46 @@ impl DrawCallList {
             vtx_ptr: 0,
             idx_ptr: 0,
             calls: vec![],
+            masks: vec![],
+        }
+    }
+
+    /// appends the commands of a draw list, clip rects are clamped to `screen_size`. returns
+    /// the number of commands
+    pub fn push_drawlist(&mut self, list: &DrawList, screen_size: Vec2) -> usize {
+        if self.calls.is_empty() {
+            self.set_clip_rect(Rect::from_min_size(Vec2::ZERO, screen_size));
+        }
+        let mask_base = self.masks.len() as u32;
+        self.masks.extend(list.clip_masks().iter().cloned());
+
+        for cmd in list.commands().iter(){
+            let vtx = &list.vtx_slice(cmd.vtx_offset..cmd.vtx_offset + cmd.vtx_count);
+            let idx = &list.idx_slice(cmd.idx_offset..cmd.idx_offset + cmd.idx_count);
+
+            let mut curr_clip = self.current_clip_rect();
+            curr_clip.min = curr_clip.min.max(Vec2::ZERO);
+            curr_clip.max = curr_clip.max.min(screen_size);
+
+            let mut clip = cmd.clip_rect;
+            clip.min = clip.min.max(Vec2::ZERO);
+            clip.max = clip.max.min(screen_size);
+
+            // draw_buff.set_clip_rect(cmd.clip_rect);
+            if cmd.clip_rect_used {
+                self.set_clip_rect(cmd.clip_rect);
+            } else if !self.current_clip_rect().contains_rect(clip) {
+                self.set_clip_rect(Rect::from_min_size(Vec2::ZERO, screen_size));
+            }
+            let mask = if cmd.clip_mask == 0 { 0 } else { mask_base + cmd.clip_mask };
+            self.set_clip_mask(mask);
+            self.set_topology(cmd.topology);
+            self.set_effect(cmd.effect);
+
+            self.push_texture(cmd.texture_id);
+            self.push(vtx, idx);
         }
+        list.commands().len()
     }
 
     pub fn get_draw_call_data(&self, chunk_idx: u32) -> Option<(&[Vertex], &[u32], Rect)> {
@@ -3226,9 +5964,12 @@ impl DrawCallList {
         }
 
         if c.textures.len() >= MAX_N_TEXTURES_PER_DRAW_CALL {
-            let prev_clip = self.calls.last().unwrap().clip_rect;
+            let prev = self.calls.last().unwrap();
             self.calls.push(DrawCall {
-                clip_rect: prev_clip,
+                clip_rect: prev.clip_rect,
+                clip_mask: prev.clip_mask,
+                topology: prev.topology,
+                effect: prev.effect,
                 vtx_ptr: self.vtx_ptr,
                 idx_ptr: self.idx_ptr,
                 n_vtx: 0,
@@ -3282,6 +6023,9 @@ impl DrawCallList {
             let prev_textures = self.calls.last().unwrap().textures;
             self.calls.push(DrawCall {
                 clip_rect: prev_clip,
+                clip_mask: c.clip_mask,
+                topology: c.topology,
+                effect: c.effect,
                 vtx_ptr: self.vtx_ptr,
                 idx_ptr: self.idx_ptr,
                 n_vtx: 0,
@@ -3348,8 +6092,12 @@ impl DrawCallList {
         if c.clip_rect == Rect::ZERO {
             c.clip_rect = rect
         } else if c.clip_rect != rect {
+            let (clip_mask, topology, effect) = (c.clip_mask, c.topology, c.effect);
             self.calls.push(DrawCall {
                 clip_rect: rect,
+                clip_mask,
+                topology,
+                effect,
                 vtx_ptr: self.vtx_ptr,
                 idx_ptr: self.idx_ptr,
                 n_vtx: 0,
@@ -3364,14 +6112,694 @@ impl DrawCallList {
     pub fn current_clip_rect(&self) -> Rect {
         self.calls.last().unwrap().clip_rect
     }
-}
-
-pub struct UiShader;
 
-impl gpu::ShaderHandle for UiShader {
-    const RENDER_PIPELINE_ID: gpu::ShaderID = "ui_shader";
-
-    fn build_pipeline<const N: usize>(&self, config: gpu::ShaderBuildConfig<'_, N>, wgpu: &WGPU) -> wgpu::RenderPipeline {
+    pub fn set_clip_mask(&mut self, mask: u32) {
+        if self.calls.is_empty() {
+            self.calls.push(DrawCall::new());
+        }
+
+        let c = self.calls.last_mut().unwrap();
+        if c.n_idx == 0 {
+            c.clip_mask = mask;
+        } else if c.clip_mask != mask {
+            let (clip_rect, topology, effect) = (c.clip_rect, c.topology, c.effect);
+            self.calls.push(DrawCall {
+                clip_rect,
+                clip_mask: mask,
+                topology,
+                effect,
+                vtx_ptr: self.vtx_ptr,
+                idx_ptr: self.idx_ptr,
+                n_vtx: 0,
+                n_idx: 0,
+                textures: ArrVec::new(),
+            });
+        }
+    }
+
+    pub fn set_topology(&mut self, topology: wgpu::PrimitiveTopology) {
+        if self.calls.is_empty() {
+            self.calls.push(DrawCall::new());
+        }
+
+        let c = self.calls.last_mut().unwrap();
+        if c.n_idx == 0 {
+            c.topology = topology;
+        } else if c.topology != topology {
+            let (clip_rect, clip_mask, effect) = (c.clip_rect, c.clip_mask, c.effect);
+            self.calls.push(DrawCall {
+                clip_rect,
+                clip_mask,
+                topology,
+                effect,
+                vtx_ptr: self.vtx_ptr,
+                idx_ptr: self.idx_ptr,
+                n_vtx: 0,
+                n_idx: 0,
+                textures: ArrVec::new(),
+            });
+        }
+    }
+
+    pub fn set_effect(&mut self, effect: EffectId) {
+        if self.calls.is_empty() {
+            self.calls.push(DrawCall::new());
+        }
+
+        let c = self.calls.last_mut().unwrap();
+        if c.n_idx == 0 {
+            c.effect = effect;
+        } else if c.effect != effect {
+            let (clip_rect, clip_mask, topology) = (c.clip_rect, c.clip_mask, c.topology);
+            self.calls.push(DrawCall {
+                clip_rect,
+                clip_mask,
+                topology,
+                effect,
+                vtx_ptr: self.vtx_ptr,
+                idx_ptr: self.idx_ptr,
+                n_vtx: 0,
+                n_idx: 0,
+                textures: ArrVec::new(),
+            });
+        }
+    }
+
+    /// the outline of the clip mask of `call`, if it is masked
+    pub fn clip_mask(&self, call: &DrawCall) -> Option<&[Vec2]> {
+        let i = call.clip_mask.checked_sub(1)?;
+        self.masks.get(i as usize).map(Vec::as_slice)
+    }
+
+    /// merges draw calls that share the same clip rect, clip mask, topology and effect and whose
+    /// textures fit into a single bind group.
+    ///
+    /// a call is only moved into an earlier batch if none of the batches in between overlap
+    /// its clip rect, so the visible draw order is preserved.
+    pub fn merge_calls(&mut self) -> BatchStats {
+        let mut stats = BatchStats {
+            n_calls_unbatched: self.calls.len(),
+            ..Default::default()
+        };
+
+        let calls: Vec<DrawCall> = self.calls.drain(..).filter(|c| c.n_idx != 0).collect();
+        stats.n_empty = stats.n_calls_unbatched - calls.len();
+
+        // (merged call, indices into `calls`)
+        let mut batches: Vec<(DrawCall, Vec<usize>)> = Vec::with_capacity(calls.len());
+
+        for (i, c) in calls.iter().enumerate() {
+            let mut target = None;
+
+            for (j, (b, _)) in batches.iter().enumerate().rev() {
+                if b.clip_rect == c.clip_rect
+                    && b.clip_mask == c.clip_mask
+                    && b.topology == c.topology
+                    && b.effect == c.effect
+                {
+                    let n_tex = c
+                        .textures
+                        .iter()
+                        .filter(|id| !b.textures.iter().any(|b_id| b_id == *id))
+                        .count();
+
+                    if b.textures.len() + n_tex <= MAX_N_TEXTURES_PER_DRAW_CALL
+                        && b.n_vtx + c.n_vtx <= self.max_vtx_per_chunk
+                        && b.n_idx + c.n_idx <= self.max_idx_per_chunk
+                    {
+                        target = Some(j);
+                    }
+                    break;
+                }
+
+                if b.clip_rect.intersects(c.clip_rect) {
+                    break;
+                }
+            }
+
+            match target {
+                Some(j) => {
+                    let (b, members) = &mut batches[j];
+                    for &id in c.textures.iter() {
+                        if !b.textures.iter().any(|&b_id| b_id == id) {
+                            b.textures.push(id);
+                        }
+                    }
+                    b.n_vtx += c.n_vtx;
+                    b.n_idx += c.n_idx;
+                    members.push(i);
+                    stats.n_merged += 1;
+                }
+                None => batches.push((*c, vec![i])),
+            }
+        }
+
+        if stats.n_merged == 0 {
+            self.calls = calls;
+            stats.n_calls = self.calls.len();
+            return stats;
+        }
+
+        let mut vtx_alloc = Vec::with_capacity(self.vtx_ptr);
+        let mut idx_alloc = Vec::with_capacity(self.idx_ptr);
+
+        for (mut b, members) in batches {
+            b.vtx_ptr = vtx_alloc.len();
+            b.idx_ptr = idx_alloc.len();
+
+            for i in members {
+                let c = &calls[i];
+                let vtx_offset = (vtx_alloc.len() - b.vtx_ptr) as u32;
+
+                vtx_alloc.extend(self.vtx_alloc[c.vtx_ptr..c.vtx_ptr + c.n_vtx].iter().map(|&v| {
+                    let mut v = v;
+                    // remap the texture binding from the old to the merged call
+                    if v.tex != 0 {
+                        let raw_tex_id = *c.textures.get(v.tex as usize - 1).unwrap();
+                        v.tex = b.textures.iter().position(|&id| id == raw_tex_id).unwrap() as u32 + 1;
+                    }
+                    v
+                }));
+
+                idx_alloc.extend(
+                    self.idx_alloc[c.idx_ptr..c.idx_ptr + c.n_idx]
+                        .iter()
+                        .map(|&idx| idx + vtx_offset),
+                );
+            }
+
+            self.calls.push(b);
+        }
+
+        self.vtx_ptr = vtx_alloc.len();
+        self.idx_ptr = idx_alloc.len();
+        self.vtx_alloc = vtx_alloc;
+        self.idx_alloc = idx_alloc;
+
+        stats.n_calls = self.calls.len();
+        stats
+    }
+}
+
+/// statistics of the last [`DrawCallList::merge_calls`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BatchStats {
+    /// number of draw commands pushed from the drawlists
+    pub n_cmds: usize,
+    /// number of draw calls before merging
+    pub n_calls_unbatched: usize,
+    /// number of draw calls submitted
+    pub n_calls: usize,
+    /// number of calls folded into an earlier call
+    pub n_merged: usize,
+    /// number of calls dropped because they contained no indices
+    pub n_empty: usize,
+}
+
+impl fmt::Display for BatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} cmds -> {} calls -> {} batches ({} merged, {} empty)",
+            self.n_cmds, self.n_calls_unbatched, self.n_calls, self.n_merged, self.n_empty
+        )
+    }
+}
+
+/// the batched draw calls of a frame and the textures they reference
+#[derive(Clone, Copy)]
+pub struct DrawOutput<'a> {
+    pub calls: &'a DrawCallList,
+    /// texture id `i` is stored at index `i - 1`, id 0 is the white texture
+    pub textures: &'a [gpu::Texture],
+    /// effect id `i` is stored at index `i - 1`
+    pub effects: &'a [UiEffect],
+    /// seconds passed to the effects
+    pub time: f32,
+    /// changes with every frame of the source, a [`Painter`] starts a new frame when it changes
+    pub frame: u64,
+}
+
+/// paints the ui into a render pass owned by the host application
+///
+/// [`RenderData`] records a separate pass for every draw call. the painter uploads all calls at
+/// once so they can be drawn into a single pass of the host's frame. the host pass has no
+/// stencil attachment, so [`ClipShape`] masks are ignored and only the clip rects apply.
+/// [`UiEffect::Pipeline`] effects are built for the passes of the context and not applied
+///
+/// the calls of a frame are written behind each other into the painter's buffers, so one
+/// painter can paint several outputs before the frame is submitted. the buffers are written
+/// from the start again when an output of a new [`DrawOutput::frame`] is painted. hosts that
+/// paint the outputs of several sources per frame call [`Painter::begin_frame`] themselves,
+/// or compose them with a [`DrawComposer`]
+pub struct Painter {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    pipeline: wgpu::RenderPipeline,
+    line_pipeline: wgpu::RenderPipeline,
+    /// pipelines of [`UiEffect::Fragment`]s by their wgsl and topology
+    effect_pipelines: HashMap<gpu::PipelineKey, wgpu::RenderPipeline>,
+    white_texture: wgpu::TextureView,
+    gpu_vertices: wgpu::Buffer,
+    gpu_indices: wgpu::Buffer,
+    vtx_cursor: WriteCursor,
+    idx_cursor: WriteCursor,
+    /// frame of the last painted output, `None` once [`Painter::begin_frame`] was called
+    /// by the host
+    frame: Option<u64>,
+    bind_groups: gpu::BindGroupCache,
+    global_uniforms: gpu::UniformRing,
+}
+
+/// the part of a [`Painter`] buffer already written this frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WriteCursor {
+    capacity: u64,
+    next: u64,
+}
+
+impl WriteCursor {
+    const MIN_CAPACITY: u64 = 1024;
+
+    fn new() -> Self {
+        Self {
+            capacity: Self::MIN_CAPACITY,
+            next: 0,
+        }
+    }
+
+    /// reserves `len` bytes, returns their offset and whether a new buffer with the new
+    /// capacity is needed. calls painted before still read the old buffer, so the new one
+    /// starts empty and is large enough for everything written this frame
+    fn alloc(&mut self, len: u64) -> (u64, bool) {
+        let len = len.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        let end = self.next + len;
+        if end <= self.capacity {
+            self.next = end;
+            return (end - len, false);
+        }
+        self.capacity = end.next_power_of_two().max(Self::MIN_CAPACITY);
+        self.next = len;
+        (0, true)
+    }
+}
+
+impl Painter {
+    /// `format` and `sample_count` have to match the render pass the ui is painted into
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let desc = Vertex::desc();
+        let config = gpu::ShaderBuildConfig::new([(&desc, "Vertex")]);
+        let pipeline = UiShader.build_pipeline_for_target(config, device, format, sample_count);
+        let variant = UiPipelineVariant {
+            stencil: StencilMode::None,
+            topology: wgpu::PrimitiveTopology::LineList,
+            ..Default::default()
+        };
+        let line_pipeline =
+            UiShader.build_pipeline_variant(config, device, format, sample_count, variant);
+
+        let white_texture = device
+            .create_texture_with_data(
+                queue,
+                &wgpu::TextureDescriptor {
+                    label: Some("painter_white_texture"),
+                    size: wgpu::Extent3d {
+                        width: 1,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+                wgpu::util::TextureDataOrder::LayerMajor,
+                &[255, 255, 255, 255],
+            )
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self {
+            gpu_vertices: Self::create_vertex_buffer(device, 0),
+            gpu_indices: Self::create_index_buffer(device, 0),
+            vtx_cursor: WriteCursor::new(),
+            idx_cursor: WriteCursor::new(),
+            frame: Some(0),
+            device: device.clone(),
+            queue: queue.clone(),
+            format,
+            sample_count,
+            pipeline,
+            line_pipeline,
+            effect_pipelines: HashMap::new(),
+            white_texture,
+            bind_groups: gpu::BindGroupCache::new(),
+            global_uniforms: GlobalUniform::ring(device),
+        }
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, size: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("painter_vertex_buffer"),
+            size: (size as u64).next_power_of_two().max(WriteCursor::MIN_CAPACITY),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_index_buffer(device: &wgpu::Device, size: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("painter_index_buffer"),
+            size: (size as u64).next_power_of_two().max(WriteCursor::MIN_CAPACITY),
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// starts a new frame, the buffers are written from the start again. call it after the
+    /// previous frame was submitted and before the first [`Self::render`]. once called, the
+    /// painter no longer starts new frames on its own
+    pub fn begin_frame(&mut self) {
+        self.frame = None;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.vtx_cursor.next = 0;
+        self.idx_cursor.next = 0;
+        self.global_uniforms.reset();
+        self.bind_groups.end_frame();
+    }
+
+    /// whether painting an output of `frame` starts a new frame
+    fn is_new_frame(last: Option<u64>, frame: u64) -> bool {
+        last.is_some_and(|last| last != frame)
+    }
+
+    /// `screen_size` is the size of the render target in pixels. writes behind the calls
+    /// painted before in the same frame, see [`Self::begin_frame`]
+    pub fn render(
+        &mut self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        output: &DrawOutput,
+        screen_size: Vec2,
+    ) {
+        if Self::is_new_frame(self.frame, output.frame) {
+            self.frame = Some(output.frame);
+            self.reset();
+        }
+
+        let list = output.calls;
+        if list.calls.is_empty() {
+            return;
+        }
+
+        let vtx_bytes: &[u8] = bytemuck::cast_slice(&list.vtx_alloc[..list.vtx_ptr]);
+        let idx_bytes: &[u8] = bytemuck::cast_slice(&list.idx_alloc[..list.idx_ptr]);
+        if vtx_bytes.is_empty() || idx_bytes.is_empty() {
+            return;
+        }
+
+        let (vtx_offset, grow) = self.vtx_cursor.alloc(vtx_bytes.len() as u64);
+        if grow {
+            let size = self.vtx_cursor.capacity as usize;
+            self.gpu_vertices = Self::create_vertex_buffer(&self.device, size);
+        }
+        let (idx_offset, grow) = self.idx_cursor.alloc(idx_bytes.len() as u64);
+        if grow {
+            let size = self.idx_cursor.capacity as usize;
+            self.gpu_indices = Self::create_index_buffer(&self.device, size);
+        }
+        {
+            crate::profile_scope!("upload_buffers");
+            self.queue.write_buffer(&self.gpu_vertices, vtx_offset, vtx_bytes);
+            self.queue.write_buffer(&self.gpu_indices, idx_offset, idx_bytes);
+        }
+
+        let proj = Mat4::orthographic_lh(0.0, screen_size.x, screen_size.y, 0.0, -1.0, 1.0);
+        let global_uniform = GlobalUniform::new(screen_size, proj).with_time(output.time);
+        let offset = self.global_uniforms.push(&self.queue, &global_uniform);
+
+        let vtx_range = vtx_offset..vtx_offset + vtx_bytes.len() as u64;
+        let idx_range = idx_offset..idx_offset + idx_bytes.len() as u64;
+        rpass.set_vertex_buffer(0, self.gpu_vertices.slice(vtx_range));
+        rpass.set_index_buffer(self.gpu_indices.slice(idx_range), wgpu::IndexFormat::Uint32);
+
+        let target_size = screen_size.as_uvec2();
+        for call in &list.calls {
+            let clip_min = call.clip_rect.min.as_uvec2().min(target_size);
+            let clip_max = call.clip_rect.max.as_uvec2().max(clip_min).min(target_size);
+            let clip_size = clip_max - clip_min;
+            if call.n_idx == 0 || clip_size.x == 0 || clip_size.y == 0 {
+                continue;
+            }
+
+            let mut tex_views = call
+                .textures
+                .iter()
+                .map(|&tex_id| output.textures[tex_id as usize - 1].view().clone())
+                .collect::<Vec<_>>();
+            tex_views.resize(MAX_N_TEXTURES_PER_DRAW_CALL, self.white_texture.clone());
+
+            let bind_group = ui_bind_group(
+                &mut self.bind_groups,
+                &self.device,
+                &self.global_uniforms,
+                &tex_views,
+            );
+            rpass.set_bind_group(0, &bind_group, &[offset]);
+            rpass.set_scissor_rect(clip_min.x, clip_min.y, clip_size.x, clip_size.y);
+            let effect = call.effect.index().and_then(|i| output.effects.get(i));
+            if let Some(UiEffect::Fragment(wgsl)) = effect {
+                let (device, format, sample_count) = (&self.device, self.format, self.sample_count);
+                let key = gpu::PipelineKey::of(&(wgsl, call.topology));
+                let pipeline = self.effect_pipelines.entry(key).or_insert_with(|| {
+                    let desc = Vertex::desc();
+                    let config = gpu::ShaderBuildConfig::new([(&desc, "Vertex")]);
+                    let variant = UiPipelineVariant {
+                        stencil: StencilMode::None,
+                        topology: call.topology,
+                        ..Default::default()
+                    };
+                    UiShader.build_pipeline_effect(
+                        config,
+                        device,
+                        format,
+                        sample_count,
+                        variant,
+                        Some(wgsl),
+                    )
+                });
+                rpass.set_pipeline(pipeline);
+            } else {
+                rpass.set_pipeline(match call.topology {
+                    wgpu::PrimitiveTopology::LineList => &self.line_pipeline,
+                    _ => &self.pipeline,
+                });
+            }
+
+            let idx_start = call.idx_ptr as u32;
+            rpass.draw_indexed(idx_start..idx_start + call.n_idx as u32, call.vtx_ptr as i32, 0..1);
+        }
+    }
+}
+
+pub struct UiShader;
+
+impl gpu::ShaderHandle for UiShader {
+    const RENDER_PIPELINE_ID: gpu::ShaderID = "ui_shader";
+
+    fn build_pipeline<const N: usize>(&self, config: gpu::ShaderBuildConfig<'_, N>, wgpu: &WGPU) -> wgpu::RenderPipeline {
+        let variant = UiPipelineVariant::default();
+        self.build_pipeline_variant(config, &wgpu.device, wgpu.surface_format, 1, variant)
+    }
+}
+
+/// render state that differs between the pipelines of the [`UiShader`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UiPipelineVariant {
+    pub stencil: StencilMode,
+    /// `TriangleList` or `LineList`, see [`LineMode`]
+    pub topology: wgpu::PrimitiveTopology,
+    /// `Line` draws a wireframe, needs [`wgpu::Features::POLYGON_MODE_LINE`]
+    pub polygon_mode: wgpu::PolygonMode,
+    /// reads [`CompactVertex`]s instead of [`Vertex`]s
+    pub compact: bool,
+}
+
+impl Default for UiPipelineVariant {
+    fn default() -> Self {
+        Self {
+            stencil: StencilMode::Ignore,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            compact: false,
+        }
+    }
+}
+
+/// how a [`UiShader`] pipeline uses the stencil attachment, used to clip to a [`ClipShape`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StencilMode {
+    /// the pass has no stencil attachment
+    None,
+    /// the pass has a stencil attachment but it is not used
+    Ignore,
+    /// inverts the stencil where the mask is drawn, without writing any color
+    WriteMask,
+    /// only draws where the stencil was set by [`StencilMode::WriteMask`]
+    TestMask,
+}
+
+impl StencilMode {
+    fn state(self) -> Option<wgpu::StencilState> {
+        let face = |compare, pass_op| wgpu::StencilFaceState {
+            compare,
+            fail_op: wgpu::StencilOperation::Keep,
+            depth_fail_op: wgpu::StencilOperation::Keep,
+            pass_op,
+        };
+        let state = |face| wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: 0xff,
+            write_mask: 0xff,
+        };
+
+        match self {
+            Self::None => None,
+            Self::Ignore => Some(wgpu::StencilState::default()),
+            Self::WriteMask => Some(state(face(
+                wgpu::CompareFunction::Always,
+                wgpu::StencilOperation::Invert,
+            ))),
+            Self::TestMask => Some(state(face(
+                wgpu::CompareFunction::NotEqual,
+                wgpu::StencilOperation::Keep,
+            ))),
+        }
+    }
+}
+
+impl UiShader {
+    /// the cached pipeline for the surface format, passes must attach a
+    /// [`gpu::STENCIL_FORMAT`] buffer unless the stencil mode is [`StencilMode::None`]
+    pub fn pipeline(
+        &self,
+        variant: UiPipelineVariant,
+        wgpu: &WGPU,
+    ) -> std::sync::Arc<wgpu::RenderPipeline> {
+        let desc = match variant.compact {
+            true => CompactVertex::desc(),
+            false => Vertex::desc(),
+        };
+        let config = gpu::ShaderBuildConfig::new([(&desc, "Vertex")]);
+        if variant == UiPipelineVariant::default() {
+            return self.get_pipeline(config, wgpu);
+        }
+
+        let key = gpu::PipelineKey::of(&(Self::RENDER_PIPELINE_ID, variant));
+        wgpu.get_or_init_pipeline(key, || {
+            self.build_pipeline_variant(config, &wgpu.device, wgpu.surface_format, 1, variant)
+        })
+    }
+
+    /// builds the pipeline for a render target with the given format and sample count
+    pub fn build_pipeline_for_target<const N: usize>(
+        &self,
+        config: gpu::ShaderBuildConfig<'_, N>,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        let variant = UiPipelineVariant {
+            stencil: StencilMode::None,
+            ..Default::default()
+        };
+        self.build_pipeline_variant(config, device, format, sample_count, variant)
+    }
+
+    pub fn build_pipeline_variant<const N: usize>(
+        &self,
+        config: gpu::ShaderBuildConfig<'_, N>,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        variant: UiPipelineVariant,
+    ) -> wgpu::RenderPipeline {
+        self.build_pipeline_effect(config, device, format, sample_count, variant, None)
+    }
+
+    /// like [`Self::build_pipeline_variant`], the fragments are shaded by the wgsl of a
+    /// [`UiEffect::Fragment`]
+    pub fn build_pipeline_effect<const N: usize>(
+        &self,
+        config: gpu::ShaderBuildConfig<'_, N>,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        variant: UiPipelineVariant,
+        effect: Option<&str>,
+    ) -> wgpu::RenderPipeline {
+        let shader_src = self.shader_source(&config, effect);
+
+        let bind_group_entries = ui_bind_group_layout_entries();
+
+        let global_bind_group_layout =
+            device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    entries: &bind_group_entries,
+                    label: Some("global_bind_group_layout"),
+                });
+
+        let vertices = config.shader_templates.iter().map(|d| d.0).collect::<Vec<_>>();
+
+        let mut builder = gpu::PipelineBuilder::new(&shader_src, format);
+        if let Some(state) = variant.stencil.state() {
+            builder = builder.stencil(gpu::STENCIL_FORMAT, state);
+        }
+        if variant.stencil == StencilMode::WriteMask {
+            builder = builder.color_writes(wgpu::ColorWrites::empty());
+        }
+
+        builder
+            .label("rect_pipeline")
+            .primitive_topology(variant.topology)
+            .polygon_mode(variant.polygon_mode)
+            .vertex_buffers(&vertices)
+            .bind_groups(&[&global_bind_group_layout])
+            .bind_group_entries(&[&bind_group_entries])
+            .blend_state(Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            }))
+            .sample_count(sample_count)
+            .build(device)
+    }
+
+    /// the wgsl of the ui pipeline with the structs of the shader templates, `effect` is the
+    /// wgsl of a [`UiEffect::Fragment`] that shades every fragment
+    pub fn shader_source<const N: usize>(
+        &self,
+        config: &gpu::ShaderBuildConfig<'_, N>,
+        effect: Option<&str>,
+    ) -> String {
         const SHADER_SRC: &str = r#"
 
 
@@ -3385,7 +6813,8 @@ impl gpu::ShaderHandle for UiShader {
 
             struct GlobalUniform {
                 screen_size: vec2<f32>,
-                _pad: vec2<f32>,
+                time: f32,
+                _pad: f32,
                 proj: mat4x4<f32>,
             }
 
@@ -3397,6 +6826,7 @@ impl gpu::ShaderHandle for UiShader {
                 @location(0) color: vec4<f32>,
                 @location(1) uv: vec2<f32>,
                 @location(2) @interpolate(flat) tex: u32,
+                @location(3) @interpolate(flat) colormap: u32,
             };
 
             @vertex
@@ -3407,7 +6837,7 @@ impl gpu::ShaderHandle for UiShader {
 
                 out.color = v.col;
                 out.uv = v.uv;
-                out.tex = v.tex;
+                @rust vertex_tex;
 
                 out.pos = global.proj * vec4(v.pos, 0.0, 1.0);
                 return out;
@@ -3419,6 +6849,7 @@ impl gpu::ShaderHandle for UiShader {
 
             @rust texture_bindings;
 
+            @rust colormap_lut;
 
             @fragment
             fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
@@ -3428,48 +6859,6 @@ impl gpu::ShaderHandle for UiShader {
             }
             "#;
 
-
-        let mut bind_group_entries = vec![
-            //global uniform
-            wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-            // sampler
-            wgpu::BindGroupLayoutEntry {
-                binding: 1,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                count: None,
-            },  
-        ];
-
-        for i in 0..MAX_N_TEXTURES_PER_DRAW_CALL {
-            bind_group_entries.push(wgpu::BindGroupLayoutEntry {
-                binding: (i + 2) as u32,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Texture {
-                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    view_dimension: wgpu::TextureViewDimension::D2,
-                    multisampled: false,
-                },
-                count: None,
-            });
-        }
-
-        let global_bind_group_layout =
-            wgpu.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    entries: &bind_group_entries,
-                    label: Some("global_bind_group_layout"),
-                });
-
         let mut shader_src = gpu::pre_process_shader_code(SHADER_SRC, &config.shader_templates).unwrap();
 
         let mut rust_texture_bindings = String::new();
@@ -3494,31 +6883,30 @@ impl gpu::ShaderHandle for UiShader {
             rust_texture_fetch.push_str(&format!("col = select(col, c{}, in.tex == {}u);\n", i + 1, i + 1));
         }
 
-        rust_texture_fetch.push_str("return col;\n");
+        rust_texture_fetch.push_str("if in.colormap != 0u {\n col = vec4(colormap(in.colormap, col.r), col.a);\n}\n");
+        match effect {
+            Some(_) => rust_texture_fetch.push_str("return effect(col, in.uv, in.pos.xy);\n"),
+            None => rust_texture_fetch.push_str("return col;\n"),
+        }
         // rust_texture_fetch.push_str("else { return vec4<f32>(1.0, 0.0, 1.0, 1.0); }");
 
         shader_src = shader_src.replace("@rust texture_bindings;", &rust_texture_bindings);
         shader_src = shader_src.replace("@rust texture_fetch;", &rust_texture_fetch);
+        shader_src = shader_src.replace("@rust colormap_lut;", &colormap_wgsl());
 
-        let vertices = config.shader_templates.iter().map(|d| d.0).collect::<Vec<_>>();
-        gpu::PipelineBuilder::new(&shader_src, wgpu.surface_format)
-            .label("rect_pipeline")
-            .vertex_buffers(&vertices)
-            .bind_groups(&[&global_bind_group_layout])
-            .blend_state(Some(wgpu::BlendState {
-                color: wgpu::BlendComponent {
-                    src_factor: wgpu::BlendFactor::SrcAlpha,
-                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                    operation: wgpu::BlendOperation::Add,
-                },
-                alpha: wgpu::BlendComponent {
-                    src_factor: wgpu::BlendFactor::One,
-                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                    operation: wgpu::BlendOperation::Add,
-                },
-            }))
-            .sample_count(1)
-            .build(&wgpu.device)
+        // a compact vertex packs the colormap into the texture id
+        let packed = config.shader_templates.iter().all(|(desc, _)| {
+            !desc.members.contains(&"colormap")
+        });
+        let vertex_tex = match packed {
+            true => "out.tex = v.tex & 0xffffu;\nout.colormap = v.tex >> 16u;",
+            false => "out.tex = v.tex;\nout.colormap = v.colormap;",
+        };
+        shader_src = shader_src.replace("@rust vertex_tex;", vertex_tex);
+        if let Some(effect) = effect {
+            shader_src.push_str(effect);
+        }
+        shader_src
     }
 }
 
@@ -3527,7 +6915,8 @@ pub struct Vertex {
     pub pos: Vec2,
     pub uv: Vec2,
     pub tex: u32,
-    pub _pad: u32,
+    /// maps the red channel of the texture to a color, 0 for none, see [`Colormap`]
+    pub colormap: u32,
     pub col: RGBA,
 }
 
@@ -3536,7 +6925,7 @@ impl Vertex {
         pos: Vec2::ZERO,
         uv: Vec2::ZERO,
         tex: 0,
-        _pad: 0,
+        colormap: 0,
         col: RGBA::ZERO,
     };
 
@@ -3545,7 +6934,7 @@ impl Vertex {
             pos,
             uv,
             tex,
-            _pad: 0,
+            colormap: 0,
             col,
         }
     }
@@ -3554,11 +6943,36 @@ impl Vertex {
     }
 }
 
+/// a [`Vertex`] in 20 instead of 40 bytes, see [`RenderData::compact_vertices`]
+///
+/// the uv is clamped to `0..=1` and the color to 8 bit per channel, the texture id and
+/// colormap are packed into the low and high 16 bits of `tex`
+#[macros::vertex]
+pub struct CompactVertex {
+    pub pos: Vec2,
+    pub uv: gpu::Unorm16x2,
+    pub col: gpu::Unorm8x4,
+    pub tex: u32,
+}
+
+impl From<&Vertex> for CompactVertex {
+    fn from(v: &Vertex) -> Self {
+        Self {
+            pos: v.pos,
+            uv: gpu::Unorm16x2::from_f32(v.uv.into()),
+            col: v.col.into(),
+            tex: (v.tex & 0xffff) | (v.colormap << 16),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct GlobalUniform {
     pub screen_size: Vec2,
-    pub _pad: Vec2,
+    /// seconds since the start of the context, for animated [`UiEffect`]s
+    pub time: f32,
+    pub _pad: f32,
     pub proj: Mat4,
 }
 
@@ -3566,11 +6980,22 @@ impl GlobalUniform {
     pub fn new(screen_size: Vec2, proj: Mat4) -> Self {
         Self {
             screen_size,
-            _pad: Vec2::ZERO,
+            time: 0.0,
+            _pad: 0.0,
             proj,
         }
     }
 
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// uniform ring with a slot for each draw call submitted at once
+    pub fn ring(device: &wgpu::Device) -> gpu::UniformRing {
+        gpu::UniformRing::new(device, size_of::<Self>() as u64, 256)
+    }
+
     // pub fn build_bind_group(&self, wgpu: &WGPU) -> wgpu::BindGroup {
     //     let global_uniform = wgpu
     //         .device
@@ -3607,31 +7032,18 @@ impl GlobalUniform {
     // }
 }
 
-pub fn build_bind_group(
-    glob: GlobalUniform,
-    tex_views: &[wgpu::TextureView],
-    wgpu: &WGPU,
-) -> wgpu::BindGroup {
-    assert!(tex_views.len() == MAX_N_TEXTURES_PER_DRAW_CALL);
-
-    let global_uniform = wgpu
-        .device
-        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("rect_global_uniform_buffer"),
-            contents: bytemuck::cast_slice(&[glob]),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-
-
-        let mut layout_entries = vec![
-        // global uniform
+/// layout of the ui bind group: the global uniform at a dynamic offset, the sampler and
+/// [`MAX_N_TEXTURES_PER_DRAW_CALL`] textures
+pub(crate) fn ui_bind_group_layout_entries() -> Vec<wgpu::BindGroupLayoutEntry> {
+    let mut entries = vec![
+        // global uniform, also read by effects
         wgpu::BindGroupLayoutEntry {
             binding: 0,
-            visibility: wgpu::ShaderStages::VERTEX,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
             ty: wgpu::BindingType::Buffer {
                 ty: wgpu::BufferBindingType::Uniform,
-                has_dynamic_offset: false,
-                min_binding_size: None,
+                has_dynamic_offset: true,
+                min_binding_size: wgpu::BufferSize::new(size_of::<GlobalUniform>() as u64),
             },
             count: None,
         },
@@ -3645,7 +7057,7 @@ pub fn build_bind_group(
     ];
 
     for i in 0..MAX_N_TEXTURES_PER_DRAW_CALL {
-        layout_entries.push(wgpu::BindGroupLayoutEntry {
+        entries.push(wgpu::BindGroupLayoutEntry {
             binding: (i + 2) as u32,
             visibility: wgpu::ShaderStages::FRAGMENT,
             ty: wgpu::BindingType::Texture {
@@ -3656,51 +7068,1949 @@ pub fn build_bind_group(
             count: None,
         });
     }
+    entries
+}
 
+/// the bind group of a ui draw call, reused while the textures stay the same. set it with the
+/// offset returned by [`gpu::UniformRing::push`]
+pub fn ui_bind_group(
+    cache: &mut gpu::BindGroupCache,
+    device: &wgpu::Device,
+    globals: &gpu::UniformRing,
+    tex_views: &[wgpu::TextureView],
+) -> wgpu::BindGroup {
+    assert!(tex_views.len() == MAX_N_TEXTURES_PER_DRAW_CALL);
 
-    let global_bind_group_layout =
-        wgpu.device
-            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &layout_entries,
-                label: Some("global_bind_group_layout"),
-            });
+    let layout = cache.layout(device, &ui_bind_group_layout_entries());
+    let sampler = cache.sampler(
+        device,
+        "ui_texture_sampler",
+        &wgpu::SamplerDescriptor {
+            label: Some("ui_texture_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        },
+    );
 
-    let sampler = wgpu.device.create_sampler(&wgpu::SamplerDescriptor {
-        label: Some("ui_texture_sampler"),
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        mipmap_filter: wgpu::FilterMode::Linear,
-        ..Default::default()
-    });
+    let mut resources = vec![globals.resource(), gpu::BindResource::Sampler(sampler)];
+    resources.extend(tex_views.iter().cloned().map(gpu::BindResource::TextureView));
+    cache.bind_group(device, &layout, &resources)
+}
 
-    let mut group_entries = vec![
-        wgpu::BindGroupEntry {
-            binding: 0,
-            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                buffer: &global_uniform,
-                offset: 0,
-                size: None,
-            }),
-        },
-        wgpu::BindGroupEntry {
-            binding: 1,
-            resource: wgpu::BindingResource::Sampler(&sampler),
-        },
-    ];
+//---------------------------------------------------------------------------------------
+// END RENDER
 
-    for i in 0..MAX_N_TEXTURES_PER_DRAW_CALL {
-        group_entries.push(wgpu::BindGroupEntry {
-            binding: (i + 2) as u32,
-            resource: wgpu::BindingResource::TextureView(&tex_views[i]),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_id_matches_hashed_id() {
+        for label in ["button", "button##1", "", "a long label with ## in it"] {
+            assert_eq!(Id::from_static_str(label), Id::from_str(label));
+            assert_eq!(Id::from_static_str(label), Id::from_str(label));
+        }
+
+        const ID: Id = Id::from_u64(42);
+        assert_eq!(ID, Id(42));
+
+        let p = Panel::new("panel");
+        p.push_id(Id(7));
+        let a = p.gen_local_id("label");
+        assert_eq!(p.gen_local_id_from_hash(crate::core::static_str_hash64("label")), a);
+
+        p.push_id(Id(8));
+        let b = p.gen_local_id("label");
+        assert_ne!(a, b);
+        p.pop_id();
+        assert_eq!(p.gen_local_id("label"), a);
+    }
+
+    fn quad(tex: u32) -> ([Vertex; 4], [u32; 6]) {
+        let v = Vertex { tex, ..Vertex::ZERO };
+        ([v; 4], [0, 1, 2, 0, 2, 3])
+    }
+
+    #[test]
+    fn test_corner_radii_clamp() {
+        let c = CornerRadii::new(30.0, 30.0, 0.0, 0.0).clamp(Vec2::new(40.0, 100.0));
+        assert_eq!(c.tl, Vec2::splat(20.0));
+        assert_eq!(c.tr, Vec2::splat(20.0));
+
+        let c = CornerRadii::elliptical(Vec2::new(10.0, 5.0)).clamp(Vec2::splat(100.0));
+        assert_eq!(c, CornerRadii::elliptical(Vec2::new(10.0, 5.0)));
+        assert!(!CornerRadii::new_elliptical(Vec2::new(5.0, 0.0), Vec2::ZERO, Vec2::ZERO, Vec2::ZERO).any_round_corners());
+    }
+
+    #[test]
+    fn test_draw_channel_below() {
+        let mut list = DrawListData::new();
+        let (vtx, idx) = quad(0);
+        list.push_vtx_idx(&vtx, &idx);
+
+        let prev = list.begin_channel();
+        let (vtx, idx) = quad(1);
+        list.push_texture(TextureId(1));
+        list.push_vtx_idx(&vtx, &idx);
+        let content = list.end_channel(prev);
+
+        // drawn after the content, but ends up below it
+        let (vtx, idx) = quad(0);
+        list.push_vtx_idx(&vtx, &idx);
+        list.append_channel(content);
+
+        assert_eq!(list.vtx_buffer.len(), 12);
+        assert_eq!(list.vtx_buffer[4].tex, 0);
+        assert_eq!(list.vtx_buffer[8].tex, 1);
+        let last = list.cmd_buffer.last().unwrap();
+        assert_eq!(last.texture_id, TextureId(1));
+        assert_eq!(last.idx_count, 6);
+        assert_eq!(list.idx_buffer[last.idx_offset], 0);
+    }
+
+    #[test]
+    fn test_draw_channels_out_of_order() {
+        let mut list = DrawListData::new();
+        let (vtx, idx) = quad(0);
+        list.push_vtx_idx(&vtx, &idx);
+
+        let record = |list: &mut DrawListData, tex: u32| {
+            let prev = list.begin_channel();
+            let (vtx, idx) = quad(tex);
+            list.push_texture(TextureId(tex as u64));
+            list.push_vtx_idx(&vtx, &idx);
+            list.end_channel(prev)
+        };
+        let first = record(&mut list, 1);
+        let second = record(&mut list, 2);
+        // the channels are appended in the reverse order they were recorded in
+        list.append_channel(second);
+        list.append_channel(first);
+
+        let cmds: Vec<_> = list.cmd_buffer.iter().filter(|c| c.idx_count > 0).collect();
+        let textures: Vec<_> = cmds.iter().map(|c| c.texture_id).collect();
+        assert_eq!(textures, [TextureId(0), TextureId(2), TextureId(1)]);
+        for cmd in cmds {
+            let vtx = &list.vtx_buffer[cmd.vtx_offset..cmd.vtx_offset + cmd.vtx_count];
+            assert!(vtx.iter().all(|v| v.tex as u64 == cmd.texture_id.0));
+            let idx = &list.idx_buffer[cmd.idx_offset..cmd.idx_offset + cmd.idx_count];
+            assert_eq!(idx, [0, 1, 2, 0, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn test_merge_calls() {
+        let a = Rect::from_min_size(Vec2::ZERO, Vec2::splat(10.0));
+        let b = Rect::from_min_size(Vec2::splat(100.0), Vec2::splat(10.0));
+        let mut list = DrawCallList::new(1024, 1024);
+
+        for (clip, tex) in [(a, 1), (b, 0), (a, 2)] {
+            let (vtx, idx) = quad(tex);
+            list.set_clip_rect(clip);
+            list.push_texture(TextureId(tex as u64));
+            list.push(&vtx, &idx);
+        }
+
+        let stats = list.merge_calls();
+        assert_eq!(stats.n_calls_unbatched, 3);
+        assert_eq!(stats.n_calls, 2);
+        assert_eq!(list.calls[0].clip_rect, a);
+        assert_eq!(list.calls[0].textures.as_slice(), &[1, 2]);
+
+        let (vtx, idx, _) = list.get_draw_call_data(0).unwrap();
+        assert_eq!(vtx[0].tex, 1);
+        assert_eq!(vtx[4].tex, 2);
+        assert_eq!(idx[6], 4);
+    }
+
+    #[test]
+    fn test_image_nine_slice() {
+        let mut list = DrawListData::new();
+        let rect = Rect::from_min_size(Vec2::ZERO, Vec2::new(100.0, 50.0));
+        let uv = NineSliceUV::new(Vec2::ZERO, Vec2::ONE, Margins::all(0.25));
+        list.add_image_nine_slice(rect, TextureId(1), Margins::all(10.0), uv, RGBA::WHITE);
+
+        assert_eq!(list.vtx_buffer.len(), 9 * 4);
+        assert_eq!(list.idx_buffer.len(), 9 * 6);
+
+        // the center slice is stretched while keeping the inner uvs
+        let center = &list.vtx_buffer[4 * 4..5 * 4];
+        let pos = Rect::from_points(&center.iter().map(|v| v.pos).collect::<Vec<_>>());
+        assert_eq!(pos, Rect::from_min_max(Vec2::splat(10.0), Vec2::new(90.0, 40.0)));
+        assert!(center.iter().all(|v| v.uv.min_element() >= 0.25 && v.uv.max_element() <= 0.75));
+
+        // margins larger than the rect are scaled down
+        let mut list = DrawListData::new();
+        let small = Rect::from_min_size(Vec2::ZERO, Vec2::splat(10.0));
+        list.add_image_nine_slice(small, TextureId(1), Margins::all(10.0), uv, RGBA::WHITE);
+        assert_eq!(list.vtx_buffer.len(), 4 * 4);
+    }
+
+    #[test]
+    fn test_tessellate_batch_order() {
+        let lines: Vec<[Vec2; 2]> = (0..300)
+            .map(|i| [Vec2::new(i as f32, 0.0), Vec2::new(i as f32, 10.0)])
+            .collect();
+        let line = |l: &[Vec2; 2]| tessellate_line(l, RGBA::WHITE, 1.0, false);
+
+        let (vtx, idx) = tessellate_batch(&lines, line);
+
+        let mut n_vtx = 0;
+        let mut n_idx = 0;
+        for l in &lines {
+            let (l_vtx, l_idx) = line(l);
+            assert_eq!(&vtx[n_vtx..n_vtx + l_vtx.len()], l_vtx.as_slice());
+            for (i, j) in l_idx.iter().enumerate() {
+                assert_eq!(idx[n_idx + i], j + n_vtx as u32);
+            }
+            n_vtx += l_vtx.len();
+            n_idx += l_idx.len();
+        }
+        assert_eq!(vtx.len(), n_vtx);
+        assert_eq!(idx.len(), n_idx);
+    }
+
+    #[test]
+    fn test_deferred_tessellation() {
+        let draw = |list: &mut DrawListData| {
+            for i in 0..40 {
+                let min = Vec2::new(i as f32 * 3.0, 10.0);
+                // a few sizes repeat, so some shapes are cached while others are pending
+                let max = min + Vec2::new(20.0 + (i % 13) as f32, 12.0);
+                let outline = Outline::inner(RGBA::RED, 1.0);
+                let (uv, white) = (Vec2::ONE, TextureId::WHITE);
+                let corners = CornerRadii::all(4.0);
+                list.add_rect_rounded(min, max, uv, uv, white, RGBA::WHITE, outline, corners);
+                if i % 5 == 0 {
+                    list.add_polyline(&[min, max], Stroke::new(RGBA::RED, 1.0), false);
+                    let line = [min, max].map(|p| Vertex::new(p, RGBA::RED, Vec2::ZERO, 1));
+                    list.push_vtx_idx_topology(&line, &[0, 1], wgpu::PrimitiveTopology::LineList);
+                }
+                if i % 9 == 0 {
+                    list.set_effect(EffectId(i as u32 % 2));
+                    let clip = Rect::from_min_size(Vec2::ZERO, Vec2::splat(60.0 + i as f32));
+                    list.push_clip_rect(clip);
+                }
+            }
+        };
+
+        let mut sequential = DrawListData::new();
+        sequential.defer_tessellation = false;
+        draw(&mut sequential);
+        let mut deferred = DrawListData::new();
+        deferred.defer_tessellation = true;
+        draw(&mut deferred);
+        assert!(!deferred.pending_shapes.is_empty());
+        deferred.resolve_pending_shapes();
+
+        assert_eq!(sequential.vtx_buffer, deferred.vtx_buffer);
+        assert_eq!(sequential.idx_buffer, deferred.idx_buffer);
+        assert_eq!(format!("{:?}", sequential.cmd_buffer), format!("{:?}", deferred.cmd_buffer));
+    }
+
+    #[test]
+    fn test_shape_cache_lru() {
+        let key = |w: f32| {
+            ShapeKey::rounded_rect(Vec2::splat(w), CornerRadii::all(2.0), RGBA::WHITE, Outline::none(), 0.3)
+        };
+        let mut cache = ShapeCache::with_capacity(4);
+
+        for w in 0..4 {
+            cache.get_or_insert_with(key(w as f32), ShapeTemplate::default);
+        }
+        // touch the first shape so it is no longer the least recently used
+        cache.get_or_insert_with(key(0.0), || panic!("expected cache hit"));
+        cache.get_or_insert_with(key(4.0), ShapeTemplate::default);
+
+        assert!(cache.shapes.contains_key(&key(0.0)));
+        assert!(!cache.shapes.contains_key(&key(1.0)));
+        assert_eq!(cache.stats.hits, 1);
+        assert_eq!(cache.stats.misses, 5);
+        assert_eq!(cache.stats.evicted, 1);
+    }
+
+    #[test]
+    fn test_large_drawlist_splits_commands() {
+        let mut data = DrawListData::new();
+        let (vtx, idx) = quad(0);
+        let n_quads = RenderData::MAX_VERTEX_COUNT as usize / vtx.len() + 1;
+
+        for _ in 0..n_quads {
+            data.push_vtx_idx(&vtx, &idx);
+        }
+
+        assert_eq!(data.cmd_buffer.len(), 2);
+        assert!(data.cmd_buffer.iter().all(|cmd| cmd.vtx_count <= RenderData::MAX_VERTEX_COUNT as usize));
+        assert_eq!(data.cmd_buffer[1].vtx_offset, data.cmd_buffer[0].vtx_count);
+        // indices are relative to their command
+        assert_eq!(data.idx_buffer[data.cmd_buffer[1].idx_offset], 0);
+    }
+
+    #[test]
+    fn test_merge_calls_keeps_overlapping_order() {
+        let a = Rect::from_min_size(Vec2::ZERO, Vec2::splat(10.0));
+        let b = Rect::from_min_size(Vec2::splat(5.0), Vec2::splat(10.0));
+        let mut list = DrawCallList::new(1024, 1024);
+
+        for clip in [a, b, a] {
+            let (vtx, idx) = quad(0);
+            list.set_clip_rect(clip);
+            list.push(&vtx, &idx);
+        }
+
+        let stats = list.merge_calls();
+        assert_eq!(stats.n_merged, 0);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn test_clip_mask() {
+        let rect = Rect::from_min_size(Vec2::ZERO, Vec2::splat(10.0));
+        let mut data = DrawListData::new();
+        data.push_clip_mask(ClipShape::Polygon(vec![
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::splat(10.0),
+            Vec2::new(0.0, 10.0),
+        ]));
+        let (vtx, idx) = quad(0);
+        data.push_vtx_idx(&vtx, &idx);
+        data.pop_clip_mask();
+        data.push_vtx_idx(&vtx, &idx);
+
+        let masks: Vec<u32> = data.cmd_buffer.iter().map(|c| c.clip_mask).collect();
+        assert_eq!(masks, [1, 0]);
+        assert_eq!(clip_mask_triangles(&data.clip_masks[0]).len(), 6);
+
+        // the masked and unmasked calls overlap and must not be merged
+        let mut list = DrawCallList::new(1024, 1024);
+        for mask in [0, 1, 0] {
+            let (vtx, idx) = quad(0);
+            list.set_clip_rect(rect);
+            list.set_clip_mask(mask);
+            list.push(&vtx, &idx);
+        }
+        let stats = list.merge_calls();
+        assert_eq!(stats.n_merged, 0);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn test_picking_ids() {
+        let (a, b) = (Id::from_hash(&"a"), Id::from_hash(&"b"));
+        let tri = [Vec2::ZERO, Vec2::X, Vec2::Y];
+        let mut picking = Picking::default();
+        picking.push(a, &tri, &[0, 1, 2]);
+        picking.push(a, &tri, &[0, 1, 2]);
+        picking.push(b, &tri, &[0, 1, 2]);
+
+        assert_eq!(picking.ids, [a, b]);
+        let ids: Vec<u32> = picking.vtx.iter().map(|v| v.id).collect();
+        assert_eq!(ids, [1, 1, 1, 1, 1, 1, 2, 2, 2]);
+        assert_eq!(picking.idx[3..6], [3, 4, 5]);
+
+        assert_eq!(Picking::resolve(&picking.ids, 0), Id::NULL);
+        assert_eq!(Picking::resolve(&picking.ids, 2), b);
+        assert_eq!(Picking::resolve(&picking.ids, 3), Id::NULL);
+    }
+
+    #[test]
+    fn test_line_primitives() {
+        use wgpu::PrimitiveTopology as PT;
+        let points = [Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::splat(10.0)];
+
+        let (vtx, idx, topology) = line_primitives(&points, RGBA::WHITE, 1.0, true);
+        assert_eq!(topology, PT::LineList);
+        assert_eq!(vtx.len(), 3);
+        assert_eq!(idx, [0, 1, 1, 2, 2, 0]);
+
+        let (vtx, idx, topology) = line_primitives(&points, RGBA::WHITE, 4.0, false);
+        assert_eq!(topology, PT::TriangleList);
+        assert_eq!((vtx.len(), idx.len()), (8, 12));
+        assert_eq!(vtx[0].pos, Vec2::new(0.0, 2.0));
+
+        // switching between lines and triangles starts a new command
+        let mut data = DrawListData::new();
+        data.add_polyline_fast(&points, Stroke::new(RGBA::WHITE, 1.0), false);
+        let (vtx, idx) = quad(0);
+        data.push_vtx_idx(&vtx, &idx);
+        let topologies: Vec<_> = data.cmd_buffer.iter().map(|c| c.topology).collect();
+        assert_eq!(topologies, [PT::LineList, PT::TriangleList]);
+    }
+
+    #[test]
+    fn test_icon_table_find() {
+        let mut icons = IconTable::new();
+        icons.register(phosphor_font::icon_font());
+        icons.register(IconFont::new("Custom").glyph("x", 'a').glyph("star", 'b'));
+
+        assert_eq!(icons.find("x"), Some((phosphor_font::FAMILY, '\u{E4F6}')));
+        assert_eq!(icons.find("star"), Some(("Custom", 'b')));
+        assert_eq!(icons.find("Custom:x"), Some(("Custom", 'a')));
+        assert_eq!(icons.find("Custom:caret-down"), None);
+        assert_eq!(icons.find("missing"), None);
+
+        // registering the same family again replaces it
+        icons.register(IconFont::new("Custom").glyph("star", 'c'));
+        assert_eq!(icons.fonts.len(), 2);
+        assert_eq!(icons.find("star"), Some(("Custom", 'c')));
+    }
+
+    #[test]
+    fn test_panel_style_merge() {
+        let base = PanelStyle::default()
+            .with_fill(RGBA::RED)
+            .with_padding(4.0);
+        let over = PanelStyle::default().with_gradient(RGBA::BLUE, RGBA::GREEN);
+
+        let merged = base.merge(over);
+        assert_eq!(merged.fill, Some(PanelFill::Gradient(RGBA::BLUE, RGBA::GREEN)));
+        assert_eq!(merged.padding, Some(4.0));
+        assert_eq!(merged.outline, None);
+        assert_eq!(merged.fill.map(PanelFill::top), Some(RGBA::BLUE));
+    }
+
+    #[test]
+    fn test_style_push_pop_all_fields() {
+        let orig = crate::ui_context::dark_theme();
+
+        // every style field can be pushed as a var
+        let mut style = orig.clone();
+        for field in StyleField::list() {
+            style.push_var(orig[field]);
+        }
+        style.push_var(StyleVar::SpacingH(3.0));
+        style.push_var(StyleVar::TextCol(RGBA::RED));
+        assert_eq!(style.spacing_h(), 3.0);
+        assert_eq!(style.text_col(), RGBA::RED);
+        assert_eq!(style.var_stack.len(), StyleTable::N_VARIABLES + 2);
+
+        while !style.var_stack.is_empty() {
+            style.pop_var();
+        }
+        assert_eq!(style, orig);
+    }
+
+    #[test]
+    fn test_high_contrast_theme() {
+        let s = crate::ui_context::high_contrast_theme();
+
+        for (fg, bg) in [
+            (s.text_col(), s.panel_bg()),
+            (s.text_col(), s.window_bg()),
+            (s.text_col(), s.titlebar_color()),
+            (s.text_col(), s.btn_default()),
+            (s.text_col(), s.btn_hover()),
+            (s.btn_press_text(), s.btn_press()),
+            (s.red(), s.panel_bg()),
+        ] {
+            assert!(fg.contrast_ratio(bg) >= 4.5, "{fg} on {bg}");
+        }
+
+        for col in [s.btn_default(), s.btn_hover(), s.btn_press(), s.panel_outline().col] {
+            assert!(col.contrast_ratio(s.panel_bg()) >= 3.0, "{col}");
+        }
+    }
+
+    #[test]
+    fn test_style_rescale() {
+        let orig = crate::ui_context::dark_theme();
+        let mut style = orig.clone();
+        style.push_var(StyleVar::SpacingH(3.0));
+        style.rescale(2.0);
+
+        assert_eq!(style.ui_scale(), 2.0);
+        assert_eq!(style.spacing_h(), 6.0);
+        assert_eq!(style.line_height(), orig.line_height() * 2.0);
+        assert_eq!(style.panel_outline().width, orig.panel_outline().width * 2.0);
+        assert_eq!(style.btn_roundness(), orig.btn_roundness());
+        assert_eq!(style.text_col(), orig.text_col());
+
+        // popped vars come back at the new scale
+        style.pop_var();
+        assert_eq!(style.spacing_h(), orig.spacing_h() * 2.0);
+
+        style.rescale(0.5);
+        assert_eq!(style, orig);
+    }
+
+    #[test]
+    fn test_monitor_ui_scale() {
+        use crate::{
+            platform::MonitorInfo,
+            settings::{MonitorUiScale, Settings},
+        };
+
+        let monitor = |name: &str, x: f32| MonitorInfo {
+            name: Some(name.to_string()),
+            position: Vec2::new(x, 0.0),
+            size: UVec2::new(1920, 1080),
+            scale_factor: 1.0,
+            refresh_rate_hz: None,
+        };
+        let (left, right) = (monitor("left", 0.0), monitor("right", 1920.0));
+        let mut settings = Settings::in_memory();
+        let mut scales = MonitorUiScale::default();
+
+        // monitors without a stored scale use the default
+        assert_eq!(scales.moved_to(&left, &settings, 1.25), Some(1.25));
+        assert_eq!(scales.moved_to(&left, &settings, 1.25), None);
+        scales.store(&mut settings, 1.5);
+
+        assert_eq!(scales.moved_to(&right, &settings, 1.25), Some(1.25));
+        scales.store(&mut settings, 0.75);
+        assert_eq!(scales.moved_to(&left, &settings, 1.25), Some(1.5));
+        assert_eq!(scales.moved_to(&right, &settings, 1.25), Some(0.75));
+
+        // the stored scales survive a restart
+        let settings = Settings::parse(&settings.to_string());
+        let mut scales = MonitorUiScale::default();
+        assert_eq!(scales.moved_to(&left, &settings, 1.0), Some(1.5));
+        assert_eq!(scales.moved_to(&right, &settings, 1.0), Some(0.75));
+    }
+
+    #[test]
+    fn test_style_push_scaled() {
+        let orig = crate::ui_context::dark_theme();
+        let mut style = orig.clone();
+        let n = style.push_scaled(1.5);
+
+        assert_eq!(style.var_stack.len(), n as usize);
+        assert_eq!(style.ui_scale(), orig.ui_scale() * 1.5);
+        assert_eq!(style.text_size(), orig.text_size() * 1.5);
+        assert_eq!(style.spacing_v(), orig.spacing_v() * 1.5);
+        assert_eq!(style.btn_hover(), orig.btn_hover());
+
+        for _ in 0..n {
+            style.pop_var();
+        }
+        assert_eq!(style, orig);
+    }
+
+    #[test]
+    fn test_stroke_pixel_width() {
+        // widths are in the physical pixels of the layout and not scaled
+        let stroke = Stroke::new(RGBA::RED, 1.5);
+        assert_eq!(stroke.pixel_width(), 1.5);
+        let hairline = Stroke {
+            width: 3.0,
+            ..Stroke::hairline(RGBA::RED)
+        };
+        assert_eq!(hairline.pixel_width(), 1.0);
+
+        let mut data = DrawListData::new();
+        data.add_polyline(&[Vec2::ZERO, Vec2::new(10.0, 0.0)], hairline, false);
+        let (min_y, max_y) = data
+            .vtx_buffer
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v.pos.y), hi.max(v.pos.y)));
+        assert!((max_y - min_y - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_polyline_multicolor() {
+        let points = [Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)];
+        let cols = [RGBA::RED, RGBA::GREEN, RGBA::BLUE];
+        let vtx_cols = |cols: &[RGBA], closed: bool| {
+            let mut data = DrawListData::new();
+            data.add_polyline_multicolor(&points, cols, 2.0, closed);
+            data.vtx_buffer.iter().map(|v| v.col).collect::<Vec<_>>()
+        };
+
+        // 4 vertices per segment, colored like the two points of the segment
+        let (r, g, b) = (RGBA::RED, RGBA::GREEN, RGBA::BLUE);
+        assert_eq!(vtx_cols(&cols, false), [r, r, g, g, g, g, b, b]);
+        assert_eq!(vtx_cols(&cols, true), [r, r, g, g, g, g, b, b, b, b, r, r]);
+
+        // one color per point is required, anything else draws nothing
+        assert!(vtx_cols(&cols[..2], false).is_empty());
+        assert!(vtx_cols(&[r, g, b, r], true).is_empty());
+    }
+
+    #[test]
+    fn test_polyline_var_width() {
+        let points = [Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)];
+        let draw = |widths: &[f32], closed: bool| {
+            let mut data = DrawListData::new();
+            data.add_polyline_var_width(&points, RGBA::RED, widths, closed);
+            data.vtx_buffer
+        };
+        // width of every segment, the distance of the two vertices at its start
+        let segment_widths = |vtx: &[Vertex]| {
+            let width = |q: &[Vertex]| (q[0].pos.distance(q[1].pos) * 1e3).round() / 1e3;
+            vtx.chunks(4).map(width).collect::<Vec<_>>()
+        };
+
+        let vtx = draw(&[2.0, 6.0], false);
+        assert_eq!(segment_widths(&vtx), [2.0, 6.0]);
+        assert!(vtx.iter().all(|v| v.col == RGBA::RED));
+        assert_eq!(segment_widths(&draw(&[1.0, 2.0, 3.0], true)), [1.0, 2.0, 3.0]);
+
+        // an open line needs one width less than a closed one
+        assert!(draw(&[1.0, 2.0, 3.0], false).is_empty());
+        assert!(draw(&[1.0, 2.0], true).is_empty());
+        assert!(draw(&[1.0, f32::NAN], false).is_empty());
+        assert!(draw(&[0.0, 0.0], false).is_empty());
+    }
+
+    #[test]
+    fn test_outline_pixel_width() {
+        let outline = Outline::center(RGBA::RED, 3.0);
+        assert_eq!(outline.to_pixels().width, 3.0);
+        let hairline = Outline {
+            width: 4.0,
+            ..Outline::hairline(RGBA::RED).with_place(OutlinePlacement::Center)
+        };
+        assert_eq!(hairline.to_pixels().width, 1.0);
+        assert!(Stroke::from(hairline).hairline);
+
+        // how far the geometry drawn for a rect with `outline` reaches out of the rect
+        let overhang = |outline: Outline, corners: f32| {
+            let mut data = DrawListData::new();
+            let (min, max) = (Vec2::splat(10.0), Vec2::splat(50.0));
+            let (uv, white) = (Vec2::ONE, TextureId::WHITE);
+            let corners = CornerRadii::all(corners);
+            data.add_rect_rounded(min, max, uv, uv, white, RGBA::WHITE, outline, corners);
+            data.resolve_pending_shapes();
+            let pts: Vec<_> = data.vtx_buffer.iter().map(|v| v.pos).collect();
+            let bounds = Rect::from_points(&pts);
+            (min - bounds.min).max_element().max((bounds.max - max).max_element())
+        };
+        for corners in [0.0, 4.0] {
+            assert!((overhang(outline, corners) - 1.5).abs() < 0.1);
+            assert!((overhang(hairline, corners) - 0.5).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_item_painter_clip() {
+        let list = DrawList::new();
+        let outer = Rect::from_min_max(Vec2::ZERO, Vec2::splat(100.0));
+        list.push_clip_rect(outer);
+
+        let rect = Rect::from_min_max(Vec2::splat(50.0), Vec2::splat(150.0));
+        {
+            let painter = ItemPainter::new(&list, rect);
+            assert_eq!(painter.to_screen(Vec2::splat(10.0)), Vec2::splat(60.0));
+            assert_eq!(
+                painter.current_clip_rect(),
+                Rect::from_min_max(Vec2::splat(50.0), Vec2::splat(100.0))
+            );
+        }
+        assert_eq!(list.current_clip_rect(), outer);
+    }
+
+    #[test]
+    fn test_panel_anchor_pos() {
+        let screen = Rect::from_min_max(Vec2::new(0.0, 30.0), Vec2::new(800.0, 600.0));
+        let size = Vec2::new(200.0, 100.0);
+
+        let a = PanelAnchor::screen(Anchor::TopRight, Vec2::new(-10.0, 10.0));
+        assert_eq!(a.panel_pos(screen, size), Vec2::new(590.0, 40.0));
+
+        let a = PanelAnchor::screen(Anchor::Center, Vec2::ZERO);
+        assert_eq!(a.panel_pos(screen, size), Vec2::new(300.0, 265.0));
+
+        // follows the target when it is resized
+        let screen = Rect::from_min_max(Vec2::new(0.0, 30.0), Vec2::new(1000.0, 700.0));
+        let a = PanelAnchor::screen(Anchor::BottomRight, Vec2::ZERO);
+        assert_eq!(a.panel_pos(screen, size), Vec2::new(800.0, 600.0));
+
+        let tools = Rect::from_min_size(Vec2::new(50.0, 50.0), Vec2::new(100.0, 300.0));
+        let a = PanelAnchor::panel("Tools", Anchor::TopLeft, Anchor::TopRight, Vec2::new(5.0, 0.0));
+        assert_eq!(a.target, AnchorTarget::Panel(Id::from_str("Tools")));
+        assert_eq!(a.panel_pos(tools, size), Vec2::new(155.0, 50.0));
+    }
+
+    #[test]
+    fn test_rect_gizmo_drag() {
+        let g = RectGizmo::new().with_min_size(Vec2::splat(10.0));
+        let start = Rect::from_min_max(Vec2::new(100.0, 100.0), Vec2::new(300.0, 200.0));
+
+        let moved = g.drag(start, None, Vec2::new(15.0, -5.0), false, false);
+        assert_eq!(moved, start.translate(Vec2::new(15.0, -5.0)));
+
+        // snapped to the 10 unit grid
+        let moved = g.drag(start, None, Vec2::new(14.0, -6.0), false, true);
+        assert_eq!(moved.min, Vec2::new(110.0, 90.0));
+
+        let r = g.drag(start, Some(Dir::SE), Vec2::new(20.0, 10.0), false, false);
+        assert_eq!(r, Rect::from_min_max(start.min, Vec2::new(320.0, 210.0)));
+
+        // never smaller than min_size
+        let r = g.drag(start, Some(Dir::W), Vec2::new(500.0, 0.0), false, false);
+        assert_eq!(r.width(), 10.0);
+        assert_eq!(r.max.x, 300.0);
+
+        // the corner keeps the 2:1 aspect ratio and the opposite corner in place
+        let r = g.drag(start, Some(Dir::NW), Vec2::new(-100.0, 0.0), true, false);
+        assert_eq!(r.max, start.max);
+        assert_eq!(r.size(), Vec2::new(300.0, 150.0));
+
+        // edges grow the other axis around its center
+        let r = g.drag(start, Some(Dir::S), Vec2::new(0.0, 50.0), true, false);
+        assert_eq!(r, Rect::from_min_max(Vec2::new(50.0, 100.0), Vec2::new(350.0, 250.0)));
+    }
+
+    #[test]
+    fn test_transform_gizmo_drag() {
+        use std::f32::consts::FRAC_PI_2;
+
+        let g = TransformGizmo::new();
+        let start = Transform2D::new(Vec2::new(100.0, 100.0));
+        let approx = |a: Vec2, b: Vec2| (a - b).length() < 1e-3;
+
+        let t = g.drag(start, GizmoPart::Move, Vec2::new(100.0, 100.0), Vec2::new(113.0, 96.0), true);
+        assert_eq!(t.pos, Vec2::new(110.0, 100.0));
+
+        // only moves along the rotated x axis
+        let rotated = start.rotation(FRAC_PI_2);
+        let t = g.drag(rotated, GizmoPart::MoveX, Vec2::ZERO, Vec2::new(30.0, 20.0), false);
+        assert!(approx(t.pos, Vec2::new(100.0, 120.0)));
+
+        let t = g.drag(start, GizmoPart::Rotate, Vec2::new(150.0, 100.0), Vec2::new(100.0, 150.0), false);
+        assert!((t.rotation - FRAC_PI_2).abs() < 1e-4);
+        let t = g.drag(start, GizmoPart::Rotate, Vec2::new(150.0, 100.0), Vec2::new(150.0, 110.0), true);
+        assert_eq!(t.rotation, 15f32.to_radians());
+
+        let t = g.drag(start, GizmoPart::ScaleX, Vec2::new(130.0, 100.0), Vec2::new(160.0, 140.0), false);
+        assert_eq!(t.scale, Vec2::new(2.0, 1.0));
+        let t = g.drag(start, GizmoPart::Scale, Vec2::new(110.0, 100.0), Vec2::new(100.0, 115.0), false);
+        assert_eq!(t.scale, Vec2::splat(1.5));
+
+        assert!(approx(rotated.transform_point(Vec2::new(10.0, 0.0)), Vec2::new(100.0, 110.0)));
+    }
+
+    #[test]
+    fn test_stream_buffer() {
+        let mut buf = StreamBuffer::new(4);
+        assert_eq!(buf.last(), None);
+        buf.extend([1.0, 2.0, 3.0]);
+        assert_eq!(buf.iter().collect::<Vec<_>>(), [1.0, 2.0, 3.0]);
+
+        // drops the oldest samples once full
+        buf.extend([4.0, 5.0, 6.0]);
+        assert_eq!(buf.len(), 4);
+        assert_eq!(buf.iter().collect::<Vec<_>>(), [3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(buf.get(0), Some(3.0));
+        assert_eq!(buf.last(), Some(6.0));
+        assert_eq!(buf.get(4), None);
+
+        let mut out = Vec::new();
+        buf.min_max_columns(4, 2, &mut out);
+        assert_eq!(out, [(3.0, 4.0), (5.0, 6.0)]);
+        buf.min_max_columns(3, 1, &mut out);
+        assert_eq!(out, [(4.0, 6.0)]);
+
+        // peaks survive decimation
+        let mut buf = StreamBuffer::new(10_000);
+        buf.extend((0..10_000).map(|i| if i == 5_123 { 100.0 } else { 0.0 }));
+        buf.min_max_columns(10_000, 100, &mut out);
+        assert_eq!(out.len(), 100);
+        assert_eq!(out[51], (0.0, 100.0));
+        assert_eq!(out.iter().filter(|(_, max)| *max > 0.0).count(), 1);
+    }
+
+    #[test]
+    fn test_colormap_sample() {
+        for cmap in Colormap::ALL {
+            let lut = cmap.lut();
+            assert_eq!(cmap.sample(0.0), lut[0]);
+            assert_eq!(cmap.sample(1.0), lut[lut.len() - 1]);
+            // clamped outside of 0..1
+            assert_eq!(cmap.sample(-3.0), lut[0]);
+            assert_eq!(cmap.sample(2.0), lut[lut.len() - 1]);
+        }
+        let viridis = Colormap::Viridis.lut();
+        assert_eq!(Colormap::Viridis.sample(0.0625), viridis[0].lerp(viridis[1], 0.5));
+        assert_eq!(Colormap::Grayscale.sample(0.5), RGBA::rgb(128, 128, 128));
+
+        let wgsl = colormap_wgsl();
+        assert!(wgsl.contains("array<vec3<f32>, 27>"));
+        assert!(wgsl.contains("fn colormap(cmap: u32, v: f32) -> vec3<f32>"));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_month_grid() {
+        use chrono::{Datelike, NaiveDate, Weekday};
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // october 2026 starts on a thursday
+        let grid = crate::ui_date::month_grid(date(2026, 10, 16));
+        assert_eq!(grid[0], date(2026, 9, 28));
+        assert_eq!(grid[3], date(2026, 10, 1));
+        assert_eq!(grid[41], date(2026, 11, 8));
+        assert!(grid.iter().step_by(7).all(|d| d.weekday() == Weekday::Mon));
+
+        let range = DateRange::new(date(2026, 3, 5), date(2026, 2, 27));
+        assert_eq!(range.start, date(2026, 2, 27));
+        assert_eq!(range.days(), 7);
+        assert!(range.contains(date(2026, 3, 1)));
+        assert!(!range.contains(date(2026, 3, 6)));
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("", "anything"), Some(0));
+        assert!(fuzzy_match("fb", "foo_bar").is_some());
+        assert!(fuzzy_match("FB", "foo_bar").is_some());
+        assert_eq!(fuzzy_match("bf", "foo_bar"), None);
+        assert_eq!(fuzzy_match("fooo", "foo"), None);
+
+        // word starts and consecutive characters rank higher
+        assert!(fuzzy_match("of", "Open File") > fuzzy_match("of", "roof"));
+        assert!(fuzzy_match("dp", "DatePicker") > fuzzy_match("dp", "dropdown"));
+        assert!(fuzzy_match("plot", "plot_heatmap") > fuzzy_match("plot", "p_l_o_t"));
+
+        let options = ["roof", "Open File", "other", "office"];
+        assert_eq!(crate::ui_select::filter_options("of", &options), vec![3, 1, 0]);
+        assert_eq!(crate::ui_select::filter_options("", &options), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_shortcut_display() {
+        use crate::mouse::{KeyCode, Shortcut};
+
+        let ctrl_shift_p = Shortcut::new(Modifiers::CONTROL | Modifiers::SHIFT, KeyCode::KeyP);
+        assert_eq!(ctrl_shift_p.to_string(), "Ctrl+Shift+P");
+        assert_eq!(Shortcut::new(Modifiers::ALT, KeyCode::Digit1).to_string(), "Alt+1");
+        assert_eq!(Shortcut::key(KeyCode::F5).to_string(), "F5");
+
+        let mut palette = CommandPalette::new();
+        palette.add("open", 0).add_with_shortcut("save", ctrl_shift_p, 1);
+        assert_eq!(palette.toggle.to_string(), "Ctrl+P");
+        assert_eq!(palette.commands[1].shortcut, Some(ctrl_shift_p));
+        assert!(!palette.is_open());
+        palette.open();
+        assert!(palette.is_open());
+    }
+
+    #[test]
+    fn test_text_wrap() {
+        let mut fonts = FontTable::new();
+        let inter = include_bytes!("../res/Inter-VariableFont_opsz,wght.ttf");
+        fonts.load_font(DEFAULT_FONT, inter.to_vec());
+        let lines = |item: &TextItem, fonts: &mut FontTable| -> Vec<(String, f32)> {
+            let buffer = item.shape(fonts);
+            let lines = buffer.layout_runs().map(|run| (run_text(&run).to_string(), run.line_w));
+            lines.collect()
+        };
+        let text = "the quick brown fox jumps over the lazy dog";
+        let item = TextItem::new(text.to_string(), 16.0, 1.0, DEFAULT_FONT);
+        assert_eq!(lines(&item, &mut fonts).len(), 1);
+        assert_eq!(lines(&item, &mut fonts)[0].0, text);
+
+        // broken at word boundaries, every line fits
+        let wrapped = item.clone().with_wrap(TextWrap::Wrap, 100.0);
+        let wrapped_lines = lines(&wrapped, &mut fonts);
+        assert!(wrapped_lines.len() > 2);
+        assert!(wrapped_lines.iter().all(|(_, w)| *w <= 100.0));
+        let words: Vec<_> = wrapped_lines.iter().flat_map(|(l, _)| l.split_whitespace()).collect();
+        assert_eq!(words, text.split_whitespace().collect::<Vec<_>>());
+
+        let truncated = lines(&item.clone().with_wrap(TextWrap::Truncate, 100.0), &mut fonts);
+        assert_eq!(truncated.len(), 1);
+        let (line, w) = &truncated[0];
+        assert!(*w <= 100.0);
+        assert!(text.starts_with(line.strip_suffix(ELLIPSIS).unwrap()));
+
+        // the last kept line ends with an ellipsis and still fits
+        let limited = lines(&wrapped.with_max_lines(Some(2)), &mut fonts);
+        assert_eq!(limited.len(), 2);
+        assert_eq!(limited[0], wrapped_lines[0]);
+        assert!(limited[1].0.ends_with(ELLIPSIS));
+        assert!(limited[1].1 <= 100.0);
+
+        let paragraphs = TextItem::new("one\ntwo\nthree".to_string(), 16.0, 1.0, DEFAULT_FONT);
+        let texts = |item: &TextItem, fonts: &mut FontTable| -> Vec<String> {
+            lines(item, fonts).into_iter().map(|(line, _)| line).collect()
+        };
+        let limited = paragraphs.clone().with_max_lines(Some(2));
+        assert_eq!(texts(&limited, &mut fonts), ["one", "two\u{2026}"]);
+        let fits = paragraphs.with_max_lines(Some(3));
+        assert_eq!(texts(&fits, &mut fonts), ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn test_text_input_changed() {
+        let item = TextItem::new("abc".to_string(), 16.0, 1.0, "Inter");
+        let mut input = TextInputState::new(Id::NULL, FontTable::new(), item, false);
+        assert!(!input.changed);
+
+        // the cursor starts at the beginning, nothing to delete
+        input.backspace(Modifiers::NONE);
+        assert!(!input.changed);
+        input.set_text("abc");
+        assert!(!input.changed);
+
+        input.paste("d");
+        assert!(input.changed);
+        assert_eq!(input.copy_all(), "abcd");
+
+        input.changed = false;
+        input.select_all();
+        input.delete_selection();
+        assert!(input.changed);
+        assert_eq!(input.copy_all(), "");
+    }
+
+    #[test]
+    fn test_selection_click() {
+        let items: Vec<Id> = (0..6).map(|i| Id::from_hash(&i)).collect();
+        let selected = |sel: &Selection| -> Vec<usize> {
+            (0..items.len()).filter(|&i| sel.is_selected(items[i])).collect()
+        };
+
+        let mut sel = Selection::new();
+        sel.click(&items, 1, Modifiers::NONE);
+        assert_eq!(selected(&sel), vec![1]);
+        sel.click(&items, 4, Modifiers::SHIFT);
+        assert_eq!(selected(&sel), vec![1, 2, 3, 4]);
+        // the range is adjusted from the same anchor
+        sel.click(&items, 0, Modifiers::SHIFT);
+        assert_eq!(selected(&sel), vec![0, 1]);
+
+        sel.click(&items, 3, Modifiers::CONTROL);
+        assert_eq!(selected(&sel), vec![0, 1, 3]);
+        sel.click(&items, 0, Modifiers::CONTROL);
+        assert_eq!(selected(&sel), vec![1, 3]);
+        // ctrl + shift adds the range from the last ctrl clicked item
+        sel.click(&items, 5, Modifiers::CONTROL | Modifiers::SHIFT);
+        assert_eq!(selected(&sel), vec![0, 1, 2, 3, 4, 5]);
+
+        sel.click(&items, 2, Modifiers::NONE);
+        assert_eq!(selected(&sel), vec![2]);
+    }
+
+    #[test]
+    fn test_highlight_line() {
+        let kinds = |lang, line: &str, in_comment: &mut bool| {
+            highlight_line(lang, line, in_comment)
+                .into_iter()
+                .map(|(r, k)| (line.chars().skip(r.start).take(r.len()).collect::<String>(), k))
+                .filter(|(s, _)| !s.trim().is_empty())
+                .collect::<Vec<_>>()
+        };
+        let mut c = false;
+
+        assert_eq!(
+            kinds(CodeLanguage::Rust, "let x: u32 = foo(1.5); // hi", &mut c),
+            vec![
+                ("let".into(), CodeToken::Keyword),
+                (" x: ".into(), CodeToken::Text),
+                ("u32".into(), CodeToken::Type),
+                (" = ".into(), CodeToken::Text),
+                ("foo".into(), CodeToken::Function),
+                ("(".into(), CodeToken::Text),
+                ("1.5".into(), CodeToken::Number),
+                ("); ".into(), CodeToken::Text),
+                ("// hi".into(), CodeToken::Comment),
+            ]
+        );
+        assert_eq!(
+            kinds(CodeLanguage::Rust, "#[derive(Debug)] 'a' \"s\\\"\" 0..10", &mut c),
+            vec![
+                ("#[derive(Debug)]".into(), CodeToken::Attribute),
+                ("'a'".into(), CodeToken::String),
+                ("\"s\\\"\"".into(), CodeToken::String),
+                ("0".into(), CodeToken::Number),
+                ("..".into(), CodeToken::Text),
+                ("10".into(), CodeToken::Number),
+            ]
+        );
+
+        // block comments continue on the next line
+        assert_eq!(kinds(CodeLanguage::Wgsl, "/* open", &mut c)[0].1, CodeToken::Comment);
+        assert!(c);
+        assert_eq!(
+            kinds(CodeLanguage::Wgsl, "close */ @vertex", &mut c),
+            vec![("close */".into(), CodeToken::Comment), ("@vertex".into(), CodeToken::Attribute)]
+        );
+        assert!(!c);
+
+        assert_eq!(CodeLanguage::from_name("RS"), CodeLanguage::Rust);
+        let plain = highlight_line(CodeLanguage::Plain, "fn x", &mut c);
+        assert_eq!(plain, vec![(0..4, CodeToken::Text)]);
+    }
+
+    #[test]
+    fn test_knob_angle() {
+        use crate::ui_items::knob_angle_to_t;
+        use std::f32::consts::PI;
+
+        // bottom left is the minimum, bottom right the maximum
+        assert!(knob_angle_to_t(PI * 1.25).abs() < 1e-5);
+        assert!((knob_angle_to_t(PI * 0.5) - 0.5).abs() < 1e-5);
+        assert!((knob_angle_to_t(-PI * 0.25) - 1.0).abs() < 1e-5);
+        assert!((knob_angle_to_t(PI) - 1.0 / 6.0).abs() < 1e-5);
+        // the gap at the bottom snaps to the closer end
+        assert_eq!(knob_angle_to_t(-PI * 0.4), 1.0);
+        assert_eq!(knob_angle_to_t(-PI * 0.6), 0.0);
+    }
+
+    #[test]
+    fn test_edge_panel_split_area() {
+        let area = Rect::from_min_max(Vec2::new(0.0, 30.0), Vec2::new(800.0, 600.0));
+
+        let (left, rest) = EdgePanel::split_area(Dir::W, area, 200.0);
+        assert_eq!(left, Rect::from_min_max(Vec2::new(0.0, 30.0), Vec2::new(200.0, 600.0)));
+        assert_eq!(rest, Rect::from_min_max(Vec2::new(200.0, 30.0), Vec2::new(800.0, 600.0)));
+
+        let (bottom, rest) = EdgePanel::split_area(Dir::S, rest, 100.0);
+        assert_eq!(bottom, Rect::from_min_max(Vec2::new(200.0, 500.0), Vec2::new(800.0, 600.0)));
+        assert_eq!(rest.max.y, 500.0);
+
+        // never takes more than the available area
+        let (right, rest) = EdgePanel::split_area(Dir::E, rest, 1000.0);
+        assert_eq!(right.min.x, 200.0);
+        assert_eq!(rest.width(), 0.0);
+    }
+
+    #[test]
+    fn test_painter_write_cursor() {
+        let mut cursor = WriteCursor::new();
+        assert_eq!(cursor.alloc(600), (0, false));
+        // a second call in the same frame does not overwrite the first one
+        assert_eq!(cursor.alloc(300), (600, false));
+        assert_eq!(cursor.alloc(6), (900, false));
+        assert_eq!(cursor.next, 908);
+
+        // the new buffer fits everything written this frame
+        assert_eq!(cursor.alloc(200), (0, true));
+        assert_eq!(cursor.capacity, 2048);
+        assert_eq!(cursor.next, 200);
+
+        // the next frame fits into the grown buffer
+        cursor.next = 0;
+        assert_eq!(cursor.alloc(1100), (0, false));
+        assert_eq!(cursor.alloc(900), (1100, false));
+
+        // outputs of a new frame reset the buffers unless the host calls begin_frame
+        assert!(Painter::is_new_frame(Some(3), 4));
+        assert!(!Painter::is_new_frame(Some(4), 4));
+        assert!(!Painter::is_new_frame(None, 5));
+    }
+
+    #[test]
+    fn test_edge_panel_clamp_size() {
+        let panel = EdgePanel::new("side", Dir::W, 200.0).with_size_range(100.0, 300.0);
+        assert_eq!(panel.clamp_size(50.0), 100.0);
+        assert_eq!(panel.clamp_size(500.0), 300.0);
+
+        // swapped bounds don't panic
+        let panel = panel.with_size_range(300.0, 100.0);
+        assert_eq!(panel.clamp_size(50.0), 100.0);
+        assert_eq!(panel.clamp_size(200.0), 200.0);
+        assert_eq!(panel.clamp_size(500.0), 300.0);
+
+        let panel = panel.with_size_range(f32::NAN, 100.0);
+        assert_eq!(panel.clamp_size(500.0), 100.0);
+    }
+
+    #[test]
+    fn test_pipeline_key() {
+        #[derive(Hash)]
+        enum MyPipeline {
+            Outline,
+        }
+        assert_eq!(gpu::PipelineKey::from("outline"), gpu::PipelineKey::of("outline"));
+        assert_ne!(gpu::PipelineKey::from("outline"), gpu::PipelineKey::from("fill"));
+        assert_eq!(
+            gpu::PipelineKey::of(&MyPipeline::Outline),
+            gpu::PipelineKey::of(&MyPipeline::Outline)
+        );
+    }
+
+    #[test]
+    fn test_shader_validation() {
+        let desc = Vertex::desc();
+        let config = gpu::ShaderBuildConfig::new([(&desc, "Vertex")]);
+        let src = UiShader.shader_source(&config, None);
+        let entries = ui_bind_group_layout_entries();
+        let entry_points = ["vs_main", "fs_main"];
+        let (attributes, members) = (&desc.attributes, &desc.members);
+        assert_eq!(
+            gpu::validate_shader(&src, entry_points, attributes, members, 1, &[&entries]),
+            Ok(())
+        );
+        assert!(matches!(
+            gpu::validate_shader(&src, entry_points, attributes, members, 0, &[]).unwrap_err()[..],
+            [gpu::ShaderMismatch::MissingBindGroup { group: 0, .. }, ..]
+        ));
+
+        let src = "
+            @vertex
+            fn vs_main(@location(0) pos: vec2<f32>, @location(1) id: f32)
+                -> @builtin(position) vec4<f32> {
+                return vec4(pos * id, 0.0, 1.0);
+            }
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4(1.0);
+            }
+        ";
+        let desc = gpu::PickVertex::desc();
+        let errors = gpu::PipelineBuilder::new(src, wgpu::TextureFormat::R32Uint)
+            .vertex_buffers(&[&desc])
+            .validate()
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "'id' at @location(1) is f32 in the shader, but the vertex attribute is Uint32"
+        );
+    }
+
+    #[test]
+    fn test_shader_struct_layout() {
+        use crate::gpu::WgslType;
+        use glam::Vec3;
+
+        #[macros::shader_struct(uniform)]
+        struct Light {
+            pos: Vec3,
+            color: RGBA,
+            intensity: f32,
+        }
+
+        #[macros::shader_struct]
+        struct Lights {
+            view: Mat4,
+            count: u32,
+            lights: [Light; 2],
+        }
+
+        let light = Light {
+            pos: Vec3::ONE,
+            color: RGBA::WHITE,
+            intensity: 2.0,
+            ..bytemuck::Zeroable::zeroed()
+        };
+        // vec3 is followed by 4 bytes of padding, the struct is padded to 16 bytes
+        assert_eq!(std::mem::offset_of!(Light, color), 16);
+        assert_eq!(std::mem::size_of::<Light>(), 48);
+        assert_eq!(bytemuck::bytes_of(&light)[32..36], 2.0f32.to_ne_bytes());
+        assert_eq!(std::mem::offset_of!(Lights, lights), 80);
+
+        let src = format!(
+            "{}@group(0) @binding(0) var<storage> lights: Lights;
+            @group(0) @binding(1) var<uniform> light: Light;",
+            Lights::wgsl_definitions()
+        );
+        assert_eq!(src.matches("struct Light {").count(), 1);
+        assert!(src.contains("    lights: array<Light, 2>,\n"));
+        assert!(naga::front::wgsl::parse_str(&src).is_ok());
+    }
+
+    #[test]
+    fn test_derive_as_vertex_format() {
+        use crate::gpu::{AsVertexFormat, Vertex as _};
+
+        #[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+        #[derive(macros::AsVertexFormat)]
+        #[repr(transparent)]
+        struct Uv(Vec2);
+
+        #[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+        #[derive(macros::AsVertexFormat)]
+        #[vertex_format(Unorm8x4)]
+        #[repr(C)]
+        struct PackedColor {
+            r: u8,
+            g: u8,
+            b: u8,
+            a: u8,
+        }
+
+        #[macros::vertex]
+        struct MyVertex {
+            uv: Uv,
+            col: PackedColor,
+        }
+
+        assert_eq!(Uv::WGSL, Some("vec2<f32>"));
+        let formats = MyVertex::VERTEX_ATTRIBUTES.iter().map(|a| a.format).collect::<Vec<_>>();
+        assert_eq!(formats, [wgpu::VertexFormat::Float32x2, wgpu::VertexFormat::Unorm8x4]);
+
+        let desc = MyVertex::desc();
+        let src = "@rust struct MyVertex { uv: vec2<f32>, col: vec4<f32> }";
+        let src = gpu::pre_process_shader_code(src, &[(&desc, "MyVertex")]).unwrap();
+        assert!(src.contains("@location(1) col: vec4<f32>"));
+    }
+
+    #[test]
+    fn test_compact_vertex() {
+        assert_eq!(std::mem::size_of::<CompactVertex>(), 20);
+        assert_eq!(gpu::Unorm8x4::from(RGBA::rgba_f(1.0, 0.0, 0.5, 2.0)).0, [255, 0, 128, 255]);
+        assert_eq!(gpu::Snorm16x2::from_f32([-2.0, 1.0]).to_f32(), [-1.0, 1.0]);
+
+        let mut v = Vertex::new(Vec2::new(3.0, 4.0), RGBA::WHITE, Vec2::new(0.5, 1.0), 7);
+        v.colormap = 2;
+        let c = CompactVertex::from(&v);
+        assert_eq!(c.tex, 7 | 2 << 16);
+        assert_eq!(c.uv.0, [32768, 65535]);
+
+        let desc = CompactVertex::desc();
+        let config = gpu::ShaderBuildConfig::new([(&desc, "Vertex")]);
+        let src = UiShader.shader_source(&config, None);
+        let entries = ui_bind_group_layout_entries();
+        let (attributes, members) = (&desc.attributes, &desc.members);
+        let entry_points = ["vs_main", "fs_main"];
+        assert_eq!(
+            gpu::validate_shader(&src, entry_points, attributes, members, 1, &[&entries]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_draw_composer() {
+        let quad = |x: f32, tex: u32| {
+            let vtx: Vec<Vertex> = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]
+                .iter()
+                .map(|&(px, py)| Vertex::new(Vec2::new(x + px, py), RGBA::WHITE, Vec2::ZERO, tex))
+                .collect();
+            let mut list = DrawCallList::new(1000, 1000);
+            list.set_clip_rect(Rect::from_min_size(Vec2::ZERO, Vec2::splat(100.0)));
+            list.push(&vtx, &[0, 1, 2]);
+            list
+        };
+        let (ui, hud) = (quad(0.0, 0), quad(5.0, 0));
+
+        let transform = LayerTransform::new()
+            .with_offset(Vec2::new(10.0, 0.0))
+            .with_scale(2.0)
+            .with_clip(Rect::from_min_size(Vec2::ZERO, Vec2::splat(50.0)));
+        let mut list = DrawCallList::new(1000, 1000);
+        list.append(&ui, transform, 3, 0);
+        let (vtx, idx, clip) = list.get_draw_call_data(0).unwrap();
+        assert_eq!(vtx[1].pos, Vec2::new(12.0, 0.0));
+        assert_eq!(idx, &[0, 1, 2]);
+        assert_eq!(clip, Rect::from_min_size(Vec2::new(10.0, 0.0), Vec2::new(40.0, 50.0)));
+
+        let mut composer = DrawComposer::new();
+        let output = |calls| DrawOutput {
+            calls,
+            textures: &[],
+            effects: &[],
+            time: 0.0,
+            frame: 0,
+        };
+        composer.push_output(1, &output(&hud), LayerTransform::new());
+        composer.push_output(0, &output(&ui), LayerTransform::new());
+        let out = composer.output();
+        assert_eq!(out.calls.calls.len(), 2);
+        let (vtx, idx, _) = out.calls.get_draw_call_data(0).unwrap();
+        assert_eq!(vtx[0].pos, Vec2::ZERO);
+        assert_eq!(idx, &[0, 1, 2]);
+        let (vtx, _, _) = out.calls.get_draw_call_data(1).unwrap();
+        assert_eq!(vtx[0].pos, Vec2::new(5.0, 0.0));
+    }
+
+    #[test]
+    fn test_deferred_ui() {
+        fn assert_send<T: Send>() {}
+        assert_send::<DeferredUi>();
+        assert_send::<DeferredSender>();
+
+        let mut queue = DeferredQueue::new();
+        let sender = queue.sender();
+        let worker = std::thread::spawn(move || {
+            for i in 0..3 {
+                let mut buffer = StreamBuffer::new(4);
+                buffer.extend([i as f32, 1.0]);
+                let mut ui = DeferredUi::new("stats");
+                ui.text(format!("run {i}"));
+                ui.stream_plot("samples", buffer, StreamPlot::new());
+                assert!(sender.send(ui));
+            }
         });
+        worker.join().unwrap();
+
+        assert_eq!(queue.receive(), 3);
+        assert_eq!(queue.receive(), 0);
+        let ui = &queue.latest["stats"];
+        assert_eq!(ui.items.len(), 2);
+        assert_eq!(ui.items[0], DeferredItem::Text("run 2".into()));
+    }
+
+    #[test]
+    fn test_keyed_ids() {
+        let p = Panel::new("panel");
+        p.push_id(p.id);
+        let header_ids = |uuids: &[u64]| -> Vec<(u64, Id)> {
+            let mut ids: Vec<(u64, Id)> = uuids
+                .iter()
+                .map(|&uuid| {
+                    p.push_id(Id::from_key(uuid));
+                    let id = p.gen_local_id("header");
+                    p.pop_id();
+                    (uuid, id)
+                })
+                .collect();
+            ids.sort_by_key(|(uuid, _)| *uuid);
+            ids
+        };
+
+        let ids = header_ids(&[1, 2, 3]);
+        assert_eq!(header_ids(&[3, 1, 2]), ids);
+        assert_ne!(ids[0].1, ids[1].1);
+    }
+
+    #[test]
+    fn test_occlusion() {
+        let (a, b, panel) = (Id(1), Id(2), Id(3));
+        let rect = Rect::from_min_size(Vec2::ZERO, Vec2::splat(10.0));
+        let mut occ = Occlusion::default();
+        let regions = |occ: &mut Occlusion| {
+            occ.push(1, panel, Rect::from_min_size(Vec2::ZERO, Vec2::splat(100.0)));
+            let la = occ.push(1, a, rect);
+            let lb = occ.push(1, b, rect.translate(Vec2::new(5.0, 0.0)));
+            (la, lb)
+        };
+
+        // nothing is known about the first frame
+        let (la, lb) = regions(&mut occ);
+        assert!(!occ.is_occluded(a, la) && !occ.is_occluded(b, lb));
+
+        // b is placed after a and covers it where they overlap
+        occ.begin_frame(Vec2::new(7.0, 5.0));
+        let (la, lb) = regions(&mut occ);
+        assert!(occ.is_occluded(a, la));
+        assert!(!occ.is_occluded(b, lb));
+
+        occ.begin_frame(Vec2::new(2.0, 5.0));
+        let (la, _) = regions(&mut occ);
+        assert!(!occ.is_occluded(a, la));
+        assert_eq!(occ.region(a), Some((la, rect)));
+        assert_eq!(occ.region(Id(4)), None);
+
+        // a region of a panel drawn on top
+        occ.push(2, Id::NULL, rect);
+        occ.begin_frame(Vec2::new(2.0, 5.0));
+        let (la, _) = regions(&mut occ);
+        assert!(occ.is_occluded(a, la));
+    }
+
+    #[test]
+    fn test_background() {
+        assert_eq!(gpu::Background::default().clear, Some(RGBA::ZERO));
+        assert_eq!(gpu::Background::load().clear, None);
+        let bg = gpu::Background::color(RGBA::RED).with_gradient(RGBA::BLUE, RGBA::GREEN);
+        assert_eq!(bg.gradient, Some((RGBA::BLUE, RGBA::GREEN)));
+
+        let desc = gpu::BackgroundVertex::desc();
+        let templates = [(&desc, "BackgroundVertex")];
+        let src = gpu::pre_process_shader_code(gpu::BACKGROUND_SHADER, &templates).unwrap();
+        let entry_points = ["vs_main", "fs_main"];
+        assert_eq!(
+            gpu::validate_shader(&src, entry_points, &desc.attributes, &desc.members, 0, &[]),
+            Ok(())
+        );
     }
 
-    wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("global_bind_group"),
-        layout: &global_bind_group_layout,
-        entries: &group_entries,
-    })
+    #[test]
+    fn test_svg_export() {
+        let list = DrawList::new();
+        let rect = Rect::from_min_size(Vec2::new(10.0, 10.0), Vec2::new(20.0, 10.0));
+        list.push_clip_rect(Rect::from_min_size(Vec2::ZERO, Vec2::splat(15.0)));
+        DrawRect::new(rect.min, rect.max).fill(RGBA::RED).add_to_drawlist(&list);
+        list.pop_clip_rect();
+        let points = [Vec2::new(10.0, 30.0), Vec2::new(20.0, 30.0)];
+        list.add_polyline_ex(&points, Stroke::new(RGBA::BLUE, 1.0), false, LineMode::Fast);
+
+        let mut svg = SvgWriter::new(Rect::from_min_size(Vec2::splat(10.0), Vec2::splat(30.0)));
+        svg.push_drawlist(&list, None);
+        let svg = svg.finish();
+        assert!(svg.starts_with("<svg "), "{svg}");
+        assert!(svg.contains(r##"fill="#ff0000""##), "{svg}");
+        assert!(svg.contains(r##"<path d="M0 20L10 20" stroke="#0000ff""##), "{svg}");
+    }
+
+    #[test]
+    fn test_soft_rasterizer() {
+        let list = DrawList::new();
+        let half_red = RGBA::rgba_f(1.0, 0.0, 0.0, 0.5);
+        let rect = DrawRect::new(Vec2::new(1.0, 1.0), Vec2::new(5.0, 5.0));
+        rect.fill(half_red).add_to_drawlist(&list);
+
+        let size = UVec2::new(6, 6);
+        let img = SoftRasterizer::new().render_drawlist(&list, size, RGBA::BLACK);
+        let px = |x: u32, y: u32| {
+            let i = ((y * size.x + x) * 4) as usize;
+            [img.rgba[i], img.rgba[i + 1], img.rgba[i + 2], img.rgba[i + 3]]
+        };
+        // the diagonal shared by the two triangles is blended exactly once
+        for i in 1..5 {
+            assert_eq!(px(i, i), [128, 0, 0, 255]);
+        }
+        assert_eq!(px(4, 1), [128, 0, 0, 255]);
+        assert_eq!(px(0, 0), [0, 0, 0, 255]);
+        assert_eq!(px(5, 5), [0, 0, 0, 255]);
+        let white = SoftRasterizer::new().render_drawlist(&list, size, RGBA::WHITE);
+        assert_eq!(img.count_differences(&white, 0), Some(36));
+        assert_eq!(img.count_differences(&white, 128), Some(20));
+    }
+
+    #[test]
+    fn test_draw_robustness() {
+        let list = DrawList::new();
+        let bad = [Vec2::new(f32::NAN, 0.0), Vec2::INFINITY, Vec2::new(1.0, 1.0)];
+        list.add_polyline(&bad, Stroke::new(RGBA::RED, 1.0), true);
+        list.add_convex_poly_filled(&bad, RGBA::RED);
+        list.add_polyline(&[Vec2::ZERO, Vec2::ONE], Stroke::new(RGBA::RED, f32::NAN), false);
+        let rects = [(Vec2::ONE, Vec2::ONE), (Vec2::NAN, Vec2::ONE), (Vec2::ZERO, Vec2::ONE)];
+        for (min, max) in rects {
+            for r in [1e30, f32::INFINITY, f32::NAN] {
+                DrawRect::new(min, max).fill(RGBA::RED).corners(r).add_to_drawlist(&list);
+            }
+        }
+
+        // a polyline that does not fit into a single draw call is split
+        let points: Vec<_> = (0..100_000).map(|i| Vec2::new(i as f32, (i % 2) as f32)).collect();
+        list.add_polyline(&points, Stroke::new(RGBA::RED, 2.0), false);
+        for cmd in list.commands().iter() {
+            assert!(cmd.vtx_count <= RenderData::MAX_VERTEX_COUNT as usize);
+            let vtx = list.vtx_slice(cmd.vtx_offset..cmd.vtx_offset + cmd.vtx_count);
+            let idx = list.idx_slice(cmd.idx_offset..cmd.idx_offset + cmd.idx_count);
+            assert!(vtx.iter().all(|v| v.pos.is_finite()));
+            assert!(idx.iter().all(|&i| (i as usize) < vtx.len()));
+        }
+        assert!(list.commands().len() > 1);
+
+        assert_eq!(sanitize_text("a\r\nb\rc\0"), "a\nb\nc");
+        assert!(matches!(sanitize_text("a\tb\n"), std::borrow::Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_ui_effect() {
+        let wgsl = "fn effect(col: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32> {
+            return vec4(uv, 0.5 + 0.5 * sin(global.time + pos.x), 1.0) * col;
+        }";
+        assert_eq!(UiEffect::fragment(wgsl).validate(), Ok(()));
+        assert!(UiEffect::fragment("fn effect() {}").validate().is_err());
+
+        // draws with an effect are not batched with the draws around them
+        let list = DrawList::new();
+        let rect = DrawRect::new(Vec2::ZERO, Vec2::splat(10.0)).fill(RGBA::RED);
+        rect.add_to_drawlist(&list);
+        rect.effect(EffectId(1)).add_to_drawlist(&list);
+        rect.effect(EffectId(1)).add_to_drawlist(&list);
+        rect.add_to_drawlist(&list);
+
+        let mut calls = DrawCallList::new(usize::MAX, usize::MAX);
+        calls.push_drawlist(&list, Vec2::splat(100.0));
+        calls.merge_calls();
+        let merged: Vec<_> = calls.calls.iter().map(|c| (c.effect, c.n_idx)).collect();
+        assert_eq!(merged, [(EffectId::NONE, 6), (EffectId(1), 12), (EffectId::NONE, 6)]);
+    }
+
+    #[test]
+    fn test_shader_canvas() {
+        let source = "fn main_image(frag_coord: vec2<f32>) -> vec4<f32> {
+            let uv = frag_coord / canvas.resolution;
+            return vec4(uv, 0.5 + 0.5 * sin(canvas.time), param(0u));
+        }";
+        assert!(crate::ui_shader_canvas::canvas_shader(source).is_ok());
+        let err = crate::ui_shader_canvas::canvas_shader("fn main_image() {}").unwrap_err();
+        assert!(!err.is_empty());
+        // the wgsl struct pads `frame` to the alignment of `params`
+        assert_eq!(std::mem::offset_of!(CanvasUniform, params), 48);
+    }
+
+    #[test]
+    fn test_gradient() {
+        let mut g = Gradient::from_colors(&[RGBA::BLACK, RGBA::WHITE]);
+        assert_eq!(g.sample(0.5), RGBA::BLACK.lerp(RGBA::WHITE, 0.5));
+        assert_eq!(g.sample(-1.0), RGBA::BLACK);
+
+        // stops stay sorted while they are moved
+        let red = g.add_stop(0.25, RGBA::RED);
+        assert_eq!(red, 1);
+        assert_eq!(g.move_stop(red, 0.9), 1);
+        assert_eq!(g.sample(0.9), RGBA::RED);
+        assert_eq!(g.lut(3)[2], RGBA::WHITE);
+
+        g.interp = GradientInterp::Step;
+        assert_eq!(g.sample(0.95), RGBA::RED);
+
+        // a step has two columns at the position of a stop, without a quad between them
+        let (vtx, idx) = g.tessellate(Rect::from_min_size(Vec2::ZERO, Vec2::splat(10.0)), Axis::X);
+        assert_eq!((vtx.len(), idx.len()), (14, 12));
+    }
+
+    #[test]
+    fn test_curve() {
+        let mut curve = Curve::ease_in_out();
+        assert_eq!(curve.eval(-1.0), 0.0);
+        assert_eq!(curve.eval(0.5), 0.5);
+        assert!(curve.eval(0.25) < 0.25 && curve.eval(0.75) > 0.75);
+        assert_eq!(curve.eval(2.0), 1.0);
+
+        // keys stay sorted while they are moved
+        let i = curve.add_key(CurveKey::new(Vec2::new(0.25, 1.0)).with_interp(CurveInterp::Linear));
+        assert_eq!(curve.eval(0.125), 0.5);
+        assert_eq!(curve.move_key(i, Vec2::new(0.75, 1.0)), 1);
+        curve.keys[0].interp = CurveInterp::Constant;
+        assert_eq!(curve.eval(0.5), 0.0);
+
+        let envelope = Curve::linear(&[Vec2::ZERO, Vec2::new(0.1, 1.0), Vec2::new(1.0, 0.0)]);
+        let points = envelope.sample(0.0, 1.0, 11);
+        assert_eq!(points[1], Vec2::new(0.1, 1.0));
+        assert!(points[10].y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_inspect_derive() {
+        #[derive(Default, macros::Inspect)]
+        enum LightMode {
+            #[default]
+            Point,
+            Spot,
+        }
+
+        #[derive(macros::Inspect)]
+        struct Light {
+            #[inspect(range = 0.0..=10.0)]
+            intensity: f32,
+            #[inspect(label = "color")]
+            col: RGBA,
+            mode: LightMode,
+            tags: Vec<String>,
+            #[inspect(skip)]
+            _cache: Vec<u8>,
+        }
+
+        #[derive(Default, macros::Inspect)]
+        struct Scene(Vec<LightMode>, Vec2);
+
+        fn assert_inspect<T: Inspect>() {}
+        assert_inspect::<Light>();
+        assert_inspect::<Scene>();
+    }
+
+    #[test]
+    fn test_form_validators() {
+        assert!(Validator::Required.validate("  ").is_err());
+        assert!(Validator::Required.validate("a").is_ok());
+
+        let range = Validator::Range(1.0, 65535.0);
+        assert!(range.validate("8080").is_ok());
+        assert!(range.validate("0").is_err());
+        assert!(range.validate("port").is_err());
+
+        let hex = Validator::pattern("[0-9a-f]+");
+        assert!(hex.validate("ff00").is_ok());
+        assert!(hex.validate("ff00x").is_err());
+        assert!(hex.validate("").is_err());
+    }
+
+    #[test]
+    fn test_settings() {
+        let mut settings = crate::Settings::in_memory();
+        settings.set("ui_scale", 1.25f32);
+        settings.set("last_file", "a b\\c\nd");
+        assert!(settings.is_dirty());
+        settings.flush();
+        settings.set("ui_scale", 1.25f32);
+        assert!(!settings.is_dirty());
+
+        let loaded = crate::Settings::parse(&settings.to_string());
+        assert_eq!(loaded.get("ui_scale"), Some(1.25f32));
+        assert_eq!(loaded.get_str("last_file"), Some("a b\\c\nd"));
+        assert_eq!(loaded.get::<u32>("last_file"), None);
+        assert!(loaded.get_or("vsync", true));
+    }
+
+    #[test]
+    fn test_i18n_catalog() {
+        let src = "# mail
+greeting = Hello, { $name }!
+unread = { $count ->
+    [0] No new mail
+    [one] One new mail
+   *[other] { $count } new mails
 }
+brace = { \"{\" }x{ \"}\" }
+";
+        let en = crate::Catalog::parse("en", src);
+        let args = |n: usize| [("count", crate::TrArg::from(n))];
+        assert_eq!(en.format("unread", &args(0)).unwrap(), "No new mail");
+        assert_eq!(en.format("unread", &args(1)).unwrap(), "One new mail");
+        assert_eq!(en.format("unread", &args(5)).unwrap(), "5 new mails");
+        assert_eq!(en.format("brace", &[]).unwrap(), "{x}");
+
+        let mut i18n = crate::Localization::default();
+        i18n.add_catalog(en);
+        let mut de = crate::Catalog::new("de");
+        de.insert("greeting", "Hallo, { $name }!");
+        i18n.add_catalog(de);
+        let name = [("name", crate::TrArg::from("Ada"))];
+        assert_eq!(i18n.tr("greeting", &name), "Hello, Ada!");
+        assert_eq!(i18n.tr("missing", &[]), "missing");
+        i18n.set_language("de");
+        assert_eq!(i18n.tr("greeting", &name), "Hallo, Ada!");
+        assert_eq!(i18n.tr("unread", &[("count", 2.into())]), "2 new mails");
+
+        assert_eq!(crate::plural_category("ru", 22.0), "few");
+        assert_eq!(crate::plural_category("ru", 11.0), "many");
+        assert_eq!(crate::plural_category("fr", 0.0), "one");
+    }
+
+    #[test]
+    fn test_attention_apply() {
+        let rect = Rect::from_min_max(Vec2::ZERO, Vec2::splat(10.0));
+        let vtx = [Vertex::ZERO, Vertex { pos: Vec2::splat(10.0), ..Vertex::ZERO }];
+        let red = RGBA::rgb(255, 0, 0);
+
+        let mut shaken = vtx;
+        Attention::Shake.apply(&mut shaken, rect, 0.05, red, false);
+        assert!(shaken[0].pos.x != 0.0 && shaken[0].pos.y == 0.0);
+        assert!(shaken[0].col.r > 0.0);
+
+        let mut still = vtx;
+        Attention::Pulse.apply(&mut still, rect, 0.5, red, true);
+        assert_eq!(still[1].pos, Vec2::splat(10.0));
+
+        // the end state leaves the item unchanged
+        let mut done = vtx;
+        Attention::Flash.apply(&mut done, rect, 1.0, red, false);
+        assert_eq!(done[1].pos, vtx[1].pos);
+        assert!(done[0].col.r.abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_panel_budget() {
+        let budget = PanelBudget::default();
+        let mut stats = PanelStats {
+            vertices: 100,
+            draw_cmds: 2,
+            glyphs: 40,
+            build_time: std::time::Duration::from_micros(300),
+        };
+        assert!(budget.exceeded(&stats).is_empty());
+
+        stats.vertices = 61_000;
+        stats.build_time = std::time::Duration::from_micros(2400);
+        assert_eq!(budget.exceeded(&stats), ["61k vtx", "2.40ms"]);
+
+        // glyphs are counted when they are drawn
+        let list = DrawList::new();
+        let glyph = DrawRect::new(Vec2::ZERO, Vec2::splat(8.0)).fill(RGBA::WHITE);
+        list.add_draw_rect(glyph.texture(TextureId::GLYPH));
+        list.add_draw_rect(glyph);
+        assert_eq!(list.data.borrow().n_glyphs, 1);
+        list.clear();
+        assert_eq!(list.data.borrow().n_glyphs, 0);
+    }
+
+    /// a context rendering into a headless window, `None` without a gpu adapter
+    fn headless_context() -> Option<Context> {
+        let wgpu = std::panic::catch_unwind(crate::gpu::WGPU::new_headless).ok()?;
+        let wgpu = std::sync::Arc::new(wgpu);
+        let window = crate::gpu::Window::headless(640, 480, &wgpu);
+        let mut ui = Context::new(wgpu, window);
+        ui.draw.screen_size = Vec2::new(640.0, 480.0);
+        ui.init();
+        ui.begin_dockspace();
+        ui.end();
+        let panel = ui.panel_id("test");
+        ui.dock_to_dockspace(panel, 1.0, Dir::E);
+        ui.update_draworder();
+        Some(ui)
+    }
+
+    #[test]
+    #[ignore = "needs a gpu adapter"]
+    fn test_culled_items_are_not_drawn() {
+        let mut ui = headless_context().expect("no gpu adapter");
+        let (mut b, mut v, mut sel) = (false, 0.5, None);
+        let mut widgets = |ui: &mut Context| {
+            ui.button("button");
+            ui.text("text");
+            ui.checkbox("checkbox", &mut b);
+            ui.switch("switch", &mut b);
+            ui.slider_f32("slider", 0.0, 1.0, &mut v);
+            ui.input_slider_f32("input slider", 0.0, 1.0, &mut v);
+            ui.input_text("input", "text");
+            ui.icon_button(phosphor_font::X, "icon button");
+            ui.collapsing_header("header", &mut b);
+            ui.searchable_select("select", &["a", "b"], &mut sel);
+            ui.separator_h(Stroke::new(RGBA::WHITE, 1.0));
+        };
+        let n_vtx = |ui: &Context| {
+            let list = &ui.get_current_panel().drawlist;
+            list.resolve_pending_shapes();
+            list.data.borrow().vtx_buffer.len()
+        };
 
-//---------------------------------------------------------------------------------------
-// END RENDER
+        ui.begin_frame();
+        ui.begin("test");
+        let start = n_vtx(&ui);
+        widgets(&mut ui);
+        assert!(n_vtx(&ui) > start, "visible items are drawn");
+
+        // push the cursor far below the panel
+        ui.place_item(Vec2::new(1.0, 100_000.0));
+        let start = n_vtx(&ui);
+        widgets(&mut ui);
+        assert!(ui.item_culled());
+        assert_eq!(n_vtx(&ui), start, "culled items are skipped");
+        ui.end();
+        ui.end_frame();
+    }
+
+    #[test]
+    #[ignore = "needs a gpu adapter"]
+    fn test_loading_scope() {
+        let mut ui = headless_context().unwrap();
+        ui.reduced_motion = true;
+        ui.begin_frame();
+        ui.begin("test");
+        let list = ui.current_drawlist().clone();
+        list.resolve_pending_shapes();
+        let start = list.data.borrow().vtx_buffer.len();
+
+        let (sig, rect) = ui.loading_scope(true, |ui| {
+            ui.button("button");
+            ui.text("text");
+            let rect = ui.place_item(Vec2::new(100.0, 20.0));
+            let sig = ui.reg_item_(ui.gen_id("item"), rect);
+            (sig, rect)
+        });
+        assert_eq!(sig, Signal::NONE);
+
+        // only the skeletons are drawn, inside of the placed items. the antialiased edges only
+        // differ in alpha
+        list.resolve_pending_shapes();
+        let vtx = list.data.borrow().vtx_buffer[start..].to_vec();
+        assert!(!vtx.is_empty());
+        let skeleton_col = ui.style.btn_default();
+        assert!(vtx.iter().all(|v| v.col.with_alpha(skeleton_col.a) == skeleton_col));
+        let bounds = Rect::from_points(&vtx.iter().map(|v| v.pos).collect::<Vec<_>>());
+        assert!(bounds.max.y <= rect.max.y + 1.0);
+        ui.end();
+        ui.end_frame();
+    }
+
+    #[test]
+    #[ignore = "needs a gpu adapter"]
+    fn test_wants_input() {
+        let mut ui = headless_context().unwrap();
+        let mut v = 0.5;
+        // a floating panel with a slider and a text input, returns their rect and id
+        let mut frame = |ui: &mut Context| {
+            ui.begin_frame();
+            ui.next.pos = Vec2::new(100.0, 100.0);
+            ui.next.size = Vec2::new(300.0, 200.0);
+            ui.begin("panel");
+            ui.slider_f32("slider", 0.0, 1.0, &mut v);
+            let slider = ui.last_item_rect;
+            ui.input_text("input", "text");
+            let input = ui.gen_id("input");
+            ui.end();
+            ui.end_frame();
+            (slider, input)
+        };
+        let move_to = |ui: &mut Context, pos: Vec2| ui.set_mouse_pos(pos.x, pos.y);
+        let outside = Vec2::splat(-50.0);
+
+        move_to(&mut ui, outside);
+        frame(&mut ui);
+        frame(&mut ui);
+        assert!(!ui.wants_pointer_input());
+        assert!(!ui.wants_keyboard_input());
+
+        // hovering the panel
+        move_to(&mut ui, Vec2::new(380.0, 280.0));
+        frame(&mut ui);
+        frame(&mut ui);
+        assert!(ui.wants_pointer_input());
+        assert!(!ui.wants_keyboard_input());
+
+        // dragging the slider keeps the pointer when it leaves the panel
+        let (slider, input) = frame(&mut ui);
+        move_to(&mut ui, slider.center());
+        ui.set_mouse_press(MouseBtn::Left, true);
+        frame(&mut ui);
+        move_to(&mut ui, outside);
+        frame(&mut ui);
+        assert!(ui.wants_pointer_input());
+        ui.set_mouse_press(MouseBtn::Left, false);
+        frame(&mut ui);
+        assert!(!ui.wants_pointer_input());
+
+        // a focused text input takes the keyboard
+        ui.active_id = input;
+        frame(&mut ui);
+        assert!(ui.wants_keyboard_input());
+        assert!(!ui.wants_pointer_input());
+    }
+
+    #[test]
+    #[ignore = "needs a gpu adapter"]
+    fn test_draworder() {
+        let mut ui = headless_context().expect("no gpu adapter");
+        let panel = |ui: &mut Context, name: &str, flags: PanelFlag| {
+            ui.begin_ex(name, flags);
+            let id = ui.current_panel_id;
+            ui.end();
+            RootId::Panel(id)
+        };
+
+        ui.begin_frame();
+        let top = panel(&mut ui, "top", PanelFlag::ALWAYS_ON_TOP);
+        let a = panel(&mut ui, "a", PanelFlag::NONE);
+        let b = panel(&mut ui, "b", PanelFlag::NONE);
+        ui.end_frame();
+        let order = |ui: &Context| -> Vec<RootId> {
+            ui.draworder.iter().copied().filter(|r| [top, a, b].contains(r)).collect()
+        };
+        assert_eq!(order(&ui), [a, b, top]);
+
+        // always on top panels stay above the others
+        ui.bring_to_front(a);
+        assert_eq!(order(&ui), [b, a, top]);
+        ui.send_to_back(top);
+        assert_eq!(order(&ui), [b, a, top]);
+        ui.send_to_back(a);
+        assert_eq!(order(&ui), [a, b, top]);
+
+        // roots that are not in the draworder are ignored
+        ui.send_to_back(RootId::Panel(Id::from_str("missing")));
+        assert_eq!(order(&ui), [a, b, top]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_style_table_serde() {
+        let mut style = crate::ui_context::dark_theme();
+        style.push_var(StyleVar::SpacingV(42.0));
+        let json = serde_json::to_string(&style).unwrap();
+        let restored: StyleTable = serde_json::from_str(&json).unwrap();
+        // only the current values are stored
+        assert_eq!(restored.values, style.values);
+        assert!(restored.var_stack.is_empty());
+
+        // the vars can be in any order, but every field needs a value
+        let mut vars = style.values.to_vec();
+        vars.reverse();
+        let json = serde_json::to_string(&vars).unwrap();
+        let reversed: StyleTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(reversed.values, style.values);
+
+        vars.pop();
+        let json = serde_json::to_string(&vars).unwrap();
+        let err = serde_json::from_str::<StyleTable>(&json).unwrap_err();
+        assert!(err.to_string().contains("missing value"), "{err}");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_layout_state_serde() {
+        let mut docktree = DockTree::new();
+        let rect = Rect::from_min_size(Vec2::ZERO, Vec2::new(800.0, 600.0));
+        let (a, b) = (Id::from_str("a"), Id::from_str("b"));
+        let root = docktree.add_root(rect, a);
+        let other = docktree.add_root(rect, b);
+        docktree.merge_nodes(root, other, 0.3, Dir::E);
+
+        let layout = |id, dock_id| PanelLayout {
+            id,
+            name: format!("{id:?}"),
+            pos: Vec2::new(10.0, 20.0),
+            size: Vec2::new(300.0, 200.0),
+            size_pre_dock: Vec2::new(100.0, 50.0),
+            dock_id,
+        };
+        let c = Id::from_str("c");
+        let state = LayoutState {
+            panels: vec![layout(a, root), layout(b, other), layout(c, Id::NULL)],
+            draworder: vec![RootId::Dock(docktree.get_root(root)), RootId::Panel(c)],
+            docktree,
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: LayoutState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.panels, state.panels);
+        assert_eq!(restored.draworder, state.draworder);
+        assert_eq!(restored.docktree.nodes.map, state.docktree.nodes.map);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[ignore = "needs a gpu adapter"]
+    fn test_restore_layout() {
+        let mut ui = headless_context().expect("no gpu adapter");
+        ui.begin_frame();
+        ui.begin("float");
+        let id = ui.current_panel_id;
+        ui.end();
+        ui.end_frame();
+        ui.panels[id].pos = Vec2::new(30.0, 40.0);
+        ui.panels[id].size = Vec2::new(200.0, 100.0);
+
+        let json = serde_json::to_string(&ui.layout_state()).unwrap();
+        let state: LayoutState = serde_json::from_str(&json).unwrap();
+        let stored = state.panels.iter().find(|p| p.id == id).unwrap();
+        assert_eq!((stored.pos, stored.size), (ui.panels[id].pos, ui.panels[id].size));
+
+        // restoring into a context where the panel does not exist yet, it gets its layout
+        // when it is created
+        let mut ui = headless_context().expect("no gpu adapter");
+        ui.restore_layout(&state);
+        assert!(ui.pending_layouts.contains_key(&id));
+        ui.begin_frame();
+        ui.begin("float");
+        ui.end();
+        ui.end_frame();
+        assert!(ui.pending_layouts.is_empty());
+        assert_eq!(ui.panels[id].pos, Vec2::new(30.0, 40.0));
+        assert_eq!(ui.panels[id].size, Vec2::new(200.0, 100.0));
+        assert_eq!(ui.layout_state().panels, state.panels);
+    }
+
+    #[test]
+    fn test_layout_dir_place() {
+        let content = Rect::from_min_size(Vec2::new(10.0, 0.0), Vec2::new(100.0, 50.0));
+        let a = Rect::from_min_size(Vec2::new(10.0, 5.0), Vec2::new(30.0, 10.0));
+        let b = Rect::from_min_size(Vec2::new(45.0, 5.0), Vec2::new(20.0, 10.0));
+        assert_eq!(LayoutDir::Ltr.place(a, content), a);
+
+        let (a, b) = (LayoutDir::Rtl.place(a, content), LayoutDir::Rtl.place(b, content));
+        assert_eq!(a, Rect::from_min_size(Vec2::new(80.0, 5.0), Vec2::new(30.0, 10.0)));
+        assert_eq!(b, Rect::from_min_size(Vec2::new(55.0, 5.0), Vec2::new(20.0, 10.0)));
+    }
+
+    #[test]
+    #[ignore = "needs a gpu adapter"]
+    fn test_rtl_layout() {
+        let mut ui = headless_context().expect("no gpu adapter");
+        ui.layout_dir = LayoutDir::Rtl;
+        let mut rects = Vec::new();
+        for _ in 0..2 {
+            ui.begin_frame();
+            ui.next.pos = Vec2::new(100.0, 100.0);
+            ui.next.size = Vec2::new(300.0, 200.0);
+            ui.begin("panel");
+            let content = ui.get_current_panel().visible_content_rect();
+            let a = ui.place_item(Vec2::new(40.0, 20.0));
+            ui.same_line();
+            let b = ui.place_item(Vec2::new(30.0, 20.0));
+            let c = ui.place_item(Vec2::new(50.0, 20.0));
+            rects = vec![content, a, b, c];
+            ui.end();
+            ui.end_frame();
+        }
+
+        let [content, a, b, c] = rects[..] else { unreachable!() };
+        // each row starts at the right edge of the content and flows to the left
+        assert_eq!(a.max.x, content.max.x);
+        assert_eq!(c.max.x, content.max.x);
+        assert!(b.max.x <= a.min.x);
+        assert_eq!(b.min.y, a.min.y);
+        assert!(c.min.y >= a.max.y);
+    }
+}
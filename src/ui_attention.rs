@@ -0,0 +1,174 @@
+//! short animations that draw attention to an item, see [`ui::Context::flash`]
+
+use std::f32::consts::PI;
+
+use glam::Vec2;
+
+use crate::{
+    core::RGBA,
+    rect::Rect,
+    ui::{self, DrawList, Id, Vertex},
+};
+
+/// the animation of a [`ui::Context::attention`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attention {
+    /// blinks in the accent color, e.g. for the target of a jump
+    Flash,
+    /// shakes sideways in red, e.g. for an invalid input
+    Shake,
+    /// grows and glows once, e.g. for a newly added entry
+    Pulse,
+}
+
+impl Attention {
+    /// seconds the animation takes
+    pub fn duration(self) -> f32 {
+        match self {
+            Self::Flash => 0.6,
+            Self::Shake => 0.4,
+            Self::Pulse => 0.5,
+        }
+    }
+
+    /// modulates the vertices of an item at `t` in `0..1` of the animation. `reduced_motion`
+    /// keeps the color change but does not move the vertices
+    pub(crate) fn apply(
+        self,
+        vtx: &mut [Vertex],
+        rect: Rect,
+        t: f32,
+        col: RGBA,
+        reduced_motion: bool,
+    ) {
+        let fade = 1.0 - t;
+        let (offset, scale, tint) = match self {
+            // two blinks
+            Self::Flash => (0.0, 1.0, (t * 2.0 * PI).sin().abs() * fade * 0.6),
+            Self::Shake => {
+                let dx = (t * 5.0 * 2.0 * PI).sin() * 6.0 * fade;
+                (dx, 1.0, fade * 0.5)
+            }
+            Self::Pulse => {
+                let k = (t * PI).sin();
+                (0.0, 1.0 + 0.06 * k, k * 0.4)
+            }
+        };
+        let (offset, scale) = if reduced_motion {
+            (0.0, 1.0)
+        } else {
+            (offset, scale)
+        };
+
+        let center = rect.center();
+        for v in vtx {
+            v.pos = center + (v.pos - center) * scale + Vec2::new(offset, 0.0);
+            v.col = RGBA {
+                a: v.col.a,
+                ..v.col.lerp(col, tint)
+            };
+        }
+    }
+}
+
+/// a running [`Attention`] of an item
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AttentionAnim {
+    pub kind: Attention,
+    /// [`ui::Context::time_since_start`] when the animation started
+    pub start: f32,
+}
+
+/// the item with a running animation whose vertices are being recorded
+#[derive(Debug, Clone)]
+pub(crate) struct AttentionItem {
+    pub id: Id,
+    pub rect: Rect,
+    pub drawlist: DrawList,
+    /// the first vertex of the item in `drawlist`
+    pub vtx_start: usize,
+}
+
+impl ui::Context {
+    /// starts `kind` on the item `id`, e.g. `ui.attention(ui.gen_id("save"), Attention::Flash)`.
+    /// the animation modulates the draws of the item until it ends, starting it again restarts
+    /// it
+    pub fn attention(&mut self, id: Id, kind: Attention) {
+        let start = self.time_since_start();
+        self.attention_anims
+            .insert(id, AttentionAnim { kind, start });
+        self.window.request_redraw();
+    }
+
+    /// blinks the item `id` in the accent color
+    pub fn flash(&mut self, id: Id) {
+        self.attention(id, Attention::Flash);
+    }
+
+    /// shakes the item `id`, e.g. after a failed validation
+    pub fn shake(&mut self, id: Id) {
+        self.attention(id, Attention::Shake);
+    }
+
+    /// grows the item `id` once, e.g. when it was just added
+    pub fn pulse(&mut self, id: Id) {
+        self.attention(id, Attention::Pulse);
+    }
+
+    /// the item `id` is animated
+    pub fn has_attention(&self, id: Id) -> bool {
+        self.attention_anims.contains_key(&id)
+    }
+
+    /// starts recording the vertices of the item `id` if it is animated, called when the item
+    /// is registered
+    pub(crate) fn begin_attention_item(&mut self, id: Id, rect: Rect) {
+        if !self.attention_anims.contains_key(&id) {
+            return;
+        }
+        self.end_attention_item();
+        let drawlist = self.current_drawlist().clone();
+        drawlist.resolve_pending_shapes();
+        let vtx_start = self
+            .last_item_vtx_start
+            .min(drawlist.data.borrow().vtx_buffer.len());
+        self.attention_item = Some(AttentionItem {
+            id,
+            rect,
+            drawlist,
+            vtx_start,
+        });
+    }
+
+    /// applies the animation to the vertices the recorded item drew, called when the next
+    /// item is placed or the panel ends
+    pub(crate) fn end_attention_item(&mut self) {
+        let Some(item) = self.attention_item.take() else {
+            return;
+        };
+        let Some(anim) = self.attention_anims.get(&item.id) else {
+            return;
+        };
+        let t = (self.time_since_start() - anim.start) / anim.kind.duration();
+        let col = match anim.kind {
+            Attention::Shake => self.style.red(),
+            Attention::Flash | Attention::Pulse => self.style.btn_press(),
+        };
+        let mut data = item.drawlist.data.borrow_mut();
+        data.resolve_pending_shapes();
+        if let Some(vtx) = data.vtx_buffer.get_mut(item.vtx_start..) {
+            anim.kind
+                .apply(vtx, item.rect, t.clamp(0.0, 1.0), col, self.reduced_motion);
+        }
+    }
+
+    /// drops the finished animations and keeps redrawing while some are running
+    pub(crate) fn update_attention(&mut self) {
+        let now = self.time_since_start();
+        self.attention_anims
+            .retain(|_, a| now - a.start < a.kind.duration());
+        if !self.attention_anims.is_empty() {
+            self.window.request_redraw();
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! read only code view with a small built-in syntax highlighter
+
+use glam::Vec2;
+
+use crate::{
+    accessibility::AccessRole,
+    core::RGBA,
+    mouse::CursorIcon,
+    rect::Rect,
+    ui::{self, Id, Signal},
+};
+
+/// monospace font family loaded by [`ui::Context::new`], used for code
+pub const MONO_FONT: &str = "CommitMono";
+
+/// tabs are expanded to this many spaces
+const CODE_TAB_WIDTH: usize = 4;
+
+/// languages known to the built-in highlighter
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeLanguage {
+    #[default]
+    Plain,
+    Rust,
+    Wgsl,
+}
+
+impl CodeLanguage {
+    /// looks up a language by name or file extension, e.g. `"rust"` or `"rs"`
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Self::Rust,
+            "wgsl" => Self::Wgsl,
+            _ => Self::Plain,
+        }
+    }
+
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Self::Plain => &[],
+            Self::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop",
+                "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static",
+                "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            Self::Wgsl => &[
+                "alias", "break", "case", "const", "const_assert", "continue", "continuing",
+                "default", "diagnostic", "discard", "else", "enable", "false", "fn", "for", "if",
+                "let", "loop", "override", "requires", "return", "struct", "switch", "true",
+                "var", "while",
+            ],
+        }
+    }
+
+    fn is_type(self, ident: &str) -> bool {
+        match self {
+            Self::Plain => false,
+            Self::Rust => {
+                matches!(
+                    ident,
+                    "bool" | "char" | "str" | "f32" | "f64" | "i8" | "i16" | "i32" | "i64"
+                        | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+                ) || ident.starts_with(char::is_uppercase)
+            }
+            Self::Wgsl => {
+                matches!(
+                    ident,
+                    "bool" | "f16" | "f32" | "i32" | "u32" | "array" | "atomic" | "ptr"
+                        | "sampler" | "sampler_comparison"
+                ) || ["vec", "mat", "texture_"].iter().any(|p| ident.starts_with(p))
+                    || ident.starts_with(char::is_uppercase)
+            }
+        }
+    }
+}
+
+/// highlight class of a piece of code
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeToken {
+    #[default]
+    Text,
+    Keyword,
+    Type,
+    Function,
+    Number,
+    String,
+    Comment,
+    /// `#[...]` in rust, `@...` in wgsl
+    Attribute,
+}
+
+impl CodeToken {
+    /// `Text` uses the text color of the style
+    pub fn color(self) -> Option<RGBA> {
+        Some(match self {
+            Self::Text => return None,
+            Self::Keyword => macros::rgba!("#c678dd"),
+            Self::Type => macros::rgba!("#e5c07b"),
+            Self::Function => macros::rgba!("#61afef"),
+            Self::Number => macros::rgba!("#d19a66"),
+            Self::String => macros::rgba!("#98c379"),
+            Self::Comment => macros::rgba!("#7f848e"),
+            Self::Attribute => macros::rgba!("#56b6c2"),
+        })
+    }
+}
+
+/// splits a line into highlighted ranges of char indices
+///
+/// `in_comment` carries an open block comment over to the next line
+pub fn highlight_line(
+    lang: CodeLanguage,
+    line: &str,
+    in_comment: &mut bool,
+) -> Vec<(std::ops::Range<usize>, CodeToken)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = Vec::new();
+    if lang == CodeLanguage::Plain {
+        if !chars.is_empty() {
+            tokens.push((0..chars.len(), CodeToken::Text));
+        }
+        return tokens;
+    }
+
+    let at = |i: usize, s: &str| {
+        s.chars().enumerate().all(|(k, c)| chars.get(i + k) == Some(&c))
+    };
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+
+        let kind = if *in_comment {
+            continue_comment(&chars, &mut i, in_comment);
+            CodeToken::Comment
+        } else if at(i, "//") {
+            i = chars.len();
+            CodeToken::Comment
+        } else if at(i, "/*") {
+            *in_comment = true;
+            i += 2;
+            continue_comment(&chars, &mut i, in_comment);
+            CodeToken::Comment
+        } else if c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            CodeToken::String
+        } else if c == '\'' && lang == CodeLanguage::Rust && char_literal_len(&chars[i..]) > 0 {
+            i += char_literal_len(&chars[i..]);
+            CodeToken::String
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (is_ident(chars[i]) || chars[i] == '.') {
+                // `0..10` is a range, not a number
+                if chars[i] == '.' && chars.get(i + 1) == Some(&'.') {
+                    break;
+                }
+                i += 1;
+            }
+            CodeToken::Number
+        } else if lang == CodeLanguage::Rust && (at(i, "#[") || at(i, "#![")) {
+            let mut depth = 0;
+            while i < chars.len() {
+                match chars[i] {
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    _ => (),
+                }
+                i += 1;
+                if depth == 0 && chars[i - 1] == ']' {
+                    break;
+                }
+            }
+            CodeToken::Attribute
+        } else if c == '@' && lang == CodeLanguage::Wgsl {
+            i += 1;
+            while i < chars.len() && is_ident(chars[i]) {
+                i += 1;
+            }
+            CodeToken::Attribute
+        } else if is_ident(c) {
+            while i < chars.len() && is_ident(chars[i]) {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            // rust macros end with `!`
+            let mut next = i;
+            if lang == CodeLanguage::Rust && chars.get(i) == Some(&'!') {
+                next += 1;
+            }
+            if lang.keywords().contains(&ident.as_str()) {
+                CodeToken::Keyword
+            } else if lang.is_type(&ident) {
+                CodeToken::Type
+            } else if chars[next..].iter().find(|c| !c.is_whitespace()) == Some(&'(') {
+                i = next;
+                CodeToken::Function
+            } else {
+                CodeToken::Text
+            }
+        } else {
+            i += 1;
+            CodeToken::Text
+        };
+
+        match tokens.last_mut() {
+            Some((range, last)) if *last == kind && range.end == start => range.end = i,
+            _ => tokens.push((start..i, kind)),
+        }
+    }
+    tokens
+}
+
+/// length of a rust char literal like `'a'` or `'\n'` at the start of `chars`, 0 for lifetimes
+fn char_literal_len(chars: &[char]) -> usize {
+    match chars {
+        ['\'', '\\', _, '\'', ..] => 4,
+        ['\'', c, '\'', ..] if *c != '\\' => 3,
+        _ => 0,
+    }
+}
+
+/// advances `i` to the end of the block comment or the line
+fn continue_comment(chars: &[char], i: &mut usize, in_comment: &mut bool) {
+    while *i < chars.len() {
+        if chars[*i] == '*' && chars.get(*i + 1) == Some(&'/') {
+            *i += 2;
+            *in_comment = false;
+            return;
+        }
+        *i += 1;
+    }
+}
+
+/// horizontal scroll offset of a code view, stored in the widget data
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CodeScroll(f32);
+
+impl ui::Context {
+    /// read only code block with syntax highlighting, line numbers and a copy button. lines
+    /// longer than the available width scroll horizontally with shift + wheel or the scrollbar
+    pub fn code(&mut self, label: &str, lang: CodeLanguage, source: &str) -> Signal {
+        let id = self.gen_id(label);
+        let text_size = self.style.text_size();
+        let line_h = (text_size * 1.4).round();
+        let pad = (line_h * 0.4).round();
+
+        let lines: Vec<String> = source
+            .lines()
+            .map(|l| l.replace('\t', &" ".repeat(CODE_TAB_WIDTH)))
+            .collect();
+        let n_lines = lines.len().max(1);
+        let char_w = self.layout_text_with_font("0", text_size, MONO_FONT).width;
+        let digits = n_lines.to_string().len();
+        let gutter_w = digits as f32 * char_w + pad * 2.0;
+        let content_w = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as f32
+            * char_w
+            + pad * 2.0;
+
+        let width = self.available_content().x;
+        let view_w = (width - gutter_w).max(0.0);
+        let max_scroll = (content_w - view_w).max(0.0);
+        let bar_h = if max_scroll > 0.0 { (pad * 0.5).max(4.0) } else { 0.0 };
+        let rect = self.place_item(Vec2::new(width, n_lines as f32 * line_h + pad * 2.0 + bar_h));
+        let sig = self.reg_item_(id, rect);
+        if let Some(node) = self.reg_access_node(id, AccessRole::Label, label, rect) {
+            node.value = Some(source.to_string());
+        }
+
+        let view = Rect::from_min_max(
+            Vec2::new(rect.min.x + gutter_w, rect.min.y),
+            Vec2::new(rect.max.x, rect.max.y - bar_h),
+        );
+
+        let mut scroll = self.widget_data.get::<CodeScroll>(&id).map_or(0.0, |s| s.0);
+        if sig.hovering() {
+            let wheel = self.mouse.scroll;
+            let dx = if self.modifiers.shift_key() { wheel.x + wheel.y } else { wheel.x };
+            scroll -= dx;
+        }
+
+        let track = Rect::from_min_max(Vec2::new(view.min.x, view.max.y), rect.max);
+        let thumb_w = if content_w > 0.0 { track.width() * view_w / content_w } else { 0.0 };
+        let bar_id = Id::from_hash(&(id, "scrollbar"));
+        let bar_sig = if bar_h > 0.0 {
+            self.reg_item_active_on_press(bar_id, track)
+        } else {
+            Signal::NONE
+        };
+        if bar_sig.pressed() && !bar_sig.dragging() {
+            self.expect_drag = true;
+        }
+        if bar_sig.dragging() && track.width() > thumb_w {
+            scroll += self.mouse.delta.x * max_scroll / (track.width() - thumb_w);
+        }
+        let scroll = scroll.clamp(0.0, max_scroll);
+        self.widget_data.insert(id, CodeScroll(scroll));
+        let thumb_x = if max_scroll > 0.0 {
+            scroll / max_scroll * (track.width() - thumb_w)
+        } else {
+            0.0
+        };
+        let thumb = Rect::from_min_size(
+            track.min + Vec2::new(thumb_x, 0.0),
+            Vec2::new(thumb_w, bar_h),
+        );
+
+        let copy_shape = self.layout_text("copy", text_size);
+        let copy_rect = Rect::from_min_size(
+            Vec2::new(rect.max.x - copy_shape.width - pad * 2.0, rect.min.y),
+            Vec2::new(copy_shape.width + pad * 2.0, line_h),
+        );
+        let copy_id = Id::from_hash(&(id, "copy"));
+        let copy_sig = self.reg_item_active_on_press(copy_id, copy_rect);
+        self.reg_access_node(copy_id, AccessRole::Button, "copy", copy_rect);
+        if copy_sig.clicked() {
+            self.clipboard.set_text(source);
+        }
+        if copy_sig.hovering() {
+            self.set_cursor_icon(CursorIcon::Pointer);
+        }
+
+        if self.item_culled() {
+            return sig;
+        }
+
+        let corners = self.style.btn_corners();
+        let text_col = self.style.text_col();
+        self.draw(rect.draw_rect().fill(self.style.panel_dark_bg()).corners(corners));
+
+        let clip = self.current_drawlist().current_clip_rect();
+        let mut in_comment = false;
+        let mut gutter_rects = Vec::new();
+        let mut code_rects = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let tokens = highlight_line(lang, line, &mut in_comment);
+            let y = rect.min.y + pad + i as f32 * line_h;
+            if y + line_h < clip.min.y || y > clip.max.y {
+                continue;
+            }
+
+            let num = self.layout_text_with_font(&(i + 1).to_string(), text_size, MONO_FONT);
+            let num_pos = Vec2::new(view.min.x - pad - num.width, y + (line_h - num.height) * 0.5);
+            gutter_rects.extend(num.draw_rects(num_pos, text_col.with_alpha(0.4)));
+
+            if line.is_empty() {
+                continue;
+            }
+            let shape = self.layout_text_with_font(line, text_size, MONO_FONT);
+            let pos = Vec2::new(view.min.x + pad - scroll, y + (line_h - shape.height) * 0.5);
+            // monospace, so the column of a glyph tells which token it belongs to
+            let kinds = tokens.iter().flat_map(|(r, k)| r.clone().map(move |_| *k));
+            let kinds: Vec<CodeToken> = kinds.collect();
+            for (g, r) in shape.glyphs.iter().zip(shape.draw_rects(pos, text_col)) {
+                let column = ((g.meta.pos.x + g.meta.size.x * 0.5) / char_w).floor() as usize;
+                let kind = kinds.get(column).copied().unwrap_or_default();
+                code_rects.push(r.fill(kind.color().unwrap_or(text_col)));
+            }
+        }
+
+        // line numbers stay in place, only the code is clipped and scrolled
+        self.draw(gutter_rects);
+        self.current_drawlist().push_merged_clip_rect(view);
+        self.draw(code_rects);
+        self.current_drawlist().pop_clip_rect();
+
+        if bar_h > 0.0 {
+            let col = if bar_sig.dragging() || bar_sig.pressed() {
+                self.style.btn_press()
+            } else if bar_sig.hovering() {
+                self.style.btn_hover()
+            } else {
+                self.style.btn_default()
+            };
+            self.draw(thumb.draw_rect().fill(col).corners(bar_h * 0.5));
+        }
+
+        if sig.hovering() || copy_sig.hovering() {
+            let bg = if copy_sig.pressed() {
+                self.style.btn_press()
+            } else if copy_sig.hovering() {
+                self.style.btn_hover()
+            } else {
+                self.style.btn_default()
+            };
+            let text_pos = copy_rect.center() - copy_shape.size() * 0.5;
+            self.draw(copy_rect.shrink(2.0).draw_rect().fill(bg).corners(corners))
+                .draw(copy_shape.draw_rects(text_pos, text_col));
+        }
+
+        sig
+    }
+}
@@ -0,0 +1,252 @@
+//! composition of the frames of several contexts or draw lists into one output, see
+//! [`DrawComposer`]
+
+use glam::Vec2;
+
+use crate::{
+    gpu,
+    rect::Rect,
+    ui::{DrawCall, DrawCallList, DrawList, DrawOutput, EffectId, RenderData, UiEffect, Vertex},
+};
+
+/// where a composed layer is placed in the target
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerTransform {
+    pub offset: Vec2,
+    pub scale: f32,
+    /// clips the layer in target space, on top of the clip rects of its calls
+    pub clip: Option<Rect>,
+}
+
+impl Default for LayerTransform {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            scale: 1.0,
+            clip: None,
+        }
+    }
+}
+
+impl LayerTransform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_clip(mut self, clip: Rect) -> Self {
+        self.clip = Some(clip);
+        self
+    }
+
+    pub fn apply(&self, p: Vec2) -> Vec2 {
+        p * self.scale + self.offset
+    }
+
+    /// maps a clip rect of the layer into the target, intersected with [`Self::clip`]
+    pub fn apply_clip(&self, rect: Rect) -> Rect {
+        let rect = Rect::from_min_max(self.apply(rect.min), self.apply(rect.max));
+        let Some(clip) = self.clip else {
+            return rect;
+        };
+        // disjoint rects give an empty rect instead of a negative one
+        let rect = rect.intersect(clip);
+        Rect::from_min_max(rect.min, rect.max.max(rect.min))
+    }
+}
+
+impl DrawCallList {
+    /// appends the calls of `other` unmerged, after the existing calls
+    ///
+    /// positions and clip rects are mapped by `transform`, every texture id except the white
+    /// texture is offset by `tex_offset` and every effect id by `effect_offset`
+    pub fn append(
+        &mut self,
+        other: &DrawCallList,
+        transform: LayerTransform,
+        tex_offset: u32,
+        effect_offset: u32,
+    ) {
+        let mask_base = self.masks.len() as u32;
+        self.masks.extend(
+            other
+                .masks
+                .iter()
+                .map(|mask| mask.iter().map(|&p| transform.apply(p)).collect()),
+        );
+
+        for call in other.calls.iter().filter(|c| c.n_idx != 0) {
+            let vtx = &other.vtx_alloc[call.vtx_ptr..call.vtx_ptr + call.n_vtx];
+            let idx = &other.idx_alloc[call.idx_ptr..call.idx_ptr + call.n_idx];
+
+            let (vtx_end, idx_end) = (self.vtx_ptr + vtx.len(), self.idx_ptr + idx.len());
+            if self.vtx_alloc.len() < vtx_end {
+                self.vtx_alloc.resize(vtx_end, Vertex::ZERO);
+            }
+            if self.idx_alloc.len() < idx_end {
+                self.idx_alloc.resize(idx_end, 0);
+            }
+
+            // the vertices refer to the texture slots of the call, only the ids change
+            let dst = &mut self.vtx_alloc[self.vtx_ptr..vtx_end];
+            for (dst, src) in dst.iter_mut().zip(vtx) {
+                *dst = Vertex {
+                    pos: transform.apply(src.pos),
+                    ..*src
+                };
+            }
+            self.idx_alloc[self.idx_ptr..idx_end].copy_from_slice(idx);
+
+            let mut textures = call.textures;
+            textures.iter_mut().for_each(|id| *id += tex_offset);
+            self.calls.push(DrawCall {
+                clip_rect: transform.apply_clip(call.clip_rect),
+                clip_mask: if call.clip_mask == 0 { 0 } else { mask_base + call.clip_mask },
+                topology: call.topology,
+                effect: match call.effect {
+                    EffectId::NONE => EffectId::NONE,
+                    EffectId(id) => EffectId(id + effect_offset),
+                },
+                vtx_ptr: self.vtx_ptr,
+                idx_ptr: self.idx_ptr,
+                n_vtx: vtx.len(),
+                n_idx: idx.len(),
+                textures,
+            });
+
+            self.vtx_ptr = vtx_end;
+            self.idx_ptr = idx_end;
+        }
+    }
+}
+
+/// composes the frames of several contexts or draw lists into one [`DrawOutput`], e.g. a game
+/// hud on top of an editor ui, to paint them together with a [`Painter`](crate::Painter)
+///
+/// layers are drawn by ascending order, layers of the same order in the order they were pushed
+pub struct DrawComposer {
+    calls: DrawCallList,
+    /// order of the layer of every call
+    orders: Vec<i32>,
+    textures: Vec<gpu::Texture>,
+    effects: Vec<UiEffect>,
+    /// the latest time of the pushed outputs
+    time: f32,
+    /// counts the calls to [`Self::clear`], see [`DrawOutput::frame`]
+    frame: u64,
+    /// converts pushed draw lists into calls
+    scratch: DrawCallList,
+}
+
+impl Default for DrawComposer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DrawComposer {
+    pub fn new() -> Self {
+        let (max_vtx, max_idx) = (RenderData::MAX_VERTEX_COUNT, RenderData::MAX_INDEX_COUNT);
+        Self {
+            calls: DrawCallList::new(usize::MAX, usize::MAX),
+            orders: Vec::new(),
+            textures: Vec::new(),
+            effects: Vec::new(),
+            time: 0.0,
+            frame: 0,
+            scratch: DrawCallList::new(max_vtx as usize, max_idx as usize),
+        }
+    }
+
+    /// removes the layers of the last frame
+    pub fn clear(&mut self) {
+        self.calls.clear();
+        self.orders.clear();
+        self.textures.clear();
+        self.effects.clear();
+        self.time = 0.0;
+        self.frame += 1;
+    }
+
+    /// adds the frame of a context, e.g. from [`RenderData::output`]
+    pub fn push_output(&mut self, order: i32, output: &DrawOutput, transform: LayerTransform) {
+        let (tex_offset, effect_offset) = (self.textures.len() as u32, self.effects.len() as u32);
+        self.textures.extend_from_slice(output.textures);
+        self.effects.extend_from_slice(output.effects);
+        self.time = self.time.max(output.time);
+        self.calls.append(output.calls, transform, tex_offset, effect_offset);
+        self.orders.resize(self.calls.calls.len(), order);
+    }
+
+    /// adds a draw list whose texture ids refer to `textures`, clipped to `size`. effects are
+    /// not applied, see [`Self::push_drawlist_effects`]
+    pub fn push_drawlist(
+        &mut self,
+        order: i32,
+        list: &DrawList,
+        textures: &[gpu::Texture],
+        size: Vec2,
+        transform: LayerTransform,
+    ) {
+        let output = DrawOutput {
+            calls: &DrawCallList::new(0, 0),
+            textures,
+            effects: &[],
+            time: 0.0,
+            frame: 0,
+        };
+        self.push_drawlist_effects(order, list, &output, size, transform);
+    }
+
+    /// like [`Self::push_drawlist`], the texture and effect ids of the draw list refer to those
+    /// of `registry`, e.g. [`RenderData::output`], whose calls are ignored
+    pub fn push_drawlist_effects(
+        &mut self,
+        order: i32,
+        list: &DrawList,
+        registry: &DrawOutput,
+        size: Vec2,
+        transform: LayerTransform,
+    ) {
+        let mut scratch = std::mem::replace(&mut self.scratch, DrawCallList::new(0, 0));
+        scratch.clear();
+        scratch.push_drawlist(list, size);
+        let output = DrawOutput {
+            calls: &scratch,
+            ..*registry
+        };
+        self.push_output(order, &output, transform);
+        self.scratch = scratch;
+    }
+
+    /// the composed calls, sorted by the order of their layers
+    pub fn output(&mut self) -> DrawOutput<'_> {
+        if !self.orders.is_sorted() {
+            let mut keyed: Vec<(i32, DrawCall)> = self
+                .orders
+                .iter()
+                .copied()
+                .zip(self.calls.calls.iter().copied())
+                .collect();
+            keyed.sort_by_key(|(order, _)| *order);
+            (self.orders, self.calls.calls) = keyed.into_iter().unzip();
+        }
+
+        DrawOutput {
+            calls: &self.calls,
+            textures: &self.textures,
+            effects: &self.effects,
+            time: self.time,
+            frame: self.frame,
+        }
+    }
+}
@@ -8,13 +8,20 @@ use std::{
 use wgpu::util::DeviceExt;
 
 use crate::{
-    Vertex as VertexTyp, core::{
-        ArrVec, Axis, DataMap, Dir, HashMap, HashSet, Instant, RGBA, id_type, stacked_fields_struct
-    }, gpu::{self, RenderPassHandle, ShaderHandle, WGPU, WGPUHandle, Window, WindowId}, mouse::{Clipboard, CursorIcon, MouseBtn, MouseState}, rect::Rect, ui::{
-        self, CornerRadii, DockNodeFlag, DockNodeKind, DockTree, DrawCallList, DrawList, DrawableRects, FontTable, GlyphCache, Id, IdMap, ItemFlags, MAX_N_TEXTURES_PER_DRAW_CALL, NextPanelData, Outline, Panel, PanelAction, PanelFlag, PrevItemData, RenderData, RootId, ShapedText, Signal, StyleTable, StyleVar, TabBar, TextInputFlags, TextInputState, TextItem, TextItemCache, TextureId
+    Vertex as VertexTyp, accessibility::{AccessAction, AccessNode, AccessRole, AccessTree}, clipboard::Clipboard, core::{
+        ArrVec, Axis, Clock, DataMap, Dir, Duration, HashMap, HashSet, RGBA, id_type, stacked_fields_struct
+    }, gpu::{self, RenderPassHandle, ShaderHandle, WGPU, WGPUHandle, Window, WindowId}, i18n::Localization, mouse::{CursorIcon, ImeEvent, InputEvent, InputSettings, KeyCode, KeyboardState, Modifiers, MouseBtn, MouseState, Shortcut}, platform, rect::Rect, settings::{MonitorUiScale, Settings}, ui::{
+        self, AnchorTarget, AttentionAnim, AttentionItem, ClipShape, CornerRadii, DeferredQueue, DockNodeFlag, DockNodeKind, DockTree, DrawCallList, DrawList, DrawableRects, EdgePanel, EdgePanelState, FloatingPanel, FontTable, Frame, GlyphCache, IconFont, IconTable, Id, IdMap, ItemFlags, ItemPainter, LayoutDir, LayoutState, MAX_N_TEXTURES_PER_DRAW_CALL, NextPanelData, Occlusion, Outline, Panel, PanelAction, PanelFlag, PanelBudget, PanelFill, PanelLayout, PanelStats, PanelStyle, Picking, PrevItemData, RenderData, RootId, Sense, ShapeCache, ShapedText, Signal, Stroke, StyleTable, StyleVar, TabBar, TextInputFlags, TextInputState, TextItem, TextItemCache, TextureId, Viewport, Widget
     }
 };
 
+/// zoom factor per pixel of ctrl + wheel scrolling on [`PanelFlag::ZOOMABLE`] panels
+const PANEL_ZOOM_SPEED: f32 = 0.005;
+const PANEL_ZOOM_MIN: f32 = 0.25;
+const PANEL_ZOOM_MAX: f32 = 4.0;
+/// name of the [`Context::debug_panel`]
+const DEBUG_PANEL: &str = "Debug##_DEBUG_PANEL";
+
 pub fn is_in_resize_region(r: Rect, pnt: Vec2, thr: f32) -> Option<Dir> {
     let in_corner_region = |corner: Vec2| -> bool { corner.distance_squared(pnt) <= thr.powi(2) };
 
@@ -58,32 +65,36 @@ fn load_window_icon() -> (u32, u32, Vec<u8>) {
     (width, height, rgba)
 }
 
-fn dark_theme() -> StyleTable {
+pub(crate) fn dark_theme() -> StyleTable {
     use ui::StyleField as SF;
     use ui::StyleVar as SV;
     StyleTable::init(|f| {
-        let accent = RGBA::hex("#cbdfd4");
-        let btn_default = RGBA::hex("#4f5559");
-        let dark = RGBA::hex("#1d1d1d");
-        let btn_hover = RGBA::hex("#576a76");
+        let accent = macros::rgba!("#cbdfd4");
+        let btn_default = macros::rgba!("#4f5559");
+        let dark = macros::rgba!("#1d1d1d");
+        let btn_hover = macros::rgba!("#576a76");
 
         match f {
             SF::TitlebarColor => SV::TitlebarColor(dark),
             SF::TitlebarHeight => SV::TitlebarHeight(26.0),
             SF::WindowTitlebarHeight => SV::WindowTitlebarHeight(40.0),
             SF::TextSize => SV::TextSize(18.0),
-            SF::TextCol => SV::TextCol(RGBA::hex("#EEEBE1")),
+            SF::TextCol => SV::TextCol(macros::rgba!("#EEEBE1")),
+            SF::TextWrap => SV::TextWrap(ui::TextWrap::None),
+            SF::TextMaxLines => SV::TextMaxLines(None),
             SF::LineHeight => SV::LineHeight(24.0),
             SF::BtnRoundness => SV::BtnRoundness(0.15),
+            SF::BtnCornerRadii => SV::BtnCornerRadii(None),
             SF::BtnDefault => SV::BtnDefault(btn_default),
             SF::BtnHover => SV::BtnHover(btn_hover),
             SF::BtnPress => SV::BtnPress(accent),
             SF::BtnPressText => SV::BtnPressText(btn_default),
-            // SF::WindowBg => SV::WindowBg(RGBA::hex("#5c6b6f")),
+            // SF::WindowBg => SV::WindowBg(macros::rgba!("#5c6b6f")),
             SF::WindowBg => SV::WindowBg(dark),
-            SF::PanelBg => SV::PanelBg(RGBA::hex("#343B40")),
-            SF::PanelDarkBg => SV::PanelDarkBg(RGBA::hex("#282c34")),
+            SF::PanelBg => SV::PanelBg(macros::rgba!("#343B40")),
+            SF::PanelDarkBg => SV::PanelDarkBg(macros::rgba!("#282c34")),
             SF::PanelCornerRadius => SV::PanelCornerRadius(7.0),
+            SF::PanelCornerRadii => SV::PanelCornerRadii(None),
             SF::PanelOutline => SV::PanelOutline(Outline::center(dark, 2.0)),
             SF::PanelHoverOutline => SV::PanelHoverOutline(Outline::center(btn_hover, 2.0)),
             SF::ScrollbarWidth => SV::ScrollbarWidth(6.0),
@@ -91,19 +102,106 @@ fn dark_theme() -> StyleTable {
             SF::PanelPadding => SV::PanelPadding(10.0),
             SF::SpacingV => SV::SpacingV(1.0),
             SF::SpacingH => SV::SpacingH(12.0),
-            SF::Red => SV::Red(RGBA::hex("#e65858")),
+            SF::Red => SV::Red(macros::rgba!("#e65858")),
+            SF::UiScale => SV::UiScale(1.0),
+        }
+    })
+}
+
+/// black and white theme, text and controls meet the wcag AA contrast ratios
+/// (4.5 for text, 3 for the outlines of controls)
+pub(crate) fn high_contrast_theme() -> StyleTable {
+    use ui::StyleField as SF;
+    use ui::StyleVar as SV;
+    StyleTable::init(|f| {
+        let black = macros::rgba!("#000000");
+        let white = macros::rgba!("#ffffff");
+        let grey = macros::rgba!("#5c5c5c");
+        let blue = macros::rgba!("#1f5fd6");
+        let yellow = macros::rgba!("#ffff00");
+
+        match f {
+            SF::TitlebarColor => SV::TitlebarColor(black),
+            SF::TitlebarHeight => SV::TitlebarHeight(26.0),
+            SF::WindowTitlebarHeight => SV::WindowTitlebarHeight(40.0),
+            SF::TextSize => SV::TextSize(18.0),
+            SF::TextCol => SV::TextCol(white),
+            SF::TextWrap => SV::TextWrap(ui::TextWrap::None),
+            SF::TextMaxLines => SV::TextMaxLines(None),
+            SF::LineHeight => SV::LineHeight(24.0),
+            SF::BtnRoundness => SV::BtnRoundness(0.15),
+            SF::BtnCornerRadii => SV::BtnCornerRadii(None),
+            SF::BtnDefault => SV::BtnDefault(grey),
+            SF::BtnHover => SV::BtnHover(blue),
+            SF::BtnPress => SV::BtnPress(yellow),
+            SF::BtnPressText => SV::BtnPressText(black),
+            SF::WindowBg => SV::WindowBg(black),
+            SF::PanelBg => SV::PanelBg(black),
+            SF::PanelDarkBg => SV::PanelDarkBg(grey),
+            SF::PanelCornerRadius => SV::PanelCornerRadius(7.0),
+            SF::PanelCornerRadii => SV::PanelCornerRadii(None),
+            SF::PanelOutline => SV::PanelOutline(Outline::center(white, 2.0)),
+            SF::PanelHoverOutline => SV::PanelHoverOutline(Outline::center(yellow, 2.0)),
+            SF::ScrollbarWidth => SV::ScrollbarWidth(6.0),
+            SF::ScrollbarPadding => SV::ScrollbarPadding(5.0),
+            SF::PanelPadding => SV::PanelPadding(10.0),
+            SF::SpacingV => SV::SpacingV(1.0),
+            SF::SpacingH => SV::SpacingH(12.0),
+            SF::Red => SV::Red(macros::rgba!("#ff6b6b")),
+            SF::UiScale => SV::UiScale(1.0),
         }
     })
 }
 
+/// style vars pushed until the scope is dropped, see [`Context::scoped_style`]
+///
+/// derefs to the context so items can be added while the vars are active
+pub struct StyleScope<'a> {
+    ctx: &'a mut Context,
+    n_pushed: u32,
+}
+
+impl StyleScope<'_> {
+    /// pushes another var that is popped together with the others
+    pub fn push(mut self, var: StyleVar) -> Self {
+        self.ctx.push_style(var);
+        self.n_pushed += 1;
+        self
+    }
+}
+
+impl std::ops::Deref for StyleScope<'_> {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        self.ctx
+    }
+}
+
+impl std::ops::DerefMut for StyleScope<'_> {
+    fn deref_mut(&mut self) -> &mut Context {
+        self.ctx
+    }
+}
+
+impl Drop for StyleScope<'_> {
+    fn drop(&mut self) {
+        self.ctx.pop_style_n(self.n_pushed);
+    }
+}
+
 pub struct Context {
     // pub panels: HashMap<Id, Panel>,
     pub panels: IdMap<Panel>,
-    // TODO: cleanup?
+    /// restored layouts of panels that were not created yet
+    pub pending_layouts: HashMap<Id, PanelLayout>,
     pub widget_data: DataMap<Id>,
+    /// widget state not used for this many frames is removed at the end of the frame
+    pub widget_data_max_age: u64,
     pub docktree: DockTree,
     // pub style: Style,
     pub style: StyleTable,
+    pub panel_style_stack: Vec<PanelStyle>,
 
     pub current_panel_stack: Vec<Id>,
     pub current_panel_id: Id,
@@ -165,6 +263,16 @@ pub struct Context {
     pub prev_hot_tabbar_id: Id,
 
     pub window_panel_id: Id,
+    /// the area not taken by edge panels, see [`Self::edge_panel`]
+    pub central_rect: Rect,
+    /// areas of the viewports shown this frame, input over them is not used by the ui
+    pub viewport_ids: Vec<Id>,
+    /// computed at the end of each frame, see [`Self::wants_pointer_input`]
+    pub wants_pointer: bool,
+    /// computed at the end of each frame, see [`Self::wants_keyboard_input`]
+    pub wants_keyboard: bool,
+    /// number of rects allocated without an id this frame, see [`Self::allocate_rect`]
+    pub auto_id_count: u32,
 
     // some items can only be interacted with while dragging, e.g. sliders
     // just holding down the mouse will not register as a drag, only a press
@@ -178,25 +286,84 @@ pub struct Context {
     pub draw_full_content_outline: bool,
     pub draw_item_outline: bool,
     pub draw_position_bounds: bool,
+    /// overlays layout guides, see [`Self::set_debug_paint`]
+    pub debug_paint: bool,
+    /// panels exceeding it show a warning badge, see [`Self::set_panel_budget`]
+    pub panel_budget: Option<PanelBudget>,
+    /// the user is dragging a rect to capture, see [`Self::start_screenshot_mode`]
+    pub screenshot_mode: bool,
+    /// region to capture from the next rendered frame
+    pub capture_request: Option<Rect>,
+    /// records the rendered frames, see [`Self::start_recording`]
+    pub recorder: Option<gpu::FrameRecorder>,
+    /// drawn on top of all panels, cleared every frame
+    pub overlay: DrawList,
 
     pub circle_max_err: f32,
 
     pub frame_count: u64,
-    pub prev_frame_time: Instant,
+    pub clock: Clock,
+    /// clock time at the start of the current frame
+    pub frame_start: Duration,
+    pub delta_time: Duration,
 
     pub mouse: MouseState,
-    pub modifiers: winit::keyboard::ModifiersState,
+    pub modifiers: Modifiers,
     pub cursor_icon: CursorIcon,
     pub cursor_icon_changed: bool,
     pub resize_threshold: f32,
     pub undock_threshold: f32,
-    pub scroll_speed: f32,
+    pub input_settings: InputSettings,
+    pub keyboard: KeyboardState,
+    /// see [`LayoutDir`] for what is mirrored in `Rtl`
+    pub layout_dir: LayoutDir,
+    /// the style was created by [`high_contrast_theme`], see [`Self::set_high_contrast`]
+    pub high_contrast: bool,
+    /// animations jump to their end state, see [`Self::set_reduced_motion`]
+    pub reduced_motion: bool,
+    /// font of the text of the widgets, has to be loaded into the [`Self::font_table`]
+    pub text_font: &'static str,
+    /// ui scale of monitors without a stored scale, see [`Self::use_system_text_scale`]
+    pub default_ui_scale: f32,
+    /// store the ui scale per monitor in the [`Self::settings`], see [`Self::set_ui_scale`]
+    pub persist_ui_scale: bool,
+    /// the monitor the ui scale was restored for
+    pub monitor_ui_scale: MonitorUiScale,
     pub n_draw_calls: usize,
+    /// the last placed item is completely outside of the clip rect and does not need to be
+    /// drawn, see [`Self::item_culled`]
+    pub last_item_culled: bool,
+    /// the last placed item, see [`Self::place_item`]
+    pub last_item_rect: Rect,
+    /// length of the current drawlist when the last item was placed, only tracked while
+    /// [`Self::attention`] animations are running
+    pub last_item_vtx_start: usize,
+    pub(crate) attention_anims: HashMap<Id, AttentionAnim>,
+    pub(crate) attention_item: Option<AttentionItem>,
+    /// the items placed in a loading [`Self::loading_scope`], drawn as skeletons
+    pub(crate) skeleton_rects: Option<Vec<Rect>>,
+    /// visible part of the last placed item, see [`Self::item_hovered`]
+    pub last_item_clipped_rect: Option<Rect>,
+    /// the last registered item is covered by another item or panel under the cursor
+    pub last_item_occluded: bool,
+    /// hit regions of the frame, see [`Self::add_occluder`]
+    pub occlusion: Occlusion,
+    /// ids of the items registered this frame in debug builds, see [`Self::keyed`]
+    pub frame_item_ids: HashSet<Id>,
+    /// items placed and culled in the current frame
+    pub n_items: u32,
+    pub n_culled_items: u32,
+    /// items placed and culled in the previous frame
+    pub prev_n_items: u32,
+    pub prev_n_culled_items: u32,
+    pub access: AccessTree,
 
     pub draw: RenderData,
+    pub shape_cache: Rc<RefCell<ShapeCache>>,
     pub glyph_cache: RefCell<GlyphCache>,
     pub text_item_cache: RefCell<TextItemCache>,
     pub font_table: FontTable,
+    pub icons: IconTable,
     pub icon_uv: Rect,
 
     pub close_pressed: bool,
@@ -204,6 +371,14 @@ pub struct Context {
     pub requested_windows: Vec<(Vec2, Vec2)>,
     pub ext_window: Option<Window>,
     pub clipboard: Clipboard,
+    /// translations, see [`Self::tr`]
+    pub i18n: Localization,
+    /// opened on first use, see [`Self::settings`]
+    pub settings: Option<Settings>,
+    /// exact hit testing with an id buffer, see [`Self::set_picking`]
+    pub picking: Picking,
+    /// ui recorded on other threads, see [`Self::deferred_sender`]
+    pub deferred: DeferredQueue,
 
     pub wgpu: WGPUHandle,
 }
@@ -212,10 +387,16 @@ impl Context {
     pub fn new(wgpu: WGPUHandle, window: Window) -> Self {
         let mut font_table = FontTable::new();
         font_table.load_font(
-            "Inter",
+            ui::DEFAULT_FONT,
             include_bytes!("../res/Inter-VariableFont_opsz,wght.ttf").to_vec(),
         );
-        font_table.load_font("Phosphor", include_bytes!("../res/Phosphor.ttf").to_vec());
+        font_table.load_font(ui::phosphor_font::FAMILY, include_bytes!("../res/Phosphor.ttf").to_vec());
+        font_table.load_font(
+            ui::MONO_FONT,
+            include_bytes!("../res/CommitMono-400-Regular.otf").to_vec(),
+        );
+        let mut icons = IconTable::new();
+        icons.register(ui::phosphor_font::icon_font());
 
         let mut glyph_cache = GlyphCache::new(&wgpu, font_table.clone());
         let icon_uv = {
@@ -225,10 +406,13 @@ impl Context {
 
         Self {
             panels: IdMap::new(),
+            pending_layouts: HashMap::new(),
             widget_data: DataMap::new(),
+            widget_data_max_age: 3600,
             docktree: DockTree::new(),
             // style: Style::dark(),
             style: dark_theme(),
+            panel_style_stack: Vec::new(),
             draw: RenderData::new(glyph_cache.texture.clone(), wgpu.clone()),
             current_panel_stack: vec![],
 
@@ -247,6 +431,11 @@ impl Context {
             active_id_changed: false,
             active_panel_id: Id::NULL,
             window_panel_id: Id::NULL,
+            central_rect: Rect::ZERO,
+            viewport_ids: Vec::new(),
+            wants_pointer: false,
+            wants_keyboard: false,
+            auto_id_count: 0,
             // window_panel_titlebar_height: 0.0,
             panel_action: PanelAction::None,
             prev_hot_panel_id: Id::NULL,
@@ -272,23 +461,56 @@ impl Context {
             draw_content_outline: false,
             draw_full_content_outline: false,
             draw_item_outline: false,
+            debug_paint: false,
+            panel_budget: None,
+            screenshot_mode: false,
+            capture_request: None,
+            recorder: None,
+            overlay: DrawList::new(),
             draw_position_bounds: false,
             circle_max_err: 0.3,
 
             frame_count: 0,
-            prev_frame_time: Instant::now(),
+            clock: Clock::system(),
+            frame_start: Duration::ZERO,
+            delta_time: Duration::ZERO,
             mouse: MouseState::new(),
-            modifiers: winit::keyboard::ModifiersState::empty(),
+            modifiers: Modifiers::NONE,
             cursor_icon: CursorIcon::Default,
             cursor_icon_changed: false,
             resize_threshold: 5.0,
             undock_threshold: 50.0,
-            scroll_speed: 1.0,
+            input_settings: InputSettings::default(),
+            keyboard: KeyboardState::new(),
+            layout_dir: LayoutDir::Ltr,
+            high_contrast: false,
+            reduced_motion: false,
+            text_font: ui::DEFAULT_FONT,
+            default_ui_scale: 1.0,
+            persist_ui_scale: false,
+            monitor_ui_scale: MonitorUiScale::default(),
             n_draw_calls: 0,
-
+            last_item_culled: false,
+            last_item_rect: Rect::ZERO,
+            last_item_vtx_start: 0,
+            attention_anims: HashMap::new(),
+            attention_item: None,
+            skeleton_rects: None,
+            last_item_clipped_rect: None,
+            last_item_occluded: false,
+            occlusion: Occlusion::default(),
+            frame_item_ids: HashSet::new(),
+            n_items: 0,
+            n_culled_items: 0,
+            prev_n_items: 0,
+            prev_n_culled_items: 0,
+            access: AccessTree::new(),
+
+            shape_cache: Rc::new(RefCell::new(ShapeCache::new())),
             glyph_cache: RefCell::new(glyph_cache),
             text_item_cache: RefCell::new(TextItemCache::new()),
             font_table,
+            icons,
             icon_uv,
 
             close_pressed: false,
@@ -296,6 +518,10 @@ impl Context {
             requested_windows: Vec::new(),
             ext_window: None,
             clipboard: Clipboard::new(),
+            i18n: Localization::default(),
+            settings: None,
+            picking: Picking::default(),
+            deferred: DeferredQueue::new(),
 
             wgpu,
         }
@@ -342,21 +568,46 @@ impl Context {
         }
     }
 
-    pub fn on_key_event(&mut self, key: &winit::event::KeyEvent) {
-        use winit::{
-            event::ElementState,
-            keyboard::{KeyCode, PhysicalKey},
-        };
+    /// feeds a platform independent input event, for integrations that run their own event loop
+    pub fn on_event(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::PointerMoved(pos) => self.set_mouse_pos(pos.x, pos.y),
+            InputEvent::PointerButton { button, pressed } => {
+                self.set_mouse_press(*button, *pressed)
+            }
+            InputEvent::Wheel(delta) => self.set_mouse_scroll(*delta),
+            InputEvent::Key { code, pressed } => self.on_key_event(*code, *pressed),
+            InputEvent::Text(text) => self.on_text_input(text),
+            InputEvent::Ime(ImeEvent::Commit(text)) => self.on_text_input(text),
+            // the preedit text is not displayed, only the committed text is inserted
+            InputEvent::Ime(_) => (),
+            InputEvent::Modifiers(modifiers) => self.modifiers = *modifiers,
+            InputEvent::Focused(focused) => {
+                // we won't receive the release events of keys held while unfocused
+                if !focused {
+                    self.keyboard.reset();
+                }
+            }
+        }
+    }
 
-        if !matches!(key.state, ElementState::Pressed) || self.active_id.is_null() {
+    /// `code` is `None` for keys without a [`KeyCode`]
+    pub fn on_key_event(&mut self, code: Option<KeyCode>, pressed: bool) {
+        if let Some(code) = code {
+            let now = self.clock.now();
+            self.keyboard
+                .set_key_press(code, pressed, now, &self.input_settings);
+        }
+
+        if !pressed || self.active_id.is_null() {
             return;
         }
 
         let ctrl = self.modifiers.control_key();
         let shift = self.modifiers.shift_key();
 
-        match key.physical_key {
-            PhysicalKey::Code(KeyCode::Tab) => {
+        match code {
+            Some(KeyCode::Tab) => {
                 if shift {
                     self.kb_focus_prev_item = true;
                 } else {
@@ -367,63 +618,121 @@ impl Context {
         }
 
         if let Some(input) = self.widget_data.get_mut::<TextInputState>(&self.active_id) {
-            match key.physical_key {
-                PhysicalKey::Code(KeyCode::ArrowRight) => {
-                    input.move_cursor_right(&self.modifiers);
-                }
-                PhysicalKey::Code(KeyCode::ArrowLeft) => {
-                    input.move_cursor_left(&self.modifiers);
-                }
-                PhysicalKey::Code(KeyCode::ArrowDown) => {
-                    input.move_cursor_down(&self.modifiers);
-                }
-                PhysicalKey::Code(KeyCode::ArrowUp) => {
-                    input.move_cursor_up(&self.modifiers);
-                }
-                PhysicalKey::Code(KeyCode::Backspace) => {
-                    input.backspace(&self.modifiers);
-                }
-                PhysicalKey::Code(KeyCode::KeyV) if ctrl => {
-                    if let Some(text) = self.clipboard.get_text() {
-                        input.paste(&text);
-                    }
-                }
-                PhysicalKey::Code(KeyCode::KeyC) if ctrl => {
+            match code {
+                // handled with our own key repeat in update_text_input_keys
+                Some(
+                    KeyCode::ArrowRight
+                    | KeyCode::ArrowLeft
+                    | KeyCode::ArrowDown
+                    | KeyCode::ArrowUp
+                    | KeyCode::Backspace
+                    | KeyCode::Delete,
+                ) => (),
+                // pasted in update_text_input_keys once the text arrived
+                Some(KeyCode::KeyV) if ctrl => self.clipboard.request_text(),
+                Some(KeyCode::KeyC) if ctrl => {
                     if let Some(text) = input.copy_selection() {
                         self.clipboard.set_text(&text);
                     }
                 }
-                PhysicalKey::Code(KeyCode::KeyX) if ctrl => {
+                Some(KeyCode::KeyX) if ctrl => {
                     if let Some(text) = input.copy_selection() {
                         self.clipboard.set_text(&text);
                         input.delete_selection();
                     }
                 }
-                PhysicalKey::Code(KeyCode::KeyA) if ctrl => {
+                Some(KeyCode::KeyA) if ctrl => {
                     input.select_all();
                 }
-                PhysicalKey::Code(KeyCode::Delete) => input.delete(),
-                PhysicalKey::Code(KeyCode::Enter) => {
+                Some(KeyCode::Enter) => {
                     if input.multiline {
                         input.enter()
                     } else {
                         self.active_id = Id::NULL;
                     }
                 }
-                _ => {
-                    if let Some(text) = &key.text {
-                        input.paste(&text);
-                    }
-                }
+                _ => (),
             }
         }
     }
 
+    /// inserts text into the active text input
+    pub fn on_text_input(&mut self, text: &str) {
+        // ctrl is used for shortcuts, ctrl + alt is altgr on windows
+        if self.modifiers.control_key() && !self.modifiers.alt_key() {
+            return;
+        }
+
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(input) = self.widget_data.get_mut::<TextInputState>(&self.active_id) {
+            input.paste(&text);
+        }
+    }
+
+    /// navigation and deletion keys of the active text input, repeated while held
+    fn update_text_input_keys(&mut self) {
+        let pasted = self.clipboard.take_text();
+        let Some(input) = self.widget_data.get_mut::<TextInputState>(&self.active_id) else {
+            return;
+        };
+        if let Some(text) = pasted {
+            input.paste(&text);
+        }
+        let kb = &self.keyboard;
+        let modifiers = self.modifiers;
+
+        if kb.pressed_with_repeat(KeyCode::ArrowRight) {
+            input.move_cursor_right(modifiers);
+        }
+        if kb.pressed_with_repeat(KeyCode::ArrowLeft) {
+            input.move_cursor_left(modifiers);
+        }
+        if kb.pressed_with_repeat(KeyCode::ArrowDown) {
+            input.move_cursor_down(modifiers);
+        }
+        if kb.pressed_with_repeat(KeyCode::ArrowUp) {
+            input.move_cursor_up(modifiers);
+        }
+        if kb.pressed_with_repeat(KeyCode::Backspace) {
+            input.backspace(modifiers);
+        }
+        if kb.pressed_with_repeat(KeyCode::Delete) {
+            input.delete();
+        }
+    }
+
+    pub fn key_pressed(&self, key: KeyCode) -> bool {
+        self.keyboard.pressed(key)
+    }
+
+    pub fn key_just_pressed(&self, key: KeyCode) -> bool {
+        self.keyboard.just_pressed(key)
+    }
+
+    /// the key of `shortcut` was just pressed while exactly its modifiers are held
+    pub fn shortcut_pressed(&self, shortcut: Shortcut) -> bool {
+        self.modifiers == shortcut.modifiers && self.keyboard.just_pressed(shortcut.key)
+    }
+
+    /// true on the first frame the key is pressed and then repeatedly while it is held,
+    /// see [`InputSettings::key_repeat_delay`] and [`InputSettings::key_repeat_interval`]
+    pub fn key_pressed_with_repeat(&self, key: KeyCode) -> bool {
+        self.keyboard.pressed_with_repeat(key)
+    }
+
     // TODO[BUG]: scrolling on mousepad with two fingers upwards and one finger leaves the mousepad results
     // in a scroll upwards
     // TODO[NOTE]: we need acceleration (or maybe smoothing) when scrolling. or momentum
     pub fn set_mouse_scroll(&mut self, delta: Vec2) {
-        let delta = delta * self.scroll_speed;
+        let delta = delta * self.input_settings.scroll_speed;
+        if self.modifiers.control_key() && self.zoom_hot_panel(delta.y) {
+            return;
+        }
+        self.mouse.scroll += delta;
         // If we recently hovered over a tabbar, attempt to scroll its tabs horizontally.
         // Only consume the wheel event if the tabbar can actually move; otherwise fall through
         // so parent panels can handle scrolling.
@@ -463,7 +772,24 @@ impl Context {
         target.set_scroll(delta);
     }
 
+    /// zooms the hovered [`PanelFlag::ZOOMABLE`] panel or its closest zoomable parent,
+    /// returns false if there is none
+    fn zoom_hot_panel(&mut self, delta: f32) -> bool {
+        let mut id = self.hot_panel_id;
+        while !id.is_null() {
+            let p = &mut self.panels[id];
+            if p.flags.has(PanelFlag::ZOOMABLE) {
+                let zoom = p.zoom * (delta * PANEL_ZOOM_SPEED).exp();
+                p.zoom = zoom.clamp(PANEL_ZOOM_MIN, PANEL_ZOOM_MAX);
+                return true;
+            }
+            id = p.parent_id;
+        }
+        false
+    }
+
     pub fn set_mouse_press(&mut self, btn: MouseBtn, press: bool) {
+        self.mouse.time = self.clock.now();
         self.mouse.set_button_press(btn, press);
 
         let w_size = self.window.window_size();
@@ -535,6 +861,16 @@ impl Context {
         list.pop_clip_rect();
     }
 
+    pub fn push_clip_mask(&self, shape: ClipShape) {
+        let list = &self.get_current_panel().drawlist;
+        list.push_clip_mask(shape);
+    }
+
+    pub fn pop_clip_mask(&self) {
+        let list = &self.get_current_panel().drawlist;
+        list.pop_clip_mask();
+    }
+
     pub fn draw(&self, itm: impl DrawableRects) -> &Self {
         let list = &self.get_current_panel().drawlist;
         itm.add_to_drawlist(list);
@@ -573,6 +909,19 @@ impl Context {
         }
     }
 
+    /// same as [`Context::gen_id`], but the label is only hashed once
+    ///
+    /// the label hash is cached by the address of the string, so this is cheaper for the
+    /// string literals most widgets are created with
+    pub fn gen_id_static(&self, label: &'static str) -> Id {
+        if self.current_panel_id.is_null() {
+            Id::from_static_str(label)
+        } else {
+            self.get_current_panel()
+                .gen_local_id_from_hash(crate::core::static_str_hash64(label))
+        }
+    }
+
     pub fn register_texture(&mut self, tex: &gpu::Texture) -> TextureId {
         if let Some(idx) = self.draw.texture_reg.iter().position(|t| t == tex) {
             return TextureId(idx as u64 + 1);
@@ -620,6 +969,15 @@ impl Context {
     }
 
     pub fn update_draworder(&mut self) {
+        // always on top roots stay above the others, the order within both groups is kept
+        let panels = &self.panels;
+        self.draworder.sort_by_key(|&r| match r {
+            RootId::Panel(id) => panels
+                .get(id)
+                .is_some_and(|p| p.flags.has(PanelFlag::ALWAYS_ON_TOP)),
+            RootId::Dock(_) => false,
+        });
+
         let mut order = 1;
 
         fn update_panel_order(ctx: &mut Context, id: Id, order: &mut usize) {
@@ -678,6 +1036,94 @@ impl Context {
         }
     }
 
+    /// snapshot of the panel positions, sizes and the dock tree, e.g. to store in a settings file
+    pub fn layout_state(&self) -> LayoutState {
+        let mut panels: Vec<PanelLayout> = self
+            .panels
+            .iter()
+            .filter(|(_, p)| !p.flags.has(PanelFlag::IS_CHILD))
+            .map(|(&id, p)| PanelLayout {
+                id,
+                name: p.name.clone(),
+                pos: p.pos,
+                size: p.size,
+                size_pre_dock: p.size_pre_dock,
+                dock_id: p.dock_id,
+            })
+            .collect();
+        panels.sort_by_key(|p| p.id);
+
+        LayoutState {
+            panels,
+            docktree: self.docktree.clone(),
+            draworder: self.draworder.clone(),
+        }
+    }
+
+    /// applies a layout returned by [`Self::layout_state`]
+    ///
+    /// panels that don't exist yet get their layout when they are created. the dock tree is only
+    /// restored if all docked panels already exist, otherwise the panels are left undocked
+    pub fn restore_layout(&mut self, state: &LayoutState) {
+        let can_dock = state
+            .panels
+            .iter()
+            .all(|p| p.dock_id.is_null() || self.panels.contains_id(p.id));
+        if !can_dock {
+            log::warn!("restoring layout without docking, not all docked panels exist yet");
+        }
+
+        self.reset_docktree();
+        self.pending_layouts.clear();
+
+        for l in &state.panels {
+            let mut l = l.clone();
+            if !can_dock {
+                l.dock_id = Id::NULL;
+            }
+            match self.panels.get_mut(l.id) {
+                Some(p) => {
+                    p.pos = l.pos;
+                    p.size = l.size;
+                    p.size_pre_dock = l.size_pre_dock;
+                    p.dock_id = l.dock_id;
+                }
+                None => {
+                    self.pending_layouts.insert(l.id, l);
+                }
+            }
+        }
+
+        if !can_dock {
+            return;
+        }
+
+        self.docktree = state.docktree.clone();
+        let panels = &self.panels;
+        let docktree = &self.docktree;
+        let mut draworder: Vec<RootId> = state
+            .draworder
+            .iter()
+            .copied()
+            .filter(|&r| match r {
+                RootId::Panel(id) => panels.get(id).is_some_and(|p| p.dock_id.is_null()),
+                RootId::Dock(id) => docktree.nodes.contains_id(id),
+            })
+            .collect();
+
+        // panels that were created after the layout was stored
+        for r in &self.draworder {
+            if let RootId::Panel(id) = *r {
+                if self.panels[id].dock_id.is_null() && !draworder.contains(r) {
+                    draworder.push(*r);
+                }
+            }
+        }
+
+        self.draworder = draworder;
+        self.update_draworder();
+    }
+
     pub fn bring_to_front(&mut self, id: RootId) {
         let idx = self.draworder.iter().position(|&i| i == id).unwrap();
         self.draworder.remove(idx);
@@ -685,6 +1131,58 @@ impl Context {
         self.update_draworder();
     }
 
+    /// moves `id` below all other roots except for the background, i.e. the window panel and the
+    /// dockspace
+    pub fn send_to_back(&mut self, id: RootId) {
+        let Some(idx) = self.draworder.iter().position(|&i| i == id) else {
+            return;
+        };
+        self.draworder.remove(idx);
+        let first = self
+            .draworder
+            .iter()
+            .position(|&r| !self.is_background_root(r))
+            .unwrap_or(self.draworder.len());
+        self.draworder.insert(first, id);
+        self.update_draworder();
+    }
+
+    /// roots that are never raised, they stay below all other roots
+    fn is_background_root(&self, r: RootId) -> bool {
+        match r {
+            RootId::Panel(id) => self.panels[id].flags.has(PanelFlag::NO_FOCUS),
+            RootId::Dock(id) => self.docktree.nodes[id]
+                .flags
+                .has(DockNodeFlag::NO_BRING_TO_FRONT),
+        }
+    }
+
+    /// the counterpart of [`Self::bring_panel_to_front`], moves the root of the panel below the
+    /// other roots
+    pub fn send_panel_to_back(&mut self, id: Id) {
+        let p = &self.panels[id];
+        if !p.parent_id.is_null() {
+            // children are drawn in the order they are created each frame
+            self.send_panel_to_back(p.parent_id);
+        } else if !p.dock_id.is_null() {
+            let dock_root = self.docktree.get_root(p.dock_id);
+            if !self.is_background_root(RootId::Dock(dock_root)) {
+                self.send_to_back(RootId::Dock(dock_root));
+            }
+        } else {
+            self.send_to_back(RootId::Panel(id));
+        }
+    }
+
+    /// the panel drawn on top at `pos`, ignoring [`PanelFlag::NO_FOCUS`] panels. items of other
+    /// panels under the cursor are not hovered
+    pub fn topmost_panel_at(&self, pos: Vec2) -> Option<Id> {
+        self.get_panels_in_order().into_iter().rev().find(|&id| {
+            let p = &self.panels[id];
+            !p.flags.has(PanelFlag::NO_FOCUS) && p.clip_rect.contains(pos)
+        })
+    }
+
     pub fn bring_panel_to_front(&mut self, id: Id) {
         let p = &mut self.panels[id];
         if !p.parent_id.is_null() {
@@ -749,6 +1247,7 @@ impl Context {
         self.next.pos = Vec2::new(0.0, win_tb_height);
         self.next.size = win_size - self.next.pos;
         let dockspace_rect = Rect::from_min_size(self.next.pos, self.next.size);
+        self.central_rect = dockspace_rect;
 
         self.push_style(StyleVar::PanelBg(RGBA::ZERO));
         self.push_style(StyleVar::PanelOutline(Outline::none()));
@@ -785,6 +1284,350 @@ impl Context {
         }
     }
 
+    /// shows a panel attached to an edge of the window and removes its space from the central
+    /// area
+    ///
+    /// has to be called before the panels docked into the dockspace. panels shown first take
+    /// the full length of their edge
+    pub fn edge_panel(&mut self, panel: EdgePanel, f: impl FnOnce(&mut Self)) -> Rect {
+        let id = self.gen_glob_id(&panel.name);
+        let strip = self.style.titlebar_height();
+
+        let open = panel.default_open || !panel.collapsible;
+        let mut state = *self.widget_data.get_or_insert(
+            id,
+            EdgePanelState {
+                size: panel.default_size,
+                open,
+                pinned: true,
+                anim: if open { 1.0 } else { 0.0 },
+            },
+        );
+        if !panel.collapsible {
+            state.open = true;
+            state.pinned = true;
+        }
+        state.size = panel.clamp_size(state.size);
+
+        let resize_id = Id::from_hash(&(id, "##_EDGE_RESIZE"));
+        if !state.pinned {
+            let hovered = self
+                .panels
+                .get(id)
+                .is_some_and(|p| p.panel_rect().contains(self.mouse.pos));
+            state.open = hovered || self.active_id == resize_id;
+        }
+
+        let target = if state.open { 1.0 } else { 0.0 };
+        if state.anim != target {
+            let step = if panel.anim_duration > 0.0 && !self.reduced_motion {
+                self.delta_time.as_secs_f32() / panel.anim_duration
+            } else {
+                1.0
+            };
+            state.anim = if target > state.anim {
+                (state.anim + step).min(target)
+            } else {
+                (state.anim - step).max(target)
+            };
+            self.window.request_redraw();
+        }
+
+        // smoothstep
+        let t = state.anim * state.anim * (3.0 - 2.0 * state.anim);
+        let min_extent = if panel.collapsible { strip } else { 0.0 };
+        let extent = min_extent + (state.size - min_extent).max(0.0) * t;
+        let reserved = if state.pinned { extent } else { min_extent };
+
+        let area = self.central_rect;
+        let (rect, _) = EdgePanel::split_area(panel.side, area, extent);
+        let (_, rest) = EdgePanel::split_area(panel.side, area, reserved);
+        self.central_rect = rest;
+        self.update_dockspace_rect();
+
+        self.next.pos = rect.min;
+        self.next.size = rect.size();
+        self.push_panel_style(PanelStyle::default().with_corners(0.0));
+        self.begin_ex(
+            &panel.name,
+            PanelFlag::NO_TITLEBAR
+                | PanelFlag::NO_MOVE
+                | PanelFlag::NO_RESIZE
+                | PanelFlag::NO_FOCUS
+                | PanelFlag::NO_DOCK_TARGET
+                | PanelFlag::NO_DOCKING
+                | PanelFlag::DRAW_V_SCROLLBAR,
+        );
+        self.pop_panel_style();
+
+        // unpinned panels float over the central area
+        let root = RootId::Panel(id);
+        if !state.pinned && state.anim > 0.0 && self.draworder.last() != Some(&root) {
+            self.bring_to_front(root);
+        }
+
+        use ui::phosphor_font::{CARET_DOWN, CARET_LEFT, CARET_RIGHT, CARET_UP};
+        let (collapse_icon, expand_icon) = match panel.side {
+            Dir::N => (CARET_UP, CARET_DOWN),
+            Dir::S => (CARET_DOWN, CARET_UP),
+            Dir::E => (CARET_RIGHT, CARET_LEFT),
+            _ => (CARET_LEFT, CARET_RIGHT),
+        };
+        let btn_rect = |pos: Vec2| Rect::from_min_size(pos, Vec2::splat(strip));
+
+        if state.anim == 0.0 {
+            if self.edge_panel_btn("##_EDGE_EXPAND", expand_icon, btn_rect(rect.min)) {
+                state.open = true;
+                state.pinned = true;
+            }
+        } else {
+            if panel.header {
+                let header = Rect::from_min_size(rect.min, Vec2::new(rect.width(), strip));
+                self.draw_over(header.draw_rect().fill(self.style.titlebar_color()));
+
+                let title = self.layout_text(&panel.name, self.style.text_size());
+                let pad = (strip - title.height) / 2.0;
+                self.draw_over(title.draw_rects(rect.min + pad, self.style.text_col()));
+
+                if panel.collapsible {
+                    let mut btn_pos = Vec2::new(rect.max.x - strip, rect.min.y);
+                    if state.pinned {
+                        let collapse_rect = btn_rect(btn_pos);
+                        if self.edge_panel_btn("##_EDGE_COLLAPSE", collapse_icon, collapse_rect) {
+                            state.open = false;
+                        }
+                        btn_pos.x -= strip;
+                    }
+
+                    let pin_icon = if state.pinned {
+                        ui::phosphor_font::PUSH_PIN
+                    } else {
+                        ui::phosphor_font::PUSH_PIN_SLASH
+                    };
+                    if self.edge_panel_btn("##_EDGE_PIN", pin_icon, btn_rect(btn_pos)) {
+                        state.pinned = !state.pinned;
+                        state.open = true;
+                    }
+                }
+
+                self.move_down(strip);
+            }
+
+            f(self);
+
+            if panel.resizable {
+                let thr = self.resize_threshold;
+                let handle = match panel.side {
+                    Dir::N => Rect::from_min_max(Vec2::new(rect.min.x, rect.max.y - thr), rect.max),
+                    Dir::S => Rect::from_min_max(rect.min, Vec2::new(rect.max.x, rect.min.y + thr)),
+                    Dir::E => Rect::from_min_max(rect.min, Vec2::new(rect.min.x + thr, rect.max.y)),
+                    _ => Rect::from_min_max(Vec2::new(rect.max.x - thr, rect.min.y), rect.max),
+                };
+
+                let sig = self.reg_item_active_on_press(resize_id, handle);
+                if sig.hovering() || sig.pressed() {
+                    self.set_cursor_icon(match panel.axis() {
+                        Axis::X => CursorIcon::MoveH,
+                        Axis::Y => CursorIcon::MoveV,
+                    });
+                }
+                if sig.pressed() {
+                    let m = self.mouse.pos;
+                    let size = match panel.side {
+                        Dir::N => m.y - rect.min.y,
+                        Dir::S => rect.max.y - m.y,
+                        Dir::E => rect.max.x - m.x,
+                        _ => m.x - rect.min.x,
+                    };
+                    state.size = panel.clamp_size(size);
+                }
+            }
+        }
+
+        self.end();
+        self.widget_data.insert(id, state);
+        rect
+    }
+
+    /// opens or collapses a shown edge panel, see [`Self::edge_panel`]
+    pub fn set_edge_panel_open(&mut self, name: &str, open: bool) {
+        let id = self.gen_glob_id(name);
+        if let Some(state) = self.widget_data.get_mut::<EdgePanelState>(&id) {
+            state.open = open;
+            state.pinned = true;
+        }
+    }
+
+    /// shows a movable panel kept between the min and max size of `panel`
+    ///
+    /// returns the rect of the panel
+    pub fn floating_panel(&mut self, panel: FloatingPanel, f: impl FnOnce(&mut Self)) -> Rect {
+        self.next.min_size = panel.min_size;
+        self.next.max_size = panel.max_size;
+        self.next.auto_size = panel.auto_size;
+        self.next.anchor = panel.anchor;
+
+        let mut flags = panel.flags;
+        if panel.auto_size {
+            flags |= PanelFlag::NO_RESIZE;
+        }
+        self.begin_ex(&panel.name, flags);
+        f(self);
+        let rect = self.get_current_panel().panel_rect();
+        self.end();
+        rect
+    }
+
+    fn edge_panel_btn(&mut self, label: &str, icon: &str, rect: Rect) -> bool {
+        let id = self.gen_id(label);
+        let sig = self.reg_item_active_on_release(id, rect);
+
+        let color = if sig.hovering() {
+            self.style.btn_hover()
+        } else {
+            self.style.text_col()
+        };
+        let shape = self.layout_icon(icon, self.style.text_size());
+        self.draw_over(shape.draw_rects(rect.center() - shape.size() / 2.0, color));
+
+        sig.released()
+    }
+
+    /// fits the dockspace into the central area
+    fn update_dockspace_rect(&mut self) {
+        let Some(p) = self.panels.get(self.gen_glob_id("##_DOCK_SPACE")) else {
+            return;
+        };
+        if p.dock_id.is_null() {
+            return;
+        }
+        let dock_root = self.docktree.get_root(p.dock_id);
+        self.docktree.recompute_rects(dock_root, self.central_rect);
+    }
+
+    /// a dockable panel with a transparent background for the scene rendered below the ui
+    ///
+    /// when first created it is docked into the dockspace, so it fills the central area left
+    /// by the edge panels
+    pub fn central_viewport(&mut self, name: &str) -> Viewport {
+        let id = self.gen_glob_id(name);
+        if !self.panels.contains_id(id) && !self.pending_layouts.contains_key(&id) {
+            self.panel_id(name);
+            self.dock_to_dockspace(id, 1.0, Dir::E);
+        }
+
+        self.push_panel_style(PanelStyle::default().with_fill(RGBA::ZERO));
+        self.begin_ex(name, PanelFlag::NONE);
+        self.pop_panel_style();
+
+        let p = self.get_current_panel();
+        let rect = Rect::from_min_max(p.pos + Vec2::new(0.0, p.titlebar_height), p.pos + p.size);
+        let area_id = self.gen_id("##_VIEWPORT_AREA");
+        let signal = self.reg_item_active_on_press(area_id, rect);
+        self.viewport_ids.push(area_id);
+        self.end();
+
+        let hovered = signal.hovering();
+        let focused = self.active_panel_id == id;
+        Viewport {
+            id,
+            rect,
+            hovered,
+            focused,
+            signal,
+            mouse_pos: self.mouse.pos - rect.min,
+            mouse_delta: if hovered || signal.pressed() {
+                self.mouse.delta
+            } else {
+                Vec2::ZERO
+            },
+            scroll: if hovered { self.mouse.scroll } else { Vec2::ZERO },
+            keyboard: focused && !self.text_input_active(),
+        }
+    }
+
+    /// removes all state stored for the widget `id`, returns the number of removed entries
+    pub fn forget(&mut self, id: Id) -> usize {
+        self.widget_data.remove_all(&id)
+    }
+
+    /// the state of type `T` stored for `id`, inserts `default()` if there is none
+    ///
+    /// state is kept per id and type, so one id can hold values of several types
+    pub fn data_get_or_insert_with<T: 'static>(
+        &mut self,
+        id: Id,
+        default: impl FnOnce() -> T,
+    ) -> &mut T {
+        self.widget_data.get_or_insert_with(id, default)
+    }
+
+    pub fn data_get<T: 'static>(&self, id: Id) -> Option<&T> {
+        self.widget_data.get::<T>(&id)
+    }
+
+    pub fn data_get_mut<T: 'static>(&mut self, id: Id) -> Option<&mut T> {
+        self.widget_data.get_mut::<T>(&id)
+    }
+
+    pub fn data_insert<T: 'static>(&mut self, id: Id, value: T) {
+        self.widget_data.insert(id, value);
+    }
+
+    /// removes and returns the state of type `T` stored for `id`
+    pub fn data_remove<T: 'static>(&mut self, id: Id) -> Option<T> {
+        self.widget_data.remove::<T>(&id)
+    }
+
+    /// id, type name and the frame of the last use of all retained widget state
+    pub fn retained_state(&self) -> Vec<(Id, &'static str, u64)> {
+        let mut entries: Vec<_> = self
+            .widget_data
+            .entries()
+            .map(|(id, ty, frame)| (*id, ty, frame))
+            .collect();
+        entries.sort_by_key(|(_, ty, frame)| (*ty, std::cmp::Reverse(*frame)));
+        entries
+    }
+
+    /// a text field is edited and receives the keyboard input
+    pub fn text_input_active(&self) -> bool {
+        self.widget_data.contains_key::<TextInputState>(&self.active_id)
+    }
+
+    /// the pointer is over the ui or the ui is interacting with it, e.g. a slider is dragged
+    ///
+    /// pointer events should only be handled by the app when this is false
+    pub fn wants_pointer_input(&self) -> bool {
+        self.wants_pointer
+    }
+
+    /// keyboard events are used by the ui, e.g. a text field is edited
+    pub fn wants_keyboard_input(&self) -> bool {
+        self.wants_keyboard
+    }
+
+    fn update_wants_input(&mut self) {
+        let is_ui_id = |id: Id| !id.is_null() && !self.viewport_ids.contains(&id);
+
+        let dockspace_id = self.gen_glob_id("##_DOCK_SPACE");
+        let over_panel = !self.hot_panel_id.is_null()
+            && self.hot_panel_id != self.window_panel_id
+            && self.hot_panel_id != dockspace_id;
+        let hovers_ui = if self.hot_id.is_null() {
+            over_panel
+        } else {
+            is_ui_id(self.hot_id)
+        };
+        let interacting = !self.panel_action.is_none()
+            || (self.mouse.pressed(MouseBtn::Left) && is_ui_id(self.active_id));
+        self.wants_pointer = hovers_ui || interacting;
+
+        // an active item takes the tab key for keyboard navigation
+        let item_active = is_ui_id(self.active_id) && !self.panels.contains_id(self.active_id);
+        self.wants_keyboard = self.text_input_active() || item_active;
+    }
+
     pub fn panel_id(&mut self, name: impl Into<String>) -> Id {
         self.begin(name);
         let id = self.current_panel_id;
@@ -792,7 +1635,29 @@ impl Context {
         id
     }
 
+    /// rect a panel anchored to `target` is placed in, `None` if the target panel is not shown
+    fn anchor_target_rect(&self, target: AnchorTarget) -> Option<Rect> {
+        match target {
+            AnchorTarget::Screen => {
+                let win = &self.panels[self.window_panel_id];
+                let min = win.pos + Vec2::new(0.0, win.titlebar_height);
+                Some(Rect::from_min_max(min, win.pos + win.size))
+            }
+            AnchorTarget::Panel(id) => {
+                let p = self.panels.get(id)?;
+                (p.last_frame_used + 1 >= self.frame_count).then(|| p.panel_rect())
+            }
+        }
+    }
+
     pub fn begin_ex(&mut self, name: impl Into<String>, flags: PanelFlag) {
+        crate::profile_scope!("begin_panel");
+        self.end_attention_item();
+        let flags = if self.next.anchor.is_some() {
+            flags | PanelFlag::NO_MOVE
+        } else {
+            flags
+        };
         fn next_window_pos(screen: Vec2, panel_size: Vec2) -> Vec2 {
             use std::sync::atomic::{AtomicU32, Ordering};
             static PANEL_COUNT: AtomicU32 = AtomicU32::new(0);
@@ -874,6 +1739,19 @@ impl Context {
                 let p = &mut self.panels[id];
                 p.pos = next_window_pos(self.draw.screen_size, self.next.size);
             }
+
+            if let Some(l) = self.pending_layouts.remove(&id) {
+                let p = &mut self.panels[id];
+                p.pos = l.pos;
+                p.size = l.size;
+                p.size_pre_dock = l.size_pre_dock;
+            }
+        }
+
+        let on_top = flags.has(PanelFlag::ALWAYS_ON_TOP);
+        if self.panels[id].flags.has(PanelFlag::ALWAYS_ON_TOP) != on_top {
+            self.panels[id].flags = flags;
+            self.update_draworder();
         }
 
         self.current_panel_stack.push(id);
@@ -888,6 +1766,18 @@ impl Context {
             p.pos.y = self.next.pos.y;
         }
 
+        if let Some(anchor) = self.next.anchor
+            && let Some(target) = self.anchor_target_rect(anchor.target)
+        {
+            let p = &mut self.panels[id];
+            if p.dock_id.is_null() {
+                p.pos = anchor.panel_pos(target, p.size);
+            }
+        }
+
+        let collect_stats = self.collects_panel_stats();
+        let p = &mut self.panels[id];
+
         // reset temp data
         if !flags.has(PanelFlag::USE_PARENT_DRAWLIST) {
             if !p.drawlist.data.borrow().clip_stack.is_empty() {
@@ -896,6 +1786,9 @@ impl Context {
             p.drawlist.clear();
             p.drawlist_over.clear();
         }
+        if collect_stats {
+            p.begin_stats();
+        }
 
         p.root = root_id;
         p.parent_id = parent_id;
@@ -908,16 +1801,24 @@ impl Context {
         p.flags = flags;
         p.explicit_size = self.next.size;
         p.drawlist.data.borrow_mut().circle_max_err = self.circle_max_err;
+        p.drawlist.data.borrow_mut().shape_cache = self.shape_cache.clone();
+        p.drawlist_over.data.borrow_mut().shape_cache = self.shape_cache.clone();
         p.drawlist.draw_clip_rect = self.draw_clip_rect;
+        p.style = self
+            .panel_style_stack
+            .iter()
+            .fold(PanelStyle::default(), |acc, s| acc.merge(*s));
         p.titlebar_height = if flags.has(PanelFlag::NO_TITLEBAR) {
             0.0
         } else if is_window {
             self.style.window_titlebar_height()
         } else {
-            self.style.titlebar_height()
+            p.style
+                .titlebar_height
+                .unwrap_or(self.style.titlebar_height())
         };
 
-        p.padding = self.style.panel_padding();
+        p.padding = p.style.padding.unwrap_or(self.style.panel_padding());
         p.scrollbar_width = self.style.scrollbar_width();
         p.scrollbar_padding = self.style.scrollbar_padding();
         p.last_frame_used = self.frame_count;
@@ -935,6 +1836,7 @@ impl Context {
 
         p.min_size = self.next.min_size;
         p.max_size = self.next.max_size;
+        p.auto_size = self.next.auto_size;
 
         if flags.has(PanelFlag::NO_MOVE) {
             // p.move_id = Id::NULL;
@@ -1011,6 +1913,16 @@ impl Context {
             }
         }
 
+        if self.collects_panel_stats() {
+            self.panels[id].end_stats();
+        }
+        if let Some(budget) = self.panel_budget {
+            let exceeded = budget.exceeded(&self.panels[id].stats);
+            if !exceeded.is_empty() {
+                self.draw_budget_badge(id, &exceeded.join(", "));
+            }
+        }
+
         let p = &mut self.panels[id];
 
         let prev_max_pos = p.cursor_max_pos();
@@ -1025,29 +1937,25 @@ impl Context {
         // } else {
         //     self.style.panel_outline()
         // };
-        let panel_outline = self.style.panel_outline();
+        let panel_outline = p.style.outline.unwrap_or(self.style.panel_outline());
 
-        p.outline_offset = panel_outline.offset();
+        p.outline_offset = panel_outline.to_pixels().offset();
 
+        let panel_corners = p.style.corners.unwrap_or(self.style.panel_corners());
         let corner_radii = if p.dock_id.is_null() {
-            CornerRadii::all(self.style.panel_corner_radius())
+            panel_corners
         } else {
             let [n_n, n_e, n_s, n_w] = self.docktree.get_neighbors(p.dock_id).map(|n| !n.is_null());
+            // corners touching a neighbor are square
             let [tl, tr, br, bl] = [
-                (n_n, n_w), // tl
-                (n_n, n_e), // tr
-                (n_s, n_e), // br
-                (n_s, n_w), // bl
+                (n_n, n_w, panel_corners.tl),
+                (n_n, n_e, panel_corners.tr),
+                (n_s, n_e, panel_corners.br),
+                (n_s, n_w, panel_corners.bl),
             ]
-            .map(|(n1, n2)| {
-                if !(n1 || n2) {
-                    self.style.panel_corner_radius()
-                } else {
-                    0.0
-                }
-            });
+            .map(|(n1, n2, r)| if !(n1 || n2) { r } else { Vec2::ZERO });
 
-            CornerRadii::new(tl, tr, bl, br)
+            CornerRadii::new_elliptical(tl, tr, bl, br)
         };
 
         // preserve when?
@@ -1083,7 +1991,7 @@ impl Context {
             p.size = dock_rect.size();
         }
 
-        let outline_width = self.style.panel_outline().width;
+        let outline_width = panel_outline.width;
         let full_rect = Rect::from_min_size(p.pos - outline_width, p.size + 2.0 * outline_width);
         let mut clip_rect = p.full_rect;
 
@@ -1108,6 +2016,12 @@ impl Context {
             self.hot_id = id;
         }
 
+        let p = &self.panels[id];
+        if !p.flags.has(PanelFlag::NO_FOCUS) {
+            let (draw_order, clip_rect) = (p.draw_order, p.clip_rect);
+            self.occlusion.push(draw_order, id, clip_rect);
+        }
+
         if let PanelAction::Move {
             id,
             dock_target,
@@ -1161,11 +2075,11 @@ impl Context {
         }
 
         // draw background
-        let bg_fill = if p.is_window_panel {
-            self.style.window_bg()
+        let bg_fill = p.style.fill.unwrap_or(if p.is_window_panel {
+            self.style.window_bg().into()
         } else {
-            self.style.panel_bg()
-        };
+            self.style.panel_bg().into()
+        });
 
         // self.draw(|list| {
         // panel clip rectangle
@@ -1180,13 +2094,21 @@ impl Context {
             self.push_clip_rect(clip);
         }
 
-        self.draw(
-            p.panel_rect()
-                .draw_rect()
-                .fill(bg_fill)
-                // .outline(panel_outline)
-                .corners(corner_radii),
-        );
+        match bg_fill {
+            PanelFill::Solid(col) => {
+                self.draw(
+                    p.panel_rect()
+                        .draw_rect()
+                        .fill(col)
+                        // .outline(panel_outline)
+                        .corners(corner_radii),
+                );
+            }
+            PanelFill::Gradient(top, bottom) => {
+                self.current_drawlist()
+                    .add_rect_gradient(p.panel_rect(), corner_radii, top, bottom);
+            }
+        }
 
         if self.draw_content_outline {
             self.draw_over(
@@ -1203,6 +2125,10 @@ impl Context {
                     .outline(Outline::new(RGBA::BLUE, 2.0)),
             );
         }
+
+        if self.debug_paint {
+            self.debug_paint_panel(p);
+        }
         // let p = &self.panels[id];
         if !p.flags.has(PanelFlag::NO_TITLEBAR) {
             let titlebar_height = p.titlebar_height;
@@ -1273,6 +2199,27 @@ impl Context {
         } else {
             self.push_clip_rect(p.visible_content_rect());
         }
+
+        // the clip rect alone lets the content overdraw the rounded corners
+        let clip_masked = corner_radii.any_round_corners();
+        if clip_masked {
+            self.push_clip_mask(ClipShape::RoundedRect(p.panel_rect(), corner_radii));
+        }
+        self.panels[id].clip_masked = clip_masked;
+
+        // only the content is zoomed, the panel itself was drawn above
+        let parent_scale = if parent_id.is_null() {
+            1.0
+        } else {
+            self.panels[parent_id].content_scale
+        };
+        let p = &mut self.panels[id];
+        p.content_scale = parent_scale * p.zoom;
+        p.zoom_vars = if p.zoom != 1.0 {
+            self.style.push_scaled(p.zoom)
+        } else {
+            0
+        };
     }
 
     pub(crate) fn draw_scrollbar(&mut self, axis: usize) {
@@ -1382,6 +2329,7 @@ impl Context {
         let titlebar_height = p.titlebar_height;
         let panel_pos = p.pos;
         let panel_size = p.size;
+        let p_style = p.style;
         let title = p.name.clone();
         // let move_id = p.move_id;
         let p_id = p.id;
@@ -1391,13 +2339,13 @@ impl Context {
 
         // Draw titlebar background
         let mut tb_corners = panel_corners;
-        tb_corners.bl = 0.0;
-        tb_corners.br = 0.0;
+        tb_corners.bl = Vec2::ZERO;
+        tb_corners.br = Vec2::ZERO;
 
         self.draw(
             Rect::from_min_size(panel_pos, Vec2::new(panel_size.x, titlebar_height))
                 .draw_rect()
-                .fill(self.style.titlebar_color())
+                .fill(p_style.titlebar_color.unwrap_or(self.style.titlebar_color()))
                 .corners(tb_corners),
         );
 
@@ -1421,13 +2369,19 @@ impl Context {
             self.draw(
                 Rect::from_min_size(panel_pos, Vec2::new(handle_width, titlebar_height))
                     .draw_rect()
-                    .corners(CornerRadii::top(self.style.panel_corner_radius()))
-                    .fill(self.style.panel_bg()),
+                    .corners(
+                        p_style
+                            .corners
+                            .unwrap_or(self.style.panel_corners())
+                            .with_bottom(0.0),
+                    )
+                    .fill(p_style.fill.map_or(self.style.panel_bg(), PanelFill::top)),
             );
         }
 
         // Draw title text
-        self.draw(title_text.draw_rects(panel_pos + pad, self.style.text_col()));
+        let title_col = p_style.title_col.unwrap_or(self.style.text_col());
+        self.draw(title_text.draw_rects(panel_pos + pad, title_col));
 
         // Register titlebar interaction area
         let tb_sig = self.reg_item_active_on_press(
@@ -1724,8 +2678,11 @@ impl Context {
                 (true, false, rect)
             };
 
-            if can_resize_in_dir && self.panel_action.is_none() && !p.is_window_panel
-            // && !(p.flags.has(PanelFlags::NO_RESIZE) || p.is_window_panel)
+            // edge panels are NO_RESIZE and resize through their own edge, see `edge_panel`
+            if can_resize_in_dir
+                && self.panel_action.is_none()
+                && !p.is_window_panel
+                && !p.flags.has(PanelFlag::NO_RESIZE)
             {
                 let dir = dir.unwrap();
                 let dock_id = p.dock_id;
@@ -2101,7 +3058,8 @@ impl Context {
                 dock_target_panel.flags,
             );
 
-            let prev_size = preview.size() - self.style.panel_outline().width * 2.0;
+            let outline = self.style.panel_outline().to_pixels();
+            let prev_size = preview.size() - outline.width * 2.0;
             let prev_center = preview.center();
 
             dock_target_panel.drawlist_over.draw(
@@ -2221,6 +3179,26 @@ impl Context {
     }
 
     pub fn end(&mut self) {
+        crate::profile_scope!("end_panel");
+        self.end_attention_item();
+        let p = self.get_current_panel();
+        let n_zoom_vars = p.zoom_vars;
+        for _ in 0..n_zoom_vars {
+            self.style.pop_var();
+        }
+
+        if self.access.enabled {
+            let p = self.get_current_panel();
+            let parent = if p.flags.has(PanelFlag::IS_CHILD) {
+                let n = self.current_panel_stack.len();
+                self.current_panel_stack[..n - 1].last().copied().unwrap_or(Id::NULL)
+            } else {
+                Id::NULL
+            };
+            let node = AccessNode::new(p.id, parent, AccessRole::Panel, &p.name, p.panel_rect());
+            self.access.nodes.push(node);
+        }
+
         let p = self.get_current_panel();
         let id = p.id;
 
@@ -2239,6 +3217,9 @@ impl Context {
         //         }
 
         let list = self.current_drawlist();
+        if p.clip_masked {
+            list.pop_clip_mask();
+        }
         list.pop_clip_rect_n(2);
         // self.draw(|list| {
         //     list.pop_clip_rect();
@@ -2258,10 +3239,12 @@ impl Context {
 
         // TODO[NOTE]: is it possible to get size from only 1 frame?
         // or configurable
-        if self.frame_count - p.frame_created <= 1 {
+        let auto_size = p.auto_size && p.dock_id.is_null();
+        if auto_size || self.frame_count - p.frame_created <= 1 {
             // p.size = p.full_size * 1.1;
             // TODO[NOTE]: account for scrollbar width?
-            p.size = p.full_size + p.padding + self.style.scrollbar_padding();
+            let size = p.full_size + p.padding + self.style.scrollbar_padding();
+            p.size = size.min(p.panel_max_size()).max(p.panel_min_size());
         }
 
         assert!(id == self.current_panel_stack.pop().unwrap());
@@ -2338,6 +3321,16 @@ impl Context {
                 sig |= Signal::DOUBLE_CLICKED_MIDDLE;
             }
 
+            if self.mouse.triple_pressed(Btn::Left) {
+                sig |= Signal::TRIPLE_PRESSED_LEFT;
+            }
+            if self.mouse.triple_pressed(Btn::Right) {
+                sig |= Signal::TRIPLE_PRESSED_RIGHT;
+            }
+            if self.mouse.triple_pressed(Btn::Middle) {
+                sig |= Signal::TRIPLE_PRESSED_MIDDLE;
+            }
+
             if self.mouse.triple_clicked(Btn::Left) {
                 sig |= Signal::TRIPLE_CLICKED_LEFT;
             }
@@ -2454,6 +3447,44 @@ impl Context {
         self.get_current_panel().set_cursor_pos(pos)
     }
 
+    /// lays out the content of `f` inside `frame`, the frame is sized to fit its content.
+    /// returns the rect of the frame without the outer margin
+    pub fn frame(&mut self, frame: Frame, f: impl FnOnce(&mut Self)) -> Rect {
+        let offset = frame.outer_margin.left_top() + frame.inner_margin.left_top();
+
+        let p = self.get_current_panel();
+        let list = p.drawlist.clone();
+        let line_start_x = p.pos.x + p.padding;
+        let backup = p._cursor.borrow().clone();
+        {
+            // new lines of the content start at the left edge of the content
+            let mut c = p._cursor.borrow_mut();
+            c.pos += offset;
+            c.indent = c.pos.x - line_start_x;
+            c.max_pos = c.pos;
+            c.line_height = 0.0;
+            c.is_same_line = false;
+        }
+
+        // the frame is drawn after the content is known, but has to end up below it
+        let below = list.data.borrow_mut().begin_channel();
+        f(self);
+        let content = list.data.borrow_mut().end_channel(below);
+
+        let content_size = {
+            let mut c = self.get_current_panel()._cursor.borrow_mut();
+            let size = (c.max_pos - (backup.pos + offset)).max(Vec2::ZERO);
+            *c = backup;
+            size
+        };
+
+        let rect = self.place_item(frame.outer_size(content_size));
+        let rect = frame.outer_margin.shrink_rect(rect);
+        frame.draw_rects(rect).add_to_drawlist(&list);
+        list.data.borrow_mut().append_channel(content);
+        rect
+    }
+
     pub fn new_line(&mut self) {
         self.place_item(Vec2::new(0.0, self.style.line_height()));
     }
@@ -2485,9 +3516,18 @@ impl Context {
     // based on: https://github.com/ocornut/imgui/blob/3dafd9e898290ca890c29a379188be9e53b88537/imgui.cpp#L11183
     // TODO[NOTE]: what do we do with layout? now that we have same_line
     pub fn place_item(&mut self, size: Vec2) -> Rect {
+        if !self.attention_anims.is_empty() {
+            self.end_attention_item();
+            let drawlist = self.current_drawlist();
+            drawlist.resolve_pending_shapes();
+            let vtx_start = drawlist.data.borrow().vtx_buffer.len();
+            self.last_item_vtx_start = vtx_start;
+        }
         let p = self.get_current_panel();
         // let rect = Rect::from_min_size(p.cursor_pos().round() + p.scroll, size.round());
         let rect = Rect::from_min_size(p.cursor_pos().round(), size.round());
+        // the cursor always moves left-to-right, for rtl we mirror the item inside the content
+        let rect = self.layout_dir.place(rect, p.visible_content_rect());
         let clip_rect = p.current_clip_rect();
 
         let mut c = p._cursor.borrow_mut();
@@ -2511,7 +3551,23 @@ impl Context {
         c.prev_line_height = line_height;
         c.line_height = 0.0;
         c.is_same_line = false;
-        // drop(c);
+        drop(c);
+
+        self.last_item_culled = !clip_rect.overlaps(rect);
+        self.last_item_rect = rect;
+        if let Some(rects) = &mut self.skeleton_rects {
+            rects.push(rect);
+        }
+        self.last_item_clipped_rect = rect.clip(clip_rect);
+        self.last_item_occluded = false;
+        self.n_items += 1;
+        self.n_culled_items += self.last_item_culled as u32;
+        // the items of a loading scope are replaced by skeletons
+        self.last_item_culled |= self.skeleton_rects.is_some();
+
+        if self.debug_paint {
+            self.debug_paint_item(rect, clip_rect);
+        }
 
         // if !id.is_null() {
         //     self.prev_item_data.reset();
@@ -2552,6 +3608,65 @@ impl Context {
         rect
     }
 
+    /// the last placed item is outside of the clip rect, widgets can skip shaping text and
+    /// building draw commands for it
+    pub fn item_culled(&self) -> bool {
+        self.last_item_culled
+    }
+
+    /// the mouse is over the visible part of the last placed item and no other panel or item
+    /// covers it
+    pub fn item_hovered(&self) -> bool {
+        let is_topmost =
+            self.prev_hot_panel_id == self.current_panel_id || self.prev_hot_panel_id.is_null();
+
+        is_topmost
+            && !self.last_item_occluded
+            && self.panel_action.is_none()
+            && self
+                .last_item_clipped_rect
+                .is_some_and(|r| r.contains(self.mouse.pos))
+    }
+
+    /// shows `text` in a box next to the mouse, drawn on top of all panels
+    pub fn tooltip(&mut self, text: &str) {
+        const MAX_WIDTH: f32 = 400.0;
+        let pad = self.style.panel_padding().max(4.0);
+        let itm = TextItem::new(text.to_string(), self.style.text_size(), 1.0, self.text_font)
+            .with_wrap(ui::TextWrap::Wrap, MAX_WIDTH);
+        let layout = self.layout_text_item(itm);
+        let size = Vec2::new(layout.width, layout.height) + Vec2::splat(2.0 * pad);
+
+        // keep the box inside the window, flip to the other side of the mouse if needed
+        let screen = self.draw.screen_size;
+        let offset = Vec2::splat(16.0);
+        let mut pos = self.mouse.pos + offset;
+        if pos.x + size.x > screen.x {
+            pos.x = (self.mouse.pos.x - offset.x - size.x).max(0.0);
+        }
+        if pos.y + size.y > screen.y {
+            pos.y = (self.mouse.pos.y - offset.y - size.y).max(0.0);
+        }
+
+        let rect = Rect::from_min_size(pos.round(), size);
+        self.overlay.add_draw_rect(
+            rect.draw_rect()
+                .fill(self.style.panel_bg())
+                .outline(self.style.panel_outline())
+                .corners(4.0),
+        );
+        for r in layout.draw_rects(rect.min + Vec2::splat(pad), self.style.text_col()) {
+            self.overlay.add_draw_rect(r);
+        }
+    }
+
+    /// [`Self::tooltip`] for the last placed item while it is hovered
+    pub fn item_tooltip(&mut self, text: &str) {
+        if self.item_hovered() {
+            self.tooltip(text);
+        }
+    }
+
     pub fn update_hot_id(&mut self, id: Id, bb: Rect, flags: ItemFlags) {
         let is_topmost =
             self.prev_hot_panel_id == self.current_panel_id || self.prev_hot_panel_id.is_null();
@@ -2559,7 +3674,9 @@ impl Context {
         if bb.contains(self.mouse.pos)
             && !id.is_null()
             && self.panel_action.is_none()
+            && !self.screenshot_mode
             && is_topmost
+            && !self.last_item_occluded
             && !self.mouse.dragging(MouseBtn::Left)
             && !self.expect_drag
         {
@@ -2609,6 +3726,35 @@ impl Context {
         self.reg_item_ex(id, bb, ItemFlags::NONE)
     }
 
+    /// places a custom item of `size` in the layout and registers it for interaction
+    ///
+    /// the id is derived from the number of rects allocated before in the frame, use
+    /// [`Self::allocate_rect_id`] if the item keeps state or is not always shown
+    pub fn allocate_rect(&mut self, size: Vec2, sense: Sense) -> (Rect, Signal) {
+        let id = Id::from_hash(&(self.current_panel_id, "##_AUTO_ID", self.auto_id_count));
+        self.auto_id_count += 1;
+        self.allocate_rect_id(id, size, sense)
+    }
+
+    pub fn allocate_rect_id(&mut self, id: Id, size: Vec2, sense: Sense) -> (Rect, Signal) {
+        let rect = self.place_item(size);
+        let sig = self.reg_item_ex(id, rect, sense.item_flags());
+        (rect, sig)
+    }
+
+    /// draws into the current panel, clipped to `rect`
+    pub fn painter(&self, rect: Rect) -> ItemPainter<'_> {
+        ItemPainter::new(&self.get_current_panel().drawlist, rect)
+    }
+
+    pub fn style(&self) -> &StyleTable {
+        &self.style
+    }
+
+    pub fn add(&mut self, widget: impl Widget) -> Signal {
+        widget.ui(self)
+    }
+
     /// "registers" the item, i.e. potentially sets hot_id and returns the item signals
     ///
     pub fn reg_item_ex(&mut self, id: Id, bb: Rect, flags: ItemFlags) -> Signal {
@@ -2630,10 +3776,15 @@ impl Context {
             }
         }
 
-        if id.is_null() {
+        // the items of a loading scope are only placeholders
+        if id.is_null() || self.skeleton_rects.is_some() {
             return Signal::NONE;
         }
 
+        // two items with the same id share their state, e.g. the same label twice in a loop
+        if cfg!(debug_assertions) && !self.frame_item_ids.insert(id) {
+            log::warn!("item id {id:?} registered twice this frame, use a keyed scope");
+        }
 
         if self.kb_focus_next_item && self.prev_item_id == self.active_id {
             self.kb_focus_item_id = id;
@@ -2654,22 +3805,84 @@ impl Context {
             self.kb_focus_item_id = Id::NULL;
         }
 
+        // requested by assistive technology, also works for items that are scrolled out of view
+        let access_sig = self.access.take_signal(id);
+
         // assert!(self.prev_item_data.id == id);
         // let p = self.get_current_panel();
         if is_hidden && self.active_id != id {
-            return Signal::NONE;
+            return access_sig;
         }
+        signal |= access_sig;
 
         if let Some(c_bb) = c_bb {
+            let draw_order = self.get_current_panel().draw_order;
+            let layer = self.occlusion.push(draw_order, id, c_bb);
+            self.last_item_occluded = self.occlusion.is_occluded(id, layer);
             self.update_hot_id(id, c_bb, flags);
             signal |= self.get_item_signal(id, c_bb);
         }
 
         self.prev_item_id = id;
+        self.begin_attention_item(id, bb);
 
         signal
     }
 
+    /// records the item in the accessibility tree, returns `None` if the tree is not needed
+    /// or the item is not visible
+    pub fn reg_access_node(
+        &mut self,
+        id: Id,
+        role: AccessRole,
+        label: &str,
+        bb: Rect,
+    ) -> Option<&mut AccessNode> {
+        if !self.access.enabled || id.is_null() {
+            return None;
+        }
+
+        let clip_rect = self.get_current_panel().current_clip_rect();
+        bb.clip(clip_rect)?;
+
+        let node = AccessNode::new(id, self.current_panel_id, role, label, bb);
+        self.access.nodes.push(node);
+        self.access.nodes.last_mut()
+    }
+
+    /// applies the actions requested during the previous frame, the ones that need the
+    /// item state are handled when the item is registered
+    fn apply_access_requests(&mut self) {
+        self.access.frame_actions.clear();
+        self.access.nodes.clear();
+
+        for (id, action) in std::mem::take(&mut self.access.requests) {
+            let input = self.widget_data.get_mut::<TextInputState>(&id);
+            match (action, input) {
+                (AccessAction::Focus, _) => {
+                    self.active_id = id;
+                    self.active_id_changed = true;
+                }
+                (AccessAction::SetValue(text), Some(input)) => input.set_text(&text),
+                (AccessAction::ReplaceSelectedText(text), Some(input)) => {
+                    input.delete_selection();
+                    input.paste(&text);
+                }
+                (AccessAction::SetTextSelection { anchor, focus }, Some(input)) => {
+                    input.set_text_selection(anchor, focus);
+                }
+                (AccessAction::SetValue(text), None) => {
+                    if let Ok(v) = text.trim().parse() {
+                        self.access.frame_actions.insert(id, AccessAction::SetNumericValue(v));
+                    }
+                }
+                (action, _) => {
+                    self.access.frame_actions.insert(id, action);
+                }
+            }
+        }
+    }
+
     pub fn create_panel(&mut self, name: impl Into<String>, id: Id) {
         let name: String = name.into();
         let mut p = Panel::new(&name);
@@ -2968,7 +4181,7 @@ impl Context {
         // let nav_root = parent.nav_root;
         parent.child_id = id;
 
-        let outline_offset = self.style.panel_outline().width;
+        let outline_offset = self.style.panel_outline().to_pixels().width;
         self.next.pos = parent.cursor_pos() + Vec2::splat(outline_offset);
         self.begin_ex(name, panel_flags);
 
@@ -2995,11 +4208,51 @@ impl Context {
         self.end_frame();
     }
 
+    /// seconds between the start of the previous and the current frame
+    pub fn delta_time(&self) -> f32 {
+        self.delta_time.as_secs_f32()
+    }
+
+    /// number of the current frame
+    pub fn frame_index(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// seconds since the clock started, measured at the start of the frame so it is the same
+    /// for all items in a frame
+    pub fn time_since_start(&self) -> f32 {
+        self.frame_start.as_secs_f32()
+    }
+
     pub fn begin_frame(&mut self) {
+        crate::profile_scope!("begin_frame");
+        let now = self.clock.now();
+        self.delta_time = now.saturating_sub(self.frame_start);
+        self.frame_start = now;
+        self.draw.time = now.as_secs_f32();
+        self.mouse.time = now;
+        let scale_factor = self.window.scale_factor();
+        self.mouse.apply_settings(&self.input_settings, scale_factor);
+        self.keyboard.update(now, &self.input_settings);
+        self.update_text_input_keys();
+        self.apply_access_requests();
+        self.update_monitor_ui_scale();
+
+        self.widget_data.set_frame(self.frame_count);
         self.draw.clear();
         self.draw.screen_size = self.window.window_size();
+        self.poll_picking();
+        self.picking.clear();
+        self.occlusion.begin_frame(self.mouse.pos);
+        self.deferred.receive();
         self.hot_panel_id = Id::NULL;
         self.hot_id = Id::NULL;
+        self.viewport_ids.clear();
+        self.auto_id_count = 0;
+        self.frame_item_ids.clear();
+        self.overlay.clear();
+        self.prev_n_items = std::mem::take(&mut self.n_items);
+        self.prev_n_culled_items = std::mem::take(&mut self.n_culled_items);
 
         if !self.mouse.pressed(MouseBtn::Left) {
             self.expect_drag = false;
@@ -3092,6 +4345,30 @@ impl Context {
         p.pop_id()
     }
 
+    /// runs `f` with the ids of its items derived from `key`, e.g. the uuid of a list entry
+    ///
+    /// labels only tell apart the items of one scope, so when the entries of a list are
+    /// inserted or reordered, state like open headers or scroll positions moves to another
+    /// entry. inside a keyed scope the state follows the logical entry
+    ///
+    /// ```ignore
+    /// for entry in &entries {
+    ///     ui.keyed(entry.uuid, |ui| ui.collapsing_header(&entry.name, &mut entry.open));
+    /// }
+    /// ```
+    pub fn keyed<R>(&mut self, key: impl hash::Hash, f: impl FnOnce(&mut Self) -> R) -> R {
+        let id = Id::from_key(key);
+        let depth = self.get_current_panel().id_stack_ref().len();
+        self.push_id(id);
+        let res = f(self);
+
+        let p = self.get_current_panel();
+        // a missing pop_id would change the ids of all following items
+        assert_eq!(p.id_stack_ref().len(), depth + 1, "unbalanced push_id in keyed scope");
+        p.pop_id();
+        res
+    }
+
     pub fn push_style(&mut self, var: StyleVar) {
         self.style.push_var(var);
     }
@@ -3102,14 +4379,325 @@ impl Context {
 
     pub fn pop_style_n(&mut self, n: u32) {
         for _ in 0..n {
-            self.style.pop_var();
+            self.pop_style();
         }
     }
 
     pub fn pop_style(&mut self) {
+        if self.style.var_stack.is_empty() {
+            log::warn!("pop_style called without a matching push_style");
+            return;
+        }
         self.style.pop_var();
     }
 
+    /// switches between the default and the high contrast theme, keeps the ui scale
+    pub fn set_high_contrast(&mut self, enabled: bool) {
+        let scale = self.style.ui_scale();
+        self.style = if enabled { high_contrast_theme() } else { dark_theme() };
+        self.style.rescale(scale);
+        self.high_contrast = enabled;
+    }
+
+    /// makes animations jump to their end state, e.g. for users sensitive to motion
+    pub fn set_reduced_motion(&mut self, enabled: bool) {
+        self.reduced_motion = enabled;
+    }
+
+    /// scales all sizes of the style, `1.0` is the size the theme was designed for. the ui is
+    /// laid out with the new sizes in the next frame
+    ///
+    /// starts out at [`Self::default_ui_scale`]. with [`Self::persist_ui_scale`] the scale is
+    /// stored for the current monitor and restored when the window moves back to it
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        let scale = scale.max(0.1);
+        self.style.rescale(scale / self.style.ui_scale());
+        self.window.request_redraw();
+        if self.persist_ui_scale {
+            let monitor_ui_scale = self.monitor_ui_scale.clone();
+            monitor_ui_scale.store(self.settings(), scale);
+        }
+    }
+
+    /// uses the text size preference of the os as [`Self::default_ui_scale`], see
+    /// [`platform::system_text_scale`]. not done by [`Self::new`], reading the setting can
+    /// block and headless contexts should not depend on the desktop
+    pub fn use_system_text_scale(&mut self) {
+        let Some(scale) = platform::system_text_scale() else {
+            return;
+        };
+        self.default_ui_scale = scale;
+        // a scale stored for the current monitor is restored again in the next frame
+        self.monitor_ui_scale.monitor = None;
+        self.style.rescale(scale / self.style.ui_scale());
+        self.window.request_redraw();
+    }
+
+    /// restores the stored ui scale when the window moved to another monitor
+    fn update_monitor_ui_scale(&mut self) {
+        if !self.persist_ui_scale {
+            return;
+        }
+        let Some(monitor) = self.window.current_monitor() else {
+            return;
+        };
+        let default = self.default_ui_scale;
+        let mut monitor_ui_scale = std::mem::take(&mut self.monitor_ui_scale);
+        let scale = monitor_ui_scale.moved_to(&monitor, self.settings(), default);
+        self.monitor_ui_scale = monitor_ui_scale;
+        // not stored again, so monitors without a stored scale keep following the os
+        if let Some(scale) = scale {
+            self.style.rescale(scale / self.style.ui_scale());
+            self.window.request_redraw();
+        }
+    }
+
+    pub fn ui_scale(&self) -> f32 {
+        self.style.ui_scale()
+    }
+
+    /// zoom of the current panel's content, changed with ctrl + wheel on
+    /// [`PanelFlag::ZOOMABLE`] panels
+    pub fn panel_zoom(&self) -> f32 {
+        self.get_current_panel().zoom
+    }
+
+    /// sets the zoom of the current panel's content, applied from the next frame
+    pub fn set_panel_zoom(&mut self, zoom: f32) {
+        let id = self.current_panel_id;
+        self.panels[id].zoom = zoom.clamp(PANEL_ZOOM_MIN, PANEL_ZOOM_MAX);
+    }
+
+    /// pushes `var` until the returned scope is dropped
+    ///
+    /// ```ignore
+    /// let mut ui = ui.scoped_style(StyleVar::SpacingV(0.0)).push(StyleVar::TextSize(14.0));
+    /// ui.button("compact");
+    /// ```
+    pub fn scoped_style(&mut self, var: StyleVar) -> StyleScope<'_> {
+        self.push_style(var);
+        StyleScope {
+            ctx: self,
+            n_pushed: 1,
+        }
+    }
+
+    /// runs `f` with `vars` pushed
+    pub fn with_style<R>(
+        &mut self,
+        vars: impl IntoIterator<Item = StyleVar>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let mut n = 0;
+        for var in vars {
+            self.push_style(var);
+            n += 1;
+        }
+        let res = f(self);
+        self.pop_style_n(n);
+        res
+    }
+
+    /// overrides the style of the panels begun until the matching [`Self::pop_panel_style`]
+    ///
+    /// nested pushes are merged, inner values take precedence
+    pub fn push_panel_style(&mut self, style: PanelStyle) {
+        self.panel_style_stack.push(style);
+    }
+
+    pub fn pop_panel_style(&mut self) {
+        if self.panel_style_stack.pop().is_none() {
+            log::warn!("pop_panel_style called without a matching push_panel_style");
+        }
+    }
+
+    /// lets the user drag a rect over the window, the rect is then available through
+    /// [`Self::take_capture_request`]. escape cancels
+    pub fn start_screenshot_mode(&mut self) {
+        self.screenshot_mode = true;
+    }
+
+    /// requests a capture of `rect` in screen space
+    pub fn request_capture(&mut self, rect: Rect) {
+        self.capture_request = Some(rect);
+    }
+
+    /// region that should be read back from the frame that is about to be rendered, e.g. with
+    /// [`gpu::RenderTarget::capture_region`]
+    pub fn take_capture_request(&mut self) -> Option<Rect> {
+        self.capture_request.take()
+    }
+
+    /// records every frame passed to [`Self::record_frame`] until [`Self::stop_recording`]
+    pub fn start_recording(&mut self, sink: gpu::FrameSink) {
+        self.recorder = Some(gpu::FrameRecorder::new(&self.wgpu, sink));
+    }
+
+    /// writes the frames in flight and closes the sink, returns it e.g. to take the frames of
+    /// [`gpu::FrameSink::Memory`]
+    pub fn stop_recording(&mut self) -> Option<std::io::Result<gpu::FrameSink>> {
+        self.recorder.take().map(gpu::FrameRecorder::finish)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// copies the frame rendered into `target` while recording, call it after rendering and
+    /// before presenting
+    pub fn record_frame(&mut self, target: &gpu::RenderTarget) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(target);
+        }
+    }
+
+    fn update_screenshot_mode(&mut self) {
+        if self.keyboard.pressed(KeyCode::Escape) {
+            self.screenshot_mode = false;
+            self.set_cursor_icon(CursorIcon::Default);
+            return;
+        }
+
+        let selection = self
+            .mouse
+            .drag_start(MouseBtn::Left)
+            .map(|start| Rect::from_min_max(start.min(self.mouse.pos), start.max(self.mouse.pos)));
+
+        if self.mouse.released(MouseBtn::Left) {
+            if let Some(rect) = selection.filter(|r| r.width() >= 1.0 && r.height() >= 1.0) {
+                self.request_capture(rect);
+            }
+            self.screenshot_mode = false;
+            self.set_cursor_icon(CursorIcon::Default);
+            return;
+        }
+
+        self.set_cursor_icon(CursorIcon::Crosshair);
+
+        // dim everything outside of the selection
+        let screen = Rect::from_min_size(Vec2::ZERO, self.draw.screen_size);
+        let dim = RGBA::BLACK.with_alpha(0.4);
+        let Some(sel) = selection else {
+            self.overlay.add_draw_rect(screen.draw_rect().fill(dim));
+            return;
+        };
+        let sel = Rect::from_min_max(
+            sel.min.clamp(screen.min, screen.max),
+            sel.max.clamp(screen.min, screen.max),
+        );
+
+        let around = [
+            Rect::from_min_max(screen.min, Vec2::new(screen.max.x, sel.min.y)),
+            Rect::from_min_max(Vec2::new(screen.min.x, sel.max.y), screen.max),
+            Rect::from_min_max(Vec2::new(screen.min.x, sel.min.y), Vec2::new(sel.min.x, sel.max.y)),
+            Rect::from_min_max(Vec2::new(sel.max.x, sel.min.y), Vec2::new(screen.max.x, sel.max.y)),
+        ];
+        for r in around {
+            self.overlay.add_draw_rect(r.draw_rect().fill(dim));
+        }
+        self.overlay
+            .add_draw_rect(sel.draw_rect().outline(Outline::outer(RGBA::WHITE, 1.0)));
+    }
+
+    /// overlays baseline grids, panel padding, item bounds and alignment guides for the
+    /// hovered item
+    pub fn set_debug_paint(&mut self, enabled: bool) {
+        self.debug_paint = enabled;
+    }
+
+    /// shows a badge on panels whose [`PanelStats`] exceed `budget`, e.g. to find the slow
+    /// parts of a ui. `None` hides the badges
+    pub fn set_panel_budget(&mut self, budget: Option<PanelBudget>) {
+        self.panel_budget = budget;
+    }
+
+    /// what the panel `name` recorded in the last frame, only collected while a budget is set
+    /// or the [`Self::debug_panel`] is shown
+    pub fn panel_stats(&self, name: &str) -> Option<PanelStats> {
+        self.panels.get(self.gen_glob_id(name)).map(|p| p.stats)
+    }
+
+    /// whether the panels count what they draw, see [`Self::panel_stats`]
+    pub(crate) fn collects_panel_stats(&self) -> bool {
+        let debug = self.panels.get(self.gen_glob_id(DEBUG_PANEL));
+        self.panel_budget.is_some()
+            || debug.is_some_and(|p| p.last_frame_used + 1 >= self.frame_count)
+    }
+
+    /// a red badge with `text` in the top right corner of the panel
+    fn draw_budget_badge(&self, id: Id, text: &str) {
+        let p = &self.panels[id];
+        let shape = self.layout_text(text, self.style.text_size() * 0.8);
+        let pad = Vec2::new(6.0, 2.0);
+        let size = shape.size() + 2.0 * pad;
+        let min = Vec2::new(p.full_rect.max.x - size.x - 4.0, p.full_rect.min.y + 4.0);
+        let rect = Rect::from_min_size(min, size);
+        let list = &p.drawlist_over;
+        rect.draw_rect()
+            .corners(CornerRadii::all(size.y * 0.5))
+            .fill(self.style.red())
+            .add_to_drawlist(list);
+        shape.draw_rects(min + pad, RGBA::WHITE).add_to_drawlist(list);
+    }
+
+    fn debug_paint_panel(&self, p: &Panel) {
+        let list = &p.drawlist_over;
+        let content = p.visible_content_rect();
+
+        // padding between the panel border and the content
+        let body = Rect::from_min_max(p.pos + Vec2::new(0.0, p.titlebar_height), p.pos + p.size);
+        list.push_clip_rect(body);
+        list.add_draw_rect(
+            body.draw_rect()
+                .outline(Outline::inner(RGBA::ORANGE.with_alpha(0.25), p.padding)),
+        );
+
+        // a line at the bottom of every row of default height
+        let row = self.style.line_height() + self.style.spacing_v();
+        let grid_col = RGBA::CYAN.with_alpha(0.2);
+        let mut y = content.min.y + p.scroll.y.rem_euclid(row) - row;
+        while y < content.max.y {
+            if y > content.min.y {
+                list.add_line(
+                    Vec2::new(content.min.x, y),
+                    Vec2::new(content.max.x, y),
+                    Stroke::hairline(grid_col),
+                );
+            }
+            y += row;
+        }
+        list.pop_clip_rect();
+    }
+
+    fn debug_paint_item(&self, rect: Rect, clip_rect: Rect) {
+        let outline = Outline::hairline(RGBA::FOLLY.with_alpha(0.6));
+        self.draw_over(rect.draw_rect().outline(outline));
+
+        if !rect.contains(self.mouse.pos) || !clip_rect.contains(self.mouse.pos) {
+            return;
+        }
+
+        // guides along the edges of the hovered item, to check alignment with its neighbours
+        let list = self.current_drawlist_over();
+        let stroke = Stroke::hairline(RGBA::SAFFRON.with_alpha(0.8));
+        list.push_merged_clip_rect(clip_rect);
+        for x in [rect.min.x, rect.max.x] {
+            list.add_line(
+                Vec2::new(x, clip_rect.min.y),
+                Vec2::new(x, clip_rect.max.y),
+                stroke,
+            );
+        }
+        for y in [rect.min.y, rect.max.y] {
+            list.add_line(
+                Vec2::new(clip_rect.min.x, y),
+                Vec2::new(clip_rect.max.x, y),
+                stroke,
+            );
+        }
+        list.pop_clip_rect();
+    }
+
     pub fn panel_debug_info(&mut self, id: Id) {
         use crate::ui_items::ui_text;
 
@@ -3140,7 +4728,7 @@ impl Context {
 
         self.next.initial_width = 450.0;
         self.begin_ex(
-            "Debug##_DEBUG_PANEL",
+            DEBUG_PANEL,
             PanelFlag::DRAW_H_SCROLLBAR | PanelFlag::DRAW_V_SCROLLBAR,
         );
 
@@ -3169,16 +4757,25 @@ impl Context {
         //     .collect();
         // ui_text!(self: "draw_order: {draw_order:?}");
 
-        let now = Instant::now();
-        let dt = (now - self.prev_frame_time).as_secs_f32();
+        let dt = self.delta_time();
         let fps = 1.0 / dt;
-        self.prev_frame_time = now;
         ui_text!(self: "dt: {:0.1?}\t, fps: {fps:0.1?}", dt * 1000.0);
 
         // self.pop_style();
 
         ui_text!(self: "action: {}", self.panel_action);
         ui_text!(self: "n. of draw calls: {}", self.n_draw_calls);
+        ui_text!(self: "culled items: {} / {}", self.prev_n_culled_items, self.prev_n_items);
+        ui_text!(self: "batching: {}", self.draw.batch_stats);
+        ui_text!(self: "shape cache: {}", self.shape_cache.borrow().prev_stats);
+        ui_text!(self: "gpu pool: {}", self.wgpu.resource_pool.lock().unwrap().stats());
+        // the slowest panels
+        let mut panels: Vec<_> =
+            self.panels.iter().map(|(_, p)| (p.stats, p.name.clone())).collect();
+        panels.sort_by_key(|(stats, _)| std::cmp::Reverse(stats.build_time));
+        for (stats, name) in panels.into_iter().take(5) {
+            ui_text!(self: "{}: {stats}", name.replace('#', ""));
+        }
 
         // self.separator_h(4.0, self.style.panel_dark_bg());
 
@@ -3206,10 +4803,31 @@ impl Context {
 
             let mut is_decorated = self.window.is_decorated();
             #[cfg(not(target_arch = "wasm32"))]
-            if self.checkbox("custom titlebar", &mut is_decorated) {
+            if self.checkbox("custom titlebar", &mut is_decorated).changed() {
                 self.window.set_window_decorations(is_decorated);
             }
 
+            let mut high_contrast = self.high_contrast;
+            if self.checkbox("high contrast", &mut high_contrast).changed() {
+                self.set_high_contrast(high_contrast);
+            }
+
+            let mut reduced_motion = self.reduced_motion;
+            if self.checkbox("reduced motion", &mut reduced_motion).changed() {
+                self.set_reduced_motion(reduced_motion);
+            }
+
+            let mut v = self.ui_scale();
+            self.input_slider_f32("ui scale", 0.5, 2.0, &mut v);
+            if v != self.ui_scale() {
+                self.set_ui_scale(v);
+            }
+
+            let mut rtl = self.layout_dir == LayoutDir::Rtl;
+            if self.checkbox("right-to-left layout", &mut rtl).changed() {
+                self.layout_dir = if rtl { LayoutDir::Rtl } else { LayoutDir::Ltr };
+            }
+
             let mut v = self.style.titlebar_height();
             self.input_slider_f32("titlebar height", 0.0, 100.0, &mut v);
             self.style.set_var(StyleVar::TitlebarHeight(v));
@@ -3283,6 +4901,15 @@ impl Context {
 
         }
 
+        if self.tabitem("Memory") {
+            let entries = self.retained_state();
+            ui_text!(self: "retained widget state: {}", entries.len());
+            for (id, ty, frame) in entries {
+                let age = self.frame_count.saturating_sub(frame);
+                ui_text!(self: "{id}: {ty}, unused for {age} frames");
+            }
+        }
+
         if self.tabitem("Debug") {
             if self.button("reset docktree") {
                 self.reset_docktree();
@@ -3290,12 +4917,25 @@ impl Context {
 
             if self.button("reset style") {
                 self.style = dark_theme();
+                self.high_contrast = false;
             }
 
             let mut tmp = self.draw_wireframe;
             self.checkbox("draw wireframe", &mut tmp);
             self.draw_wireframe = tmp;
 
+            let mut tmp = self.draw.batching;
+            self.checkbox("batch draw calls", &mut tmp);
+            self.draw.batching = tmp;
+
+            let mut tmp = self.draw.compact_vertices;
+            self.checkbox("compact vertices", &mut tmp);
+            self.draw.compact_vertices = tmp;
+
+            let mut tmp = self.shape_cache.borrow().enabled;
+            self.checkbox("cache shapes", &mut tmp);
+            self.shape_cache.borrow_mut().enabled = tmp;
+
             let mut tmp = self.clip_content;
             self.checkbox("clip content", &mut tmp);
             self.clip_content = tmp;
@@ -3320,6 +4960,14 @@ impl Context {
             self.checkbox("draw item outline", &mut tmp);
             self.draw_item_outline = tmp;
 
+            let mut tmp = self.debug_paint;
+            self.checkbox("debug paint", &mut tmp);
+            self.set_debug_paint(tmp);
+
+            if self.button("screenshot") {
+                self.start_screenshot_mode();
+            }
+
             self.begin_tabbar("tabbar 2");
             self.tabitem("tab1");
             self.tabitem("tab2");
@@ -3335,8 +4983,15 @@ impl Context {
     }
 
     pub fn end_frame(&mut self) {
-        if !self.style.var_stack.is_empty() {
-            log::warn!("style stack is not empty");
+        crate::profile_scope!("end_frame");
+        let n_unpopped = self.style.var_stack.len();
+        debug_assert!(
+            n_unpopped == 0,
+            "{n_unpopped} style vars were pushed without a matching pop"
+        );
+        if n_unpopped != 0 {
+            log::warn!("style stack is not empty, popping {n_unpopped} vars");
+            self.pop_style_n(n_unpopped as u32);
         }
         // if self.mouse.pressed(MouseBtn::Left) {
         //     println!("{}, {}, {}: {}, {}", !self.mouse.dragging(MouseBtn::Left), !self.expect_drag, self.panel_action.is_none(), self.hot_panel_id, self.hot_id);
@@ -3381,24 +5036,36 @@ impl Context {
         }
 
         self.update_panel_scroll();
-        self.update_panel_resize();
-        self.update_panel_move();
-        self.update_panel_dock();
+        if self.screenshot_mode {
+            self.update_screenshot_mode();
+        } else {
+            self.update_panel_resize();
+            self.update_panel_move();
+            self.update_panel_dock();
+        }
+
+        self.update_wants_input();
 
         self.prev_hot_panel_id = self.hot_panel_id;
         self.prev_active_panel_id = self.active_panel_id;
         self.prev_hot_id = self.hot_id;
         self.prev_active_id = self.active_id;
         self.prev_hot_tabbar_id = self.hot_tabbar_id;
+        self.access.focus = self.active_id;
 
         self.end_assert(Some("##_WINDOW_PANEL"));
 
-        if !self.draw_wireframe {
+        // the gpu draws the wireframe if it can, otherwise the edges are tessellated
+        let gpu_wireframe = self.draw_wireframe && self.wgpu.supports_wireframe();
+        self.draw.wireframe = gpu_wireframe;
+        if !self.draw_wireframe || gpu_wireframe {
             self.build_draw_data();
         } else {
             self.build_dbg_draw_data();
         }
+        self.draw.batch_calls();
         self.n_draw_calls = self.draw.call_list.len();
+        self.render_picking();
 
         // self.prev_item_data.reset();
 
@@ -3420,9 +5087,22 @@ impl Context {
         // }
 
         self.prune_nodes();
+        self.shape_cache.borrow_mut().end_frame();
+        self.widget_data.gc(self.widget_data_max_age);
+        if let Some(settings) = &mut self.settings {
+            settings.flush_if_due();
+            // come back for the changes that were made too soon after the last flush
+            if settings.is_dirty() {
+                self.window.request_redraw();
+            }
+        }
+        self.update_relayout();
+        self.update_attention();
 
         self.frame_count += 1;
+        self.mouse.time = self.clock.now();
         self.mouse.end_frame();
+        self.keyboard.end_frame();
     }
 
     pub fn prune_nodes(&mut self) {
@@ -3470,17 +5150,36 @@ impl Context {
         font_size: f32,
         font: &'static str,
     ) -> ShapedText {
-        let text = match text.find("##") {
-            Some(idx) => text[..idx].to_string(),
-            None => text.to_string(),
-        };
+        self.layout_text_item(TextItem::new(text.to_string(), font_size, 1.0, font))
+    }
+
+    /// size of the laid out text, shapes the text only if it is not cached yet
+    pub fn measure_text_item(&self, mut itm: TextItem) -> Vec2 {
+        if let Some(idx) = itm.string.find("##") {
+            itm.string.truncate(idx);
+        }
+
+        if let Some(shaped) = self.text_item_cache.borrow().get(&itm) {
+            return shaped.size();
+        }
+        self.layout_text_item(itm).size()
+    }
+
+    pub fn measure_text(&self, text: &str, font_size: f32) -> Vec2 {
+        self.measure_text_item(TextItem::new(text.to_string(), font_size, 1.0, self.text_font))
+    }
+
+    pub fn layout_text_item(&self, mut itm: TextItem) -> ShapedText {
+        if let Some(idx) = itm.string.find("##") {
+            itm.string.truncate(idx);
+        }
 
-        let itm = TextItem::new(text, font_size, 1.0, font);
         let mut text_cache = self.text_item_cache.borrow_mut();
         let mut glyph_cache = self.glyph_cache.borrow_mut();
         let mut font_table = self.font_table.clone();
 
         let shaped_text = if !text_cache.contains_key(&itm) {
+            crate::profile_scope!("shape_text");
             let shaped_text = itm.layout(&mut font_table, &mut glyph_cache, &self.wgpu);
             text_cache.entry(itm).or_insert(shaped_text)
         } else {
@@ -3490,11 +5189,51 @@ impl Context {
     }
 
     pub fn layout_text(&self, text: &str, font_size: f32) -> ShapedText {
-        self.layout_text_with_font(text, font_size, "Inter")
+        self.layout_text_with_font(text, font_size, self.text_font)
     }
 
     pub fn layout_icon(&self, text: &str, font_size: f32) -> ShapedText {
-        self.layout_text_with_font(text, font_size, "Phosphor")
+        self.layout_text_with_font(text, font_size, ui::phosphor_font::FAMILY)
+    }
+
+    /// loads the font and makes its glyphs available by name
+    pub fn register_icon_font(&mut self, font: IconFont, bytes: Vec<u8>) {
+        self.font_table.load_font(font.family, bytes);
+        self.icons.register(font);
+    }
+
+    /// lays out the icon `name` of any registered icon font, see [`IconTable::find`]
+    pub fn layout_named_icon(&self, name: &str, font_size: f32) -> Option<ShapedText> {
+        let (family, ch) = self.icons.find(name)?;
+        Some(self.layout_text_with_font(ch.encode_utf8(&mut [0; 4]), font_size, family))
+    }
+
+    /// shapes the icon and the text and returns the offsets that put both on the same baseline
+    ///
+    /// returns `(icon, text, icon_offset, text_offset, size)`
+    pub fn layout_icon_text(
+        &self,
+        icon: &str,
+        text: &str,
+        font_size: f32,
+    ) -> (ShapedText, ShapedText, Vec2, Vec2, Vec2) {
+        let icon = self.layout_named_icon(icon, font_size).unwrap_or_else(|| {
+            log::warn!("unknown icon: {icon}");
+            self.layout_text(icon, font_size)
+        });
+        let text = self.layout_text(text, font_size);
+
+        let baseline = icon.baseline.max(text.baseline);
+        let gap = if text.width > 0.0 { font_size * 0.4 } else { 0.0 };
+
+        let icon_off = Vec2::new(0.0, baseline - icon.baseline);
+        let text_off = Vec2::new(icon.width + gap, baseline - text.baseline);
+        let size = Vec2::new(
+            text_off.x + text.width,
+            (icon_off.y + icon.height).max(text_off.y + text.height),
+        );
+
+        (icon, text, icon_off, text_off, size)
     }
 
     pub fn draw_text(&mut self, text: &str, pos: Vec2) {
@@ -3596,8 +5335,16 @@ impl Context {
         for cmd in draw_list.commands().iter() {
             let vtx = draw_list.vtx_slice(cmd.vtx_offset..cmd.vtx_offset + cmd.vtx_count);
             let idx = draw_list.idx_slice(cmd.idx_offset..cmd.idx_offset + cmd.idx_count);
+            draw_buff.set_topology(cmd.topology);
+            if cmd.topology == wgpu::PrimitiveTopology::LineList {
+                // already a wireframe
+                draw_buff.push(&vtx, &idx);
+                continue;
+            }
+            let tris: Vec<_> = idx.chunks_exact(3).collect();
+            let vtx: &[ui::Vertex] = &vtx;
 
-            for i in idx.chunks_exact(3) {
+            let outline_tri = |i: &&[u32]| {
                 let v0 = vtx[i[0] as usize];
                 let v1 = vtx[i[1] as usize];
                 let v2 = vtx[i[2] as usize];
@@ -3608,14 +5355,20 @@ impl Context {
                 vtx.iter_mut().enumerate().for_each(|(i, v)| {
                     v.col = cols[i % cols.len()];
                 });
+                (vtx, idx)
+            };
 
-                // draw_buff.set_clip_rect(Rect::from_min_size(Vec2::ZERO, self.draw.screen_size));
+            // each outline has 16 vertices, keep the pushed chunks within the draw call limits
+            let max_tris = draw_buff.max_vtx_per_chunk / 16;
+            for tris in tris.chunks(max_tris) {
+                let (vtx, idx) = ui::tessellate_batch(tris, &outline_tri);
                 draw_buff.push(&vtx, &idx);
             }
         }
     }
 
     pub fn build_draw_data(&mut self) {
+        crate::profile_scope!("build_draw_data");
         let order = self.get_panels_in_order();
         // let panels = &self.panels;
         // let draw_buff = &mut self.draw.call_list;
@@ -3634,6 +5387,7 @@ impl Context {
             self.draw.push_drawlist(&p.drawlist_over);
             // Self::build_draw_list(&mut self.draw.call_list, &p.drawlist_over, self.draw.screen_size);
         }
+        self.draw.push_drawlist(&self.overlay);
         // self.upload_draw_data();
 
         // let panels = &self.panels;
@@ -0,0 +1,551 @@
+//! piecewise curves for animation, easing and envelopes and an editor for them, see [`Curve`]
+
+use glam::Vec2;
+
+use crate::{
+    accessibility::AccessRole,
+    core::RGBA,
+    mouse::{CursorIcon, KeyCode, MouseBtn},
+    rect::Rect,
+    ui::{self, Outline, Response, Stroke},
+};
+
+/// length in pixels of the tangent handles of the selected key
+const TANGENT_HANDLE_LEN: f32 = 36.0;
+
+/// how a [`Curve`] goes from a key to the next one
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CurveInterp {
+    /// cubic hermite spline through the tangents of both keys
+    #[default]
+    Smooth,
+    Linear,
+    /// holds the value until the next key
+    Constant,
+}
+
+impl CurveInterp {
+    pub const ALL: [CurveInterp; 3] = [Self::Smooth, Self::Linear, Self::Constant];
+
+    /// the next mode in [`Self::ALL`]
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// a point of a [`Curve`], the tangents are slopes, i.e. the change in y per x
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurveKey {
+    pub pos: Vec2,
+    /// slope arriving at the key
+    pub in_tangent: f32,
+    /// slope leaving the key
+    pub out_tangent: f32,
+    /// interpolation of the segment to the next key
+    pub interp: CurveInterp,
+}
+
+impl CurveKey {
+    /// a smooth key with a flat tangent
+    pub fn new(pos: Vec2) -> Self {
+        Self {
+            pos,
+            in_tangent: 0.0,
+            out_tangent: 0.0,
+            interp: CurveInterp::Smooth,
+        }
+    }
+
+    pub fn with_tangent(mut self, slope: f32) -> Self {
+        self.in_tangent = slope;
+        self.out_tangent = slope;
+        self
+    }
+
+    pub fn with_tangents(mut self, in_tangent: f32, out_tangent: f32) -> Self {
+        self.in_tangent = in_tangent;
+        self.out_tangent = out_tangent;
+        self
+    }
+
+    pub fn with_interp(mut self, interp: CurveInterp) -> Self {
+        self.interp = interp;
+        self
+    }
+}
+
+/// a function of x given by keys, e.g. an animation curve, an easing or an audio envelope.
+/// edited with [`ui::Context::curve_editor`]
+///
+/// before the first and after the last key the curve keeps their value
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Curve {
+    /// sorted by x, the methods keep the order
+    pub keys: Vec<CurveKey>,
+}
+
+impl Curve {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// linear segments through `points`
+    pub fn linear(points: &[Vec2]) -> Self {
+        let mut curve = Self::new();
+        for &p in points {
+            curve.add_key(CurveKey::new(p).with_interp(CurveInterp::Linear));
+        }
+        curve
+    }
+
+    /// smooth ease in and out from `(0, 0)` to `(1, 1)`
+    pub fn ease_in_out() -> Self {
+        Self::new()
+            .with_key(CurveKey::new(Vec2::ZERO))
+            .with_key(CurveKey::new(Vec2::ONE))
+    }
+
+    pub fn with_key(mut self, key: CurveKey) -> Self {
+        self.add_key(key);
+        self
+    }
+
+    /// inserts a key after the keys at the same x, returns its index
+    pub fn add_key(&mut self, key: CurveKey) -> usize {
+        let i = self.keys.partition_point(|k| k.pos.x <= key.pos.x);
+        self.keys.insert(i, key);
+        i
+    }
+
+    pub fn remove_key(&mut self, index: usize) -> Option<CurveKey> {
+        (index < self.keys.len()).then(|| self.keys.remove(index))
+    }
+
+    /// moves a key to `pos`, returns its new index
+    pub fn move_key(&mut self, index: usize, pos: Vec2) -> usize {
+        match self.remove_key(index) {
+            Some(key) => self.add_key(CurveKey { pos, ..key }),
+            None => index,
+        }
+    }
+
+    /// restores the order after `keys` was changed directly
+    pub fn sort(&mut self) {
+        self.keys.sort_by(|a, b| a.pos.x.total_cmp(&b.pos.x));
+    }
+
+    /// the value at `x`, 0 for a curve without keys
+    pub fn eval(&self, x: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return 0.0;
+        };
+        let i = self.keys.partition_point(|k| k.pos.x <= x);
+        if i == 0 {
+            return first.pos.y;
+        }
+        if i == self.keys.len() {
+            return last.pos.y;
+        }
+
+        let (a, b) = (self.keys[i - 1], self.keys[i]);
+        let dx = b.pos.x - a.pos.x;
+        let t = (x - a.pos.x) / dx;
+        match a.interp {
+            CurveInterp::Constant => a.pos.y,
+            CurveInterp::Linear => a.pos.y + (b.pos.y - a.pos.y) * t,
+            CurveInterp::Smooth => {
+                let (t2, t3) = (t * t, t * t * t);
+                let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+                let h10 = t3 - 2.0 * t2 + t;
+                let h01 = -2.0 * t3 + 3.0 * t2;
+                let h11 = t3 - t2;
+                h00 * a.pos.y + h10 * dx * a.out_tangent + h01 * b.pos.y + h11 * dx * b.in_tangent
+            }
+        }
+    }
+
+    /// the slope at `x`, estimated from the values around it
+    pub fn slope(&self, x: f32) -> f32 {
+        let h = 1e-3;
+        (self.eval(x + h) - self.eval(x - h)) / (2.0 * h)
+    }
+
+    /// `n` points evenly spaced in x from `x_min` to `x_max`, e.g. to bake the curve into a table
+    pub fn sample(&self, x_min: f32, x_max: f32, n: usize) -> Vec<Vec2> {
+        let last = n.saturating_sub(1).max(1) as f32;
+        (0..n)
+            .map(|i| {
+                let x = x_min + (x_max - x_min) * i as f32 / last;
+                Vec2::new(x, self.eval(x))
+            })
+            .collect()
+    }
+}
+
+/// options of [`ui::Context::curve_editor_ex`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurveEditor {
+    /// NAN width fills the available width
+    pub size: Vec2,
+    /// the region of the curve that is shown, keys are kept inside of it
+    pub range: Rect,
+    /// keys snap to multiples of the step while dragged, holding shift disables it
+    pub snap: Option<Vec2>,
+    /// distance between the grid lines, the snap step if `None`
+    pub grid: Option<Vec2>,
+    pub col: RGBA,
+}
+
+impl Default for CurveEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CurveEditor {
+    pub fn new() -> Self {
+        Self {
+            size: Vec2::new(f32::NAN, 160.0),
+            range: Rect::from_min_max(Vec2::ZERO, Vec2::ONE),
+            snap: None,
+            grid: None,
+            col: RGBA::CYAN,
+        }
+    }
+
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_range(mut self, min: Vec2, max: Vec2) -> Self {
+        self.range = Rect::from_min_max(min, max);
+        self
+    }
+
+    pub fn with_snap(mut self, step: Vec2) -> Self {
+        self.snap = Some(step);
+        self
+    }
+
+    pub fn with_grid(mut self, step: Vec2) -> Self {
+        self.grid = Some(step);
+        self
+    }
+
+    pub fn with_col(mut self, col: RGBA) -> Self {
+        self.col = col;
+        self
+    }
+}
+
+/// the part of the selected key that is dragged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CurveGrab {
+    Key,
+    InTangent,
+    OutTangent,
+}
+
+/// state of a [`ui::Context::curve_editor`]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct CurveEditState {
+    selected: Option<usize>,
+    grab: Option<CurveGrab>,
+}
+
+/// maps between the values of a curve and the screen
+#[derive(Debug, Clone, Copy)]
+struct CurveView {
+    rect: Rect,
+    range: Rect,
+}
+
+impl CurveView {
+    fn to_screen(self, v: Vec2) -> Vec2 {
+        let t = (v - self.range.min) / self.range.size();
+        Vec2::new(
+            self.rect.min.x + t.x * self.rect.width(),
+            self.rect.max.y - t.y * self.rect.height(),
+        )
+    }
+
+    fn to_value(self, p: Vec2) -> Vec2 {
+        let t = Vec2::new(p.x - self.rect.min.x, self.rect.max.y - p.y) / self.rect.size();
+        self.range.min + t * self.range.size()
+    }
+
+    /// offset in pixels of a tangent handle with `slope`
+    fn tangent_offset(self, slope: f32) -> Vec2 {
+        let scale = self.rect.size() / self.range.size();
+        let dir = Vec2::new(scale.x, -slope * scale.y).normalize_or(Vec2::X);
+        dir * TANGENT_HANDLE_LEN
+    }
+}
+
+impl ui::Context {
+    /// edits a curve in the unit square, see [`Self::curve_editor_ex`]
+    pub fn curve_editor(&mut self, label: &str, curve: &mut Curve) -> Response {
+        self.curve_editor_ex(label, curve, CurveEditor::new())
+    }
+
+    /// edits the keys of a curve on a grid
+    ///
+    /// keys are dragged to move them, a double click adds a key on an empty spot and removes
+    /// the key under the mouse. a click selects a key and shows its tangent handles, dragging
+    /// one handle sets both tangents unless alt is held. a right click on a key switches the
+    /// [`CurveInterp`] of the segment after it
+    pub fn curve_editor_ex(
+        &mut self,
+        label: &str,
+        curve: &mut Curve,
+        opts: CurveEditor,
+    ) -> Response {
+        let id = self.gen_id(label);
+        let old = curve.clone();
+        let mut size = opts.size;
+        if size.x.is_nan() {
+            size.x = self.available_content().x;
+        }
+        let rect = self.place_item(size);
+        let sig = self.reg_item_active_on_press(id, rect);
+        if let Some(node) = self.reg_access_node(id, AccessRole::Image, label, rect) {
+            node.value = Some(format!("{} keys", curve.keys.len()));
+        }
+
+        let range = match opts.range.size().min_element() > 0.0 {
+            true => opts.range,
+            false => Rect::from_min_max(Vec2::ZERO, Vec2::ONE),
+        };
+        let view = CurveView {
+            rect: rect.shrink(4.0),
+            range,
+        };
+        let mut state = self
+            .widget_data
+            .get::<CurveEditState>(&id)
+            .copied()
+            .unwrap_or_default();
+        state.selected = state.selected.filter(|&i| i < curve.keys.len());
+
+        // the key or tangent handle under the mouse, handles of the selected key come first
+        let mouse = self.mouse.pos;
+        let radius = 6.0;
+        let near = |p: Vec2| (p - mouse).length() <= radius;
+        let hovered = if sig.hovering() || sig.pressed() {
+            let tangents = state.selected.and_then(|i| {
+                let key = curve.keys[i];
+                let p = view.to_screen(key.pos);
+                if near(p - view.tangent_offset(key.in_tangent)) {
+                    Some((i, CurveGrab::InTangent))
+                } else if near(p + view.tangent_offset(key.out_tangent)) {
+                    Some((i, CurveGrab::OutTangent))
+                } else {
+                    None
+                }
+            });
+            tangents.or_else(|| {
+                let keys = curve.keys.iter().enumerate();
+                keys.rev()
+                    .find(|(_, k)| near(view.to_screen(k.pos)))
+                    .map(|(i, _)| (i, CurveGrab::Key))
+            })
+        } else {
+            None
+        };
+
+        if sig.double_pressed() {
+            match hovered {
+                Some((i, CurveGrab::Key)) if curve.keys.len() > 1 => {
+                    curve.remove_key(i);
+                    state = CurveEditState::default();
+                }
+                Some(_) => (),
+                None => {
+                    let pos = view.to_value(mouse).clamp(range.min, range.max);
+                    let key = CurveKey::new(pos).with_tangent(curve.slope(pos.x));
+                    let i = curve.add_key(key.with_interp(self.curve_interp_at(curve, pos.x)));
+                    state.selected = Some(i);
+                    state.grab = Some(CurveGrab::Key);
+                }
+            }
+        } else if sig.just_pressed() {
+            self.expect_drag = true;
+            state.selected = hovered.map(|(i, _)| i);
+            state.grab = hovered.map(|(_, grab)| grab);
+        }
+
+        if let Some(i) = state
+            .selected
+            .filter(|_| sig.pressed() && self.mouse.delta != Vec2::ZERO)
+        {
+            let v = view.to_value(mouse);
+            let key = &mut curve.keys[i];
+            match state.grab {
+                Some(CurveGrab::Key) => {
+                    let mut pos = v.clamp(range.min, range.max);
+                    if let Some(step) = opts.snap.filter(|_| !self.modifiers.shift_key()) {
+                        let step = step.max(Vec2::splat(f32::EPSILON));
+                        pos = ((pos / step).round() * step).clamp(range.min, range.max);
+                    }
+                    state.selected = Some(curve.move_key(i, pos));
+                }
+                Some(grab @ (CurveGrab::InTangent | CurveGrab::OutTangent)) => {
+                    let d = v - key.pos;
+                    let min_dx = range.width() * 1e-3;
+                    let slope = match grab {
+                        CurveGrab::InTangent => d.y / d.x.min(-min_dx),
+                        _ => d.y / d.x.max(min_dx),
+                    };
+                    let linked = !self.modifiers.alt_key();
+                    if grab == CurveGrab::InTangent || linked {
+                        key.in_tangent = slope;
+                    }
+                    if grab == CurveGrab::OutTangent || linked {
+                        key.out_tangent = slope;
+                    }
+                }
+                None => (),
+            }
+        }
+        if !sig.pressed() {
+            state.grab = None;
+        }
+        let right_click = self.mouse.just_pressed(MouseBtn::Right);
+        if let Some((i, CurveGrab::Key)) = hovered.filter(|_| right_click) {
+            curve.keys[i].interp = curve.keys[i].interp.next();
+        }
+        let delete = self.key_just_pressed(KeyCode::Delete) && curve.keys.len() > 1;
+        if let Some(i) = state.selected.filter(|_| delete) {
+            curve.remove_key(i);
+            state = CurveEditState::default();
+        }
+
+        if state.grab.is_some() {
+            self.set_cursor_icon(CursorIcon::Crosshair);
+        } else if hovered.is_some() {
+            self.set_cursor_icon(CursorIcon::Pointer);
+        }
+
+        if !self.item_culled() {
+            self.draw_curve_editor(label, curve, &state, hovered, view, &opts);
+        }
+
+        match state == CurveEditState::default() {
+            true => {
+                self.widget_data.remove::<CurveEditState>(&id);
+            }
+            false => self.widget_data.insert(id, state),
+        }
+        let changed = *curve != old;
+        self.value_response(id, sig, state.grab.is_some(), changed)
+    }
+
+    /// interpolation of the segment a new key at `x` is inserted into
+    fn curve_interp_at(&self, curve: &Curve, x: f32) -> CurveInterp {
+        let i = curve.keys.partition_point(|k| k.pos.x <= x);
+        i.checked_sub(1)
+            .map_or(CurveInterp::Smooth, |i| curve.keys[i].interp)
+    }
+
+    fn draw_curve_editor(
+        &mut self,
+        label: &str,
+        curve: &Curve,
+        state: &CurveEditState,
+        hovered: Option<(usize, CurveGrab)>,
+        view: CurveView,
+        opts: &CurveEditor,
+    ) {
+        let text_col = self.style.text_col();
+        let text_size = self.style.text_size() * 0.85;
+        let shape = self.layout_text(label, text_size);
+        let painter = self.painter(view.rect.expand(4.0));
+        painter.draw(
+            painter
+                .rect()
+                .draw_rect()
+                .fill(self.style.panel_dark_bg())
+                .corners(self.style.btn_corners()),
+        );
+
+        // grid lines, at most 64 per axis
+        let range = view.range;
+        let step = opts.grid.or(opts.snap).unwrap_or(range.size() * 0.25);
+        let grid_col = text_col.with_alpha(0.08);
+        for axis in 0..2 {
+            let step = step[axis].max(range.size()[axis] / 64.0);
+            let mut v = (range.min[axis] / step).ceil() * step;
+            while v <= range.max[axis] {
+                let (a, b) = match axis {
+                    0 => (Vec2::new(v, range.min.y), Vec2::new(v, range.max.y)),
+                    _ => (Vec2::new(range.min.x, v), Vec2::new(range.max.x, v)),
+                };
+                painter.add_line(
+                    view.to_screen(a),
+                    view.to_screen(b),
+                    Stroke::new(grid_col, 1.0),
+                );
+                v += step;
+            }
+        }
+
+        // a point every two pixels, plus the jumps of constant segments
+        let n = (view.rect.width() / 2.0).max(2.0) as usize;
+        let mut points = curve.sample(range.min.x, range.max.x, n);
+        for (a, b) in curve.keys.iter().zip(curve.keys.iter().skip(1)) {
+            if a.interp == CurveInterp::Constant {
+                let x = b.pos.x - 1e-4 * range.width();
+                let i = points.partition_point(|p| p.x < x);
+                points.insert(i, Vec2::new(x, a.pos.y));
+            }
+        }
+        let points: Vec<Vec2> = points.into_iter().map(|p| view.to_screen(p)).collect();
+        painter.add_polyline(&points, Stroke::new(opts.col, 1.5), false);
+
+        for (i, key) in curve.keys.iter().enumerate() {
+            let p = view.to_screen(key.pos);
+            let selected = state.selected == Some(i);
+            if selected {
+                let handle_col = text_col.with_alpha(0.6);
+                for (grab, h) in [
+                    (
+                        CurveGrab::InTangent,
+                        p - view.tangent_offset(key.in_tangent),
+                    ),
+                    (
+                        CurveGrab::OutTangent,
+                        p + view.tangent_offset(key.out_tangent),
+                    ),
+                ] {
+                    painter.add_line(p, h, Stroke::new(handle_col, 1.0));
+                    let fill = match hovered == Some((i, grab)) || state.grab == Some(grab) {
+                        true => text_col,
+                        false => handle_col,
+                    };
+                    painter.draw(
+                        Rect::from_center_size(h, Vec2::splat(6.0))
+                            .draw_rect()
+                            .fill(fill),
+                    );
+                }
+            }
+
+            let hot = selected || hovered == Some((i, CurveGrab::Key));
+            let fill = if hot { text_col } else { opts.col };
+            painter.draw(
+                Rect::from_center_size(p, Vec2::splat(8.0))
+                    .draw_rect()
+                    .circle()
+                    .fill(fill)
+                    .outline(Outline::hairline(self.style.panel_dark_bg())),
+            );
+        }
+
+        painter.text(painter.rect().min + Vec2::splat(4.0), &shape, text_col);
+    }
+}
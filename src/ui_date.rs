@@ -0,0 +1,314 @@
+//! calendar date and time pickers, enabled by the `chrono` feature
+
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveTime, Timelike};
+use glam::Vec2;
+
+use crate::{
+    accessibility::AccessRole,
+    mouse::CursorIcon,
+    rect::Rect,
+    ui::{self, Id, Outline, Response, Signal},
+};
+
+/// vertical drag distance in pixels that changes a time field by one
+const TIME_DRAG_STEP: f32 = 8.0;
+
+/// inclusive range of days, see [`ui::Context::date_range_picker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    /// orders `a` and `b` so that `start <= end`
+    pub fn new(a: NaiveDate, b: NaiveDate) -> Self {
+        Self {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    pub fn single(date: NaiveDate) -> Self {
+        Self::new(date, date)
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// number of days in the range, including both ends
+    pub fn days(&self) -> u64 {
+        (self.end - self.start).num_days() as u64 + 1
+    }
+}
+
+/// first day of the month a calendar shows, stored in the widget data
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CalendarMonth(NaiveDate);
+
+/// first day of a range selection waiting for the second click
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RangeAnchor(NaiveDate);
+
+/// drag distance of a time field not yet applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TimeDrag(f32);
+
+/// the 6 weeks shown for the month of `date`, starting on the monday on or before the first
+pub(crate) fn month_grid(date: NaiveDate) -> [NaiveDate; 42] {
+    let first = date.with_day(1).unwrap();
+    let start = first - Days::new(first.weekday().num_days_from_monday() as u64);
+    std::array::from_fn(|i| start + Days::new(i as u64))
+}
+
+impl ui::Context {
+    /// calendar to pick a single day
+    pub fn date_picker(&mut self, label: &str, date: &mut NaiveDate) -> Response {
+        let id = self.gen_id(label);
+        let (sig, picked) = self.calendar(id, label, DateRange::single(*date), None);
+        let changed = picked.is_some_and(|day| day != *date);
+        if let Some(day) = picked {
+            *date = day;
+        }
+        self.value_response(id, sig, false, changed)
+    }
+
+    /// calendar to pick a range of days, the first click picks one end and the second click
+    /// the other. the interaction lasts from the first to the second click
+    pub fn date_range_picker(&mut self, label: &str, range: &mut DateRange) -> Response {
+        let id = self.gen_id(label);
+        let anchor = self.widget_data.get::<RangeAnchor>(&id).map(|a| a.0);
+        let (sig, picked) = self.calendar(id, label, *range, anchor);
+
+        let mut changed = false;
+        match (anchor, picked) {
+            (_, None) => (),
+            (None, Some(day)) => {
+                self.widget_data.insert(id, RangeAnchor(day));
+            }
+            (Some(anchor), Some(day)) => {
+                self.widget_data.remove::<RangeAnchor>(&id);
+                let new = DateRange::new(anchor, day);
+                changed = new != *range;
+                *range = new;
+            }
+        }
+        let engaged = self.widget_data.contains_key::<RangeAnchor>(&id);
+        self.value_response(id, sig, engaged, changed)
+    }
+
+    /// month grid with navigation, highlights `range` or, while `anchor` is set, the range from
+    /// the anchor to the hovered day. returns the signals of all cells and the clicked day
+    fn calendar(
+        &mut self,
+        id: Id,
+        label: &str,
+        range: DateRange,
+        anchor: Option<NaiveDate>,
+    ) -> (Signal, Option<NaiveDate>) {
+        let cell = self.style.line_height();
+        let rect = self.place_item(Vec2::new(cell * 7.0, cell * 8.0));
+        let mut calendar_sig = self.reg_item_(id, rect);
+        self.reg_access_node(id, AccessRole::Label, label, rect);
+        let cell_rect = |col: usize, row: usize| {
+            Rect::from_min_size(
+                rect.min + Vec2::new(col as f32, row as f32) * cell,
+                Vec2::splat(cell),
+            )
+        };
+
+        let mut month = self
+            .widget_data
+            .get::<CalendarMonth>(&id)
+            .map_or_else(|| range.start.with_day(1).unwrap(), |m| m.0);
+
+        let prev_id = Id::from_hash(&(id, "prev"));
+        let next_id = Id::from_hash(&(id, "next"));
+        let prev = self.reg_item_active_on_press(prev_id, cell_rect(0, 0));
+        let next = self.reg_item_active_on_press(next_id, cell_rect(6, 0));
+        self.reg_access_node(prev_id, AccessRole::Button, "previous month", cell_rect(0, 0));
+        self.reg_access_node(next_id, AccessRole::Button, "next month", cell_rect(6, 0));
+        if prev.clicked() {
+            month = month.checked_sub_months(Months::new(1)).unwrap_or(month);
+        }
+        if next.clicked() {
+            month = month.checked_add_months(Months::new(1)).unwrap_or(month);
+        }
+        self.widget_data.insert(id, CalendarMonth(month));
+
+        let days = month_grid(month);
+        let mut hovered = None;
+        let mut picked = None;
+        let mut day_sigs = [Signal::NONE; 42];
+        for (i, (&day, sig)) in days.iter().zip(&mut day_sigs).enumerate() {
+            let day_id = Id::from_hash(&(id, day));
+            *sig = self.reg_item_active_on_press(day_id, cell_rect(i % 7, 2 + i / 7));
+            if sig.hovering() {
+                hovered = Some(day);
+            }
+            if sig.clicked() {
+                picked = Some(day);
+            }
+        }
+        if picked.is_some_and(|d| d.month() != month.month()) {
+            self.widget_data.insert(id, CalendarMonth(picked.unwrap().with_day(1).unwrap()));
+        }
+        for sig in day_sigs.iter().chain([&prev, &next]) {
+            calendar_sig |= *sig;
+        }
+
+        if self.item_culled() {
+            return (calendar_sig, picked);
+        }
+
+        let text_size = self.style.text_size();
+        let text_col = self.style.text_col();
+        let corners = self.style.btn_corners();
+
+        for (sig, r, icon) in [
+            (prev, cell_rect(0, 0), ui::phosphor_font::CARET_LEFT),
+            (next, cell_rect(6, 0), ui::phosphor_font::CARET_RIGHT),
+        ] {
+            if sig.hovering() {
+                self.draw(r.draw_rect().fill(self.style.btn_hover()).corners(corners));
+            }
+            let shape = self.layout_icon(icon, text_size);
+            self.draw(shape.draw_rects(r.center() - shape.size() * 0.5, text_col));
+        }
+        let title = self.layout_text(&month.format("%B %Y").to_string(), text_size);
+        let title_pos = Rect::from_min_max(cell_rect(1, 0).min, cell_rect(5, 0).max).center();
+        self.draw(title.draw_rects(title_pos - title.size() * 0.5, text_col));
+
+        let weekday_col = text_col.with_alpha(0.6);
+        for (i, name) in ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"].iter().enumerate() {
+            let shape = self.layout_text(name, text_size);
+            let pos = cell_rect(i, 1).center() - shape.size() * 0.5;
+            self.draw(shape.draw_rects(pos, weekday_col));
+        }
+
+        let shown = anchor.map_or(range, |a| DateRange::new(a, hovered.unwrap_or(a)));
+        let today = chrono::Local::now().date_naive();
+        for (i, (&day, sig)) in days.iter().zip(&day_sigs).enumerate() {
+            let r = cell_rect(i % 7, 2 + i / 7);
+            let is_end = day == shown.start || day == shown.end;
+
+            let mut bg = if is_end {
+                Some(self.style.btn_press())
+            } else if shown.contains(day) || sig.hovering() {
+                Some(self.style.btn_hover())
+            } else {
+                None
+            };
+            if sig.pressed() && !is_end {
+                bg = Some(self.style.btn_default());
+            }
+            let mut col = if is_end { self.style.btn_press_text() } else { text_col };
+            if day.month() != month.month() {
+                col = col.with_alpha(0.4);
+            }
+
+            let mut cell_bg = r.shrink(1.0).draw_rect().corners(corners);
+            if let Some(bg) = bg {
+                cell_bg = cell_bg.fill(bg);
+            }
+            if day == today {
+                cell_bg = cell_bg.outline(Outline::center(text_col, 1.0));
+            }
+            self.draw(cell_bg);
+
+            let shape = self.layout_text(&day.day().to_string(), text_size);
+            self.draw(shape.draw_rects(r.center() - shape.size() * 0.5, col));
+        }
+
+        self.same_line();
+        self.text(label);
+        (calendar_sig, picked)
+    }
+
+    /// hour and minute fields, drag a field up or down to change it. seconds are kept as they
+    /// are
+    pub fn time_picker(&mut self, label: &str, time: &mut NaiveTime) -> Response {
+        let id = self.gen_id(label);
+        let height = self.style.line_height();
+        let text_size = self.style.text_size();
+        let field_w = self.measure_text("00", text_size).x + height * 0.6;
+        let sep_w = height * 0.4;
+        let rect = self.place_item(Vec2::new(field_w * 2.0 + sep_w, height));
+
+        let mut vals = [time.hour(), time.minute()];
+        let (mut time_sig, mut engaged) = (Signal::NONE, false);
+        let fields = [
+            ("hour", 24, Rect::from_min_size(rect.min, Vec2::new(field_w, height))),
+            (
+                "minute",
+                60,
+                Rect::from_min_size(
+                    rect.min + Vec2::new(field_w + sep_w, 0.0),
+                    Vec2::new(field_w, height),
+                ),
+            ),
+        ];
+
+        for ((name, modulo, r), val) in fields.into_iter().zip(&mut vals) {
+            let field_id = Id::from_hash(&(id, name));
+            let sig = self.reg_item_active_on_press(field_id, r);
+            time_sig |= sig;
+            engaged |= sig.pressed() || sig.dragging();
+
+            if sig.pressed() && !sig.dragging() {
+                self.expect_drag = true;
+            }
+            if sig.pressed() || sig.dragging() {
+                let drag = self.widget_data.get::<TimeDrag>(&field_id).map_or(0.0, |d| d.0)
+                    - self.mouse.delta.y / TIME_DRAG_STEP;
+                let steps = drag.trunc();
+                self.widget_data.insert(field_id, TimeDrag(drag - steps));
+                *val = (*val as i32 + steps as i32).rem_euclid(modulo) as u32;
+            } else if self.widget_data.contains_key::<TimeDrag>(&field_id) {
+                self.widget_data.remove::<TimeDrag>(&field_id);
+            }
+
+            let max = (modulo - 1) as f32;
+            let mut v = *val as f32;
+            if let Some(action) = self.access.take_action(field_id) {
+                action.apply_numeric(&mut v, 0.0, max);
+                *val = v.round() as u32;
+            }
+            if let Some(node) = self.reg_access_node(field_id, AccessRole::Slider, name, r) {
+                node.numeric = Some((*val as f32, 0.0, max));
+            }
+
+            if sig.hovering() || sig.dragging() {
+                self.set_cursor_icon(CursorIcon::MoveV);
+            }
+
+            if !self.item_culled() {
+                let bg = if sig.dragging() || sig.pressed() {
+                    self.style.btn_press()
+                } else if sig.hovering() {
+                    self.style.btn_hover()
+                } else {
+                    self.style.btn_default()
+                };
+                self.draw(r.draw_rect().corners(self.style.btn_corners()).fill(bg));
+                let shape = self.layout_text(&format!("{:02}", *val), text_size);
+                self.draw(shape.draw_rects(r.center() - shape.size() * 0.5, self.style.text_col()));
+            }
+        }
+
+        if !self.item_culled() {
+            let shape = self.layout_text(":", text_size);
+            let sep_center = rect.min + Vec2::new(field_w + sep_w * 0.5, height * 0.5);
+            self.draw(shape.draw_rects(sep_center - shape.size() * 0.5, self.style.text_col()));
+        }
+
+        self.same_line();
+        self.text(label);
+
+        let new = NaiveTime::from_hms_opt(vals[0], vals[1], time.second()).unwrap_or(*time);
+        let changed = new != *time;
+        *time = new;
+        self.value_response(id, time_sig, engaged, changed)
+    }
+}
@@ -0,0 +1,248 @@
+//! ui recorded on worker threads and replayed by the ui thread, see [`DeferredUi`]
+
+use std::sync::mpsc;
+
+use glam::Vec2;
+
+use crate::{
+    core::{HashMap, RGBA},
+    rect::Rect,
+    ui::{self, CornerRadii, HeatmapPlot, LineMode, StreamBuffer, StreamPlot, Stroke},
+};
+
+/// a shape of a [`DeferredItem::Canvas`], relative to the top left corner of the canvas
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeferredShape {
+    Polyline {
+        points: Vec<Vec2>,
+        stroke: Stroke,
+        closed: bool,
+        mode: LineMode,
+    },
+    Rect {
+        rect: Rect,
+        fill: RGBA,
+        corners: CornerRadii,
+    },
+}
+
+/// an element of a [`DeferredUi`], owns all of its data so it can be prepared on any thread
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeferredItem {
+    Text(String),
+    Separator,
+    StreamPlot {
+        label: String,
+        buffer: StreamBuffer,
+        opts: StreamPlot,
+    },
+    Heatmap {
+        label: String,
+        values: Vec<f32>,
+        width: u32,
+        height: u32,
+        opts: HeatmapPlot,
+    },
+    Canvas {
+        size: Vec2,
+        shapes: Vec<DeferredShape>,
+    },
+}
+
+/// items recorded without a [`ui::Context`], e.g. plots of data that is expensive to prepare
+///
+/// the items do not depend on the layout, they are placed when the ui thread replays them with
+/// [`ui::Context::deferred_ui`]. send finished buffers with a [`DeferredSender`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeferredUi {
+    /// the buffer replaces the last one sent with the same key
+    pub key: String,
+    pub items: Vec<DeferredItem>,
+}
+
+impl DeferredUi {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn text(&mut self, text: impl Into<String>) {
+        self.items.push(DeferredItem::Text(text.into()));
+    }
+
+    pub fn separator(&mut self) {
+        self.items.push(DeferredItem::Separator);
+    }
+
+    /// see [`ui::Context::stream_plot_ex`]
+    pub fn stream_plot(
+        &mut self,
+        label: impl Into<String>,
+        buffer: StreamBuffer,
+        opts: StreamPlot,
+    ) {
+        self.items.push(DeferredItem::StreamPlot {
+            label: label.into(),
+            buffer,
+            opts,
+        });
+    }
+
+    /// see [`ui::Context::plot_heatmap_ex`]
+    pub fn plot_heatmap(
+        &mut self,
+        label: impl Into<String>,
+        values: Vec<f32>,
+        width: u32,
+        height: u32,
+        opts: HeatmapPlot,
+    ) {
+        assert_eq!(values.len(), (width * height) as usize);
+        self.items.push(DeferredItem::Heatmap {
+            label: label.into(),
+            values,
+            width,
+            height,
+            opts,
+        });
+    }
+
+    /// an item of `size` with custom shapes, NAN width fills the available width
+    pub fn canvas(&mut self, size: Vec2, shapes: Vec<DeferredShape>) {
+        self.items.push(DeferredItem::Canvas { size, shapes });
+    }
+}
+
+/// sends [`DeferredUi`]s from any thread to a [`ui::Context`], see
+/// [`ui::Context::deferred_sender`]
+#[derive(Debug, Clone)]
+pub struct DeferredSender(mpsc::Sender<DeferredUi>);
+
+impl DeferredSender {
+    /// returns false if the context was dropped
+    pub fn send(&self, ui: DeferredUi) -> bool {
+        self.0.send(ui).is_ok()
+    }
+}
+
+/// the receiving end of the [`DeferredSender`]s of a context and the newest buffer per key
+#[derive(Debug)]
+pub struct DeferredQueue {
+    sender: mpsc::Sender<DeferredUi>,
+    receiver: mpsc::Receiver<DeferredUi>,
+    pub latest: HashMap<String, DeferredUi>,
+}
+
+impl Default for DeferredQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeferredQueue {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            latest: HashMap::new(),
+        }
+    }
+
+    pub fn sender(&self) -> DeferredSender {
+        DeferredSender(self.sender.clone())
+    }
+
+    /// takes the buffers sent since the last call, returns the number of buffers
+    pub fn receive(&mut self) -> usize {
+        let mut n = 0;
+        for ui in self.receiver.try_iter() {
+            self.latest.insert(ui.key.clone(), ui);
+            n += 1;
+        }
+        n
+    }
+}
+
+impl ui::Context {
+    /// a handle to record ui on other threads, buffers sent with it are received at the start
+    /// of the next frame
+    pub fn deferred_sender(&self) -> DeferredSender {
+        self.deferred.sender()
+    }
+
+    /// replaces the buffer of `ui.key` directly, e.g. with the result of a scoped thread
+    pub fn submit_deferred(&mut self, ui: DeferredUi) {
+        self.deferred.latest.insert(ui.key.clone(), ui);
+    }
+
+    /// lays out the newest buffer sent with `key` into the current panel. returns false if no
+    /// buffer was received yet
+    ///
+    /// the buffer stays until it is replaced, so it is shown every frame while the worker
+    /// prepares the next one
+    pub fn deferred_ui(&mut self, key: &str) -> bool {
+        // taken out while replaying, the items need the context mutably
+        let Some(ui) = self.deferred.latest.remove(key) else {
+            return false;
+        };
+
+        for item in &ui.items {
+            match item {
+                DeferredItem::Text(text) => self.text(text),
+                DeferredItem::Separator => self.separator(),
+                DeferredItem::StreamPlot { label, buffer, opts } => {
+                    self.stream_plot_ex(label, buffer, *opts);
+                }
+                DeferredItem::Heatmap {
+                    label,
+                    values,
+                    width,
+                    height,
+                    opts,
+                } => {
+                    self.plot_heatmap_ex(label, values, *width, *height, *opts);
+                }
+                DeferredItem::Canvas { size, shapes } => self.deferred_canvas(*size, shapes),
+            }
+        }
+
+        self.deferred.latest.insert(ui.key.clone(), ui);
+        true
+    }
+
+    fn deferred_canvas(&mut self, mut size: Vec2, shapes: &[DeferredShape]) {
+        if size.x.is_nan() {
+            size.x = self.available_content().x;
+        }
+        let (rect, _) = self.allocate_rect(size, ui::Sense::Hover);
+        if self.item_culled() {
+            return;
+        }
+
+        let painter = self.painter(rect);
+        for shape in shapes {
+            match shape {
+                DeferredShape::Polyline {
+                    points,
+                    stroke,
+                    closed,
+                    mode,
+                } => {
+                    let points: Vec<Vec2> = points.iter().map(|&p| painter.to_screen(p)).collect();
+                    painter.add_polyline_ex(&points, *stroke, *closed, *mode);
+                }
+                DeferredShape::Rect {
+                    rect,
+                    fill,
+                    corners,
+                } => {
+                    let rect = Rect::from_min_size(painter.to_screen(rect.min), rect.size());
+                    painter.draw(rect.draw_rect().fill(*fill).corners(*corners));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,429 @@
+//! a panel showcasing the widgets, layout containers and style options, see
+//! [`Context::demo_window`]
+//!
+//! every item shows the code that created it as a tooltip, so the demo doubles as a reference
+
+use glam::Vec2;
+
+use crate::{
+    core::RGBA,
+    mouse::{KeyCode, Modifiers, Shortcut},
+    rect::Rect,
+    ui::{
+        self, Context, CornerRadii, Frame, Outline, PanelFlag, Shadow, StyleVar, TextAlign,
+        TextWrap,
+    },
+    ui_items::ui_text,
+};
+
+/// runs the code and attaches it as a tooltip to the last placed item
+macro_rules! demo {
+    ($ui:ident: $($code:tt)*) => {{
+        let res = { $($code)* };
+        $ui.item_tooltip(stringify!($($code)*));
+        res
+    }};
+}
+
+impl Context {
+    /// a panel showing every widget, layout container and style option
+    ///
+    /// hovering an item shows the code that created it
+    pub fn demo_window(&mut self) {
+        self.next.initial_width = 420.0;
+        self.begin_ex(
+            "Demo##_DEMO_WINDOW",
+            PanelFlag::DRAW_V_SCROLLBAR | PanelFlag::ZOOMABLE,
+        );
+        let ui = self;
+
+        ui.text_ex(
+            "hover an item to see the code that created it, ctrl + wheel zooms",
+            TextWrap::Wrap,
+            None,
+        );
+        ui.separator();
+
+        if ui.collapsing_header_intern("Text") {
+            demo_text(ui);
+        }
+        if ui.collapsing_header_intern("Buttons") {
+            demo_buttons(ui);
+        }
+        if ui.collapsing_header_intern("Inputs") {
+            demo_inputs(ui);
+        }
+        if ui.collapsing_header_intern("Layout") {
+            demo_layout(ui);
+        }
+        if ui.collapsing_header_intern("Plots") {
+            demo_plots(ui);
+        }
+        if ui.collapsing_header_intern("Custom widgets") {
+            demo_custom(ui);
+        }
+        if ui.collapsing_header_intern("Style") {
+            demo_style(ui);
+        }
+
+        ui.end();
+    }
+}
+
+fn demo_text(ui: &mut Context) {
+    demo!(ui: ui.text("plain text"));
+    demo!(ui: ui.text_ex(
+        "long text is wrapped at the edge of the panel instead of being cut off",
+        TextWrap::Wrap,
+        None,
+    ));
+    demo!(ui: ui.text_ex(
+        "or truncated to a number of lines, the last line ends with an ellipsis",
+        TextWrap::Truncate,
+        Some(1),
+    ));
+    demo!(ui: ui.text_aligned("centered", TextAlign::Center));
+    demo!(ui: ui.text_aligned("right aligned", TextAlign::Right));
+    demo!(ui: ui.icon("push-pin", ui.style().text_size(), RGBA::SAFFRON));
+    ui.same_line();
+    demo!(ui: ui.icon_text(ui::phosphor_font::CARET_RIGHT, "icon and text"));
+
+    demo!(ui: ui.code(
+        "rust code",
+        ui::CodeLanguage::Rust,
+        "/// greets `name`\n#[inline]\nfn greet(name: &str) -> String {\n    \
+         let n = name.len() * 2; // counts twice\n    format!(\"hello {name}, {n}\")\n}",
+    ));
+    demo!(ui: ui.code(
+        "wgsl code",
+        ui::CodeLanguage::Wgsl,
+        "@fragment\nfn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {\n    \
+         return vec4(uv, 0.5, 1.0); /* a very long line that scrolls horizontally once it \
+         is wider than the panel */\n}",
+    ));
+}
+
+fn demo_buttons(ui: &mut Context) {
+    let clicks_id = ui.gen_id("##clicks");
+
+    demo!(ui: if ui.button("click me") {
+        *ui.data_get_or_insert_with(clicks_id, || 0u32) += 1;
+    });
+    ui.same_line();
+    let clicks = *ui.data_get_or_insert_with(clicks_id, || 0u32);
+    ui_text!(ui: "clicked {clicks} times");
+
+    demo!(ui: ui.icon_button(ui::phosphor_font::PUSH_PIN, "pin"));
+    ui.same_line();
+    demo!(ui: ui.icon_button(ui::phosphor_font::X, ""));
+
+    demo!(ui: ui.checkbox_intern("checkbox"));
+    demo!(ui: ui.switch_intern("switch"));
+
+    let palette_id = ui.gen_id("##palette");
+    let mut palette = ui.data_remove(palette_id).unwrap_or_else(|| {
+        let mut palette = ui::CommandPalette::<fn(&mut u32)>::new();
+        let alt_i = Shortcut::new(Modifiers::ALT, KeyCode::KeyI);
+        palette
+            .add_with_shortcut("increment clicks", alt_i, |n| *n += 1)
+            .add("double clicks", |n| *n *= 2)
+            .add("reset clicks", |n| *n = 0);
+        palette
+    });
+    demo!(ui: if ui.button("command palette") {
+        palette.open();
+    });
+    ui.same_line();
+    ui_text!(ui: "or press {}", palette.toggle);
+    if let Some(run) = ui.command_palette(&mut palette) {
+        run(ui.data_get_or_insert_with(clicks_id, || 0u32));
+    }
+    ui.data_insert(palette_id, palette);
+}
+
+fn demo_inputs(ui: &mut Context) {
+    demo!(ui: ui.slider_f32_intern("slider", 0.0, 10.0));
+
+    let val_id = ui.gen_id("##input_slider");
+    let mut val = *ui.data_get_or_insert_with(val_id, || 0.5f32);
+    demo!(ui: ui.input_slider_f32("input slider", 0.0, 1.0, &mut val));
+    ui.data_insert(val_id, val);
+
+    let knob_id = ui.gen_id("##knob");
+    let mut knob = *ui.data_get_or_insert_with(knob_id, || 40.0f32);
+    // the value after every finished interaction, the last one is the current value
+    let history_id = ui.gen_id("##knob_history");
+    let mut history = ui.data_remove(history_id).unwrap_or_else(|| vec![knob]);
+    let res = demo!(ui: ui.knob("knob", 0.0, 100.0, &mut knob));
+    if res.drag_stopped() && history.last() != Some(&knob) {
+        history.push(knob);
+    }
+    ui.same_line();
+    if ui.button("undo") && history.len() > 1 {
+        history.pop();
+        knob = history[history.len() - 1];
+    }
+    ui.data_insert(history_id, history);
+    ui.data_insert(knob_id, knob);
+    demo!(ui: ui.gauge_ex(
+        "gauge",
+        0.0,
+        100.0,
+        knob,
+        &[
+            (0.0, 60.0, RGBA::rgb(70, 167, 88)),
+            (60.0, 85.0, RGBA::SAFFRON),
+            (85.0, 100.0, RGBA::RED),
+        ],
+    ));
+
+    demo!(ui: ui.input_text("text field", "edit me"));
+
+    let options_id = ui.gen_id("##select_options");
+    let options = ui
+        .data_remove::<Vec<String>>(options_id)
+        .unwrap_or_else(|| (0..10_000).map(|i| format!("option {i}")).collect());
+    let select_id = ui.gen_id("##select");
+    let mut selected = *ui.data_get_or_insert_with(select_id, || None::<usize>);
+    demo!(ui: ui.searchable_select("searchable select", &options, &mut selected));
+    ui.data_insert(select_id, selected);
+    ui.data_insert(options_id, options);
+
+    #[cfg(feature = "chrono")]
+    {
+        use chrono::NaiveTime;
+
+        let date_id = ui.gen_id("##date");
+        let mut date = *ui.data_get_or_insert_with(date_id, || {
+            chrono::Local::now().date_naive()
+        });
+        demo!(ui: ui.date_picker("date", &mut date));
+        ui.data_insert(date_id, date);
+
+        let range_id = ui.gen_id("##date_range");
+        let mut range = *ui.data_get_or_insert_with(range_id, || {
+            ui::DateRange::new(date, date + chrono::Days::new(4))
+        });
+        demo!(ui: ui.date_range_picker("date range", &mut range));
+        ui.data_insert(range_id, range);
+        ui_text!(ui: "{} - {}, {} days", range.start, range.end, range.days());
+
+        let time_id = ui.gen_id("##time");
+        let mut time = *ui.data_get_or_insert_with(time_id, || {
+            NaiveTime::from_hms_opt(12, 30, 0).unwrap()
+        });
+        demo!(ui: ui.time_picker("time", &mut time));
+        ui.data_insert(time_id, time);
+    }
+}
+
+fn demo_layout(ui: &mut Context) {
+    demo!(ui: {
+        ui.button("one");
+        ui.same_line();
+        ui.button("two")
+    });
+
+    demo!(ui: {
+        ui.indent(20.0);
+        ui.button("indented");
+        ui.unindent(20.0);
+    });
+
+    demo!(ui: ui.separator());
+
+    demo!(ui: ui.frame(Frame::group(ui.style()), |ui| {
+        ui.text("grouped in a frame");
+        ui.button("inside");
+    }));
+
+    demo!(ui: {
+        ui.begin_child("child panel");
+        for i in 0..20 {
+            ui_text!(ui: "scrolling line {i}");
+        }
+        ui.end_child();
+    });
+
+    demo!(ui: {
+        ui.begin_tabbar("tabs");
+        if ui.tabitem("first") {
+            ui.text("content of the first tab");
+        }
+        if ui.tabitem("second") {
+            ui.text("content of the second tab");
+        }
+        ui.end_tabbar();
+    });
+}
+
+fn demo_plots(ui: &mut Context) {
+    let id = ui.gen_id("##stream");
+    let mut buffer = ui
+        .data_remove::<ui::StreamBuffer>(id)
+        .unwrap_or_else(|| ui::StreamBuffer::new(20_000));
+    // 200 samples per frame of a noisy sine
+    let t = ui.time_since_start();
+    buffer.extend((0..200).map(|i| {
+        let t = t + i as f32 / 12_000.0;
+        (t * 3.0).sin() + (t * 997.0).sin() * 0.2
+    }));
+
+    demo!(ui: ui.stream_plot("scope", &buffer));
+    demo!(ui: ui.stream_plot_ex(
+        "last 500 samples",
+        &buffer,
+        ui::StreamPlot::new().with_window(500).with_range(-1.5, 1.5).with_col(RGBA::SAFFRON),
+    ));
+    ui.data_insert(id, buffer);
+
+    let cmap_id = ui.gen_id("##colormap");
+    let mut cmap = *ui.data_get_or_insert_with(cmap_id, ui::Colormap::default);
+    for c in ui::Colormap::ALL {
+        if ui.button(c.name()) {
+            cmap = c;
+        }
+        ui.same_line();
+    }
+    ui.new_line();
+    ui.data_insert(cmap_id, cmap);
+
+    let (w, h) = (64, 32);
+    let field: Vec<f32> = (0..w * h)
+        .map(|i| {
+            let (x, y) = ((i % w) as f32 / 8.0, (i / w) as f32 / 8.0);
+            (x + t).sin() * (y - t * 0.5).cos()
+        })
+        .collect();
+    demo!(ui: ui.plot_heatmap("heatmap", &field, w, h, cmap));
+    ui.window.request_redraw();
+}
+
+fn demo_custom(ui: &mut Context) {
+    demo!(ui: ui.add(|ui: &mut Context| {
+        let id = ui.gen_id("swatch");
+        let size = Vec2::splat(ui.style().line_height());
+        let (rect, sig) = ui.allocate_rect_id(id, size, ui::Sense::Click);
+        let hue = ui.data_get_or_insert_with(id, || 0.0f32);
+        if sig.clicked() {
+            *hue = (*hue + 40.0) % 360.0;
+        }
+        let col = RGBA::from(crate::core::HSVA::new(*hue, 0.6, 0.9, 1.0));
+        ui.painter(rect).draw(rect.draw_rect().fill(col).corners(4.0));
+        sig
+    }));
+
+    demo!(ui: {
+        let (rect, _) = ui.allocate_rect(Vec2::new(120.0, 40.0), ui::Sense::Hover);
+        let pts: Vec<Vec2> = (0..=32)
+            .map(|i| {
+                let t = i as f32 / 32.0;
+                rect.min + Vec2::new(t, 0.5 - (t * std::f32::consts::TAU).sin() * 0.4) * rect.size()
+            })
+            .collect();
+        ui.painter(rect).add_polyline(&pts, ui::Stroke::new(RGBA::CYAN, 1.5), false);
+    });
+
+    let (area, _) = ui.allocate_rect(Vec2::new(240.0, 120.0), ui::Sense::Hover);
+    ui.draw(area.draw_rect().fill(ui.style().btn_default()).corners(4.0));
+    let gizmo_id = ui.gen_id("##rect_gizmo");
+    let mut rect = *ui.data_get_or_insert_with(gizmo_id, || {
+        Rect::from_min_size(Vec2::new(20.0, 20.0), Vec2::new(80.0, 50.0))
+    });
+    // stored relative to the area, the gizmo works in screen space
+    let mut screen_rect = rect.translate(area.min);
+    demo!(ui: ui.rect_gizmo_ex(gizmo_id, &mut screen_rect, ui::RectGizmo::new().with_snap(10.0, area.min)));
+    rect = screen_rect.translate(-area.min);
+    ui.data_insert(gizmo_id, rect);
+
+    let (area, _) = ui.allocate_rect(Vec2::new(240.0, 200.0), ui::Sense::Hover);
+    ui.draw(area.draw_rect().fill(ui.style().btn_default()).corners(4.0));
+    let gizmo_id = ui.gen_id("##transform_gizmo");
+    let mut t = *ui.data_get_or_insert_with(gizmo_id, || ui::Transform2D::new(Vec2::new(120.0, 100.0)));
+    t.pos += area.min;
+    let quad = [(-20.0, -20.0), (20.0, -20.0), (20.0, 20.0), (-20.0, 20.0)]
+        .map(|(x, y)| t.transform_point(Vec2::new(x, y)));
+    ui.painter(area).add_convex_poly_filled(&quad, RGBA::SAFFRON);
+    demo!(ui: ui.transform_gizmo(gizmo_id, &mut t));
+    t.pos -= area.min;
+    ui.data_insert(gizmo_id, t);
+
+    let list_id = ui.gen_id("##list_selection");
+    let mut selection = ui.data_remove::<ui::Selection>(list_id).unwrap_or_default();
+    let labels: Vec<String> = (0..5).map(|i| format!("list item {i}")).collect();
+    let rows: Vec<ui::Id> = labels.iter().map(|l| ui.gen_id(l)).collect();
+    for (i, label) in labels.iter().enumerate() {
+        demo!(ui: if ui.selectable(label, selection.is_selected(rows[i])).clicked() {
+            selection.click(&rows, i, ui.modifiers);
+        });
+    }
+    ui.data_insert(list_id, selection);
+
+    let (area, _) = ui.allocate_rect(Vec2::new(240.0, 120.0), ui::Sense::Hover);
+    ui.draw(area.draw_rect().fill(ui.style().btn_default()).corners(4.0));
+    let canvas_id = ui.gen_id("##box_selection");
+    let mut selection = ui.data_remove::<ui::Selection>(canvas_id).unwrap_or_default();
+    let boxes: Vec<(ui::Id, Rect)> = (0..6)
+        .map(|i| {
+            let min = Vec2::new(20.0 + (i % 3) as f32 * 75.0, 20.0 + (i / 3) as f32 * 50.0);
+            let rect = Rect::from_min_size(area.min + min, Vec2::new(50.0, 30.0));
+            (ui::Id::from_hash(&(canvas_id, i)), rect)
+        })
+        .collect();
+    demo!(ui: ui.box_select(canvas_id, area, &boxes, &mut selection));
+    for (id, rect) in &boxes {
+        let col = if selection.is_selected(*id) {
+            RGBA::SAFFRON
+        } else {
+            ui.style().btn_press()
+        };
+        ui.draw(rect.draw_rect().fill(col).corners(4.0));
+    }
+    ui.data_insert(canvas_id, selection);
+}
+
+fn demo_style(ui: &mut Context) {
+    demo!(ui: ui.with_style([StyleVar::TextCol(RGBA::SAFFRON)], |ui| ui.text("pushed text color")));
+    demo!(ui: ui.with_style([StyleVar::BtnRoundness(0.5)], |ui| ui.button("round button")));
+    demo!(ui: ui.with_style([StyleVar::BtnRoundness(0.0)], |ui| ui.button("square button")));
+    demo!(ui: ui.with_style(
+        [StyleVar::BtnCornerRadii(Some(CornerRadii::new(8.0, 0.0, 0.0, 8.0)))],
+        |ui| ui.button("per corner radii"),
+    ));
+    demo!(ui: {
+        let mut ui = ui.scoped_style(StyleVar::SpacingV(0.0));
+        ui.button("no");
+        ui.button("vertical");
+        ui.button("spacing");
+    });
+
+    let frame = Frame::group(ui.style())
+        .with_border(Outline::outer(RGBA::CYAN, 1.0))
+        .with_corners(8.0)
+        .with_shadow(Shadow::new(Vec2::new(0.0, 2.0), 6.0, RGBA::BLACK.with_alpha(0.4)));
+    demo!(ui: ui.frame(frame, |ui| ui.text("frame with border, corners and shadow")));
+
+    ui.separator();
+    ui.text("the global style is edited in the debug panel");
+    let swatches = [
+        ("text", ui.style().text_col()),
+        ("button", ui.style().btn_default()),
+        ("hover", ui.style().btn_hover()),
+        ("press", ui.style().btn_press()),
+        ("panel", ui.style().panel_bg()),
+        ("titlebar", ui.style().titlebar_color()),
+    ];
+    for (name, col) in swatches {
+        let size = Vec2::splat(ui.style().line_height());
+        let (rect, _) = ui.allocate_rect(size, ui::Sense::Hover);
+        ui.painter(rect).draw(
+            rect.draw_rect()
+                .fill(col)
+                .outline(Outline::hairline(ui.style().panel_outline().col))
+                .corners(4.0),
+        );
+        ui.item_tooltip(&format!("{name}: {col:?}"));
+        ui.same_line();
+    }
+    ui.new_line();
+}
@@ -0,0 +1,126 @@
+//! custom shaders for single rects and images, see [`UiEffect`]
+
+use std::{fmt, sync::Arc};
+
+use crate::{
+    gpu::{self, ShaderHandle, Vertex as _, WGPU},
+    ui::{self, CompactVertex, UiPipelineVariant, UiShader, Vertex},
+};
+
+/// id of an effect registered with [`ui::Context::register_effect`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EffectId(pub u32);
+
+impl EffectId {
+    /// draws with the default ui shader
+    pub const NONE: Self = Self(0);
+
+    /// index into the registered effects
+    pub fn index(self) -> Option<usize> {
+        (self.0 as usize).checked_sub(1)
+    }
+}
+
+/// builds the pipeline of a [`UiEffect::Pipeline`]
+pub type EffectPipelineFn = dyn Fn(&WGPU) -> Arc<wgpu::RenderPipeline>;
+
+/// a custom shader for the fragments of a draw, e.g. an animated gradient on a button or the
+/// preview tile of a material, attached with [`ui::DrawRect::effect`]
+///
+/// draws with different effects are never batched together. the [`ui::SoftRasterizer`] and
+/// the svg export ignore effects
+#[derive(Clone)]
+pub enum UiEffect {
+    /// wgsl that defines `fn effect(col: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32>`
+    ///
+    /// it gets the textured color, the uv and the position in pixels of a fragment and returns
+    /// its color. `global.time` holds the seconds since the start of the context and
+    /// `global.screen_size` the size of the target. the pipeline variants, e.g. for clip masks,
+    /// are built from it when they are first used
+    Fragment(String),
+    /// the pipeline of a custom shader, see [`UiEffect::shader`]
+    Pipeline(Arc<EffectPipelineFn>),
+}
+
+impl fmt::Debug for UiEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fragment(wgsl) => f.debug_tuple("Fragment").field(wgsl).finish(),
+            Self::Pipeline(_) => f.write_str("Pipeline"),
+        }
+    }
+}
+
+impl UiEffect {
+    pub fn fragment(wgsl: impl Into<String>) -> Self {
+        Self::Fragment(wgsl.into())
+    }
+
+    /// draws with the pipeline of `shader`, built for [`Vertex`]s
+    ///
+    /// the pipeline is used as is, so it has to match the ui pass: the surface format, a
+    /// [`gpu::STENCIL_FORMAT`] attachment and the bind group of the [`UiShader`]. clip masks
+    /// and wireframes are not applied
+    pub fn shader<S: ShaderHandle + 'static>(shader: S) -> Self {
+        Self::Pipeline(Arc::new(move |wgpu: &WGPU| {
+            let desc = Vertex::desc();
+            shader.get_pipeline(gpu::ShaderBuildConfig::new([(&desc, "Vertex")]), wgpu)
+        }))
+    }
+
+    /// checks the wgsl of a [`UiEffect::Fragment`] with the ui shader it is inserted into
+    pub fn validate(&self) -> Result<(), Vec<gpu::ShaderMismatch>> {
+        let Self::Fragment(wgsl) = self else {
+            return Ok(());
+        };
+        let desc = Vertex::desc();
+        let config = gpu::ShaderBuildConfig::new([(&desc, "Vertex")]);
+        let src = UiShader.shader_source(&config, Some(wgsl));
+        let entries = ui::ui_bind_group_layout_entries();
+        let (attributes, members) = (&desc.attributes, &desc.members);
+        gpu::validate_shader(&src, ["vs_main", "fs_main"], attributes, members, 1, &[&entries])
+    }
+}
+
+impl UiShader {
+    /// the cached pipeline of a [`UiEffect::Fragment`] for the surface format, see
+    /// [`Self::pipeline`]
+    pub fn effect_pipeline(
+        &self,
+        wgsl: &str,
+        variant: UiPipelineVariant,
+        wgpu: &WGPU,
+    ) -> Arc<wgpu::RenderPipeline> {
+        let desc = match variant.compact {
+            true => CompactVertex::desc(),
+            false => Vertex::desc(),
+        };
+        let config = gpu::ShaderBuildConfig::new([(&desc, "Vertex")]);
+        let key = gpu::PipelineKey::of(&(Self::RENDER_PIPELINE_ID, variant, wgsl));
+        wgpu.get_or_init_pipeline(key, || {
+            let (device, format) = (&wgpu.device, wgpu.surface_format);
+            self.build_pipeline_effect(config, device, format, 1, variant, Some(wgsl))
+        })
+    }
+}
+
+impl ui::Context {
+    /// registers an effect for rects and images, see [`UiEffect`]. fragment effects with the
+    /// same wgsl share an id
+    ///
+    /// returns the errors of the wgsl of a fragment effect
+    pub fn register_effect(
+        &mut self,
+        effect: UiEffect,
+    ) -> Result<EffectId, Vec<gpu::ShaderMismatch>> {
+        if let UiEffect::Fragment(wgsl) = &effect {
+            let same = |e: &UiEffect| matches!(e, UiEffect::Fragment(w) if w == wgsl);
+            if let Some(i) = self.draw.effects.iter().position(same) {
+                return Ok(EffectId(i as u32 + 1));
+            }
+        }
+        effect.validate()?;
+        self.draw.effects.push(effect);
+        Ok(EffectId(self.draw.effects.len() as u32))
+    }
+}
@@ -0,0 +1,247 @@
+//! input validation for settings dialogs and the like, see [`ui::Context::form`]
+
+use std::{
+    fmt::Display,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use glam::Vec2;
+
+use crate::{
+    accessibility::AccessRole,
+    ui::{self, Id, Margins, Outline, Response, TextInputState},
+};
+
+/// a rule the text of a [`Form::field`] has to follow
+#[derive(Debug, Clone)]
+pub enum Validator {
+    /// the text is not empty or whitespace
+    Required,
+    /// the text is a number in `min..=max`
+    Range(f64, f64),
+    /// the whole text matches the regex
+    Pattern(regex::Regex),
+    /// returns the error message of an invalid text
+    Custom(fn(&str) -> Result<(), String>),
+}
+
+impl Validator {
+    /// panics if `re` is not a valid regex, see [`regex::Regex::new`]
+    pub fn pattern(re: &str) -> Self {
+        Self::Pattern(regex::Regex::new(re).expect("invalid validator pattern"))
+    }
+
+    /// the error message if `text` is invalid
+    pub fn validate(&self, text: &str) -> Result<(), String> {
+        match self {
+            Self::Required if text.trim().is_empty() => Err("required".into()),
+            Self::Range(min, max) => match text.trim().parse::<f64>() {
+                Ok(v) if (*min..=*max).contains(&v) => Ok(()),
+                Ok(_) => Err(format!("must be between {min} and {max}")),
+                Err(_) => Err("must be a number".into()),
+            },
+            Self::Pattern(re) => match re.find(text) {
+                Some(m) if m.start() == 0 && m.end() == text.len() => Ok(()),
+                _ => Err(format!("must match `{}`", re.as_str())),
+            },
+            Self::Custom(f) => f(text),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// state of a [`ui::Context::form`] between frames
+#[derive(Debug, Default, Clone, Copy)]
+struct FormState {
+    /// a disabled submit button was clicked, errors of untouched fields are shown too
+    show_errors: bool,
+}
+
+/// state of a [`Form::field`]
+#[derive(Debug, Default, Clone, Copy)]
+struct FormField {
+    /// the field was edited, its errors are shown
+    touched: bool,
+}
+
+/// the fields of a [`ui::Context::form`], derefs to the context for other widgets
+pub struct Form<'a> {
+    ui: &'a mut ui::Context,
+    id: Id,
+    valid: bool,
+    show_errors: bool,
+    /// the invalid fields so far, shaken when the disabled submit button is clicked
+    invalid: Vec<Id>,
+}
+
+impl Deref for Form<'_> {
+    type Target = ui::Context;
+
+    fn deref(&self) -> &ui::Context {
+        self.ui
+    }
+}
+
+impl DerefMut for Form<'_> {
+    fn deref_mut(&mut self) -> &mut ui::Context {
+        self.ui
+    }
+}
+
+impl Form<'_> {
+    /// all fields so far are valid
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// a text field for `val`, the value only changes when the text parses and passes the
+    /// `validators`. errors are shown below the field once it was edited
+    pub fn field<T: FromStr + Display + PartialEq>(
+        &mut self,
+        label: &str,
+        val: &mut T,
+        validators: &[Validator],
+    ) -> Response {
+        let ui = &mut *self.ui;
+        let id = ui.gen_id(label);
+        let text = val.to_string();
+        let resp = ui.input_text(label, &text);
+        let rect = ui.last_item_rect;
+        ui.same_line();
+        ui.text(label);
+
+        let active = ui.active_id == id;
+        let Some(input) = ui.widget_data.get_mut::<TextInputState>(&id) else {
+            return resp;
+        };
+        let validate = |text: &str| {
+            validators.iter().try_for_each(|v| v.validate(text))?;
+            text.trim()
+                .parse::<T>()
+                .map_err(|_| "invalid value".to_string())
+        };
+        let mut current = input.copy_all();
+        // the value was changed from outside, invalid text is kept to show its error
+        if !active
+            && !resp.changed()
+            && current != text
+            && validate(&current).is_ok_and(|v| v != *val)
+        {
+            input.set_text(&text);
+            input.changed = false;
+            current = text;
+        }
+
+        let mut changed = false;
+        let error = match validate(&current) {
+            Ok(v) => {
+                if resp.changed() && v != *val {
+                    *val = v;
+                    changed = true;
+                }
+                None
+            }
+            Err(msg) => Some(msg),
+        };
+
+        let field = ui.widget_data.get_or_insert_with(id, FormField::default);
+        field.touched |= resp.changed();
+        let show = field.touched || self.show_errors;
+        if let Some(msg) = error.as_deref().filter(|_| show) {
+            let red = ui.style.red();
+            ui.draw(
+                rect.draw_rect()
+                    .corners(ui.style.btn_corners())
+                    .outline(Outline::hairline(red)),
+            );
+            let shape = ui.layout_text(msg, ui.style.text_size() * 0.85);
+            let pos = ui.place_item(shape.size()).min;
+            ui.draw(shape.draw_rects(pos, red));
+        }
+        if error.is_some() {
+            self.valid = false;
+            self.invalid.push(id);
+        }
+        Response::new(
+            resp.signal(),
+            changed,
+            resp.drag_started(),
+            resp.drag_stopped(),
+        )
+    }
+
+    /// a button that can only be clicked while the fields before it are valid. clicking it
+    /// while it is disabled shows the errors of all fields and shakes the invalid ones
+    pub fn submit_button(&mut self, label: &str) -> bool {
+        if self.valid {
+            return self.ui.button(label);
+        }
+
+        let ui = &mut *self.ui;
+        let id = ui.gen_id(label);
+        let height = ui.style.line_height();
+        let text_dim = ui.measure_text(label, ui.style.text_size());
+        let padding = Margins::all(((height - text_dim.y) / 2.0).max(0.0));
+        let size = Vec2::new(text_dim.x + padding.sum().x, height);
+        let rect = ui.place_item(size);
+        let sig = ui.reg_item_active_on_press(id, rect);
+        ui.reg_access_node(id, AccessRole::Button, label, rect);
+        if sig.clicked() {
+            ui.widget_data
+                .insert(self.id, FormState { show_errors: true });
+            for &field in &self.invalid {
+                ui.shake(field);
+            }
+        }
+
+        if !ui.item_culled() {
+            let col = ui.style.text_col().with_alpha(0.4);
+            let shape = ui.layout_text(label, ui.style.text_size());
+            ui.draw(
+                rect.draw_rect()
+                    .corners(ui.style.btn_corners())
+                    .fill(ui.style.btn_default().with_alpha(0.5)),
+            );
+            ui.draw(shape.draw_rects(rect.min + (size - text_dim) * 0.5, col));
+        }
+        false
+    }
+}
+
+impl ui::Context {
+    /// a scope whose fields are validated, e.g. for a settings dialog
+    ///
+    /// ```ignore
+    /// ui.form("server", |form| {
+    ///     form.field("host", &mut host, &[Validator::Required]);
+    ///     form.field("port", &mut port, &[Validator::Range(1.0, 65535.0)]);
+    ///     if form.submit_button("connect") {
+    ///         connect(&host, port);
+    ///     }
+    /// });
+    /// ```
+    pub fn form<R>(&mut self, label: &str, f: impl FnOnce(&mut Form) -> R) -> R {
+        let id = self.gen_id(label);
+        let state = self
+            .widget_data
+            .get::<FormState>(&id)
+            .copied()
+            .unwrap_or_default();
+        self.keyed(id, |ui| {
+            let mut form = Form {
+                ui,
+                id,
+                valid: true,
+                show_errors: state.show_errors,
+                invalid: Vec::new(),
+            };
+            let res = f(&mut form);
+            // errors stay visible until the form is valid
+            if form.valid {
+                form.ui.widget_data.remove::<FormState>(&id);
+            }
+            res
+        })
+    }
+}
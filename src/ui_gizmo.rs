@@ -0,0 +1,619 @@
+//! widgets that manipulate geometry in screen space with the mouse, e.g. crop rects or the
+//! transform of canvas items
+
+use glam::Vec2;
+
+use crate::{
+    core::{Axis, Dir, RGBA},
+    mouse::{CursorIcon, MouseBtn},
+    rect::Rect,
+    ui::{self, Id, Outline, Signal, Stroke},
+};
+
+/// interaction with a gizmo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoResponse {
+    /// combined signal of all handles
+    pub signal: Signal,
+    /// the geometry was changed this frame
+    pub changed: bool,
+    /// a handle is held
+    pub dragging: bool,
+    /// a handle was released this frame, e.g. to record an undo step
+    pub finished: bool,
+}
+
+/// options of [`ui::Context::rect_gizmo_ex`]
+///
+/// holding shift keeps the aspect ratio, holding control snaps to the grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RectGizmo {
+    pub min_size: Vec2,
+    /// grid step used for snapping, 0 disables snapping
+    pub snap: f32,
+    /// a point on the snapping grid, e.g. the corner of the image being cropped
+    pub snap_origin: Vec2,
+    /// always keep the aspect ratio, not only while shift is held
+    pub keep_aspect: bool,
+}
+
+impl Default for RectGizmo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RectGizmo {
+    pub fn new() -> Self {
+        Self {
+            min_size: Vec2::splat(1.0),
+            snap: 10.0,
+            snap_origin: Vec2::ZERO,
+            keep_aspect: false,
+        }
+    }
+
+    pub fn with_min_size(mut self, size: Vec2) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    pub fn with_snap(mut self, step: f32, origin: Vec2) -> Self {
+        self.snap = step;
+        self.snap_origin = origin;
+        self
+    }
+
+    pub fn with_keep_aspect(mut self, keep: bool) -> Self {
+        self.keep_aspect = keep;
+        self
+    }
+
+    fn snap_to_grid(&self, v: Vec2) -> Vec2 {
+        if self.snap > 0.0 {
+            ((v - self.snap_origin) / self.snap).round() * self.snap + self.snap_origin
+        } else {
+            v
+        }
+    }
+
+    /// `start` after dragging `handle` by `delta`, `None` moves the whole rect
+    pub fn drag(
+        &self,
+        start: Rect,
+        handle: Option<Dir>,
+        delta: Vec2,
+        keep_aspect: bool,
+        snap: bool,
+    ) -> Rect {
+        let snap_pos = |v: Vec2| if snap { self.snap_to_grid(v) } else { v };
+
+        let Some(dir) = handle else {
+            return Rect::from_min_size(snap_pos(start.min + delta), start.size());
+        };
+
+        let (mut min, mut max) = (start.min, start.max);
+        let moved_min = snap_pos(min + delta);
+        let moved_max = snap_pos(max + delta);
+        if dir.has_w() {
+            min.x = moved_min.x.min(max.x - self.min_size.x);
+        }
+        if dir.has_e() {
+            max.x = moved_max.x.max(min.x + self.min_size.x);
+        }
+        if dir.has_n() {
+            min.y = moved_min.y.min(max.y - self.min_size.y);
+        }
+        if dir.has_s() {
+            max.y = moved_max.y.max(min.y + self.min_size.y);
+        }
+
+        if keep_aspect && start.width() > 0.0 && start.height() > 0.0 {
+            let aspect = start.width() / start.height();
+            let (w, h) = (max.x - min.x, max.y - min.y);
+            // edges drive the size along their axis, corners the larger change
+            let (w, h) = match dir.axis() {
+                Some(Axis::X) => (w, w / aspect),
+                Some(Axis::Y) => (h * aspect, h),
+                None if w / aspect > h => (w, w / aspect),
+                None => (h * aspect, h),
+            };
+
+            // the opposite side stays in place, edges grow from their center
+            if dir.has_w() {
+                min.x = max.x - w;
+            } else if dir.has_e() {
+                max.x = min.x + w;
+            } else {
+                let c = (min.x + max.x) / 2.0;
+                (min.x, max.x) = (c - w / 2.0, c + w / 2.0);
+            }
+            if dir.has_n() {
+                min.y = max.y - h;
+            } else if dir.has_s() {
+                max.y = min.y + h;
+            } else {
+                let c = (min.y + max.y) / 2.0;
+                (min.y, max.y) = (c - h / 2.0, c + h / 2.0);
+            }
+        }
+
+        Rect::from_min_max(min, max)
+    }
+}
+
+/// drag state of a gizmo, stored in the widget data while a handle is held
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RectGizmoDrag {
+    handle: Option<Dir>,
+    start_rect: Rect,
+    start_mouse: Vec2,
+}
+
+impl ui::Context {
+    /// draws `rect` with handles on its corners and edges, dragging the handles resizes the
+    /// rect, dragging the inside moves it
+    ///
+    /// `rect` is in screen space. holding shift keeps the aspect ratio, holding control
+    /// snaps to a grid, see [`RectGizmo`]
+    pub fn rect_gizmo(&mut self, id: Id, rect: &mut Rect) -> GizmoResponse {
+        self.rect_gizmo_ex(id, rect, RectGizmo::new())
+    }
+
+    pub fn rect_gizmo_ex(&mut self, id: Id, rect: &mut Rect, opts: RectGizmo) -> GizmoResponse {
+        const HANDLES: [Dir; 8] = [
+            Dir::N,
+            Dir::E,
+            Dir::S,
+            Dir::W,
+            Dir::NE,
+            Dir::SE,
+            Dir::SW,
+            Dir::NW,
+        ];
+
+        let handle_size = self.style.text_size() * 0.5;
+        let handle_rect = |r: Rect, dir: Dir| {
+            let x = if dir.has_w() {
+                r.min.x
+            } else if dir.has_e() {
+                r.max.x
+            } else {
+                r.center().x
+            };
+            let y = if dir.has_n() {
+                r.min.y
+            } else if dir.has_s() {
+                r.max.y
+            } else {
+                r.center().y
+            };
+            Rect::from_center_size(Vec2::new(x, y), Vec2::splat(handle_size))
+        };
+        let handle_id = |dir: Option<Dir>| Id::from_hash(&(id, dir));
+
+        // registered last, the handles take precedence over the body
+        let mut signal = self.reg_item_active_on_press(handle_id(None), *rect);
+        let mut pressed = signal.pressed().then_some(None);
+        let mut hovered = signal.hovering().then_some(None);
+        for dir in HANDLES {
+            let sig = self.reg_item_active_on_press(handle_id(Some(dir)), handle_rect(*rect, dir));
+            if sig.pressed() {
+                pressed = Some(Some(dir));
+            }
+            if sig.hovering() {
+                hovered = Some(Some(dir));
+            }
+            signal |= sig;
+        }
+
+        if let Some(handle) = pressed
+            && self.active_id == handle_id(handle)
+            && !self.widget_data.contains_key::<RectGizmoDrag>(&id)
+        {
+            self.widget_data.insert(
+                id,
+                RectGizmoDrag {
+                    handle,
+                    start_rect: *rect,
+                    start_mouse: self.mouse.pos,
+                },
+            );
+        }
+
+        let mut resp = GizmoResponse {
+            signal,
+            changed: false,
+            dragging: false,
+            finished: false,
+        };
+
+        let drag = self.widget_data.get::<RectGizmoDrag>(&id).copied();
+        if let Some(drag) = drag {
+            if self.mouse.pressed(MouseBtn::Left) && self.active_id == handle_id(drag.handle) {
+                self.expect_drag = true;
+                let keep_aspect = opts.keep_aspect || self.modifiers.shift_key();
+                let snap = self.modifiers.control_key();
+                let delta = self.mouse.pos - drag.start_mouse;
+                let new_rect = opts.drag(drag.start_rect, drag.handle, delta, keep_aspect, snap);
+                resp.changed = new_rect != *rect;
+                resp.dragging = true;
+                *rect = new_rect;
+                hovered = Some(drag.handle);
+            } else {
+                self.widget_data.remove::<RectGizmoDrag>(&id);
+                resp.finished = true;
+            }
+        }
+
+        if let Some(handle) = hovered {
+            self.set_cursor_icon(handle.map_or(CursorIcon::Pointer, Dir::as_cursor));
+        }
+
+        let col = self.style.btn_hover();
+        let active_col = self.style.btn_press();
+        self.draw(rect.draw_rect().outline(Outline::center(col, 1.5)));
+        for dir in HANDLES {
+            let fill = if hovered == Some(Some(dir)) {
+                active_col
+            } else {
+                RGBA::WHITE
+            };
+            self.draw(
+                handle_rect(*rect, dir)
+                    .draw_rect()
+                    .fill(fill)
+                    .outline(Outline::center(col, 1.0)),
+            );
+        }
+
+        resp
+    }
+}
+
+/// position, rotation and scale of a canvas item, see [`ui::Context::transform_gizmo`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    pub pos: Vec2,
+    /// rotation in radians, clockwise since y points down
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::new(Vec2::ZERO)
+    }
+}
+
+impl Transform2D {
+    pub fn new(pos: Vec2) -> Self {
+        Self {
+            pos,
+            rotation: 0.0,
+            scale: Vec2::ONE,
+        }
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// local x and y axis, not scaled
+    pub fn axes(&self) -> (Vec2, Vec2) {
+        let x = Vec2::from_angle(self.rotation);
+        (x, x.perp())
+    }
+
+    /// maps a point from item space to the space of `pos`
+    pub fn transform_point(&self, p: Vec2) -> Vec2 {
+        let (x, y) = self.axes();
+        self.pos + x * p.x * self.scale.x + y * p.y * self.scale.y
+    }
+}
+
+/// part of a [`TransformGizmo`] that is dragged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GizmoPart {
+    Move,
+    MoveX,
+    MoveY,
+    Rotate,
+    Scale,
+    ScaleX,
+    ScaleY,
+}
+
+/// options of [`ui::Context::transform_gizmo_ex`]
+///
+/// holding control snaps every change to its step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformGizmo {
+    /// length of the axis arrows, the rotate ring is slightly larger
+    pub size: f32,
+    /// grid step of the position
+    pub snap_pos: f32,
+    /// step of the rotation in radians
+    pub snap_angle: f32,
+    pub snap_scale: f32,
+}
+
+impl Default for TransformGizmo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransformGizmo {
+    pub fn new() -> Self {
+        Self {
+            size: 60.0,
+            snap_pos: 10.0,
+            snap_angle: 15f32.to_radians(),
+            snap_scale: 0.1,
+        }
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_snap(mut self, pos: f32, angle: f32, scale: f32) -> Self {
+        self.snap_pos = pos;
+        self.snap_angle = angle;
+        self.snap_scale = scale;
+        self
+    }
+
+    /// `start` after dragging `part` from `start_mouse` to `mouse`
+    pub fn drag(
+        &self,
+        start: Transform2D,
+        part: GizmoPart,
+        start_mouse: Vec2,
+        mouse: Vec2,
+        snap: bool,
+    ) -> Transform2D {
+        let snap_to = |v: f32, step: f32| {
+            if snap && step > 0.0 {
+                (v / step).round() * step
+            } else {
+                v
+            }
+        };
+
+        let (x, y) = start.axes();
+        let delta = mouse - start_mouse;
+        let from = start_mouse - start.pos;
+        let to = mouse - start.pos;
+        // ratio of the distances to the center along `axis`, 1 if the drag started on the center
+        let ratio = |axis: Vec2| {
+            let d = from.dot(axis);
+            if d.abs() > f32::EPSILON {
+                to.dot(axis) / d
+            } else {
+                1.0
+            }
+        };
+
+        let mut t = start;
+        match part {
+            GizmoPart::Move => {
+                let pos = start.pos + delta;
+                t.pos = Vec2::new(snap_to(pos.x, self.snap_pos), snap_to(pos.y, self.snap_pos));
+            }
+            GizmoPart::MoveX => t.pos = start.pos + x * snap_to(delta.dot(x), self.snap_pos),
+            GizmoPart::MoveY => t.pos = start.pos + y * snap_to(delta.dot(y), self.snap_pos),
+            GizmoPart::Rotate => {
+                let angle = from.angle_to(to);
+                if angle.is_finite() {
+                    t.rotation = start.rotation + snap_to(angle, self.snap_angle);
+                }
+            }
+            GizmoPart::Scale => {
+                let f = if from.length() > f32::EPSILON {
+                    to.length() / from.length()
+                } else {
+                    1.0
+                };
+                t.scale = start.scale * snap_to(f, self.snap_scale);
+            }
+            GizmoPart::ScaleX => t.scale.x = start.scale.x * snap_to(ratio(x), self.snap_scale),
+            GizmoPart::ScaleY => t.scale.y = start.scale.y * snap_to(ratio(y), self.snap_scale),
+        }
+        t
+    }
+}
+
+/// drag state of a transform gizmo, stored in the widget data while a part is held
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TransformGizmoDrag {
+    part: GizmoPart,
+    start: Transform2D,
+    start_mouse: Vec2,
+}
+
+/// square of size `2 * tol` around the point on the segment `a`-`b` closest to `p`
+fn segment_hit_rect(a: Vec2, b: Vec2, p: Vec2, tol: f32) -> Rect {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    Rect::from_center_size(a + ab * t, Vec2::splat(2.0 * tol))
+}
+
+impl ui::Context {
+    /// draws translate arrows, a rotate ring and scale handles around `transform.pos` and
+    /// applies the drags to `transform`
+    ///
+    /// `transform.pos` is in screen space, the arrows follow the rotation of the item.
+    /// holding control snaps to the steps of [`TransformGizmo`]
+    pub fn transform_gizmo(&mut self, id: Id, transform: &mut Transform2D) -> GizmoResponse {
+        self.transform_gizmo_ex(id, transform, TransformGizmo::new())
+    }
+
+    pub fn transform_gizmo_ex(
+        &mut self,
+        id: Id,
+        transform: &mut Transform2D,
+        opts: TransformGizmo,
+    ) -> GizmoResponse {
+        const RING_SEGMENTS: usize = 64;
+
+        let center = transform.pos;
+        let (x, y) = transform.axes();
+        let len = opts.size;
+        let ring_radius = len + 15.0;
+        let handle = self.style.text_size() * 0.5;
+        let tol = handle * 0.75;
+        let mouse = self.mouse.pos;
+        let part_id = |part: GizmoPart| Id::from_hash(&(id, part));
+
+        let ring_dir = (mouse - center).try_normalize().unwrap_or(x);
+        let scale_pos = |axis: Vec2| center + axis * len * 0.6;
+        let uniform_pos = center + (x + y) * len * 0.35;
+
+        // later parts take precedence when they overlap
+        let parts = [
+            (
+                GizmoPart::Rotate,
+                Rect::from_center_size(center + ring_dir * ring_radius, Vec2::splat(2.0 * tol)),
+            ),
+            (GizmoPart::MoveX, segment_hit_rect(center, center + x * len, mouse, tol)),
+            (GizmoPart::MoveY, segment_hit_rect(center, center + y * len, mouse, tol)),
+            (
+                GizmoPart::ScaleX,
+                Rect::from_center_size(scale_pos(x), Vec2::splat(handle + 2.0)),
+            ),
+            (
+                GizmoPart::ScaleY,
+                Rect::from_center_size(scale_pos(y), Vec2::splat(handle + 2.0)),
+            ),
+            (
+                GizmoPart::Scale,
+                Rect::from_center_size(uniform_pos, Vec2::splat(handle + 2.0)),
+            ),
+            (
+                GizmoPart::Move,
+                Rect::from_center_size(center, Vec2::splat(handle * 1.5)),
+            ),
+        ];
+
+        let mut signal = Signal::NONE;
+        let mut pressed = None;
+        let mut hovered = None;
+        for (part, rect) in parts {
+            let sig = self.reg_item_active_on_press(part_id(part), rect);
+            if sig.pressed() {
+                pressed = Some(part);
+            }
+            if sig.hovering() {
+                hovered = Some(part);
+            }
+            signal |= sig;
+        }
+
+        if let Some(part) = pressed
+            && self.active_id == part_id(part)
+            && !self.widget_data.contains_key::<TransformGizmoDrag>(&id)
+        {
+            self.widget_data.insert(
+                id,
+                TransformGizmoDrag {
+                    part,
+                    start: *transform,
+                    start_mouse: mouse,
+                },
+            );
+        }
+
+        let mut resp = GizmoResponse {
+            signal,
+            changed: false,
+            dragging: false,
+            finished: false,
+        };
+
+        let drag = self.widget_data.get::<TransformGizmoDrag>(&id).copied();
+        if let Some(drag) = drag {
+            if self.mouse.pressed(MouseBtn::Left) && self.active_id == part_id(drag.part) {
+                self.expect_drag = true;
+                let snap = self.modifiers.control_key();
+                let new = opts.drag(drag.start, drag.part, drag.start_mouse, mouse, snap);
+                resp.changed = new != *transform;
+                resp.dragging = true;
+                *transform = new;
+                hovered = Some(drag.part);
+            } else {
+                self.widget_data.remove::<TransformGizmoDrag>(&id);
+                resp.finished = true;
+            }
+        }
+
+        if hovered.is_some() {
+            self.set_cursor_icon(CursorIcon::Pointer);
+        }
+
+        // drawn with the updated transform
+        let center = transform.pos;
+        let (x, y) = transform.axes();
+        let highlight = self.style.btn_press();
+        let col = |part: GizmoPart, col: RGBA| if hovered == Some(part) { highlight } else { col };
+        let x_col = macros::rgba!("#e5484d");
+        let y_col = macros::rgba!("#46a758");
+        let ring_col = self.style.btn_hover();
+
+        let bounds = Rect::from_center_size(center, Vec2::splat(2.0 * (ring_radius + tol)));
+        let painter = self.painter(bounds);
+
+        let ring: Vec<Vec2> = (0..RING_SEGMENTS)
+            .map(|i| {
+                let a = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                center + Vec2::from_angle(a) * ring_radius
+            })
+            .collect();
+        painter.add_polyline(&ring, Stroke::new(col(GizmoPart::Rotate, ring_col), 2.0), true);
+
+        for (axis, part, scale_part, c) in [
+            (x, GizmoPart::MoveX, GizmoPart::ScaleX, x_col),
+            (y, GizmoPart::MoveY, GizmoPart::ScaleY, y_col),
+        ] {
+            let c = col(part, c);
+            let tip = center + axis * len;
+            let base = tip - axis * handle * 1.5;
+            painter.add_line(center, base, Stroke::new(c, 2.0));
+            painter.add_convex_poly_filled(
+                &[tip, base + axis.perp() * handle * 0.75, base - axis.perp() * handle * 0.75],
+                c,
+            );
+
+            let s = scale_pos(axis);
+            painter.add_draw_rect(
+                Rect::from_center_size(s, Vec2::splat(handle))
+                    .draw_rect()
+                    .fill(col(scale_part, c)),
+            );
+        }
+
+        let uniform_pos = center + (x + y) * len * 0.35;
+        painter.add_draw_rect(
+            Rect::from_center_size(uniform_pos, Vec2::splat(handle))
+                .draw_rect()
+                .fill(col(GizmoPart::Scale, ring_col)),
+        );
+        painter.add_draw_rect(
+            Rect::from_center_size(center, Vec2::splat(handle * 1.5))
+                .draw_rect()
+                .fill(col(GizmoPart::Move, RGBA::WHITE))
+                .outline(Outline::center(ring_col, 1.0))
+                .corners(handle),
+        );
+
+        resp
+    }
+}
@@ -0,0 +1,477 @@
+//! color gradients with stops and an editor for them, see [`Gradient`]
+
+use glam::Vec2;
+
+use crate::{
+    accessibility::AccessRole,
+    core::{Axis, HSVA, RGBA},
+    mouse::{CursorIcon, KeyCode, MouseBtn},
+    rect::Rect,
+    ui::{self, Colormap, Id, Outline, Response, Vertex},
+};
+
+/// number of quads between two stops for interpolations that are not linear in rgb
+const GRADIENT_SUBDIV: usize = 8;
+
+/// how the colors between two stops of a [`Gradient`] are blended
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GradientInterp {
+    /// linear in rgb
+    #[default]
+    Linear,
+    /// linear in oklab, keeps the perceived brightness even
+    Oklab,
+    /// eases in and out of every stop
+    Smooth,
+    /// the color of the previous stop, for discrete colormaps
+    Step,
+}
+
+impl GradientInterp {
+    pub const ALL: [GradientInterp; 4] = [Self::Linear, Self::Oklab, Self::Smooth, Self::Step];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Oklab => "oklab",
+            Self::Smooth => "smooth",
+            Self::Step => "step",
+        }
+    }
+
+    /// the next mode in [`Self::ALL`]
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// a color at `pos` in `[0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientStop {
+    pub pos: f32,
+    pub col: RGBA,
+}
+
+/// colors along `[0, 1]` blended between stops, e.g. for
+/// [`ui::DrawList::add_rect_gradient_stops`] or as a colormap lut, see [`Self::lut`]
+///
+/// before the first and after the last stop the gradient keeps their color. edited with
+/// [`ui::Context::gradient_editor`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gradient {
+    /// sorted by position, the methods keep the order
+    pub stops: Vec<GradientStop>,
+    pub interp: GradientInterp,
+}
+
+impl Default for Gradient {
+    /// black to white
+    fn default() -> Self {
+        Self::from_colors(&[RGBA::BLACK, RGBA::WHITE])
+    }
+}
+
+impl Gradient {
+    /// a gradient without stops, it samples as transparent
+    pub fn new() -> Self {
+        Self {
+            stops: Vec::new(),
+            interp: GradientInterp::Linear,
+        }
+    }
+
+    /// evenly spaced stops
+    pub fn from_colors(cols: &[RGBA]) -> Self {
+        let n = cols.len().saturating_sub(1).max(1) as f32;
+        let stops = cols
+            .iter()
+            .enumerate()
+            .map(|(i, &col)| GradientStop {
+                pos: i as f32 / n,
+                col,
+            })
+            .collect();
+        Self {
+            stops,
+            interp: GradientInterp::Linear,
+        }
+    }
+
+    /// the lut of a [`Colormap`] as stops
+    pub fn from_colormap(cmap: Colormap) -> Self {
+        Self::from_colors(cmap.lut())
+    }
+
+    pub fn with_interp(mut self, interp: GradientInterp) -> Self {
+        self.interp = interp;
+        self
+    }
+
+    pub fn with_stop(mut self, pos: f32, col: RGBA) -> Self {
+        self.add_stop(pos, col);
+        self
+    }
+
+    /// inserts a stop after the stops at the same position, returns its index
+    pub fn add_stop(&mut self, pos: f32, col: RGBA) -> usize {
+        let pos = if pos.is_finite() {
+            pos.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let i = self.stops.partition_point(|s| s.pos <= pos);
+        self.stops.insert(i, GradientStop { pos, col });
+        i
+    }
+
+    pub fn remove_stop(&mut self, index: usize) -> Option<GradientStop> {
+        (index < self.stops.len()).then(|| self.stops.remove(index))
+    }
+
+    /// moves a stop to `pos`, returns its new index
+    pub fn move_stop(&mut self, index: usize, pos: f32) -> usize {
+        match self.remove_stop(index) {
+            Some(stop) => self.add_stop(pos, stop.col),
+            None => index,
+        }
+    }
+
+    /// restores the order after `stops` was changed directly
+    pub fn sort(&mut self) {
+        self.stops.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    }
+
+    /// the color at `t`, clamped to `[0, 1]`
+    pub fn sample(&self, t: f32) -> RGBA {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return RGBA::ZERO;
+        };
+        let t = if t.is_finite() {
+            t.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let i = self.stops.partition_point(|s| s.pos <= t);
+        if i == 0 {
+            return first.col;
+        }
+        if i == self.stops.len() {
+            return last.col;
+        }
+
+        let (a, b) = (self.stops[i - 1], self.stops[i]);
+        let f = (t - a.pos) / (b.pos - a.pos);
+        match self.interp {
+            GradientInterp::Linear => a.col.lerp(b.col, f),
+            GradientInterp::Oklab => a.col.lerp_oklab(b.col, f),
+            GradientInterp::Smooth => a.col.lerp(b.col, f * f * (3.0 - 2.0 * f)),
+            GradientInterp::Step => a.col,
+        }
+    }
+
+    /// `n` evenly spaced samples from 0 to 1
+    pub fn lut(&self, n: usize) -> Vec<RGBA> {
+        let last = n.saturating_sub(1).max(1) as f32;
+        (0..n).map(|i| self.sample(i as f32 / last)).collect()
+    }
+
+    /// quads covering `rect` with the gradient running along `axis`
+    pub(crate) fn tessellate(&self, rect: Rect, axis: Axis) -> (Vec<Vertex>, Vec<u32>) {
+        // positions along the axis with their color, a step has two columns at the same position
+        let mut cols = vec![(0.0, self.sample(0.0))];
+        for (i, stop) in self.stops.iter().enumerate() {
+            let prev = cols.last().unwrap().0;
+            match self.interp {
+                GradientInterp::Oklab | GradientInterp::Smooth if i > 0 => {
+                    for k in 1..GRADIENT_SUBDIV {
+                        let t = prev + (stop.pos - prev) * k as f32 / GRADIENT_SUBDIV as f32;
+                        cols.push((t, self.sample(t)));
+                    }
+                }
+                GradientInterp::Step if i > 0 => cols.push((stop.pos, self.stops[i - 1].col)),
+                _ => (),
+            }
+            cols.push((stop.pos, stop.col));
+        }
+        cols.push((1.0, self.sample(1.0)));
+
+        let (along, across) = match axis {
+            Axis::X => (Vec2::X, Vec2::Y),
+            Axis::Y => (Vec2::Y, Vec2::X),
+        };
+        let (min, size) = (rect.min, rect.size());
+        let mut vtx = Vec::with_capacity(cols.len() * 2);
+        let mut idx = Vec::with_capacity(cols.len() * 6);
+        for (j, &(t, col)) in cols.iter().enumerate() {
+            let p = min + along * size * t;
+            vtx.push(Vertex::color(p, col));
+            vtx.push(Vertex::color(p + across * size, col));
+            if j > 0 && cols[j - 1].0 < t {
+                let v = (j as u32 - 1) * 2;
+                idx.extend_from_slice(&[v, v + 1, v + 3, v, v + 3, v + 2]);
+            }
+        }
+        (vtx, idx)
+    }
+}
+
+/// state of a [`ui::Context::gradient_editor`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct GradientEditState {
+    /// the stop whose color picker is open
+    selected: Option<usize>,
+    /// the selected stop is dragged
+    grabbed: bool,
+    /// color of the selected stop, kept as hsv so the hue survives black and gray
+    hsv: HSVA,
+}
+
+impl ui::Context {
+    /// edits the stops of a gradient on a bar as wide as the available space
+    ///
+    /// a click on the bar adds a stop, the handles below it are dragged to move a stop, and
+    /// dragged away or right clicked to remove it. a selected stop opens a color picker below
+    /// the bar, the button next to the label switches the [`GradientInterp`]
+    pub fn gradient_editor(&mut self, label: &str, gradient: &mut Gradient) -> Response {
+        let id = self.gen_id(label);
+        let old = gradient.clone();
+        let height = self.style.line_height();
+        let handle = (height * 0.6).round();
+        let width = self.available_content().x;
+        let area = self.place_item(Vec2::new(width, height + handle + 2.0));
+        let bar = area.with_max_y(area.min.y + height);
+        let sig = self.reg_item_active_on_press(id, area);
+        if let Some(node) = self.reg_access_node(id, AccessRole::Image, label, bar) {
+            node.value = Some(format!("{} stops", gradient.stops.len()));
+        }
+
+        let mut state = self.widget_data.get::<GradientEditState>(&id).copied();
+        let select = |gradient: &Gradient, i: usize, grabbed: bool| GradientEditState {
+            selected: Some(i),
+            grabbed,
+            hsv: gradient.stops[i].col.into(),
+        };
+
+        let t = ((self.mouse.pos.x - bar.min.x) / bar.width().max(1.0)).clamp(0.0, 1.0);
+        // the handle under the mouse, the closest one if they overlap
+        let mouse = self.mouse.pos;
+        let on_handles = mouse.y >= bar.max.y && (sig.hovering() || sig.pressed());
+        let hovered = gradient
+            .stops
+            .iter()
+            .map(|s| (bar.min.x + s.pos * bar.width() - mouse.x).abs())
+            .enumerate()
+            .filter(|&(_, d)| on_handles && d <= handle * 0.5)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i);
+
+        if sig.just_pressed() {
+            self.expect_drag = true;
+            state = match hovered {
+                // a click on the selected handle closes its picker
+                Some(i) if state.is_some_and(|s| s.selected == Some(i)) => {
+                    Some(GradientEditState {
+                        selected: None,
+                        grabbed: false,
+                        hsv: gradient.stops[i].col.into(),
+                    })
+                }
+                Some(i) => Some(select(gradient, i, true)),
+                None if bar.contains(self.mouse.pos) => {
+                    let i = gradient.add_stop(t, gradient.sample(t));
+                    Some(select(gradient, i, true))
+                }
+                None => state,
+            };
+        }
+
+        // dragging the handle away from the bar removes the stop on release
+        let far = (self.mouse.pos.y - area.center().y).abs() > area.height() + height;
+        let mut removing = None;
+        if let Some(s) = state.as_mut().filter(|s| s.grabbed) {
+            let i = s
+                .selected
+                .unwrap_or(0)
+                .min(gradient.stops.len().saturating_sub(1));
+            if sig.pressed() && !gradient.stops.is_empty() {
+                if self.mouse.delta != Vec2::ZERO {
+                    s.selected = Some(gradient.move_stop(i, t));
+                }
+                if far && gradient.stops.len() > 1 {
+                    removing = s.selected;
+                }
+            } else {
+                s.grabbed = false;
+                if far && gradient.stops.len() > 1 && !sig.pressed() {
+                    gradient.remove_stop(i);
+                    s.selected = None;
+                }
+            }
+        }
+        let right_click = self.mouse.just_pressed(MouseBtn::Right) && gradient.stops.len() > 1;
+        if let Some(i) = hovered.filter(|_| right_click) {
+            gradient.remove_stop(i);
+            state = None;
+        }
+        if self.key_just_pressed(KeyCode::Escape) && state.is_some_and(|s| !s.grabbed) {
+            state = None;
+        }
+        // the stops were replaced from outside
+        if let Some(s) = &mut state {
+            s.selected = s.selected.filter(|&i| i < gradient.stops.len());
+        }
+
+        if hovered.is_some() || sig.pressed() {
+            self.set_cursor_icon(CursorIcon::MoveH);
+        } else if sig.hovering() && bar.contains(self.mouse.pos) {
+            self.set_cursor_icon(CursorIcon::Pointer);
+        }
+
+        if !self.item_culled() {
+            let painter = self.painter(area);
+            painter.draw(bar.draw_rect().fill(self.style.panel_dark_bg()));
+            painter.add_rect_gradient_stops(bar, gradient, Axis::X);
+            for (i, stop) in gradient.stops.iter().enumerate() {
+                let selected = state.is_some_and(|s| s.selected == Some(i));
+                let x = (bar.min.x + stop.pos * bar.width()).round();
+                let rect = Rect::from_min_size(
+                    Vec2::new(x - handle * 0.5, bar.max.y + 2.0),
+                    Vec2::splat(handle),
+                );
+                let outline = if selected || hovered == Some(i) {
+                    self.style.text_col()
+                } else {
+                    self.style.btn_press()
+                };
+                let alpha = if removing == Some(i) { 0.3 } else { 1.0 };
+                painter.draw(
+                    rect.draw_rect()
+                        .corners(2.0)
+                        .fill(stop.col.with_alpha(stop.col.a * alpha))
+                        .outline(Outline::inner(outline.with_alpha(alpha), 1.5)),
+                );
+            }
+        }
+
+        let mode_id = Id::from_hash(&(id, "interp"));
+        let text_size = self.style.text_size();
+        let shape = self.layout_text(gradient.interp.name(), text_size);
+        let pad = (height - text_size) * 0.5;
+        let mode = self.place_item(Vec2::new(shape.size().x + pad * 2.0, height));
+        let mode_sig = self.reg_item_active_on_press(mode_id, mode);
+        if let Some(node) = self.reg_access_node(mode_id, AccessRole::ComboBox, label, mode) {
+            node.value = Some(gradient.interp.name().to_string());
+        }
+        if mode_sig.clicked() {
+            gradient.interp = gradient.interp.next();
+        }
+        let bg = if mode_sig.pressed() {
+            self.style.btn_press()
+        } else if mode_sig.hovering() {
+            self.style.btn_hover()
+        } else {
+            self.style.btn_default()
+        };
+        if !self.item_culled() {
+            self.draw(mode.draw_rect().corners(self.style.btn_corners()).fill(bg));
+            let pos = mode.min + (mode.size() - shape.size()) * 0.5;
+            self.draw(shape.draw_rects(pos, self.style.text_col()));
+        }
+        self.same_line();
+        self.text(label);
+
+        let mut picking = false;
+        if let Some(s) = state.as_mut().filter(|s| s.selected.is_some()) {
+            let i = s.selected.unwrap();
+            picking = self.hsv_picker(id, &mut s.hsv);
+            if picking {
+                gradient.stops[i].col = s.hsv.into();
+            }
+        }
+
+        match state {
+            Some(s) => self.widget_data.insert(id, s),
+            None => {
+                self.widget_data.remove::<GradientEditState>(&id);
+            }
+        }
+        let changed = *gradient != old;
+        self.value_response(id, sig, sig.pressed() || picking, changed)
+    }
+
+    /// saturation / value square with hue and alpha bars, returns true while it is dragged
+    pub(crate) fn hsv_picker(&mut self, id: Id, hsv: &mut HSVA) -> bool {
+        let height = self.style.line_height();
+        let side = height * 5.0;
+        let bar = Vec2::new(height * 0.8, side);
+
+        let sv = self.place_item(Vec2::splat(side));
+        let sv_sig = self.reg_item_active_on_press(Id::from_hash(&(id, "sv")), sv);
+        self.same_line();
+        let hue = self.place_item(bar);
+        let hue_sig = self.reg_item_active_on_press(Id::from_hash(&(id, "hue")), hue);
+        self.same_line();
+        let alpha = self.place_item(bar);
+        let alpha_sig = self.reg_item_active_on_press(Id::from_hash(&(id, "alpha")), alpha);
+
+        let rel =
+            |r: Rect, p: Vec2| ((p - r.min) / r.size().max(Vec2::ONE)).clamp(Vec2::ZERO, Vec2::ONE);
+        if sv_sig.pressed() {
+            let p = rel(sv, self.mouse.pos);
+            hsv.s = p.x;
+            hsv.v = 1.0 - p.y;
+        }
+        if hue_sig.pressed() {
+            hsv.h = rel(hue, self.mouse.pos).y * 359.9;
+        }
+        if alpha_sig.pressed() {
+            hsv.a = 1.0 - rel(alpha, self.mouse.pos).y;
+        }
+
+        if !self.item_culled() {
+            let list = self.current_drawlist();
+            let pure = RGBA::from(HSVA::new(hsv.h, 1.0, 1.0, 1.0));
+            let [tl, tr, br, bl] = [sv.min, sv.right_top(), sv.max, sv.left_bottom()];
+            let (white, black) = (RGBA::WHITE, RGBA::BLACK);
+            list.add_convex_poly_filled_multicolor(&[tl, tr, br, bl], &[white, pure, pure, white]);
+            let clear = black.with_alpha(0.0);
+            list.add_convex_poly_filled_multicolor(
+                &[tl, tr, br, bl],
+                &[clear, clear, black, black],
+            );
+
+            let hues: Vec<RGBA> = (0..=6)
+                .map(|i| HSVA::new(i as f32 * 60.0, 1.0, 1.0, 1.0).into())
+                .collect();
+            list.add_rect_gradient_stops(hue, &Gradient::from_colors(&hues), Axis::Y);
+
+            let col = RGBA::from(*hsv).with_alpha(1.0);
+            list.draw(alpha.draw_rect().fill(self.style.panel_dark_bg()));
+            let fade = Gradient::from_colors(&[col, col.with_alpha(0.0)]);
+            list.add_rect_gradient_stops(alpha, &fade, Axis::Y);
+
+            let marker = Outline::center(self.style.text_col(), 1.5);
+            let p = sv.min + Vec2::new(hsv.s, 1.0 - hsv.v) * sv.size();
+            list.draw(
+                Rect::from_center_size(p, Vec2::splat(8.0))
+                    .draw_rect()
+                    .circle()
+                    .outline(marker),
+            );
+            for (r, t) in [(hue, hsv.h / 360.0), (alpha, 1.0 - hsv.a)] {
+                let y = r.min.y + t * r.height();
+                let line = Rect::from_min_max(
+                    Vec2::new(r.min.x - 1.0, y - 2.0),
+                    Vec2::new(r.max.x + 1.0, y + 2.0),
+                );
+                list.draw(line.draw_rect().outline(marker));
+            }
+        }
+
+        sv_sig.pressed() || hue_sig.pressed() || alpha_sig.pressed()
+    }
+}
@@ -0,0 +1,290 @@
+//! property panels generated from types, see [`Inspect`] and `#[derive(Inspect)]`
+
+use std::{fmt::Display, str::FromStr};
+
+use glam::{Vec2, Vec3};
+
+use crate::{
+    accessibility::AccessRole,
+    core::{HSVA, RGBA},
+    mouse::CursorIcon,
+    ui::{self, Outline, Response, TextInputState},
+};
+
+/// a value with widgets to edit it, e.g. in the property panel of an editor
+///
+/// implemented for numbers, `bool`, `String`, [`RGBA`], vectors and `Vec`s, and derived with
+/// `#[derive(Inspect)]` for structs and enums with unit variants. the methods return true
+/// if the value was changed
+pub trait Inspect {
+    fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool;
+
+    /// the widgets without a section around them, see [`ui::Context::inspect`]
+    fn inspect_fields(&mut self, ui: &mut ui::Context) -> bool {
+        self.inspect(ui, "")
+    }
+}
+
+/// a number shown as a slider, for fields with `#[inspect(range = min..=max)]`
+pub trait InspectRange {
+    fn inspect_range(&mut self, ui: &mut ui::Context, label: &str, min: f64, max: f64) -> bool;
+}
+
+/// open state of an [`ui::Context::inspect_section`]
+struct InspectSection(bool);
+
+/// open state and hsv of an [`ui::Context::color_edit`]
+struct ColorEditState(HSVA);
+
+impl ui::Context {
+    /// edits the fields of `value`, e.g. the selected object of an editor
+    ///
+    /// ```ignore
+    /// #[derive(Inspect)]
+    /// struct Light {
+    ///     #[inspect(range = 0.0..=10.0)]
+    ///     intensity: f32,
+    ///     col: RGBA,
+    /// }
+    ///
+    /// ui.inspect(&mut light);
+    /// ```
+    pub fn inspect(&mut self, value: &mut impl Inspect) -> bool {
+        value.inspect_fields(self)
+    }
+
+    /// a collapsible section with the items of `f` indented, open by default. `f` returns
+    /// whether something was changed
+    pub fn inspect_section(&mut self, label: &str, f: impl FnOnce(&mut Self) -> bool) -> bool {
+        let id = self.gen_id(label);
+        let mut open = self.data_get_or_insert_with(id, || InspectSection(true)).0;
+        self.collapsing_header(label, &mut open);
+        self.data_insert(id, InspectSection(open));
+        if !open {
+            return false;
+        }
+
+        let indent = self.style.line_height() * 0.5;
+        self.indent(indent);
+        let changed = self.keyed(id, f);
+        self.unindent(indent);
+        changed
+    }
+
+    /// a combo box over `names`, returns the picked index if it is not `current`
+    pub fn inspect_enum(&mut self, label: &str, names: &[&str], current: usize) -> Option<usize> {
+        let mut selected = Some(current);
+        self.searchable_select(label, names, &mut selected);
+        selected.filter(|&i| i != current)
+    }
+
+    /// a text field that parses its input, the value only changes when the text is valid
+    pub fn inspect_parsed<T: FromStr + Display + PartialEq>(
+        &mut self,
+        label: &str,
+        val: &mut T,
+    ) -> bool {
+        let id = self.gen_id(label);
+        let text = val.to_string();
+        let resp = self.input_text(label, &text);
+        self.same_line();
+        self.text(label);
+
+        let active = self.active_id == id;
+        let Some(input) = self.widget_data.get_mut::<TextInputState>(&id) else {
+            return false;
+        };
+        let current = input.copy_all();
+        if resp.changed() {
+            if let Ok(v) = current.trim().parse::<T>()
+                && v != *val
+            {
+                *val = v;
+                return true;
+            }
+        } else if !active && current != text {
+            // the value was changed from outside
+            input.set_text(&text);
+            input.changed = false;
+        }
+        false
+    }
+
+    /// a color swatch that opens a color picker below it when clicked
+    pub fn color_edit(&mut self, label: &str, col: &mut RGBA) -> Response {
+        let id = self.gen_id(label);
+        let old = *col;
+        let height = self.style.line_height();
+        let rect = self.place_item(Vec2::new(height * 2.0, height));
+        let sig = self.reg_item_active_on_press(id, rect);
+        let open = self.widget_data.contains_key::<ColorEditState>(&id);
+        if let Some(node) = self.reg_access_node(id, AccessRole::Button, label, rect) {
+            node.value = Some(col.to_string());
+            node.expanded = Some(open);
+        }
+        if sig.hovering() {
+            self.set_cursor_icon(CursorIcon::Pointer);
+        }
+        if sig.clicked() {
+            match open {
+                true => {
+                    self.widget_data.remove::<ColorEditState>(&id);
+                }
+                false => self.widget_data.insert(id, ColorEditState((*col).into())),
+            }
+        }
+
+        if !self.item_culled() {
+            let corners = self.style.btn_corners();
+            let outline = Outline::hairline(self.style.btn_press());
+            self.draw(
+                rect.draw_rect()
+                    .corners(corners)
+                    .fill(self.style.panel_dark_bg()),
+            );
+            self.draw(
+                rect.draw_rect()
+                    .corners(corners)
+                    .fill(*col)
+                    .outline(outline),
+            );
+        }
+        self.same_line();
+        self.text(label);
+
+        let mut picking = false;
+        if let Some(ColorEditState(mut hsv)) = self.widget_data.remove::<ColorEditState>(&id) {
+            // keep the hue and saturation of the picker while they don't change the color
+            if RGBA::from(hsv) != *col {
+                hsv = (*col).into();
+            }
+            picking = self.hsv_picker(id, &mut hsv);
+            if picking {
+                *col = hsv.into();
+            }
+            self.widget_data.insert(id, ColorEditState(hsv));
+        }
+        self.value_response(id, sig, picking, *col != old)
+    }
+
+    /// the entries of a list with buttons to move and remove them and to add a default entry
+    fn inspect_list<T: Inspect + Default>(&mut self, label: &str, list: &mut Vec<T>) -> bool {
+        use ui::phosphor_font::{CARET_DOWN, CARET_UP, X};
+
+        self.inspect_section(label, |ui| {
+            let mut changed = false;
+            let mut action = None;
+            let n = list.len();
+            for (i, entry) in list.iter_mut().enumerate() {
+                ui.keyed(i, |ui| {
+                    if ui.icon_button(CARET_UP, "") && i > 0 {
+                        action = Some((i, i - 1));
+                    }
+                    ui.same_line();
+                    if ui.icon_button(CARET_DOWN, "") && i + 1 < n {
+                        action = Some((i, i + 1));
+                    }
+                    ui.same_line();
+                    if ui.icon_button(X, "") {
+                        action = Some((i, usize::MAX));
+                    }
+                    ui.same_line();
+                    changed |= entry.inspect(ui, &format!("[{i}]"));
+                });
+            }
+            match action {
+                Some((i, usize::MAX)) => {
+                    list.remove(i);
+                }
+                Some((i, j)) => list.swap(i, j),
+                None => (),
+            }
+            if ui.button("add") {
+                list.push(T::default());
+                changed = true;
+            }
+            changed || action.is_some()
+        })
+    }
+}
+
+macro_rules! impl_inspect_number {
+    ($($ty:ty),*) => {$(
+        impl Inspect for $ty {
+            fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool {
+                ui.inspect_parsed(label, self)
+            }
+        }
+
+        impl InspectRange for $ty {
+            fn inspect_range(
+                &mut self,
+                ui: &mut ui::Context,
+                label: &str,
+                min: f64,
+                max: f64,
+            ) -> bool {
+                let mut v = *self as f32;
+                ui.slider_f32(label, min as f32, max as f32, &mut v);
+                let new = v as f64 as $ty;
+                let changed = new != *self;
+                *self = new;
+                changed
+            }
+        }
+    )*};
+}
+
+impl_inspect_number!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl Inspect for bool {
+    fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool {
+        ui.checkbox(label, self).changed()
+    }
+}
+
+impl Inspect for String {
+    fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool {
+        ui.inspect_parsed(label, self)
+    }
+}
+
+impl Inspect for RGBA {
+    fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool {
+        ui.color_edit(label, self).changed()
+    }
+}
+
+impl Inspect for Vec2 {
+    fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool {
+        ui.inspect_section(label, |ui| {
+            ui.inspect_parsed("x", &mut self.x) | ui.inspect_parsed("y", &mut self.y)
+        })
+    }
+}
+
+impl Inspect for Vec3 {
+    fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool {
+        ui.inspect_section(label, |ui| {
+            ui.inspect_parsed("x", &mut self.x)
+                | ui.inspect_parsed("y", &mut self.y)
+                | ui.inspect_parsed("z", &mut self.z)
+        })
+    }
+}
+
+impl<T: Inspect + Default> Inspect for Vec<T> {
+    fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool {
+        ui.inspect_list(label, self)
+    }
+}
+
+impl<T: Inspect + ?Sized> Inspect for Box<T> {
+    fn inspect(&mut self, ui: &mut ui::Context, label: &str) -> bool {
+        (**self).inspect(ui, label)
+    }
+
+    fn inspect_fields(&mut self, ui: &mut ui::Context) -> bool {
+        (**self).inspect_fields(ui)
+    }
+}
@@ -1,9 +1,57 @@
+use std::f32::consts::{PI, TAU};
+
 use glam::Vec2;
 
 use crate::{
-    core::RGBA, ctext, gpu, mouse::{CursorIcon, MouseBtn}, rect::Rect, ui::{self, CornerRadii, Id, ItemFlags, Signal, TabBar, TextInputFlags, TextInputState, TextureId}
+    accessibility::AccessRole, core::RGBA, ctext, gpu, mouse::{CursorIcon, MouseBtn}, rect::Rect, ui::{self, CornerRadii, Id, ItemFlags, Margins, Response, Signal, LayoutDir, Stroke, TabBar, TextAlign, TextInputFlags, TextInputState, TextItem, TextWrap, TextureId}
 };
 
+/// formats with up to 3 decimal places, trimming unnecessary trailing zeros
+fn format_value(v: f32) -> String {
+    if !v.is_finite() {
+        return format!("{}", v);
+    }
+    let formatted = format!("{:.3}", v);
+    if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        formatted
+    }
+}
+
+/// the gauge scale starts at the bottom left and runs clockwise over 240 degrees
+const GAUGE_START: f32 = PI * 7.0 / 6.0;
+const GAUGE_SWEEP: f32 = -PI * 4.0 / 3.0;
+const GAUGE_TICKS: u32 = 10;
+
+/// the knob range starts at the bottom left and runs clockwise over 270 degrees
+const KNOB_START: f32 = PI * 5.0 / 4.0;
+const KNOB_SWEEP: f32 = -PI * 3.0 / 2.0;
+
+/// how a knob is being dragged, decided when it is pressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct KnobDrag {
+    circular: bool,
+}
+
+/// marks a value widget that is being interacted with, see [`Response`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Engaged;
+
+/// maps an angle around the knob center (counter clockwise, y up) to `[0, 1]`,
+/// angles in the gap at the bottom snap to the closer end
+pub(crate) fn knob_angle_to_t(angle: f32) -> f32 {
+    let sweep = -KNOB_SWEEP;
+    let d = (KNOB_START - angle).rem_euclid(TAU);
+    if d <= sweep {
+        d / sweep
+    } else if d < sweep + (TAU - sweep) * 0.5 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
 macro_rules! ui_text {
     ($ui:ident: $($tt:tt)*) => {
         $ui.text(&format!($($tt)*));
@@ -13,17 +61,73 @@ pub(crate) use ui_text;
 
 impl ui::Context {
 
+    /// response of a value widget, `engaged` is true while the widget is held or edited
+    pub(crate) fn value_response(
+        &mut self,
+        id: Id,
+        sig: Signal,
+        engaged: bool,
+        changed: bool,
+    ) -> Response {
+        let was_engaged = self.widget_data.get::<Engaged>(&id).is_some();
+        if engaged && !was_engaged {
+            self.widget_data.insert(id, Engaged);
+        } else if !engaged && was_engaged {
+            self.widget_data.remove::<Engaged>(&id);
+        }
+        // a change outside of an interaction is a single step
+        let step = changed && !engaged && !was_engaged;
+        Response::new(
+            sig,
+            changed,
+            (engaged && !was_engaged) || step,
+            (!engaged && was_engaged) || step,
+        )
+    }
+
     pub fn image(&mut self, size: Vec2, uv_min: Vec2, uv_max: Vec2, tex: &gpu::Texture) {
         let tex_id = self.register_texture(tex);
         self.image_id(size, uv_min, uv_max, tex_id);
     }
 
     pub fn image_id(&mut self, size: Vec2, uv_min: Vec2, uv_max: Vec2, tex_id: TextureId) {
+        self.image_ex(size, uv_min, uv_max, tex_id, CornerRadii::zero());
+    }
+
+    /// image with rounded corners
+    pub fn image_rounded(
+        &mut self,
+        size: Vec2,
+        uv_min: Vec2,
+        uv_max: Vec2,
+        tex: &gpu::Texture,
+        corners: impl Into<CornerRadii>,
+    ) {
+        let tex_id = self.register_texture(tex);
+        self.image_ex(size, uv_min, uv_max, tex_id, corners.into());
+    }
+
+    pub fn image_ex(
+        &mut self,
+        size: Vec2,
+        uv_min: Vec2,
+        uv_max: Vec2,
+        tex_id: TextureId,
+        corners: CornerRadii,
+    ) {
         // let id = self.gen_id(tex_id);
         let id = Id::NULL;
         let rect = self.place_item(size);
         self.reg_item_(id, rect);
-        self.draw(rect.draw_rect().uv(uv_min, uv_max).texture(tex_id));
+        if self.item_culled() {
+            return;
+        }
+        self.draw(
+            rect.draw_rect()
+                .uv(uv_min, uv_max)
+                .texture(tex_id)
+                .corners(corners),
+        );
         // self.draw(|list| {
         //     list.rect(rect.min, rect.max)
         //         .texture_uv(uv_min, uv_max, tex_id)
@@ -38,15 +142,18 @@ impl ui::Context {
         let default = self.style.btn_default();
 
         let total_h = self.style.line_height();
-        let text_shape = self.layout_text(label, self.style.text_size());
-        let text_dim = text_shape.size();
+        let text_dim = self.measure_text(label, self.style.text_size());
 
-        let vert_pad = ((total_h - text_dim.y) / 2.0).max(0.0);
-        let horiz_pad = vert_pad;
-        let size = Vec2::new(text_dim.x + horiz_pad * 2.0, total_h);
+        let padding = Margins::all(((total_h - text_dim.y) / 2.0).max(0.0));
+        let size = Vec2::new(text_dim.x + padding.sum().x, total_h);
 
         let rect = self.place_item(size);
         let sig = self.reg_item_active_on_press(id, rect);
+        self.reg_access_node(id, AccessRole::Button, label, rect);
+        if self.item_culled() {
+            return sig.released();
+        }
+        let text_shape = self.layout_text(label, self.style.text_size());
 
         let start_drag_outside = self
             .mouse
@@ -66,7 +173,7 @@ impl ui::Context {
 
         self.draw(
             rect.draw_rect()
-                .corners(CornerRadii::all(self.style.btn_corner_radius()))
+                .corners(self.style.btn_corners())
                 .fill(btn_col),
         )
         .draw(text_shape.draw_rects(text_pos, text_col));
@@ -81,7 +188,83 @@ impl ui::Context {
         sig.released() && !start_drag_outside
     }
 
-    pub fn switch(&mut self, label: &str, b: &mut bool) -> bool {
+    /// icon of a registered icon font, see [`ui::IconTable::find`]
+    pub fn icon(&mut self, name: &str, size: f32, col: RGBA) {
+        let Some(shape) = self.layout_named_icon(name, size) else {
+            log::warn!("unknown icon: {name}");
+            return;
+        };
+
+        let line_height = self.style.line_height().max(shape.height);
+        let rect = self.place_item(Vec2::new(shape.width, line_height));
+        if self.item_culled() {
+            return;
+        }
+        let pos = rect.min + Vec2::new(0.0, (line_height - shape.height) * 0.5);
+        self.draw(shape.draw_rects(pos, col));
+    }
+
+    /// icon followed by text on the same baseline
+    pub fn icon_text(&mut self, icon: &str, text: &str) {
+        let (icon_shape, text_shape, icon_off, text_off, size) =
+            self.layout_icon_text(icon, text, self.style.text_size());
+
+        let line_height = self.style.line_height().max(size.y);
+        let rect = self.place_item(Vec2::new(size.x, line_height));
+        if self.item_culled() {
+            return;
+        }
+        let pos = rect.min + Vec2::new(0.0, (line_height - size.y) * 0.5);
+
+        let col = self.style.text_col();
+        self.draw(icon_shape.draw_rects(pos + icon_off, col))
+            .draw(text_shape.draw_rects(pos + text_off, col));
+    }
+
+    /// button with an icon in front of the label, the label may be empty
+    pub fn icon_button(&mut self, icon: &str, label: &str) -> bool {
+        let id = self.gen_id(&format!("{icon}{label}"));
+        let (icon_shape, text_shape, icon_off, text_off, content_size) =
+            self.layout_icon_text(icon, label, self.style.text_size());
+
+        let total_h = self.style.line_height();
+        let padding = Margins::all(((total_h - content_size.y) / 2.0).max(0.0));
+        let size = Vec2::new(content_size.x + padding.sum().x, total_h.max(content_size.y));
+
+        let rect = self.place_item(size);
+        let sig = self.reg_item_active_on_press(id, rect);
+        self.reg_access_node(id, AccessRole::Button, if label.is_empty() { icon } else { label }, rect);
+        if self.item_culled() {
+            return sig.released();
+        }
+
+        let start_drag_outside = self
+            .mouse
+            .drag_start(MouseBtn::Left)
+            .map_or(false, |pos| !rect.contains(pos));
+
+        let (btn_col, text_col) = if sig.pressed() && !start_drag_outside {
+            (self.style.btn_press(), self.style.btn_press_text())
+        } else if sig.hovering() {
+            (self.style.btn_hover(), self.style.text_col())
+        } else {
+            (self.style.btn_default(), self.style.text_col())
+        };
+
+        let pos = rect.min + (size - content_size) * 0.5;
+        self.draw(
+            rect.draw_rect()
+                .corners(self.style.btn_corners())
+                .fill(btn_col),
+        )
+        .draw(icon_shape.draw_rects(pos + icon_off, text_col))
+        .draw(text_shape.draw_rects(pos + text_off, text_col));
+
+        sig.released() && !start_drag_outside
+    }
+
+    pub fn switch(&mut self, label: &str, b: &mut bool) -> Response {
+        let prev_b = *b;
         let height = self.style.line_height();
         let width = height * 1.8;
         let size = Vec2::new(width, self.style.line_height());
@@ -95,6 +278,9 @@ impl ui::Context {
         if sig.released() {
             *b = !*b;
         }
+        if let Some(node) = self.reg_access_node(id, AccessRole::Switch, label, rect) {
+            node.toggled = Some(*b);
+        }
 
         let mut bg_col = if sig.hovering() {
             self.style.btn_hover()
@@ -108,14 +294,14 @@ impl ui::Context {
         }
 
         // self.draw(|list|
-        {
+        if !self.item_culled() {
             let rail_min = rect.min;
             let rail_max = rail_min + Vec2::new(width, height);
             self.draw(
                 rect.draw_rect()
                     // .corners(CornerRadii::all(height * 0.5))
                     // .corners(CornerRadii::all(height * 0.3))
-                    .corners(CornerRadii::all(self.style.btn_corner_radius()))
+                    .corners(self.style.btn_corners())
                     .fill(bg_col),
             );
 
@@ -132,7 +318,7 @@ impl ui::Context {
                     .draw_rect()
                     // .circle(handle_center, handle_r)
                     // .corners(CornerRadii::all(height * 0.8 * 0.3))
-                    .corners(CornerRadii::all(self.style.btn_corner_radius()))
+                    .corners(self.style.btn_corners())
                     .fill(handle_col),
             );
             // .add();
@@ -142,10 +328,10 @@ impl ui::Context {
         self.same_line();
         self.text(label);
 
-        *b
+        self.value_response(id, sig, false, *b != prev_b)
     }
 
-    pub fn checkbox(&mut self, label: &str, b: &mut bool) -> bool {
+    pub fn checkbox(&mut self, label: &str, b: &mut bool) -> Response {
         let prev_b = *b;
         let id = self.gen_id(label);
         let active = self.style.btn_press();
@@ -161,6 +347,9 @@ impl ui::Context {
         if sig.released() {
             *b = !*b;
         }
+        if let Some(node) = self.reg_access_node(id, AccessRole::CheckBox, label, rect) {
+            node.toggled = Some(*b);
+        }
 
         let col = if sig.pressed() {
             active
@@ -170,16 +359,16 @@ impl ui::Context {
             default
         };
 
-        let radii = CornerRadii::all(self.style.btn_corner_radius());
+        let radii = self.style.btn_corners();
         // self.draw(|list| {
-        let inset = box_size * 0.15;
-        let inner_min = rect.min + Vec2::splat(inset);
-        let inner_max = rect.max - Vec2::splat(inset);
+        let inner = Margins::all(box_size * 0.15).shrink_rect(rect);
 
-        self.draw(rect.draw_rect().fill(col).corners(radii));
-        if *b {
+        if !self.item_culled() {
+            self.draw(rect.draw_rect().fill(col).corners(radii));
+        }
+        if *b && !self.item_culled() {
             self.draw(
-                Rect::from_min_max(inner_min, inner_max)
+                inner
                     .draw_rect()
                     .corners(radii)
                     .fill(active),
@@ -190,20 +379,31 @@ impl ui::Context {
         self.same_line();
         self.text(label);
 
-        *b != prev_b
+        self.value_response(id, sig, false, *b != prev_b)
     }
 
-    pub fn separator_h(&mut self, thickness: f32, fill: RGBA) {
+    /// horizontal line over the available width
+    pub fn separator_h(&mut self, stroke: impl Into<Stroke>) {
+        let stroke = stroke.into();
+        let thickness = stroke.pixel_width();
         let width = self.available_content().x;
         let rect = self.place_item(Vec2::new(width, thickness));
-        let col = self.style.panel_dark_bg();
+        if self.item_culled() {
+            return;
+        }
 
         // self.draw(|list| list.rect(rect.min, rect.max).fill(fill).add());
-        self.draw(rect.draw_rect().fill(fill));
+        self.draw(rect.draw_rect().fill(stroke.col));
+    }
+
+    /// hairline separator in the dark panel color
+    pub fn separator(&mut self) {
+        self.separator_h(Stroke::hairline(self.style.panel_dark_bg()));
     }
 
-    pub fn slider_f32(&mut self, label: &str, min: f32, max: f32, val: &mut f32) {
+    pub fn slider_f32(&mut self, label: &str, min: f32, max: f32, val: &mut f32) -> Response {
         let id = self.gen_id(label);
+        let old = *val;
         let height = self.style.line_height();
         let width = self.available_content().x / 2.5;
         let rect = self.place_item(Vec2::new(width, height));
@@ -223,6 +423,12 @@ impl ui::Context {
                 *val = min + t * (max - min);
             }
         }
+        if let Some(action) = self.access.take_action(id) {
+            action.apply_numeric(val, min, max);
+        }
+        if let Some(node) = self.reg_access_node(id, AccessRole::Slider, label, rect) {
+            node.numeric = Some((*val, min, max));
+        }
 
         let ratio = if (max - min).abs() < f32::EPSILON {
             0.0
@@ -250,17 +456,19 @@ impl ui::Context {
         };
 
         // self.draw(|list| {
-        self.draw(
-            rect.draw_rect()
-                .corners(CornerRadii::all(self.style.btn_corner_radius()))
-                .fill(rail_col),
-        )
-        .draw(
-            Rect::from_min_max(handle_min, handle_max)
-                .draw_rect()
-                .corners(self.style.btn_corner_radius())
-                .fill(handle_col),
-        );
+        if !self.item_culled() {
+            self.draw(
+                rect.draw_rect()
+                    .corners(self.style.btn_corners())
+                    .fill(rail_col),
+            )
+            .draw(
+                Rect::from_min_max(handle_min, handle_max)
+                    .draw_rect()
+                    .corners(self.style.btn_corners())
+                    .fill(handle_col),
+            );
+        }
 
         // list.rect(handle_min, handle_max)
         //     .corners(CornerRadii::all(self.style.btn_corner_radius()))
@@ -270,14 +478,23 @@ impl ui::Context {
 
         self.same_line();
         self.text(label);
+
+        self.value_response(id, sig, sig.pressed() || sig.dragging(), *val != old)
     }
 
     /// Slider that shows the current value centered. Click to edit the value as text,
     /// drag to change it continuously.
-    pub fn input_slider_f32(&mut self, label: &str, min: f32, max: f32, val: &mut f32) {
+    pub fn input_slider_f32(
+        &mut self,
+        label: &str,
+        min: f32,
+        max: f32,
+        val: &mut f32,
+    ) -> Response {
         // AI SLOP
         use ctext::Edit;
 
+        let old = *val;
         let height = self.style.line_height();
         let width = self.available_content().x / 2.5;
         let id = self.gen_id(label);
@@ -290,7 +507,7 @@ impl ui::Context {
 
         if (sig.clicked() || sig.keyboard_focused()) && !is_editing {
             let s = format!("{}", *val);
-            let item = ui::TextItem::new(s, self.style.text_size(), 1.0, "Inter");
+            let item = ui::TextItem::new(s, self.style.text_size(), 1.0, self.text_font);
             self.active_id = id;
             self.widget_data.insert(id, TextInputState::new(id, self.font_table.clone(), item, false));
             self.widget_data.get_mut::<TextInputState>(&id).unwrap().select_all();
@@ -317,6 +534,12 @@ impl ui::Context {
                 *val = min + t * (max - min);
             }
         }
+        if let Some(action) = self.access.take_action(id) {
+            action.apply_numeric(val, min, max);
+        }
+        if let Some(node) = self.reg_access_node(id, AccessRole::Slider, label, rect) {
+            node.numeric = Some((*val, min, max));
+        }
 
         // Draw only the rail background here; the numeric/text editor is drawn below
         let rail_col = if sig.dragging() || sig.pressed() {
@@ -326,11 +549,15 @@ impl ui::Context {
         } else {
             self.style.btn_default()
         };
-        self.draw(
-            rect.draw_rect()
-                .corners(CornerRadii::all(self.style.btn_corner_radius()))
-                .fill(rail_col),
-        );
+        // the editor still handles its input while culled
+        let culled = self.item_culled();
+        if !culled {
+            self.draw(
+                rect.draw_rect()
+                    .corners(self.style.btn_corners())
+                    .fill(rail_col),
+            );
+        }
 
         self.current_drawlist().push_merged_clip_rect(rect);
 
@@ -348,13 +575,7 @@ impl ui::Context {
 
             // Forward mouse events relative to the editor origin
             let rel = self.mouse.pos - edit_pos;
-            if sig.double_pressed() {
-                input.mouse_double_clicked(rel);
-            } else if sig.dragging() {
-                input.mouse_dragging(rel);
-            } else if sig.pressed() {
-                input.mouse_pressed(rel);
-            }
+            input.handle_mouse(rel, sig);
 
             // Live-validate input text
             let cur_text = input.copy_all();
@@ -367,7 +588,7 @@ impl ui::Context {
             self.draw(
                 rect.draw_rect()
                     .fill(bg)
-                    .corners(self.style.btn_corner_radius()),
+                    .corners(self.style.btn_corners()),
             );
             self.draw_text_input(id, edit_pos, rect);
 
@@ -378,23 +599,11 @@ impl ui::Context {
                     *val = v.clamp(min, max);
                 }
                 self.widget_data.remove::<TextInputState>(&id);
+                is_editing = false;
             }
-        } else {
+        } else if !culled {
             // Display centered numeric value when not editing
-            // Format with up to 3 decimal places, trimming unnecessary trailing zeros
-            let val_txt = {
-                let v = *val;
-                if !v.is_finite() {
-                    format!("{}", v)
-                } else {
-                    let formatted = format!("{:.3}", v);
-                    if formatted.contains('.') {
-                        formatted.trim_end_matches('0').trim_end_matches('.').to_string()
-                    } else {
-                        formatted
-                    }
-                }
-            };
+            let val_txt = format_value(*val);
             let txt = self.layout_text(&val_txt, self.style.text_size());
             let txt_sz = txt.size();
             let txt_pos = rect.min + Vec2::new((rect.width() - txt_sz.x) * 0.5, (rect.height() - txt_sz.y) * 0.5);
@@ -413,6 +622,179 @@ impl ui::Context {
 
         self.same_line();
         self.text(label);
+
+        let engaged = is_editing || sig.pressed() || sig.dragging();
+        self.value_response(id, sig, engaged, *val != old)
+    }
+
+    /// read only circular gauge showing `val` on a scale from `min` to `max`
+    pub fn gauge(&mut self, label: &str, min: f32, max: f32, val: f32) {
+        self.gauge_ex(label, min, max, val, &[]);
+    }
+
+    /// gauge with colored `zones` along the scale, given as `(from, to, color)` in value units
+    pub fn gauge_ex(
+        &mut self,
+        label: &str,
+        min: f32,
+        max: f32,
+        val: f32,
+        zones: &[(f32, f32, RGBA)],
+    ) {
+        let id = self.gen_id(label);
+        let radius = self.style.line_height() * 2.0;
+        let rect = self.place_item(Vec2::splat(radius * 2.0));
+        self.reg_item_(id, rect);
+        if let Some(node) = self.reg_access_node(id, AccessRole::Label, label, rect) {
+            node.numeric = Some((val, min, max));
+        }
+
+        if !self.item_culled() {
+            let ratio = |v: f32| {
+                if (max - min).abs() < f32::EPSILON {
+                    0.0
+                } else {
+                    ((v - min) / (max - min)).clamp(0.0, 1.0)
+                }
+            };
+            let angle = |v: f32| GAUGE_START + ratio(v) * GAUGE_SWEEP;
+
+            let center = Vec2::new(rect.center().x, rect.min.y + radius);
+            let width = radius * 0.12;
+            let track_r = radius - width * 0.5;
+            let painter = self.painter(rect);
+
+            painter.add_arc(
+                center,
+                track_r,
+                GAUGE_START,
+                GAUGE_SWEEP,
+                Stroke::new(self.style.btn_default(), width),
+            );
+            for &(from, to, col) in zones {
+                let (a0, a1) = (angle(from), angle(to));
+                painter.add_arc(center, track_r, a0, a1 - a0, Stroke::new(col, width));
+            }
+
+            for i in 0..=GAUGE_TICKS {
+                let a = GAUGE_START + i as f32 / GAUGE_TICKS as f32 * GAUGE_SWEEP;
+                let dir = Vec2::new(a.cos(), -a.sin());
+                let len = if i % 5 == 0 { 0.18 } else { 0.1 };
+                painter.add_line(
+                    center + dir * (radius - width - radius * len),
+                    center + dir * (radius - width),
+                    Stroke::new(self.style.text_col(), 1.0),
+                );
+            }
+
+            let a = angle(val);
+            let needle_col = self.style.btn_press();
+            painter.add_line(
+                center,
+                center + Vec2::new(a.cos(), -a.sin()) * radius * 0.75,
+                Stroke::new(needle_col, 2.0),
+            );
+            let dot = radius * 0.1;
+            painter.add_draw_rect(
+                Rect::from_center_size(center, Vec2::splat(dot * 2.0))
+                    .draw_rect()
+                    .fill(needle_col)
+                    .corners(dot),
+            );
+
+            let txt = self.layout_text(&format_value(val), self.style.text_size());
+            let pos = center + Vec2::new(-txt.size().x * 0.5, radius * 0.25);
+            self.draw(txt.draw_rects(pos, self.style.text_col()));
+        }
+
+        self.same_line();
+        self.text(label);
+    }
+
+    /// rotary knob, drag up / down to change the value or press on the outer ring and drag
+    /// around it to set the value directly. hold shift for fine adjustments
+    pub fn knob(&mut self, label: &str, min: f32, max: f32, val: &mut f32) -> Response {
+        let id = self.gen_id(label);
+        let old = *val;
+        let radius = self.style.line_height();
+        let rect = self.place_item(Vec2::splat(radius * 2.0));
+        let sig = self.reg_item_active_on_press(id, rect);
+        let center = rect.center();
+
+        if sig.pressed() && !sig.dragging() {
+            self.expect_drag = true;
+            let circular = (self.mouse.pos - center).length() > radius * 0.6;
+            self.widget_data.insert(id, KnobDrag { circular });
+        }
+        if sig.pressed() || sig.dragging() {
+            let circular = self.widget_data.get::<KnobDrag>(&id).is_some_and(|d| d.circular);
+            if circular {
+                let rel = self.mouse.pos - center;
+                let t = knob_angle_to_t((-rel.y).atan2(rel.x));
+                *val = min + t * (max - min);
+            } else {
+                let speed = if self.modifiers.shift_key() { 0.1 } else { 1.0 };
+                *val = (*val - self.mouse.delta.y * speed * (max - min) / 200.0).clamp(min, max);
+            }
+        } else if self.widget_data.contains_key::<KnobDrag>(&id) {
+            self.widget_data.remove::<KnobDrag>(&id);
+        }
+        if let Some(action) = self.access.take_action(id) {
+            action.apply_numeric(val, min, max);
+        }
+        if let Some(node) = self.reg_access_node(id, AccessRole::Slider, label, rect) {
+            node.numeric = Some((*val, min, max));
+        }
+
+        if sig.hovering() || sig.dragging() {
+            self.set_cursor_icon(CursorIcon::MoveV);
+        }
+
+        if !self.item_culled() {
+            let ratio = if (max - min).abs() < f32::EPSILON {
+                0.0
+            } else {
+                ((*val - min) / (max - min)).clamp(0.0, 1.0)
+            };
+            let (track_col, knob_col) = if sig.dragging() || sig.pressed() {
+                (self.style.btn_press(), self.style.btn_hover())
+            } else if sig.hovering() {
+                (self.style.btn_hover(), self.style.btn_press())
+            } else {
+                (self.style.btn_default(), self.style.btn_press())
+            };
+
+            let width = radius * 0.15;
+            let track_r = radius - width * 0.5;
+            let painter = self.painter(rect);
+            painter.add_arc(center, track_r, KNOB_START, KNOB_SWEEP, Stroke::new(track_col, width));
+            painter.add_arc(
+                center,
+                track_r,
+                KNOB_START,
+                ratio * KNOB_SWEEP,
+                Stroke::new(self.style.text_col(), width),
+            );
+            let inner = radius * 0.6;
+            painter.add_draw_rect(
+                Rect::from_center_size(center, Vec2::splat(inner * 2.0))
+                    .draw_rect()
+                    .fill(knob_col)
+                    .corners(inner),
+            );
+            let a = KNOB_START + ratio * KNOB_SWEEP;
+            let dir = Vec2::new(a.cos(), -a.sin());
+            painter.add_line(
+                center + dir * inner * 0.3,
+                center + dir * inner * 0.9,
+                Stroke::new(self.style.text_col(), 2.0),
+            );
+        }
+
+        self.same_line();
+        self.text(label);
+
+        self.value_response(id, sig, sig.pressed() || sig.dragging(), *val != old)
     }
 
     pub fn collapsing_header(&mut self, label: &str, open: &mut bool) -> bool {
@@ -426,8 +808,11 @@ impl ui::Context {
         let text_shape = self.layout_text(label, self.style.text_size());
         let text_dim = text_shape.size();
 
+        let rtl = self.layout_dir == LayoutDir::Rtl;
         let icon = if *open {
             ui::phosphor_font::CARET_DOWN
+        } else if rtl {
+            ui::phosphor_font::CARET_LEFT
         } else {
             ui::phosphor_font::CARET_RIGHT
         };
@@ -449,6 +834,12 @@ impl ui::Context {
         if sig.just_pressed() {
             *open = !*open;
         }
+        if let Some(node) = self.reg_access_node(id, AccessRole::Header, label, rect) {
+            node.expanded = Some(*open);
+        }
+        if self.item_culled() {
+            return *open;
+        }
 
         let (btn_col, text_col) = if sig.hovering() {
             (hover, self.style.text_col())
@@ -456,13 +847,17 @@ impl ui::Context {
             (default, self.style.text_col())
         };
 
-        let icon_pos = rect.min + Vec2::new(vert_pad, (size.y - icon_dim.y) * 0.5);
-
-        let text_pos = icon_pos + Vec2::new(self.style.text_size() * 2.0, 0.0);
+        let mut icon_pos = rect.min + Vec2::new(vert_pad, (size.y - icon_dim.y) * 0.5);
+        let mut text_pos = icon_pos + Vec2::new(self.style.text_size() * 2.0, 0.0);
+        // icon on the right, text to the left of it
+        if rtl {
+            icon_pos.x = rect.max.x - vert_pad - icon_shape.width;
+            text_pos.x = rect.max.x - vert_pad - self.style.text_size() * 2.0 - text_dim.x;
+        }
 
         self.draw(
             rect.draw_rect()
-                .corners(CornerRadii::all(self.style.btn_corner_radius()))
+                .corners(self.style.btn_corners())
                 .fill(btn_col),
         )
         .draw(icon_shape.draw_rects(icon_pos, text_col))
@@ -471,30 +866,87 @@ impl ui::Context {
         *open
     }
 
+    /// text fit into the available width with [`ui::StyleVar::TextWrap`] and
+    /// [`ui::StyleVar::TextMaxLines`]. the labels of widgets like [`Self::checkbox`] use it too
     pub fn text(&mut self, text: &str) {
+        self.text_ex(text, self.style.text_wrap(), self.style.text_max_lines());
+    }
+
+    /// text that is fit into the available width of the current panel according to `wrap`
+    pub fn text_ex(&mut self, text: &str, wrap: TextWrap, max_lines: Option<u32>) {
         let text_height = self.style.text_size();
         let line_height = self.style.line_height().max(text_height);
 
         let pad = (line_height - text_height) / 2.0;
         self.move_down(pad);
-        let layout = self.layout_text(text, self.style.text_size());
 
-        let id = self.gen_id(text);
+        let avail_w = self.available_content().x;
+        let mut itm = TextItem::new(text.to_string(), self.style.text_size(), 1.0, self.text_font)
+            .with_max_lines(max_lines);
+        if matches!(wrap, TextWrap::Wrap | TextWrap::Truncate) && avail_w > 0.0 {
+            itm = itm.with_wrap(wrap, avail_w);
+        }
+        let text_size = self.measure_text_item(itm.clone());
 
-        let size = Vec2::new(layout.width, layout.height.max(self.style.line_height()));
+        let mut size = Vec2::new(text_size.x, text_size.y.max(self.style.line_height()));
+        if wrap == TextWrap::Clip {
+            size.x = size.x.min(avail_w.max(0.0));
+        }
         let rect = self.place_item(size);
         // self.register_item(id);
         self.move_down(pad);
 
-        self.draw(layout.draw_rects(rect.min, self.style.text_col()));
+        if self.item_culled() {
+            return;
+        }
+        let layout = self.layout_text_item(itm);
+
+        if wrap == TextWrap::Clip {
+            self.push_merged_clip_rect(rect);
+            self.draw(layout.draw_rects(rect.min, self.style.text_col()));
+            self.pop_clip_rect();
+        } else {
+            self.draw(layout.draw_rects(rect.min, self.style.text_col()));
+        }
         // self.draw(|list| list.add_text(rect.min, &layout, self.style.text_col()));
     }
 
-    pub fn input_text(&mut self, label: &str, default_text: &str) {
-        self.input_text_ex(label, default_text, TextInputFlags::NONE);
+    /// single or multi line text aligned inside the available width
+    pub fn text_aligned(&mut self, text: &str, align: TextAlign) {
+        let text_height = self.style.text_size();
+        let line_height = self.style.line_height().max(text_height);
+
+        let pad = (line_height - text_height) / 2.0;
+        self.move_down(pad);
+
+        let avail_w = self.available_content().x;
+        let itm = TextItem::new(text.to_string(), self.style.text_size(), 1.0, self.text_font)
+            .with_wrap(TextWrap::Wrap, avail_w)
+            .with_align(align);
+        let text_size = self.measure_text_item(itm.clone());
+
+        let size = Vec2::new(avail_w.max(text_size.x), text_size.y.max(self.style.line_height()));
+        let rect = self.place_item(size);
+        self.move_down(pad);
+
+        if self.item_culled() {
+            return;
+        }
+        let layout = self.layout_text_item(itm);
+        self.draw(layout.draw_rects(rect.min, self.style.text_col()));
+    }
+
+    pub fn input_text(&mut self, label: &str, default_text: &str) -> Response {
+        self.input_text_ex(label, default_text, TextInputFlags::NONE)
     }
 
-    pub fn input_text_ex(&mut self, label: &str, default_text: &str, flags: TextInputFlags) {
+    /// the interaction of the returned [`Response`] lasts while the input is focused
+    pub fn input_text_ex(
+        &mut self,
+        label: &str,
+        default_text: &str,
+        flags: TextInputFlags,
+    ) -> Response {
         use ctext::Edit;
 
         let text_height = self.style.text_size();
@@ -505,7 +957,8 @@ impl ui::Context {
         let id = self.gen_id(label);
 
         if !self.widget_data.contains_key::<TextInputState>(&id) {
-            let item = ui::TextItem::new(default_text.to_string(), self.style.text_size(), 1.0, "Inter");
+            let text_size = self.style.text_size();
+            let item = ui::TextItem::new(default_text.to_string(), text_size, 1.0, self.text_font);
             self.widget_data.insert(
                 id,
                 TextInputState::new(id, self.font_table.clone(), item, false),
@@ -521,9 +974,8 @@ impl ui::Context {
         let text_dim = layout.size();
 
         let total_h = (text_dim.y).max(self.style.line_height());
-        let vert_pad = ((total_h - text_dim.y) / 2.0).max(0.0);
-        let horiz_pad = vert_pad;
-        let size = Vec2::new(text_dim.x + horiz_pad * 2.0, total_h);
+        let padding = Margins::all(((total_h - text_dim.y) / 2.0).max(0.0));
+        let size = Vec2::new(text_dim.x + padding.sum().x, total_h);
 
         let rect = self.place_item(size);
         // let sig = self.register_item_ex(id, ui::ItemFlags::ACTIVATE_ON_RELEASE);
@@ -547,15 +999,7 @@ impl ui::Context {
         let relative_pos = self.mouse.pos - rect.min;
 
         let input = &mut self.widget_data.get_mut::<TextInputState>(&id).unwrap();
-        if sig.double_pressed() {
-            // TODO[BUG]: only works when double pressing and dragging afterwards, if holding the
-            // double press we loose word selection mode
-            input.mouse_double_clicked(relative_pos);
-        } else if sig.dragging() {
-            input.mouse_dragging(relative_pos);
-        } else if sig.pressed() {
-            input.mouse_pressed(relative_pos);
-        }
+        input.handle_mouse(relative_pos, sig);
 
         if self.active_id != id {
             input.deselect_all();
@@ -568,16 +1012,33 @@ impl ui::Context {
             input.select_all();
         }
 
+        if self.access.enabled {
+            let input = self.widget_data.get::<TextInputState>(&id).unwrap();
+            let (lines, selection) = (input.lines(), input.text_selection());
+            let multiline = input.multiline;
+            if let Some(node) = self.reg_access_node(id, AccessRole::TextInput, label, rect) {
+                node.lines = lines;
+                node.text_selection = Some(selection);
+                node.multiline = multiline;
+            }
+        }
+
         let text_pos =
             rect.min + Vec2::new((size.x - text_dim.x) * 0.5, (size.y - text_dim.y) * 0.5);
         // Draw input background (caller is responsible now)
-        let bg = self.style.panel_dark_bg();
-        self.draw(
-            rect.draw_rect()
-                .fill(bg)
-                .corners(self.style.btn_corner_radius()),
-        );
-        self.draw_text_input(id, text_pos, rect);
+        if !self.item_culled() {
+            let bg = self.style.panel_dark_bg();
+            self.draw(
+                rect.draw_rect()
+                    .fill(bg)
+                    .corners(self.style.btn_corners()),
+            );
+            self.draw_text_input(id, text_pos, rect);
+        }
+
+        let input = self.widget_data.get_mut::<TextInputState>(&id).unwrap();
+        let changed = std::mem::take(&mut input.changed);
+        self.value_response(id, sig, self.active_id == id, changed)
     }
 
     pub fn draw_text_input(&mut self, id: Id, pos: Vec2, rect: Rect) {
@@ -796,7 +1257,7 @@ impl ui::Context {
         self.push_style(ui::StyleVar::SpacingV(0.0));
         let rect = self.place_item(Vec2::new(avail.x, self.style.line_height()));
         self.pop_style();
-        self.separator_h(3.0, self.style.btn_hover());
+        self.separator_h(Stroke::new(self.style.btn_hover(), 3.0));
 
         let cursor = self.get_current_panel()._cursor.clone().into_inner();
 
@@ -880,6 +1341,11 @@ impl ui::Context {
         if sig.pressed() {
             tb.selected_tab_id = id;
         }
+        let selected_now = tb.selected_tab_id == id;
+        if let Some(node) = self.reg_access_node(id, AccessRole::Tab, label, rect) {
+            node.selected = Some(selected_now);
+        }
+        let tb = self.widget_data.get_mut::<TabBar>(&tb_id).unwrap();
         if sig.dragging() && self.active_id == id && !tb.is_dragging {
             tb.is_dragging = true;
             tb.selected_tab_id = id;
@@ -919,7 +1385,7 @@ impl ui::Context {
                 Rect::from_min_size(item_pos, rect.size())
                     .draw_rect()
                     .fill(btn_col)
-                    .corners(CornerRadii::top(self.style.btn_corner_radius())),
+                    .corners(self.style.btn_corners().with_bottom(0.0)),
             )
             .draw_over(text_shape.draw_rects(text_pos, text_col));
         } else {
@@ -927,7 +1393,7 @@ impl ui::Context {
                 Rect::from_min_size(item_pos, rect.size())
                     .draw_rect()
                     .fill(btn_col)
-                    .corners(CornerRadii::top(self.style.btn_corner_radius())),
+                    .corners(self.style.btn_corners().with_bottom(0.0)),
             )
             .draw(text_shape.draw_rects(text_pos, text_col));
         }
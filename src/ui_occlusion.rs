@@ -0,0 +1,71 @@
+//! hit testing of overlapping items, only the topmost region under the cursor is hovered
+
+use glam::Vec2;
+
+use crate::{
+    rect::Rect,
+    ui::{self, Id},
+};
+
+/// position of a hit region in the draw order: the draw order of its panel and the number of
+/// regions registered before it in the frame
+pub type HitLayer = (usize, u32);
+
+/// the hit regions of panels and items of the current frame
+///
+/// items are registered one after another, so when an item is registered it is not known yet
+/// whether a later one covers it. the topmost region under the cursor is therefore taken from
+/// the regions of the previous frame, see [`Occlusion::is_occluded`]
+#[derive(Debug, Default)]
+pub struct Occlusion {
+    regions: Vec<(HitLayer, Id, Rect)>,
+    /// the topmost region of the previous frame under the current cursor
+    pub topmost: Option<(HitLayer, Id)>,
+    seq: u32,
+}
+
+impl Occlusion {
+    /// finds the topmost region under `mouse` and starts recording the regions of a new frame
+    pub fn begin_frame(&mut self, mouse: Vec2) {
+        self.topmost = self
+            .regions
+            .iter()
+            .filter(|(_, _, rect)| rect.contains(mouse))
+            .max_by_key(|(layer, _, _)| *layer)
+            .map(|&(layer, id, _)| (layer, id));
+        self.regions.clear();
+        self.seq = 0;
+    }
+
+    /// adds a region on top of all earlier regions of the same panel, [`Id::NULL`] only blocks
+    /// the regions below
+    pub fn push(&mut self, draw_order: usize, id: Id, rect: Rect) -> HitLayer {
+        self.seq += 1;
+        let layer = (draw_order, self.seq);
+        self.regions.push((layer, id, rect));
+        layer
+    }
+
+    /// the last region registered for `id` this frame
+    pub fn region(&self, id: Id) -> Option<(HitLayer, Rect)> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(_, region_id, _)| *region_id == id)
+            .map(|&(layer, _, rect)| (layer, rect))
+    }
+
+    /// whether a region of another item or an occluder above `layer` was under the cursor
+    pub fn is_occluded(&self, id: Id, layer: HitLayer) -> bool {
+        self.topmost.is_some_and(|(top_layer, top)| top != id && top_layer > layer)
+    }
+}
+
+impl ui::Context {
+    /// blocks the input of the items below `rect`, i.e. the items of lower panels and the items
+    /// placed before in the current panel, e.g. for a custom popup drawn over its panel
+    pub fn add_occluder(&mut self, rect: Rect) {
+        let draw_order = self.get_current_panel().draw_order;
+        self.occlusion.push(draw_order, Id::NULL, rect);
+    }
+}
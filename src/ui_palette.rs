@@ -0,0 +1,317 @@
+//! command palette, a searchable list of commands summoned with a shortcut
+
+use glam::Vec2;
+
+use crate::{
+    accessibility::AccessRole,
+    ctext,
+    mouse::{KeyCode, Modifiers, Shortcut},
+    rect::Rect,
+    ui::{self, Anchor, FloatingPanel, Id, PanelAnchor, PanelFlag, TextInputState},
+    ui_select::filter_options,
+};
+
+/// number of commands shown at once, longer lists scroll
+const PALETTE_VISIBLE_ROWS: usize = 10;
+
+/// entry of a [`CommandPalette`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command<C> {
+    pub name: String,
+    /// runs the command without opening the palette, also shown next to the name
+    pub shortcut: Option<Shortcut>,
+    /// returned by [`ui::Context::command_palette`] when the command is run
+    pub action: C,
+}
+
+/// commands and state of a [`ui::Context::command_palette`], owned by the app
+///
+/// `C` is usually an enum of the app's commands, use `Rc<dyn Fn(..)>` for callbacks
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandPalette<C> {
+    pub commands: Vec<Command<C>>,
+    /// opens and closes the palette, `Ctrl+P` by default
+    pub toggle: Shortcut,
+    open: bool,
+    just_opened: bool,
+    /// query the matches were computed for
+    query: String,
+    n_commands: usize,
+    matches: Vec<usize>,
+    /// index into `matches`
+    highlight: usize,
+    /// first visible row in `matches`
+    scroll: usize,
+}
+
+impl<C> Default for CommandPalette<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> CommandPalette<C> {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            toggle: Shortcut::new(Modifiers::CONTROL, KeyCode::KeyP),
+            open: false,
+            just_opened: false,
+            query: String::new(),
+            n_commands: 0,
+            matches: Vec::new(),
+            highlight: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn with_shortcut(mut self, toggle: Shortcut) -> Self {
+        self.toggle = toggle;
+        self
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, action: C) -> &mut Self {
+        self.commands.push(Command {
+            name: name.into(),
+            shortcut: None,
+            action,
+        });
+        self
+    }
+
+    pub fn add_with_shortcut(
+        &mut self,
+        name: impl Into<String>,
+        shortcut: Shortcut,
+        action: C,
+    ) -> &mut Self {
+        self.commands.push(Command {
+            name: name.into(),
+            shortcut: Some(shortcut),
+            action,
+        });
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        if !self.open {
+            self.open = true;
+            self.just_opened = true;
+            self.query.clear();
+            self.n_commands = self.commands.len();
+            self.matches = (0..self.commands.len()).collect();
+            self.highlight = 0;
+            self.scroll = 0;
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// refilters the commands if `query` or the commands changed
+    fn update_matches(&mut self, query: String) {
+        if query == self.query && self.n_commands == self.commands.len() {
+            return;
+        }
+        let names: Vec<&str> = self.commands.iter().map(|c| c.name.as_str()).collect();
+        self.matches = filter_options(&query, &names);
+        self.query = query;
+        self.n_commands = self.commands.len();
+        self.highlight = 0;
+        self.scroll = 0;
+    }
+
+    fn scroll_to_highlight(&mut self) {
+        if self.highlight < self.scroll {
+            self.scroll = self.highlight;
+        } else if self.highlight >= self.scroll + PALETTE_VISIBLE_ROWS {
+            self.scroll = self.highlight + 1 - PALETTE_VISIBLE_ROWS;
+        }
+    }
+}
+
+impl ui::Context {
+    /// shows the palette as an overlay at the top of the screen while it is open and handles
+    /// its toggle shortcut and the shortcuts of the commands. call it every frame
+    ///
+    /// type to fuzzy filter the commands, the arrow keys and enter or a click run one and
+    /// escape or clicking somewhere else closes the palette. returns the action of the command
+    /// run this frame
+    pub fn command_palette<C: Clone>(&mut self, palette: &mut CommandPalette<C>) -> Option<C> {
+        if self.shortcut_pressed(palette.toggle) {
+            if palette.open {
+                palette.close();
+            } else {
+                palette.open();
+            }
+        } else if !palette.open && !self.text_input_active() {
+            return palette
+                .commands
+                .iter()
+                .find(|c| c.shortcut.is_some_and(|s| self.shortcut_pressed(s)))
+                .map(|c| c.action.clone());
+        }
+
+        let input_id = Id::from_str("##command_palette_input");
+        if !palette.open {
+            self.widget_data.remove::<TextInputState>(&input_id);
+            return None;
+        }
+
+        let mut picked = None;
+        let flags = PanelFlag::NO_TITLEBAR | PanelFlag::NO_DOCKING | PanelFlag::NO_DOCK_TARGET;
+        let panel = FloatingPanel::new("##command_palette")
+            .with_flags(flags)
+            .auto_size()
+            .anchor(PanelAnchor::screen(Anchor::Top, Vec2::new(0.0, 40.0)));
+        self.floating_panel(panel, |ui| {
+            picked = ui.command_palette_content(palette, input_id);
+        });
+
+        if picked.is_some() || !palette.open {
+            palette.close();
+            self.widget_data.remove::<TextInputState>(&input_id);
+            if self.active_id == input_id {
+                self.active_id = Id::NULL;
+            }
+        }
+        picked.map(|i| palette.commands[i].action.clone())
+    }
+
+    /// the input and the rows of an open palette, returns the index of the command to run
+    fn command_palette_content<C>(
+        &mut self,
+        palette: &mut CommandPalette<C>,
+        input_id: Id,
+    ) -> Option<usize> {
+        use ctext::Edit;
+
+        let height = self.style.line_height();
+        let text_size = self.style.text_size();
+        let pad = (height - text_size) * 0.5;
+        let width = height * 18.0;
+
+        if palette.just_opened {
+            palette.just_opened = false;
+            let item = ui::TextItem::new(String::new(), text_size, 1.0, self.text_font);
+            let input = TextInputState::new(input_id, self.font_table.clone(), item, false);
+            self.widget_data.insert(input_id, input);
+            self.active_id = input_id;
+            self.bring_panel_to_front(self.current_panel_id);
+        }
+
+        let rect = self.place_item(Vec2::new(width, height));
+        let sig = self.reg_item_active_on_press(input_id, rect);
+        self.reg_access_node(input_id, AccessRole::TextInput, "command", rect);
+        self.draw(
+            rect.draw_rect()
+                .corners(self.style.btn_corners())
+                .fill(self.style.panel_dark_bg()),
+        );
+
+        self.current_drawlist().push_merged_clip_rect(rect);
+        let Some(input) = self.widget_data.get_mut::<TextInputState>(&input_id) else {
+            self.current_drawlist().pop_clip_rect();
+            palette.close();
+            return None;
+        };
+        input.edit.shape_as_needed(&mut self.font_table.sys(), true);
+        let layout = input.layout_text(self.glyph_cache.get_mut(), &self.wgpu);
+        let edit_pos = rect.min + Vec2::new(pad, (height - layout.size().y) * 0.5);
+        input.handle_mouse(self.mouse.pos - edit_pos, sig);
+        let query = input.copy_all();
+        if query.is_empty() {
+            let hint = self.layout_text("type a command...", text_size);
+            self.draw(hint.draw_rects(edit_pos, self.style.text_col().with_alpha(0.5)));
+        }
+        self.draw_text_input(input_id, edit_pos, rect);
+        self.current_drawlist().pop_clip_rect();
+
+        palette.update_matches(query);
+        let n_matches = palette.matches.len();
+
+        let mut picked = None;
+        if self.key_just_pressed(KeyCode::Escape) {
+            palette.close();
+        }
+        if n_matches > 0 {
+            if self.key_pressed_with_repeat(KeyCode::ArrowDown) {
+                palette.highlight = (palette.highlight + 1) % n_matches;
+                palette.scroll_to_highlight();
+            }
+            if self.key_pressed_with_repeat(KeyCode::ArrowUp) {
+                palette.highlight = (palette.highlight + n_matches - 1) % n_matches;
+                palette.scroll_to_highlight();
+            }
+            if self.key_just_pressed(KeyCode::Enter) {
+                picked = palette.matches.get(palette.highlight).copied();
+            }
+        }
+
+        let rows = n_matches.clamp(1, PALETTE_VISIBLE_ROWS);
+        let list_rect = self.place_item(Vec2::new(width, rows as f32 * height));
+        if list_rect.contains(self.mouse.pos) && self.mouse.scroll.y != 0.0 {
+            let max_scroll = n_matches.saturating_sub(PALETTE_VISIBLE_ROWS);
+            palette.scroll = if self.mouse.scroll.y > 0.0 {
+                palette.scroll.saturating_sub(1)
+            } else {
+                (palette.scroll + 1).min(max_scroll)
+            };
+        }
+
+        let mut row_active = false;
+        let first = palette.scroll;
+        for (k, &cmd) in palette.matches.iter().enumerate().skip(first).take(rows) {
+            let row = Rect::from_min_size(
+                list_rect.min + Vec2::new(0.0, (k - first) as f32 * height),
+                Vec2::new(width, height),
+            );
+            let command = &palette.commands[cmd];
+            let row_id = Id::from_hash(&(input_id, cmd));
+            let row_sig = self.reg_item_active_on_press(row_id, row);
+            self.reg_access_node(row_id, AccessRole::ListItem, &command.name, row);
+            if row_sig.hovering() && self.mouse.delta != Vec2::ZERO {
+                palette.highlight = k;
+            }
+            if row_sig.clicked() {
+                picked = Some(cmd);
+            }
+            row_active |= row_sig.pressed();
+
+            if k == palette.highlight {
+                self.draw(
+                    row.draw_rect()
+                        .corners(self.style.btn_corners())
+                        .fill(self.style.btn_hover()),
+                );
+            }
+            let name = self.layout_text(&command.name, text_size);
+            let pos = row.min + Vec2::new(pad, (height - name.size().y) * 0.5);
+            self.draw(name.draw_rects(pos, self.style.text_col()));
+            if let Some(shortcut) = command.shortcut {
+                let shape = self.layout_text(&shortcut.to_string(), text_size);
+                let pos = Vec2::new(
+                    row.max.x - pad - shape.size().x,
+                    row.min.y + (height - shape.size().y) * 0.5,
+                );
+                self.draw(shape.draw_rects(pos, self.style.text_col().with_alpha(0.5)));
+            }
+        }
+        if n_matches == 0 {
+            let shape = self.layout_text("no matching commands", text_size);
+            let pos = list_rect.min + Vec2::new(pad, (height - shape.size().y) * 0.5);
+            self.draw(shape.draw_rects(pos, self.style.text_col().with_alpha(0.5)));
+        }
+
+        // focus moved somewhere else, rows take the focus while they are pressed
+        if self.active_id != input_id && !row_active {
+            palette.close();
+        }
+        picked
+    }
+}
@@ -8,9 +8,9 @@ use std::{
 use wgpu::util::DeviceExt;
 
 use crate::{
-    core::{Axis, Dir},
+    core::{Axis, Dir, Duration, Instant},
     rect::Rect,
-    ui::{DrawList, Id, IdMap, RootId},
+    ui::{DrawList, Id, IdMap, PanelAnchor, PanelStyle, RootId, Signal},
 };
 
 macros::flags!(PanelFlag:
@@ -33,6 +33,12 @@ macros::flags!(PanelFlag:
     USE_PARENT_DRAWLIST,
     USE_PARENT_CLIP,
     IS_CHILD,
+
+    // ctrl + wheel scales the content, see `Context::set_panel_zoom`
+    ZOOMABLE,
+
+    // drawn above and hit tested before all panels without the flag
+    ALWAYS_ON_TOP,
 );
 
 #[derive(Clone, Debug)]
@@ -106,12 +112,25 @@ pub struct Panel {
     pub explicit_size: Vec2,
 
     pub outline_offset: f32,
+    /// the content is clipped to the rounded corners, see [`ClipShape`]
+    pub clip_masked: bool,
+    /// panel style overrides active when the panel was begun
+    pub style: PanelStyle,
 
     pub min_size: Vec2,
     pub max_size: Vec2,
+    /// sized to the content every frame, see [`FloatingPanel::auto_size`]
+    pub auto_size: bool,
 
     pub draw_order: usize,
 
+    /// scale of the content, the titlebar, padding and scrollbars are not scaled
+    pub zoom: f32,
+    /// zoom including the zoom of the parent panels
+    pub content_scale: f32,
+    /// number of style vars pushed for the zoom, popped in `end`
+    pub zoom_vars: u32,
+
     pub last_frame_used: u64,
     pub frame_created: u64,
     pub close_pressed: bool,
@@ -122,8 +141,80 @@ pub struct Panel {
     pub drawlist: DrawList,
     pub drawlist_over: DrawList,
     pub id_stack: RefCell<Vec<Id>>,
+    /// hasher state after hashing the id stack, reset on push / pop
+    pub id_stack_hasher: RefCell<Option<ahash::AHasher>>,
     pub _cursor: RefCell<Cursor>,
     pub scroll_offset: f32,
+
+    /// what the panel recorded in the last frame, see [`Panel::end_stats`]
+    pub stats: PanelStats,
+    /// the start of the current frame's [`Self::stats`], the time and the lengths of the
+    /// vertex, command and glyph counts of the drawlist
+    pub stats_begin: (Instant, usize, usize, usize),
+}
+
+/// what a panel recorded in a frame, including its child panels
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PanelStats {
+    pub vertices: usize,
+    pub draw_cmds: usize,
+    pub glyphs: usize,
+    /// time between the begin and the end of the panel
+    pub build_time: Duration,
+}
+
+impl fmt::Display for PanelStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} vtx, {} cmds, {} glyphs, {:.2}ms",
+            self.vertices,
+            self.draw_cmds,
+            self.glyphs,
+            self.build_time.as_secs_f32() * 1000.0
+        )
+    }
+}
+
+/// limits of the [`PanelStats`] of a panel, exceeding one shows a warning badge on the panel,
+/// see `Context::set_panel_budget`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PanelBudget {
+    pub vertices: usize,
+    pub draw_cmds: usize,
+    pub glyphs: usize,
+    pub build_time: Duration,
+}
+
+impl Default for PanelBudget {
+    fn default() -> Self {
+        Self {
+            vertices: 60_000,
+            draw_cmds: 64,
+            glyphs: 8_000,
+            build_time: Duration::from_millis(2),
+        }
+    }
+}
+
+impl PanelBudget {
+    /// short descriptions of the exceeded limits, e.g. `["61k vtx", "2.40ms"]`
+    pub fn exceeded(&self, stats: &PanelStats) -> Vec<String> {
+        let mut out = Vec::new();
+        if stats.vertices > self.vertices {
+            out.push(format!("{}k vtx", stats.vertices / 1000));
+        }
+        if stats.draw_cmds > self.draw_cmds {
+            out.push(format!("{} cmds", stats.draw_cmds));
+        }
+        if stats.glyphs > self.glyphs {
+            out.push(format!("{}k glyphs", stats.glyphs / 1000));
+        }
+        if stats.build_time > self.build_time {
+            out.push(format!("{:.2}ms", stats.build_time.as_secs_f32() * 1000.0));
+        }
+        out
+    }
 }
 
 // impl fmt::Debug for Panel {
@@ -176,7 +267,12 @@ impl Panel {
 
             explicit_size: Vec2::NAN,
             outline_offset: 0.0,
+            clip_masked: false,
+            style: PanelStyle::default(),
             draw_order: 0,
+            zoom: 1.0,
+            content_scale: 1.0,
+            zoom_vars: 0,
             // bg_color: RGBA::ZERO,
             titlebar_height: 0.0,
             title_handle_rect: Rect::ZERO,
@@ -185,6 +281,7 @@ impl Panel {
             size_pre_dock: Vec2::NAN,
             min_size: Vec2::ZERO,
             max_size: Vec2::ZERO,
+            auto_size: false,
             frame_created: 0,
             last_frame_used: 0,
             // draw_list: DrawList::new(),
@@ -196,11 +293,39 @@ impl Panel {
             drawlist: DrawList::new(),
             drawlist_over: DrawList::new(),
             id_stack: RefCell::new(Vec::new()),
+            id_stack_hasher: RefCell::new(None),
             _cursor: RefCell::new(Cursor::default()),
             scroll_offset: 0.0,
+
+            stats: PanelStats::default(),
+            stats_begin: (Instant::now(), 0, 0, 0),
         }
     }
 
+    /// remembers where the draws of this frame start, called when the panel begins
+    pub fn begin_stats(&mut self) {
+        self.drawlist.resolve_pending_shapes();
+        let data = self.drawlist.data.borrow();
+        let (vtx, cmds) = (data.vtx_buffer.len(), data.cmd_buffer.len());
+        self.stats_begin = (Instant::now(), vtx, cmds, data.n_glyphs);
+    }
+
+    /// counts what was drawn since [`Self::begin_stats`], called when the panel ends while the
+    /// stats are collected, see `Context::collects_panel_stats`
+    pub fn end_stats(&mut self) {
+        let (start, vtx_start, cmd_start, glyph_start) = self.stats_begin;
+        self.drawlist.resolve_pending_shapes();
+        self.drawlist_over.resolve_pending_shapes();
+        let data = self.drawlist.data.borrow();
+        let over = self.drawlist_over.data.borrow();
+        self.stats = PanelStats {
+            vertices: data.vtx_buffer.len().saturating_sub(vtx_start) + over.vtx_buffer.len(),
+            draw_cmds: data.cmd_buffer.len().saturating_sub(cmd_start) + over.cmd_buffer.len(),
+            glyphs: data.n_glyphs.saturating_sub(glyph_start) + over.n_glyphs,
+            build_time: start.elapsed(),
+        };
+    }
+
     pub fn panel_min_size(&self) -> Vec2 {
         let pad = 2.0 * self.padding;
         (self.title_handle_rect.size() + pad).max(self.min_size)
@@ -373,28 +498,39 @@ impl Panel {
 
     pub fn push_id(&self, id: Id) {
         self.id_stack.borrow_mut().push(id);
+        self.id_stack_hasher.replace(None);
     }
 
     pub fn pop_id(&self) -> Id {
+        self.id_stack_hasher.replace(None);
         self.id_stack.borrow_mut().pop().unwrap()
     }
 
     pub fn gen_local_id(&self, label: impl hash::Hash) -> Id {
-        use std::hash::{Hash, Hasher};
-        // let ids = &self.id_stack.borrow();
-        // let seed = ids.last().expect("at least self.id should be in the stack");
+        // Use a global stable hash for the label to ensure the same seeding
+        // behaviour across the codebase.
+        self.gen_local_id_from_hash(crate::core::global_hash64(&label))
+    }
 
-        assert!(!self.id_stack.borrow().is_empty());
+    /// same as [`Panel::gen_local_id`] for a label that was already hashed
+    pub fn gen_local_id_from_hash(&self, label_hash: u64) -> Id {
+        use std::hash::{Hash, Hasher};
 
-        let mut hasher = ahash::AHasher::new_with_keys(0, 0);
+        let mut hasher = self
+            .id_stack_hasher
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                let ids = self.id_stack.borrow();
+                assert!(!ids.is_empty());
 
-        for &id in self.id_stack.borrow().iter() {
-            id.hash(&mut hasher);
-        }
+                let mut hasher = ahash::AHasher::new_with_keys(0, 0);
+                for &id in ids.iter() {
+                    id.hash(&mut hasher);
+                }
+                hasher
+            })
+            .clone();
 
-        // Use a global stable hash for the label to ensure the same seeding
-        // behaviour across the codebase.
-        let label_hash = crate::core::global_hash64(&label);
         label_hash.hash(&mut hasher);
         Id(hasher.finish().max(1))
     }
@@ -492,6 +628,7 @@ impl Panel {
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cursor {
     pub pos: Vec2,
     pub max_pos: Vec2,
@@ -504,6 +641,235 @@ pub struct Cursor {
     pub indent: f32,
 }
 
+/// a panel attached to an edge of the window, see [`SidePanel`], [`TopBottomPanel`] and
+/// `Context::edge_panel`
+///
+/// the space taken by the panel is removed from the central area, i.e. the dockspace
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgePanel {
+    pub name: String,
+    /// edge of the window, one of `N`, `E`, `S` or `W`
+    pub side: Dir,
+    pub default_size: f32,
+    pub min_size: f32,
+    pub max_size: f32,
+    pub resizable: bool,
+    pub collapsible: bool,
+    pub default_open: bool,
+    /// draw a header with the name and the pin / collapse buttons
+    pub header: bool,
+    /// duration of the show / hide animation in seconds
+    pub anim_duration: f32,
+}
+
+impl EdgePanel {
+    pub fn new(name: impl Into<String>, side: Dir, default_size: f32) -> Self {
+        assert!(matches!(side, Dir::N | Dir::E | Dir::S | Dir::W));
+        Self {
+            name: name.into(),
+            side,
+            default_size,
+            min_size: 40.0,
+            max_size: f32::INFINITY,
+            resizable: true,
+            collapsible: true,
+            default_open: true,
+            header: true,
+            anim_duration: 0.15,
+        }
+    }
+
+    pub fn with_default_size(mut self, size: f32) -> Self {
+        self.default_size = size;
+        self
+    }
+
+    pub fn with_size_range(mut self, min: f32, max: f32) -> Self {
+        self.min_size = min;
+        self.max_size = max;
+        self
+    }
+
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn with_collapsible(mut self, collapsible: bool) -> Self {
+        self.collapsible = collapsible;
+        self
+    }
+
+    pub fn with_default_open(mut self, open: bool) -> Self {
+        self.default_open = open;
+        self
+    }
+
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn with_anim_duration(mut self, secs: f32) -> Self {
+        self.anim_duration = secs;
+        self
+    }
+
+    pub fn axis(&self) -> Axis {
+        match self.side {
+            Dir::N | Dir::S => Axis::Y,
+            _ => Axis::X,
+        }
+    }
+
+    /// clamps `size` to the size range, swaps the bounds if `min_size > max_size` and treats
+    /// negative or NaN bounds as 0
+    pub fn clamp_size(&self, size: f32) -> f32 {
+        let (a, b) = (self.min_size.max(0.0), self.max_size.max(0.0));
+        size.clamp(a.min(b), a.max(b))
+    }
+
+    /// takes a slice of `size` from the `side` of `area`, returns the slice and the rest
+    pub fn split_area(side: Dir, area: Rect, size: f32) -> (Rect, Rect) {
+        let (mut slice, mut rest) = (area, area);
+        match side {
+            Dir::N => {
+                slice.max.y = (area.min.y + size).min(area.max.y);
+                rest.min.y = slice.max.y;
+            }
+            Dir::S => {
+                slice.min.y = (area.max.y - size).max(area.min.y);
+                rest.max.y = slice.min.y;
+            }
+            Dir::W => {
+                slice.max.x = (area.min.x + size).min(area.max.x);
+                rest.min.x = slice.max.x;
+            }
+            Dir::E => {
+                slice.min.x = (area.max.x - size).max(area.min.x);
+                rest.max.x = slice.min.x;
+            }
+            _ => unreachable!(),
+        }
+        (slice, rest)
+    }
+}
+
+/// panels attached to the left or right edge of the window
+pub struct SidePanel;
+
+impl SidePanel {
+    pub fn left(name: impl Into<String>) -> EdgePanel {
+        EdgePanel::new(name, Dir::W, 250.0)
+    }
+
+    pub fn right(name: impl Into<String>) -> EdgePanel {
+        EdgePanel::new(name, Dir::E, 250.0)
+    }
+}
+
+/// panels attached to the top or bottom edge of the window, e.g. toolbars or status bars
+pub struct TopBottomPanel;
+
+impl TopBottomPanel {
+    pub fn top(name: impl Into<String>) -> EdgePanel {
+        EdgePanel::new(name, Dir::N, 150.0)
+    }
+
+    pub fn bottom(name: impl Into<String>) -> EdgePanel {
+        EdgePanel::new(name, Dir::S, 150.0)
+    }
+}
+
+/// a movable panel with size constraints, see `Context::floating_panel`
+///
+/// the panel is sized to its content on the first frame, afterwards it keeps the size the
+/// user resized it to. the size always stays between `min_size` and `max_size`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatingPanel {
+    pub name: String,
+    pub flags: PanelFlag,
+    /// follow the size of the content every frame instead of only on the first one
+    pub auto_size: bool,
+    pub min_size: Vec2,
+    pub max_size: Vec2,
+    pub anchor: Option<PanelAnchor>,
+}
+
+impl FloatingPanel {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            flags: PanelFlag::DRAW_V_SCROLLBAR,
+            auto_size: false,
+            min_size: Vec2::ZERO,
+            max_size: Vec2::INFINITY,
+            anchor: None,
+        }
+    }
+
+    pub fn with_flags(mut self, flags: PanelFlag) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// keep the panel at a position relative to the screen or another panel
+    pub fn anchor(mut self, anchor: PanelAnchor) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
+
+    /// resize the panel to fit its content every frame, the user can not resize it
+    pub fn auto_size(mut self) -> Self {
+        self.auto_size = true;
+        self
+    }
+
+    pub fn min_size(mut self, size: Vec2) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    pub fn max_size(mut self, size: Vec2) -> Self {
+        self.max_size = size;
+        self
+    }
+}
+
+/// where and how the app should render and control the scene below the ui, see
+/// `Context::central_viewport`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub id: Id,
+    /// area of the viewport below its titlebar
+    pub rect: Rect,
+    /// the pointer is over the viewport and not over an item or another panel
+    pub hovered: bool,
+    /// the viewport was the last panel clicked
+    pub focused: bool,
+    /// interaction with the viewport area, e.g. dragging to orbit a camera
+    pub signal: Signal,
+    /// pointer position relative to `rect.min`
+    pub mouse_pos: Vec2,
+    /// pointer movement since the last frame, zero unless hovered or pressed
+    pub mouse_delta: Vec2,
+    /// wheel movement since the last frame, zero unless hovered
+    pub scroll: Vec2,
+    /// keyboard input is meant for the viewport, it is focused and no text is edited
+    pub keyboard: bool,
+}
+
+/// persistent state of an [`EdgePanel`], stored in the widget data of the context
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgePanelState {
+    pub size: f32,
+    pub open: bool,
+    /// unpinned panels float over the central area and only open while hovered
+    pub pinned: bool,
+    /// 0 when collapsed, 1 when fully open
+    pub anim: f32,
+}
+
 macros::flags!(DockNodeFlag:
     NO_BRING_TO_FRONT,
     // a tree will not dissolve if only a single leaf is at the root
@@ -511,6 +877,7 @@ macros::flags!(DockNodeFlag:
 );
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DockNodeKind {
     Split {
         // first id is top / left, second id is bottom / right depending on axis
@@ -532,7 +899,9 @@ impl DockNodeKind {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DockNode {
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub label: Option<&'static str>,
     pub id: Id,
     pub parent_id: Id,
@@ -576,7 +945,29 @@ impl fmt::Display for DockNode {
     }
 }
 
+/// position and size of a panel, see [`LayoutState`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PanelLayout {
+    pub id: Id,
+    pub name: String,
+    pub pos: Vec2,
+    pub size: Vec2,
+    pub size_pre_dock: Vec2,
+    pub dock_id: Id,
+}
+
+/// persistent layout of all top level panels and the dock tree, see `Context::layout_state`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutState {
+    pub panels: Vec<PanelLayout>,
+    pub docktree: DockTree,
+    pub draworder: Vec<RootId>,
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DockTree {
     pub nodes: IdMap<DockNode>,
 }
@@ -0,0 +1,140 @@
+//! pixel accurate hit testing with an offscreen id buffer, see [`ui::Context::add_pick_shape`]
+
+use glam::Vec2;
+
+use crate::{
+    core::RGBA,
+    gpu::{PickVertex, PickingBuffer},
+    ui::{self, Id, tessellate_convex_fill, tessellate_line},
+};
+
+/// the pick shapes of the current frame and the state of the id buffer
+///
+/// disabled by default, items are then only hit tested by their rect. see
+/// [`ui::Context::set_picking`]
+#[derive(Default)]
+pub struct Picking {
+    pub enabled: bool,
+    pub vtx: Vec<PickVertex>,
+    pub idx: Vec<u32>,
+    /// the item of pick id `i` is `ids[i - 1]`, 0 is the background
+    pub ids: Vec<Id>,
+    /// ids of the frame whose readback is in flight
+    pending_ids: Vec<Id>,
+    buffer: Option<PickingBuffer>,
+    /// the item under the cursor, lags a few frames behind the cursor
+    pub picked: Id,
+}
+
+impl Picking {
+    pub fn clear(&mut self) {
+        self.vtx.clear();
+        self.idx.clear();
+        self.ids.clear();
+    }
+
+    /// adds triangles that pick `id`, later shapes are on top of earlier ones
+    pub fn push(&mut self, id: Id, vtx: &[Vec2], idx: &[u32]) {
+        if self.ids.last() != Some(&id) {
+            self.ids.push(id);
+        }
+        let pick_id = self.ids.len() as u32;
+
+        let base = self.vtx.len() as u32;
+        self.vtx.extend(vtx.iter().map(|&pos| PickVertex { pos, id: pick_id }));
+        self.idx.extend(idx.iter().map(|i| base + i));
+    }
+
+    /// the item of a value read from the id buffer
+    pub fn resolve(ids: &[Id], pick_id: u32) -> Id {
+        pick_id
+            .checked_sub(1)
+            .and_then(|i| ids.get(i as usize))
+            .copied()
+            .unwrap_or(Id::NULL)
+    }
+}
+
+impl ui::Context {
+    /// renders the pick shapes into an id buffer at the end of every frame and reads back the
+    /// item under the cursor, see [`Self::add_pick_shape`]
+    pub fn set_picking(&mut self, enabled: bool) {
+        self.picking.enabled = enabled;
+        if !enabled {
+            self.picking.buffer = None;
+            self.picking.picked = Id::NULL;
+        }
+    }
+
+    /// triangles in screen space that hit test `id` exactly, e.g. a rotated or custom shape.
+    /// ignored unless picking is enabled
+    ///
+    /// the shapes are not clipped, combine [`Self::is_picked`] with the rect hit test of the
+    /// item
+    pub fn add_pick_shape(&mut self, id: Id, vtx: &[Vec2], idx: &[u32]) {
+        if self.picking.enabled {
+            self.picking.push(id, vtx, idx);
+        }
+    }
+
+    /// a convex polygon that picks `id`
+    pub fn add_pick_polygon(&mut self, id: Id, points: &[Vec2]) {
+        if !self.picking.enabled {
+            return;
+        }
+        let (vtx, idx) = tessellate_convex_fill(points, RGBA::ZERO, false);
+        let vtx: Vec<Vec2> = vtx.iter().map(|v| v.pos).collect();
+        self.picking.push(id, &vtx, &idx);
+    }
+
+    /// a polyline, e.g. a flattened bezier curve, that picks `id` within `width` of the line
+    pub fn add_pick_line(&mut self, id: Id, points: &[Vec2], width: f32) {
+        if !self.picking.enabled {
+            return;
+        }
+        let (vtx, idx) = tessellate_line(points, RGBA::ZERO, width, false);
+        let vtx: Vec<Vec2> = vtx.iter().map(|v| v.pos).collect();
+        self.picking.push(id, &vtx, &idx);
+    }
+
+    /// the item under the cursor according to the id buffer, [`Id::NULL`] if there is none
+    pub fn picked_id(&self) -> Id {
+        self.picking.picked
+    }
+
+    pub fn is_picked(&self, id: Id) -> bool {
+        !id.is_null() && self.picking.picked == id
+    }
+
+    /// takes the id of a finished readback, called at the start of a frame
+    pub(crate) fn poll_picking(&mut self) {
+        let p = &mut self.picking;
+        if let Some(buffer) = &mut p.buffer
+            && let Some(pick_id) = buffer.poll(&self.wgpu)
+        {
+            p.picked = Picking::resolve(&p.pending_ids, pick_id);
+        }
+    }
+
+    /// renders the pick shapes of the frame, called at the end of a frame
+    pub(crate) fn render_picking(&mut self) {
+        let p = &mut self.picking;
+        if !p.enabled {
+            return;
+        }
+
+        let size = self.draw.screen_size.ceil().as_uvec2();
+        let buffer = p
+            .buffer
+            .get_or_insert_with(|| PickingBuffer::new(&self.wgpu, size));
+        if buffer.is_pending() {
+            return;
+        }
+
+        if buffer.render(&self.wgpu, size, &p.vtx, &p.idx, self.mouse.pos) {
+            std::mem::swap(&mut p.pending_ids, &mut p.ids);
+        } else {
+            p.picked = Id::NULL;
+        }
+    }
+}
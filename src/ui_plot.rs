@@ -0,0 +1,510 @@
+//! plot widgets for data that changes every frame
+
+use glam::Vec2;
+
+use crate::{
+    core::RGBA,
+    gpu,
+    ui::{self, LineMode, Signal, Stroke, TextureId},
+};
+
+/// fixed size ring buffer of samples, see [`ui::Context::stream_plot`]
+///
+/// once full, pushing a sample drops the oldest one
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamBuffer {
+    data: Vec<f32>,
+    capacity: usize,
+    /// index the next sample is written to once the buffer is full
+    head: usize,
+}
+
+impl StreamBuffer {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            data: Vec::with_capacity(capacity),
+            capacity,
+            head: 0,
+        }
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        if self.data.len() < self.capacity {
+            self.data.push(sample);
+        } else {
+            self.data[self.head] = sample;
+            self.head = (self.head + 1) % self.capacity;
+        }
+    }
+
+    pub fn extend(&mut self, samples: impl IntoIterator<Item = f32>) {
+        for s in samples {
+            self.push(s);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.head = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// sample `i`, counted from the oldest one
+    pub fn get(&self, i: usize) -> Option<f32> {
+        (i < self.data.len()).then(|| self.data[(self.head + i) % self.data.len()])
+    }
+
+    pub fn last(&self) -> Option<f32> {
+        self.len().checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// the samples from oldest to newest, split where the buffer wraps
+    pub fn as_slices(&self) -> (&[f32], &[f32]) {
+        let (new, old) = self.data.split_at(self.head);
+        (old, new)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        let (a, b) = self.as_slices();
+        a.iter().chain(b).copied()
+    }
+
+    /// min and max of the last `count` samples split into `columns` equal chunks
+    ///
+    /// used to draw many samples with one line segment per pixel column
+    pub fn min_max_columns(&self, count: usize, columns: usize, out: &mut Vec<(f32, f32)>) {
+        out.clear();
+        let count = count.min(self.len());
+        if count == 0 || columns == 0 {
+            return;
+        }
+
+        let start = self.len() - count;
+        for c in 0..columns {
+            let from = start + c * count / columns;
+            let to = (start + (c + 1) * count / columns).max(from + 1);
+            let mut min_max = (f32::INFINITY, f32::NEG_INFINITY);
+            for i in from..to {
+                let s = self.data[(self.head + i) % self.data.len()];
+                min_max = (min_max.0.min(s), min_max.1.max(s));
+            }
+            out.push(min_max);
+        }
+    }
+}
+
+/// options of [`ui::Context::stream_plot_ex`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamPlot {
+    /// NAN width fills the available width
+    pub size: Vec2,
+    /// value range shown, `None` fits the visible samples
+    pub range: Option<(f32, f32)>,
+    /// number of newest samples shown, `None` shows the whole buffer
+    pub window: Option<usize>,
+    pub col: RGBA,
+    /// [`LineMode::Fast`] for buffers with many thousands of samples
+    pub line_mode: LineMode,
+}
+
+impl Default for StreamPlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamPlot {
+    pub fn new() -> Self {
+        Self {
+            size: Vec2::new(f32::NAN, 80.0),
+            range: None,
+            window: None,
+            col: RGBA::CYAN,
+            line_mode: LineMode::Tessellated,
+        }
+    }
+
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn with_window(mut self, n_samples: usize) -> Self {
+        self.window = Some(n_samples);
+        self
+    }
+
+    pub fn with_col(mut self, col: RGBA) -> Self {
+        self.col = col;
+        self
+    }
+
+    pub fn with_line_mode(mut self, mode: LineMode) -> Self {
+        self.line_mode = mode;
+        self
+    }
+}
+
+impl ui::Context {
+    /// scrolling plot of the newest samples in `buffer`, e.g. an audio scope or a sensor value
+    pub fn stream_plot(&mut self, label: &str, buffer: &StreamBuffer) -> Signal {
+        self.stream_plot_ex(label, buffer, StreamPlot::new())
+    }
+
+    /// when there are more samples than pixels, every pixel column shows the min and max of its
+    /// samples, so peaks are never dropped
+    pub fn stream_plot_ex(&mut self, label: &str, buffer: &StreamBuffer, opts: StreamPlot) -> Signal {
+        let id = self.gen_id(label);
+        let mut size = opts.size;
+        if size.x.is_nan() {
+            size.x = self.available_content().x;
+        }
+        let rect = self.place_item(size);
+        let sig = self.reg_item_(id, rect);
+        if self.item_culled() {
+            return sig;
+        }
+
+        self.draw(
+            rect.draw_rect()
+                .fill(self.style.btn_default())
+                .corners(self.style.btn_corners()),
+        );
+
+        let window = opts.window.unwrap_or(buffer.capacity()).max(2);
+        let count = window.min(buffer.len());
+        let scale = self.window.scale_factor();
+        let columns = ((rect.width() * scale) as usize).max(1);
+        let decimate = window > columns;
+        // the newest sample is at the right edge, the plot scrolls in from the right until
+        // the window is full
+        let used_width = rect.width() * count as f32 / window as f32;
+
+        let mut min_max = Vec::new();
+        if decimate {
+            let used_columns = (columns * count / window).max(1);
+            buffer.min_max_columns(count, used_columns, &mut min_max);
+        } else {
+            min_max.extend(
+                (buffer.len() - count..buffer.len()).filter_map(|i| buffer.get(i).map(|s| (s, s))),
+            );
+        }
+
+        let (lo, hi) = opts.range.unwrap_or_else(|| {
+            min_max
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &(a, b)| {
+                    (lo.min(a), hi.max(b))
+                })
+        });
+        let (lo, hi) = if lo.is_finite() && hi > lo {
+            (lo, hi)
+        } else if lo.is_finite() {
+            (lo - 1.0, lo + 1.0)
+        } else {
+            (-1.0, 1.0)
+        };
+
+        let pad = 2.0;
+        let y = |v: f32| {
+            let t = ((v - lo) / (hi - lo)).clamp(0.0, 1.0);
+            rect.max.y - pad - t * (rect.height() - 2.0 * pad)
+        };
+
+        let mut points = Vec::with_capacity(min_max.len() * 2);
+        let x0 = rect.max.x - used_width;
+        if decimate {
+            // zig zag between min and max, draws a filled envelope with a single polyline
+            let dx = used_width / min_max.len() as f32;
+            for (c, &(min, max)) in min_max.iter().enumerate() {
+                let x = x0 + (c as f32 + 0.5) * dx;
+                points.push(Vec2::new(x, y(max)));
+                points.push(Vec2::new(x, y(min)));
+            }
+        } else {
+            let dx = rect.width() / (window - 1) as f32;
+            let x0 = rect.max.x - count.saturating_sub(1) as f32 * dx;
+            for (i, &(s, _)) in min_max.iter().enumerate() {
+                points.push(Vec2::new(x0 + i as f32 * dx, y(s)));
+            }
+        }
+
+        let painter = self.painter(rect);
+        painter.add_polyline_ex(&points, Stroke::new(opts.col, 1.0), false, opts.line_mode);
+
+        let text_col = self.style.text_col();
+        let text_size = self.style.text_size() * 0.85;
+        let text = match buffer.last() {
+            Some(v) => format!("{label}: {v:.3}"),
+            None => label.to_string(),
+        };
+        let shape = self.layout_text(&text, text_size);
+        painter.text(rect.min + Vec2::splat(4.0), &shape, text_col);
+
+        sig
+    }
+}
+
+/// maps a value from 0 to 1 to a color, applied per pixel in the fragment shader
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum Colormap {
+    #[default]
+    Viridis = 1,
+    Magma = 2,
+    Grayscale = 3,
+}
+
+impl Colormap {
+    pub const ALL: [Colormap; 3] = [Colormap::Viridis, Colormap::Magma, Colormap::Grayscale];
+
+    /// evenly spaced samples of the colormap, interpolated linearly in between
+    pub fn lut(self) -> &'static [RGBA; 9] {
+        const VIRIDIS: [RGBA; 9] = [
+            macros::rgba!("#440154"),
+            macros::rgba!("#472d7b"),
+            macros::rgba!("#3b528b"),
+            macros::rgba!("#2c728e"),
+            macros::rgba!("#21918c"),
+            macros::rgba!("#28ae80"),
+            macros::rgba!("#5ec962"),
+            macros::rgba!("#addc30"),
+            macros::rgba!("#fde725"),
+        ];
+        const MAGMA: [RGBA; 9] = [
+            macros::rgba!("#000004"),
+            macros::rgba!("#1c1044"),
+            macros::rgba!("#4f127b"),
+            macros::rgba!("#812581"),
+            macros::rgba!("#b5367a"),
+            macros::rgba!("#e55064"),
+            macros::rgba!("#fb8761"),
+            macros::rgba!("#fec287"),
+            macros::rgba!("#fcfdbf"),
+        ];
+        const GRAYSCALE: [RGBA; 9] = [
+            RGBA::rgb(0, 0, 0),
+            RGBA::rgb(32, 32, 32),
+            RGBA::rgb(64, 64, 64),
+            RGBA::rgb(96, 96, 96),
+            RGBA::rgb(128, 128, 128),
+            RGBA::rgb(159, 159, 159),
+            RGBA::rgb(191, 191, 191),
+            RGBA::rgb(223, 223, 223),
+            RGBA::rgb(255, 255, 255),
+        ];
+
+        match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Grayscale => &GRAYSCALE,
+        }
+    }
+
+    /// the same color the shader produces for `t`
+    pub fn sample(self, t: f32) -> RGBA {
+        let lut = self.lut();
+        let x = t.clamp(0.0, 1.0) * (lut.len() - 1) as f32;
+        let i = (x as usize).min(lut.len() - 2);
+        lut[i].lerp(lut[i + 1], x - i as f32)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Magma => "magma",
+            Colormap::Grayscale => "grayscale",
+        }
+    }
+}
+
+/// the luts of all colormaps and `fn colormap(cmap: u32, v: f32) -> vec3<f32>` for the ui shader
+pub(crate) fn colormap_wgsl() -> String {
+    let n = Colormap::Viridis.lut().len();
+    let entries: Vec<String> = Colormap::ALL
+        .iter()
+        .flat_map(|c| c.lut())
+        .map(|c| format!("vec3<f32>({:?}, {:?}, {:?})", c.r, c.g, c.b))
+        .collect();
+    let len = entries.len();
+
+    format!(
+        "
+        const CMAP_N: u32 = {n}u;
+        const CMAP_LUT: array<vec3<f32>, {len}> = array<vec3<f32>, {len}>({});
+
+        fn colormap(cmap: u32, v: f32) -> vec3<f32> {{
+            var lut = CMAP_LUT;
+            let x = clamp(v, 0.0, 1.0) * f32(CMAP_N - 1u);
+            let i = min(u32(x), CMAP_N - 2u);
+            let base = (cmap - 1u) * CMAP_N;
+            return mix(lut[base + i], lut[base + i + 1u], x - f32(i));
+        }}
+        ",
+        entries.join(", ")
+    )
+}
+
+/// options of [`ui::Context::plot_heatmap_ex`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeatmapPlot {
+    pub colormap: Colormap,
+    /// NAN width fills the available width, NAN height keeps the aspect ratio of the cells
+    pub size: Vec2,
+    /// values mapped to the ends of the colormap, `None` uses the min and max of the values
+    pub range: Option<(f32, f32)>,
+    /// show the value under the cursor in a tooltip
+    pub readout: bool,
+}
+
+impl HeatmapPlot {
+    pub fn new(colormap: Colormap) -> Self {
+        Self {
+            colormap,
+            size: Vec2::NAN,
+            range: None,
+            readout: true,
+        }
+    }
+
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn with_readout(mut self, readout: bool) -> Self {
+        self.readout = readout;
+        self
+    }
+}
+
+/// texture a heatmap is uploaded to, reused while the size of the field does not change
+#[derive(Debug, Clone)]
+struct HeatmapTexture {
+    tex: gpu::Texture,
+    tex_id: TextureId,
+}
+
+impl ui::Context {
+    /// draws a `width` x `height` field of values, stored row by row, with a colormap
+    pub fn plot_heatmap(
+        &mut self,
+        label: &str,
+        values: &[f32],
+        width: u32,
+        height: u32,
+        colormap: Colormap,
+    ) -> Signal {
+        self.plot_heatmap_ex(label, values, width, height, HeatmapPlot::new(colormap))
+    }
+
+    /// the values are uploaded to a texture every frame, NAN values are transparent. nothing
+    /// is drawn if there are not exactly `width * height` values
+    pub fn plot_heatmap_ex(
+        &mut self,
+        label: &str,
+        values: &[f32],
+        width: u32,
+        height: u32,
+        opts: HeatmapPlot,
+    ) -> Signal {
+        let n_cells = (width as usize).checked_mul(height as usize);
+        if n_cells != Some(values.len()) {
+            log::warn!("heatmap {label}: {} values for {width}x{height} cells", values.len());
+            return Signal::NONE;
+        }
+
+        let id = self.gen_id(label);
+        let mut size = opts.size;
+        if size.x.is_nan() {
+            size.x = self.available_content().x;
+        }
+        if size.y.is_nan() {
+            size.y = size.x * height as f32 / width.max(1) as f32;
+        }
+        let rect = self.place_item(size);
+        let sig = self.reg_item_(id, rect);
+        if self.item_culled() || values.is_empty() {
+            return sig;
+        }
+
+        let (lo, hi) = opts.range.unwrap_or_else(|| {
+            values
+                .iter()
+                .filter(|v| !v.is_nan())
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| {
+                    (lo.min(v), hi.max(v))
+                })
+        });
+        let span = if hi > lo { hi - lo } else { 1.0 };
+
+        let mut data = Vec::with_capacity(values.len() * 4);
+        for &v in values {
+            if v.is_nan() {
+                data.extend([0, 0, 0, 0]);
+            } else {
+                let t = (((v - lo) / span).clamp(0.0, 1.0) * 255.0).round() as u8;
+                data.extend([t, t, t, 255]);
+            }
+        }
+
+        let cached = self.widget_data.get::<HeatmapTexture>(&id).cloned();
+        let heatmap = match cached {
+            Some(h) if h.tex.width() == width && h.tex.height() == height => {
+                h.tex.write(&self.wgpu, &data);
+                h
+            }
+            prev => {
+                let tex = gpu::Texture::create(&self.wgpu, width, height, &data);
+                // a resized heatmap reuses its slot in the texture registry
+                let tex_id = match prev {
+                    Some(HeatmapTexture { tex_id, .. }) => {
+                        self.draw.texture_reg[tex_id.0 as usize - 1] = tex.clone();
+                        tex_id
+                    }
+                    None => self.register_texture(&tex),
+                };
+                let h = HeatmapTexture { tex, tex_id };
+                self.widget_data.insert(id, h.clone());
+                h
+            }
+        };
+
+        self.get_current_panel()
+            .drawlist
+            .add_rect_colormap(rect, heatmap.tex_id, opts.colormap);
+
+        if opts.readout && sig.hovering() {
+            let cell = ((self.mouse.pos - rect.min) / rect.size() * Vec2::new(width as f32, height as f32))
+                .floor()
+                .as_uvec2()
+                .min(glam::UVec2::new(width - 1, height - 1));
+            let v = values[cell.y as usize * width as usize + cell.x as usize];
+            self.tooltip(&format!("[{}, {}] {v:.3}", cell.x, cell.y));
+        }
+
+        sig
+    }
+}
@@ -0,0 +1,236 @@
+//! deterministic cpu rasterizer for golden image tests, see [`SoftRasterizer`]
+
+use glam::{UVec2, Vec2, Vec4};
+
+use crate::{
+    core::{HashMap, RGBA},
+    gpu::CapturedImage,
+    rect::Rect,
+    ui::{self, Colormap, DrawCallList, DrawList, TextureId, Vertex},
+};
+
+/// rasterizes draw calls on the cpu, e.g. to compare frames against reference images in ci
+/// without a gpu adapter
+///
+/// the output only depends on the draw calls, not on the machine. it follows the ui pipeline
+/// without multisampling: colors are interpolated per triangle, textures are sampled bilinear
+/// and blended with straight alpha. textures without pixels are sampled as white
+#[derive(Debug, Clone, Default)]
+pub struct SoftRasterizer {
+    /// pixels of the textures by their raw id
+    textures: HashMap<u32, CapturedImage>,
+}
+
+impl SoftRasterizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_texture(mut self, id: TextureId, pixels: CapturedImage) -> Self {
+        self.set_texture(id, pixels);
+        self
+    }
+
+    pub fn set_texture(&mut self, id: TextureId, pixels: CapturedImage) {
+        self.textures.insert(id.0 as u32, pixels);
+    }
+
+    /// draws the calls into an image of `size` cleared to `clear`
+    pub fn render(&self, calls: &DrawCallList, size: UVec2, clear: RGBA) -> CapturedImage {
+        let mut target = SoftTarget {
+            size,
+            pixels: vec![Vec4::new(clear.r, clear.g, clear.b, clear.a); (size.x * size.y) as usize],
+            clip: Rect::ZERO,
+            mask: None,
+        };
+
+        for call in calls.calls.iter().filter(|c| c.n_idx != 0) {
+            let vtx = &calls.vtx_alloc[call.vtx_ptr..call.vtx_ptr + call.n_vtx];
+            let idx = &calls.idx_alloc[call.idx_ptr..call.idx_ptr + call.n_idx];
+            target.clip = call.clip_rect;
+            target.mask = (call.clip_mask != 0).then(|| &calls.masks[call.clip_mask as usize - 1]);
+
+            // the vertices refer to the texture slots of the call
+            let texture = |slot: u32| match slot {
+                0 => None,
+                slot => self.textures.get(&call.textures[slot as usize - 1]),
+            };
+
+            if call.topology == wgpu::PrimitiveTopology::LineList {
+                for &[a, b] in idx.as_chunks::<2>().0 {
+                    let (a, b) = (vtx[a as usize], vtx[b as usize]);
+                    target.line(a, b, texture(a.tex));
+                }
+            } else {
+                for &[a, b, c] in idx.as_chunks::<3>().0 {
+                    let tri = [vtx[a as usize], vtx[b as usize], vtx[c as usize]];
+                    target.triangle(tri, texture(tri[0].tex));
+                }
+            }
+        }
+
+        CapturedImage {
+            width: size.x,
+            height: size.y,
+            rgba: target
+                .pixels
+                .iter()
+                .flat_map(|c| c.to_array().map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+                .collect(),
+        }
+    }
+
+    /// draws a single draw list, see [`Self::render`]
+    pub fn render_drawlist(&self, list: &DrawList, size: UVec2, clear: RGBA) -> CapturedImage {
+        let mut calls = DrawCallList::new(usize::MAX, usize::MAX);
+        calls.push_drawlist(list, size.as_vec2());
+        self.render(&calls, size, clear)
+    }
+}
+
+/// the pixels and the clip state of the current draw call
+struct SoftTarget<'a> {
+    size: UVec2,
+    pixels: Vec<Vec4>,
+    clip: Rect,
+    mask: Option<&'a Vec<Vec2>>,
+}
+
+impl SoftTarget<'_> {
+    fn triangle(&mut self, mut tri: [Vertex; 3], texture: Option<&CapturedImage>) {
+        let [a, b, c] = tri.map(|v| v.pos);
+        let area = edge(a, b, c);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        if area < 0.0 {
+            tri.swap(1, 2);
+        }
+        let [a, b, c] = tri.map(|v| v.pos);
+
+        let (min, max) = self.pixel_range(a.min(b).min(c), a.max(b).max(c));
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let w = [(b, c), (c, a), (a, b)].map(|(from, to)| (edge(from, to, p), to - from));
+                if !w.iter().all(|&(w, dir)| covers(w, dir)) {
+                    continue;
+                }
+                let sum = w[0].0 + w[1].0 + w[2].0;
+                let l = w.map(|(w, _)| w / sum);
+                let col = tri.iter().zip(l).fold(Vec4::ZERO, |acc, (v, l)| acc + rgba(v.col) * l);
+                let uv = tri.iter().zip(l).fold(Vec2::ZERO, |acc, (v, l)| acc + v.uv * l);
+                self.blend(x, y, shade(col, uv, texture, tri[0].colormap));
+            }
+        }
+    }
+
+    /// a one pixel wide line without the last pixel
+    fn line(&mut self, a: Vertex, b: Vertex, texture: Option<&CapturedImage>) {
+        let d = b.pos - a.pos;
+        let n = d.abs().max_element().round() as u32;
+        for i in 0..n {
+            let t = (i as f32 + 0.5) / n as f32;
+            let p = (a.pos + d * t).floor();
+            let (min, max) = self.pixel_range(p, p + Vec2::ONE);
+            if min.x >= max.x || min.y >= max.y {
+                continue;
+            }
+            let col = rgba(a.col).lerp(rgba(b.col), t);
+            let uv = a.uv.lerp(b.uv, t);
+            self.blend(min.x, min.y, shade(col, uv, texture, a.colormap));
+        }
+    }
+
+    /// the pixels whose centers can be inside of `min..max`, limited to the target and the clip
+    /// rect
+    fn pixel_range(&self, min: Vec2, max: Vec2) -> (UVec2, UVec2) {
+        let min = min.max(self.clip.min).max(Vec2::ZERO);
+        let max = max.min(self.clip.max).min(self.size.as_vec2());
+        let first_center = |p: Vec2| (p - 0.5).ceil().max(Vec2::ZERO).as_uvec2();
+        (first_center(min), first_center(max))
+    }
+
+    fn blend(&mut self, x: u32, y: u32, src: Vec4) {
+        let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+        if self.mask.is_some_and(|mask| !contains_even_odd(mask, p)) {
+            return;
+        }
+        let dst = &mut self.pixels[(y * self.size.x + x) as usize];
+        let rgb = src.truncate() * src.w + dst.truncate() * (1.0 - src.w);
+        *dst = rgb.extend(src.w + dst.w * (1.0 - src.w));
+    }
+}
+
+/// edge function of `p` against the edge from `a` to `b`, positive on the right in screen space
+///
+/// computed in a fixed vertex order, so the triangles of a shared edge get exactly opposite
+/// values and no pixel is drawn twice or skipped
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    if (a.y, a.x) > (b.y, b.x) {
+        return -edge(b, a, p);
+    }
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// pixels on an edge belong to one of the two triangles, decided by the direction of the edge
+fn covers(w: f32, dir: Vec2) -> bool {
+    w > 0.0 || (w == 0.0 && (dir.y > 0.0 || (dir.y == 0.0 && dir.x > 0.0)))
+}
+
+/// the stencil of clip masks inverts per triangle of the fan, i.e. the even odd rule
+fn contains_even_odd(outline: &[Vec2], p: Vec2) -> bool {
+    let mut inside = false;
+    for (i, &a) in outline.iter().enumerate() {
+        let b = outline[(i + 1) % outline.len()];
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn rgba(col: RGBA) -> Vec4 {
+    Vec4::new(col.r, col.g, col.b, col.a)
+}
+
+/// the fragment shader of the ui pipeline
+fn shade(col: Vec4, uv: Vec2, texture: Option<&CapturedImage>, colormap: u32) -> Vec4 {
+    let col = col * texture.map_or(Vec4::ONE, |t| sample_bilinear(t, uv));
+    match Colormap::ALL.get((colormap as usize).wrapping_sub(1)) {
+        Some(cmap) => rgba(cmap.sample(col.x)).truncate().extend(col.w),
+        None => col,
+    }
+}
+
+/// linear filtering with clamp to edge addressing, like the ui sampler
+fn sample_bilinear(texture: &CapturedImage, uv: Vec2) -> Vec4 {
+    let size = UVec2::new(texture.width, texture.height);
+    if size.x == 0 || size.y == 0 {
+        return Vec4::ONE;
+    }
+    let texel = |x: i64, y: i64| {
+        let x = x.clamp(0, size.x as i64 - 1) as usize;
+        let y = y.clamp(0, size.y as i64 - 1) as usize;
+        let i = (y * size.x as usize + x) * 4;
+        Vec4::from_array(std::array::from_fn(|c| texture.rgba[i + c] as f32 / 255.0))
+    };
+
+    let p = uv * size.as_vec2() - 0.5;
+    let (base, t) = (p.floor(), p - p.floor());
+    let (x, y) = (base.x as i64, base.y as i64);
+    let top = texel(x, y).lerp(texel(x + 1, y), t.x);
+    let bottom = texel(x, y + 1).lerp(texel(x + 1, y + 1), t.x);
+    top.lerp(bottom, t.y)
+}
+
+impl ui::Context {
+    /// draws the last frame with the [`SoftRasterizer`], including the glyph atlas. textures
+    /// registered by the app are sampled as white
+    pub fn render_software(&self, clear: RGBA) -> CapturedImage {
+        let atlas = self.glyph_cache.borrow().cpu_atlas.clone();
+        let raster = SoftRasterizer::new().with_texture(TextureId::GLYPH, atlas);
+        let size = self.draw.screen_size.max(Vec2::ZERO).as_uvec2();
+        raster.render(&self.draw.call_list, size, clear)
+    }
+}
@@ -0,0 +1,313 @@
+//! searchable select, a combo box that filters its options while typing
+
+use glam::Vec2;
+
+use crate::{
+    accessibility::AccessRole,
+    ctext,
+    mouse::{CursorIcon, KeyCode},
+    rect::Rect,
+    ui::{self, Id, Response, TextInputState},
+};
+
+/// number of option rows shown at once, longer lists scroll
+const SELECT_VISIBLE_ROWS: usize = 8;
+
+/// scores how well `pattern` matches `text`, `None` if the characters of `pattern` don't appear
+/// in `text` in order. case insensitive, consecutive characters and word starts score higher
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<i32> {
+    let lower = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let mut pattern = pattern.chars().filter(|c| !c.is_whitespace()).map(lower).peekable();
+    if pattern.peek().is_none() {
+        return Some(0);
+    }
+
+    let mut score = 0;
+    let mut leading_gap = 0;
+    let mut matched_any = false;
+    let mut prev_matched = false;
+    let mut prev: Option<char> = None;
+    for c in text.chars() {
+        let Some(&p) = pattern.peek() else {
+            break;
+        };
+
+        if lower(c) == p {
+            pattern.next();
+            score += 16;
+            if prev_matched {
+                score += 24;
+            }
+            let word_start = match prev {
+                None => true,
+                Some(prev) => !prev.is_alphanumeric() || (prev.is_lowercase() && c.is_uppercase()),
+            };
+            if word_start {
+                score += 16;
+            }
+            matched_any = true;
+            prev_matched = true;
+        } else {
+            if matched_any {
+                score -= 1;
+            } else {
+                leading_gap += 1;
+            }
+            prev_matched = false;
+        }
+        prev = Some(c);
+    }
+
+    if pattern.peek().is_some() {
+        return None;
+    }
+    Some(score - leading_gap.min(8))
+}
+
+/// indices of the options matching `query`, best match first and otherwise in list order
+pub(crate) fn filter_options<S: AsRef<str>>(query: &str, options: &[S]) -> Vec<usize> {
+    let mut matches: Vec<(i32, usize)> = options
+        .iter()
+        .enumerate()
+        .filter_map(|(i, opt)| fuzzy_match(query, opt.as_ref()).map(|score| (-score, i)))
+        .collect();
+    matches.sort_unstable();
+    matches.into_iter().map(|(_, i)| i).collect()
+}
+
+/// state of an open searchable select
+#[derive(Debug, Clone, PartialEq)]
+struct SelectState {
+    /// query the matches were computed for
+    query: String,
+    n_options: usize,
+    matches: Vec<usize>,
+    /// index into `matches`
+    highlight: usize,
+    /// first visible row in `matches`, fractional while scrolling with the wheel
+    scroll: f32,
+}
+
+impl SelectState {
+    fn scroll_to_highlight(&mut self) {
+        let first = self.scroll as usize;
+        if self.highlight < first {
+            self.scroll = self.highlight as f32;
+        } else if self.highlight >= first + SELECT_VISIBLE_ROWS {
+            self.scroll = (self.highlight + 1 - SELECT_VISIBLE_ROWS) as f32;
+        }
+    }
+}
+
+impl ui::Context {
+    /// combo box with a text filter. click it and type to fuzzy filter `options`, pick one with
+    /// the mouse or the arrow keys and enter. only the visible rows are laid out, so `options`
+    /// can be large
+    ///
+    /// the picked index is written to `selected`, the interaction lasts while the list is open
+    pub fn searchable_select<S: AsRef<str>>(
+        &mut self,
+        label: &str,
+        options: &[S],
+        selected: &mut Option<usize>,
+    ) -> Response {
+        use ctext::Edit;
+
+        let id = self.gen_id(label);
+        let height = self.style.line_height();
+        let width = self.available_content().x / 2.5;
+        let rect = self.place_item(Vec2::new(width, height));
+        let sig = self.reg_item_active_on_press(id, rect);
+
+        let mut open = self.widget_data.contains_key::<TextInputState>(&id);
+        if sig.clicked() && !open {
+            let text_size = self.style.text_size();
+            let item = ui::TextItem::new(String::new(), text_size, 1.0, self.text_font);
+            self.active_id = id;
+            let input = TextInputState::new(id, self.font_table.clone(), item, false);
+            self.widget_data.insert(id, input);
+            self.widget_data.insert(
+                id,
+                SelectState {
+                    query: String::new(),
+                    n_options: options.len(),
+                    matches: (0..options.len()).collect(),
+                    highlight: selected.unwrap_or(0).min(options.len().saturating_sub(1)),
+                    scroll: 0.0,
+                },
+            );
+            if let Some(state) = self.widget_data.get_mut::<SelectState>(&id) {
+                state.scroll_to_highlight();
+            }
+            open = true;
+        }
+
+        if let Some(node) = self.reg_access_node(id, AccessRole::ComboBox, label, rect) {
+            node.value = selected.and_then(|i| options.get(i)).map(|s| s.as_ref().to_string());
+            node.expanded = Some(open);
+        }
+
+        if sig.hovering() {
+            self.set_cursor_icon(if open { CursorIcon::Text } else { CursorIcon::Pointer });
+        }
+
+        // the open editor still handles its input while culled
+        let culled = self.item_culled() && !open;
+        let bg = if open || sig.pressed() {
+            self.style.panel_dark_bg()
+        } else if sig.hovering() {
+            self.style.btn_hover()
+        } else {
+            self.style.btn_default()
+        };
+        if !culled {
+            self.draw(rect.draw_rect().corners(self.style.btn_corners()).fill(bg));
+        }
+
+        let text_size = self.style.text_size();
+        let pad = (height - text_size) * 0.5;
+        let icon = if open {
+            ui::phosphor_font::CARET_UP
+        } else {
+            ui::phosphor_font::CARET_DOWN
+        };
+        let icon = self.layout_icon(icon, text_size);
+        let icon_pos = Vec2::new(
+            rect.max.x - pad - icon.size().x,
+            rect.center().y - icon.size().y * 0.5,
+        );
+        if !culled {
+            self.draw(icon.draw_rects(icon_pos, self.style.text_col()));
+        }
+
+        let text_rect = rect.with_max_x(icon_pos.x - pad * 0.5);
+        self.current_drawlist().push_merged_clip_rect(text_rect);
+        if open {
+            let input = self.widget_data.get_mut::<TextInputState>(&id).unwrap();
+            input.edit.shape_as_needed(&mut self.font_table.sys(), true);
+            let layout = input.layout_text(self.glyph_cache.get_mut(), &self.wgpu);
+            let edit_pos = rect.min + Vec2::new(pad, (height - layout.size().y) * 0.5);
+            input.handle_mouse(self.mouse.pos - edit_pos, sig);
+            self.draw_text_input(id, edit_pos, text_rect);
+        } else if !culled {
+            let (text, col) = match selected.and_then(|i| options.get(i)) {
+                Some(opt) => (opt.as_ref(), self.style.text_col()),
+                None => ("select...", self.style.text_col().with_alpha(0.5)),
+            };
+            let shape = self.layout_text(text, text_size);
+            let pos = rect.min + Vec2::new(pad, (height - shape.size().y) * 0.5);
+            self.draw(shape.draw_rects(pos, col));
+        }
+        self.current_drawlist().pop_clip_rect();
+
+        self.same_line();
+        self.text(label);
+
+        if !open {
+            return self.value_response(id, sig, false, false);
+        }
+
+        // refilter only when the query or the options changed
+        let query = self.widget_data.get::<TextInputState>(&id).unwrap().copy_all();
+        let mut state = self.widget_data.remove::<SelectState>(&id).unwrap();
+        if state.query != query || state.n_options != options.len() {
+            state.matches = filter_options(&query, options);
+            state.query = query;
+            state.n_options = options.len();
+            state.highlight = 0;
+            state.scroll = 0.0;
+        }
+
+        let n_matches = state.matches.len();
+        let mut picked = None;
+        let mut close = self.key_just_pressed(KeyCode::Escape);
+        if n_matches > 0 {
+            if self.key_pressed_with_repeat(KeyCode::ArrowDown) {
+                state.highlight = (state.highlight + 1).min(n_matches - 1);
+                state.scroll_to_highlight();
+            }
+            if self.key_pressed_with_repeat(KeyCode::ArrowUp) {
+                state.highlight = state.highlight.saturating_sub(1);
+                state.scroll_to_highlight();
+            }
+            if self.key_just_pressed(KeyCode::Enter) {
+                picked = state.matches.get(state.highlight).copied();
+            }
+        }
+
+        let rows = n_matches.clamp(1, SELECT_VISIBLE_ROWS);
+        let list_rect = self.place_item(Vec2::new(width, rows as f32 * height));
+        self.draw(
+            list_rect
+                .draw_rect()
+                .corners(self.style.btn_corners())
+                .fill(self.style.panel_dark_bg()),
+        );
+
+        if list_rect.contains(self.mouse.pos) {
+            let max_scroll = n_matches.saturating_sub(SELECT_VISIBLE_ROWS) as f32;
+            state.scroll = (state.scroll - self.mouse.scroll.y / height).clamp(0.0, max_scroll);
+        }
+
+        self.current_drawlist().push_merged_clip_rect(list_rect);
+        let mut row_active = false;
+        let first = state.scroll as usize;
+        for (k, &opt) in state.matches.iter().enumerate().skip(first).take(rows) {
+            let row = Rect::from_min_size(
+                list_rect.min + Vec2::new(0.0, (k - first) as f32 * height),
+                Vec2::new(width, height),
+            );
+            let row_id = Id::from_hash(&(id, opt));
+            let row_sig = self.reg_item_active_on_press(row_id, row);
+            if let Some(node) =
+                self.reg_access_node(row_id, AccessRole::ListItem, options[opt].as_ref(), row)
+            {
+                node.selected = Some(*selected == Some(opt));
+            }
+            if row_sig.hovering() && self.mouse.delta != Vec2::ZERO {
+                state.highlight = k;
+            }
+            if row_sig.clicked() {
+                picked = Some(opt);
+            }
+            row_active |= row_sig.pressed();
+
+            if k == state.highlight {
+                self.draw(
+                    row.draw_rect()
+                        .corners(self.style.btn_corners())
+                        .fill(self.style.btn_hover()),
+                );
+            }
+            let shape = self.layout_text(options[opt].as_ref(), text_size);
+            let pos = row.min + Vec2::new(pad, (height - shape.size().y) * 0.5);
+            self.draw(shape.draw_rects(pos, self.style.text_col()));
+        }
+        if n_matches == 0 {
+            let shape = self.layout_text("no matches", text_size);
+            let pos = list_rect.min + Vec2::new(pad, (height - shape.size().y) * 0.5);
+            self.draw(shape.draw_rects(pos, self.style.text_col().with_alpha(0.5)));
+        }
+        self.current_drawlist().pop_clip_rect();
+
+        let changed = picked.is_some() && picked != *selected;
+        if picked.is_some() {
+            *selected = picked;
+            close = true;
+        }
+        // focus moved somewhere else, rows take the focus while they are pressed
+        if self.active_id != id && !row_active {
+            close = true;
+        }
+
+        if close {
+            self.widget_data.remove::<TextInputState>(&id);
+            if self.active_id == id {
+                self.active_id = Id::NULL;
+            }
+        } else {
+            self.widget_data.insert(id, state);
+        }
+        self.value_response(id, sig, !close, changed)
+    }
+}
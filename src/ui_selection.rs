@@ -0,0 +1,205 @@
+//! multi selection in lists and canvases, see [`Selection`]
+
+use glam::Vec2;
+
+use crate::{
+    accessibility::AccessRole,
+    core::HashSet,
+    mouse::{CursorIcon, Modifiers},
+    rect::Rect,
+    ui::{self, Id, Outline, Signal},
+};
+
+/// set of selected items with the usual click semantics, owned by the app
+///
+/// a click selects a single item, ctrl + click toggles an item and shift + click selects the
+/// range from the last clicked item. see [`ui::Context::selectable`] for list rows and
+/// [`ui::Context::box_select`] for canvases
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selection {
+    ids: HashSet<Id>,
+    /// the last clicked item, start of shift + click ranges
+    anchor: Option<Id>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_selected(&self, id: Id) -> bool {
+        self.ids.contains(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// the selected ids in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = Id> + '_ {
+        self.ids.iter().copied()
+    }
+
+    pub fn select(&mut self, id: Id) {
+        self.ids.insert(id);
+    }
+
+    pub fn deselect(&mut self, id: Id) {
+        self.ids.remove(&id);
+    }
+
+    pub fn toggle(&mut self, id: Id) {
+        if !self.ids.remove(&id) {
+            self.ids.insert(id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.anchor = None;
+    }
+
+    /// updates the selection for a click on `items[index]`, `items` are all items of the list
+    /// in order. ctrl toggles the item, shift selects the range from the last clicked item and
+    /// ctrl + shift adds that range
+    pub fn click(&mut self, items: &[Id], index: usize, modifiers: Modifiers) {
+        let Some(&id) = items.get(index) else {
+            return;
+        };
+        let ctrl = modifiers.control_key();
+
+        if modifiers.shift_key() {
+            let anchor = self
+                .anchor
+                .and_then(|a| items.iter().position(|&i| i == a))
+                .unwrap_or(index);
+            if !ctrl {
+                self.ids.clear();
+            }
+            let (start, end) = (anchor.min(index), anchor.max(index));
+            self.ids.extend(items[start..=end].iter().copied());
+            // the anchor stays so the range can be adjusted with further shift clicks
+            self.anchor = Some(items[anchor]);
+        } else if ctrl {
+            self.toggle(id);
+            self.anchor = Some(id);
+        } else {
+            self.ids.clear();
+            self.ids.insert(id);
+            self.anchor = Some(id);
+        }
+    }
+}
+
+/// state of an ongoing box selection
+#[derive(Debug, Clone, PartialEq)]
+struct BoxSelect {
+    start: Vec2,
+    /// the selection the box adds to, empty unless shift or ctrl was held on press
+    base: Selection,
+}
+
+impl ui::Context {
+    /// full width list row showing `label`, highlighted while `selected`
+    ///
+    /// pass the clicks to [`Selection::click`] for the usual ctrl / shift semantics
+    pub fn selectable(&mut self, label: &str, selected: bool) -> Signal {
+        let id = self.gen_id(label);
+        let height = self.style.line_height();
+        let rect = self.place_item(Vec2::new(self.available_content().x, height));
+        let sig = self.reg_item_active_on_press(id, rect);
+        if let Some(node) = self.reg_access_node(id, AccessRole::ListItem, label, rect) {
+            node.selected = Some(selected);
+        }
+
+        if sig.hovering() {
+            self.set_cursor_icon(CursorIcon::Pointer);
+        }
+        if self.item_culled() {
+            return sig;
+        }
+
+        let bg = if selected || sig.pressed() {
+            Some(self.style.btn_hover())
+        } else if sig.hovering() {
+            Some(self.style.btn_default())
+        } else {
+            None
+        };
+        if let Some(bg) = bg {
+            self.draw(rect.draw_rect().corners(self.style.btn_corners()).fill(bg));
+        }
+        let text_size = self.style.text_size();
+        let shape = self.layout_text(label, text_size);
+        let pad = (height - text_size) * 0.5;
+        let pos = rect.min + Vec2::new(pad, (height - shape.size().y) * 0.5);
+        self.draw(shape.draw_rects(pos, self.style.text_col()));
+        sig
+    }
+
+    /// rubber band selection inside `area`, call it before registering the items so they can
+    /// still be clicked. dragging from an empty spot selects the `items` whose rect intersects
+    /// the box, holding shift or ctrl adds to the current selection. a click on an empty spot
+    /// clears the selection
+    ///
+    /// rects are in screen space, returns true if the selection changed
+    pub fn box_select(
+        &mut self,
+        id: Id,
+        area: Rect,
+        items: &[(Id, Rect)],
+        selection: &mut Selection,
+    ) -> bool {
+        let sig = self.reg_item_active_on_press(id, area);
+        let mut changed = false;
+
+        if sig.just_pressed() {
+            self.expect_drag = true;
+            let additive = self.modifiers.shift_key() || self.modifiers.control_key();
+            let base = if additive {
+                selection.clone()
+            } else {
+                Selection::new()
+            };
+            self.widget_data.insert(
+                id,
+                BoxSelect {
+                    start: self.mouse.pos,
+                    base,
+                },
+            );
+        }
+
+        if sig.pressed() || sig.dragging() {
+            let Some(drag) = self.widget_data.get::<BoxSelect>(&id) else {
+                return false;
+            };
+            let end = self.mouse.pos.clamp(area.min, area.max);
+            let rect = Rect::from_points(&[drag.start, end]);
+            let mut new = drag.base.clone();
+            for &(item, item_rect) in items {
+                if item_rect.intersects(rect) {
+                    new.select(item);
+                }
+            }
+            if new != *selection {
+                *selection = new;
+                changed = true;
+            }
+
+            let col = self.style.btn_press();
+            self.draw_over(
+                rect.draw_rect()
+                    .fill(col.with_alpha(0.25))
+                    .outline(Outline::hairline(col)),
+            );
+        } else if self.widget_data.contains_key::<BoxSelect>(&id) {
+            self.widget_data.remove::<BoxSelect>(&id);
+        }
+        changed
+    }
+}
@@ -0,0 +1,285 @@
+//! shadertoy style preview of a user fragment shader, see [`ui::Context::shader_canvas`]
+
+use std::sync::Arc;
+
+use glam::{Vec2, Vec4};
+
+use crate::{
+    accessibility::AccessRole,
+    core::RGBA,
+    gpu::{self, WGPU},
+    ui::{self, MONO_FONT, Signal, TextItem, TextWrap, TextureId},
+};
+
+/// number of floats passed to a [`ui::Context::shader_canvas`], read with `param(i)`
+pub const MAX_CANVAS_PARAMS: usize = 16;
+
+/// format of the texture a canvas renders into
+const CANVAS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// declarations in front of the source of a canvas
+const CANVAS_PRELUDE: &str = r#"
+struct Canvas {
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    mouse: vec4<f32>,
+    frame: u32,
+    params: array<vec4<f32>, 4>,
+}
+
+@group(0) @binding(0)
+var<uniform> canvas: Canvas;
+
+fn param(i: u32) -> f32 {
+    return canvas.params[i / 4u][i % 4u];
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+    // a triangle that covers the target
+    let p = vec2(f32((i << 1u) & 2u), f32(i & 2u));
+    return vec4(p * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    // the origin is at the bottom left like in shadertoy
+    return main_image(vec2(pos.x, canvas.resolution.y - pos.y));
+}
+"#;
+
+/// the inputs of a [`ui::Context::shader_canvas`], named after the shadertoy uniforms in the
+/// docs of the fields
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct CanvasUniform {
+    /// `iResolution`, the size of the canvas in pixels
+    pub resolution: Vec2,
+    /// `iTime`, seconds since the start of the context
+    pub time: f32,
+    /// `iTimeDelta`
+    pub time_delta: f32,
+    /// `iMouse`, the position while the canvas is dragged and the position of the last press.
+    /// `z` is negative when the button is up and `w` is only positive in the frame of a press
+    pub mouse: Vec4,
+    /// `iFrame`, the number of frames rendered by the canvas
+    pub frame: u32,
+    pub _pad: [u32; 3],
+    /// the values read with `param(i)`
+    pub params: [f32; MAX_CANVAS_PARAMS],
+}
+
+/// gpu resources of a canvas, stored in the widget data
+struct CanvasState {
+    /// the pipeline of the source or the errors of its compilation
+    pipeline: Option<(gpu::PipelineKey, Result<Arc<wgpu::RenderPipeline>, String>)>,
+    /// render target, registered for the ui
+    texture: Option<(gpu::Texture, TextureId)>,
+    layout: wgpu::BindGroupLayout,
+    uniform: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    mouse: Vec4,
+    frame: u32,
+}
+
+impl CanvasState {
+    fn layout_entries() -> [wgpu::BindGroupLayoutEntry; 1] {
+        [wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(size_of::<CanvasUniform>() as u64),
+            },
+            count: None,
+        }]
+    }
+
+    fn new(wgpu: &WGPU) -> Self {
+        let layout = wgpu.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("shader_canvas_bind_group_layout"),
+            entries: &Self::layout_entries(),
+        });
+        let uniform = wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("shader_canvas_uniform_buffer"),
+            size: size_of::<CanvasUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = wgpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shader_canvas_bind_group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            pipeline: None,
+            texture: None,
+            layout,
+            uniform,
+            bind_group,
+            mouse: Vec4::ZERO,
+            frame: 0,
+        }
+    }
+
+    /// compiles `source` unless it is the source of the current pipeline
+    fn compile(&mut self, wgpu: &WGPU, source: &str) {
+        let key = gpu::PipelineKey::of(&("shader_canvas", source));
+        if self.pipeline.as_ref().is_some_and(|(k, _)| *k == key) {
+            return;
+        }
+
+        let pipeline = canvas_shader(source).map(|src| {
+            wgpu.get_or_init_pipeline(key, || {
+                let entries = Self::layout_entries();
+                gpu::PipelineBuilder::new(&src, CANVAS_FORMAT)
+                    .label("shader_canvas")
+                    .bind_groups(&[&self.layout])
+                    .bind_group_entries(&[&entries])
+                    .build(&wgpu.device)
+            })
+        });
+        self.pipeline = Some((key, pipeline));
+    }
+}
+
+/// the wgsl of a canvas with the user `source`, or the errors of its compilation
+pub(crate) fn canvas_shader(source: &str) -> Result<String, String> {
+    let src = format!("{CANVAS_PRELUDE}\n{source}");
+    let entries = CanvasState::layout_entries();
+    match gpu::validate_shader(&src, ["vs_main", "fs_main"], &[], &[], 1, &[&entries]) {
+        Ok(()) => Ok(src),
+        Err(errors) => {
+            let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+impl ui::Context {
+    /// renders a fragment shader into a widget as wide as the available space, e.g. to
+    /// preview a shader while editing it in a [`Self::code`] block
+    ///
+    /// `source` is wgsl that defines `fn main_image(frag_coord: vec2<f32>) -> vec4<f32>`. it
+    /// can read the fields of [`CanvasUniform`] from `canvas`, e.g. `canvas.time`, and the
+    /// first [`MAX_CANVAS_PARAMS`] values of `params` with `param(i)`. the shader is only
+    /// compiled again when the source changes, errors are shown in place of the output
+    ///
+    /// the canvas keeps requesting redraws while it is visible
+    pub fn shader_canvas(&mut self, label: &str, source: &str, params: &[f32]) -> Signal {
+        let id = self.gen_id(label);
+        let width = self.available_content().x;
+        let rect = self.place_item(Vec2::new(width, (width * 9.0 / 16.0).round()));
+        let sig = self.reg_item_active_on_press(id, rect);
+        self.reg_access_node(id, AccessRole::Image, label, rect);
+        if self.item_culled() {
+            return sig;
+        }
+
+        let wgpu = self.wgpu.clone();
+        let mut state = self
+            .widget_data
+            .remove::<CanvasState>(&id)
+            .unwrap_or_else(|| CanvasState::new(&wgpu));
+        state.compile(&wgpu, source);
+
+        // shadertoy positions have the origin at the bottom left
+        let size = rect.size().max(Vec2::ONE).round();
+        let p = self.mouse.pos - rect.min;
+        let p = Vec2::new(p.x, size.y - p.y);
+        if sig.just_pressed() {
+            state.mouse = Vec4::new(p.x, p.y, p.x, p.y);
+        } else if sig.pressed() {
+            state.mouse = Vec4::new(p.x, p.y, state.mouse.z, -state.mouse.w.abs());
+        } else {
+            state.mouse.z = -state.mouse.z.abs();
+            state.mouse.w = -state.mouse.w.abs();
+        }
+
+        let texture = match &state.texture {
+            Some((tex, id)) if tex.width() == size.x as u32 && tex.height() == size.y as u32 => {
+                Some(*id)
+            }
+            _ => None,
+        };
+        let tex_id = texture.unwrap_or_else(|| {
+            let tex = gpu::Texture::create_render_texture(&wgpu, size.x as u32, size.y as u32);
+            // a resized canvas reuses its slot in the texture registry
+            let id = match &state.texture {
+                Some((_, id)) => {
+                    self.draw.texture_reg[id.0 as usize - 1] = tex.clone();
+                    *id
+                }
+                None => self.register_texture(&tex),
+            };
+            state.texture = Some((tex, id));
+            id
+        });
+
+        match &state.pipeline {
+            Some((_, Ok(pipeline))) => {
+                let mut uniform = CanvasUniform {
+                    resolution: size,
+                    time: self.time_since_start(),
+                    time_delta: self.delta_time(),
+                    mouse: state.mouse,
+                    frame: state.frame,
+                    _pad: [0; 3],
+                    params: [0.0; MAX_CANVAS_PARAMS],
+                };
+                let n = params.len().min(MAX_CANVAS_PARAMS);
+                uniform.params[..n].copy_from_slice(&params[..n]);
+                wgpu.queue.write_buffer(&state.uniform, 0, bytemuck::bytes_of(&uniform));
+
+                let (texture, _) = state.texture.as_ref().unwrap();
+                let mut encoder = wgpu.device.create_command_encoder(&Default::default());
+                {
+                    let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("shader_canvas_render_pass"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: texture.view(),
+                            resolve_target: None,
+                            depth_slice: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                    rpass.set_pipeline(pipeline);
+                    rpass.set_bind_group(0, &state.bind_group, &[]);
+                    rpass.draw(0..3, 0..1);
+                }
+                wgpu.queue.submit([encoder.finish()]);
+                state.frame += 1;
+
+                self.draw(rect.draw_rect().texture(tex_id));
+                self.window.request_redraw();
+            }
+            Some((_, Err(errors))) => {
+                let pad = self.style.panel_padding().max(4.0);
+                let itm = TextItem::new(errors.clone(), self.style.text_size(), 1.0, MONO_FONT)
+                    .with_wrap(TextWrap::Wrap, (rect.width() - 2.0 * pad).max(0.0));
+                let text = self.layout_text_item(itm);
+
+                self.draw(rect.draw_rect().fill(self.style.panel_dark_bg()));
+                self.current_drawlist().push_merged_clip_rect(rect);
+                self.draw(text.draw_rects(rect.min + pad, RGBA::rgb(255, 110, 110)));
+                self.current_drawlist().pop_clip_rect();
+            }
+            None => (),
+        }
+
+        self.widget_data.insert(id, state);
+        sig
+    }
+}
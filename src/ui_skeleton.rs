@@ -0,0 +1,113 @@
+//! placeholders for content that is still loading, see [`ui::Context::loading_scope`]
+
+use glam::Vec2;
+
+use crate::{
+    core::Axis,
+    rect::Rect,
+    ui::{self, ClipShape, CornerRadii, Gradient},
+};
+
+/// seconds the shimmer takes to sweep over a skeleton
+const SHIMMER_PERIOD: f32 = 1.4;
+
+impl ui::Context {
+    /// a grey bar of `size` with a shimmer sweeping over it, `f32::NAN` as the width uses the
+    /// available width
+    pub fn skeleton(&mut self, size: Vec2) -> Rect {
+        let width = if size.x.is_nan() {
+            self.available_content().x
+        } else {
+            size.x
+        };
+        let rect = self.place_item(Vec2::new(width, size.y));
+        if !self.item_culled() {
+            self.draw_skeleton(rect, self.style.btn_corners());
+        }
+        rect
+    }
+
+    /// a grey circle, e.g. in place of an avatar
+    pub fn skeleton_circle(&mut self, diameter: f32) -> Rect {
+        let rect = self.place_item(Vec2::splat(diameter));
+        if !self.item_culled() {
+            self.draw_skeleton(rect, CornerRadii::all(diameter * 0.5));
+        }
+        rect
+    }
+
+    /// `lines` bars as high as text, the last one is shorter like the end of a paragraph
+    pub fn skeleton_text(&mut self, lines: usize) {
+        let width = self.available_content().x;
+        let height = self.style.text_size();
+        for i in 0..lines {
+            let w = if i + 1 == lines && lines > 1 {
+                width * 0.6
+            } else {
+                width
+            };
+            self.skeleton(Vec2::new(w, height));
+        }
+    }
+
+    /// runs `f` and, while `is_loading`, draws skeletons in place of the items it placed. the
+    /// items are laid out like the loaded content, e.g. with empty strings, but are not drawn
+    /// and don't react to input
+    ///
+    /// ```ignore
+    /// ui.loading_scope(user.is_none(), |ui| {
+    ///     let user = user.as_ref().map_or("", |u| u.name.as_str());
+    ///     ui.text(user);
+    /// });
+    /// ```
+    pub fn loading_scope<R>(&mut self, is_loading: bool, f: impl FnOnce(&mut Self) -> R) -> R {
+        if !is_loading {
+            return f(self);
+        }
+
+        // the items are culled, whatever is drawn anyway is recorded into a channel and dropped
+        let drawlist = self.current_drawlist().clone();
+        let prev = drawlist.data.borrow_mut().begin_channel();
+        let outer = self.skeleton_rects.replace(Vec::new());
+        let res = f(self);
+        let rects = std::mem::replace(&mut self.skeleton_rects, outer).unwrap_or_default();
+        drawlist.data.borrow_mut().end_channel(prev);
+
+        // nested scopes are drawn by the outermost one
+        if let Some(outer) = &mut self.skeleton_rects {
+            outer.extend(rects);
+            return res;
+        }
+        let clip = self.current_drawlist().current_clip_rect();
+        let corners = self.style.btn_corners();
+        for rect in rects.into_iter().filter(|r| r.overlaps(clip)) {
+            // a little gap between items on consecutive lines
+            let rect = rect.shrink2(Vec2::new(0.0, (rect.height() * 0.1).min(3.0)));
+            self.draw_skeleton(rect, corners);
+        }
+        res
+    }
+
+    fn draw_skeleton(&self, rect: Rect, corners: CornerRadii) {
+        let base = self.style.btn_default();
+        self.draw(rect.draw_rect().corners(corners).fill(base));
+        if self.reduced_motion {
+            return;
+        }
+
+        // the band starts and ends outside of the rect so the sweep fades in and out
+        let band = (rect.width() * 0.4).max(rect.height() * 2.0);
+        let t = (self.time_since_start() % SHIMMER_PERIOD) / SHIMMER_PERIOD;
+        let x = rect.min.x - band + (rect.width() + band) * t;
+        let band_rect =
+            Rect::from_min_size(Vec2::new(x, rect.min.y), Vec2::new(band, rect.height()));
+        let hl = self.style.btn_hover();
+        let gradient = Gradient::from_colors(&[hl.with_alpha(0.0), hl, hl.with_alpha(0.0)]);
+
+        let painter = self.painter(rect);
+        painter.push_clip_mask(ClipShape::RoundedRect(rect, corners));
+        painter.add_rect_gradient_stops(band_rect, &gradient, Axis::X);
+        painter.pop_clip_mask();
+        self.window.request_redraw();
+    }
+}
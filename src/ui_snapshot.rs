@@ -0,0 +1,107 @@
+//! renders single panels or items of the last frame into images, see
+//! [`ui::Context::snapshot_widget`]
+
+use glam::Vec2;
+
+use crate::{
+    gpu::{self, RenderTarget},
+    rect::Rect,
+    ui::{self, DrawComposer, Id, LayerTransform, Painter},
+};
+
+impl ui::Context {
+    /// the rect of a panel or the visible rect of an item registered in the last frame
+    pub fn item_rect(&self, id: Id) -> Option<Rect> {
+        match self.panels.get(id) {
+            Some(p) => Some(p.full_rect),
+            None => self.occlusion.region(id).map(|(_, rect)| rect),
+        }
+    }
+
+    /// renders a panel with its child panels or an item of the last frame into an image, e.g.
+    /// to export a chart or to generate documentation images. call it after
+    /// [`Self::end_frame`]
+    ///
+    /// `scale` multiplies the size in pixels, text is not shaped again so it is resampled.
+    /// returns `None` if `id` was not drawn in the last frame
+    pub fn snapshot_widget(
+        &self,
+        id: Id,
+        scale: f32,
+        background: gpu::Background,
+    ) -> Option<gpu::CapturedImage> {
+        let (panel_id, rect) = self.snapshot_root(id)?;
+
+        let size = (rect.size() * scale).ceil().max(Vec2::ONE);
+        let transform = LayerTransform::new()
+            .with_offset(-rect.min * scale)
+            .with_scale(scale)
+            .with_clip(Rect::from_min_size(Vec2::ZERO, size));
+        let screen_size = self.draw.screen_size;
+        let registry = self.draw.output();
+
+        let mut composer = DrawComposer::new();
+        for p in self.get_panels_in_order() {
+            if !self.is_panel_in_tree(p, panel_id) {
+                continue;
+            }
+            let p = &self.panels[p];
+            let lists = [&p.drawlist, &p.drawlist_over];
+            for list in lists {
+                composer.push_drawlist_effects(0, list, &registry, screen_size, transform);
+            }
+        }
+
+        let wgpu = &self.wgpu;
+        let (width, height) = (size.x as u32, size.y as u32);
+        let texture = gpu::Texture::create_render_texture(wgpu, width, height);
+        let mut painter = Painter::new(&wgpu.device, &wgpu.queue, texture.raw().format(), 1);
+
+        let mut target = RenderTarget::from_texture(&texture, wgpu).with_background(background);
+        target.clear();
+        let output = composer.output();
+        target.encoder.with_encoder(|encoder| {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.target_view,
+                    resolve_target: None,
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                label: Some("snapshot_render_pass"),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            painter.render(&mut rpass, &output, size);
+        });
+
+        target.capture_region(Rect::from_min_size(Vec2::ZERO, size))
+    }
+
+    /// the panel that draws `id` and the rect of `id`
+    pub(crate) fn snapshot_root(&self, id: Id) -> Option<(Id, Rect)> {
+        match self.panels.get(id) {
+            Some(p) => Some((id, p.full_rect)),
+            None => {
+                let ((draw_order, _), rect) = self.occlusion.region(id)?;
+                let (&panel_id, _) = self.panels.iter().find(|(_, p)| p.draw_order == draw_order)?;
+                Some((panel_id, rect))
+            }
+        }
+    }
+
+    /// `id` is `root` or one of its child panels
+    pub(crate) fn is_panel_in_tree(&self, mut id: Id, root: Id) -> bool {
+        while !id.is_null() {
+            if id == root {
+                return true;
+            }
+            id = self.panels[id].parent_id;
+        }
+        false
+    }
+}
@@ -0,0 +1,309 @@
+//! vector export of the last frame, see [`ui::Context::export_svg`]
+//!
+//! the triangles of the draw lists are written as filled paths and text as the outlines of its
+//! glyphs, so the output does not depend on the installed fonts. pdf is not written directly,
+//! convert the svg with an external tool
+
+use std::fmt::Write;
+
+use glam::Vec2;
+
+use crate::{
+    core::{HashMap, RGBA},
+    ctext,
+    rect::Rect,
+    ui::{self, DrawList, GlyphCache, GlyphMeta, Id, TextureId, Vertex},
+};
+
+/// how the current path is painted, consecutive shapes with the same paint share a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SvgPaint {
+    Fill([u8; 4]),
+    Stroke([u8; 4]),
+}
+
+/// writes draw lists as an svg document
+///
+/// anti aliasing fringes of filled shapes are dropped, svg renderers anti alias the edges
+/// themselves. textured triangles other than glyphs, e.g. images, are skipped
+pub struct SvgWriter {
+    view: Rect,
+    defs: String,
+    body: String,
+    n_clips: usize,
+    path: Option<(SvgPaint, String)>,
+}
+
+impl SvgWriter {
+    /// `view` is the region of the screen that is written
+    pub fn new(view: Rect) -> Self {
+        Self {
+            view,
+            defs: String::new(),
+            body: String::new(),
+            n_clips: 0,
+            path: None,
+        }
+    }
+
+    /// appends the commands of `list`, text is only written if the glyphs of `glyphs` are given
+    pub fn push_drawlist(&mut self, list: &DrawList, mut glyphs: Option<&mut GlyphCache>) {
+        // glyph quads are found by the position of their texture in the glyph atlas
+        let atlas: HashMap<[u32; 2], (ctext::CacheKey, GlyphMeta)> = glyphs
+            .as_ref()
+            .map(|g| {
+                let glyphs = g.cached_glyphs.iter();
+                glyphs.map(|(&key, &meta)| (meta.uv_min.to_array().map(f32::to_bits), (key, meta)))
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+
+        list.resolve_pending_shapes();
+        let masks = list.clip_masks();
+        for cmd in list.commands().iter() {
+            let vtx = list.vtx_slice(cmd.vtx_offset..cmd.vtx_offset + cmd.vtx_count);
+            let idx = list.idx_slice(cmd.idx_offset..cmd.idx_offset + cmd.idx_count);
+
+            let mut n_groups = 0;
+            if cmd.clip_rect_used {
+                let r = cmd.clip_rect;
+                let rect = Rect::from_min_max(r.min, r.max.max(r.min));
+                self.begin_clip(&rect_points(rect));
+                n_groups += 1;
+            }
+            if cmd.clip_mask != 0 {
+                self.begin_clip(&masks[cmd.clip_mask as usize - 1]);
+                n_groups += 1;
+            }
+
+            if cmd.topology == wgpu::PrimitiveTopology::LineList {
+                for seg in idx.chunks_exact(2) {
+                    let (a, b) = (vtx[seg[0] as usize], vtx[seg[1] as usize]);
+                    let d = format!("M{}L{}", self.point(a.pos), self.point(b.pos));
+                    self.add_path(SvgPaint::Stroke(color_bytes(a.col)), &d);
+                }
+            } else {
+                let mut last_glyph = None;
+                for tri in idx.as_chunks::<3>().0 {
+                    let tri = tri.map(|i| vtx[i as usize]);
+                    match TextureId(tri[0].tex as u64) {
+                        TextureId::WHITE => self.add_triangle(tri),
+                        TextureId::GLYPH => {
+                            let Some(glyphs) = glyphs.as_deref_mut() else {
+                                continue;
+                            };
+                            // both triangles of a quad share its min corner
+                            let pos = tri.map(|v| v.pos).into_iter();
+                            let quad = Rect::from_min_max(
+                                pos.clone().reduce(Vec2::min).unwrap(),
+                                pos.reduce(Vec2::max).unwrap(),
+                            );
+                            let uv_min = tri.map(|v| v.uv).into_iter().reduce(Vec2::min).unwrap();
+                            if last_glyph == Some((quad.min, uv_min)) {
+                                continue;
+                            }
+                            last_glyph = Some((quad.min, uv_min));
+                            self.add_glyph(glyphs, &atlas, quad, uv_min, tri[0].col);
+                        }
+                        _ => (),
+                    }
+                }
+            }
+
+            self.flush_path();
+            for _ in 0..n_groups {
+                self.body.push_str("</g>\n");
+            }
+        }
+    }
+
+    /// the svg document
+    pub fn finish(mut self) -> String {
+        self.flush_path();
+        let size = self.view.size();
+        let (w, h) = (num(size.x), num(size.y));
+        let mut svg = String::new();
+        let xmlns = "http://www.w3.org/2000/svg";
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="{xmlns}" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+        );
+        if !self.defs.is_empty() {
+            let _ = write!(svg, "<defs>\n{}</defs>\n", self.defs);
+        }
+        svg.push_str(&self.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// a point in the coordinates of the document
+    fn point(&self, p: Vec2) -> String {
+        let p = p - self.view.min;
+        format!("{} {}", num(p.x), num(p.y))
+    }
+
+    fn begin_clip(&mut self, outline: &[Vec2]) {
+        self.flush_path();
+        self.n_clips += 1;
+        let points: Vec<String> = outline.iter().map(|&p| self.point(p)).collect();
+        let _ = writeln!(
+            self.defs,
+            r#"<clipPath id="clip{}"><path d="M{}Z"/></clipPath>"#,
+            self.n_clips,
+            points.join("L")
+        );
+        let _ = writeln!(self.body, r#"<g clip-path="url(#clip{})">"#, self.n_clips);
+    }
+
+    fn add_triangle(&mut self, tri: [Vertex; 3]) {
+        if tri.iter().any(|v| v.col.a <= 0.0) {
+            return;
+        }
+        let [a, b, c] = tri.map(|v| v.col);
+        let avg = |f: fn(RGBA) -> f32| (f(a) + f(b) + f(c)) / 3.0;
+        let col = RGBA::rgba_f(avg(|c| c.r), avg(|c| c.g), avg(|c| c.b), avg(|c| c.a));
+        let [a, b, c] = tri.map(|v| self.point(v.pos));
+        self.add_path(SvgPaint::Fill(color_bytes(col)), &format!("M{a}L{b}L{c}Z"));
+    }
+
+    fn add_glyph(
+        &mut self,
+        glyphs: &mut GlyphCache,
+        atlas: &HashMap<[u32; 2], (ctext::CacheKey, GlyphMeta)>,
+        quad: Rect,
+        uv_min: Vec2,
+        col: RGBA,
+    ) {
+        let (key, meta, offset) = match atlas.get(&uv_min.to_array().map(f32::to_bits)) {
+            Some(&(key, meta)) => (key, meta, Vec2::ZERO),
+            // quads clipped by their draw list start inside of the glyph
+            None => {
+                let Some(&(key, meta)) = atlas.values().find(|(_, meta)| {
+                    Rect::from_min_max(meta.uv_min, meta.uv_max).contains(uv_min)
+                }) else {
+                    return;
+                };
+                let offset = (uv_min - meta.uv_min) / (meta.uv_max - meta.uv_min) * meta.size;
+                (key, meta, offset)
+            }
+        };
+        let origin = quad.min - offset - meta.pos - self.view.min;
+
+        let GlyphCache {
+            swash_cache, fonts, ..
+        } = glyphs;
+        let Some(commands) = swash_cache.get_outline_commands(&mut fonts.sys(), key) else {
+            return;
+        };
+
+        // outlines are y up with the origin at the pen position
+        let mut d = String::new();
+        for cmd in commands {
+            let (op, points) = match *cmd {
+                ctext::Command::MoveTo(a) => ('M', vec![a]),
+                ctext::Command::LineTo(a) => ('L', vec![a]),
+                ctext::Command::QuadTo(a, b) => ('Q', vec![a, b]),
+                ctext::Command::CurveTo(a, b, c) => ('C', vec![a, b, c]),
+                ctext::Command::Close => ('Z', vec![]),
+            };
+            let points: Vec<String> = points
+                .into_iter()
+                .map(|p| {
+                    let p = origin + Vec2::new(p.x, -p.y);
+                    format!("{} {}", num(p.x), num(p.y))
+                })
+                .collect();
+            let _ = write!(d, "{op}{}", points.join(" "));
+        }
+
+        // the outline is not clipped like the quad, so partly visible glyphs get their own clip
+        let clipped = offset != Vec2::ZERO || (quad.size() - meta.size).abs().max_element() > 0.01;
+        if clipped {
+            self.begin_clip(&rect_points(quad));
+        }
+        self.add_path(SvgPaint::Fill(color_bytes(col)), &d);
+        if clipped {
+            self.flush_path();
+            self.body.push_str("</g>\n");
+        }
+    }
+
+    fn add_path(&mut self, paint: SvgPaint, d: &str) {
+        match &mut self.path {
+            Some((p, path)) if *p == paint => path.push_str(d),
+            _ => {
+                self.flush_path();
+                self.path = Some((paint, d.to_string()));
+            }
+        }
+    }
+
+    fn flush_path(&mut self) {
+        let Some((paint, d)) = self.path.take() else {
+            return;
+        };
+        let attrs = match paint {
+            SvgPaint::Fill(col) => paint_attrs("fill", col),
+            SvgPaint::Stroke(col) => {
+                paint_attrs("stroke", col) + r#" stroke-width="1" fill="none""#
+            }
+        };
+        let _ = writeln!(self.body, r#"<path d="{d}" {attrs}/>"#);
+    }
+}
+
+fn rect_points(rect: Rect) -> [Vec2; 4] {
+    let (min, max) = (rect.min, rect.max);
+    [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+}
+
+fn color_bytes(col: RGBA) -> [u8; 4] {
+    [col.r, col.g, col.b, col.a].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+fn paint_attrs(kind: &str, [r, g, b, a]: [u8; 4]) -> String {
+    let mut attrs = format!(r##"{kind}="#{r:02x}{g:02x}{b:02x}""##);
+    if a != 255 {
+        let _ = write!(attrs, r#" {kind}-opacity="{}""#, num(a as f32 / 255.0));
+    }
+    attrs
+}
+
+/// rounds to two decimals, which is below the precision of a pixel
+fn num(v: f32) -> f32 {
+    (v * 100.0).round() / 100.0
+}
+
+impl ui::Context {
+    /// writes a panel with its child panels or an item of the last frame as svg, e.g. to export
+    /// a plot for a publication. call it after [`Self::end_frame`]
+    ///
+    /// returns `None` if `id` was not drawn in the last frame, see [`SvgWriter`]
+    pub fn export_svg(&self, id: Id) -> Option<String> {
+        let (panel_id, rect) = self.snapshot_root(id)?;
+        let mut svg = SvgWriter::new(rect);
+        let mut glyphs = self.glyph_cache.borrow_mut();
+        for p in self.get_panels_in_order() {
+            if !self.is_panel_in_tree(p, panel_id) {
+                continue;
+            }
+            let p = &self.panels[p];
+            svg.push_drawlist(&p.drawlist, Some(&mut glyphs));
+            svg.push_drawlist(&p.drawlist_over, Some(&mut glyphs));
+        }
+        Some(svg.finish())
+    }
+
+    /// writes all panels of the last frame as svg
+    pub fn export_frame_svg(&self) -> String {
+        let mut svg = SvgWriter::new(Rect::from_min_size(Vec2::ZERO, self.draw.screen_size));
+        let mut glyphs = self.glyph_cache.borrow_mut();
+        for p in self.get_panels_in_order() {
+            let p = &self.panels[p];
+            svg.push_drawlist(&p.drawlist, Some(&mut glyphs));
+            svg.push_drawlist(&p.drawlist_over, Some(&mut glyphs));
+        }
+        svg.finish()
+    }
+}